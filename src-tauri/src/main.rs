@@ -5,23 +5,27 @@ mod monitoring;
 use std::sync::Arc;
 use tauri::{Manager, State, Emitter};
 use tokio::sync::RwLock;
-use monitoring::{MonitoringService, SystemInfo, SystemMetrics};
+use tracing::{debug, error, info, trace, warn};
+use tracing_subscriber::fmt::format::FmtSpan;
+use tracing_subscriber::EnvFilter;
+use monitoring::{MonitoringService, ProcessSelection, ProcessSortKey, SystemInfo, SystemMetrics};
 use monitoring::high_perf_monitor::HighPerfMetrics;
 use monitoring::kernel_monitor::KernelMetrics;
+use monitoring::wire;
+use monitoring::inventory::{HardwareInventory, SystemInventory};
 
 type ServiceState = Arc<RwLock<MonitoringService>>;
 
 #[tauri::command]
 async fn get_system_info(state: State<'_, ServiceState>) -> Result<SystemInfo, String> {
-    
     let service = state.read().await;
     match service.get_system_info().await {
         Ok(info) => {
-            println!("System info retrieved successfully");
+            debug!("System info retrieved");
             Ok(info)
         }
         Err(e) => {
-            println!("ERROR getting system info: {}", e);
+            error!("Failed to get system info: {}", e);
             Err(e.to_string())
         }
     }
@@ -29,31 +33,29 @@ async fn get_system_info(state: State<'_, ServiceState>) -> Result<SystemInfo, S
 
 #[tauri::command]
 async fn start_monitoring(state: State<'_, ServiceState>, app: tauri::AppHandle) -> Result<(), String> {
-    println!("=== start_monitoring called ===");
+    info!("Starting monitoring");
     let mut service = state.write().await;
     
     // Clone app handle for the callback
     let app_handle = app.clone();
-    println!("App handle cloned for metrics callback");
     
     // Set up the standard metrics callback to emit events to the frontend
     service.set_metrics_callback(move |metrics| {
-        println!("Emitting system-metrics event with {} processes", metrics.top_processes.len());
+        trace!(processes = metrics.top_processes.len(), "Emitting system-metrics event");
         let result = app_handle.emit("system-metrics", &metrics);
         if let Err(e) = result {
-            println!("Error emitting system-metrics event: {}", e);
+            warn!("Failed to emit system-metrics event: {}", e);
         }
     }).await;
     
     // Set up high-performance metrics callback
     let app_handle_high_perf = app.clone();
     service.set_high_perf_callback(move |metrics| {
-        // Use binary serialization for high-performance metrics
-        if let Ok(encoded) = bincode::serialize(&metrics) {
-            let result = app_handle_high_perf.emit("high-perf-metrics", &encoded);
-            if let Err(e) = result {
-                println!("Error emitting high-perf-metrics event: {}", e);
-            }
+        // Versioned binary frames, so a frontend from another build fails
+        // to decode them cleanly
+        let encoded = wire::encode_high_perf(&metrics);
+        if let Err(e) = app_handle_high_perf.emit("high-perf-metrics", &encoded) {
+            warn!("Failed to emit high-perf-metrics event: {}", e);
         }
     }).await;
     
@@ -64,82 +66,98 @@ async fn start_monitoring(state: State<'_, ServiceState>, app: tauri::AppHandle)
         if let Ok(encoded) = bincode::serialize(&metrics) {
             let result = app_handle_kernel.emit("kernel-metrics", &encoded);
             if let Err(e) = result {
-                println!("Error emitting kernel-metrics event: {}", e);
+                warn!("Failed to emit kernel-metrics event: {}", e);
             }
         }
     }).await;
     
-    println!("Starting monitoring service...");
     service.start_monitoring().await;
     
     // Start high-performance monitoring
     service.start_high_perf_monitoring();
-    println!("High-performance monitoring started");
+    info!("High-performance monitoring started");
     
     // Start kernel-level monitoring
     match service.start_kernel_monitoring() {
-        Ok(()) => println!("Kernel-level monitoring started"),
-        Err(e) => println!("Warning: Failed to start kernel monitoring: {}", e),
+        Ok(()) => info!("Kernel-level monitoring started"),
+        Err(e) => warn!("Failed to start kernel monitoring: {}", e),
     }
     
-    println!("Monitoring service started successfully");
+    // The metrics loop takes the write lock itself
+    drop(service);
+    MonitoringService::start_metrics_loop(state.inner().clone()).await;
+    
+    info!("Monitoring started");
     Ok(())
 }
 
 #[tauri::command]
 async fn start_high_perf_monitoring(state: State<'_, ServiceState>, app: tauri::AppHandle) -> Result<(), String> {
-    println!("=== start_high_perf_monitoring called ===");
+    info!("Starting high-performance monitoring");
     let mut service = state.write().await;
     
-    // Set up high-performance metrics callback with binary serialization
+    // Set up high-performance metrics callback with versioned binary frames
     let app_handle = app.clone();
     service.set_high_perf_callback(move |metrics| {
-        if let Ok(encoded) = bincode::serialize(&metrics) {
-            let result = app_handle.emit("high-perf-metrics", &encoded);
-            if let Err(e) = result {
-                println!("Error emitting high-perf-metrics event: {}", e);
-            }
+        let encoded = wire::encode_high_perf(&metrics);
+        if let Err(e) = app_handle.emit("high-perf-metrics", &encoded) {
+            warn!("Failed to emit high-perf-metrics event: {}", e);
         }
     }).await;
     
     service.start_high_perf_monitoring();
-    println!("High-performance monitoring started successfully");
+    info!("High-performance monitoring started");
     Ok(())
 }
 
 #[tauri::command]
-async fn stop_monitoring(_state: State<'_, ServiceState>) -> Result<(), String> {
-    // In this simple version, we don't stop the monitoring
+async fn stop_monitoring(state: State<'_, ServiceState>) -> Result<(), String> {
+    let mut service = state.write().await;
+    service.stop_monitoring();
+    info!("Monitoring stopped");
     Ok(())
 }
 
 #[tauri::command]
 async fn get_current_metrics(state: State<'_, ServiceState>) -> Result<SystemMetrics, String> {
-    
     let service = state.read().await;
     match service.collect_metrics().await {
         Ok(metrics) => {
             Ok(metrics)
         }
         Err(e) => {
-            println!("ERROR collecting current metrics: {}", e);
-            Err(e)
+            error!("Failed to collect current metrics: {}", e);
+            Err(e.to_string())
         }
     }
 }
 
+#[tauri::command]
+async fn set_process_selection(
+    state: State<'_, ServiceState>,
+    sort_key: ProcessSortKey,
+    count: usize,
+) -> Result<(), String> {
+    if count == 0 {
+        return Err("Process count must be at least 1".to_string());
+    }
+    
+    let service = state.read().await;
+    service.set_process_selection(ProcessSelection { sort_key, count });
+    Ok(())
+}
+
 #[tauri::command]
 async fn get_high_perf_metrics(state: State<'_, ServiceState>) -> Result<Option<HighPerfMetrics>, String> {
-    println!("=== get_high_perf_metrics called ===");
     let service = state.read().await;
     let metrics = service.get_high_perf_metrics();
-    println!("High-performance metrics retrieved: {}", metrics.is_some());
+    debug!(available = metrics.is_some(), "High-performance metrics requested");
     Ok(metrics)
 }
 
 #[tauri::command]
 async fn start_kernel_monitoring(state: State<'_, ServiceState>, app: tauri::AppHandle) -> Result<(), String> {
-    println!("=== start_kernel_monitoring called ===");
+    info!("Starting kernel-level monitoring");
     let mut service = state.write().await;
     
     // Set up kernel metrics callback with binary serialization
@@ -148,18 +166,18 @@ async fn start_kernel_monitoring(state: State<'_, ServiceState>, app: tauri::App
         if let Ok(encoded) = bincode::serialize(&metrics) {
             let result = app_handle.emit("kernel-metrics", &encoded);
             if let Err(e) = result {
-                println!("Error emitting kernel-metrics event: {}", e);
+                warn!("Failed to emit kernel-metrics event: {}", e);
             }
         }
     }).await;
     
     match service.start_kernel_monitoring() {
         Ok(()) => {
-            println!("Kernel-level monitoring started successfully");
+            info!("Kernel-level monitoring started");
             Ok(())
         }
         Err(e) => {
-            println!("Failed to start kernel monitoring: {}", e);
+            error!("Failed to start kernel monitoring: {}", e);
             Err(e.to_string())
         }
     }
@@ -167,55 +185,70 @@ async fn start_kernel_monitoring(state: State<'_, ServiceState>, app: tauri::App
 
 #[tauri::command]
 async fn stop_kernel_monitoring(state: State<'_, ServiceState>) -> Result<(), String> {
-    println!("=== stop_kernel_monitoring called ===");
     let mut service = state.write().await;
     service.stop_kernel_monitoring();
-    println!("Kernel-level monitoring stopped");
+    info!("Kernel-level monitoring stopped");
     Ok(())
 }
 
 #[tauri::command]
 async fn get_kernel_metrics(state: State<'_, ServiceState>) -> Result<Option<KernelMetrics>, String> {
-    println!("=== get_kernel_metrics called ===");
     let service = state.read().await;
     let metrics = service.get_kernel_metrics();
-    println!("Kernel metrics retrieved: {}", metrics.is_some());
+    debug!(available = metrics.is_some(), "Kernel metrics requested");
     Ok(metrics)
 }
 
+#[tauri::command]
+async fn get_inventory() -> Result<SystemInventory, String> {
+    // Enumerating the buses shells out to lspci/wmic
+    tokio::task::spawn_blocking(|| HardwareInventory::new().collect())
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Logs to stderr at the level named by `RUST_LOG` (`error` to `trace`),
+/// `info` by default. Closed spans are logged with their fields, so
+/// `RUST_LOG=debug` shows how long each collection pass and collector took.
+fn init_tracing() {
+    // Full `RUST_LOG` directives, e.g. `info,system_monitor::monitoring=debug`
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+    tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_span_events(FmtSpan::CLOSE)
+        .with_writer(std::io::stderr)
+        .init();
+}
+
 fn main() {
-    println!("=== Starting System Monitor Tauri Application ===");
+    init_tracing();
+    info!("Starting System Monitor");
     
     // Initialize the monitoring service with high-performance capabilities
-    println!("Initializing high-performance monitoring service...");
     let service = Arc::new(RwLock::new(MonitoringService::new_with_high_perf(3000))); // 3000ms update interval (3 seconds)
-    println!("High-performance monitoring service initialized successfully");
     
     tauri::Builder::default()
         .manage(service)
         .setup(|app| {
-            println!("=== Tauri App Setup ===");
-            println!("App is initializing...");
-            
             #[cfg(debug_assertions)]
             {
                 if let Some(window) = app.get_webview_window("main") {
-                    println!("Opening devtools for main window");
+                    debug!("Opening devtools for main window");
                     window.open_devtools();
                     
                     // Log window properties
                     if let Ok(pos) = window.outer_position() {
-                        println!("Window position: {:?}", pos);
+                        debug!("Window position: {:?}", pos);
                     }
                     if let Ok(size) = window.outer_size() {
-                        println!("Window size: {:?}", size);
+                        debug!("Window size: {:?}", size);
                     }
                 } else {
-                    println!("WARNING: Main window not found!");
+                    warn!("Main window not found");
                 }
             }
             
-            println!("Tauri setup complete");
+            info!("App setup complete");
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
@@ -224,18 +257,20 @@ fn main() {
             start_high_perf_monitoring,
             stop_monitoring,
             get_current_metrics,
+            set_process_selection,
             get_high_perf_metrics,
             start_kernel_monitoring,
             stop_kernel_monitoring,
-            get_kernel_metrics
+            get_kernel_metrics,
+            get_inventory
         ])
         .on_window_event(|window, event| {
             match event {
                 tauri::WindowEvent::Focused(focused) => {
-                    println!("Window {} focused: {}", window.label(), focused);
+                    trace!("Window {} focused: {}", window.label(), focused);
                 }
                 tauri::WindowEvent::Resized(size) => {
-                    println!("Window {} resized to: {:?}", window.label(), size);
+                    trace!("Window {} resized to: {:?}", window.label(), size);
                 }
                 _ => {}
             }
@@ -1,9 +1,11 @@
 use serde::{Deserialize, Serialize};
-use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex as StdMutex};
 use tokio::sync::RwLock;
 use sysinfo::{System, Disks, Networks, ProcessStatus};
 use std::collections::HashMap;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+use tracing::{field, Instrument, Span};
 use hostname;
 use os_info;
 
@@ -15,12 +17,120 @@ use high_perf_monitor::{HighPerfMonitoringService, HighPerfMetrics};
 pub mod ultra_perf_monitor;
 use ultra_perf_monitor::{UltraPerfMonitoringService, UltraPerfMetrics};
 
+// Shared with the library so both crates key disks alike
+#[path = "../../src/core/ids.rs"]
+mod ids;
+pub use ids::disk_id;
+
+// Shared with the library so both crates take the same sort keys
+#[path = "../../src/core/process_sort.rs"]
+mod process_sort;
+pub use process_sort::ProcessSortKey;
+
+// Shared with the library's process monitor; not every reading is used here
+#[allow(dead_code)]
+#[path = "../../src/backend/process_info.rs"]
+mod process_info;
+
+// `From` impls for funnelling the fast snapshots into `SystemMetrics`
+mod conversions;
+
+// Versioned binary frames of the high-performance stream for IPC
+pub mod wire;
+
 // Import kernel-level monitoring
 pub mod kernel_monitor;
 pub mod linux_ebpf;
 pub mod windows_etw;
+// Shared with the library's GPU monitor
+#[cfg(all(target_os = "windows", feature = "windows"))]
+#[path = "../../src/backend/windows_gpu.rs"]
+pub mod windows_gpu;
+// Shared with the library's Windows monitors; only single counters are read here
+#[cfg(all(target_os = "windows", feature = "windows"))]
+#[allow(dead_code)]
+#[path = "../../src/backend/windows_pdh.rs"]
+mod windows_pdh;
+// Shared with the library; the parsers for other platforms go unused here
+#[allow(dead_code)]
+#[path = "../../src/backend/inventory.rs"]
+pub mod inventory;
+
+use kernel_monitor::{KernelMonitor, KernelMetrics, KernelMonitorError};
+
+/// Thread counts for `pids`; processes that can't be inspected are left out
+pub fn thread_counts(pids: impl IntoIterator<Item = u32>) -> HashMap<u32, u32> {
+    #[cfg(target_os = "linux")]
+    {
+        pids.into_iter()
+            .filter_map(|pid| {
+                let status = std::fs::read_to_string(format!("/proc/{}/status", pid)).ok()?;
+                Some((pid, process_info::parse_status_threads(&status)?))
+            })
+            .collect()
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        // One snapshot covers every process, instead of one per process
+        let wanted: std::collections::HashSet<u32> = pids.into_iter().collect();
+        let mut counts = process_info::toolhelp::thread_counts();
+        counts.retain(|pid, _| wanted.contains(pid));
+        counts
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "windows")))]
+    {
+        let _ = pids;
+        HashMap::new()
+    }
+}
+
+/// User plus kernel CPU seconds consumed by each of `pids`; processes that
+/// can't be inspected are left out
+pub fn cpu_times(pids: impl IntoIterator<Item = u32>) -> HashMap<u32, f64> {
+    pids.into_iter()
+        .filter_map(|pid| Some((pid, process_info::cpu_time_seconds(pid)?)))
+        .collect()
+}
+
+/// Logical CPUs each of `pids` may be scheduled on, ascending; processes
+/// whose affinity can't be read are left out, and callers should treat them
+/// as free to run on all cores
+pub fn cpu_affinities(pids: impl IntoIterator<Item = u32>) -> HashMap<u32, Vec<u32>> {
+    pids.into_iter()
+        .map(|pid| (pid, process_info::cpu_affinity(pid)))
+        .filter(|(_, cpus)| !cpus.is_empty())
+        .collect()
+}
 
-use kernel_monitor::{KernelMonitor, KernelMetrics};
+/// Why a monitoring call failed. Serialized as `{"kind": "permission",
+/// "message": "..."}` so the frontend can branch on `kind`; `Display` gives
+/// the message alone, ready to show the user.
+#[derive(Debug, Clone, PartialEq, thiserror::Error, Serialize)]
+#[serde(tag = "kind", content = "message", rename_all = "snake_case")]
+pub enum MonitoringError {
+    #[error("{0}")]
+    Io(String),
+    #[error("{0}")]
+    Parse(String),
+    #[error("{0}")]
+    Unsupported(String),
+    #[error("{0}")]
+    Permission(String),
+}
+
+impl From<KernelMonitorError> for MonitoringError {
+    fn from(error: KernelMonitorError) -> Self {
+        let message = error.user_message();
+        match error {
+            KernelMonitorError::UnsupportedPlatform(_) => MonitoringError::Unsupported(message),
+            KernelMonitorError::PermissionDenied(_) => MonitoringError::Permission(message),
+            KernelMonitorError::SystemCallFailed(_) => MonitoringError::Io(message),
+            KernelMonitorError::InvalidConfig(_) => MonitoringError::Parse(message),
+        }
+    }
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SystemInfo {
@@ -39,7 +149,9 @@ pub struct SystemInfo {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CpuMetrics {
     pub usage_percent: f32,
+    /// Highest current clock across cores
     pub frequency_mhz: u64,
+    pub per_core_frequency_mhz: Vec<u64>,
     pub per_core_usage: Vec<f32>,
     pub temperature: Option<f32>,
     pub load_average: [f32; 3],
@@ -72,12 +184,16 @@ pub struct GpuMetrics {
     pub memory_usage_percent: f32,
     pub power_watts: f32,
     pub fan_speed_percent: Option<f32>,
-    pub clock_mhz: f32,
-    pub memory_clock_mhz: f32,
+    /// Whole MHz, as in the core library's `GpuMetrics`; 0 when unknown
+    pub clock_mhz: u32,
+    pub memory_clock_mhz: u32,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DiskMetrics {
+    /// Stable key for this filesystem across samples, from `disk_id`
+    #[serde(default)]
+    pub id: String,
     pub mount_point: String,
     pub device_name: String,
     pub fs_type: String,
@@ -92,6 +208,9 @@ pub struct DiskMetrics {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NetworkMetrics {
+    /// Stable key for this interface across samples; the interface name
+    #[serde(default)]
+    pub id: String,
     pub interface_name: String,
     pub is_up: bool,
     pub mac_address: String,
@@ -119,6 +238,14 @@ pub struct ProcessMetrics {
     pub status: String,
     pub threads: u32,
     pub start_time: String,
+    /// User plus kernel CPU time consumed since the process started
+    pub cpu_time_seconds: f64,
+    /// Wall-clock time since the process started
+    pub elapsed_seconds: u64,
+    /// Logical CPUs the process may be scheduled on; empty when unknown,
+    /// which means all cores
+    #[serde(default)]
+    pub cpu_affinity: Vec<u32>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -128,11 +255,185 @@ pub struct SystemMetrics {
     pub cpu: CpuMetrics,
     pub memory: MemoryMetrics,
     pub gpus: Vec<GpuMetrics>,
+    /// Sorted by mount point, so the order doesn't change between samples
     pub disks: Vec<DiskMetrics>,
+    /// Sorted by interface name, so the order doesn't change between samples
     pub networks: Vec<NetworkMetrics>,
     pub top_processes: Vec<ProcessMetrics>,
 }
 
+/// `used` as a percentage of `total`; 0 when `total` is 0, as it can be in
+/// containers and sandboxes, so no NaN reaches the serialized metrics
+pub fn usage_percent(used: u64, total: u64) -> f32 {
+    if total > 0 {
+        (used as f32 / total as f32) * 100.0
+    } else {
+        0.0
+    }
+}
+
+/// Milliseconds since `started`, for the timing fields of collection spans
+fn elapsed_ms(started: Instant) -> f64 {
+    started.elapsed().as_secs_f64() * 1000.0
+}
+
+/// Latest undelivered sample, shared between a throttled callback and its
+/// delivery thread
+struct Coalescer<T> {
+    latest: StdMutex<Option<T>>,
+    ready: std::sync::Condvar,
+    closed: AtomicBool,
+}
+
+/// Sending half of a throttled callback; dropping it ends the delivery thread
+struct ThrottleSender<T> {
+    shared: Arc<Coalescer<T>>,
+}
+
+impl<T> ThrottleSender<T> {
+    fn send(&self, sample: T) {
+        *self.shared.latest.lock().unwrap() = Some(sample);
+        self.shared.ready.notify_one();
+    }
+}
+
+impl<T> Drop for ThrottleSender<T> {
+    fn drop(&mut self) {
+        self.shared.closed.store(true, Ordering::Release);
+        self.shared.ready.notify_one();
+    }
+}
+
+/// Wraps `callback` so it runs at most once per `min_interval`
+///
+/// Samples arriving in between replace one another, and the most recent is
+/// always delivered once the interval has passed, so the consumer never sees
+/// stale data. Delivery happens on a dedicated thread, leaving the collector
+/// free to keep sampling.
+pub fn throttled<T, F>(callback: F, min_interval: std::time::Duration) -> impl Fn(T) + Send + Sync + 'static
+where
+    T: Send + 'static,
+    F: Fn(T) + Send + 'static,
+{
+    let shared = Arc::new(Coalescer {
+        latest: StdMutex::new(None),
+        ready: std::sync::Condvar::new(),
+        closed: AtomicBool::new(false),
+    });
+
+    let worker = shared.clone();
+    std::thread::spawn(move || loop {
+        let sample = {
+            let mut latest = worker.latest.lock().unwrap();
+            while latest.is_none() && !worker.closed.load(Ordering::Acquire) {
+                latest = worker.ready.wait(latest).unwrap();
+            }
+            match latest.take() {
+                Some(sample) => sample,
+                None => break,
+            }
+        };
+        callback(sample);
+        std::thread::sleep(min_interval);
+    });
+
+    let sender = ThrottleSender { shared };
+    move |sample| sender.send(sample)
+}
+
+/// How many processes the collectors report, and ranked by what
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProcessSelection {
+    pub sort_key: ProcessSortKey,
+    pub count: usize,
+}
+
+impl Default for ProcessSelection {
+    fn default() -> Self {
+        Self {
+            sort_key: ProcessSortKey::Cpu,
+            count: 20,
+        }
+    }
+}
+
+/// The fields every collector's process type can be ranked by
+pub trait RankedProcess {
+    fn cpu_usage(&self) -> f32;
+    fn memory(&self) -> u64;
+    fn disk_io(&self) -> u64;
+    fn process_name(&self) -> &str;
+    fn pid(&self) -> u32;
+}
+
+impl RankedProcess for ProcessMetrics {
+    fn cpu_usage(&self) -> f32 {
+        self.cpu_usage_percent
+    }
+
+    fn memory(&self) -> u64 {
+        self.memory_bytes
+    }
+
+    fn disk_io(&self) -> u64 {
+        self.disk_read_bytes + self.disk_write_bytes
+    }
+
+    fn process_name(&self) -> &str {
+        &self.name
+    }
+
+    fn pid(&self) -> u32 {
+        self.pid
+    }
+}
+
+impl ProcessSelection {
+    /// Sorts by the configured key and keeps the first `count` processes
+    pub fn apply<P: RankedProcess>(&self, processes: &mut Vec<P>) {
+        match self.sort_key {
+            ProcessSortKey::Cpu => processes.sort_by(|a, b| b.cpu_usage().total_cmp(&a.cpu_usage())),
+            ProcessSortKey::Memory => processes.sort_by_key(|p| std::cmp::Reverse(p.memory())),
+            ProcessSortKey::DiskIo => processes.sort_by_key(|p| std::cmp::Reverse(p.disk_io())),
+            ProcessSortKey::Name => processes.sort_by(|a, b| a.process_name().cmp(b.process_name())),
+            ProcessSortKey::Pid => processes.sort_by_key(|p| p.pid()),
+        }
+        processes.truncate(self.count);
+    }
+}
+
+/// Unix-style 1/5/15 minute load average for platforms without one.
+///
+/// Each sample decays the previous averages by `exp(-elapsed / period)`, the
+/// same exponential smoothing the kernel applies to its run-queue length.
+#[derive(Debug, Default)]
+pub struct LoadAverageEstimator {
+    averages: [f64; 3],
+    last_sample: Option<std::time::Instant>,
+}
+
+impl LoadAverageEstimator {
+    const PERIODS_SECS: [f64; 3] = [60.0, 300.0, 900.0];
+
+    /// Folds in the current number of running plus runnable threads
+    pub fn update(&mut self, runnable: f64, now: std::time::Instant) -> [f32; 3] {
+        match self.last_sample {
+            // Seed with the first sample instead of climbing from zero
+            None => self.averages = [runnable; 3],
+            Some(last) => {
+                let elapsed = now.saturating_duration_since(last).as_secs_f64();
+                for (average, period) in self.averages.iter_mut().zip(Self::PERIODS_SECS) {
+                    let decay = (-elapsed / period).exp();
+                    *average = *average * decay + runnable * (1.0 - decay);
+                }
+            }
+        }
+        self.last_sample = Some(now);
+
+        self.averages.map(|average| average as f32)
+    }
+}
+
 pub struct MonitoringService {
     system: Arc<RwLock<System>>,
     metrics_callback: Arc<RwLock<Option<Box<dyn Fn(SystemMetrics) + Send + Sync>>>>,
@@ -146,6 +447,18 @@ pub struct MonitoringService {
     // Kernel-level monitoring system
     kernel_monitor: Option<KernelMonitor>,
     kernel_callback: Arc<RwLock<Option<Box<dyn Fn(KernelMetrics) + Send + Sync>>>>,
+    // Standard metrics loop; each loop owns the flag it was started with
+    running: Arc<AtomicBool>,
+    update_interval_ms: u64,
+    // Only consulted where the OS has no load average (Windows)
+    load_estimator: Arc<RwLock<LoadAverageEstimator>>,
+    process_selection: Arc<StdMutex<ProcessSelection>>,
+    // AMD/Intel GPU performance counters, opened on first use
+    #[cfg(all(target_os = "windows", feature = "windows"))]
+    gpu_counters: Arc<StdMutex<Option<windows_gpu::GpuCounters>>>,
+    // `\System\Processor Queue Length`, opened on first use
+    #[cfg(all(target_os = "windows", feature = "windows"))]
+    processor_queue: Arc<StdMutex<Option<windows_pdh::RawCounters>>>,
 }
 
 impl MonitoringService {
@@ -281,16 +594,40 @@ impl MonitoringService {
             ultra_perf_callback: Arc::new(RwLock::new(None)),
             kernel_monitor: None,
             kernel_callback: Arc::new(RwLock::new(None)),
+            running: Arc::new(AtomicBool::new(false)),
+            update_interval_ms: 1000,
+            load_estimator: Arc::new(RwLock::new(LoadAverageEstimator::default())),
+            process_selection: Arc::new(StdMutex::new(ProcessSelection::default())),
+            #[cfg(all(target_os = "windows", feature = "windows"))]
+            gpu_counters: Arc::new(StdMutex::new(None)),
+            #[cfg(all(target_os = "windows", feature = "windows"))]
+            processor_queue: Arc::new(StdMutex::new(None)),
         }
     }
 
     pub fn new_with_high_perf(update_interval_ms: u64) -> Self {
         let mut service = Self::new();
+        service.update_interval_ms = update_interval_ms;
         service.high_perf_service = Some(HighPerfMonitoringService::new(update_interval_ms));
         service.ultra_perf_service = Some(UltraPerfMonitoringService::new(update_interval_ms));
         service
     }
 
+    /// Applies to the standard, high-performance and ultra-performance collectors
+    pub fn set_process_selection(&self, selection: ProcessSelection) {
+        *self.process_selection.lock().unwrap() = selection;
+        if let Some(service) = &self.high_perf_service {
+            service.set_process_selection(selection);
+        }
+        if let Some(service) = &self.ultra_perf_service {
+            service.set_process_selection(selection);
+        }
+    }
+
+    pub fn process_selection(&self) -> ProcessSelection {
+        *self.process_selection.lock().unwrap()
+    }
+
     pub async fn set_metrics_callback<F>(&mut self, callback: F)
     where
         F: Fn(SystemMetrics) + Send + Sync + 'static,
@@ -298,6 +635,15 @@ impl MonitoringService {
         *self.metrics_callback.write().await = Some(Box::new(callback));
     }
 
+    /// Like `set_metrics_callback`, but invokes `callback` at most once per
+    /// `min_interval` with the most recent sample
+    pub async fn set_metrics_callback_throttled<F>(&mut self, callback: F, min_interval: std::time::Duration)
+    where
+        F: Fn(SystemMetrics) + Send + Sync + 'static,
+    {
+        self.set_metrics_callback(throttled(callback, min_interval)).await;
+    }
+
     pub async fn set_high_perf_callback<F>(&mut self, callback: F)
     where
         F: Fn(HighPerfMetrics) + Send + Sync + 'static,
@@ -305,6 +651,13 @@ impl MonitoringService {
         *self.high_perf_callback.write().await = Some(Box::new(callback));
     }
 
+    pub async fn set_high_perf_callback_throttled<F>(&mut self, callback: F, min_interval: std::time::Duration)
+    where
+        F: Fn(HighPerfMetrics) + Send + Sync + 'static,
+    {
+        self.set_high_perf_callback(throttled(callback, min_interval)).await;
+    }
+
     pub async fn set_ultra_perf_callback<F>(&mut self, callback: F)
     where
         F: Fn(UltraPerfMetrics) + Send + Sync + 'static,
@@ -352,7 +705,15 @@ impl MonitoringService {
         *self.kernel_callback.write().await = Some(Box::new(callback));
     }
 
-    pub fn start_kernel_monitoring(&mut self) -> Result<(), kernel_monitor::KernelMonitorError> {
+    pub async fn set_kernel_callback_throttled<F>(&mut self, callback: F, min_interval: std::time::Duration)
+    where
+        F: Fn(KernelMetrics) + Send + Sync + 'static,
+    {
+        self.set_kernel_callback(throttled(callback, min_interval)).await;
+    }
+
+    /// Starts kernel-level collection; the error is ready to show the user
+    pub fn start_kernel_monitoring(&mut self) -> Result<(), MonitoringError> {
         if self.kernel_monitor.is_none() {
             self.kernel_monitor = Some(KernelMonitor::new()?);
         }
@@ -374,7 +735,7 @@ impl MonitoringService {
         self.kernel_monitor.as_ref()?.get_latest_metrics()
     }
 
-    pub async fn get_system_info(&self) -> Result<SystemInfo, String> {
+    pub async fn get_system_info(&self) -> Result<SystemInfo, MonitoringError> {
         let mut system = self.system.write().await;
         
         // Refresh system data for accurate information
@@ -382,7 +743,7 @@ impl MonitoringService {
         system.refresh_memory();
         
         let hostname = hostname::get()
-            .map_err(|e| format!("Failed to get hostname: {}", e))?
+            .map_err(|e| MonitoringError::Io(format!("Failed to get hostname: {}", e)))?
             .to_string_lossy()
             .to_string();
 
@@ -406,6 +767,57 @@ impl MonitoringService {
         })
     }
 
+    /// Fills in usage and video memory, which WMI doesn't report, for the
+    /// Threads ready to run but waiting for a CPU; 0 without PDH
+    fn processor_queue_length(&self) -> f64 {
+        #[cfg(all(target_os = "windows", feature = "windows"))]
+        {
+            let mut queue = self.processor_queue.lock().unwrap();
+            if queue.is_none() {
+                *queue = windows_pdh::RawCounters::open(&["\\System\\Processor Queue Length"]);
+            }
+            if let Some(length) = queue.as_ref().filter(|q| q.collect()).and_then(|q| q.value(0)) {
+                return length as f64;
+            }
+        }
+        0.0
+    }
+
+    /// `vendor_id` adapters in `gpus`
+    #[cfg(all(target_os = "windows", feature = "windows"))]
+    fn apply_windows_gpu_usage(&self, gpus: &mut [GpuMetrics], vendor_id: u32) {
+        let adapters: Vec<_> = match windows_gpu::dxgi_adapters() {
+            Ok(adapters) => adapters.into_iter().filter(|a| a.vendor_id == vendor_id).collect(),
+            Err(_) => return,
+        };
+
+        let usage = {
+            let mut counters = self.gpu_counters.lock().unwrap();
+            if counters.is_none() {
+                *counters = windows_gpu::GpuCounters::open();
+            }
+            counters.as_ref().map(windows_gpu::GpuCounters::sample).unwrap_or_default()
+        };
+
+        for (index, gpu) in gpus.iter_mut().enumerate() {
+            let Some(adapter) = adapters.iter().find(|a| a.name == gpu.name).or_else(|| adapters.get(index)) else {
+                continue;
+            };
+
+            // AdapterRAM is a 32-bit value and tops out at 4 GiB
+            if adapter.dedicated_memory_bytes > 0 {
+                gpu.memory_total_bytes = adapter.dedicated_memory_bytes;
+            }
+            if let Some(usage) = usage.get(&adapter.luid) {
+                gpu.usage_percent = usage.usage_percent;
+                gpu.memory_used_bytes = usage.dedicated_used_bytes;
+            }
+            if gpu.memory_total_bytes > 0 {
+                gpu.memory_usage_percent = gpu.memory_used_bytes as f32 / gpu.memory_total_bytes as f32 * 100.0;
+            }
+        }
+    }
+
     async fn get_gpu_metrics(&self) -> Vec<GpuMetrics> {
         let mut gpus = Vec::new();
         
@@ -438,8 +850,8 @@ impl MonitoringService {
                                     memory_usage_percent,
                                     power_watts: device.power_usage().unwrap_or(0) as f32 / 1000.0,
                                     fan_speed_percent: device.fan_speed(0).ok().map(|speed| speed as f32),
-                                    clock_mhz: device.max_clock_info(nvml_wrapper::enum_wrappers::device::Clock::Graphics).unwrap_or(0) as f32,
-                                    memory_clock_mhz: device.max_clock_info(nvml_wrapper::enum_wrappers::device::Clock::Memory).unwrap_or(0) as f32,
+                                    clock_mhz: device.max_clock_info(nvml_wrapper::enum_wrappers::device::Clock::Graphics).unwrap_or(0),
+                                    memory_clock_mhz: device.max_clock_info(nvml_wrapper::enum_wrappers::device::Clock::Memory).unwrap_or(0),
                                 });
                             }
                         }
@@ -451,6 +863,8 @@ impl MonitoringService {
         // AMD GPU detection (Windows)
         #[cfg(target_os = "windows")]
         {
+            #[cfg(feature = "windows")]
+            let first = gpus.len();
             // Try to detect AMD GPUs using Windows Management Instrumentation
             if let Ok(output) = std::process::Command::new("wmic")
                 .args(&["path", "win32_VideoController", "get", "name,adapterram,driverversion", "/format:csv"])
@@ -479,18 +893,22 @@ impl MonitoringService {
                                 memory_usage_percent,
                                 power_watts: 0.0, // WMI doesn't provide power info
                                 fan_speed_percent: None,
-                                clock_mhz: 0.0, // WMI doesn't provide clock info
-                                memory_clock_mhz: 0.0,
+                                clock_mhz: 0, // WMI doesn't provide clock info
+                                memory_clock_mhz: 0,
                             });
                         }
                     }
                 }
             }
+            #[cfg(feature = "windows")]
+            self.apply_windows_gpu_usage(&mut gpus[first..], windows_gpu::VENDOR_AMD);
         }
         
         // Intel GPU detection (Windows)
         #[cfg(target_os = "windows")]
         {
+            #[cfg(feature = "windows")]
+            let first = gpus.len();
             // Try to detect Intel GPUs using Windows Management Instrumentation
             if let Ok(output) = std::process::Command::new("wmic")
                 .args(&["path", "win32_VideoController", "get", "name,adapterram,driverversion", "/format:csv"])
@@ -519,13 +937,15 @@ impl MonitoringService {
                                 memory_usage_percent,
                                 power_watts: 0.0, // WMI doesn't provide power info
                                 fan_speed_percent: None,
-                                clock_mhz: 0.0, // WMI doesn't provide clock info
-                                memory_clock_mhz: 0.0,
+                                clock_mhz: 0, // WMI doesn't provide clock info
+                                memory_clock_mhz: 0,
                             });
                         }
                     }
                 }
             }
+            #[cfg(feature = "windows")]
+            self.apply_windows_gpu_usage(&mut gpus[first..], windows_gpu::VENDOR_INTEL);
         }
         
         // Apple Silicon GPU detection (macOS)
@@ -566,8 +986,8 @@ impl MonitoringService {
                                     memory_usage_percent: 0.0,
                                     power_watts: 0.0,
                                     fan_speed_percent: None,
-                                    clock_mhz: 0.0,
-                                    memory_clock_mhz: 0.0,
+                                    clock_mhz: 0,
+                                    memory_clock_mhz: 0,
                                 });
                             }
                         }
@@ -625,8 +1045,8 @@ impl MonitoringService {
                             memory_usage_percent: 0.0,
                             power_watts: 0.0,
                             fan_speed_percent: None,
-                            clock_mhz: 0.0,
-                            memory_clock_mhz: 0.0,
+                            clock_mhz: 0,
+                            memory_clock_mhz: 0,
                         });
                     }
                 }
@@ -639,7 +1059,7 @@ impl MonitoringService {
     async fn get_disk_metrics(&self) -> Vec<DiskMetrics> {
         let disks = Disks::new_with_refreshed_list();
         
-        disks.iter().map(|disk| {
+        let mut disk_metrics: Vec<DiskMetrics> = disks.iter().map(|disk| {
             let total_space = disk.total_space();
             let available_space = disk.available_space();
             let used_space = total_space - available_space;
@@ -649,9 +1069,12 @@ impl MonitoringService {
                 0.0
             };
 
+            let mount_point = disk.mount_point().to_string_lossy().to_string();
+            let device_name = disk.name().to_string_lossy().to_string();
             DiskMetrics {
-                mount_point: disk.mount_point().to_string_lossy().to_string(),
-                device_name: disk.name().to_string_lossy().to_string(),
+                id: disk_id(&device_name, &mount_point),
+                mount_point,
+                device_name,
                 fs_type: disk.file_system().to_string_lossy().to_string(),
                 total_bytes: total_space,
                 used_bytes: used_space,
@@ -661,7 +1084,9 @@ impl MonitoringService {
                 write_bytes_per_sec: 0,
                 io_operations_per_sec: 0,
             }
-        }).collect()
+        }).collect();
+        disk_metrics.sort_by(|a, b| a.mount_point.cmp(&b.mount_point));
+        disk_metrics
     }
 
     async fn get_network_metrics(&self) -> Vec<NetworkMetrics> {
@@ -686,6 +1111,7 @@ impl MonitoringService {
             };
 
             network_metrics.push(NetworkMetrics {
+                id: name.clone(),
                 interface_name: name.clone(),
                 is_up: true, // TODO: Implement is_up detection
                 mac_address: "Unknown".to_string(), // TODO: Implement MAC address detection
@@ -702,43 +1128,65 @@ impl MonitoringService {
             });
         }
         
+        // sysinfo keeps interfaces in a HashMap, so its order varies
+        network_metrics.sort_by(|a, b| a.interface_name.cmp(&b.interface_name));
         network_metrics
     }
 
-    pub async fn collect_metrics(&self) -> Result<SystemMetrics, String> {
+    /// Collects one snapshot inside a `collect_metrics` span whose fields
+    /// record, in milliseconds, how long the whole pass and each collector
+    /// took; the subscriber prints them when the span closes
+    pub async fn collect_metrics(&self) -> Result<SystemMetrics, MonitoringError> {
+        let span = tracing::debug_span!(
+            "collect_metrics",
+            refresh_ms = field::Empty,
+            gpu_ms = field::Empty,
+            disk_ms = field::Empty,
+            network_ms = field::Empty,
+            process_ms = field::Empty,
+            duration_ms = field::Empty,
+        );
+        let started = Instant::now();
+        let metrics = self.collect_pass(&span).instrument(span.clone()).await;
+        span.record("duration_ms", elapsed_ms(started));
+        metrics
+    }
+
+    async fn collect_pass(&self, span: &Span) -> Result<SystemMetrics, MonitoringError> {
         let mut system = self.system.write().await;
         
         // Refresh system data for accurate metrics
+        let started = Instant::now();
         system.refresh_all();
+        span.record("refresh_ms", elapsed_ms(started));
         
         // CPU metrics
         let cpu_usage = system.global_cpu_info().cpu_usage();
         let per_core_usage: Vec<f32> = system.cpus().iter().map(|cpu| cpu.cpu_usage()).collect();
         
-        let raw_frequency = system.cpus().first()
-            .map(|cpu| cpu.frequency())
-            .unwrap_or(0);
-
-        let frequency_mhz = raw_frequency; // sysinfo already provides frequency in MHz
+        // sysinfo already provides frequency in MHz
+        let per_core_frequency_mhz: Vec<u64> = system.cpus().iter().map(|cpu| cpu.frequency()).collect();
+        let frequency_mhz = per_core_frequency_mhz.iter().copied().max().unwrap_or(0);
+        
+        let load_average = if cfg!(target_os = "windows") {
+            // Windows has no run-queue average. Busy logical CPUs stand in for
+            // the running threads and the processor queue holds the runnable
+            // ones, together the count the Unix average is based on.
+            let running = cpu_usage as f64 / 100.0 * system.cpus().len() as f64;
+            let runnable = running + self.processor_queue_length();
+            self.load_estimator.write().await.update(runnable, std::time::Instant::now())
+        } else {
+            let load = System::load_average();
+            [load.one as f32, load.five as f32, load.fifteen as f32]
+        };
         
         let cpu_metrics = CpuMetrics {
             usage_percent: cpu_usage,
-            frequency_mhz, // Now correctly in MHz
+            frequency_mhz,
+            per_core_frequency_mhz,
             per_core_usage,
             temperature: None,
-            load_average: {
-                // Windows doesn't have traditional load average like Unix systems
-                // We'll calculate a pseudo-load average based on CPU usage and process count
-                let cpu_usage = cpu_usage;
-                let process_count = system.processes().len() as f32;
-                let cpu_count = system.cpus().len() as f32;
-                
-                // Calculate a load-like metric: (CPU usage * process count) / CPU count
-                let load_metric = (cpu_usage * process_count) / (cpu_count * 100.0);
-                
-                // Use the same value for all three intervals since we can't get historical data easily
-                [load_metric, load_metric, load_metric]
-            },
+            load_average,
             processes_total: system.processes().len(),
             processes_running: system.processes().values()
                 .filter(|p| matches!(p.status(), ProcessStatus::Run))
@@ -755,24 +1203,27 @@ impl MonitoringService {
             cached_bytes: 0, // Would need additional system calls
             swap_total_bytes: system.total_swap(),
             swap_used_bytes: system.used_swap(),
-            usage_percent: (system.used_memory() as f32 / system.total_memory() as f32) * 100.0,
-            swap_usage_percent: if system.total_swap() > 0 {
-                (system.used_swap() as f32 / system.total_swap() as f32) * 100.0
-            } else {
-                0.0
-            },
+            usage_percent: usage_percent(system.used_memory(), system.total_memory()),
+            swap_usage_percent: usage_percent(system.used_swap(), system.total_swap()),
         };
 
         // GPU metrics
+        let started = Instant::now();
         let gpu_metrics = self.get_gpu_metrics().await;
+        span.record("gpu_ms", elapsed_ms(started));
 
         // Disk metrics
+        let started = Instant::now();
         let disk_metrics = self.get_disk_metrics().await;
+        span.record("disk_ms", elapsed_ms(started));
 
         // Network metrics
+        let started = Instant::now();
         let network_metrics = self.get_network_metrics().await;
+        span.record("network_ms", elapsed_ms(started));
 
-        // Process metrics (top processes by CPU usage)
+        // Process metrics (top processes by the configured key)
+        let started = Instant::now();
         let mut processes: Vec<ProcessMetrics> = system.processes()
             .iter()
             .map(|(pid, process)| {
@@ -781,18 +1232,30 @@ impl MonitoringService {
                     name: process.name().to_string(),
                     cpu_usage_percent: process.cpu_usage(),
                     memory_bytes: process.memory(),
-                    memory_percent: (process.memory() as f32 / system.total_memory() as f32) * 100.0,
+                    memory_percent: usage_percent(process.memory(), system.total_memory()),
                     disk_read_bytes: 0, // Would need additional system calls
                     disk_write_bytes: 0, // Would need additional system calls
                     status: format!("{:?}", process.status()),
-                    threads: process.thread_kind().map(|t| t as u32).unwrap_or(1),
+                    threads: 1, // Filled in below for the processes that are kept
                     start_time: process.start_time().to_string(),
+                    cpu_time_seconds: 0.0, // Filled in below for the processes that are kept
+                    elapsed_seconds: process.run_time(),
+                    cpu_affinity: Vec::new(), // Filled in below for the processes that are kept
                 }
             })
             .collect();
 
-        processes.sort_by(|a, b| b.cpu_usage_percent.partial_cmp(&a.cpu_usage_percent).unwrap());
-        processes.truncate(20);
+        self.process_selection().apply(&mut processes);
+
+        let threads = thread_counts(processes.iter().map(|p| p.pid));
+        let cpu_times = cpu_times(processes.iter().map(|p| p.pid));
+        let mut affinities = cpu_affinities(processes.iter().map(|p| p.pid));
+        for process in &mut processes {
+            process.threads = threads.get(&process.pid).copied().unwrap_or(1);
+            process.cpu_time_seconds = cpu_times.get(&process.pid).copied().unwrap_or(0.0);
+            process.cpu_affinity = affinities.remove(&process.pid).unwrap_or_default();
+        }
+        span.record("process_ms", elapsed_ms(started));
 
         // Get system info without acquiring another lock (avoid deadlock)
         let system_info = SystemInfo {
@@ -871,4 +1334,216 @@ impl MonitoringService {
         // Note: Kernel monitoring callbacks are handled separately
         // to avoid lifetime issues with the monitor reference
     }
-}
\ No newline at end of file
+
+    /// Spawns the loop that collects `SystemMetrics` and hands them to the
+    /// metrics callback, replacing any loop started earlier
+    pub async fn start_metrics_loop(service: Arc<RwLock<MonitoringService>>) {
+        let (running, interval_ms) = {
+            let mut guard = service.write().await;
+            let running = Arc::new(AtomicBool::new(true));
+            // A previous loop keeps its own flag, so flipping it stops only that loop
+            std::mem::replace(&mut guard.running, running.clone()).store(false, Ordering::SeqCst);
+            (running, guard.update_interval_ms)
+        };
+
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(std::time::Duration::from_millis(interval_ms));
+
+            loop {
+                ticker.tick().await;
+
+                // Holding the read guard across collect and emit means
+                // stop_monitoring (which needs the write guard) waits for an
+                // in-flight emit, and every later iteration sees the flag
+                let guard = service.read().await;
+                if !running.load(Ordering::SeqCst) {
+                    break;
+                }
+
+                match guard.collect_metrics().await {
+                    Ok(metrics) => {
+                        if let Some(callback) = &*guard.metrics_callback.read().await {
+                            callback(metrics);
+                        }
+                    }
+                    Err(e) => tracing::error!("Error collecting metrics: {}", e),
+                }
+            }
+        });
+    }
+
+    /// Stops the metrics loop. No callback runs after this returns.
+    pub fn stop_monitoring(&mut self) {
+        self.running.store(false, Ordering::SeqCst);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::usage_percent;
+
+    #[test]
+    fn usage_percent_handles_zero_total() {
+        assert_eq!(usage_percent(0, 0), 0.0);
+        assert_eq!(usage_percent(512, 0), 0.0);
+        assert_eq!(usage_percent(1, 4), 25.0);
+        assert_eq!(serde_json::to_string(&usage_percent(512, 0)).unwrap(), "0.0");
+    }
+
+    #[test]
+    fn throttled_callback_coalesces_to_latest() {
+        use std::sync::{Arc, Mutex};
+        use std::time::Duration;
+
+        let delivered = Arc::new(Mutex::new(Vec::new()));
+        let sink = delivered.clone();
+        let callback = super::throttled(move |n: u32| sink.lock().unwrap().push(n), Duration::from_millis(200));
+
+        callback(1);
+        std::thread::sleep(Duration::from_millis(50));
+        // Both arrive inside the interval; only the newer one is kept
+        callback(2);
+        callback(3);
+        std::thread::sleep(Duration::from_millis(400));
+
+        assert_eq!(*delivered.lock().unwrap(), vec![1, 3]);
+    }
+
+    #[test]
+    fn ring_buffer_overwrites_oldest_when_full() {
+        use super::high_perf_monitor::RingBuffer;
+
+        let buffer = RingBuffer::new(3);
+        assert!(buffer.is_empty());
+        for value in 1..=5 {
+            assert!(buffer.push(value));
+        }
+
+        assert_eq!(buffer.len(), 3);
+        assert_eq!(buffer.snapshot(), vec![3, 4, 5]);
+        assert_eq!(buffer.pop(), Some(3));
+        buffer.push(6);
+        assert_eq!(buffer.snapshot(), vec![4, 5, 6]);
+        assert_eq!(buffer.pop(), Some(4));
+        assert_eq!(buffer.pop(), Some(5));
+        assert_eq!(buffer.pop(), Some(6));
+        assert_eq!(buffer.pop(), None);
+        assert!(buffer.is_empty());
+    }
+
+    #[test]
+    fn numa_node_files_parse() {
+        use super::ultra_perf_monitor::{parse_node_meminfo, parse_node_numastat};
+
+        // Captured from a two-socket server
+        let node0 = "Node 0 MemTotal:       65842072 kB\n\
+                     Node 0 MemFree:        12104944 kB\n\
+                     Node 0 MemUsed:        53737128 kB\n\
+                     Node 0 Active:         30227912 kB\n";
+        let node1 = "Node 1 MemTotal:       66060652 kB\n\
+                     Node 1 MemFree:        40012300 kB\n\
+                     Node 1 MemUsed:        26048352 kB\n";
+        assert_eq!(parse_node_meminfo(node0), Some((65842072 * 1024, 12104944 * 1024)));
+        assert_eq!(parse_node_meminfo(node1), Some((66060652 * 1024, 40012300 * 1024)));
+        assert_eq!(parse_node_meminfo("Node 0 Active: 1 kB\n"), None);
+
+        let numastat = "numa_hit 1180247782\nnuma_miss 3184\nnuma_foreign 3184\n\
+                        interleave_hit 36291\nlocal_node 1180012974\nother_node 237992\n";
+        assert_eq!(parse_node_numastat(numastat), (1180247782, 3184));
+        assert_eq!(parse_node_numastat(""), (0, 0));
+    }
+
+    #[test]
+    fn cpu_lists_parse() {
+        use super::process_info::parse_cpu_list;
+
+        assert_eq!(parse_cpu_list("0-3,8,10-11\n"), Some(vec![0, 1, 2, 3, 8, 10, 11]));
+        assert_eq!(parse_cpu_list("\t7"), Some(vec![7]));
+        assert_eq!(parse_cpu_list("4-2"), None);
+
+        // Every process may run on at least one CPU
+        #[cfg(any(target_os = "linux", target_os = "windows"))]
+        assert!(!super::cpu_affinities([std::process::id()])[&std::process::id()].is_empty());
+    }
+
+    #[test]
+    fn diskstats_parse() {
+        use super::high_perf_monitor::{parse_diskstats, DiskStat};
+
+        // Captured from a laptop on 6.1; loop devices report all zeroes
+        let diskstats = "   7       0 loop0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0\n\
+                         259       0 nvme0n1 318519 74366 23153298 61562 581302 354847 36744376 1066298 3 430640 1167470 0 0 0 0 43981 39609\n\
+                         259       1 nvme0n1p1 332 1071 13346 59 2 0 2 1 0 96 60 0 0 0 0 0 0\n";
+        let stats = parse_diskstats(diskstats);
+
+        assert_eq!(stats.len(), 3);
+        assert_eq!(stats["loop0"], DiskStat::default());
+        assert_eq!(
+            stats["nvme0n1"],
+            DiskStat {
+                reads: 318519,
+                sectors_read: 23153298,
+                read_ms: 61562,
+                writes: 581302,
+                sectors_written: 36744376,
+                write_ms: 1066298,
+                in_flight: 3,
+            }
+        );
+        assert_eq!(stats["nvme0n1p1"].sectors_read, 13346);
+        // Kernels before 4.18 stop after the eleven I/O fields
+        let old = parse_diskstats("   8       0 sda 1549 0 112066 1331 4 1 40 23 0 1524 1355\n");
+        assert_eq!(old["sda"].writes, 4);
+
+        assert!(parse_diskstats("   8       0 sdb 1549 0\n").is_empty());
+    }
+
+    #[test]
+    fn high_perf_frames_reject_other_versions() {
+        use super::high_perf_monitor::HighPerfMetrics;
+        use super::wire::{decode_high_perf, encode_high_perf, WireError, HIGH_PERF_VERSION};
+
+        let mut metrics = HighPerfMetrics::default();
+        metrics.timestamp_nanos = 42;
+        metrics.cpu.per_core_usage = vec![12.5, 87.5];
+        let frame = encode_high_perf(&metrics);
+        assert_eq!(&frame[..4], b"SDHP");
+
+        let decoded = decode_high_perf(&frame).unwrap();
+        assert_eq!(decoded.timestamp_nanos, 42);
+        assert_eq!(decoded.cpu.per_core_usage, vec![12.5, 87.5]);
+
+        // A frame from a build with a newer schema
+        let mut bumped = frame.clone();
+        bumped[4..6].copy_from_slice(&(HIGH_PERF_VERSION + 1).to_le_bytes());
+        let error = decode_high_perf(&bumped).unwrap_err();
+        assert!(matches!(
+            error,
+            WireError::UnsupportedVersion { expected, found } if expected == HIGH_PERF_VERSION && found == HIGH_PERF_VERSION + 1
+        ));
+        assert_eq!(
+            error.to_string(),
+            format!("Unsupported frame version: expected {}, found {}", HIGH_PERF_VERSION, HIGH_PERF_VERSION + 1)
+        );
+
+        // Bare bincode, as emitted before frames existed
+        let bare = bincode::serialize(&metrics).unwrap();
+        assert!(matches!(decode_high_perf(&bare), Err(WireError::BadMagic(_))));
+        assert!(matches!(decode_high_perf(&frame[..3]), Err(WireError::Truncated(3))));
+        assert!(matches!(decode_high_perf(&frame[..frame.len() - 1]), Err(WireError::Payload(_))));
+    }
+
+    #[test]
+    fn monitoring_error_serializes_kind() {
+        use super::kernel_monitor::KernelMonitorError;
+        use super::MonitoringError;
+
+        let error = MonitoringError::from(KernelMonitorError::PermissionDenied("no CAP_PERFMON".to_string()));
+        assert!(matches!(error, MonitoringError::Permission(_)));
+
+        let json = serde_json::to_value(&error).unwrap();
+        assert_eq!(json["kind"], "permission");
+        assert_eq!(json["message"], error.to_string());
+        assert!(error.to_string().contains("no CAP_PERFMON"));
+    }
+}
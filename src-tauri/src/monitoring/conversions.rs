@@ -0,0 +1,245 @@
+//! Conversions from the high- and ultra-performance snapshots to the standard
+//! `SystemMetrics`, so consumers mixing the streams can handle one schema.
+//!
+//! Fields the fast collectors don't gather are filled with defaults:
+//! - `system_info` only has `total_memory` and `cpu_threads`, the latter being
+//!   the number of cores reported
+//! - `processes_total`/`processes_running` are 0; the process list is only
+//!   the top processes
+//! - GPU `driver_version` and disk `fs_type` are empty
+//! - networks have no addresses, speed or cumulative counters and are
+//!   assumed up, since down interfaces aren't sampled
+//! - processes have no status, start time or CPU time, and cumulative disk
+//!   bytes are 0 as only per-second rates are collected
+//!
+//! Extras without a standard counterpart are dropped: cache, branch and
+//! hardware counters, page faults, NUMA figures, latencies, per-second packet
+//! and error rates, GPU compute/PCIe figures, process priorities, and the
+//! specialized accelerators. High-performance processes carry no affinity,
+//! so theirs is empty.
+
+use super::high_perf_monitor::{self as high_perf, HighPerfMetrics};
+use super::ultra_perf_monitor::{
+    UltraCpuMetrics, UltraDiskMetrics, UltraGpuMetrics, UltraMemoryMetrics, UltraNetworkMetrics, UltraPerfMetrics,
+    UltraProcessMetrics,
+};
+use super::{
+    disk_id, usage_percent, CpuMetrics, DiskMetrics, GpuMetrics, MemoryMetrics, NetworkMetrics, ProcessMetrics,
+    SystemInfo, SystemMetrics,
+};
+
+/// Implements `From` for each of the high- and ultra-performance `$source`
+/// types, which share the field names `$body` reads
+macro_rules! from_fast_metrics {
+    ($($source:ty),+ => $target:ty, |$value:ident| $body:expr) => {
+        $(
+            impl From<$source> for $target {
+                fn from($value: $source) -> Self {
+                    $body
+                }
+            }
+        )+
+    };
+}
+
+from_fast_metrics!(HighPerfMetrics, UltraPerfMetrics => SystemMetrics, |metrics| {
+    let total_memory = metrics.memory.total_bytes;
+    SystemMetrics {
+        timestamp: timestamp(metrics.timestamp_nanos),
+        system_info: system_info(total_memory, metrics.cpu.per_core_usage.len()),
+        cpu: metrics.cpu.into(),
+        memory: metrics.memory.into(),
+        gpus: metrics.gpus.into_iter().map(Into::into).collect(),
+        disks: metrics.disks.into_iter().map(Into::into).collect(),
+        networks: metrics.networks.into_iter().map(Into::into).collect(),
+        top_processes: metrics.processes.into_iter().map(|p| p.into_process(total_memory)).collect(),
+    }
+});
+
+from_fast_metrics!(high_perf::CpuMetrics, UltraCpuMetrics => CpuMetrics, |cpu| CpuMetrics {
+    usage_percent: cpu.global_usage,
+    frequency_mhz: cpu.frequency_mhz.iter().copied().max().unwrap_or(0),
+    per_core_frequency_mhz: cpu.frequency_mhz,
+    per_core_usage: cpu.per_core_usage,
+    temperature: cpu.temperature,
+    load_average: cpu.load_average,
+    processes_total: 0,
+    processes_running: 0,
+    context_switches: cpu.context_switches,
+    interrupts: cpu.interrupts,
+});
+
+from_fast_metrics!(high_perf::MemoryMetrics, UltraMemoryMetrics => MemoryMetrics, |memory| MemoryMetrics {
+    total_bytes: memory.total_bytes,
+    used_bytes: memory.used_bytes,
+    available_bytes: memory.available_bytes,
+    cached_bytes: memory.cached_bytes,
+    swap_total_bytes: memory.swap_total_bytes,
+    swap_used_bytes: memory.swap_used_bytes,
+    usage_percent: usage_percent(memory.used_bytes, memory.total_bytes),
+    swap_usage_percent: usage_percent(memory.swap_used_bytes, memory.swap_total_bytes),
+});
+
+from_fast_metrics!(high_perf::GpuMetrics, UltraGpuMetrics => GpuMetrics, |gpu| GpuMetrics {
+    name: gpu.name,
+    driver_version: String::new(),
+    temperature_celsius: gpu.temperature_celsius,
+    usage_percent: gpu.usage_percent,
+    memory_total_bytes: gpu.memory_total_bytes,
+    memory_used_bytes: gpu.memory_used_bytes,
+    memory_usage_percent: usage_percent(gpu.memory_used_bytes, gpu.memory_total_bytes),
+    power_watts: gpu.power_watts,
+    fan_speed_percent: gpu.fan_speed_percent,
+    clock_mhz: gpu.clock_mhz,
+    memory_clock_mhz: gpu.memory_clock_mhz,
+});
+
+from_fast_metrics!(high_perf::DiskMetrics, UltraDiskMetrics => DiskMetrics, |disk| DiskMetrics {
+    id: disk_id(&disk.device_name, &disk.mount_point),
+    mount_point: disk.mount_point,
+    device_name: disk.device_name,
+    fs_type: String::new(),
+    total_bytes: disk.total_bytes,
+    used_bytes: disk.used_bytes,
+    available_bytes: disk.total_bytes.saturating_sub(disk.used_bytes),
+    usage_percent: usage_percent(disk.used_bytes, disk.total_bytes),
+    read_bytes_per_sec: disk.read_bytes_per_sec,
+    write_bytes_per_sec: disk.write_bytes_per_sec,
+    io_operations_per_sec: disk.io_operations_per_sec,
+});
+
+from_fast_metrics!(high_perf::NetworkMetrics, UltraNetworkMetrics => NetworkMetrics, |network| {
+    network_rates(network.interface_name, network.bytes_sent_per_sec, network.bytes_received_per_sec)
+});
+
+/// Processes need the total memory for `memory_percent`, so they aren't `From`
+trait IntoProcess {
+    fn into_process(self, total_memory: u64) -> ProcessMetrics;
+}
+
+impl IntoProcess for high_perf::ProcessMetrics {
+    fn into_process(self, total_memory: u64) -> ProcessMetrics {
+        top_process(self.pid, self.name, self.cpu_usage_percent, self.memory_bytes, self.threads, total_memory)
+    }
+}
+
+impl IntoProcess for UltraProcessMetrics {
+    fn into_process(self, total_memory: u64) -> ProcessMetrics {
+        ProcessMetrics {
+            cpu_affinity: self.cpu_affinity,
+            ..top_process(self.pid, self.name, self.cpu_usage_percent, self.memory_bytes, self.threads, total_memory)
+        }
+    }
+}
+
+/// Seconds since the epoch, as the standard collector writes them
+fn timestamp(nanos: u64) -> String {
+    (nanos / 1_000_000_000).to_string()
+}
+
+fn system_info(total_memory: u64, cpu_threads: usize) -> SystemInfo {
+    SystemInfo {
+        hostname: String::new(),
+        os_name: String::new(),
+        os_version: String::new(),
+        kernel_version: String::new(),
+        architecture: String::new(),
+        cpu_brand: String::new(),
+        cpu_cores: 0,
+        cpu_threads,
+        total_memory,
+        boot_time: 0,
+    }
+}
+
+fn network_rates(interface_name: String, bytes_sent_rate: u64, bytes_received_rate: u64) -> NetworkMetrics {
+    NetworkMetrics {
+        id: interface_name.clone(),
+        interface_name,
+        is_up: true,
+        mac_address: String::new(),
+        ip_addresses: Vec::new(),
+        bytes_sent: 0,
+        bytes_received: 0,
+        packets_sent: 0,
+        packets_received: 0,
+        errors_sent: 0,
+        errors_received: 0,
+        speed_mbps: None,
+        bytes_sent_rate,
+        bytes_received_rate,
+    }
+}
+
+fn top_process(pid: u32, name: String, cpu_usage_percent: f32, memory_bytes: u64, threads: u32, total_memory: u64) -> ProcessMetrics {
+    ProcessMetrics {
+        pid,
+        name,
+        cpu_usage_percent,
+        memory_bytes,
+        memory_percent: usage_percent(memory_bytes, total_memory),
+        disk_read_bytes: 0,
+        disk_write_bytes: 0,
+        status: String::new(),
+        threads,
+        start_time: String::new(),
+        cpu_time_seconds: 0.0,
+        elapsed_seconds: 0,
+        cpu_affinity: Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn high_perf_metrics_convert_to_system_metrics() {
+        use super::high_perf::{DiskMetrics, HighPerfMetrics, ProcessMetrics};
+        use super::SystemMetrics;
+
+        let mut high_perf = HighPerfMetrics::default();
+        high_perf.timestamp_nanos = 1_700_000_000_500_000_000;
+        high_perf.cpu.global_usage = 37.5;
+        high_perf.cpu.per_core_usage = vec![25.0, 50.0];
+        high_perf.cpu.frequency_mhz = vec![3200, 4800];
+        high_perf.memory.total_bytes = 8 << 30;
+        high_perf.memory.used_bytes = 2 << 30;
+        high_perf.disks.push(DiskMetrics {
+            device_name: "nvme0n1".to_string(),
+            mount_point: "/".to_string(),
+            total_bytes: 1000,
+            used_bytes: 400,
+            read_bytes_per_sec: 10,
+            write_bytes_per_sec: 20,
+            io_operations_per_sec: 3,
+            read_latency_ms: 0.5,
+            write_latency_ms: 0.7,
+        });
+        high_perf.processes.push(ProcessMetrics {
+            pid: 42,
+            name: "worker".to_string(),
+            cpu_usage_percent: 12.0,
+            memory_bytes: 1 << 30,
+            disk_read_bytes_per_sec: 0,
+            disk_write_bytes_per_sec: 0,
+            network_bytes_per_sec: 0,
+            threads: 4,
+            priority: 0,
+        });
+
+        let metrics = SystemMetrics::from(high_perf);
+        assert_eq!(metrics.timestamp, "1700000000");
+        assert_eq!(metrics.system_info.cpu_threads, 2);
+        assert_eq!(metrics.cpu.usage_percent, 37.5);
+        // The standard frequency is the highest across cores
+        assert_eq!(metrics.cpu.frequency_mhz, 4800);
+        assert_eq!(metrics.memory.usage_percent, 25.0);
+        assert_eq!(metrics.disks[0].available_bytes, 600);
+        assert_eq!(metrics.disks[0].usage_percent, 40.0);
+        assert_eq!(metrics.top_processes[0].memory_percent, 12.5);
+        assert_eq!(metrics.top_processes[0].threads, 4);
+
+        let ultra = SystemMetrics::from(super::UltraPerfMetrics::default());
+        assert_eq!(ultra.memory.usage_percent, 0.0);
+        assert!(ultra.gpus.is_empty());
+    }
+}
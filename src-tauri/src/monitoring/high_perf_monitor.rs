@@ -1,10 +1,11 @@
 use serde::{Deserialize, Serialize};
+use super::{ProcessSelection, RankedProcess};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use crossbeam::channel::{bounded, Receiver, Sender};
 use dashmap::DashMap;
 use std::thread;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Mutex as StdMutex;
 use rayon::prelude::*;
 use sysinfo;
@@ -64,8 +65,9 @@ pub struct GpuMetrics {
     pub temperature_celsius: f32,
     pub power_watts: f32,
     pub fan_speed_percent: Option<f32>,
-    pub clock_mhz: f32,
-    pub memory_clock_mhz: f32,
+    /// Whole MHz, as in the standard `GpuMetrics`
+    pub clock_mhz: u32,
+    pub memory_clock_mhz: u32,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -105,6 +107,28 @@ pub struct ProcessMetrics {
     pub priority: i32,
 }
 
+impl RankedProcess for ProcessMetrics {
+    fn cpu_usage(&self) -> f32 {
+        self.cpu_usage_percent
+    }
+
+    fn memory(&self) -> u64 {
+        self.memory_bytes
+    }
+
+    fn disk_io(&self) -> u64 {
+        self.disk_read_bytes_per_sec + self.disk_write_bytes_per_sec
+    }
+
+    fn process_name(&self) -> &str {
+        &self.name
+    }
+
+    fn pid(&self) -> u32 {
+        self.pid
+    }
+}
+
 // Specialized hardware metrics
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DpuMetrics {
@@ -196,47 +220,77 @@ pub struct QuantumProcessorMetrics {
     pub error_rate: f32,
 }
 
-// High-performance ring buffer for lock-free data storage
-pub struct MetricsRingBuffer {
-    buffer: StdMutex<Vec<HighPerfMetrics>>,
+/// Fixed-capacity circular buffer that overwrites its oldest entry when full
+///
+/// Slots are allocated once up front, so `push` and `pop` are O(1) and never
+/// shift elements. `head` and `tail` are monotonically increasing positions,
+/// written under the slot lock but readable without it, which keeps `len()`
+/// lock-free for the collector thread.
+pub struct RingBuffer<T> {
+    slots: StdMutex<Box<[Option<T>]>>,
     head: std::sync::atomic::AtomicUsize,
     tail: std::sync::atomic::AtomicUsize,
     capacity: usize,
 }
 
-impl MetricsRingBuffer {
+pub type MetricsRingBuffer = RingBuffer<HighPerfMetrics>;
+
+impl<T: Clone> RingBuffer<T> {
     pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
         Self {
-            buffer: StdMutex::new(Vec::with_capacity(capacity)),
+            slots: StdMutex::new((0..capacity).map(|_| None).collect()),
             head: std::sync::atomic::AtomicUsize::new(0),
             tail: std::sync::atomic::AtomicUsize::new(0),
             capacity,
         }
     }
 
-    pub fn push(&self, metrics: HighPerfMetrics) -> bool {
-        if let Ok(mut buffer) = self.buffer.lock() {
-            if buffer.len() >= self.capacity {
-                // Remove oldest entry
-                buffer.remove(0);
-            }
-            buffer.push(metrics);
-            true
-        } else {
-            false
+    pub fn push(&self, metrics: T) -> bool {
+        let Ok(mut slots) = self.slots.lock() else {
+            return false;
+        };
+        let head = self.head.load(Ordering::Acquire);
+        let tail = self.tail.load(Ordering::Acquire);
+        if tail - head == self.capacity {
+            // Full: the write below overwrites the oldest entry
+            self.head.store(head + 1, Ordering::Release);
         }
+        slots[tail % self.capacity] = Some(metrics);
+        self.tail.store(tail + 1, Ordering::Release);
+        true
     }
 
-    pub fn pop(&self) -> Option<HighPerfMetrics> {
-        if let Ok(mut buffer) = self.buffer.lock() {
-            if buffer.is_empty() {
-                None
-            } else {
-                Some(buffer.remove(0))
-            }
-        } else {
-            None
+    pub fn pop(&self) -> Option<T> {
+        let mut slots = self.slots.lock().ok()?;
+        let head = self.head.load(Ordering::Acquire);
+        if head == self.tail.load(Ordering::Acquire) {
+            return None;
         }
+        self.head.store(head + 1, Ordering::Release);
+        slots[head % self.capacity].take()
+    }
+
+    pub fn len(&self) -> usize {
+        // Tail first, so a concurrent pop can only make this an overestimate
+        // that is then clamped, never an underflow
+        let tail = self.tail.load(Ordering::Acquire);
+        let head = self.head.load(Ordering::Acquire);
+        tail.saturating_sub(head).min(self.capacity)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Copies the buffered entries, oldest first, without draining them
+    pub fn snapshot(&self) -> Vec<T> {
+        let Ok(slots) = self.slots.lock() else {
+            return Vec::new();
+        };
+        let head = self.head.load(Ordering::Acquire);
+        let tail = self.tail.load(Ordering::Acquire);
+        (head..tail).filter_map(|position| slots[position % self.capacity].clone()).collect()
     }
 }
 
@@ -293,6 +347,91 @@ impl Default for MemoryMetrics {
     }
 }
 
+/// `previous_stats` entry holding the timestamp (ns) of the last collection
+pub(super) const SAMPLED_AT_KEY: &str = "sampled_at";
+
+/// Cumulative counters for one block device from `/proc/diskstats`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DiskStat {
+    pub reads: u64,
+    pub sectors_read: u64,
+    pub read_ms: u64,
+    pub writes: u64,
+    pub sectors_written: u64,
+    pub write_ms: u64,
+    /// I/Os currently in flight, i.e. the queue depth
+    pub in_flight: u64,
+}
+
+/// Parses `/proc/diskstats`, keyed by device name (`sda`, `nvme0n1p2`)
+pub fn parse_diskstats(content: &str) -> std::collections::HashMap<String, DiskStat> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let number = |index: usize| fields.get(index)?.parse::<u64>().ok();
+            Some((
+                fields.get(2)?.to_string(),
+                DiskStat {
+                    reads: number(3)?,
+                    sectors_read: number(5)?,
+                    read_ms: number(6)?,
+                    writes: number(7)?,
+                    sectors_written: number(9)?,
+                    write_ms: number(10)?,
+                    in_flight: number(11)?,
+                },
+            ))
+        })
+        .collect()
+}
+
+pub(super) fn per_second(current: u64, previous: u64, elapsed_secs: f64) -> u64 {
+    (current.saturating_sub(previous) as f64 / elapsed_secs) as u64
+}
+
+/// Time spent per completed operation between two samples
+pub(super) fn average_latency_ms(busy_ms: u64, previous_busy_ms: u64, ops: u64, previous_ops: u64) -> f32 {
+    match ops.saturating_sub(previous_ops) {
+        0 => 0.0,
+        completed => busy_ms.saturating_sub(previous_busy_ms) as f32 / completed as f32,
+    }
+}
+
+/// Samples the collection thread dropped because subscribers fell behind
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LagStats {
+    pub total_dropped: u64,
+    /// Most samples queued at once, at most the channel capacity
+    pub max_lag_observed: u64,
+}
+
+/// Counts the samples a full channel turns away
+#[derive(Debug, Default)]
+pub struct ChannelLag {
+    dropped: AtomicU64,
+    max_lag: AtomicU64,
+}
+
+impl ChannelLag {
+    /// Queues `value` without blocking the collection thread
+    pub fn send<T>(&self, sender: &Sender<T>, value: T) -> bool {
+        let sent = sender.try_send(value).is_ok();
+        if !sent {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+        self.max_lag.fetch_max(sender.len() as u64, Ordering::Relaxed);
+        sent
+    }
+
+    pub fn stats(&self) -> LagStats {
+        LagStats {
+            total_dropped: self.dropped.load(Ordering::Relaxed),
+            max_lag_observed: self.max_lag.load(Ordering::Relaxed),
+        }
+    }
+}
+
 // High-performance monitoring service
 pub struct HighPerfMonitoringService {
     ring_buffer: Arc<MetricsRingBuffer>,
@@ -301,6 +440,8 @@ pub struct HighPerfMonitoringService {
     running: Arc<AtomicBool>,
     update_interval: Duration,
     previous_stats: Arc<DashMap<String, (u64, u64)>>,
+    process_selection: Arc<StdMutex<ProcessSelection>>,
+    lag: Arc<ChannelLag>,
 }
 
 impl HighPerfMonitoringService {
@@ -316,6 +457,8 @@ impl HighPerfMonitoringService {
             running: Arc::new(AtomicBool::new(false)),
             update_interval: Duration::from_millis(update_interval_ms),
             previous_stats,
+            process_selection: Arc::new(StdMutex::new(ProcessSelection::default())),
+            lag: Arc::new(ChannelLag::default()),
         }
     }
 
@@ -329,7 +472,9 @@ impl HighPerfMonitoringService {
         let running = self.running.clone();
         let update_interval = self.update_interval;
         let previous_stats = self.previous_stats.clone();
+        let process_selection = self.process_selection.clone();
         let ring_buffer = self.ring_buffer.clone();
+        let lag = self.lag.clone();
 
         // Spawn dedicated monitoring thread with high priority
         thread::spawn(move || {
@@ -351,13 +496,13 @@ impl HighPerfMonitoringService {
                 let start = Instant::now();
                 
                 // Collect high-performance metrics
-                let metrics = Self::collect_metrics_high_perf(&previous_stats);
+                let metrics = Self::collect_metrics_high_perf(&previous_stats, *process_selection.lock().unwrap());
                 
                 // Store in ring buffer
                 ring_buffer.push(metrics.clone());
                 
-                // Send to subscribers
-                if let Err(_) = sender.send(metrics) {
+                // Send to subscribers without blocking on a full channel
+                if !lag.send(&sender, metrics) {
                     // Channel is full, drop oldest
                     let _ = ring_buffer.pop();
                 }
@@ -380,16 +525,33 @@ impl HighPerfMonitoringService {
         self.metrics_receiver.try_recv().ok()
     }
 
+    pub fn set_process_selection(&self, selection: ProcessSelection) {
+        *self.process_selection.lock().unwrap() = selection;
+    }
+
     pub fn subscribe(&self) -> Receiver<HighPerfMetrics> {
         self.metrics_receiver.clone()
     }
 
-    fn collect_metrics_high_perf(previous_stats: &DashMap<String, (u64, u64)>) -> HighPerfMetrics {
+    pub fn lag_stats(&self) -> LagStats {
+        self.lag.stats()
+    }
+
+    fn collect_metrics_high_perf(
+        previous_stats: &DashMap<String, (u64, u64)>,
+        process_selection: ProcessSelection,
+    ) -> HighPerfMetrics {
         let timestamp = std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
             .unwrap()
             .as_nanos() as u64;
 
+        // Rates are computed against the counters stored on the previous pass
+        let elapsed_secs = previous_stats
+            .insert(SAMPLED_AT_KEY.to_string(), (timestamp, 0))
+            .map(|(previous, _)| timestamp.saturating_sub(previous) as f64 / 1e9)
+            .filter(|elapsed| *elapsed > 0.0);
+
         // Use sysinfo with minimal refresh for high performance
         let mut sys = sysinfo::System::new_all();
         sys.refresh_cpu();
@@ -401,9 +563,9 @@ impl HighPerfMonitoringService {
             cpu: Self::collect_cpu_metrics(&sys),
             memory: Self::collect_memory_metrics(&sys),
             gpus: Self::collect_gpu_metrics(),
-            disks: Self::collect_disk_metrics(&sys, previous_stats),
-            networks: Self::collect_network_metrics(&sys, previous_stats),
-            processes: Self::collect_process_metrics(&sys),
+            disks: Self::collect_disk_metrics(&sys, previous_stats, elapsed_secs),
+            networks: Self::collect_network_metrics(&sys, previous_stats, elapsed_secs),
+            processes: Self::collect_process_metrics(&sys, process_selection),
             dpus: Self::collect_dpu_metrics(),
             npus: Self::collect_npu_metrics(),
             external_ddr: Self::collect_external_ddr_metrics(),
@@ -480,8 +642,8 @@ impl HighPerfMonitoringService {
                                     temperature_celsius: temperature as f32,
                                     power_watts: power as f32 / 1000.0,
                                     fan_speed_percent: device.fan_speed(0).ok().map(|s| s as f32),
-                                    clock_mhz: device.clock_info(nvml_wrapper::enum_wrappers::device::Clock::Graphics).unwrap_or(0) as f32,
-                                    memory_clock_mhz: device.clock_info(nvml_wrapper::enum_wrappers::device::Clock::Memory).unwrap_or(0) as f32,
+                                    clock_mhz: device.clock_info(nvml_wrapper::enum_wrappers::device::Clock::Graphics).unwrap_or(0),
+                                    memory_clock_mhz: device.clock_info(nvml_wrapper::enum_wrappers::device::Clock::Memory).unwrap_or(0),
                                 });
                             }
                         }
@@ -493,23 +655,114 @@ impl HighPerfMonitoringService {
         gpus
     }
 
-    fn collect_disk_metrics(_sys: &sysinfo::System, _previous_stats: &DashMap<String, (u64, u64)>) -> Vec<DiskMetrics> {
-        let disks = Vec::new();
-        
-        // TODO: Implement actual disk I/O monitoring
-        // For now, return empty vector
-        disks
+    fn collect_disk_metrics(
+        _sys: &sysinfo::System,
+        previous_stats: &DashMap<String, (u64, u64)>,
+        elapsed_secs: Option<f64>,
+    ) -> Vec<DiskMetrics> {
+        #[cfg(target_os = "linux")]
+        let stats = std::fs::read_to_string("/proc/diskstats")
+            .map(|content| parse_diskstats(&content))
+            .unwrap_or_default();
+        // Per-device counters are only exposed as /proc/diskstats; other
+        // platforms report capacity with zero rates
+        #[cfg(not(target_os = "linux"))]
+        let stats: std::collections::HashMap<String, DiskStat> = std::collections::HashMap::new();
+
+        let disks = sysinfo::Disks::new_with_refreshed_list();
+        let mut metrics: Vec<DiskMetrics> = disks
+            .iter()
+            .map(|disk| {
+                let device_name = disk.name().to_string_lossy().to_string();
+                let total_bytes = disk.total_space();
+                let mut metrics = DiskMetrics {
+                    device_name: device_name.clone(),
+                    mount_point: disk.mount_point().to_string_lossy().to_string(),
+                    total_bytes,
+                    used_bytes: total_bytes.saturating_sub(disk.available_space()),
+                    read_bytes_per_sec: 0,
+                    write_bytes_per_sec: 0,
+                    io_operations_per_sec: 0,
+                    read_latency_ms: 0.0,
+                    write_latency_ms: 0.0,
+                };
+
+                let Some(stat) = stats.get(device_name.trim_start_matches("/dev/")) else {
+                    return metrics;
+                };
+                let bytes = (stat.sectors_read * 512, stat.sectors_written * 512);
+                let ops = (stat.reads, stat.writes);
+                let busy_ms = (stat.read_ms, stat.write_ms);
+
+                let previous_bytes = previous_stats.insert(format!("disk:{}:bytes", device_name), bytes);
+                let previous_ops = previous_stats.insert(format!("disk:{}:ops", device_name), ops);
+                let previous_ms = previous_stats.insert(format!("disk:{}:ms", device_name), busy_ms);
+
+                if let (Some(elapsed), Some(prev_bytes), Some(prev_ops), Some(prev_ms)) =
+                    (elapsed_secs, previous_bytes, previous_ops, previous_ms)
+                {
+                    metrics.read_bytes_per_sec = per_second(bytes.0, prev_bytes.0, elapsed);
+                    metrics.write_bytes_per_sec = per_second(bytes.1, prev_bytes.1, elapsed);
+                    metrics.io_operations_per_sec =
+                        per_second(ops.0, prev_ops.0, elapsed) + per_second(ops.1, prev_ops.1, elapsed);
+                    metrics.read_latency_ms = average_latency_ms(busy_ms.0, prev_ms.0, ops.0, prev_ops.0);
+                    metrics.write_latency_ms = average_latency_ms(busy_ms.1, prev_ms.1, ops.1, prev_ops.1);
+                }
+
+                metrics
+            })
+            .collect();
+        metrics.sort_by(|a, b| a.mount_point.cmp(&b.mount_point));
+        metrics
     }
 
-    fn collect_network_metrics(_sys: &sysinfo::System, _previous_stats: &DashMap<String, (u64, u64)>) -> Vec<NetworkMetrics> {
-        let networks = Vec::new();
-        
-        // TODO: Implement actual network monitoring
-        // For now, return empty vector
-        networks
+    fn collect_network_metrics(
+        _sys: &sysinfo::System,
+        previous_stats: &DashMap<String, (u64, u64)>,
+        elapsed_secs: Option<f64>,
+    ) -> Vec<NetworkMetrics> {
+        let networks = sysinfo::Networks::new_with_refreshed_list();
+        let mut metrics: Vec<NetworkMetrics> = networks
+            .iter()
+            .map(|(name, data)| {
+                let bytes = (data.total_transmitted(), data.total_received());
+                let packets = (data.total_packets_transmitted(), data.total_packets_received());
+                let errors = (data.total_errors_on_transmitted(), data.total_errors_on_received());
+
+                let previous_bytes = previous_stats.insert(format!("net:{}:bytes", name), bytes);
+                let previous_packets = previous_stats.insert(format!("net:{}:packets", name), packets);
+                let previous_errors = previous_stats.insert(format!("net:{}:errors", name), errors);
+
+                let mut metrics = NetworkMetrics {
+                    interface_name: name.clone(),
+                    bytes_sent_per_sec: 0,
+                    bytes_received_per_sec: 0,
+                    packets_sent_per_sec: 0,
+                    packets_received_per_sec: 0,
+                    errors_per_sec: 0,
+                    latency_ms: 0.0,
+                };
+
+                if let (Some(elapsed), Some(prev_bytes), Some(prev_packets), Some(prev_errors)) =
+                    (elapsed_secs, previous_bytes, previous_packets, previous_errors)
+                {
+                    metrics.bytes_sent_per_sec = per_second(bytes.0, prev_bytes.0, elapsed);
+                    metrics.bytes_received_per_sec = per_second(bytes.1, prev_bytes.1, elapsed);
+                    metrics.packets_sent_per_sec = per_second(packets.0, prev_packets.0, elapsed);
+                    metrics.packets_received_per_sec = per_second(packets.1, prev_packets.1, elapsed);
+                    metrics.errors_per_sec =
+                        per_second(errors.0, prev_errors.0, elapsed) + per_second(errors.1, prev_errors.1, elapsed);
+                }
+
+                metrics
+            })
+            .collect();
+        // sysinfo keeps interfaces in a HashMap, so its order varies
+        metrics.sort_by(|a, b| a.interface_name.cmp(&b.interface_name));
+        metrics
     }
 
-    fn collect_process_metrics(sys: &sysinfo::System) -> Vec<ProcessMetrics> {
+    fn collect_process_metrics(sys: &sysinfo::System, selection: ProcessSelection) -> Vec<ProcessMetrics> {
         let mut processes: Vec<ProcessMetrics> = sys.processes()
             .par_iter()
             .map(|(pid, process)| {
@@ -521,15 +774,18 @@ impl HighPerfMonitoringService {
                     disk_read_bytes_per_sec: 0, // TODO: Add process I/O monitoring
                     disk_write_bytes_per_sec: 0,
                     network_bytes_per_sec: 0, // TODO: Add process network monitoring
-                    threads: 1, // TODO: Add thread count
+                    threads: 1, // Filled in below for the processes that are kept
                     priority: 0, // TODO: Add process priority
                 }
             })
             .collect();
 
-        // Sort by CPU usage and take top 20
-        processes.sort_by(|a, b| b.cpu_usage_percent.partial_cmp(&a.cpu_usage_percent).unwrap());
-        processes.truncate(20);
+        selection.apply(&mut processes);
+        
+        let threads = super::thread_counts(processes.iter().map(|p| p.pid));
+        for process in &mut processes {
+            process.threads = threads.get(&process.pid).copied().unwrap_or(1);
+        }
         
         processes
     }
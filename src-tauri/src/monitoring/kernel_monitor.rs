@@ -18,6 +18,22 @@ pub enum KernelMonitorError {
     InvalidConfig(String),
 }
 
+impl KernelMonitorError {
+    /// What to tell the user, including how to fix it where that's possible
+    pub fn user_message(&self) -> String {
+        match self {
+            KernelMonitorError::PermissionDenied(reason) => format!(
+                "Kernel metrics need elevated privileges ({}). Run as administrator to enable kernel metrics.",
+                reason
+            ),
+            KernelMonitorError::UnsupportedPlatform(os) => {
+                format!("Kernel metrics are not supported on {}", os)
+            }
+            other => other.to_string(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct KernelMetrics {
     pub timestamp: u64,  // Nanosecond precision
@@ -107,6 +123,9 @@ impl KernelMonitor {
             return Ok(());
         }
 
+        // Fail up front rather than spawning a loop that only reports zeroes
+        Self::check_access()?;
+
         *self.running.lock() = true;
         let sender = self.sender.clone();
         let running = self.running.clone();
@@ -169,6 +188,37 @@ impl KernelMonitor {
         }
     }
 
+    /// Checks that this process may open the kernel event sources at all
+    fn check_access() -> Result<(), KernelMonitorError> {
+        cfg_if::cfg_if! {
+            if #[cfg(target_os = "linux")] {
+                // Above level 1, perf_event_open refuses CPU-wide events to
+                // anyone without CAP_PERFMON; a missing file means no perf
+                // support in the kernel at all
+                let paranoid = std::fs::read_to_string("/proc/sys/kernel/perf_event_paranoid")
+                    .map_err(|_| KernelMonitorError::UnsupportedPlatform(
+                        "Linux without perf events".to_string()
+                    ))?
+                    .trim()
+                    .parse::<i32>()
+                    .unwrap_or(2);
+                if paranoid > 1 && unsafe { libc::geteuid() } != 0 {
+                    return Err(KernelMonitorError::PermissionDenied(format!(
+                        "kernel.perf_event_paranoid is {}", paranoid
+                    )));
+                }
+                Ok(())
+            } else if #[cfg(target_os = "windows")] {
+                // Kernel ETW sessions are checked when the session is opened
+                Ok(())
+            } else {
+                Err(KernelMonitorError::UnsupportedPlatform(
+                    std::env::consts::OS.to_string()
+                ))
+            }
+        }
+    }
+
     fn collect_kernel_metrics() -> Result<KernelMetrics, KernelMonitorError> {
         cfg_if::cfg_if! {
             if #[cfg(target_os = "linux")] {
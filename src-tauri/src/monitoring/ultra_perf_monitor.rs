@@ -7,6 +7,12 @@ use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Mutex;
 use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
+use super::{ProcessSelection, RankedProcess};
+use super::high_perf_monitor::{average_latency_ms, per_second, ChannelLag, LagStats, RingBuffer, SAMPLED_AT_KEY};
+#[cfg(target_os = "linux")]
+use super::high_perf_monitor::parse_diskstats;
+#[cfg(not(target_os = "linux"))]
+use super::high_perf_monitor::DiskStat;
 
 // Ultra-high-performance metrics with nanosecond precision
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -54,6 +60,43 @@ pub struct UltraMemoryMetrics {
     pub numa_misses: u64,
     pub memory_bandwidth_gbps: f32,
     pub memory_latency_ns: f32,
+    /// Per-node breakdown; a machine without NUMA reports its memory as
+    /// node 0, and platforms other than Linux report none
+    pub numa_nodes: Vec<NumaNodeMemory>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct NumaNodeMemory {
+    pub node_id: u32,
+    pub total_bytes: u64,
+    pub free_bytes: u64,
+    /// Allocations satisfied from this node as intended
+    pub hits: u64,
+    /// Allocations meant for another node that landed here instead
+    pub misses: u64,
+}
+
+/// Total and free bytes from a `/sys/devices/system/node/nodeN/meminfo`,
+/// whose lines look like `Node 0 MemTotal:       16318976 kB`
+pub fn parse_node_meminfo(content: &str) -> Option<(u64, u64)> {
+    let field = |name: &str| {
+        content.lines().find_map(|line| {
+            let mut fields = line.split_whitespace().skip(2);
+            (fields.next()? == name).then(|| fields.next()?.parse::<u64>().ok())?
+        })
+    };
+    Some((field("MemTotal:")? * 1024, field("MemFree:")? * 1024))
+}
+
+/// `numa_hit` and `numa_miss` page counts from a node's `numastat`
+pub fn parse_node_numastat(content: &str) -> (u64, u64) {
+    let counter = |name: &str| {
+        content
+            .lines()
+            .find_map(|line| line.strip_prefix(name)?.trim().parse::<u64>().ok())
+            .unwrap_or(0)
+    };
+    (counter("numa_hit "), counter("numa_miss "))
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -65,8 +108,9 @@ pub struct UltraGpuMetrics {
     pub temperature_celsius: f32,
     pub power_watts: f32,
     pub fan_speed_percent: Option<f32>,
-    pub clock_mhz: f32,
-    pub memory_clock_mhz: f32,
+    /// Whole MHz, as in the standard `GpuMetrics`
+    pub clock_mhz: u32,
+    pub memory_clock_mhz: u32,
     pub compute_utilization: f32,
     pub memory_utilization: f32,
     pub pcie_bandwidth_gbps: f32,
@@ -111,11 +155,35 @@ pub struct UltraProcessMetrics {
     pub network_bytes_per_sec: u64,
     pub threads: u32,
     pub priority: i32,
+    /// Logical CPUs the process may be scheduled on; empty when unknown,
+    /// which means all cores
     pub cpu_affinity: Vec<u32>,
     pub memory_working_set: u64,
     pub io_priority: i32,
 }
 
+impl RankedProcess for UltraProcessMetrics {
+    fn cpu_usage(&self) -> f32 {
+        self.cpu_usage_percent
+    }
+
+    fn memory(&self) -> u64 {
+        self.memory_bytes
+    }
+
+    fn disk_io(&self) -> u64 {
+        self.disk_read_bytes_per_sec + self.disk_write_bytes_per_sec
+    }
+
+    fn process_name(&self) -> &str {
+        &self.name
+    }
+
+    fn pid(&self) -> u32 {
+        self.pid
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HardwareCounterMetrics {
     pub cpu_cycles: u64,
@@ -130,44 +198,7 @@ pub struct HardwareCounterMetrics {
     pub ref_cpu_cycles: u64,
 }
 
-// Lock-free ring buffer optimized for ultra-high performance
-pub struct UltraPerfRingBuffer {
-    buffer: Arc<Mutex<Vec<UltraPerfMetrics>>>,
-    capacity: usize,
-}
-
-impl UltraPerfRingBuffer {
-    pub fn new(capacity: usize) -> Self {
-        Self {
-            buffer: Arc::new(Mutex::new(Vec::with_capacity(capacity))),
-            capacity,
-        }
-    }
-
-    pub fn push(&self, metrics: UltraPerfMetrics) -> bool {
-        if let Ok(mut buffer) = self.buffer.lock() {
-            if buffer.len() >= self.capacity {
-                buffer.remove(0);
-            }
-            buffer.push(metrics);
-            true
-        } else {
-            false
-        }
-    }
-
-    pub fn pop(&self) -> Option<UltraPerfMetrics> {
-        if let Ok(mut buffer) = self.buffer.lock() {
-            if buffer.is_empty() {
-                None
-            } else {
-                Some(buffer.remove(0))
-            }
-        } else {
-            None
-        }
-    }
-}
+pub type UltraPerfRingBuffer = RingBuffer<UltraPerfMetrics>;
 
 impl Default for UltraPerfMetrics {
     fn default() -> Self {
@@ -221,6 +252,7 @@ impl Default for UltraMemoryMetrics {
             numa_misses: 0,
             memory_bandwidth_gbps: 0.0,
             memory_latency_ns: 0.0,
+            numa_nodes: Vec::new(),
         }
     }
 }
@@ -250,6 +282,8 @@ pub struct UltraPerfMonitoringService {
     running: Arc<AtomicBool>,
     update_interval: Duration,
     previous_stats: Arc<DashMap<String, (u64, u64)>>,
+    process_selection: Arc<Mutex<ProcessSelection>>,
+    lag: Arc<ChannelLag>,
 }
 
 impl UltraPerfMonitoringService {
@@ -265,6 +299,8 @@ impl UltraPerfMonitoringService {
             running: Arc::new(AtomicBool::new(false)),
             update_interval: Duration::from_micros(update_interval_ms * 1000), // Convert to microseconds
             previous_stats,
+            process_selection: Arc::new(Mutex::new(ProcessSelection::default())),
+            lag: Arc::new(ChannelLag::default()),
         }
     }
 
@@ -278,7 +314,9 @@ impl UltraPerfMonitoringService {
         let running = self.running.clone();
         let update_interval = self.update_interval;
         let previous_stats = self.previous_stats.clone();
+        let process_selection = self.process_selection.clone();
         let ring_buffer = self.ring_buffer.clone();
+        let lag = self.lag.clone();
 
         // Spawn ultra-high-priority monitoring thread
         thread::spawn(move || {
@@ -300,13 +338,13 @@ impl UltraPerfMonitoringService {
                 let start = Instant::now();
                 
                 // Collect ultra-high-performance metrics
-                let metrics = Self::collect_ultra_perf_metrics(&previous_stats);
+                let metrics = Self::collect_ultra_perf_metrics(&previous_stats, *process_selection.lock().unwrap());
                 
                 // Store in ring buffer
                 ring_buffer.push(metrics.clone());
                 
                 // Send to subscribers with non-blocking send
-                if let Err(_) = sender.try_send(metrics) {
+                if !lag.send(&sender, metrics) {
                     // Channel is full, drop oldest
                     let _ = ring_buffer.pop();
                 }
@@ -336,12 +374,21 @@ impl UltraPerfMonitoringService {
         self.metrics_receiver.try_recv().ok()
     }
 
+    pub fn set_process_selection(&self, selection: ProcessSelection) {
+        *self.process_selection.lock().unwrap() = selection;
+    }
+
     pub fn subscribe(&self) -> Receiver<UltraPerfMetrics> {
         self.metrics_receiver.clone()
     }
 
+    pub fn lag_stats(&self) -> LagStats {
+        self.lag.stats()
+    }
+
     fn collect_ultra_perf_metrics(
         previous_stats: &DashMap<String, (u64, u64)>,
+        process_selection: ProcessSelection,
     ) -> UltraPerfMetrics {
         let collection_start = Instant::now();
         
@@ -350,6 +397,12 @@ impl UltraPerfMonitoringService {
             .unwrap()
             .as_nanos() as u64;
 
+        // Rates are computed against the counters stored on the previous pass
+        let elapsed_secs = previous_stats
+            .insert(SAMPLED_AT_KEY.to_string(), (timestamp, 0))
+            .map(|(previous, _)| timestamp.saturating_sub(previous) as f64 / 1e9)
+            .filter(|elapsed| *elapsed > 0.0);
+
         // Use sysinfo with minimal refresh for ultra-high performance
         let mut sys = sysinfo::System::new_all();
         sys.refresh_cpu();
@@ -364,9 +417,9 @@ impl UltraPerfMonitoringService {
             cpu: Self::collect_ultra_cpu_metrics(&sys),
             memory: Self::collect_ultra_memory_metrics(&sys),
             gpus: Self::collect_ultra_gpu_metrics(),
-            disks: Self::collect_ultra_disk_metrics(&sys, previous_stats),
+            disks: Self::collect_ultra_disk_metrics(&sys, previous_stats, elapsed_secs),
             networks: Self::collect_ultra_network_metrics(&sys, previous_stats),
-            processes: Self::collect_ultra_process_metrics(&sys),
+            processes: Self::collect_ultra_process_metrics(&sys, process_selection),
             hardware_counters: Self::collect_hardware_counters(),
         }
     }
@@ -404,6 +457,7 @@ impl UltraPerfMonitoringService {
     }
 
     fn collect_ultra_memory_metrics(sys: &sysinfo::System) -> UltraMemoryMetrics {
+        let numa_nodes = Self::collect_numa_nodes();
         UltraMemoryMetrics {
             total_bytes: sys.total_memory(),
             used_bytes: sys.used_memory(),
@@ -415,13 +469,42 @@ impl UltraPerfMonitoringService {
             page_faults: 0, // TODO: Add page fault monitoring
             page_ins: 0,
             page_outs: 0,
-            numa_hits: 0,
-            numa_misses: 0,
+            numa_hits: numa_nodes.iter().map(|n| n.hits).sum(),
+            numa_misses: numa_nodes.iter().map(|n| n.misses).sum(),
             memory_bandwidth_gbps: 0.0,
             memory_latency_ns: 0.0,
+            numa_nodes,
         }
     }
 
+    #[cfg(target_os = "linux")]
+    fn collect_numa_nodes() -> Vec<NumaNodeMemory> {
+        let Ok(entries) = std::fs::read_dir("/sys/devices/system/node") else {
+            return Vec::new();
+        };
+
+        let mut nodes: Vec<NumaNodeMemory> = entries
+            .flatten()
+            .filter_map(|entry| {
+                let node_id = entry.file_name().to_str()?.strip_prefix("node")?.parse::<u32>().ok()?;
+                let path = entry.path();
+                let (total_bytes, free_bytes) =
+                    parse_node_meminfo(&std::fs::read_to_string(path.join("meminfo")).ok()?)?;
+                let (hits, misses) = std::fs::read_to_string(path.join("numastat"))
+                    .map(|content| parse_node_numastat(&content))
+                    .unwrap_or_default();
+                Some(NumaNodeMemory { node_id, total_bytes, free_bytes, hits, misses })
+            })
+            .collect();
+        nodes.sort_by_key(|node| node.node_id);
+        nodes
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn collect_numa_nodes() -> Vec<NumaNodeMemory> {
+        Vec::new()
+    }
+
     fn collect_ultra_gpu_metrics() -> Vec<UltraGpuMetrics> {
         let mut gpus = Vec::new();
         
@@ -445,8 +528,8 @@ impl UltraPerfMonitoringService {
                                     temperature_celsius: temperature as f32,
                                     power_watts: power as f32 / 1000.0,
                                     fan_speed_percent: device.fan_speed(0).ok().map(|s| s as f32),
-                                    clock_mhz: device.clock_info(nvml_wrapper::enum_wrappers::device::Clock::Graphics).unwrap_or(0) as f32,
-                                    memory_clock_mhz: device.clock_info(nvml_wrapper::enum_wrappers::device::Clock::Memory).unwrap_or(0) as f32,
+                                    clock_mhz: device.clock_info(nvml_wrapper::enum_wrappers::device::Clock::Graphics).unwrap_or(0),
+                                    memory_clock_mhz: device.clock_info(nvml_wrapper::enum_wrappers::device::Clock::Memory).unwrap_or(0),
                                     compute_utilization: utilization.gpu as f32,
                                     memory_utilization: (memory.used as f32 / memory.total as f32) * 100.0,
                                     pcie_bandwidth_gbps: 0.0, // TODO: Add PCIe bandwidth monitoring
@@ -461,12 +544,72 @@ impl UltraPerfMonitoringService {
         gpus
     }
 
-    fn collect_ultra_disk_metrics(_sys: &sysinfo::System, _previous_stats: &DashMap<String, (u64, u64)>) -> Vec<UltraDiskMetrics> {
-        let disks = Vec::new();
-        
-        // TODO: Implement actual disk I/O monitoring with ultra-low latency
-        // For now, return empty vector
-        disks
+    fn collect_ultra_disk_metrics(
+        _sys: &sysinfo::System,
+        previous_stats: &DashMap<String, (u64, u64)>,
+        elapsed_secs: Option<f64>,
+    ) -> Vec<UltraDiskMetrics> {
+        #[cfg(target_os = "linux")]
+        let stats = std::fs::read_to_string("/proc/diskstats")
+            .map(|content| parse_diskstats(&content))
+            .unwrap_or_default();
+        // Latency and queue depth come from /proc/diskstats; other platforms
+        // report capacity only
+        #[cfg(not(target_os = "linux"))]
+        let stats: std::collections::HashMap<String, DiskStat> = std::collections::HashMap::new();
+
+        let disks = sysinfo::Disks::new_with_refreshed_list();
+        let mut metrics: Vec<UltraDiskMetrics> = disks
+            .iter()
+            .map(|disk| {
+                let device_name = disk.name().to_string_lossy().to_string();
+                let total_bytes = disk.total_space();
+                let mut metrics = UltraDiskMetrics {
+                    device_name: device_name.clone(),
+                    mount_point: disk.mount_point().to_string_lossy().to_string(),
+                    total_bytes,
+                    used_bytes: total_bytes.saturating_sub(disk.available_space()),
+                    read_bytes_per_sec: 0,
+                    write_bytes_per_sec: 0,
+                    io_operations_per_sec: 0,
+                    read_latency_ms: 0.0,
+                    write_latency_ms: 0.0,
+                    queue_depth: 0,
+                    throughput_gbps: 0.0,
+                };
+
+                let Some(stat) = stats.get(device_name.trim_start_matches("/dev/")) else {
+                    return metrics;
+                };
+                // In-flight I/Os are a gauge, so they need no previous sample
+                metrics.queue_depth = stat.in_flight.min(u32::MAX as u64) as u32;
+
+                let bytes = (stat.sectors_read * 512, stat.sectors_written * 512);
+                let ops = (stat.reads, stat.writes);
+                let busy_ms = (stat.read_ms, stat.write_ms);
+
+                let previous_bytes = previous_stats.insert(format!("disk:{}:bytes", device_name), bytes);
+                let previous_ops = previous_stats.insert(format!("disk:{}:ops", device_name), ops);
+                let previous_ms = previous_stats.insert(format!("disk:{}:ms", device_name), busy_ms);
+
+                if let (Some(elapsed), Some(prev_bytes), Some(prev_ops), Some(prev_ms)) =
+                    (elapsed_secs, previous_bytes, previous_ops, previous_ms)
+                {
+                    metrics.read_bytes_per_sec = per_second(bytes.0, prev_bytes.0, elapsed);
+                    metrics.write_bytes_per_sec = per_second(bytes.1, prev_bytes.1, elapsed);
+                    metrics.io_operations_per_sec =
+                        per_second(ops.0, prev_ops.0, elapsed) + per_second(ops.1, prev_ops.1, elapsed);
+                    metrics.read_latency_ms = average_latency_ms(busy_ms.0, prev_ms.0, ops.0, prev_ops.0);
+                    metrics.write_latency_ms = average_latency_ms(busy_ms.1, prev_ms.1, ops.1, prev_ops.1);
+                    metrics.throughput_gbps =
+                        (metrics.read_bytes_per_sec + metrics.write_bytes_per_sec) as f32 * 8.0 / 1e9;
+                }
+
+                metrics
+            })
+            .collect();
+        metrics.sort_by(|a, b| a.mount_point.cmp(&b.mount_point));
+        metrics
     }
 
     fn collect_ultra_network_metrics(_sys: &sysinfo::System, _previous_stats: &DashMap<String, (u64, u64)>) -> Vec<UltraNetworkMetrics> {
@@ -477,7 +620,7 @@ impl UltraPerfMonitoringService {
         networks
     }
 
-    fn collect_ultra_process_metrics(sys: &sysinfo::System) -> Vec<UltraProcessMetrics> {
+    fn collect_ultra_process_metrics(sys: &sysinfo::System, selection: ProcessSelection) -> Vec<UltraProcessMetrics> {
         let mut processes: Vec<UltraProcessMetrics> = sys.processes()
             .par_iter()
             .map(|(pid, process)| {
@@ -489,18 +632,23 @@ impl UltraPerfMonitoringService {
                     disk_read_bytes_per_sec: 0, // TODO: Add process I/O monitoring
                     disk_write_bytes_per_sec: 0,
                     network_bytes_per_sec: 0, // TODO: Add process network monitoring
-                    threads: 1, // TODO: Add thread count
+                    threads: 1, // Filled in below for the processes that are kept
                     priority: 0, // TODO: Add process priority
-                    cpu_affinity: Vec::new(), // TODO: Add CPU affinity
+                    cpu_affinity: Vec::new(), // Filled in below for the processes that are kept
                     memory_working_set: 0, // TODO: Add working set size
                     io_priority: 0, // TODO: Add I/O priority
                 }
             })
             .collect();
 
-        // Sort by CPU usage and take top 20
-        processes.sort_by(|a, b| b.cpu_usage_percent.partial_cmp(&a.cpu_usage_percent).unwrap());
-        processes.truncate(20);
+        selection.apply(&mut processes);
+        
+        let threads = super::thread_counts(processes.iter().map(|p| p.pid));
+        let mut affinities = super::cpu_affinities(processes.iter().map(|p| p.pid));
+        for process in &mut processes {
+            process.threads = threads.get(&process.pid).copied().unwrap_or(1);
+            process.cpu_affinity = affinities.remove(&process.pid).unwrap_or_default();
+        }
         
         processes
     }
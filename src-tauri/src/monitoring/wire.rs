@@ -0,0 +1,74 @@
+//! Framed binary encoding of the high-performance stream for IPC.
+//!
+//! bincode carries no schema, so a frame is a 4-byte magic, the schema
+//! version as a little-endian u16 and then the bincode payload. Decoding
+//! checks both before touching the payload, so a frame from an app built
+//! with a different `HighPerfMetrics` fails with `UnsupportedVersion`
+//! instead of decoding into garbage.
+//!
+//! Bump `HIGH_PERF_VERSION` whenever `HighPerfMetrics` or anything it
+//! contains changes shape.
+
+use thiserror::Error;
+
+use super::high_perf_monitor::HighPerfMetrics;
+
+/// Marks a frame as carrying `HighPerfMetrics`
+pub const HIGH_PERF_MAGIC: [u8; 4] = *b"SDHP";
+/// Schema version of `HighPerfMetrics` frames
+pub const HIGH_PERF_VERSION: u16 = 1;
+
+const HEADER_LEN: usize = 6;
+
+#[derive(Debug, Error)]
+pub enum WireError {
+    #[error("Frame too short: {0} bytes")]
+    Truncated(usize),
+
+    #[error("Not a high-performance metrics frame (magic {0:02x?})")]
+    BadMagic([u8; 4]),
+
+    #[error("Unsupported frame version: expected {expected}, found {found}")]
+    UnsupportedVersion { expected: u16, found: u16 },
+
+    #[error("Malformed frame payload: {0}")]
+    Payload(#[from] bincode::Error),
+}
+
+pub fn encode_high_perf(metrics: &HighPerfMetrics) -> Vec<u8> {
+    // Plain structs, vectors and strings, which bincode always encodes
+    encode(HIGH_PERF_MAGIC, HIGH_PERF_VERSION, metrics).expect("HighPerfMetrics encodes with bincode")
+}
+
+// The frontend decodes frames itself (highPerfWire.ts); this checks the
+// encoding round-trips
+#[cfg_attr(not(test), allow(dead_code))]
+pub fn decode_high_perf(frame: &[u8]) -> Result<HighPerfMetrics, WireError> {
+    decode(HIGH_PERF_MAGIC, HIGH_PERF_VERSION, frame)
+}
+
+fn encode<T: serde::Serialize>(magic: [u8; 4], version: u16, value: &T) -> Result<Vec<u8>, WireError> {
+    let payload = bincode::serialize(value)?;
+    let mut frame = Vec::with_capacity(HEADER_LEN + payload.len());
+    frame.extend_from_slice(&magic);
+    frame.extend_from_slice(&version.to_le_bytes());
+    frame.extend_from_slice(&payload);
+    Ok(frame)
+}
+
+fn decode<T: serde::de::DeserializeOwned>(magic: [u8; 4], version: u16, frame: &[u8]) -> Result<T, WireError> {
+    if frame.len() < HEADER_LEN {
+        return Err(WireError::Truncated(frame.len()));
+    }
+
+    let found_magic = [frame[0], frame[1], frame[2], frame[3]];
+    if found_magic != magic {
+        return Err(WireError::BadMagic(found_magic));
+    }
+    let found = u16::from_le_bytes([frame[4], frame[5]]);
+    if found != version {
+        return Err(WireError::UnsupportedVersion { expected: version, found });
+    }
+
+    Ok(bincode::deserialize(&frame[HEADER_LEN..])?)
+}
@@ -0,0 +1,427 @@
+use async_trait::async_trait;
+use parking_lot::RwLock;
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use crate::core::{
+    BatteryMetrics, BatteryState, history_in_range, history_since, TimedHistory, trim_history, Metric, MetricType, MetricValue, Monitor, MonitorConfig, MonitorShared,
+    MonitorError, MonitorState, Result, SharedClock, SystemClock,
+};
+
+/// Builds battery metrics from the attributes of one `/sys/class/power_supply`
+/// entry; `read` returns the trimmed content of an attribute file.
+///
+/// Returns `None` for non-battery supplies (AC adapters, USB) and for
+/// peripheral batteries such as wireless mice.
+pub fn parse_power_supply(name: &str, read: impl Fn(&str) -> Option<String>) -> Option<BatteryMetrics> {
+    if read("type")? != "Battery" || read("scope").as_deref() == Some("Device") {
+        return None;
+    }
+    let number = |attr: &str| read(attr).and_then(|v| v.parse::<f64>().ok());
+
+    // Energy (µWh, with power in µW) or charge (µAh, with current in µA);
+    // the ratios below are unit-free either way
+    let (now, full, design, rate) = match number("energy_now") {
+        Some(now) => (Some(now), number("energy_full"), number("energy_full_design"), number("power_now")),
+        None => (number("charge_now"), number("charge_full"), number("charge_full_design"), number("current_now")),
+    };
+
+    let state: BatteryState = read("status").unwrap_or_default().parse().unwrap_or_default();
+    let charge_percent = number("capacity")
+        .or_else(|| Some(now? / full? * 100.0))
+        .unwrap_or(0.0) as f32;
+
+    // Some drivers report the rate as negative while discharging; the
+    // direction comes from `status`, so only the magnitude is used
+    let seconds_to = |remaining: Option<f64>| -> Option<u64> {
+        let rate = rate.map(f64::abs).filter(|r| *r > 0.0)?;
+        Some((remaining? / rate * 3600.0) as u64)
+    };
+    let (time_to_empty_seconds, time_to_full_seconds) = match state {
+        BatteryState::Discharging => (seconds_to(now), None),
+        BatteryState::Charging => (None, seconds_to(full.zip(now).map(|(full, now)| (full - now).max(0.0)))),
+        _ => (None, None),
+    };
+
+    Some(BatteryMetrics {
+        name: name.to_string(),
+        charge_percent,
+        state,
+        time_to_empty_seconds,
+        time_to_full_seconds,
+        cycle_count: number("cycle_count").map(|c| c as u32).filter(|c| *c > 0),
+        health_percent: full.zip(design.filter(|d| *d > 0.0)).map(|(full, design)| (full / design * 100.0) as f32),
+    })
+}
+
+/// Parses `pmset -g batt` output on macOS, e.g.
+/// ` -InternalBattery-0 (id=4653155)  85%; discharging; 4:12 remaining present: true`
+pub fn parse_pmset_batt(output: &str) -> Vec<BatteryMetrics> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim().strip_prefix('-')?;
+            let (name, rest) = line.split_once(char::is_whitespace)?;
+            let mut fields = rest.split(';').map(str::trim);
+
+            let charge_percent = fields
+                .next()?
+                .split_whitespace()
+                .last()?
+                .strip_suffix('%')?
+                .parse::<f32>()
+                .ok()?;
+            let state: BatteryState = match fields.next().unwrap_or_default() {
+                "AC attached" => BatteryState::NotCharging,
+                other => other.parse().unwrap_or_default(),
+            };
+
+            // "4:12 remaining", "(no estimate)" or "0:00 remaining"
+            let remaining = fields.next().and_then(|field| {
+                let (hours, minutes) = field.split_whitespace().next()?.split_once(':')?;
+                Some(hours.parse::<u64>().ok()? * 3600 + minutes.parse::<u64>().ok()? * 60)
+            });
+
+            Some(BatteryMetrics {
+                name: name.to_string(),
+                charge_percent,
+                state,
+                time_to_empty_seconds: remaining.filter(|_| state == BatteryState::Discharging),
+                time_to_full_seconds: remaining.filter(|_| state == BatteryState::Charging),
+                ..Default::default()
+            })
+        })
+        .collect()
+}
+
+/// Reads `(cycle_count, health_percent)` from `ioreg -rn AppleSmartBattery` output
+pub fn parse_ioreg_battery(output: &str) -> (Option<u32>, Option<f32>) {
+    let value = |key: &str| {
+        output.lines().find_map(|line| {
+            let (k, v) = line.trim().split_once(" = ")?;
+            (k.trim_matches('"') == key).then(|| v.trim().parse::<f64>().ok()).flatten()
+        })
+    };
+
+    // Newer macOS reports MaxCapacity as a percentage and the raw value separately
+    let full = value("AppleRawMaxCapacity").or_else(|| value("MaxCapacity").filter(|c| *c > 100.0));
+    let health = full
+        .zip(value("DesignCapacity").filter(|d| *d > 0.0))
+        .map(|(full, design)| (full / design * 100.0) as f32);
+
+    (value("CycleCount").map(|c| c as u32), health)
+}
+
+#[cfg(target_os = "windows")]
+mod power_status {
+    use crate::core::{BatteryMetrics, BatteryState};
+
+    #[repr(C)]
+    #[derive(Default)]
+    struct SystemPowerStatus {
+        ac_line_status: u8,
+        battery_flag: u8,
+        battery_life_percent: u8,
+        system_status_flag: u8,
+        battery_life_time: u32,
+        battery_full_life_time: u32,
+    }
+
+    const BATTERY_FLAG_CHARGING: u8 = 8;
+    const BATTERY_FLAG_NO_BATTERY: u8 = 128;
+    const BATTERY_FLAG_UNKNOWN: u8 = 255;
+    const UNKNOWN_PERCENT: u8 = 255;
+    const UNKNOWN_LIFE_TIME: u32 = u32::MAX;
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetSystemPowerStatus(status: *mut SystemPowerStatus) -> i32;
+    }
+
+    pub fn read() -> Option<BatteryMetrics> {
+        let mut status = SystemPowerStatus::default();
+        // SAFETY: the struct matches SYSTEM_POWER_STATUS and outlives the call
+        if unsafe { GetSystemPowerStatus(&mut status) } == 0 {
+            return None;
+        }
+        if status.battery_flag & BATTERY_FLAG_NO_BATTERY != 0 || status.battery_flag == BATTERY_FLAG_UNKNOWN {
+            return None;
+        }
+
+        let charge_percent = match status.battery_life_percent {
+            UNKNOWN_PERCENT => 0.0,
+            percent => percent as f32,
+        };
+        let state = if status.battery_flag & BATTERY_FLAG_CHARGING != 0 {
+            BatteryState::Charging
+        } else if status.ac_line_status == 1 {
+            if charge_percent >= 100.0 { BatteryState::Full } else { BatteryState::NotCharging }
+        } else {
+            BatteryState::Discharging
+        };
+
+        Some(BatteryMetrics {
+            name: "Battery".to_string(),
+            charge_percent,
+            state,
+            time_to_empty_seconds: Some(status.battery_life_time as u64)
+                .filter(|_| status.battery_life_time != UNKNOWN_LIFE_TIME && state == BatteryState::Discharging),
+            ..Default::default()
+        })
+    }
+}
+
+pub struct BatteryMonitor {
+    shared: MonitorShared,
+    // Each sample with the time it was collected
+    metrics_history: TimedHistory<Vec<BatteryMetrics>>,
+    clock: SharedClock,
+}
+
+impl Default for BatteryMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BatteryMonitor {
+    pub fn new() -> Self {
+        Self::with_clock(SystemClock::shared())
+    }
+
+    /// Timestamps samples and computes rates by `clock`
+    pub fn with_clock(clock: SharedClock) -> Self {
+        Self {
+            shared: MonitorShared::new(clock.now()),
+            metrics_history: Arc::new(RwLock::new(VecDeque::new())),
+            clock,
+        }
+    }
+
+    /// Reads every battery present; an empty list means the system has none
+    fn collect_battery_metrics(&self) -> Vec<BatteryMetrics> {
+        #[cfg(target_os = "linux")]
+        {
+            let Ok(entries) = std::fs::read_dir("/sys/class/power_supply") else {
+                return Vec::new();
+            };
+            let mut batteries: Vec<BatteryMetrics> = entries
+                .filter_map(|e| e.ok())
+                .filter_map(|entry| {
+                    let name = entry.file_name().to_string_lossy().to_string();
+                    let path = entry.path();
+                    parse_power_supply(&name, |attr| {
+                        std::fs::read_to_string(path.join(attr)).ok().map(|v| v.trim().to_string())
+                    })
+                })
+                .collect();
+            batteries.sort_by(|a, b| a.name.cmp(&b.name));
+            batteries
+        }
+
+        #[cfg(target_os = "windows")]
+        {
+            power_status::read().into_iter().collect()
+        }
+
+        #[cfg(target_os = "macos")]
+        {
+            use std::process::Command;
+
+            let Ok(output) = Command::new("pmset").args(["-g", "batt"]).output() else {
+                return Vec::new();
+            };
+            let mut batteries = parse_pmset_batt(&String::from_utf8_lossy(&output.stdout));
+
+            if let Ok(output) = Command::new("ioreg").args(["-rn", "AppleSmartBattery"]).output() {
+                let (cycle_count, health_percent) = parse_ioreg_battery(&String::from_utf8_lossy(&output.stdout));
+                // ioreg describes the internal battery, which pmset lists first
+                if let Some(battery) = batteries.first_mut() {
+                    battery.cycle_count = cycle_count;
+                    battery.health_percent = health_percent;
+                }
+            }
+            batteries
+        }
+
+        #[cfg(not(any(target_os = "linux", target_os = "windows", target_os = "macos")))]
+        {
+            Vec::new()
+        }
+    }
+
+    fn update_history(&self, metrics: Vec<BatteryMetrics>) {
+        let mut history = self.metrics_history.write();
+        let config = self.shared.config.read();
+
+        history.push_back((self.clock.now(), metrics));
+
+        // Remove old metrics based on retention policy
+        let max_entries = (config.retain_history_seconds * 1000 / config.interval_ms) as usize;
+        while history.len() > max_entries {
+            history.pop_front();
+        }
+
+        trim_history(&mut history, config.max_history_bytes);
+    }
+
+    fn battery_to_metrics(battery: &BatteryMetrics) -> Vec<Metric> {
+        let mut metrics = vec![
+            Metric::new(
+                MetricType::BatteryCharge,
+                MetricValue::Float(battery.charge_percent as f64),
+                "%",
+            ).with_tag("battery", &battery.name),
+            Metric::new(
+                MetricType::BatteryState,
+                MetricValue::String(battery.state.to_string()),
+                "",
+            ).with_tag("battery", &battery.name),
+        ];
+
+        if let Some(seconds) = battery.time_to_empty_seconds {
+            metrics.push(Metric::new(
+                MetricType::BatteryCharge,
+                MetricValue::Unsigned(seconds),
+                "s",
+            ).with_tag("battery", &battery.name).with_tag("type", "time_to_empty"));
+        }
+        if let Some(seconds) = battery.time_to_full_seconds {
+            metrics.push(Metric::new(
+                MetricType::BatteryCharge,
+                MetricValue::Unsigned(seconds),
+                "s",
+            ).with_tag("battery", &battery.name).with_tag("type", "time_to_full"));
+        }
+        if let Some(health) = battery.health_percent {
+            metrics.push(Metric::new(
+                MetricType::BatteryHealth,
+                MetricValue::Float(health as f64),
+                "%",
+            ).with_tag("battery", &battery.name));
+        }
+        if let Some(cycles) = battery.cycle_count {
+            metrics.push(Metric::new(
+                MetricType::BatteryHealth,
+                MetricValue::Unsigned(cycles as u64),
+                "cycles",
+            ).with_tag("battery", &battery.name).with_tag("type", "cycle_count"));
+        }
+
+        metrics
+    }
+}
+
+#[async_trait]
+impl Monitor for BatteryMonitor {
+    fn name(&self) -> &str {
+        "Battery Monitor"
+    }
+
+    fn state(&self) -> MonitorState {
+        self.shared.state.read().clone()
+    }
+
+    fn shared(&self) -> Option<&MonitorShared> {
+        Some(&self.shared)
+    }
+
+    async fn initialize(&mut self, config: MonitorConfig) -> Result<()> {
+        *self.shared.state.write() = MonitorState::Initializing;
+        *self.shared.config.write() = config;
+        *self.shared.state.write() = MonitorState::Running;
+        Ok(())
+    }
+
+
+    async fn start(&mut self) -> Result<()> {
+        match self.state() {
+            MonitorState::Running | MonitorState::Degraded { .. } => return Ok(()),
+            MonitorState::Uninitialized => {
+                return Err(MonitorError::NotInitialized);
+            }
+            _ => {}
+        }
+
+        *self.shared.state.write() = MonitorState::Running;
+        Ok(())
+    }
+
+    async fn stop(&mut self) -> Result<()> {
+        *self.shared.state.write() = MonitorState::Stopped;
+        Ok(())
+    }
+
+    async fn pause(&mut self) -> Result<()> {
+        *self.shared.state.write() = MonitorState::Paused;
+        Ok(())
+    }
+
+    async fn resume(&mut self) -> Result<()> {
+        *self.shared.state.write() = MonitorState::Running;
+        Ok(())
+    }
+
+    async fn collect(&mut self) -> Result<Vec<Metric>> {
+        if !self.state().is_collecting() {
+            return Err(MonitorError::NotInitialized);
+        }
+
+        let batteries = self.collect_battery_metrics();
+        let metrics = batteries.iter().flat_map(Self::battery_to_metrics).collect();
+        self.update_history(batteries);
+        *self.shared.last_update.write() = self.clock.now();
+
+        Ok(metrics)
+    }
+
+    async fn get_current_metrics(&self) -> Result<Vec<Metric>> {
+        let history = self.metrics_history.read();
+        Ok(history
+            .back()
+            .map(|(_, batteries)| batteries.iter().flat_map(Self::battery_to_metrics).collect())
+            .unwrap_or_default())
+    }
+
+    async fn get_historical_metrics(&self, duration_seconds: u64) -> Result<Vec<Metric>> {
+        let history = self.metrics_history.read();
+        let mut metrics = Vec::new();
+
+        for batteries in history_since(&history, duration_seconds, self.clock.now()) {
+            for battery in batteries {
+                metrics.push(Metric::new(
+                    MetricType::BatteryCharge,
+                    MetricValue::Float(battery.charge_percent as f64),
+                    "%",
+                ).with_tag("battery", &battery.name));
+            }
+        }
+
+        Ok(metrics)
+    }
+
+    async fn history_range(&self, start: SystemTime, end: SystemTime) -> Vec<(SystemTime, Vec<Metric>)> {
+        history_in_range(&self.metrics_history.read(), start, end, |batteries| {
+            batteries.iter().flat_map(Self::battery_to_metrics).collect()
+        })
+    }
+
+    fn supports_feature(&self, feature: &str) -> bool {
+        match feature {
+            "battery" => !self.collect_battery_metrics().is_empty(),
+            _ => false,
+        }
+    }
+
+    fn supported_metrics(&self) -> Vec<MetricType> {
+        let history = self.metrics_history.read();
+        let mut types: Vec<MetricType> = Vec::new();
+        for metric in history.back().into_iter().flat_map(|(_, batteries)| batteries).flat_map(Self::battery_to_metrics) {
+            if !types.contains(&metric.metric_type) {
+                types.push(metric.metric_type);
+            }
+        }
+        types
+    }
+}
@@ -2,14 +2,417 @@ use async_trait::async_trait;
 use parking_lot::RwLock;
 use std::collections::VecDeque;
 use std::sync::Arc;
-use std::time::{Duration, SystemTime};
+use std::time::{Duration, Instant, SystemTime};
 use sysinfo::{System, CpuRefreshKind, RefreshKind};
 
 use crate::core::{
-    CpuMetrics, Metric, MetricType, MetricValue, Monitor, MonitorConfig, MonitorError,
-    MonitorState, Result,
+    counter_rate, CpuCoreTimes, CpuMetrics, CrossNodeIrq, history_in_range, history_since, TimedHistory, trim_history, Metric, MetricType, MetricValue, Monitor, MonitorConfig, MonitorShared, MonitorError,
+    MonitorState, NumaNode, Result, SharedClock, SystemClock, ThermalStatus, ThrottleReason,
 };
 
+/// Reads the NUMA node to CPU mapping from `/sys/devices/system/node`.
+///
+/// Returns an empty list on non-Linux platforms or kernels without NUMA support.
+pub fn read_numa_topology() -> Vec<NumaNode> {
+    #[cfg(target_os = "linux")]
+    {
+        let mut nodes = Vec::new();
+        if let Ok(entries) = std::fs::read_dir("/sys/devices/system/node") {
+            for entry in entries.filter_map(|e| e.ok()) {
+                let file_name = entry.file_name();
+                let Some(id) = file_name
+                    .to_str()
+                    .and_then(|name| name.strip_prefix("node"))
+                    .and_then(|id| id.parse::<usize>().ok())
+                else {
+                    continue;
+                };
+
+                let path = entry.path();
+                let cpus = std::fs::read_to_string(path.join("cpulist"))
+                    .ok()
+                    .and_then(|list| crate::backend::process_monitor::parse_cpu_list(&list))
+                    .map(|cpus| cpus.into_iter().map(|cpu| cpu as usize).collect())
+                    .unwrap_or_default();
+                let memory_bytes = std::fs::read_to_string(path.join("meminfo"))
+                    .ok()
+                    .and_then(|meminfo| parse_node_mem_total(&meminfo))
+                    .unwrap_or(0);
+
+                nodes.push(NumaNode { id, cpus, memory_bytes });
+            }
+        }
+        nodes.sort_by_key(|node| node.id);
+        nodes
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        Vec::new()
+    }
+}
+
+/// Device interrupts handled on CPUs outside their device's NUMA node, from
+/// `/proc/interrupts` and `/proc/irq/<irq>/node`.
+///
+/// Returns an empty list on non-Linux platforms or single-node machines.
+#[cfg_attr(not(target_os = "linux"), allow(unused_variables))]
+pub fn read_cross_node_irqs(nodes: &[NumaNode]) -> Vec<CrossNodeIrq> {
+    #[cfg(target_os = "linux")]
+    {
+        if nodes.len() < 2 {
+            return Vec::new();
+        }
+        let Ok(interrupts) = std::fs::read_to_string("/proc/interrupts") else {
+            return Vec::new();
+        };
+        cross_node_irqs(&interrupts, nodes, |irq| {
+            std::fs::read_to_string(format!("/proc/irq/{}/node", irq)).ok()
+        })
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        Vec::new()
+    }
+}
+
+/// Correlates `/proc/interrupts` content with the node each CPU belongs to.
+///
+/// `read_node` returns the content of `/proc/irq/<irq>/node`; IRQs without
+/// one, or whose device has no node (`-1`), are skipped, as are the
+/// architecture's own rows such as `LOC` and `NMI`.
+pub fn cross_node_irqs(
+    interrupts: &str,
+    nodes: &[NumaNode],
+    read_node: impl Fn(&str) -> Option<String>,
+) -> Vec<CrossNodeIrq> {
+    let mut lines = interrupts.lines();
+    // Only online CPUs get a column: "           CPU0       CPU1       CPU4"
+    let cpus: Vec<usize> = lines
+        .next()
+        .unwrap_or_default()
+        .split_whitespace()
+        .filter_map(|column| column.strip_prefix("CPU")?.parse().ok())
+        .collect();
+    let node_of_cpu: std::collections::HashMap<usize, usize> = nodes
+        .iter()
+        .flat_map(|node| node.cpus.iter().map(move |&cpu| (cpu, node.id)))
+        .collect();
+
+    let mut irqs = Vec::new();
+    for line in lines {
+        // "  24:     120      98   IR-PCI-MSI 327680-edge      nvme0q1"
+        let mut fields = line.split_whitespace();
+        let Some(irq) = fields.next().and_then(|field| field.strip_suffix(':')) else {
+            continue;
+        };
+        if irq.parse::<u32>().is_err() {
+            continue;
+        }
+        let Some(node) = read_node(irq).and_then(|node| node.trim().parse::<usize>().ok()) else {
+            continue;
+        };
+
+        let counts: Vec<u64> = fields.by_ref().take(cpus.len()).filter_map(|count| count.parse().ok()).collect();
+        if counts.len() != cpus.len() {
+            continue;
+        }
+        let device = fields.last().unwrap_or_default().to_string();
+
+        let total_count = counts.iter().sum();
+        let remote_count = cpus
+            .iter()
+            .zip(&counts)
+            .filter(|(cpu, _)| node_of_cpu.get(cpu).is_some_and(|&cpu_node| cpu_node != node))
+            .map(|(_, &count)| count)
+            .sum();
+        if remote_count > 0 {
+            irqs.push(CrossNodeIrq { irq: irq.to_string(), device, node, total_count, remote_count });
+        }
+    }
+    irqs
+}
+
+/// Extracts `MemTotal` from a per-node meminfo file (`Node 0 MemTotal: 16318412 kB`)
+#[cfg(target_os = "linux")]
+fn parse_node_mem_total(meminfo: &str) -> Option<u64> {
+    meminfo
+        .lines()
+        .find(|line| line.contains("MemTotal:"))
+        .and_then(|line| line.split_whitespace().nth(3))
+        .and_then(|kb| kb.parse::<u64>().ok())
+        .map(|kb| kb * 1024)
+}
+
+/// Lowest passive trip point of a thermal zone, where the kernel starts
+/// throttling, or its lowest hot or critical one when it has no passive trip,
+/// in °C
+///
+/// `read` returns the contents of a file in the zone's directory.
+pub fn parse_thermal_zone_limit(read: impl Fn(&str) -> Option<String>) -> Option<f32> {
+    let mut passive: Option<f32> = None;
+    let mut critical: Option<f32> = None;
+
+    for index in 0.. {
+        let Some(kind) = read(&format!("trip_point_{}_type", index)) else {
+            break;
+        };
+        // Disabled trip points read as 0 or a negative sentinel
+        let Some(celsius) = read(&format!("trip_point_{}_temp", index))
+            .and_then(|temp| temp.trim().parse::<f32>().ok())
+            .map(|millidegrees| millidegrees / 1000.0)
+            .filter(|celsius| *celsius > 0.0)
+        else {
+            continue;
+        };
+
+        let slot = match kind.trim() {
+            "passive" => &mut passive,
+            "hot" | "critical" => &mut critical,
+            _ => continue,
+        };
+        *slot = Some(slot.map_or(celsius, |lowest| lowest.min(celsius)));
+    }
+
+    passive.or(critical)
+}
+
+/// Treats the CPU as throttling when the hardware counted throttle events
+/// since the last sample or the temperature reached the trip point
+pub fn cpu_thermal_status(temperature: f32, limit: f32, throttle_events: u64) -> ThermalStatus {
+    let throttling = throttle_events > 0 || temperature >= limit;
+    ThermalStatus {
+        throttling,
+        reason: if throttling { ThrottleReason::Thermal } else { ThrottleReason::None },
+        temperature,
+        limit,
+    }
+}
+
+/// Current temperature and throttling limit of the thermal zone that best
+/// matches the CPU package
+#[cfg(target_os = "linux")]
+fn read_cpu_thermal_zone() -> Option<(f32, f32)> {
+    let mut zones = Vec::new();
+    for entry in std::fs::read_dir("/sys/class/thermal").ok()?.filter_map(|e| e.ok()) {
+        if !entry.file_name().to_string_lossy().starts_with("thermal_zone") {
+            continue;
+        }
+        let path = entry.path();
+        let read = |file: &str| std::fs::read_to_string(path.join(file)).ok();
+
+        let kind = read("type").unwrap_or_default().trim().to_string();
+        let Some(temperature) = read("temp").and_then(|t| t.trim().parse::<f32>().ok()) else {
+            continue;
+        };
+        if let Some(limit) = parse_thermal_zone_limit(read) {
+            zones.push((kind, temperature / 1000.0, limit));
+        }
+    }
+
+    // Intel's package sensor first, then anything naming the CPU, then
+    // whatever zone has a trip point, such as the ACPI one
+    let rank = |kind: &str| match kind {
+        "x86_pkg_temp" => 0,
+        kind if kind.contains("cpu") => 1,
+        _ => 2,
+    };
+    zones.sort_by_key(|(kind, _, _)| rank(kind));
+    zones.into_iter().next().map(|(_, temperature, limit)| (temperature, limit))
+}
+
+/// Sum of the core and package throttle counters Intel CPUs expose under
+/// `/sys/devices/system/cpu/cpu*/thermal_throttle`, which the kernel bumps
+/// on each thermal interrupt raised from the throttle status MSRs
+#[cfg(target_os = "linux")]
+fn read_throttle_count() -> Option<u64> {
+    let mut total = None;
+    for entry in std::fs::read_dir("/sys/devices/system/cpu").ok()?.filter_map(|e| e.ok()) {
+        let dir = entry.path().join("thermal_throttle");
+        for counter in ["core_throttle_count", "package_throttle_count"] {
+            if let Some(count) = std::fs::read_to_string(dir.join(counter))
+                .ok()
+                .and_then(|c| c.trim().parse::<u64>().ok())
+            {
+                total = Some(total.unwrap_or(0) + count);
+            }
+        }
+    }
+    total
+}
+
+/// Cumulative context switches and interrupts from `/proc/stat` content: the
+/// `ctxt` line, and the total that leads the `intr` line
+pub fn parse_proc_stat_counters(content: &str) -> (Option<u64>, Option<u64>) {
+    let counter = |name: &str| {
+        content.lines().find_map(|line| {
+            let mut fields = line.split_whitespace();
+            if fields.next()? != name {
+                return None;
+            }
+            fields.next()?.parse::<u64>().ok()
+        })
+    };
+    (counter("ctxt"), counter("intr"))
+}
+
+/// Cumulative time a logical core has spent in each state, in the
+/// platform's ticks (jiffies on Linux, 100 ns units on Windows)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CpuTimeCounters {
+    pub user: u64,
+    pub nice: u64,
+    pub system: u64,
+    pub idle: u64,
+    pub iowait: u64,
+    pub irq: u64,
+    pub softirq: u64,
+    pub steal: u64,
+}
+
+impl CpuTimeCounters {
+    /// Shares of the time that passed since `previous`; all 0 when none did.
+    /// Counters that went backwards, as `iowait` can, count as no time.
+    pub fn share_since(&self, previous: &CpuTimeCounters) -> CpuCoreTimes {
+        // Ticks spent in a state, i.e. its rate over a single tick
+        let delta = |current: u64, previous: u64| counter_rate(current, previous, 1.0);
+        let user = delta(self.user, previous.user) + delta(self.nice, previous.nice);
+        let system = delta(self.system, previous.system);
+        let idle = delta(self.idle, previous.idle);
+        let iowait = delta(self.iowait, previous.iowait);
+        let irq = delta(self.irq, previous.irq);
+        let softirq = delta(self.softirq, previous.softirq);
+        let steal = delta(self.steal, previous.steal);
+
+        let total = user + system + idle + iowait + irq + softirq + steal;
+        if total == 0.0 {
+            return CpuCoreTimes::default();
+        }
+        let percent = |ticks: f64| (ticks / total * 100.0) as f32;
+        CpuCoreTimes {
+            user: percent(user),
+            system: percent(system),
+            idle: percent(idle),
+            iowait: percent(iowait),
+            irq: percent(irq),
+            softirq: percent(softirq),
+            steal: percent(steal),
+        }
+    }
+}
+
+/// Per-core counters from `/proc/stat` content, in the order of the `cpuN`
+/// lines; the aggregate `cpu` line is skipped. `guest` time is already
+/// included in `user`, so it isn't read.
+pub fn parse_proc_stat_cores(content: &str) -> Vec<CpuTimeCounters> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let label = fields.next()?;
+            if !label.strip_prefix("cpu")?.chars().next()?.is_ascii_digit() {
+                return None;
+            }
+
+            // Older kernels lack the trailing fields
+            let mut next = || fields.next().and_then(|f| f.parse::<u64>().ok()).unwrap_or(0);
+            Some(CpuTimeCounters {
+                user: next(),
+                nice: next(),
+                system: next(),
+                idle: next(),
+                iowait: next(),
+                irq: next(),
+                softirq: next(),
+                steal: next(),
+            })
+        })
+        .collect()
+}
+
+/// Per-core counters from the raw `\Processor(*)` time counters, each
+/// given as `(instance, value)` pairs in 100 ns units, ordered by core; the
+/// `_Total` instance is skipped.
+///
+/// Privileged time includes interrupt and DPC time, which are split out
+/// into `irq` and `softirq`.
+pub fn processor_core_counters(
+    user: &[(String, u64)],
+    privileged: &[(String, u64)],
+    idle: &[(String, u64)],
+    interrupt: &[(String, u64)],
+    dpc: &[(String, u64)],
+) -> Vec<CpuTimeCounters> {
+    let value = |counter: &[(String, u64)], instance: &str| {
+        counter.iter().find(|(name, _)| name == instance).map_or(0, |(_, value)| *value)
+    };
+
+    let mut cores: Vec<(usize, CpuTimeCounters)> = user
+        .iter()
+        .filter_map(|(instance, user)| {
+            let core = instance.parse::<usize>().ok()?;
+            let (irq, softirq) = (value(interrupt, instance), value(dpc, instance));
+            Some((
+                core,
+                CpuTimeCounters {
+                    user: *user,
+                    system: value(privileged, instance).saturating_sub(irq + softirq),
+                    idle: value(idle, instance),
+                    irq,
+                    softirq,
+                    ..CpuTimeCounters::default()
+                },
+            ))
+        })
+        .collect();
+    cores.sort_by_key(|(core, _)| *core);
+    cores.into_iter().map(|(_, counters)| counters).collect()
+}
+
+/// Raw counters the Windows collection reads in one PDH query: the
+/// `\Processor(*)` times in `processor_core_counters` order, then the
+/// context switch and interrupt totals. The "/sec" counters are running
+/// totals when read raw; rates are taken against the previous sample like
+/// on Linux.
+#[cfg(target_os = "windows")]
+const WINDOWS_COUNTERS: [&str; 7] = [
+    "\\Processor(*)\\% User Time",
+    "\\Processor(*)\\% Privileged Time",
+    "\\Processor(*)\\% Idle Time",
+    "\\Processor(*)\\% Interrupt Time",
+    "\\Processor(*)\\% DPC Time",
+    "\\System\\Context Switches/sec",
+    "\\Processor(_Total)\\Interrupts/sec",
+];
+
+/// Cumulative counters read once per collection, where the platform has them
+#[derive(Default)]
+struct CpuCounters {
+    /// Per-core time counters
+    cores: Option<Vec<CpuTimeCounters>>,
+    /// Context switches since boot
+    context_switches: Option<u64>,
+    /// Interrupts since boot
+    interrupts: Option<u64>,
+}
+
+/// Cumulative scheduler counters and their rates since the last collection
+struct CounterRates {
+    context_switches: Option<u64>,
+    interrupts: Option<u64>,
+    context_switches_per_sec: f64,
+    interrupts_per_sec: f64,
+}
+
+/// Scheduler counters at the last collection, to turn them into rates
+#[derive(Debug, Clone, Copy)]
+struct CounterSample {
+    context_switches: Option<u64>,
+    interrupts: Option<u64>,
+    taken_at: Instant,
+}
+
 /// CPU monitoring implementation
 /// 
 /// Monitors CPU usage, frequency, temperature, load average, and per-core metrics.
@@ -18,11 +421,12 @@ use crate::core::{
 /// 
 /// - Overall CPU usage percentage
 /// - Per-core usage tracking
+/// - Per-core user/system/idle/iowait breakdown (Linux; partial on Windows)
 /// - CPU frequency monitoring
 /// - Temperature sensing (Linux only)
 /// - Load average (1, 5, 15 minutes)
 /// - Process count tracking
-/// - Context switches and interrupts (Linux only)
+/// - Context switch and interrupt rates (Linux and Windows)
 /// 
 /// # Example
 /// 
@@ -42,22 +446,47 @@ use crate::core::{
 /// # }
 /// ```
 pub struct CpuMonitor {
-    state: Arc<RwLock<MonitorState>>,
-    config: Arc<RwLock<MonitorConfig>>,
+    shared: MonitorShared,
     system: Arc<RwLock<System>>,
-    metrics_history: Arc<RwLock<VecDeque<CpuMetrics>>>,
-    last_update: Arc<RwLock<SystemTime>>,
+    // Each sample with the time it was collected
+    metrics_history: TimedHistory<CpuMetrics>,
+    /// Throttle counter total at the last collection, to detect new events
+    #[cfg(target_os = "linux")]
+    throttle_count: Arc<RwLock<Option<u64>>>,
+    scheduler_counters: Arc<RwLock<Option<CounterSample>>>,
+    /// Per-core time counters at the last collection
+    core_counters: Arc<RwLock<Vec<CpuTimeCounters>>>,
+    // Performance counter query, opened on first use
+    #[cfg(target_os = "windows")]
+    windows_counters: Arc<RwLock<Option<super::windows_pdh::RawCounters>>>,
+    clock: SharedClock,
+}
+
+impl Default for CpuMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl CpuMonitor {
     /// Creates a new CPU monitor instance
     pub fn new() -> Self {
+        Self::with_clock(SystemClock::shared())
+    }
+
+    /// Timestamps samples and computes rates by `clock`
+    pub fn with_clock(clock: SharedClock) -> Self {
         Self {
-            state: Arc::new(RwLock::new(MonitorState::Uninitialized)),
-            config: Arc::new(RwLock::new(MonitorConfig::default())),
+            shared: MonitorShared::new(clock.now()),
             system: Arc::new(RwLock::new(System::new_with_specifics(RefreshKind::everything()))),
             metrics_history: Arc::new(RwLock::new(VecDeque::new())),
-            last_update: Arc::new(RwLock::new(SystemTime::now())),
+            #[cfg(target_os = "linux")]
+            throttle_count: Arc::new(RwLock::new(None)),
+            scheduler_counters: Arc::new(RwLock::new(None)),
+            core_counters: Arc::new(RwLock::new(Vec::new())),
+            #[cfg(target_os = "windows")]
+            windows_counters: Arc::new(RwLock::new(None)),
+            clock,
         }
     }
 
@@ -70,6 +499,8 @@ impl CpuMonitor {
         let cpus = system.cpus();
         
         let per_core_usage: Vec<f32> = cpus.iter().map(|cpu| cpu.cpu_usage()).collect();
+        // sysinfo reports frequencies in MHz
+        let per_core_frequency_mhz: Vec<u64> = cpus.iter().map(|cpu| cpu.frequency()).collect();
         
         let load_avg = System::load_average();
         let load_average = [load_avg.one as f32, load_avg.five as f32, load_avg.fifteen as f32];
@@ -85,71 +516,225 @@ impl CpuMonitor {
             sensors.read_cpu_temperature().ok().flatten()
         };
 
+        let raw = self.read_cpu_counters();
+        let counters = self.read_counter_rates(raw.context_switches, raw.interrupts);
+        let per_core_times = self.read_core_times(&per_core_usage, raw.cores);
+
         Ok(CpuMetrics {
             usage_percent: global_cpu.cpu_usage(),
-            frequency_mhz: global_cpu.frequency() / 1_000_000, // Convert from Hz to MHz
+            frequency_mhz: per_core_frequency_mhz.iter().copied().max().unwrap_or(0),
+            per_core_frequency_mhz,
             temperature_celsius,
             load_average,
             per_core_usage,
+            per_core_times,
             processes_running,
             processes_total: processes.len(),
-            context_switches: self.read_context_switches().unwrap_or(0),
-            interrupts: self.read_interrupts().unwrap_or(0),
+            context_switches: counters.context_switches.unwrap_or(0),
+            interrupts: counters.interrupts.unwrap_or(0),
+            context_switches_per_sec: counters.context_switches_per_sec,
+            interrupts_per_sec: counters.interrupts_per_sec,
+            cpu_thermal: self.read_cpu_thermal(),
         })
     }
 
-    fn read_context_switches(&self) -> Option<u64> {
+    fn read_cpu_thermal(&self) -> Option<ThermalStatus> {
         #[cfg(target_os = "linux")]
         {
-            if let Ok(stat) = std::fs::read_to_string("/proc/stat") {
-                for line in stat.lines() {
-                    if line.starts_with("ctxt") {
-                        let parts: Vec<&str> = line.split_whitespace().collect();
-                        if parts.len() >= 2 {
-                            return parts[1].parse::<u64>().ok();
-                        }
-                    }
-                }
+            if super::environment::is_wsl() {
+                return None;
             }
+
+            let count = read_throttle_count();
+            let previous = std::mem::replace(&mut *self.throttle_count.write(), count);
+            let events = match (count, previous) {
+                (Some(count), Some(previous)) => count.saturating_sub(previous),
+                _ => 0,
+            };
+
+            let (temperature, limit) = read_cpu_thermal_zone()?;
+            Some(cpu_thermal_status(temperature, limit, events))
+        }
+
+        #[cfg(not(target_os = "linux"))]
+        {
+            None
         }
-        None
     }
 
-    fn read_interrupts(&self) -> Option<u64> {
+    fn read_cpu_counters(&self) -> CpuCounters {
         #[cfg(target_os = "linux")]
         {
-            if let Ok(stat) = std::fs::read_to_string("/proc/stat") {
-                for line in stat.lines() {
-                    if line.starts_with("intr") {
-                        let parts: Vec<&str> = line.split_whitespace().collect();
-                        if parts.len() >= 2 {
-                            return parts[1].parse::<u64>().ok();
-                        }
-                    }
-                }
+            let Ok(stat) = std::fs::read_to_string("/proc/stat") else {
+                return CpuCounters::default();
+            };
+            let (context_switches, interrupts) = parse_proc_stat_counters(&stat);
+            CpuCounters {
+                cores: Some(parse_proc_stat_cores(&stat)).filter(|cores| !cores.is_empty()),
+                context_switches,
+                interrupts,
+            }
+        }
+
+        #[cfg(target_os = "windows")]
+        {
+            let mut counters = self.windows_counters.write();
+            if counters.is_none() {
+                *counters = super::windows_pdh::RawCounters::open(&WINDOWS_COUNTERS);
+            }
+            let Some(counters) = counters.as_ref().filter(|counters| counters.collect()) else {
+                return CpuCounters::default();
+            };
+
+            let cores = processor_core_counters(
+                &counters.instances(0),
+                &counters.instances(1),
+                &counters.instances(2),
+                &counters.instances(3),
+                &counters.instances(4),
+            );
+            CpuCounters {
+                cores: Some(cores).filter(|cores| !cores.is_empty()),
+                context_switches: counters.value(5),
+                interrupts: counters.value(6),
+            }
+        }
+
+        #[cfg(not(any(target_os = "linux", target_os = "windows")))]
+        {
+            CpuCounters::default()
+        }
+    }
+
+    /// The first collection covers the time since boot
+    fn read_core_times(&self, per_core_usage: &[f32], cores: Option<Vec<CpuTimeCounters>>) -> Vec<CpuCoreTimes> {
+        let Some(current) = cores else {
+            return per_core_usage
+                .iter()
+                .map(|&usage| CpuCoreTimes { user: usage, idle: 100.0 - usage, ..CpuCoreTimes::default() })
+                .collect();
+        };
+
+        let previous = std::mem::replace(&mut *self.core_counters.write(), current.clone());
+        current
+            .iter()
+            .enumerate()
+            .map(|(core, counters)| counters.share_since(&previous.get(core).copied().unwrap_or_default()))
+            .collect()
+    }
+
+    fn read_counter_rates(&self, context_switches: Option<u64>, interrupts: Option<u64>) -> CounterRates {
+        let sample = CounterSample { context_switches, interrupts, taken_at: self.clock.instant() };
+        let previous = self.scheduler_counters.write().replace(sample);
+
+        let rate = |current: Option<u64>, previous: Option<u64>, elapsed: f64| match (current, previous) {
+            (Some(current), Some(previous)) => counter_rate(current, previous, elapsed),
+            _ => 0.0,
+        };
+        let (context_switches_per_sec, interrupts_per_sec) = match previous {
+            Some(previous) => {
+                let elapsed = sample.taken_at.duration_since(previous.taken_at).as_secs_f64();
+                (
+                    rate(context_switches, previous.context_switches, elapsed),
+                    rate(interrupts, previous.interrupts, elapsed),
+                )
+            }
+            None => (0.0, 0.0),
+        };
+
+        CounterRates { context_switches, interrupts, context_switches_per_sec, interrupts_per_sec }
+    }
+
+    /// The metrics `collect()` reports for one sample
+    fn cpu_to_metrics(cpu_metrics: &CpuMetrics) -> Vec<Metric> {
+        let mut metrics = vec![
+            Metric::new(
+                MetricType::CpuUsage,
+                MetricValue::percentage(cpu_metrics.usage_percent as f64),
+                "%",
+            ),
+            Metric::new(
+                MetricType::CpuFrequency,
+                MetricValue::Unsigned(cpu_metrics.frequency_mhz),
+                "MHz",
+            ),
+            Metric::new(
+                MetricType::ProcessCount,
+                MetricValue::Integer(cpu_metrics.processes_total as i64),
+                "count",
+            ).with_tag("type", "total"),
+            Metric::new(
+                MetricType::ProcessCount,
+                MetricValue::Integer(cpu_metrics.processes_running as i64),
+                "count",
+            ).with_tag("type", "running"),
+        ];
+        
+        // Add per-core usage metrics
+        for (i, usage) in cpu_metrics.per_core_usage.iter().enumerate() {
+            metrics.push(Metric::new(
+                MetricType::CpuUsage,
+                MetricValue::percentage(*usage as f64),
+                "%",
+            ).with_tag("core", i.to_string()));
+        }
+        
+        for (i, frequency) in cpu_metrics.per_core_frequency_mhz.iter().enumerate() {
+            metrics.push(Metric::new(
+                MetricType::CpuFrequency,
+                MetricValue::Unsigned(*frequency),
+                "MHz",
+            ).with_tag("core", i.to_string()));
+        }
+        
+        for (i, times) in cpu_metrics.per_core_times.iter().enumerate() {
+            for (state, percent) in times.states() {
+                metrics.push(Metric::new(
+                    MetricType::CpuTime,
+                    MetricValue::percentage(percent as f64),
+                    "%",
+                ).with_tag("core", i.to_string()).with_tag("state", state));
             }
         }
-        None
+        
+        if let Some(thermal) = &cpu_metrics.cpu_thermal {
+            metrics.push(thermal.to_metric());
+        }
+        
+        // Counters that can't be read on this platform stay at 0 and aren't reported
+        for (metric_type, total, rate) in [
+            (MetricType::ContextSwitches, cpu_metrics.context_switches, cpu_metrics.context_switches_per_sec),
+            (MetricType::Interrupts, cpu_metrics.interrupts, cpu_metrics.interrupts_per_sec),
+        ] {
+            if total > 0 {
+                metrics.push(Metric::new(metric_type, MetricValue::Unsigned(total), "count").with_tag("type", "total"));
+                metrics.push(Metric::new(metric_type, MetricValue::Float(rate), "/s").with_tag("type", "rate"));
+            }
+        }
+        
+        metrics
     }
 
     fn update_history(&self, metrics: CpuMetrics) {
         let mut history = self.metrics_history.write();
-        let config = self.config.read();
+        let config = self.shared.config.read();
         
-        history.push_back(metrics);
+        history.push_back((self.clock.now(), metrics));
         
         // Remove old metrics based on retention policy
-        let _cutoff_time = SystemTime::now() - Duration::from_secs(config.retain_history_seconds);
-        let now = SystemTime::now();
+        let _cutoff_time = self.clock.now() - Duration::from_secs(config.retain_history_seconds);
+        let now = self.clock.now();
         
-        while history.len() > 0 {
-            let age_secs = now.duration_since(*self.last_update.read()).unwrap_or_default().as_secs();
+        while !history.is_empty() {
+            let age_secs = now.duration_since(*self.shared.last_update.read()).unwrap_or_default().as_secs();
             if age_secs > config.retain_history_seconds {
                 history.pop_front();
             } else {
                 break;
             }
         }
+
+        trim_history(&mut history, config.max_history_bytes);
     }
 }
 
@@ -160,109 +745,77 @@ impl Monitor for CpuMonitor {
     }
 
     fn state(&self) -> MonitorState {
-        *self.state.read()
+        self.shared.state.read().clone()
+    }
+
+    fn shared(&self) -> Option<&MonitorShared> {
+        Some(&self.shared)
     }
 
     async fn initialize(&mut self, config: MonitorConfig) -> Result<()> {
-        *self.state.write() = MonitorState::Initializing;
-        *self.config.write() = config;
+        *self.shared.state.write() = MonitorState::Initializing;
+        *self.shared.config.write() = config;
         
         // Initialize system info
         let mut system = self.system.write();
         system.refresh_all();
         
-        *self.state.write() = MonitorState::Running;
+        *self.shared.state.write() = MonitorState::Running;
         Ok(())
     }
 
+
     async fn start(&mut self) -> Result<()> {
         match self.state() {
-            MonitorState::Running => return Ok(()),
+            MonitorState::Running | MonitorState::Degraded { .. } => return Ok(()),
             MonitorState::Uninitialized => {
                 return Err(MonitorError::NotInitialized);
             }
             _ => {}
         }
         
-        *self.state.write() = MonitorState::Running;
+        *self.shared.state.write() = MonitorState::Running;
         Ok(())
     }
 
     async fn stop(&mut self) -> Result<()> {
-        *self.state.write() = MonitorState::Stopped;
+        *self.shared.state.write() = MonitorState::Stopped;
         Ok(())
     }
 
     async fn pause(&mut self) -> Result<()> {
-        *self.state.write() = MonitorState::Paused;
+        *self.shared.state.write() = MonitorState::Paused;
         Ok(())
     }
 
     async fn resume(&mut self) -> Result<()> {
-        *self.state.write() = MonitorState::Running;
+        *self.shared.state.write() = MonitorState::Running;
         Ok(())
     }
 
     async fn collect(&mut self) -> Result<Vec<Metric>> {
-        if self.state() != MonitorState::Running {
+        if !self.state().is_collecting() {
             return Err(MonitorError::NotInitialized);
         }
 
-        let cpu_metrics = self.collect_cpu_metrics()?;
+        let result = self.collect_cpu_metrics();
+        self.shared.state.write().record_collection(&result);
+        let cpu_metrics = result?;
         self.update_history(cpu_metrics.clone());
-        *self.last_update.write() = SystemTime::now();
+        *self.shared.last_update.write() = self.clock.now();
 
-        let mut metrics = Vec::new();
-        
-        metrics.push(Metric::new(
-            MetricType::CpuUsage,
-            MetricValue::Float(cpu_metrics.usage_percent as f64),
-            "%",
-        ));
-        
-        metrics.push(Metric::new(
-            MetricType::CpuFrequency,
-            MetricValue::Unsigned(cpu_metrics.frequency_mhz),
-            "MHz",
-        ));
-        
-        metrics.push(Metric::new(
-            MetricType::ProcessCount,
-            MetricValue::Integer(cpu_metrics.processes_total as i64),
-            "count",
-        ).with_tag("type", "total"));
-        
-        metrics.push(Metric::new(
-            MetricType::ProcessCount,
-            MetricValue::Integer(cpu_metrics.processes_running as i64),
-            "count",
-        ).with_tag("type", "running"));
-        
-        // Add per-core usage metrics
-        for (i, usage) in cpu_metrics.per_core_usage.iter().enumerate() {
-            metrics.push(Metric::new(
-                MetricType::CpuUsage,
-                MetricValue::Float(*usage as f64),
-                "%",
-            ).with_tag("core", i.to_string()));
-        }
-        
-        Ok(metrics)
+        Ok(Self::cpu_to_metrics(&cpu_metrics))
     }
 
     async fn get_current_metrics(&self) -> Result<Vec<Metric>> {
         let history = self.metrics_history.read();
         
-        if let Some(latest) = history.back() {
-            let mut metrics = Vec::new();
-            
-            metrics.push(Metric::new(
+        if let Some((_, latest)) = history.back() {
+            Ok(vec![Metric::new(
                 MetricType::CpuUsage,
-                MetricValue::Float(latest.usage_percent as f64),
+                MetricValue::percentage(latest.usage_percent as f64),
                 "%",
-            ));
-            
-            Ok(metrics)
+            )])
         } else {
             Ok(Vec::new())
         }
@@ -270,14 +823,12 @@ impl Monitor for CpuMonitor {
 
     async fn get_historical_metrics(&self, duration_seconds: u64) -> Result<Vec<Metric>> {
         let history = self.metrics_history.read();
-        let _cutoff_time = SystemTime::now() - Duration::from_secs(duration_seconds);
-        
         let mut metrics = Vec::new();
         
-        for cpu_metrics in history.iter() {
+        for cpu_metrics in history_since(&history, duration_seconds, self.clock.now()) {
             metrics.push(Metric::new(
                 MetricType::CpuUsage,
-                MetricValue::Float(cpu_metrics.usage_percent as f64),
+                MetricValue::percentage(cpu_metrics.usage_percent as f64),
                 "%",
             ));
         }
@@ -285,7 +836,41 @@ impl Monitor for CpuMonitor {
         Ok(metrics)
     }
 
+    async fn history_range(&self, start: SystemTime, end: SystemTime) -> Vec<(SystemTime, Vec<Metric>)> {
+        history_in_range(&self.metrics_history.read(), start, end, Self::cpu_to_metrics)
+    }
+
     fn supports_feature(&self, feature: &str) -> bool {
-        matches!(feature, "cpu_usage" | "cpu_frequency" | "per_core_usage" | "process_count")
+        match feature {
+            "cpu_usage" | "cpu_frequency" | "per_core_usage" | "process_count" => true,
+            "context_switches" | "interrupts" => cfg!(any(target_os = "linux", target_os = "windows")),
+            "cpu_temperature" => !super::environment::is_wsl(),
+            _ => false,
+        }
+    }
+
+    fn supported_metrics(&self) -> Vec<MetricType> {
+        let mut types = vec![MetricType::CpuUsage, MetricType::CpuFrequency, MetricType::CpuTime, MetricType::ProcessCount];
+
+        let history = self.metrics_history.read();
+        match history.back() {
+            Some((_, latest)) => {
+                if latest.cpu_thermal.is_some() {
+                    types.push(MetricType::ThermalThrottling);
+                }
+                if latest.context_switches > 0 {
+                    types.push(MetricType::ContextSwitches);
+                }
+                if latest.interrupts > 0 {
+                    types.push(MetricType::Interrupts);
+                }
+            }
+            None if cfg!(any(target_os = "linux", target_os = "windows")) => {
+                types.extend([MetricType::ContextSwitches, MetricType::Interrupts]);
+            }
+            None => {}
+        }
+
+        types
     }
 }
@@ -0,0 +1,31 @@
+use std::sync::OnceLock;
+
+use crate::core::RuntimeEnvironment;
+
+static ENVIRONMENT: OnceLock<RuntimeEnvironment> = OnceLock::new();
+
+/// Detects the runtime environment once and caches the result
+pub fn detect_environment() -> RuntimeEnvironment {
+    *ENVIRONMENT.get_or_init(|| {
+        #[cfg(target_os = "linux")]
+        {
+            if let Ok(release) = std::fs::read_to_string("/proc/sys/kernel/osrelease") {
+                if is_wsl_release(&release) {
+                    return RuntimeEnvironment::Wsl;
+                }
+            }
+        }
+        RuntimeEnvironment::Native
+    })
+}
+
+/// Returns true when running under Windows Subsystem for Linux
+pub fn is_wsl() -> bool {
+    detect_environment() == RuntimeEnvironment::Wsl
+}
+
+/// WSL kernels carry `microsoft` in their release string,
+/// e.g. `5.15.90.1-microsoft-standard-WSL2` or `4.4.0-19041-Microsoft`
+pub fn is_wsl_release(release: &str) -> bool {
+    release.to_lowercase().contains("microsoft")
+}
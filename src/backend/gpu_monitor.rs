@@ -1,25 +1,274 @@
 use async_trait::async_trait;
 use parking_lot::RwLock;
-use std::collections::VecDeque;
+use std::collections::{HashMap, VecDeque};
 use std::sync::Arc;
-use std::time::SystemTime;
+use std::time::{Duration, Instant, SystemTime};
 
 use crate::core::{
-    GpuMetrics, Metric, MetricType, MetricValue, Monitor, MonitorConfig, MonitorError,
-    MonitorState, Result,
+    GpuMetrics, history_in_range, history_since, TimedHistory, trim_history, Metric, MetricType, MetricValue, Monitor, MonitorHealth, MonitorConfig, MonitorShared, MonitorError,
+    MonitorState, Result, SharedClock, SystemClock,
 };
 
 #[cfg(feature = "nvidia")]
-use nvml_wrapper::Nvml;
+use super::nvidia_mig::MigApi;
+#[cfg(feature = "nvidia")]
+use crate::core::{GpuProcessInfo, ThermalStatus, ThrottleReason};
+#[cfg(feature = "nvidia")]
+use nvml_wrapper::{
+    bitmasks::device::ThrottleReasons,
+    enum_wrappers::device::TemperatureThreshold,
+    enums::device::UsedGpuMemory,
+    error::NvmlError,
+    struct_wrappers::device::{ProcessInfo, ProcessUtilizationSample},
+    Nvml,
+};
+
+/// Cumulative busy nanoseconds per engine and when they were read
+#[cfg(target_os = "linux")]
+type EngineBusySample = (HashMap<String, u64>, std::time::Instant);
 
 pub struct GpuMonitor {
-    state: Arc<RwLock<MonitorState>>,
-    config: Arc<RwLock<MonitorConfig>>,
-    metrics_history: Arc<RwLock<VecDeque<Vec<GpuMetrics>>>>,
-    last_update: Arc<RwLock<SystemTime>>,
+    shared: MonitorShared,
+    // Each sample with the time it was collected
+    metrics_history: TimedHistory<Vec<GpuMetrics>>,
     #[cfg(feature = "nvidia")]
     nvml: Arc<RwLock<Option<Nvml>>>,
+    #[cfg(feature = "nvidia")]
+    nvml_recovery: Arc<RwLock<NvmlRecovery>>,
+    // Loaded alongside NVML; None when the driver predates MIG
+    #[cfg(feature = "nvidia")]
+    mig: Arc<RwLock<Option<MigApi>>>,
     gpu_type: Arc<RwLock<GpuType>>,
+    gpu_redetection: Arc<RwLock<GpuRedetection>>,
+    // Previous engine busy counters per Intel card, to turn them into a rate
+    #[cfg(target_os = "linux")]
+    intel_engine_busy: Arc<RwLock<HashMap<std::path::PathBuf, EngineBusySample>>>,
+    // Recent gpu_busy_percent readings per AMD card, for smoothing
+    #[cfg(target_os = "linux")]
+    amd_busy_history: Arc<RwLock<HashMap<std::path::PathBuf, VecDeque<f32>>>>,
+    busy_smoothing_samples: Arc<RwLock<usize>>,
+    // Performance counter query for AMD/Intel usage, opened on first use
+    #[cfg(target_os = "windows")]
+    windows_counters: Arc<RwLock<Option<crate::backend::windows_gpu::GpuCounters>>>,
+    // Why part of the last collection came back empty, e.g. missing privileges
+    collection_warning: Arc<RwLock<Option<String>>>,
+    clock: SharedClock,
+}
+
+/// Default number of readings averaged into the reported AMD usage
+pub const DEFAULT_BUSY_SMOOTHING_SAMPLES: usize = 4;
+
+/// Adds `sample` to `history`, keeping the last `window` readings, and
+/// returns their mean
+pub fn smoothed_usage(history: &mut VecDeque<f32>, sample: f32, window: usize) -> f32 {
+    history.push_back(sample);
+    while history.len() > window.max(1) {
+        history.pop_front();
+    }
+    history.iter().sum::<f32>() / history.len() as f32
+}
+
+/// The most specific cause among the reasons NVML gives for holding clocks
+/// down; idling and user or application clock settings don't count
+#[cfg(feature = "nvidia")]
+pub fn throttle_reason(reasons: ThrottleReasons) -> ThrottleReason {
+    if reasons.intersects(ThrottleReasons::SW_THERMAL_SLOWDOWN | ThrottleReasons::HW_THERMAL_SLOWDOWN) {
+        ThrottleReason::Thermal
+    } else if reasons.intersects(ThrottleReasons::SW_POWER_CAP | ThrottleReasons::HW_POWER_BRAKE_SLOWDOWN) {
+        ThrottleReason::Power
+    } else if reasons.intersects(ThrottleReasons::HW_SLOWDOWN | ThrottleReasons::SYNC_BOOST) {
+        ThrottleReason::Other
+    } else {
+        ThrottleReason::None
+    }
+}
+
+/// Combines NVML's compute and graphics process lists with its utilization
+/// samples into one entry per pid, sorted by pid
+///
+/// A process with both a compute and a graphics context is listed twice by
+/// NVML with the same memory figure, so it is only counted once. Names are
+/// left empty for the caller to fill in.
+#[cfg(feature = "nvidia")]
+pub fn merge_gpu_processes(
+    compute: &[ProcessInfo],
+    graphics: &[ProcessInfo],
+    samples: &[ProcessUtilizationSample],
+) -> Vec<GpuProcessInfo> {
+    let mut by_pid: std::collections::BTreeMap<u32, GpuProcessInfo> = std::collections::BTreeMap::new();
+    for process in compute.iter().chain(graphics) {
+        let used = match process.used_gpu_memory {
+            UsedGpuMemory::Used(bytes) => bytes,
+            UsedGpuMemory::Unavailable => 0,
+        };
+        let entry = by_pid.entry(process.pid).or_insert_with(|| GpuProcessInfo {
+            pid: process.pid,
+            name: String::new(),
+            used_memory_bytes: 0,
+            sm_utilization: None,
+        });
+        entry.used_memory_bytes = entry.used_memory_bytes.max(used);
+    }
+
+    // The sample buffer can hold several readings per pid; keep the newest
+    let mut latest: HashMap<u32, &ProcessUtilizationSample> = HashMap::new();
+    for sample in samples {
+        let newest = latest.entry(sample.pid).or_insert(sample);
+        if sample.timestamp > newest.timestamp {
+            *newest = sample;
+        }
+    }
+    for (pid, sample) in latest {
+        if let Some(entry) = by_pid.get_mut(&pid) {
+            entry.sm_utilization = Some(sample.sm_util);
+        }
+    }
+
+    by_pid.into_values().collect()
+}
+
+/// `merge_gpu_processes` over the processes NVML places in one GPU instance
+/// of a GPU in MIG mode
+#[cfg(feature = "nvidia")]
+pub fn mig_instance_processes(
+    compute: &[ProcessInfo],
+    graphics: &[ProcessInfo],
+    samples: &[ProcessUtilizationSample],
+    gpu_instance_id: u32,
+) -> Vec<GpuProcessInfo> {
+    let in_instance = |processes: &[ProcessInfo]| -> Vec<ProcessInfo> {
+        processes.iter().filter(|p| p.gpu_instance_id == Some(gpu_instance_id)).cloned().collect()
+    };
+    merge_gpu_processes(&in_instance(compute), &in_instance(graphics), samples)
+}
+
+/// Active clock in MHz from a `pp_dpm_sclk` / `pp_dpm_mclk` table
+///
+/// The active state is starred. Older cards write `1: 1000Mhz *`; some RDNA
+/// memory clock tables omit the index (`1000Mhz *`) or use `MHz`.
+pub fn parse_dpm_clock(content: &str) -> Option<u32> {
+    content
+        .lines()
+        .find(|line| line.contains('*'))?
+        .split_whitespace()
+        .find_map(|field| {
+            let lower = field.to_ascii_lowercase();
+            lower.strip_suffix("mhz")?.parse::<u32>().ok()
+        })
+}
+
+/// Utilization between two samples of cumulative engine busy time in
+/// nanoseconds, as the busiest engine's share of `elapsed`.
+///
+/// Engines missing from either sample are ignored; `None` if no engine
+/// appears in both or no time has passed.
+pub fn engine_busy_percent(
+    previous: &HashMap<String, u64>,
+    current: &HashMap<String, u64>,
+    elapsed: Duration,
+) -> Option<f32> {
+    if elapsed.is_zero() {
+        return None;
+    }
+
+    current
+        .iter()
+        .filter_map(|(engine, busy)| Some(busy.saturating_sub(*previous.get(engine)?)))
+        .map(|delta| (delta as f64 / elapsed.as_nanos() as f64 * 100.0).min(100.0) as f32)
+        .reduce(f32::max)
+}
+
+/// State for re-initializing NVML once after its handle is lost (driver
+/// reset, GPU fell off the bus). The attempt waits out a short delay so the
+/// driver can come back; if it fails, the GPU stays unavailable.
+#[cfg(feature = "nvidia")]
+#[derive(Debug, Clone, Default)]
+pub(crate) struct NvmlRecovery {
+    next_attempt: Option<Instant>,
+    attempted: bool,
+}
+
+#[cfg(feature = "nvidia")]
+impl NvmlRecovery {
+    const DELAY: Duration = Duration::from_secs(5);
+
+    /// Records a lost handle and schedules the re-init attempt
+    pub(crate) fn handle_lost(&mut self, now: Instant) {
+        self.attempted = false;
+        self.next_attempt = Some(now + Self::DELAY);
+    }
+
+    /// Records that the re-init attempt failed
+    pub(crate) fn attempt_failed(&mut self) {
+        self.attempted = true;
+    }
+
+    pub(crate) fn is_recovering(&self) -> bool {
+        self.next_attempt.is_some()
+    }
+
+    pub(crate) fn is_exhausted(&self) -> bool {
+        self.attempted
+    }
+
+    pub(crate) fn is_due(&self, now: Instant) -> bool {
+        !self.is_exhausted() && self.next_attempt.is_some_and(|at| now >= at)
+    }
+}
+
+/// Backoff state for re-running GPU detection when no GPU is being reported,
+/// e.g. because the driver was still loading when the monitor initialized.
+///
+/// Unlike `NvmlRecovery` this never gives up; attempts back off to a
+/// maximum interval.
+#[derive(Debug, Clone)]
+pub(crate) struct GpuRedetection {
+    empty_cycles: u32,
+    next_attempt: Option<Instant>,
+    backoff: Duration,
+}
+
+impl Default for GpuRedetection {
+    fn default() -> Self {
+        Self { empty_cycles: 0, next_attempt: None, backoff: Self::INITIAL_BACKOFF }
+    }
+}
+
+impl GpuRedetection {
+    const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+    const MAX_BACKOFF: Duration = Duration::from_secs(60);
+    /// Empty or failed collections from a detected GPU before detection is
+    /// run again; with no GPU detected at all, the first one is enough
+    pub(crate) const EMPTY_CYCLES: u32 = 3;
+
+    /// Records the outcome of one collection
+    pub(crate) fn observe(&mut self, found_gpus: bool, detected: bool, now: Instant) {
+        if found_gpus {
+            *self = Self::default();
+            return;
+        }
+
+        self.empty_cycles += 1;
+        if self.next_attempt.is_none() && (!detected || self.empty_cycles >= Self::EMPTY_CYCLES) {
+            self.next_attempt = Some(now + self.backoff);
+        }
+    }
+
+    /// Records a detection attempt; the next one, if collections stay empty,
+    /// waits twice as long
+    pub(crate) fn attempted(&mut self) {
+        self.empty_cycles = 0;
+        self.next_attempt = None;
+        self.backoff = (self.backoff * 2).min(Self::MAX_BACKOFF);
+    }
+
+    pub(crate) fn is_due(&self, now: Instant) -> bool {
+        self.next_attempt.is_some_and(|at| now >= at)
+    }
+
+    pub(crate) fn backoff(&self) -> Duration {
+        self.backoff
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -30,31 +279,66 @@ enum GpuType {
     Unknown,
 }
 
+impl Default for GpuMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl GpuMonitor {
     pub fn new() -> Self {
+        Self::with_clock(SystemClock::shared())
+    }
+
+    /// Timestamps samples and computes rates by `clock`
+    pub fn with_clock(clock: SharedClock) -> Self {
         Self {
-            state: Arc::new(RwLock::new(MonitorState::Uninitialized)),
-            config: Arc::new(RwLock::new(MonitorConfig::default())),
+            shared: MonitorShared::new(clock.now()),
             metrics_history: Arc::new(RwLock::new(VecDeque::new())),
-            last_update: Arc::new(RwLock::new(SystemTime::now())),
             #[cfg(feature = "nvidia")]
             nvml: Arc::new(RwLock::new(None)),
+            #[cfg(feature = "nvidia")]
+            nvml_recovery: Arc::new(RwLock::new(NvmlRecovery::default())),
+            #[cfg(feature = "nvidia")]
+            mig: Arc::new(RwLock::new(None)),
             gpu_type: Arc::new(RwLock::new(GpuType::Unknown)),
+            gpu_redetection: Arc::new(RwLock::new(GpuRedetection::default())),
+            #[cfg(target_os = "linux")]
+            intel_engine_busy: Arc::new(RwLock::new(HashMap::new())),
+            #[cfg(target_os = "linux")]
+            amd_busy_history: Arc::new(RwLock::new(HashMap::new())),
+            busy_smoothing_samples: Arc::new(RwLock::new(DEFAULT_BUSY_SMOOTHING_SAMPLES)),
+            #[cfg(target_os = "windows")]
+            windows_counters: Arc::new(RwLock::new(None)),
+            collection_warning: Arc::new(RwLock::new(None)),
+            clock,
         }
     }
 
+    /// Sets how many `gpu_busy_percent` readings are averaged into the
+    /// reported AMD usage; 1 reports the raw reading
+    pub fn set_busy_smoothing(&self, samples: usize) {
+        *self.busy_smoothing_samples.write() = samples.max(1);
+    }
+
     fn detect_gpu_type(&self) -> GpuType {
         // First, try NVIDIA
         #[cfg(feature = "nvidia")]
         {
-            if let Ok(nvml) = Nvml::init() {
+            if let Ok(nvml) = Self::init_nvml() {
                 if nvml.device_count().unwrap_or(0) > 0 {
                     *self.nvml.write() = Some(nvml);
+                    *self.mig.write() = MigApi::load();
                     return GpuType::Nvidia;
                 }
             }
         }
 
+        // WSL has no PCI or DRM sysfs; only NVML (above) reaches the GPU
+        if super::environment::is_wsl() {
+            return GpuType::Unknown;
+        }
+
         // Try to detect via PCI devices
         #[cfg(target_os = "linux")]
         {
@@ -93,6 +377,19 @@ impl GpuMonitor {
         GpuType::Unknown
     }
 
+    /// Initializes NVML, falling back to the driver library WSL2 mounts
+    /// outside the default loader path
+    #[cfg(feature = "nvidia")]
+    fn init_nvml() -> std::result::Result<Nvml, NvmlError> {
+        match Nvml::init() {
+            Err(e) if super::environment::is_wsl() => Nvml::builder()
+                .lib_path(std::ffi::OsStr::new("/usr/lib/wsl/lib/libnvidia-ml.so.1"))
+                .init()
+                .map_err(|_| e),
+            result => result,
+        }
+    }
+
     fn collect_gpu_metrics(&self) -> Result<Vec<GpuMetrics>> {
         let gpu_type = self.gpu_type.read().clone();
         
@@ -106,18 +403,94 @@ impl GpuMonitor {
 
     #[cfg(feature = "nvidia")]
     fn collect_nvidia_metrics(&self) -> Result<Vec<GpuMetrics>> {
-        let nvml_guard = self.nvml.read();
-        let nvml = nvml_guard.as_ref()
-            .ok_or(MonitorError::NotInitialized)?;
+        if self.nvml.read().is_none() {
+            self.try_reinit_nvml()?;
+        }
+
+        let result = {
+            let nvml_guard = self.nvml.read();
+            let nvml = nvml_guard.as_ref()
+                .ok_or(MonitorError::NotInitialized)?;
+            Self::read_nvidia_devices(nvml, self.mig.read().as_ref())
+        };
 
-        let device_count = nvml.device_count()
-            .map_err(|e| MonitorError::CollectionError(e.to_string()))?;
+        match result {
+            Ok(mut metrics) => {
+                Self::name_gpu_processes(&mut metrics);
+                Ok(metrics)
+            }
+            Err(e @ (NvmlError::Uninitialized | NvmlError::GpuLost)) => {
+                // The handle is unusable until NVML is initialized again
+                *self.nvml.write() = None;
+                self.nvml_recovery.write().handle_lost(self.clock.instant());
+                tracing::warn!("NVML handle lost ({}), will attempt to re-initialize", e);
+                Err(MonitorError::HardwareNotAvailable(format!(
+                    "NVIDIA GPU temporarily unavailable: {}", e
+                )))
+            }
+            Err(e) => Err(MonitorError::CollectionError(e.to_string())),
+        }
+    }
+
+    /// Fills in process names from the OS; NVML only knows pids
+    #[cfg(feature = "nvidia")]
+    fn name_gpu_processes(metrics: &mut [GpuMetrics]) {
+        use sysinfo::{Pid, ProcessRefreshKind, System};
+
+        let mut system = System::new();
+        for process in metrics.iter_mut().flat_map(|gpu| gpu.gpu_processes.iter_mut()) {
+            let pid = Pid::from_u32(process.pid);
+            system.refresh_process_specifics(pid, ProcessRefreshKind::new());
+            if let Some(found) = system.process(pid) {
+                process.name = found.name().to_string();
+            }
+        }
+    }
+
+    #[cfg(feature = "nvidia")]
+    fn try_reinit_nvml(&self) -> Result<()> {
+        let mut recovery = self.nvml_recovery.write();
+
+        if !recovery.is_recovering() {
+            return Err(MonitorError::NotInitialized);
+        }
+        if recovery.is_exhausted() {
+            return Err(MonitorError::HardwareNotAvailable(
+                "NVML could not be re-initialized, giving up".to_string(),
+            ));
+        }
+
+        let now = self.clock.instant();
+        if !recovery.is_due(now) {
+            return Err(MonitorError::HardwareNotAvailable(
+                "NVIDIA GPU temporarily unavailable, waiting to re-initialize NVML".to_string(),
+            ));
+        }
+
+        match Self::init_nvml() {
+            Ok(nvml) => {
+                tracing::info!("NVML re-initialized after lost handle");
+                *self.nvml.write() = Some(nvml);
+                *recovery = NvmlRecovery::default();
+                Ok(())
+            }
+            Err(e) => {
+                recovery.attempt_failed();
+                Err(MonitorError::HardwareNotAvailable(format!("NVML re-initialization failed, giving up: {}", e)))
+            }
+        }
+    }
+
+    #[cfg(feature = "nvidia")]
+    /// One entry per GPU, followed by one per MIG instance for a GPU in MIG
+    /// mode
+    fn read_nvidia_devices(nvml: &Nvml, mig: Option<&MigApi>) -> std::result::Result<Vec<GpuMetrics>, NvmlError> {
+        let device_count = nvml.device_count()?;
 
         let mut metrics = Vec::new();
 
         for i in 0..device_count {
-            let device = nvml.device_by_index(i)
-                .map_err(|e| MonitorError::CollectionError(e.to_string()))?;
+            let device = nvml.device_by_index(i)?;
 
             let name = device.name()
                 .unwrap_or_else(|_| format!("GPU {}", i));
@@ -129,8 +502,7 @@ impl GpuMonitor {
                 .map(|u| u.gpu)
                 .unwrap_or(0) as f32;
             
-            let memory_info = device.memory_info()
-                .map_err(|e| MonitorError::CollectionError(e.to_string()))?;
+            let memory_info = device.memory_info()?;
             
             let power = device.power_usage()
                 .unwrap_or(0) as f32 / 1000.0; // Convert mW to W
@@ -147,10 +519,35 @@ impl GpuMonitor {
             
             let driver_version = nvml.sys_driver_version()
                 .unwrap_or_else(|_| "Unknown".to_string());
+            
+            let thermal = device.current_throttle_reasons().ok().map(|reasons| {
+                let reason = throttle_reason(reasons);
+                ThermalStatus {
+                    throttling: reason != ThrottleReason::None,
+                    reason,
+                    temperature,
+                    limit: device.temperature_threshold(TemperatureThreshold::Slowdown)
+                        .map(|limit| limit as f32)
+                        .unwrap_or(0.0),
+                }
+            });
+
+            // Per-process accounting needs driver support and, for other
+            // users' processes, privileges; go without it rather than fail
+            let compute = device.running_compute_processes().unwrap_or_default();
+            let graphics = device.running_graphics_processes().unwrap_or_default();
+            let samples = device.process_utilization_stats(None::<u64>).unwrap_or_default();
+            let gpu_processes = merge_gpu_processes(&compute, &graphics, &samples);
+
+            let mig_devices = match mig {
+                Some(mig) if mig.is_enabled(&device) => mig.devices(&device),
+                _ => Vec::new(),
+            };
+            let parent_id = if mig_devices.is_empty() { None } else { device.uuid().ok() };
 
             metrics.push(GpuMetrics {
-                name,
-                driver_version,
+                name: name.clone(),
+                driver_version: driver_version.clone(),
                 temperature_celsius: temperature,
                 usage_percent: utilization,
                 memory_total_bytes: memory_info.total,
@@ -160,7 +557,41 @@ impl GpuMonitor {
                 fan_speed_percent: fan_speed,
                 clock_mhz: clocks,
                 memory_clock_mhz: memory_clock,
+                gpu_processes,
+                thermal,
+                instance_id: None,
+                parent_id: parent_id.clone(),
             });
+
+            for mig_device in mig_devices {
+                let instance = &mig_device.device;
+                let memory = instance.memory_info().ok();
+                let (memory_total, memory_used) = memory.map(|m| (m.total, m.used)).unwrap_or_default();
+                let instance_name = instance.name().unwrap_or_else(|_| format!("{} MIG", name));
+
+                metrics.push(GpuMetrics {
+                    // Two instances of the same profile share a name
+                    name: format!("{} GI {}", instance_name, mig_device.gpu_instance_id),
+                    driver_version: driver_version.clone(),
+                    temperature_celsius: 0.0,
+                    usage_percent: 0.0,
+                    memory_total_bytes: memory_total,
+                    memory_used_bytes: memory_used,
+                    memory_usage_percent: if memory_total > 0 {
+                        (memory_used as f32 / memory_total as f32) * 100.0
+                    } else {
+                        0.0
+                    },
+                    power_watts: 0.0,
+                    fan_speed_percent: None,
+                    clock_mhz: 0,
+                    memory_clock_mhz: 0,
+                    gpu_processes: mig_instance_processes(&compute, &graphics, &samples, mig_device.gpu_instance_id),
+                    thermal: None,
+                    instance_id: instance.uuid().ok(),
+                    parent_id: parent_id.clone(),
+                });
+            }
         }
 
         Ok(metrics)
@@ -292,6 +723,10 @@ impl GpuMonitor {
             fan_speed_percent: None,
             clock_mhz: 0,
             memory_clock_mhz: 0,
+            gpu_processes: Vec::new(),
+            thermal: None,
+            instance_id: None,
+            parent_id: None,
         }])
     }
 
@@ -364,6 +799,10 @@ impl GpuMonitor {
                         fan_speed_percent: fan_speed,
                         clock_mhz,
                         memory_clock_mhz,
+                        gpu_processes: Vec::new(),
+                        thermal: None,
+                        instance_id: None,
+                        parent_id: None,
                     });
                 }
             }
@@ -397,10 +836,16 @@ impl GpuMonitor {
             }
         }
         
-        // Read GPU usage
+        // The driver's own busy percentage; clocks say nothing about load.
+        // It is sampled by the driver and noisy, so average the last few.
         let usage = std::fs::read_to_string(device_path.join("gpu_busy_percent"))
             .ok()
             .and_then(|s| s.trim().parse::<f32>().ok())
+            .map(|busy| {
+                let window = *self.busy_smoothing_samples.read();
+                let mut history = self.amd_busy_history.write();
+                smoothed_usage(history.entry(card_path.to_path_buf()).or_default(), busy, window)
+            })
             .unwrap_or(0.0);
         
         // Read memory info
@@ -446,25 +891,18 @@ impl GpuMonitor {
             } else { 0.0 },
             power_watts: power,
             fan_speed_percent: fan_speed,
-            clock_mhz: clock_mhz,
-            memory_clock_mhz: memory_clock_mhz,
+            clock_mhz,
+            memory_clock_mhz,
+            gpu_processes: Vec::new(),
+            thermal: None,
+            instance_id: None,
+            parent_id: None,
         })
     }
     
     #[cfg(target_os = "linux")]
     fn read_amd_clock(&self, device_path: &std::path::Path, clock_file: &str) -> Option<u32> {
-        std::fs::read_to_string(device_path.join(clock_file))
-            .ok()
-            .and_then(|content| {
-                // Parse the active clock from the DPM states
-                content.lines()
-                    .find(|line| line.contains('*'))
-                    .and_then(|line| {
-                        line.split_whitespace()
-                            .nth(1)
-                            .and_then(|s| s.trim_end_matches("Mhz").parse::<u32>().ok())
-                    })
-            })
+        parse_dpm_clock(&std::fs::read_to_string(device_path.join(clock_file)).ok()?)
     }
     
     #[cfg(target_os = "windows")]
@@ -504,10 +942,15 @@ impl GpuMonitor {
                     fan_speed_percent: None,
                     clock_mhz: 0,
                     memory_clock_mhz: 0,
+                    gpu_processes: Vec::new(),
+                    thermal: None,
+                    instance_id: None,
+                    parent_id: None,
                 });
             }
         }
         
+        self.apply_windows_usage(&mut metrics, crate::backend::windows_gpu::VENDOR_AMD);
         Ok(metrics)
     }
 
@@ -527,19 +970,7 @@ impl GpuMonitor {
             .and_then(|s| s.trim().parse::<u32>().ok())
             .unwrap_or(0);
         
-        // Read max frequency for reference
-        let max_freq = std::fs::read_to_string(card_path.join("gt_max_freq_mhz"))
-            .or_else(|_| std::fs::read_to_string(device_path.join("gt_max_freq_mhz")))
-            .ok()
-            .and_then(|s| s.trim().parse::<u32>().ok())
-            .unwrap_or(clock_mhz);
-        
-        // Calculate usage based on frequency (approximation)
-        let usage_percent = if max_freq > 0 {
-            (clock_mhz as f32 / max_freq as f32) * 100.0
-        } else {
-            0.0
-        };
+        let usage_percent = self.read_intel_busy_percent(card_path).unwrap_or(0.0);
         
         // Read power consumption
         let power_watts = self.read_intel_power(&device_path).unwrap_or(0.0);
@@ -547,8 +978,19 @@ impl GpuMonitor {
         // Read temperature
         let temperature_celsius = self.read_intel_temperature(&device_path).unwrap_or(0.0);
         
-        // Try to get memory info from debugfs (requires root)
-        let (memory_total, memory_used) = self.read_intel_memory_info().unwrap_or((0, 0));
+        // Try to get memory info from debugfs (requires root). Without it
+        // the rest of the card's metrics are still good, so report the
+        // reason through health() instead of failing the collection.
+        let (memory_total, memory_used) = match self.read_intel_memory_info() {
+            Ok(memory) => {
+                *self.collection_warning.write() = None;
+                memory
+            }
+            Err(e) => {
+                *self.collection_warning.write() = Some(e.to_string());
+                (0, 0)
+            }
+        };
         
         Ok(vec![GpuMetrics {
             name,
@@ -564,9 +1006,36 @@ impl GpuMonitor {
             fan_speed_percent: None, // Intel integrated GPUs typically don't have fans
             clock_mhz,
             memory_clock_mhz: 0, // Not easily accessible for Intel GPUs
+            gpu_processes: Vec::new(),
+            thermal: None,
+            instance_id: None,
+            parent_id: None,
         }])
     }
     
+    /// Samples the per-engine busy counters (nanoseconds) under
+    /// `card*/engine/*/busy` and returns the busiest engine's utilization since
+    /// the previous collection. The first sample only sets the baseline.
+    #[cfg(target_os = "linux")]
+    fn read_intel_busy_percent(&self, card_path: &std::path::Path) -> Option<f32> {
+        let current: HashMap<String, u64> = std::fs::read_dir(card_path.join("engine"))
+            .ok()?
+            .flatten()
+            .filter_map(|entry| {
+                let busy = std::fs::read_to_string(entry.path().join("busy")).ok()?;
+                Some((entry.file_name().to_string_lossy().to_string(), busy.trim().parse().ok()?))
+            })
+            .collect();
+        if current.is_empty() {
+            return None;
+        }
+
+        let now = self.clock.instant();
+        let previous = self.intel_engine_busy.write().insert(card_path.to_path_buf(), (current.clone(), now));
+        let (previous, at) = previous?;
+        engine_busy_percent(&previous, &current, now.duration_since(at))
+    }
+    
     #[cfg(target_os = "linux")]
     fn read_intel_power(&self, device_path: &std::path::Path) -> Option<f32> {
         // Try multiple power reading locations
@@ -590,7 +1059,7 @@ impl GpuMonitor {
     }
     
     #[cfg(target_os = "linux")]
-    fn read_intel_temperature(&self, device_path: &std::path::Path) -> Option<f32> {
+    fn read_intel_temperature(&self, _device_path: &std::path::Path) -> Option<f32> {
         // Try to read temperature from thermal zones
         if let Ok(entries) = std::fs::read_dir("/sys/class/thermal") {
             for entry in entries.flatten() {
@@ -611,35 +1080,44 @@ impl GpuMonitor {
     }
     
     #[cfg(target_os = "linux")]
-    fn read_intel_memory_info(&self) -> Option<(u64, u64)> {
-        // Try to parse memory info from i915_gem_objects in debugfs
-        if let Ok(gem_objects) = std::fs::read_to_string("/sys/kernel/debug/dri/0/i915_gem_objects") {
-            let mut total_bytes = 0u64;
-            let mut active_bytes = 0u64;
-            
-            for line in gem_objects.lines() {
-                if line.contains("total") && line.contains("objects") {
-                    // Parse lines like: "831 objects, 123456789 bytes"
-                    if let Some(bytes_part) = line.split(',').nth(1) {
-                        if let Some(bytes_str) = bytes_part.split_whitespace().next() {
-                            total_bytes = bytes_str.parse().unwrap_or(0);
-                        }
+    fn read_intel_memory_info(&self) -> Result<(u64, u64)> {
+        // Parse memory info from i915_gem_objects in debugfs, which is
+        // root-only on most distributions
+        let gem_objects = match std::fs::read_to_string("/sys/kernel/debug/dri/0/i915_gem_objects") {
+            Ok(gem_objects) => gem_objects,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Err(MonitorError::Unsupported {
+                    feature: "Intel GPU memory statistics (debugfs is not mounted)".to_string(),
+                });
+            }
+            Err(e) => return Err(MonitorError::from_io("reading Intel GPU memory from debugfs", e)),
+        };
+
+        let mut total_bytes = 0u64;
+        let mut active_bytes = 0u64;
+        
+        for line in gem_objects.lines() {
+            if line.contains("total") && line.contains("objects") {
+                // Parse lines like: "831 objects, 123456789 bytes"
+                if let Some(bytes_part) = line.split(',').nth(1) {
+                    if let Some(bytes_str) = bytes_part.split_whitespace().next() {
+                        total_bytes = bytes_str.parse().unwrap_or(0);
                     }
-                } else if line.contains("active") {
-                    if let Some(bytes_part) = line.split(',').nth(1) {
-                        if let Some(bytes_str) = bytes_part.split_whitespace().next() {
-                            active_bytes = bytes_str.parse().unwrap_or(0);
-                        }
+                }
+            } else if line.contains("active") {
+                if let Some(bytes_part) = line.split(',').nth(1) {
+                    if let Some(bytes_str) = bytes_part.split_whitespace().next() {
+                        active_bytes = bytes_str.parse().unwrap_or(0);
                     }
                 }
             }
-            
-            if total_bytes > 0 {
-                return Some((total_bytes, active_bytes));
-            }
         }
         
-        None
+        if total_bytes > 0 {
+            return Ok((total_bytes, active_bytes));
+        }
+        
+        Err(MonitorError::CollectionError("no totals in i915_gem_objects".to_string()))
     }
     
     #[cfg(target_os = "linux")]
@@ -674,7 +1152,8 @@ impl GpuMonitor {
             }
         }
         
-        let frequency = json["frequency"]["actual"].as_u64().unwrap_or(0) as u32;
+        // Reported as a fractional MHz average over the sampling period
+        let frequency = json["frequency"]["actual"].as_f64().map_or(0, |mhz| mhz.round() as u32);
         
         Ok(vec![GpuMetrics {
             name: "Intel Graphics".to_string(),
@@ -688,6 +1167,10 @@ impl GpuMonitor {
             fan_speed_percent: None,
             clock_mhz: frequency,
             memory_clock_mhz: 0,
+            gpu_processes: Vec::new(),
+            thermal: None,
+            instance_id: None,
+            parent_id: None,
         }])
     }
     
@@ -726,24 +1209,155 @@ impl GpuMonitor {
                     fan_speed_percent: None,
                     clock_mhz: 0,
                     memory_clock_mhz: 0,
+                    gpu_processes: Vec::new(),
+                    thermal: None,
+                    instance_id: None,
+                    parent_id: None,
                 });
             }
         }
         
+        self.apply_windows_usage(&mut metrics, crate::backend::windows_gpu::VENDOR_INTEL);
         Ok(metrics)
     }
 
+    /// Fills in usage and video memory, which WMI doesn't report, for the
+    /// `vendor_id` adapters in `metrics`
+    #[cfg(target_os = "windows")]
+    fn apply_windows_usage(&self, metrics: &mut [GpuMetrics], vendor_id: u32) {
+        use crate::backend::windows_gpu::{dxgi_adapters, GpuCounters};
+
+        let adapters: Vec<_> = match dxgi_adapters() {
+            Ok(adapters) => adapters.into_iter().filter(|a| a.vendor_id == vendor_id).collect(),
+            Err(e) => {
+                tracing::debug!("Failed to enumerate DXGI adapters: {}", e);
+                return;
+            }
+        };
+
+        let usage = {
+            let mut counters = self.windows_counters.write();
+            if counters.is_none() {
+                *counters = GpuCounters::open();
+            }
+            counters.as_ref().map(GpuCounters::sample).unwrap_or_default()
+        };
+
+        for (index, gpu) in metrics.iter_mut().enumerate() {
+            // WMI and DXGI report the same adapter name; fall back to the order
+            let Some(adapter) = adapters
+                .iter()
+                .find(|a| a.name == gpu.name.trim())
+                .or_else(|| adapters.get(index))
+            else {
+                continue;
+            };
+
+            // AdapterRAM in WMI is a 32-bit value and tops out at 4 GiB
+            if adapter.dedicated_memory_bytes > 0 {
+                gpu.memory_total_bytes = adapter.dedicated_memory_bytes;
+            }
+            if let Some(usage) = usage.get(&adapter.luid) {
+                gpu.usage_percent = usage.usage_percent;
+                gpu.memory_used_bytes = usage.dedicated_used_bytes;
+            }
+            if gpu.memory_total_bytes > 0 {
+                gpu.memory_usage_percent = gpu.memory_used_bytes as f32 / gpu.memory_total_bytes as f32 * 100.0;
+            }
+        }
+    }
+
+    /// The metrics `collect()` reports for one sample
+    ///
+    /// NVML has no temperature, power or utilization per MIG instance, so
+    /// those series come only from the physical GPU
+    pub(crate) fn gpus_to_metrics(gpu_metrics: &[GpuMetrics]) -> Vec<Metric> {
+        let mut metrics = Vec::new();
+        
+        for (idx, gpu) in gpu_metrics.iter().enumerate() {
+            let gpu_id = idx.to_string();
+            let tag = |mut metric: Metric| {
+                metric = metric.with_tag("gpu", &gpu_id);
+                if let Some(instance_id) = &gpu.instance_id {
+                    metric = metric.with_tag("instance_id", instance_id);
+                }
+                if let Some(parent_id) = &gpu.parent_id {
+                    metric = metric.with_tag("parent_id", parent_id);
+                }
+                metric
+            };
+            let is_instance = gpu.instance_id.is_some();
+            
+            if !is_instance {
+                metrics.push(tag(Metric::new(
+                    MetricType::GpuUsage,
+                    MetricValue::percentage(gpu.usage_percent as f64),
+                    "%",
+                )).with_tag("name", &gpu.name));
+                
+                metrics.push(tag(Metric::new(
+                    MetricType::GpuTemperature,
+                    MetricValue::Float(gpu.temperature_celsius as f64),
+                    "°C",
+                )));
+            }
+            
+            metrics.push(tag(Metric::new(
+                MetricType::GpuMemoryUsage,
+                MetricValue::percentage(gpu.memory_usage_percent as f64),
+                "%",
+            )));
+            
+            if !is_instance {
+                metrics.push(tag(Metric::new(
+                    MetricType::GpuPower,
+                    MetricValue::Float(gpu.power_watts as f64),
+                    "W",
+                )));
+            }
+            
+            if let Some(fan_speed) = gpu.fan_speed_percent {
+                metrics.push(tag(Metric::new(
+                    MetricType::GpuFanSpeed,
+                    MetricValue::Float(fan_speed as f64),
+                    "%",
+                )));
+            }
+            
+            if let Some(thermal) = &gpu.thermal {
+                metrics.push(tag(thermal.to_metric()));
+            }
+            
+            for process in &gpu.gpu_processes {
+                let mut metric = tag(Metric::new(
+                    MetricType::GpuProcessMemory,
+                    MetricValue::Unsigned(process.used_memory_bytes),
+                    "bytes",
+                )).with_tag("pid", process.pid.to_string())
+                    .with_tag("name", &process.name);
+                if let Some(sm) = process.sm_utilization {
+                    metric = metric.with_tag("sm_utilization", sm.to_string());
+                }
+                metrics.push(metric);
+            }
+        }
+        
+        metrics
+    }
+
     fn update_history(&self, metrics: Vec<GpuMetrics>) {
         let mut history = self.metrics_history.write();
-        let config = self.config.read();
+        let config = self.shared.config.read();
         
-        history.push_back(metrics);
+        history.push_back((self.clock.now(), metrics));
         
         // Remove old metrics based on retention policy
         let max_entries = (config.retain_history_seconds * 1000 / config.interval_ms) as usize;
         while history.len() > max_entries {
             history.pop_front();
         }
+
+        trim_history(&mut history, config.max_history_bytes);
     }
 }
 
@@ -754,109 +1368,91 @@ impl Monitor for GpuMonitor {
     }
 
     fn state(&self) -> MonitorState {
-        *self.state.read()
+        self.shared.state.read().clone()
+    }
+
+    fn shared(&self) -> Option<&MonitorShared> {
+        Some(&self.shared)
     }
 
     async fn initialize(&mut self, config: MonitorConfig) -> Result<()> {
-        *self.state.write() = MonitorState::Initializing;
-        *self.config.write() = config;
+        *self.shared.state.write() = MonitorState::Initializing;
+        *self.shared.config.write() = config;
         
         // Detect GPU type
         let gpu_type = self.detect_gpu_type();
         *self.gpu_type.write() = gpu_type;
         
-        *self.state.write() = MonitorState::Running;
+        *self.shared.state.write() = MonitorState::Running;
         Ok(())
     }
 
+
     async fn start(&mut self) -> Result<()> {
         match self.state() {
-            MonitorState::Running => return Ok(()),
+            MonitorState::Running | MonitorState::Degraded { .. } => return Ok(()),
             MonitorState::Uninitialized => {
                 return Err(MonitorError::NotInitialized);
             }
             _ => {}
         }
         
-        *self.state.write() = MonitorState::Running;
+        *self.shared.state.write() = MonitorState::Running;
         Ok(())
     }
 
     async fn stop(&mut self) -> Result<()> {
-        *self.state.write() = MonitorState::Stopped;
+        *self.shared.state.write() = MonitorState::Stopped;
         Ok(())
     }
 
     async fn pause(&mut self) -> Result<()> {
-        *self.state.write() = MonitorState::Paused;
+        *self.shared.state.write() = MonitorState::Paused;
         Ok(())
     }
 
     async fn resume(&mut self) -> Result<()> {
-        *self.state.write() = MonitorState::Running;
+        *self.shared.state.write() = MonitorState::Running;
         Ok(())
     }
 
     async fn collect(&mut self) -> Result<Vec<Metric>> {
-        if self.state() != MonitorState::Running {
+        if !self.state().is_collecting() {
             return Err(MonitorError::NotInitialized);
         }
 
-        let gpu_metrics = self.collect_gpu_metrics()?;
+        let now = self.clock.instant();
+        if self.gpu_redetection.read().is_due(now) {
+            let gpu_type = self.detect_gpu_type();
+            let mut redetection = self.gpu_redetection.write();
+            redetection.attempted();
+            tracing::debug!("Re-ran GPU detection: {:?}, backing off {:?}", gpu_type, redetection.backoff());
+            *self.gpu_type.write() = gpu_type;
+        }
+
+        let result = self.collect_gpu_metrics();
+        let detected = !matches!(*self.gpu_type.read(), GpuType::Unknown);
+        let found_gpus = result.as_ref().is_ok_and(|gpus| !gpus.is_empty());
+        self.gpu_redetection.write().observe(found_gpus, detected, now);
+
+        self.shared.state.write().record_collection(&result);
+        let gpu_metrics = result?;
         self.update_history(gpu_metrics.clone());
-        *self.last_update.write() = SystemTime::now();
+        *self.shared.last_update.write() = self.clock.now();
 
-        let mut metrics = Vec::new();
-        
-        for (idx, gpu) in gpu_metrics.iter().enumerate() {
-            let gpu_id = idx.to_string();
-            
-            metrics.push(Metric::new(
-                MetricType::GpuUsage,
-                MetricValue::Float(gpu.usage_percent as f64),
-                "%",
-            ).with_tag("gpu", &gpu_id).with_tag("name", &gpu.name));
-            
-            metrics.push(Metric::new(
-                MetricType::GpuTemperature,
-                MetricValue::Float(gpu.temperature_celsius as f64),
-                "°C",
-            ).with_tag("gpu", &gpu_id));
-            
-            metrics.push(Metric::new(
-                MetricType::GpuMemoryUsage,
-                MetricValue::Float(gpu.memory_usage_percent as f64),
-                "%",
-            ).with_tag("gpu", &gpu_id));
-            
-            metrics.push(Metric::new(
-                MetricType::GpuPower,
-                MetricValue::Float(gpu.power_watts as f64),
-                "W",
-            ).with_tag("gpu", &gpu_id));
-            
-            if let Some(fan_speed) = gpu.fan_speed_percent {
-                metrics.push(Metric::new(
-                    MetricType::GpuFanSpeed,
-                    MetricValue::Float(fan_speed as f64),
-                    "%",
-                ).with_tag("gpu", &gpu_id));
-            }
-        }
-        
-        Ok(metrics)
+        Ok(Self::gpus_to_metrics(&gpu_metrics))
     }
 
     async fn get_current_metrics(&self) -> Result<Vec<Metric>> {
         let history = self.metrics_history.read();
         
-        if let Some(latest) = history.back() {
+        if let Some((_, latest)) = history.back() {
             let mut metrics = Vec::new();
             
             for (idx, gpu) in latest.iter().enumerate() {
                 metrics.push(Metric::new(
                     MetricType::GpuUsage,
-                    MetricValue::Float(gpu.usage_percent as f64),
+                    MetricValue::percentage(gpu.usage_percent as f64),
                     "%",
                 ).with_tag("gpu", idx.to_string()));
             }
@@ -867,15 +1463,15 @@ impl Monitor for GpuMonitor {
         }
     }
 
-    async fn get_historical_metrics(&self, _duration_seconds: u64) -> Result<Vec<Metric>> {
+    async fn get_historical_metrics(&self, duration_seconds: u64) -> Result<Vec<Metric>> {
         let history = self.metrics_history.read();
         let mut metrics = Vec::new();
         
-        for gpu_list in history.iter() {
+        for gpu_list in history_since(&history, duration_seconds, self.clock.now()) {
             for (idx, gpu) in gpu_list.iter().enumerate() {
                 metrics.push(Metric::new(
                     MetricType::GpuUsage,
-                    MetricValue::Float(gpu.usage_percent as f64),
+                    MetricValue::percentage(gpu.usage_percent as f64),
                     "%",
                 ).with_tag("gpu", idx.to_string()));
             }
@@ -884,10 +1480,65 @@ impl Monitor for GpuMonitor {
         Ok(metrics)
     }
 
+    async fn history_range(&self, start: SystemTime, end: SystemTime) -> Vec<(SystemTime, Vec<Metric>)> {
+        history_in_range(&self.metrics_history.read(), start, end, |sample| Self::gpus_to_metrics(sample))
+    }
+
     fn supports_feature(&self, feature: &str) -> bool {
-        matches!(feature, 
+        let is_gpu_feature = matches!(feature, 
             "gpu_usage" | "gpu_temperature" | "gpu_memory" | 
             "gpu_power" | "gpu_clock" | "gpu_fan_speed"
-        )
+        );
+        
+        // Under WSL only NVML-backed GPUs report real values
+        if is_gpu_feature && super::environment::is_wsl() {
+            return matches!(*self.gpu_type.read(), GpuType::Nvidia);
+        }
+        
+        is_gpu_feature
+    }
+
+    fn supported_metrics(&self) -> Vec<MetricType> {
+        let history = self.metrics_history.read();
+        let Some((_, gpus)) = history.back().filter(|(_, gpus)| !gpus.is_empty()) else {
+            return Vec::new();
+        };
+
+        // The generic fallback only knows the vendor, and under WSL only
+        // NVML reports real values
+        let gpu_type = self.gpu_type.read().clone();
+        if matches!(gpu_type, GpuType::Unknown)
+            || (super::environment::is_wsl() && !matches!(gpu_type, GpuType::Nvidia))
+        {
+            return Vec::new();
+        }
+
+        let mut types = vec![MetricType::GpuUsage, MetricType::GpuMemoryUsage];
+        // Readings a backend can't take are left at zero rather than omitted
+        if gpus.iter().any(|gpu| gpu.temperature_celsius > 0.0) {
+            types.push(MetricType::GpuTemperature);
+        }
+        if gpus.iter().any(|gpu| gpu.power_watts > 0.0) {
+            types.push(MetricType::GpuPower);
+        }
+        if gpus.iter().any(|gpu| gpu.fan_speed_percent.is_some()) {
+            types.push(MetricType::GpuFanSpeed);
+        }
+        if gpus.iter().any(|gpu| gpu.thermal.is_some()) {
+            types.push(MetricType::ThermalThrottling);
+        }
+        if matches!(gpu_type, GpuType::Nvidia) {
+            types.push(MetricType::GpuProcessMemory);
+        }
+        types
+    }
+
+    fn health(&self) -> MonitorHealth {
+        MonitorHealth {
+            state: self.shared.state.read().clone(),
+            last_update: *self.shared.last_update.read(),
+            last_error: self.collection_warning.read().clone().or_else(|| self.shared.state.read().degraded_reason()),
+            consecutive_failures: 0,
+        }
     }
 }
\ No newline at end of file
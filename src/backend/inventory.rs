@@ -0,0 +1,265 @@
+//! One-shot inventory of the PCI and USB devices attached to the machine, for
+//! asset tracking rather than live monitoring.
+//!
+//! Linux reads `/sys/bus/pci` and `/sys/bus/usb`, with device names from
+//! `lspci` and a fallback to `lsusb`; Windows queries `Win32_PnPEntity` and
+//! `Win32_USBHub` through `wmic`. Other platforms report empty lists.
+//!
+//! This file has no dependencies on the rest of the crate so the Tauri
+//! backend can include it as well.
+
+use serde::{Deserialize, Serialize};
+use std::time::SystemTime;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PciDevice {
+    /// Bus address such as `0000:01:00.0`; the PnP device ID on Windows
+    pub address: String,
+    pub vendor_id: u16,
+    pub device_id: u16,
+    /// Class, subclass and programming interface, e.g. `0x030000` for a VGA
+    /// controller; not reported on Windows
+    pub class: Option<u32>,
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UsbDevice {
+    /// Bus and port path such as `1-2.4`, `Bus 001 Device 003` from
+    /// `lsusb`, or the PnP device ID on Windows
+    pub address: String,
+    pub vendor_id: u16,
+    pub product_id: u16,
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SystemInventory {
+    pub pci_devices: Vec<PciDevice>,
+    pub usb_devices: Vec<UsbDevice>,
+    pub collected_at: SystemTime,
+}
+
+/// Collects a `SystemInventory`; unlike the monitors it keeps no state and
+/// every call enumerates the buses again
+#[derive(Debug, Default)]
+pub struct HardwareInventory;
+
+impl HardwareInventory {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Enumerates the devices; this shells out, so call it off the async
+    /// runtime
+    pub fn collect(&self) -> SystemInventory {
+        SystemInventory {
+            pci_devices: read_pci_devices(),
+            usb_devices: read_usb_devices(),
+            collected_at: SystemTime::now(),
+        }
+    }
+}
+
+/// Parses a sysfs ID file such as `0x10de`
+pub fn parse_hex_id(content: &str) -> Option<u32> {
+    let content = content.trim();
+    u32::from_str_radix(content.strip_prefix("0x").unwrap_or(content), 16).ok()
+}
+
+/// Device names by bus address from `lspci -Dmm` output, whose lines look
+/// like `0000:01:00.0 "VGA compatible controller" "NVIDIA Corporation" "GA104" -ra1 ...`
+pub fn parse_lspci_names(output: &str) -> Vec<(String, String)> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (address, rest) = line.split_once(' ')?;
+            // Quoted fields alternate with the text between them
+            let quoted: Vec<&str> = rest.split('"').skip(1).step_by(2).collect();
+            let (vendor, device) = (quoted.get(1)?, quoted.get(2)?);
+            Some((address.to_string(), format!("{} {}", vendor, device)))
+        })
+        .collect()
+}
+
+/// Devices from `lsusb` output, whose lines look like
+/// `Bus 001 Device 003: ID 046d:c52b Logitech, Inc. Unifying Receiver`
+pub fn parse_lsusb(output: &str) -> Vec<UsbDevice> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (address, rest) = line.split_once(": ID ")?;
+            let (ids, name) = rest.split_once(' ').unwrap_or((rest, ""));
+            let (vendor, product) = ids.split_once(':')?;
+            Some(UsbDevice {
+                address: address.trim().to_string(),
+                vendor_id: u16::from_str_radix(vendor, 16).ok()?,
+                product_id: u16::from_str_radix(product, 16).ok()?,
+                name: Some(name.trim().to_string()).filter(|name| !name.is_empty()),
+            })
+        })
+        .collect()
+}
+
+/// The hex value following `key` in a PnP device ID such as
+/// `PCI\VEN_10DE&DEV_2484&SUBSYS_...`, e.g. `pnp_id_field(id, "VEN_")`
+pub fn pnp_id_field(device_id: &str, key: &str) -> Option<u16> {
+    let start = device_id.to_ascii_uppercase().find(key)? + key.len();
+    let value = device_id.get(start..start + 4)?;
+    u16::from_str_radix(value, 16).ok()
+}
+
+/// `(DeviceID, Name)` rows of `wmic ... get DeviceID,Name /format:csv` output.
+/// Names may contain commas, so each row is split no further than the
+/// header.
+pub fn parse_wmic_devices(output: &str) -> Vec<(String, Option<String>)> {
+    let mut lines = output.lines().map(str::trim).filter(|line| !line.is_empty());
+    let Some(header) = lines.next() else {
+        return Vec::new();
+    };
+    let header: Vec<&str> = header.split(',').collect();
+    let column = |name: &str| header.iter().position(|c| c.eq_ignore_ascii_case(name));
+    let (Some(id), Some(name)) = (column("DeviceID"), column("Name")) else {
+        return Vec::new();
+    };
+
+    lines
+        .filter_map(|line| {
+            let row: Vec<&str> = line.splitn(header.len(), ',').collect();
+            let device_id = row.get(id)?.trim().to_string();
+            let name = row.get(name).map(|n| n.trim().to_string()).filter(|n| !n.is_empty());
+            Some((device_id, name))
+        })
+        .collect()
+}
+
+#[cfg(target_os = "windows")]
+fn wmic_devices(class: &str, filter: Option<&str>) -> Vec<(String, Option<String>)> {
+    let mut args = vec!["path", class];
+    if let Some(filter) = filter {
+        args.extend(["where", filter]);
+    }
+    args.extend(["get", "DeviceID,Name", "/format:csv"]);
+
+    match std::process::Command::new("wmic").args(&args).output() {
+        Ok(output) if output.status.success() => parse_wmic_devices(&String::from_utf8_lossy(&output.stdout)),
+        _ => Vec::new(),
+    }
+}
+
+fn read_pci_devices() -> Vec<PciDevice> {
+    #[cfg(target_os = "linux")]
+    {
+        use std::collections::HashMap;
+
+        let names: HashMap<String, String> = std::process::Command::new("lspci")
+            .arg("-Dmm")
+            .output()
+            .map(|output| parse_lspci_names(&String::from_utf8_lossy(&output.stdout)).into_iter().collect())
+            .unwrap_or_default();
+
+        let Ok(entries) = std::fs::read_dir("/sys/bus/pci/devices") else {
+            return Vec::new();
+        };
+
+        let mut devices: Vec<PciDevice> = entries
+            .flatten()
+            .filter_map(|entry| {
+                let dir = entry.path();
+                let read_id = |file: &str| std::fs::read_to_string(dir.join(file)).ok().and_then(|c| parse_hex_id(&c));
+                let address = entry.file_name().to_string_lossy().to_string();
+                Some(PciDevice {
+                    vendor_id: read_id("vendor")? as u16,
+                    device_id: read_id("device")? as u16,
+                    class: read_id("class"),
+                    name: names.get(&address).cloned(),
+                    address,
+                })
+            })
+            .collect();
+        devices.sort_by(|a, b| a.address.cmp(&b.address));
+        devices
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        wmic_devices("Win32_PnPEntity", Some("DeviceID like 'PCI%'"))
+            .into_iter()
+            .filter_map(|(address, name)| {
+                Some(PciDevice {
+                    vendor_id: pnp_id_field(&address, "VEN_")?,
+                    device_id: pnp_id_field(&address, "DEV_")?,
+                    class: None,
+                    name,
+                    address,
+                })
+            })
+            .collect()
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "windows")))]
+    {
+        Vec::new()
+    }
+}
+
+fn read_usb_devices() -> Vec<UsbDevice> {
+    #[cfg(target_os = "linux")]
+    {
+        let mut devices = Vec::new();
+
+        // Interfaces (`1-2:1.0`) sit next to the devices but have no IDs
+        if let Ok(entries) = std::fs::read_dir("/sys/bus/usb/devices") {
+            for entry in entries.flatten() {
+                let dir = entry.path();
+                let read = |file: &str| std::fs::read_to_string(dir.join(file)).ok().map(|c| c.trim().to_string());
+                let (Some(vendor_id), Some(product_id)) = (
+                    read("idVendor").and_then(|id| u16::from_str_radix(&id, 16).ok()),
+                    read("idProduct").and_then(|id| u16::from_str_radix(&id, 16).ok()),
+                ) else {
+                    continue;
+                };
+
+                let name = match (read("manufacturer"), read("product")) {
+                    (Some(manufacturer), Some(product)) => Some(format!("{} {}", manufacturer, product)),
+                    (manufacturer, product) => product.or(manufacturer),
+                };
+                devices.push(UsbDevice {
+                    address: entry.file_name().to_string_lossy().to_string(),
+                    vendor_id,
+                    product_id,
+                    name,
+                });
+            }
+        }
+
+        if devices.is_empty() {
+            if let Ok(output) = std::process::Command::new("lsusb").output() {
+                devices = parse_lsusb(&String::from_utf8_lossy(&output.stdout));
+            }
+        }
+
+        devices.sort_by(|a, b| a.address.cmp(&b.address));
+        devices
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        wmic_devices("Win32_USBHub", None)
+            .into_iter()
+            .filter_map(|(address, name)| {
+                Some(UsbDevice {
+                    vendor_id: pnp_id_field(&address, "VID_")?,
+                    product_id: pnp_id_field(&address, "PID_")?,
+                    name,
+                    address,
+                })
+            })
+            .collect()
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "windows")))]
+    {
+        Vec::new()
+    }
+}
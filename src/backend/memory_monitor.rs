@@ -2,30 +2,262 @@ use async_trait::async_trait;
 use parking_lot::RwLock;
 use std::collections::VecDeque;
 use std::sync::Arc;
-use std::time::SystemTime;
+use std::time::{Instant, SystemTime};
 use sysinfo::{System, RefreshKind, MemoryRefreshKind};
 
 use crate::core::{
-    MemoryMetrics, Metric, MetricType, MetricValue, Monitor, MonitorConfig, MonitorError,
-    MonitorState, Result,
+    counter_rate, MemoryFragmentation, MemoryMetrics, history_in_range, history_since, TimedHistory, trim_history, Metric, MetricType, MetricValue, Monitor, MonitorConfig, MonitorShared,
+    MonitorError, MonitorState, Result, SharedClock, SystemClock, ThpStatus,
 };
 
+/// Reads the active transparent huge pages configuration from sysfs.
+///
+/// Returns `None` on platforms without THP or when the sysfs files are unreadable.
+pub fn read_transparent_hugepages() -> Option<ThpStatus> {
+    #[cfg(target_os = "linux")]
+    {
+        let base = "/sys/kernel/mm/transparent_hugepage";
+        if let (Ok(enabled), Ok(defrag)) = (
+            std::fs::read_to_string(format!("{}/enabled", base)),
+            std::fs::read_to_string(format!("{}/defrag", base)),
+        ) {
+            if let (Some(enabled), Some(defrag)) =
+                (parse_sysfs_selection(&enabled), parse_sysfs_selection(&defrag))
+            {
+                return Some(ThpStatus { enabled, defrag });
+            }
+        }
+    }
+    None
+}
+
+/// Extracts the bracketed (active) choice from a sysfs selection file such as
+/// `always [madvise] never`.
+pub fn parse_sysfs_selection(content: &str) -> Option<String> {
+    content
+        .split_whitespace()
+        .find(|choice| choice.starts_with('[') && choice.ends_with(']'))
+        .map(|choice| choice.trim_matches(|c| c == '[' || c == ']').to_string())
+}
+
+/// Order of a 2 MiB huge page with 4 KiB base pages
+const HUGEPAGE_ORDER: usize = 9;
+
+/// Sums free block counts per order over every zone in `/proc/buddyinfo`
+pub fn parse_buddyinfo(content: &str) -> Vec<u64> {
+    let mut totals: Vec<u64> = Vec::new();
+
+    for line in content.lines() {
+        // Node 0, zone   Normal   2750   3134    844 ...
+        let counts = line
+            .split_whitespace()
+            .skip(4)
+            .filter_map(|count| count.parse::<u64>().ok());
+        for (order, count) in counts.enumerate() {
+            if order >= totals.len() {
+                totals.resize(order + 1, 0);
+            }
+            totals[order] += count;
+        }
+    }
+
+    totals
+}
+
+/// Unusable free space index: the fraction of free pages sitting in blocks
+/// too small to satisfy an allocation of `order`
+pub fn unusable_index(free_blocks_by_order: &[u64], order: usize) -> f32 {
+    let pages = |(o, count): (usize, &u64)| count << o;
+    let total: u64 = free_blocks_by_order.iter().enumerate().map(pages).sum();
+    if total == 0 {
+        return 0.0;
+    }
+    let usable: u64 = free_blocks_by_order.iter().enumerate().skip(order).map(pages).sum();
+    (total - usable) as f32 / total as f32
+}
+
+/// Fills the compaction and reclaim counters from `/proc/vmstat` content
+pub fn parse_vmstat_fragmentation(content: &str, fragmentation: &mut MemoryFragmentation) {
+    for line in content.lines() {
+        let mut parts = line.split_whitespace();
+        let (Some(key), Some(value)) = (parts.next(), parts.next().and_then(|v| v.parse::<u64>().ok())) else {
+            continue;
+        };
+        match key {
+            "compact_stall" => fragmentation.compact_stall = value,
+            "compact_fail" => fragmentation.compact_fail = value,
+            "compact_success" => fragmentation.compact_success = value,
+            "thp_fault_fallback" => fragmentation.thp_fault_fallback = value,
+            // Older kernels have a single counter, newer ones one per zone
+            k if k == "allocstall" || k.starts_with("allocstall_") => fragmentation.allocstall += value,
+            _ => {}
+        }
+    }
+}
+
+/// Cumulative paging counters; `None` where the platform doesn't report one
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PagingCounters {
+    pub page_faults: Option<u64>,
+    pub major_faults: Option<u64>,
+    pub pages_in: Option<u64>,
+    pub pages_out: Option<u64>,
+}
+
+/// Reads the fault and swap counters from `/proc/vmstat` content
+pub fn parse_vmstat_paging(content: &str) -> PagingCounters {
+    let mut counters = PagingCounters::default();
+    for line in content.lines() {
+        let mut parts = line.split_whitespace();
+        let (Some(key), Some(value)) = (parts.next(), parts.next().and_then(|v| v.parse::<u64>().ok())) else {
+            continue;
+        };
+        match key {
+            "pgfault" => counters.page_faults = Some(value),
+            "pgmajfault" => counters.major_faults = Some(value),
+            "pswpin" => counters.pages_in = Some(value),
+            "pswpout" => counters.pages_out = Some(value),
+            _ => {}
+        }
+    }
+    counters
+}
+
+/// Raw `\Memory` counters behind `PagingCounters`, in field order. The
+/// "/sec" counters are running totals when read raw; rates are taken like
+/// on Linux.
+#[cfg(target_os = "windows")]
+const WINDOWS_PAGING_COUNTERS: [&str; 4] = [
+    "\\Memory\\Page Faults/sec",
+    "\\Memory\\Page Reads/sec",
+    "\\Memory\\Pages Input/sec",
+    "\\Memory\\Pages Output/sec",
+];
+
+/// Per-second rates of the paging counters since the last collection
+#[derive(Default)]
+struct PagingRates {
+    page_faults: f64,
+    major_faults: f64,
+    pages_in: f64,
+    pages_out: f64,
+}
+
+/// Reads `(cached, buffers)` in bytes from `/proc/meminfo` content; reclaimable
+/// slab counts as cache since the kernel frees it under pressure the same way
+pub fn parse_meminfo_cache(content: &str) -> (u64, u64) {
+    let mut cached = 0;
+    let mut buffers = 0;
+    for line in content.lines() {
+        let mut parts = line.split_whitespace();
+        let (Some(key), Some(kb)) = (parts.next(), parts.next().and_then(|v| v.parse::<u64>().ok())) else {
+            continue;
+        };
+        match key {
+            "Cached:" | "SReclaimable:" => cached += kb * 1024,
+            "Buffers:" => buffers = kb * 1024,
+            _ => {}
+        }
+    }
+    (cached, buffers)
+}
+
+/// Reads file-backed plus purgeable pages, in bytes, from `vm_stat` output
+pub fn parse_vm_stat_cache(output: &str) -> Option<u64> {
+    // Mach Virtual Memory Statistics: (page size of 16384 bytes)
+    let page_size: u64 = output
+        .lines()
+        .next()?
+        .split("page size of ")
+        .nth(1)?
+        .split_whitespace()
+        .next()?
+        .parse()
+        .ok()?;
+    let pages = |label: &str| {
+        output
+            .lines()
+            .find_map(|line| line.strip_prefix(label))
+            .and_then(|value| value.trim().trim_end_matches('.').parse::<u64>().ok())
+            .unwrap_or(0)
+    };
+    Some((pages("File-backed pages:") + pages("Pages purgeable:")) * page_size)
+}
+
+#[cfg(target_os = "windows")]
+mod performance_info {
+    /// `PERFORMANCE_INFORMATION` from `<psapi.h>`
+    #[repr(C)]
+    struct PerformanceInformation {
+        cb: u32,
+        commit_total: usize,
+        commit_limit: usize,
+        commit_peak: usize,
+        physical_total: usize,
+        physical_available: usize,
+        system_cache: usize,
+        kernel_total: usize,
+        kernel_paged: usize,
+        kernel_nonpaged: usize,
+        page_size: usize,
+        handle_count: u32,
+        process_count: u32,
+        thread_count: u32,
+    }
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn K32GetPerformanceInfo(info: *mut PerformanceInformation, cb: u32) -> i32;
+    }
+
+    /// System cache size in bytes
+    pub fn system_cache_bytes() -> Option<u64> {
+        // SAFETY: the struct is zeroed and sized as the API requires
+        unsafe {
+            let mut info: PerformanceInformation = std::mem::zeroed();
+            info.cb = std::mem::size_of::<PerformanceInformation>() as u32;
+            if K32GetPerformanceInfo(&mut info, info.cb) == 0 {
+                return None;
+            }
+            Some(info.system_cache as u64 * info.page_size as u64)
+        }
+    }
+}
+
 pub struct MemoryMonitor {
-    state: Arc<RwLock<MonitorState>>,
-    config: Arc<RwLock<MonitorConfig>>,
+    shared: MonitorShared,
     system: Arc<RwLock<System>>,
-    metrics_history: Arc<RwLock<VecDeque<MemoryMetrics>>>,
-    last_update: Arc<RwLock<SystemTime>>,
+    // Each sample with the time it was collected
+    metrics_history: TimedHistory<MemoryMetrics>,
+    /// Paging counters at the last collection, to turn them into rates
+    paging_counters: Arc<RwLock<Option<(PagingCounters, Instant)>>>,
+    // Performance counter query, opened on first use
+    #[cfg(target_os = "windows")]
+    windows_counters: Arc<RwLock<Option<super::windows_pdh::RawCounters>>>,
+    clock: SharedClock,
+}
+
+impl Default for MemoryMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl MemoryMonitor {
     pub fn new() -> Self {
+        Self::with_clock(SystemClock::shared())
+    }
+
+    /// Timestamps samples and computes rates by `clock`
+    pub fn with_clock(clock: SharedClock) -> Self {
         Self {
-            state: Arc::new(RwLock::new(MonitorState::Uninitialized)),
-            config: Arc::new(RwLock::new(MonitorConfig::default())),
+            shared: MonitorShared::new(clock.now()),
             system: Arc::new(RwLock::new(System::new_with_specifics(RefreshKind::everything()))),
             metrics_history: Arc::new(RwLock::new(VecDeque::new())),
-            last_update: Arc::new(RwLock::new(SystemTime::now())),
+            paging_counters: Arc::new(RwLock::new(None)),
+            #[cfg(target_os = "windows")]
+            windows_counters: Arc::new(RwLock::new(None)),
+            clock,
         }
     }
 
@@ -51,29 +283,256 @@ impl MemoryMonitor {
             0.0
         };
 
+        let (cached_bytes, buffer_bytes) = self.read_cache_usage();
+        let paging = self.read_paging_rates();
+
         Ok(MemoryMetrics {
             total_bytes: total_memory * 1024, // Convert KB to bytes
             used_bytes: used_memory * 1024,
             available_bytes: available_memory * 1024,
-            cached_bytes: 0, // Platform-specific, will implement later
+            cached_bytes,
+            buffer_bytes,
             swap_total_bytes: total_swap * 1024,
             swap_used_bytes: used_swap * 1024,
             usage_percent,
             swap_usage_percent,
+            anon_hugepages_bytes: self.read_anon_hugepages().unwrap_or(0),
+            fragmentation: self.read_fragmentation(),
+            page_fault_rate: paging.page_faults,
+            major_fault_rate: paging.major_faults,
+            swap_in_rate: paging.pages_in,
+            swap_out_rate: paging.pages_out,
         })
     }
 
+    fn read_paging_counters(&self) -> PagingCounters {
+        #[cfg(target_os = "linux")]
+        {
+            std::fs::read_to_string("/proc/vmstat")
+                .map(|vmstat| parse_vmstat_paging(&vmstat))
+                .unwrap_or_default()
+        }
+
+        #[cfg(target_os = "windows")]
+        {
+            let mut counters = self.windows_counters.write();
+            if counters.is_none() {
+                *counters = super::windows_pdh::RawCounters::open(&WINDOWS_PAGING_COUNTERS);
+            }
+            match counters.as_ref().filter(|counters| counters.collect()) {
+                Some(counters) => PagingCounters {
+                    page_faults: counters.value(0),
+                    major_faults: counters.value(1),
+                    pages_in: counters.value(2),
+                    pages_out: counters.value(3),
+                },
+                None => PagingCounters::default(),
+            }
+        }
+
+        #[cfg(not(any(target_os = "linux", target_os = "windows")))]
+        {
+            PagingCounters::default()
+        }
+    }
+
+    /// Rates are 0 on the first collection and for counters the platform
+    /// doesn't report
+    fn read_paging_rates(&self) -> PagingRates {
+        let current = self.read_paging_counters();
+        let now = self.clock.instant();
+        let Some((previous, taken_at)) = self.paging_counters.write().replace((current, now)) else {
+            return PagingRates::default();
+        };
+
+        let elapsed = now.duration_since(taken_at).as_secs_f64();
+        let rate = |current: Option<u64>, previous: Option<u64>| match (current, previous) {
+            (Some(current), Some(previous)) => counter_rate(current, previous, elapsed),
+            _ => 0.0,
+        };
+        PagingRates {
+            page_faults: rate(current.page_faults, previous.page_faults),
+            major_faults: rate(current.major_faults, previous.major_faults),
+            pages_in: rate(current.pages_in, previous.pages_in),
+            pages_out: rate(current.pages_out, previous.pages_out),
+        }
+    }
+
+    /// Returns `(cached, buffers)` in bytes, zero where the platform has no
+    /// equivalent
+    fn read_cache_usage(&self) -> (u64, u64) {
+        #[cfg(target_os = "linux")]
+        {
+            if let Ok(meminfo) = std::fs::read_to_string("/proc/meminfo") {
+                return parse_meminfo_cache(&meminfo);
+            }
+        }
+        #[cfg(target_os = "windows")]
+        {
+            if let Some(cached) = performance_info::system_cache_bytes() {
+                return (cached, 0);
+            }
+        }
+        #[cfg(target_os = "macos")]
+        {
+            if let Some(cached) = std::process::Command::new("vm_stat")
+                .output()
+                .ok()
+                .and_then(|output| parse_vm_stat_cache(&String::from_utf8_lossy(&output.stdout)))
+            {
+                return (cached, 0);
+            }
+        }
+        (0, 0)
+    }
+
+    fn read_fragmentation(&self) -> Option<MemoryFragmentation> {
+        #[cfg(target_os = "linux")]
+        {
+            if let Ok(buddyinfo) = std::fs::read_to_string("/proc/buddyinfo") {
+                let free_blocks_by_order = parse_buddyinfo(&buddyinfo);
+                let mut fragmentation = MemoryFragmentation {
+                    hugepage_unusable_index: unusable_index(&free_blocks_by_order, HUGEPAGE_ORDER),
+                    free_blocks_by_order,
+                    ..Default::default()
+                };
+                if let Ok(vmstat) = std::fs::read_to_string("/proc/vmstat") {
+                    parse_vmstat_fragmentation(&vmstat, &mut fragmentation);
+                }
+                return Some(fragmentation);
+            }
+        }
+        None
+    }
+
+    fn read_anon_hugepages(&self) -> Option<u64> {
+        #[cfg(target_os = "linux")]
+        {
+            if let Ok(meminfo) = std::fs::read_to_string("/proc/meminfo") {
+                for line in meminfo.lines() {
+                    if line.starts_with("AnonHugePages:") {
+                        let parts: Vec<&str> = line.split_whitespace().collect();
+                        if parts.len() >= 2 {
+                            return parts[1].parse::<u64>().ok().map(|kb| kb * 1024);
+                        }
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    /// The metrics `collect()` reports for one sample
+    fn memory_to_metrics(memory_metrics: &MemoryMetrics) -> Vec<Metric> {
+        let mut metrics = vec![
+            Metric::new(
+                MetricType::MemoryUsage,
+                MetricValue::percentage(memory_metrics.usage_percent as f64),
+                "%",
+            ),
+            Metric::new(
+                MetricType::MemoryAvailable,
+                MetricValue::Unsigned(memory_metrics.available_bytes),
+                "bytes",
+            ),
+            Metric::new(
+                MetricType::SwapUsage,
+                MetricValue::percentage(memory_metrics.swap_usage_percent as f64),
+                "%",
+            ),
+        ];
+        
+        // Add detailed memory metrics
+        metrics.push(Metric::new(
+            MetricType::MemoryUsage,
+            MetricValue::Unsigned(memory_metrics.used_bytes),
+            "bytes",
+        ).with_tag("type", "used"));
+        
+        metrics.push(Metric::new(
+            MetricType::MemoryUsage,
+            MetricValue::Unsigned(memory_metrics.total_bytes),
+            "bytes",
+        ).with_tag("type", "total"));
+        
+        metrics.push(Metric::new(
+            MetricType::Memory,
+            MetricValue::Unsigned(memory_metrics.anon_hugepages_bytes),
+            "bytes",
+        ).with_tag("type", "anon_hugepages"));
+        
+        metrics.push(Metric::new(
+            MetricType::Memory,
+            MetricValue::Unsigned(memory_metrics.cached_bytes),
+            "bytes",
+        ).with_tag("type", "cached"));
+        
+        metrics.push(Metric::new(
+            MetricType::Memory,
+            MetricValue::Unsigned(memory_metrics.buffer_bytes),
+            "bytes",
+        ).with_tag("type", "buffers"));
+        
+        for (kind, rate) in [
+            ("page_fault_rate", memory_metrics.page_fault_rate),
+            ("major_fault_rate", memory_metrics.major_fault_rate),
+            ("swap_in_rate", memory_metrics.swap_in_rate),
+            ("swap_out_rate", memory_metrics.swap_out_rate),
+        ] {
+            metrics.push(Metric::new(
+                MetricType::Memory,
+                MetricValue::Float(rate),
+                "/s",
+            ).with_tag("type", kind));
+        }
+        
+        // Fragmentation indicators (Linux only)
+        if let Some(fragmentation) = &memory_metrics.fragmentation {
+            metrics.push(Metric::new(
+                MetricType::Memory,
+                MetricValue::IntegerArray(
+                    fragmentation.free_blocks_by_order.iter().map(|count| *count as i64).collect(),
+                ),
+                "blocks",
+            ).with_tag("type", "free_blocks_by_order"));
+            
+            metrics.push(Metric::new(
+                MetricType::Memory,
+                MetricValue::Float(fragmentation.hugepage_unusable_index as f64),
+                "ratio",
+            ).with_tag("type", "hugepage_unusable_index"));
+            
+            for (counter, value) in [
+                ("compact_stall", fragmentation.compact_stall),
+                ("compact_fail", fragmentation.compact_fail),
+                ("compact_success", fragmentation.compact_success),
+                ("allocstall", fragmentation.allocstall),
+                ("thp_fault_fallback", fragmentation.thp_fault_fallback),
+            ] {
+                metrics.push(Metric::new(
+                    MetricType::Memory,
+                    MetricValue::Unsigned(value),
+                    "count",
+                ).with_tag("type", counter));
+            }
+        }
+        
+        metrics
+    }
+
     fn update_history(&self, metrics: MemoryMetrics) {
         let mut history = self.metrics_history.write();
-        let config = self.config.read();
+        let config = self.shared.config.read();
         
-        history.push_back(metrics);
+        history.push_back((self.clock.now(), metrics));
         
         // Remove old metrics based on retention policy
         let max_entries = (config.retain_history_seconds * 1000 / config.interval_ms) as usize;
         while history.len() > max_entries {
             history.pop_front();
         }
+
+        trim_history(&mut history, config.max_history_bytes);
     }
 }
 
@@ -84,126 +543,97 @@ impl Monitor for MemoryMonitor {
     }
 
     fn state(&self) -> MonitorState {
-        *self.state.read()
+        self.shared.state.read().clone()
+    }
+
+    fn shared(&self) -> Option<&MonitorShared> {
+        Some(&self.shared)
     }
 
     async fn initialize(&mut self, config: MonitorConfig) -> Result<()> {
-        *self.state.write() = MonitorState::Initializing;
-        *self.config.write() = config;
+        *self.shared.state.write() = MonitorState::Initializing;
+        *self.shared.config.write() = config;
         
         // Initialize system info
         let mut system = self.system.write();
         system.refresh_memory_specifics(MemoryRefreshKind::everything());
         
-        *self.state.write() = MonitorState::Running;
+        *self.shared.state.write() = MonitorState::Running;
         Ok(())
     }
 
+
     async fn start(&mut self) -> Result<()> {
         match self.state() {
-            MonitorState::Running => return Ok(()),
+            MonitorState::Running | MonitorState::Degraded { .. } => return Ok(()),
             MonitorState::Uninitialized => {
                 return Err(MonitorError::NotInitialized);
             }
             _ => {}
         }
         
-        *self.state.write() = MonitorState::Running;
+        *self.shared.state.write() = MonitorState::Running;
         Ok(())
     }
 
     async fn stop(&mut self) -> Result<()> {
-        *self.state.write() = MonitorState::Stopped;
+        *self.shared.state.write() = MonitorState::Stopped;
         Ok(())
     }
 
     async fn pause(&mut self) -> Result<()> {
-        *self.state.write() = MonitorState::Paused;
+        *self.shared.state.write() = MonitorState::Paused;
         Ok(())
     }
 
     async fn resume(&mut self) -> Result<()> {
-        *self.state.write() = MonitorState::Running;
+        *self.shared.state.write() = MonitorState::Running;
         Ok(())
     }
 
     async fn collect(&mut self) -> Result<Vec<Metric>> {
-        if self.state() != MonitorState::Running {
+        if !self.state().is_collecting() {
             return Err(MonitorError::NotInitialized);
         }
 
-        let memory_metrics = self.collect_memory_metrics()?;
+        let result = self.collect_memory_metrics();
+        self.shared.state.write().record_collection(&result);
+        let memory_metrics = result?;
         self.update_history(memory_metrics.clone());
-        *self.last_update.write() = SystemTime::now();
+        *self.shared.last_update.write() = self.clock.now();
 
-        let mut metrics = Vec::new();
-        
-        metrics.push(Metric::new(
-            MetricType::MemoryUsage,
-            MetricValue::Float(memory_metrics.usage_percent as f64),
-            "%",
-        ));
-        
-        metrics.push(Metric::new(
-            MetricType::MemoryAvailable,
-            MetricValue::Unsigned(memory_metrics.available_bytes),
-            "bytes",
-        ));
-        
-        metrics.push(Metric::new(
-            MetricType::SwapUsage,
-            MetricValue::Float(memory_metrics.swap_usage_percent as f64),
-            "%",
-        ));
-        
-        // Add detailed memory metrics
-        metrics.push(Metric::new(
-            MetricType::MemoryUsage,
-            MetricValue::Unsigned(memory_metrics.used_bytes),
-            "bytes",
-        ).with_tag("type", "used"));
-        
-        metrics.push(Metric::new(
-            MetricType::MemoryUsage,
-            MetricValue::Unsigned(memory_metrics.total_bytes),
-            "bytes",
-        ).with_tag("type", "total"));
-        
-        Ok(metrics)
+        Ok(Self::memory_to_metrics(&memory_metrics))
     }
 
     async fn get_current_metrics(&self) -> Result<Vec<Metric>> {
         let history = self.metrics_history.read();
         
-        if let Some(latest) = history.back() {
-            let mut metrics = Vec::new();
-            
-            metrics.push(Metric::new(
-                MetricType::MemoryUsage,
-                MetricValue::Float(latest.usage_percent as f64),
-                "%",
-            ));
-            
-            metrics.push(Metric::new(
-                MetricType::MemoryAvailable,
-                MetricValue::Unsigned(latest.available_bytes),
-                "bytes",
-            ));
-            
-            Ok(metrics)
+        if let Some((_, latest)) = history.back() {
+            Ok(vec![
+                Metric::new(
+                    MetricType::MemoryUsage,
+                    MetricValue::percentage(latest.usage_percent as f64),
+                    "%",
+                ),
+                Metric::new(
+                    MetricType::MemoryAvailable,
+                    MetricValue::Unsigned(latest.available_bytes),
+                    "bytes",
+                ),
+            ])
         } else {
             Ok(Vec::new())
         }
     }
 
-    async fn get_historical_metrics(&self, _duration_seconds: u64) -> Result<Vec<Metric>> {
+    async fn get_historical_metrics(&self, duration_seconds: u64) -> Result<Vec<Metric>> {
         let history = self.metrics_history.read();
         let mut metrics = Vec::new();
         
-        for memory_metrics in history.iter() {
+        for memory_metrics in history_since(&history, duration_seconds, self.clock.now()) {
             metrics.push(Metric::new(
                 MetricType::MemoryUsage,
-                MetricValue::Float(memory_metrics.usage_percent as f64),
+                MetricValue::percentage(memory_metrics.usage_percent as f64),
                 "%",
             ));
         }
@@ -211,7 +641,15 @@ impl Monitor for MemoryMonitor {
         Ok(metrics)
     }
 
+    async fn history_range(&self, start: SystemTime, end: SystemTime) -> Vec<(SystemTime, Vec<Metric>)> {
+        history_in_range(&self.metrics_history.read(), start, end, Self::memory_to_metrics)
+    }
+
     fn supports_feature(&self, feature: &str) -> bool {
-        matches!(feature, "memory_usage" | "memory_available" | "swap_usage")
+        matches!(feature, "memory_usage" | "memory_available" | "swap_usage" | "paging_rates")
+    }
+
+    fn supported_metrics(&self) -> Vec<MetricType> {
+        vec![MetricType::MemoryUsage, MetricType::MemoryAvailable, MetricType::SwapUsage, MetricType::Memory]
     }
 }
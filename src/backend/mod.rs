@@ -1,15 +1,27 @@
+pub mod battery_monitor;
 pub mod cpu_monitor;
+pub mod environment;
 pub mod gpu_monitor;
+pub mod inventory;
 pub mod memory_monitor;
 pub mod storage_monitor;
 pub mod network_monitor;
+#[cfg(feature = "nvidia")]
+pub mod nvidia_mig;
 pub mod process_monitor;
+mod process_info;
 pub mod sensors;
 pub mod system_monitor;
+pub mod windows_gpu;
+#[cfg(target_os = "windows")]
+pub mod windows_pdh;
 
+pub use battery_monitor::BatteryMonitor;
 pub use cpu_monitor::CpuMonitor;
 pub use gpu_monitor::GpuMonitor;
+pub use inventory::HardwareInventory;
 pub use memory_monitor::MemoryMonitor;
 pub use storage_monitor::StorageMonitor;
 pub use network_monitor::NetworkMonitor;
 pub use process_monitor::ProcessMonitor;
+pub use sensors::SensorsMonitor;
@@ -1,62 +1,721 @@
 use async_trait::async_trait;
 use parking_lot::RwLock;
-use std::collections::{HashMap, VecDeque};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::sync::Arc;
-use std::time::SystemTime;
+use std::time::{Instant, SystemTime};
 use sysinfo::{System, RefreshKind, Networks};
 
 use crate::core::{
-    NetworkMetrics, Metric, MetricType, MetricValue, Monitor, MonitorConfig, MonitorError,
-    MonitorState, Result,
+    counter_rate, HistorySize, IpAddress, NetworkMetrics, TcpStats, WirelessInfo, history_in_range, history_since, TimedHistory, trim_history, Metric, MetricType, MetricValue, Monitor, MonitorConfig, MonitorShared, MonitorError,
+    MonitorState, Result, SharedClock, SystemClock,
 };
+pub use crate::core::is_loopback_interface;
 
 pub struct NetworkMonitor {
-    state: Arc<RwLock<MonitorState>>,
-    config: Arc<RwLock<MonitorConfig>>,
+    shared: MonitorShared,
     #[allow(dead_code)] // Will be used for future platform-specific optimizations
     system: Arc<RwLock<System>>,
-    metrics_history: Arc<RwLock<VecDeque<Vec<NetworkMetrics>>>>,
-    last_update: Arc<RwLock<SystemTime>>,
+    // Each sample with the time it was collected
+    metrics_history: TimedHistory<NetworkSample>,
     previous_stats: Arc<RwLock<HashMap<String, NetworkStats>>>,
+    wireless: WirelessReader,
+    clock: SharedClock,
 }
 
 #[derive(Clone, Debug)]
 struct NetworkStats {
     bytes_sent: u64,
     bytes_received: u64,
-    #[allow(dead_code)] // Used in metric collection and error reporting
-    packets_sent: u64,
-    #[allow(dead_code)] // Used in metric collection and error reporting
-    packets_received: u64,
-    #[allow(dead_code)] // Used in metric collection and error reporting
-    errors_sent: u64,
-    #[allow(dead_code)] // Used in metric collection and error reporting
-    errors_received: u64,
-    timestamp: SystemTime,
+    taken_at: Instant,
+}
+
+/// One collection: the interfaces and the host-wide TCP connection states
+#[derive(Clone, Debug)]
+struct NetworkSample {
+    interfaces: Vec<NetworkMetrics>,
+    tcp: Option<TcpStats>,
+}
+
+impl HistorySize for NetworkSample {
+    fn heap_bytes(&self) -> usize {
+        self.interfaces.heap_bytes()
+    }
+}
+
+/// Interface details parsed from `ifconfig <name>` output on macOS:
+/// `(is_up, mac_address, ip_addresses, speed_mbps)`
+pub fn parse_ifconfig(output: &str) -> (Option<bool>, Option<String>, Vec<IpAddress>, Option<u64>) {
+    let mut is_up = None;
+    let mut mac_address = None;
+    let mut ip_addresses = Vec::new();
+    let mut speed_mbps = None;
+
+    for line in output.lines() {
+        let line = line.trim();
+        let mut fields = line.split_whitespace();
+        match fields.next() {
+            Some("ether") => mac_address = fields.next().map(|mac| mac.to_lowercase()),
+            Some(family @ ("inet" | "inet6")) => {
+                // inet 192.168.1.23 netmask 0xffffff00 broadcast ...
+                // inet6 fe80::1%en0 prefixlen 64 secured scopeid 0x6
+                let Some(mut address) = fields.next().and_then(IpAddress::parse) else {
+                    continue;
+                };
+                let rest: Vec<&str> = fields.collect();
+                let option = |name: &str| rest.iter().position(|f| *f == name).and_then(|i| rest.get(i + 1));
+                let prefix_len = if family == "inet" {
+                    option("netmask")
+                        .and_then(|mask| u32::from_str_radix(mask.trim_start_matches("0x"), 16).ok())
+                        .map(|mask| mask.count_ones() as u8)
+                } else {
+                    option("prefixlen").and_then(|len| len.parse().ok())
+                };
+                if let Some(prefix_len) = prefix_len {
+                    address.prefix_len = prefix_len;
+                }
+                ip_addresses.push(address);
+            }
+            // status: active / status: inactive
+            Some("status:") => is_up = fields.next().map(|status| status == "active"),
+            // media: autoselect (1000baseT <full-duplex>)
+            Some("media:") => speed_mbps = line.split(['(', ' ']).find_map(parse_media_speed),
+            Some(name) if name.ends_with(':') && is_up.is_none() => {
+                // en0: flags=8863<UP,BROADCAST,...> mtu 1500
+                if let Some(flags) = line.split('<').nth(1).and_then(|rest| rest.split('>').next()) {
+                    is_up = Some(flags.split(',').any(|flag| flag == "UP"));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    (is_up, mac_address, ip_addresses, speed_mbps)
+}
+
+/// Parses an ifconfig media subtype such as `1000baseT` or `10GbaseT` into Mbps
+fn parse_media_speed(media: &str) -> Option<u64> {
+    let (rate, _) = media.split_once("base")?;
+    match rate.strip_suffix('G') {
+        Some(gigabits) => gigabits.parse::<u64>().ok().map(|g| g * 1000),
+        None => rate.parse::<u64>().ok(),
+    }
+}
+
+/// Formats a hardware address as lowercase, colon-separated hex
+pub fn format_mac(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect::<Vec<_>>().join(":")
+}
+
+/// Converts an adapter link speed in bits per second into Mbps. Windows
+/// reports an unknown speed as 0 or `u64::MAX`.
+pub fn link_speed_mbps(bits_per_second: u64) -> Option<u64> {
+    (bits_per_second > 0 && bits_per_second != u64::MAX).then_some(bits_per_second / 1_000_000)
+}
+
+/// Collects addresses from `ip addr show <name>` output on Linux
+pub fn parse_ip_addr_show(output: &str) -> Vec<IpAddress> {
+    output
+        .lines()
+        .filter_map(|line| {
+            // inet 192.168.1.23/24 brd 192.168.1.255 scope global dynamic eth0
+            // inet6 fe80::1c2d:3e4f:5a6b:7c8d/64 scope link
+            let mut fields = line.split_whitespace();
+            match fields.next() {
+                Some("inet") | Some("inet6") => fields.next().and_then(IpAddress::parse),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+/// Rough link quality for a signal level, from 0% at -100 dBm to 100% at
+/// -50 dBm and above, for platforms that only report one of the two
+pub fn signal_quality_percent(signal_dbm: i32) -> f32 {
+    (2 * (signal_dbm + 100)).clamp(0, 100) as f32
+}
+
+/// Centre frequency of a Wi-Fi channel in the 2.4 and 5 GHz bands; 6 GHz
+/// channel numbers overlap those, so they aren't resolved
+pub fn channel_frequency_mhz(channel: u32) -> Option<u32> {
+    match channel {
+        1..=13 => Some(2407 + 5 * channel),
+        14 => Some(2484),
+        32..=177 => Some(5000 + 5 * channel),
+        _ => None,
+    }
+}
+
+/// Parses `iw dev <name> link` output on Linux; `None` when not connected
+pub fn parse_iw_link(output: &str) -> Option<WirelessInfo> {
+    if !output.trim_start().starts_with("Connected to") {
+        return None;
+    }
+
+    let mut info = WirelessInfo::default();
+    for line in output.lines() {
+        let Some((key, value)) = line.trim().split_once(':') else {
+            continue;
+        };
+        let first_number = || value.split_whitespace().next().and_then(|v| v.parse::<f64>().ok());
+        match key {
+            "SSID" => info.ssid = value.trim().to_string(),
+            // "freq: 5180" or, on newer iw, "freq: 5180.0"
+            "freq" => info.frequency_mhz = first_number().map_or(0, |mhz| mhz as u32),
+            // "signal: -52 dBm"
+            "signal" => info.signal_dbm = first_number().map_or(0, |dbm| dbm as i32),
+            // "tx bitrate: 390.0 MBit/s VHT-MCS 8 80MHz short GI VHT-NSS 1"
+            "tx bitrate" => info.tx_rate_mbps = first_number().map_or(0, |mbps| mbps.round() as u32),
+            _ => {}
+        }
+    }
+    info.link_quality_percent = signal_quality_percent(info.signal_dbm);
+    Some(info)
+}
+
+/// Reads `(link_quality_percent, signal_dbm)` of `interface_name` from
+/// `/proc/net/wireless` content, taking the link quality out of 70 as the
+/// common drivers report it
+pub fn parse_proc_net_wireless(content: &str, interface_name: &str) -> Option<(f32, i32)> {
+    content.lines().skip(2).find_map(|line| {
+        // " wlan0: 0000   70.  -40.  -256        0      0      0      0      0        0"
+        let (name, rest) = line.split_once(':')?;
+        if name.trim() != interface_name {
+            return None;
+        }
+        let mut fields = rest.split_whitespace().skip(1).map(|f| f.trim_end_matches('.').parse::<f32>().ok());
+        let link = fields.next()??;
+        let level = fields.next()??;
+        Some(((link * 100.0 / 70.0).clamp(0.0, 100.0), level as i32))
+    })
+}
+
+/// Parses `airport -I` output on macOS; `None` when not associated
+pub fn parse_airport_info(output: &str) -> Option<WirelessInfo> {
+    let field = |name: &str| {
+        output.lines().find_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            (key.trim() == name).then(|| value.trim())
+        })
+    };
+
+    let ssid = field("SSID")?;
+    let signal_dbm = field("agrCtlRSSI")?.parse::<i32>().ok()?;
+    // "channel: 149,80", the primary channel and the channel width
+    let channel = field("channel").and_then(|c| c.split(',').next()).and_then(|c| c.parse::<u32>().ok());
+    Some(WirelessInfo {
+        ssid: ssid.to_string(),
+        signal_dbm,
+        link_quality_percent: signal_quality_percent(signal_dbm),
+        frequency_mhz: channel.and_then(channel_frequency_mhz).unwrap_or(0),
+        tx_rate_mbps: field("lastTxRate").and_then(|r| r.parse().ok()).unwrap_or(0),
+    })
+}
+
+/// The connected interfaces in `netsh wlan show interfaces` output on
+/// Windows, by interface name
+pub fn parse_netsh_wlan(output: &str) -> Vec<(String, WirelessInfo)> {
+    let mut links = Vec::new();
+    let mut block: Vec<(&str, &str)> = Vec::new();
+    for line in output.lines().chain([""]) {
+        match line.split_once(" : ").or_else(|| line.split_once(": ")) {
+            Some((key, value)) => block.push((key.trim(), value.trim())),
+            // Each interface is a block of "Key : Value" lines
+            None if !block.is_empty() => {
+                links.extend(netsh_wlan_link(&block));
+                block.clear();
+            }
+            None => {}
+        }
+    }
+    links
+}
+
+fn netsh_wlan_link(block: &[(&str, &str)]) -> Option<(String, WirelessInfo)> {
+    let field = |name: &str| block.iter().find(|(key, _)| *key == name).map(|(_, value)| *value);
+    let name = field("Name")?;
+    if field("State")? != "connected" {
+        return None;
+    }
+    // Windows reports the signal as a percentage only
+    let quality = field("Signal")?.trim_end_matches('%').trim().parse::<f32>().ok()?;
+    Some((
+        name.to_string(),
+        WirelessInfo {
+            ssid: field("SSID").unwrap_or_default().to_string(),
+            signal_dbm: (quality / 2.0) as i32 - 100,
+            link_quality_percent: quality,
+            frequency_mhz: field("Channel").and_then(|c| c.parse().ok()).and_then(channel_frequency_mhz).unwrap_or(0),
+            tx_rate_mbps: field("Transmit rate (Mbps)").and_then(|r| r.parse::<f64>().ok()).map_or(0, |r| r as u32),
+        },
+    ))
+}
+
+/// Devices of the Wi-Fi hardware ports in `networksetup
+/// -listallhardwareports` output on macOS
+pub fn parse_wifi_ports(output: &str) -> Vec<String> {
+    // "Hardware Port: Wi-Fi" is followed by "Device: en0"
+    let lines: Vec<&str> = output.lines().map(str::trim).collect();
+    lines
+        .windows(2)
+        .filter(|pair| pair[0] == "Hardware Port: Wi-Fi")
+        .filter_map(|pair| pair[1].strip_prefix("Device:").map(|device| device.trim().to_string()))
+        .collect()
+}
+
+/// Removed in macOS 14.4, after which Wi-Fi links aren't reported
+#[cfg(target_os = "macos")]
+const AIRPORT: &str = "/System/Library/PrivateFrameworks/Apple80211.framework/Versions/Current/Resources/airport";
+
+/// Reads the radio links of wireless interfaces, remembering what it
+/// would otherwise spawn a process every collection to find out
+#[derive(Clone, Default)]
+pub struct WirelessReader {
+    /// Wi-Fi devices, listed on first use; adapters plugged in later
+    /// aren't picked up
+    #[cfg(target_os = "macos")]
+    wifi_ports: Arc<std::sync::OnceLock<Vec<String>>>,
+}
+
+impl WirelessReader {
+    /// The links of the connected wireless interfaces among
+    /// `interface_names`, by name. Runs at most one platform tool per
+    /// wireless interface (one in total on macOS and Windows), so call it
+    /// off the async runtime.
+    pub fn read(&self, interface_names: &[String]) -> HashMap<String, WirelessInfo> {
+        #[cfg(target_os = "linux")]
+        {
+            // Only wireless interfaces have this directory, which spares
+            // running iw for every wired one
+            let wireless: Vec<&String> = interface_names
+                .iter()
+                .filter(|name| std::path::Path::new(&format!("/sys/class/net/{}/wireless", name)).exists())
+                .collect();
+            if wireless.is_empty() {
+                return HashMap::new();
+            }
+            let proc_wireless = std::fs::read_to_string("/proc/net/wireless").unwrap_or_default();
+
+            wireless
+                .into_iter()
+                .filter_map(|name| Some((name.clone(), read_linux_link(name, parse_proc_net_wireless(&proc_wireless, name))?)))
+                .collect()
+        }
+
+        #[cfg(target_os = "macos")]
+        {
+            let wifi_ports = self.wifi_ports.get_or_init(|| {
+                std::process::Command::new("networksetup")
+                    .arg("-listallhardwareports")
+                    .output()
+                    .map(|output| parse_wifi_ports(&String::from_utf8_lossy(&output.stdout)))
+                    .unwrap_or_default()
+            });
+            // airport describes the primary Wi-Fi port only
+            let Some(port) = interface_names.iter().find(|name| wifi_ports.contains(name)) else {
+                return HashMap::new();
+            };
+            if !std::path::Path::new(AIRPORT).exists() {
+                return HashMap::new();
+            }
+
+            std::process::Command::new(AIRPORT)
+                .arg("-I")
+                .output()
+                .ok()
+                .and_then(|output| parse_airport_info(&String::from_utf8_lossy(&output.stdout)))
+                .map(|info| HashMap::from([(port.clone(), info)]))
+                .unwrap_or_default()
+        }
+
+        #[cfg(target_os = "windows")]
+        {
+            if interface_names.is_empty() {
+                return HashMap::new();
+            }
+            let Ok(output) = std::process::Command::new("netsh").args(["wlan", "show", "interfaces"]).output() else {
+                return HashMap::new();
+            };
+            // netsh names interfaces the way sysinfo does, up to case
+            parse_netsh_wlan(&String::from_utf8_lossy(&output.stdout))
+                .into_iter()
+                .filter_map(|(name, info)| {
+                    let interface = interface_names.iter().find(|n| n.eq_ignore_ascii_case(&name))?;
+                    Some((interface.clone(), info))
+                })
+                .collect()
+        }
+
+        #[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+        {
+            let _ = interface_names;
+            HashMap::new()
+        }
+    }
+}
+
+/// The link of a Linux wireless interface, given its `/proc/net/wireless`
+/// reading; `None` when it isn't connected
+#[cfg(target_os = "linux")]
+fn read_linux_link(interface_name: &str, proc_wireless: Option<(f32, i32)>) -> Option<WirelessInfo> {
+    let iw = std::process::Command::new("iw")
+        .args(["dev", interface_name, "link"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success());
+    match iw {
+        Some(output) => {
+            let mut info = parse_iw_link(&String::from_utf8_lossy(&output.stdout))?;
+            // The driver's own quality figure beats one derived from dBm
+            if let Some((quality, _)) = proc_wireless {
+                info.link_quality_percent = quality;
+            }
+            Some(info)
+        }
+        // Without iw the SSID and rates are unknown; a zero quality
+        // means the interface isn't associated
+        None => proc_wireless
+            .filter(|(quality, _)| *quality > 0.0)
+            .map(|(link_quality_percent, signal_dbm)| WirelessInfo {
+                signal_dbm,
+                link_quality_percent,
+                ..Default::default()
+            }),
+    }
+}
+
+/// Counts the connections in `/proc/net/tcp` or `/proc/net/tcp6` content,
+/// whose fourth column is the state in hex, e.g.
+/// `0: 0100007F:0277 00000000:0000 0A 00000000:00000000 00:00000000 00000000 ...`
+pub fn parse_proc_net_tcp(content: &str, stats: &mut TcpStats) {
+    // Skip the header row
+    for line in content.lines().skip(1) {
+        let state = match line.split_whitespace().nth(3) {
+            Some("01") => "ESTABLISHED",
+            Some("02") => "SYN_SENT",
+            Some("06") => "TIME_WAIT",
+            Some("08") => "CLOSE_WAIT",
+            Some("0A") => "LISTEN",
+            _ => continue,
+        };
+        stats.count(state);
+    }
+}
+
+/// Counts the TCP connections in `netstat -an -p tcp` output on macOS, where
+/// the state is the last column of the `tcp4`/`tcp6` rows
+pub fn parse_netstat_tcp(output: &str) -> TcpStats {
+    let mut stats = TcpStats::default();
+    for line in output.lines().filter(|line| line.starts_with("tcp")) {
+        if let Some(state) = line.split_whitespace().last() {
+            stats.count(state);
+        }
+    }
+    stats
+}
+
+/// Reads the host's TCP connection states; `None` where that isn't
+/// supported or the table can't be read
+pub fn read_tcp_stats() -> Option<TcpStats> {
+    #[cfg(target_os = "linux")]
+    {
+        let mut stats = TcpStats::default();
+        let mut read_any = false;
+        // tcp6 is missing when IPv6 is disabled
+        for path in ["/proc/net/tcp", "/proc/net/tcp6"] {
+            if let Ok(content) = std::fs::read_to_string(path) {
+                parse_proc_net_tcp(&content, &mut stats);
+                read_any = true;
+            }
+        }
+        read_any.then_some(stats)
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        tcp_table::read_tcp_stats()
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        let output = std::process::Command::new("netstat").args(["-an", "-p", "tcp"]).output().ok()?;
+        output.status.success().then(|| parse_netstat_tcp(&String::from_utf8_lossy(&output.stdout)))
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "windows", target_os = "macos")))]
+    {
+        None
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod tcp_table {
+    use crate::core::TcpStats;
+
+    const AF_INET: u32 = 2;
+    const AF_INET6: u32 = 23;
+    // The basic table classes are IPv4 only
+    const TCP_TABLE_OWNER_PID_ALL: u32 = 5;
+    const NO_ERROR: u32 = 0;
+    const ERROR_INSUFFICIENT_BUFFER: u32 = 122;
+
+    // Size of MIB_TCPROW_OWNER_PID and MIB_TCP6ROW_OWNER_PID, and the offset
+    // of the state in each
+    const ROW_SIZE_V4: usize = 24;
+    const STATE_OFFSET_V4: usize = 0;
+    const ROW_SIZE_V6: usize = 56;
+    const STATE_OFFSET_V6: usize = 48;
+
+    #[link(name = "iphlpapi")]
+    extern "system" {
+        fn GetExtendedTcpTable(
+            table: *mut std::ffi::c_void,
+            size: *mut u32,
+            order: i32,
+            address_family: u32,
+            table_class: u32,
+            reserved: u32,
+        ) -> u32;
+    }
+
+    /// The rows of one address family's table as raw bytes, after the
+    /// leading entry count
+    fn read_table(address_family: u32) -> Option<(u32, Vec<u8>)> {
+        let mut size = 0u32;
+        let mut buffer: Vec<u32> = Vec::new();
+        // The table can grow between the sizing call and the read
+        for _ in 0..3 {
+            let result = unsafe {
+                GetExtendedTcpTable(buffer.as_mut_ptr().cast(), &mut size, 0, address_family, TCP_TABLE_OWNER_PID_ALL, 0)
+            };
+            match result {
+                NO_ERROR if !buffer.is_empty() => {
+                    let count = buffer[0];
+                    let bytes: Vec<u8> = buffer[1..].iter().flat_map(|word| word.to_ne_bytes()).collect();
+                    return Some((count, bytes));
+                }
+                NO_ERROR | ERROR_INSUFFICIENT_BUFFER => buffer = vec![0; (size as usize).div_ceil(4).max(1)],
+                _ => return None,
+            }
+        }
+        None
+    }
+
+    /// Maps a MIB_TCP_STATE to the name `TcpStats::count` takes
+    fn state_name(state: u32) -> &'static str {
+        match state {
+            2 => "LISTEN",
+            3 => "SYN_SENT",
+            5 => "ESTABLISHED",
+            8 => "CLOSE_WAIT",
+            11 => "TIME_WAIT",
+            _ => "",
+        }
+    }
+
+    pub fn read_tcp_stats() -> Option<TcpStats> {
+        let mut stats = TcpStats::default();
+        let mut read_any = false;
+        for (family, row_size, state_offset) in
+            [(AF_INET, ROW_SIZE_V4, STATE_OFFSET_V4), (AF_INET6, ROW_SIZE_V6, STATE_OFFSET_V6)]
+        {
+            let Some((count, rows)) = read_table(family) else {
+                continue;
+            };
+            read_any = true;
+            for row in rows.chunks_exact(row_size).take(count as usize) {
+                let state = u32::from_ne_bytes(row[state_offset..state_offset + 4].try_into().unwrap());
+                stats.count(state_name(state));
+            }
+        }
+        read_any.then_some(stats)
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod adapters {
+    use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+    use windows::Win32::Foundation::{ERROR_BUFFER_OVERFLOW, NO_ERROR};
+    use windows::Win32::NetworkManagement::IpHelper::{
+        GetAdaptersAddresses, GAA_FLAG_SKIP_ANYCAST, GAA_FLAG_SKIP_DNS_SERVER, GAA_FLAG_SKIP_MULTICAST,
+        IP_ADAPTER_ADDRESSES_LH,
+    };
+    use windows::Win32::NetworkManagement::Ndis::IfOperStatusUp;
+    use windows::Win32::Networking::WinSock::{AF_INET, AF_INET6, AF_UNSPEC, SOCKADDR_IN, SOCKADDR_IN6, SOCKET_ADDRESS};
+
+    use super::{format_mac, link_speed_mbps};
+    use crate::core::IpAddress;
+
+    /// Details of the adapter whose friendly name (the name sysinfo reports)
+    /// is `interface_name`: `(is_up, mac_address, ip_addresses, speed_mbps)`
+    pub fn details(interface_name: &str) -> Option<(bool, String, Vec<IpAddress>, Option<u64>)> {
+        let flags = GAA_FLAG_SKIP_ANYCAST | GAA_FLAG_SKIP_MULTICAST | GAA_FLAG_SKIP_DNS_SERVER;
+        let mut size = 0u32;
+        // u64 words keep the adapter structs aligned
+        let mut buffer: Vec<u64> = Vec::new();
+        // The list can grow between the sizing call and the read
+        for _ in 0..3 {
+            let head = if buffer.is_empty() { None } else { Some(buffer.as_mut_ptr().cast::<IP_ADAPTER_ADDRESSES_LH>()) };
+            let result = unsafe { GetAdaptersAddresses(AF_UNSPEC.0 as u32, flags, None, head, &mut size) };
+            match result {
+                code if code == NO_ERROR.0 && !buffer.is_empty() => {
+                    return unsafe { find(buffer.as_ptr().cast(), interface_name) };
+                }
+                code if code == NO_ERROR.0 || code == ERROR_BUFFER_OVERFLOW.0 => {
+                    buffer = vec![0; (size as usize).div_ceil(8).max(1)];
+                }
+                _ => return None,
+            }
+        }
+        None
+    }
+
+    /// Walks the adapter list GetAdaptersAddresses filled in from `adapter`
+    unsafe fn find(
+        mut adapter: *const IP_ADAPTER_ADDRESSES_LH,
+        interface_name: &str,
+    ) -> Option<(bool, String, Vec<IpAddress>, Option<u64>)> {
+        while let Some(current) = adapter.as_ref() {
+            adapter = current.Next;
+            let Ok(name) = current.FriendlyName.to_string() else {
+                continue;
+            };
+            if !name.eq_ignore_ascii_case(interface_name) {
+                continue;
+            }
+
+            let length = (current.PhysicalAddressLength as usize).min(current.PhysicalAddress.len());
+            let mut ip_addresses = Vec::new();
+            let mut unicast = current.FirstUnicastAddress;
+            while let Some(address) = unicast.as_ref() {
+                ip_addresses.extend(ip_addr(&address.Address).map(|ip| IpAddress::new(ip, address.OnLinkPrefixLength)));
+                unicast = address.Next;
+            }
+
+            return Some((
+                current.OperStatus == IfOperStatusUp,
+                format_mac(&current.PhysicalAddress[..length]),
+                ip_addresses,
+                link_speed_mbps(current.TransmitLinkSpeed),
+            ));
+        }
+        None
+    }
+
+    unsafe fn ip_addr(address: &SOCKET_ADDRESS) -> Option<IpAddr> {
+        let sockaddr = address.lpSockaddr.as_ref()?;
+        if sockaddr.sa_family == AF_INET {
+            let ipv4 = &*address.lpSockaddr.cast::<SOCKADDR_IN>();
+            Some(IpAddr::V4(Ipv4Addr::from(u32::from_be(ipv4.sin_addr.S_un.S_addr))))
+        } else if sockaddr.sa_family == AF_INET6 {
+            let ipv6 = &*address.lpSockaddr.cast::<SOCKADDR_IN6>();
+            Some(IpAddr::V6(Ipv6Addr::from(ipv6.sin6_addr.u.Byte)))
+        } else {
+            None
+        }
+    }
+}
+
+/// Matches `text` against a glob supporting `*` (any run) and `?` (any one
+/// character)
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // Position after the last `*`, and the text position it was tried at
+    let mut backtrack = None;
+
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p + 1, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                // Let the last `*` absorb one more character
+                Some((after_star, tried)) => {
+                    p = after_star;
+                    t = tried + 1;
+                    backtrack = Some((after_star, tried + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Whether `config` asks for `interface_name` to be reported
+pub fn interface_included(config: &MonitorConfig, interface_name: &str) -> bool {
+    if !config.include_loopback && is_loopback_interface(interface_name) {
+        return false;
+    }
+    match &config.interface_filter {
+        Some(patterns) => patterns.iter().any(|pattern| glob_match(pattern, interface_name)),
+        None => true,
+    }
+}
+
+impl Default for NetworkMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl NetworkMonitor {
     pub fn new() -> Self {
+        Self::with_clock(SystemClock::shared())
+    }
+
+    /// Timestamps samples and computes rates by `clock`
+    pub fn with_clock(clock: SharedClock) -> Self {
         Self {
-            state: Arc::new(RwLock::new(MonitorState::Uninitialized)),
-            config: Arc::new(RwLock::new(MonitorConfig::default())),
+            shared: MonitorShared::new(clock.now()),
             system: Arc::new(RwLock::new(System::new_with_specifics(RefreshKind::everything()))),
             metrics_history: Arc::new(RwLock::new(VecDeque::new())),
-            last_update: Arc::new(RwLock::new(SystemTime::now())),
             previous_stats: Arc::new(RwLock::new(HashMap::new())),
+            wireless: WirelessReader::default(),
+            clock,
         }
     }
 
+    /// Send and receive rates of `interface_name`, in bytes/s, from its
+    /// cumulative byte counters against those of the previous call for it,
+    /// timed by the monitor's clock
+    ///
+    /// Both are 0 the first time an interface is seen, and a counter that
+    /// went backwards (a driver reload) reads as 0 rather than wrapping.
+    pub fn record_byte_counters(&self, interface_name: &str, bytes_sent: u64, bytes_received: u64) -> (u64, u64) {
+        let now = self.clock.instant();
+        let current = NetworkStats { bytes_sent, bytes_received, taken_at: now };
+        let previous = self.previous_stats.write().insert(interface_name.to_string(), current);
+        let Some(previous) = previous else {
+            return (0, 0);
+        };
+
+        let secs = now.duration_since(previous.taken_at).as_secs_f64();
+        (
+            counter_rate(bytes_sent, previous.bytes_sent, secs) as u64,
+            counter_rate(bytes_received, previous.bytes_received, secs) as u64,
+        )
+    }
+
     fn collect_network_metrics(&self) -> Result<Vec<NetworkMetrics>> {
         let mut networks = Networks::new_with_refreshed_list();
         networks.refresh();
 
         let mut metrics = Vec::new();
-        let mut current_stats = HashMap::new();
-        let now = SystemTime::now();
-        let previous_stats = self.previous_stats.read();
+        let mut reported = HashSet::new();
+        let config = self.shared.config.read().clone();
 
-        for (interface_name, network) in networks.iter() {
+        // Excluded interfaces aren't reported, so their previous counters
+        // are dropped below
+        for (interface_name, network) in networks.iter().filter(|(name, _)| interface_included(&config, name)) {
             let bytes_sent = network.total_transmitted();
             let bytes_received = network.total_received();
             let packets_sent = network.total_packets_transmitted();
@@ -64,41 +723,15 @@ impl NetworkMonitor {
             let errors_sent = network.total_errors_on_transmitted();
             let errors_received = network.total_errors_on_received();
 
-            // Store current stats for rate calculation
-            let stats = NetworkStats {
-                bytes_sent,
-                bytes_received,
-                packets_sent,
-                packets_received,
-                errors_sent,
-                errors_received,
-                timestamp: now,
-            };
-
-            // Calculate rates if we have previous stats
-            let (bytes_sent_rate, bytes_received_rate) = if let Some(prev_stats) = previous_stats.get(interface_name) {
-                if let Ok(duration) = now.duration_since(prev_stats.timestamp) {
-                    let secs = duration.as_secs_f64();
-                    if secs > 0.0 {
-                        let sent_rate = ((bytes_sent.saturating_sub(prev_stats.bytes_sent)) as f64 / secs) as u64;
-                        let recv_rate = ((bytes_received.saturating_sub(prev_stats.bytes_received)) as f64 / secs) as u64;
-                        (sent_rate, recv_rate)
-                    } else {
-                        (0, 0)
-                    }
-                } else {
-                    (0, 0)
-                }
-            } else {
-                (0, 0)
-            };
-
-            current_stats.insert(interface_name.clone(), stats);
+            let (bytes_sent_rate, bytes_received_rate) =
+                self.record_byte_counters(interface_name, bytes_sent, bytes_received);
+            reported.insert(interface_name.clone());
 
             // Get additional interface information
             let (is_up, mac_address, ip_addresses, speed_mbps) = self.get_interface_details(interface_name);
 
             metrics.push(NetworkMetrics {
+                id: interface_name.clone(),
                 interface_name: interface_name.clone(),
                 is_up,
                 mac_address,
@@ -112,25 +745,42 @@ impl NetworkMonitor {
                 speed_mbps,
                 bytes_sent_rate,
                 bytes_received_rate,
+                // Filled in by `fill_wireless`
+                wireless: None,
             });
         }
 
-        // Update previous stats for next calculation
-        *self.previous_stats.write() = current_stats;
+        self.previous_stats.write().retain(|name, _| reported.contains(name));
 
+        // sysinfo keeps interfaces in a HashMap, so its order varies
+        metrics.sort_by(|a, b| a.interface_name.cmp(&b.interface_name));
         Ok(metrics)
     }
 
-    fn get_interface_details(&self, interface_name: &str) -> (bool, String, Vec<String>, Option<u64>) {
-        let is_up = true;
-        let mac_address = String::from("00:00:00:00:00:00");
-        let ip_addresses = Vec::new();
-        let speed_mbps = None;
+    /// Reads the radio links of `interfaces` on the blocking pool
+    async fn fill_wireless(&self, interfaces: &mut [NetworkMetrics]) {
+        // A down interface can't be associated, so skip the wireless tools
+        let up: Vec<String> = interfaces.iter().filter(|n| n.is_up).map(|n| n.interface_name.clone()).collect();
+        if up.is_empty() {
+            return;
+        }
+
+        let reader = self.wireless.clone();
+        let mut links = tokio::task::spawn_blocking(move || reader.read(&up)).await.unwrap_or_default();
+        for network in interfaces {
+            network.wireless = links.remove(&network.interface_name);
+        }
+    }
+
+    fn get_interface_details(&self, interface_name: &str) -> (bool, String, Vec<IpAddress>, Option<u64>) {
+        let mut is_up = true;
+        let mut mac_address = String::from("00:00:00:00:00:00");
+        let mut ip_addresses = Vec::new();
+        let mut speed_mbps = None;
 
         #[cfg(target_os = "linux")]
         {
             use std::fs;
-            use std::path::Path;
 
             // Check if interface is up
             let state_path = format!("/sys/class/net/{}/operstate", interface_name);
@@ -156,148 +806,45 @@ impl NetworkMonitor {
 
             // Get IP addresses using ip command
             if let Ok(output) = std::process::Command::new("ip")
-                .args(&["addr", "show", interface_name])
+                .args(["addr", "show", interface_name])
                 .output()
             {
-                let output_str = String::from_utf8_lossy(&output.stdout);
-                for line in output_str.lines() {
-                    if line.contains("inet ") {
-                        if let Some(ip_part) = line.split_whitespace().nth(1) {
-                            if let Some(ip) = ip_part.split('/').next() {
-                                ip_addresses.push(ip.to_string());
-                            }
-                        }
-                    }
-                }
+                ip_addresses = parse_ip_addr_show(&String::from_utf8_lossy(&output.stdout));
             }
         }
 
         #[cfg(target_os = "windows")]
-        {
-            // Windows implementation would use WMI or iphlpapi
-            // This is a placeholder
-            use std::process::Command;
-            if let Ok(output) = Command::new("wmic")
-                .args(&["nic", "where", &format!("NetConnectionID='{}'", interface_name), "get", "MACAddress,Speed,NetConnectionStatus"])
-                .output()
-            {
-                let _output_str = String::from_utf8_lossy(&output.stdout);
-                // Parse WMI output
-                // This is simplified
-            }
+        if let Some(details) = adapters::details(interface_name) {
+            (is_up, mac_address, ip_addresses, speed_mbps) = details;
         }
 
         #[cfg(target_os = "macos")]
         {
-            // macOS implementation would use ifconfig or system configuration framework
             use std::process::Command;
             if let Ok(output) = Command::new("ifconfig")
                 .arg(interface_name)
                 .output()
             {
-                let output_str = String::from_utf8_lossy(&output.stdout);
-                // Parse ifconfig output
-                for line in output_str.lines() {
-                    if line.contains("ether") {
-                        if let Some(mac) = line.split_whitespace().nth(1) {
-                            mac_address = mac.to_string();
-                        }
-                    } else if line.contains("inet ") {
-                        if let Some(ip) = line.split_whitespace().nth(1) {
-                            ip_addresses.push(ip.to_string());
-                        }
-                    }
+                let (up, mac, ips, speed) = parse_ifconfig(&String::from_utf8_lossy(&output.stdout));
+                if let Some(up) = up {
+                    is_up = up;
                 }
+                if let Some(mac) = mac {
+                    mac_address = mac;
+                }
+                ip_addresses = ips;
+                speed_mbps = speed;
             }
         }
 
         (is_up, mac_address, ip_addresses, speed_mbps)
     }
 
-    fn update_history(&self, metrics: Vec<NetworkMetrics>) {
-        let mut history = self.metrics_history.write();
-        let config = self.config.read();
-        
-        history.push_back(metrics);
-        
-        // Remove old metrics based on retention policy
-        let max_entries = (config.retain_history_seconds * 1000 / config.interval_ms) as usize;
-        while history.len() > max_entries {
-            history.pop_front();
-        }
-    }
-}
-
-#[async_trait]
-impl Monitor for NetworkMonitor {
-    fn name(&self) -> &str {
-        "Network Monitor"
-    }
-
-    fn state(&self) -> MonitorState {
-        *self.state.read()
-    }
-
-    async fn initialize(&mut self, config: MonitorConfig) -> Result<()> {
-        *self.state.write() = MonitorState::Initializing;
-        *self.config.write() = config;
-        
-        // Initialize network info
-        let mut networks = Networks::new_with_refreshed_list();
-        networks.refresh();
-        
-        // Collect initial stats
-        let _ = self.collect_network_metrics()?;
-        
-        *self.state.write() = MonitorState::Running;
-        Ok(())
-    }
-
-    async fn start(&mut self) -> Result<()> {
-        match self.state() {
-            MonitorState::Running => return Ok(()),
-            MonitorState::Uninitialized => {
-                return Err(MonitorError::NotInitialized);
-            }
-            _ => {}
-        }
-        
-        *self.state.write() = MonitorState::Running;
-        Ok(())
-    }
-
-    async fn stop(&mut self) -> Result<()> {
-        *self.state.write() = MonitorState::Stopped;
-        Ok(())
-    }
-
-    async fn pause(&mut self) -> Result<()> {
-        *self.state.write() = MonitorState::Paused;
-        Ok(())
-    }
-
-    async fn resume(&mut self) -> Result<()> {
-        *self.state.write() = MonitorState::Running;
-        Ok(())
-    }
-
-    async fn collect(&mut self) -> Result<Vec<Metric>> {
-        if self.state() != MonitorState::Running {
-            return Err(MonitorError::NotInitialized);
-        }
-
-        let network_metrics = self.collect_network_metrics()?;
-        self.update_history(network_metrics.clone());
-        *self.last_update.write() = SystemTime::now();
-
+    /// The metrics `collect()` reports for one sample
+    fn sample_to_metrics(sample: &NetworkSample) -> Vec<Metric> {
         let mut metrics = Vec::new();
         
-        for network in network_metrics.iter() {
-            // Skip loopback and inactive interfaces unless configured otherwise
-            if network.interface_name.contains("lo") && !self.config.read().include_loopback {
-                continue;
-            }
-
+        for network in sample.interfaces.iter() {
             // Network throughput rates
             metrics.push(Metric::new(
                 MetricType::NetworkThroughput,
@@ -375,22 +922,128 @@ impl Monitor for NetworkMonitor {
                     "Mbps",
                 ).with_tag("interface", &network.interface_name));
             }
+
+            if let Some(wireless) = &network.wireless {
+                metrics.push(wireless.to_metric().with_tag("interface", &network.interface_name));
+            }
+        }
+        
+        // Connection states are host-wide, so they carry no interface tag
+        if let Some(tcp) = &sample.tcp {
+            for (state, count) in tcp.by_state() {
+                metrics.push(Metric::new(
+                    MetricType::TcpConnections,
+                    MetricValue::Unsigned(count),
+                    "connections",
+                ).with_tag("state", state));
+            }
         }
         
+        metrics
+    }
+
+    fn update_history(&self, sample: NetworkSample) {
+        let mut history = self.metrics_history.write();
+        let config = self.shared.config.read();
+        
+        history.push_back((self.clock.now(), sample));
+        
+        // Remove old metrics based on retention policy
+        let max_entries = (config.retain_history_seconds * 1000 / config.interval_ms) as usize;
+        while history.len() > max_entries {
+            history.pop_front();
+        }
+
+        trim_history(&mut history, config.max_history_bytes);
+    }
+}
+
+#[async_trait]
+impl Monitor for NetworkMonitor {
+    fn name(&self) -> &str {
+        "Network Monitor"
+    }
+
+    fn state(&self) -> MonitorState {
+        self.shared.state.read().clone()
+    }
+
+    fn shared(&self) -> Option<&MonitorShared> {
+        Some(&self.shared)
+    }
+
+    async fn initialize(&mut self, config: MonitorConfig) -> Result<()> {
+        *self.shared.state.write() = MonitorState::Initializing;
+        *self.shared.config.write() = config;
+        
+        // Initialize network info
+        let mut networks = Networks::new_with_refreshed_list();
+        networks.refresh();
+        
+        // Collect initial stats
+        let _ = self.collect_network_metrics()?;
+        
+        *self.shared.state.write() = MonitorState::Running;
+        Ok(())
+    }
+
+
+    async fn start(&mut self) -> Result<()> {
+        match self.state() {
+            MonitorState::Running | MonitorState::Degraded { .. } => return Ok(()),
+            MonitorState::Uninitialized => {
+                return Err(MonitorError::NotInitialized);
+            }
+            _ => {}
+        }
+        
+        *self.shared.state.write() = MonitorState::Running;
+        Ok(())
+    }
+
+    async fn stop(&mut self) -> Result<()> {
+        *self.shared.state.write() = MonitorState::Stopped;
+        Ok(())
+    }
+
+    async fn pause(&mut self) -> Result<()> {
+        *self.shared.state.write() = MonitorState::Paused;
+        Ok(())
+    }
+
+    async fn resume(&mut self) -> Result<()> {
+        *self.shared.state.write() = MonitorState::Running;
+        Ok(())
+    }
+
+    async fn collect(&mut self) -> Result<Vec<Metric>> {
+        if !self.state().is_collecting() {
+            return Err(MonitorError::NotInitialized);
+        }
+
+        let mut result = self.collect_network_metrics();
+        if let Ok(interfaces) = &mut result {
+            self.fill_wireless(interfaces).await;
+        }
+        self.shared.state.write().record_collection(&result);
+        let sample = NetworkSample {
+            interfaces: result?,
+            tcp: read_tcp_stats(),
+        };
+        let metrics = Self::sample_to_metrics(&sample);
+        self.update_history(sample);
+        *self.shared.last_update.write() = self.clock.now();
+
         Ok(metrics)
     }
 
     async fn get_current_metrics(&self) -> Result<Vec<Metric>> {
         let history = self.metrics_history.read();
         
-        if let Some(latest) = history.back() {
+        if let Some((_, latest)) = history.back() {
             let mut metrics = Vec::new();
             
-            for network in latest.iter() {
-                if network.interface_name.contains("lo") && !self.config.read().include_loopback {
-                    continue;
-                }
-                
+            for network in latest.interfaces.iter() {
                 metrics.push(Metric::new(
                     MetricType::NetworkThroughput,
                     MetricValue::Unsigned(network.bytes_sent_rate + network.bytes_received_rate),
@@ -404,16 +1057,12 @@ impl Monitor for NetworkMonitor {
         }
     }
 
-    async fn get_historical_metrics(&self, _duration_seconds: u64) -> Result<Vec<Metric>> {
+    async fn get_historical_metrics(&self, duration_seconds: u64) -> Result<Vec<Metric>> {
         let history = self.metrics_history.read();
         let mut metrics = Vec::new();
         
-        for network_list in history.iter() {
-            for network in network_list.iter() {
-                if network.interface_name.contains("lo") && !self.config.read().include_loopback {
-                    continue;
-                }
-                
+        for sample in history_since(&history, duration_seconds, self.clock.now()) {
+            for network in sample.interfaces.iter() {
                 metrics.push(Metric::new(
                     MetricType::NetworkThroughput,
                     MetricValue::Unsigned(network.bytes_sent_rate + network.bytes_received_rate),
@@ -425,18 +1074,47 @@ impl Monitor for NetworkMonitor {
         Ok(metrics)
     }
 
+    async fn history_range(&self, start: SystemTime, end: SystemTime) -> Vec<(SystemTime, Vec<Metric>)> {
+        history_in_range(&self.metrics_history.read(), start, end, Self::sample_to_metrics)
+    }
+
     fn supports_feature(&self, feature: &str) -> bool {
         matches!(feature, 
             "network_throughput" | "network_bytes" | "network_packets" | 
-            "network_errors" | "network_status" | "network_speed"
+            "network_errors" | "network_status" | "network_speed" |
+            "tcp_connections" | "wireless_signal"
         )
     }
+
+    fn supported_metrics(&self) -> Vec<MetricType> {
+        let mut types = vec![
+            MetricType::NetworkThroughput,
+            MetricType::NetworkBytes,
+            MetricType::NetworkPackets,
+            MetricType::NetworkErrors,
+            MetricType::NetworkStatus,
+        ];
+        let history = self.metrics_history.read();
+        if let Some((_, latest)) = history.back() {
+            if latest.interfaces.iter().any(|n| n.speed_mbps.is_some()) {
+                types.push(MetricType::NetworkSpeed);
+            }
+            if latest.interfaces.iter().any(|n| n.wireless.is_some()) {
+                types.push(MetricType::WirelessSignal);
+            }
+            if latest.tcp.is_some() {
+                types.push(MetricType::TcpConnections);
+            }
+        }
+        types
+    }
 }
 
 // Extension for NetworkMetrics to include rate calculations
 impl NetworkMetrics {
     pub fn new(interface_name: String) -> Self {
         Self {
+            id: interface_name.clone(),
             interface_name,
             is_up: false,
             mac_address: String::from("00:00:00:00:00:00"),
@@ -450,6 +1128,7 @@ impl NetworkMetrics {
             speed_mbps: None,
             bytes_sent_rate: 0,
             bytes_received_rate: 0,
+            wireless: None,
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,76 @@
+//! Multi-Instance GPU (MIG) partitions of NVIDIA data-center GPUs such as
+//! the A100 and H100.
+//!
+//! nvml-wrapper 0.9 has no MIG calls, so the few needed here are resolved
+//! from the driver library through nvml-wrapper-sys. NVML keeps its state per
+//! process, so device handles from the `Nvml` instance are valid with this
+//! second library handle, and MIG device handles can be wrapped in a `Device`
+//! for the usual memory and process queries.
+
+use nvml_wrapper::Device;
+use nvml_wrapper_sys::bindings::{
+    nvmlDevice_t, nvmlReturn_enum_NVML_SUCCESS as NVML_SUCCESS, NvmlLib, NVML_DEVICE_MIG_ENABLE,
+};
+
+/// A MIG device, i.e. a compute instance within a GPU instance
+pub struct MigDevice<'nvml> {
+    pub gpu_instance_id: u32,
+    pub device: Device<'nvml>,
+}
+
+/// The MIG entry points of the driver library
+pub struct MigApi {
+    lib: NvmlLib,
+}
+
+impl MigApi {
+    /// None when the driver library can't be loaded or predates MIG
+    pub fn load() -> Option<Self> {
+        #[cfg(target_os = "windows")]
+        let paths = ["nvml.dll"];
+        // Same fallback as `GpuMonitor::init_nvml` for WSL2
+        #[cfg(not(target_os = "windows"))]
+        let paths = ["libnvidia-ml.so", "/usr/lib/wsl/lib/libnvidia-ml.so.1"];
+
+        paths.iter().find_map(|path| {
+            let lib = unsafe { NvmlLib::new(path) }.ok()?;
+            let supported = lib.nvmlDeviceGetMigMode.is_ok()
+                && lib.nvmlDeviceGetMaxMigDeviceCount.is_ok()
+                && lib.nvmlDeviceGetMigDeviceHandleByIndex.is_ok()
+                && lib.nvmlDeviceGetGpuInstanceId.is_ok();
+            supported.then_some(Self { lib })
+        })
+    }
+
+    /// Whether MIG mode is on; a pending change only applies after a GPU reset
+    pub fn is_enabled(&self, device: &Device) -> bool {
+        let (mut current, mut pending) = (0, 0);
+        let result = unsafe { self.lib.nvmlDeviceGetMigMode(device.handle(), &mut current, &mut pending) };
+        result == NVML_SUCCESS && current == NVML_DEVICE_MIG_ENABLE
+    }
+
+    /// The MIG devices currently created on `device`
+    pub fn devices<'nvml>(&self, device: &Device<'nvml>) -> Vec<MigDevice<'nvml>> {
+        let mut count = 0;
+        if unsafe { self.lib.nvmlDeviceGetMaxMigDeviceCount(device.handle(), &mut count) } != NVML_SUCCESS {
+            return Vec::new();
+        }
+
+        // Unused slots return NVML_ERROR_NOT_FOUND
+        (0..count)
+            .filter_map(|index| unsafe {
+                let mut handle: nvmlDevice_t = std::ptr::null_mut();
+                let mut gpu_instance_id = 0;
+                if self.lib.nvmlDeviceGetMigDeviceHandleByIndex(device.handle(), index, &mut handle) != NVML_SUCCESS
+                    || self.lib.nvmlDeviceGetGpuInstanceId(handle, &mut gpu_instance_id) != NVML_SUCCESS
+                {
+                    return None;
+                }
+                Some(MigDevice {
+                    gpu_instance_id,
+                    device: Device::new(handle, device.nvml()),
+                })
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,427 @@
+//! Per-process readings shared with the tauri crate, which includes this file
+
+/// Logical CPUs process `pid` may be scheduled on, ascending; empty when
+/// the affinity cannot be read or the platform has no notion of it, which
+/// callers should treat as all cores
+pub fn cpu_affinity(pid: u32) -> Vec<u32> {
+    #[cfg(target_os = "linux")]
+    {
+        std::fs::read_to_string(format!("/proc/{}/status", pid))
+            .ok()
+            .and_then(|status| parse_status_cpus_allowed(&status))
+            .unwrap_or_default()
+    }
+    #[cfg(target_os = "windows")]
+    {
+        affinity::affinity_mask(pid).map(cpus_in_mask).unwrap_or_default()
+    }
+    #[cfg(not(any(target_os = "linux", target_os = "windows")))]
+    {
+        let _ = pid;
+        Vec::new()
+    }
+}
+
+/// Reads the `Cpus_allowed_list:` line of `/proc/<pid>/status` content
+pub fn parse_status_cpus_allowed(content: &str) -> Option<Vec<u32>> {
+    content
+        .lines()
+        .find_map(|line| line.strip_prefix("Cpus_allowed_list:"))
+        .and_then(parse_cpu_list)
+}
+
+/// Parses a kernel CPU list such as `0-3,8,10-11` into ascending CPU
+/// numbers; `None` when any part is malformed
+pub fn parse_cpu_list(list: &str) -> Option<Vec<u32>> {
+    let list = list.trim();
+    if list.is_empty() {
+        return Some(Vec::new());
+    }
+
+    let mut cpus = Vec::new();
+    for part in list.split(',') {
+        match part.trim().split_once('-') {
+            Some((first, last)) => {
+                let (first, last): (u32, u32) = (first.parse().ok()?, last.parse().ok()?);
+                if first > last {
+                    return None;
+                }
+                cpus.extend(first..=last);
+            }
+            None => cpus.push(part.trim().parse().ok()?),
+        }
+    }
+    cpus.sort_unstable();
+    cpus.dedup();
+    Some(cpus)
+}
+
+/// CPU numbers of the bits set in an affinity mask
+pub fn cpus_in_mask(mask: u64) -> Vec<u32> {
+    (0..u64::BITS).filter(|bit| mask & (1 << bit) != 0).collect()
+}
+
+/// Number of threads in process `pid`, or 1 when it cannot be determined
+pub fn thread_count(pid: u32) -> u32 {
+    #[cfg(target_os = "linux")]
+    {
+        if let Some(count) = linux_thread_count(pid) {
+            return count;
+        }
+    }
+    #[cfg(target_os = "windows")]
+    {
+        if let Some(count) = toolhelp::thread_counts().get(&pid) {
+            return *count;
+        }
+    }
+    let _ = pid;
+    1
+}
+
+/// Reads the `Threads:` line of `/proc/<pid>/status` content
+pub fn parse_status_threads(content: &str) -> Option<u32> {
+    content
+        .lines()
+        .find_map(|line| line.strip_prefix("Threads:"))
+        .and_then(|count| count.trim().parse().ok())
+}
+
+#[cfg(target_os = "linux")]
+fn linux_thread_count(pid: u32) -> Option<u32> {
+    use std::fs;
+
+    if let Some(count) = fs::read_to_string(format!("/proc/{}/status", pid))
+        .ok()
+        .and_then(|status| parse_status_threads(&status))
+    {
+        return Some(count);
+    }
+
+    // Fall back to counting the task directory
+    let entries = fs::read_dir(format!("/proc/{}/task", pid)).ok()?;
+    Some(entries.filter_map(|e| e.ok()).count() as u32)
+}
+
+/// Number of open file descriptors (handles on Windows) in process `pid`, or
+/// `None` when they cannot be read
+pub fn handle_count(pid: u32) -> Option<u32> {
+    #[cfg(target_os = "linux")]
+    {
+        let entries = std::fs::read_dir(format!("/proc/{}/fd", pid)).ok()?;
+        Some(entries.filter_map(|e| e.ok()).count() as u32)
+    }
+    #[cfg(target_os = "windows")]
+    {
+        handles::handle_count(pid)
+    }
+    #[cfg(target_os = "macos")]
+    {
+        libproc::fd_count(pid)
+    }
+    #[cfg(not(any(target_os = "linux", target_os = "windows", target_os = "macos")))]
+    {
+        let _ = pid;
+        None
+    }
+}
+
+/// User plus kernel CPU time process `pid` has consumed, in seconds, or
+/// `None` when it cannot be read
+pub fn cpu_time_seconds(pid: u32) -> Option<f64> {
+    #[cfg(target_os = "linux")]
+    {
+        let stat = std::fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+        let ticks = parse_stat_cpu_ticks(&stat)?;
+        Some(ticks as f64 / clock_ticks_per_second())
+    }
+    #[cfg(target_os = "windows")]
+    {
+        process_times::cpu_time_seconds(pid)
+    }
+    #[cfg(target_os = "macos")]
+    {
+        libproc::cpu_time_seconds(pid)
+    }
+    #[cfg(not(any(target_os = "linux", target_os = "windows", target_os = "macos")))]
+    {
+        let _ = pid;
+        None
+    }
+}
+
+/// Sums the `utime` and `stime` fields of `/proc/<pid>/stat` content, in
+/// clock ticks
+pub fn parse_stat_cpu_ticks(content: &str) -> Option<u64> {
+    // The command name is in parentheses and may itself contain spaces or
+    // parentheses, so fields are counted from the last `)`; the first field
+    // after it is the state, field 3 of the file
+    let fields: Vec<&str> = content[content.rfind(')')? + 1..].split_whitespace().collect();
+    let utime: u64 = fields.get(11)?.parse().ok()?;
+    let stime: u64 = fields.get(12)?.parse().ok()?;
+    Some(utime + stime)
+}
+
+#[cfg(target_os = "windows")]
+mod affinity {
+    const PROCESS_QUERY_LIMITED_INFORMATION: u32 = 0x1000;
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn OpenProcess(desired_access: u32, inherit_handle: i32, process_id: u32) -> isize;
+        fn GetProcessAffinityMask(process: isize, process_mask: *mut usize, system_mask: *mut usize) -> i32;
+        fn CloseHandle(handle: isize) -> i32;
+    }
+
+    /// The process affinity mask, which only covers the process's processor
+    /// group on systems with more than 64 logical CPUs
+    pub fn affinity_mask(pid: u32) -> Option<u64> {
+        // SAFETY: the process handle is checked before use and closed once
+        unsafe {
+            let process = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, pid);
+            if process == 0 {
+                return None;
+            }
+
+            let (mut process_mask, mut system_mask) = (0usize, 0usize);
+            let ok = GetProcessAffinityMask(process, &mut process_mask, &mut system_mask);
+            CloseHandle(process);
+
+            (ok != 0).then_some(process_mask as u64)
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn clock_ticks_per_second() -> f64 {
+    // SAFETY: sysconf only reads a configuration value
+    let ticks = unsafe { libc::sysconf(libc::_SC_CLK_TCK) };
+    if ticks > 0 { ticks as f64 } else { 100.0 }
+}
+
+#[cfg(target_os = "windows")]
+mod process_times {
+    const PROCESS_QUERY_LIMITED_INFORMATION: u32 = 0x1000;
+
+    #[repr(C)]
+    #[derive(Default)]
+    struct FileTime {
+        low: u32,
+        high: u32,
+    }
+
+    impl FileTime {
+        /// FILETIME durations count 100-nanosecond intervals
+        fn seconds(&self) -> f64 {
+            (((self.high as u64) << 32) | self.low as u64) as f64 / 10_000_000.0
+        }
+    }
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn OpenProcess(desired_access: u32, inherit_handle: i32, process_id: u32) -> isize;
+        fn GetProcessTimes(
+            process: isize,
+            creation: *mut FileTime,
+            exit: *mut FileTime,
+            kernel: *mut FileTime,
+            user: *mut FileTime,
+        ) -> i32;
+        fn CloseHandle(handle: isize) -> i32;
+    }
+
+    pub fn cpu_time_seconds(pid: u32) -> Option<f64> {
+        let (mut creation, mut exit) = (FileTime::default(), FileTime::default());
+        let (mut kernel, mut user) = (FileTime::default(), FileTime::default());
+
+        // SAFETY: the process handle is checked before use and closed once
+        unsafe {
+            let process = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, pid);
+            if process == 0 {
+                return None;
+            }
+
+            let ok = GetProcessTimes(process, &mut creation, &mut exit, &mut kernel, &mut user);
+            CloseHandle(process);
+
+            (ok != 0).then(|| kernel.seconds() + user.seconds())
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod handles {
+    const PROCESS_QUERY_LIMITED_INFORMATION: u32 = 0x1000;
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn OpenProcess(desired_access: u32, inherit_handle: i32, process_id: u32) -> isize;
+        fn GetProcessHandleCount(process: isize, handle_count: *mut u32) -> i32;
+        fn CloseHandle(handle: isize) -> i32;
+    }
+
+    pub fn handle_count(pid: u32) -> Option<u32> {
+        // SAFETY: the process handle is checked before use and closed once
+        unsafe {
+            let process = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, pid);
+            if process == 0 {
+                return None;
+            }
+
+            let mut count = 0u32;
+            let ok = GetProcessHandleCount(process, &mut count);
+            CloseHandle(process);
+
+            (ok != 0).then_some(count)
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod libproc {
+    const PROC_PIDLISTFDS: i32 = 1;
+    const PROC_PIDTASKINFO: i32 = 4;
+
+    /// `struct proc_fdinfo` from `<sys/proc_info.h>`
+    #[repr(C)]
+    struct ProcFdInfo {
+        proc_fd: i32,
+        proc_fdtype: u32,
+    }
+
+    /// `struct proc_taskinfo` from `<sys/proc_info.h>`
+    #[repr(C)]
+    #[derive(Default)]
+    struct ProcTaskInfo {
+        pti_virtual_size: u64,
+        pti_resident_size: u64,
+        pti_total_user: u64,
+        pti_total_system: u64,
+        pti_threads_user: u64,
+        pti_threads_system: u64,
+        pti_policy: i32,
+        pti_faults: i32,
+        pti_pageins: i32,
+        pti_cow_faults: i32,
+        pti_messages_sent: i32,
+        pti_messages_received: i32,
+        pti_syscalls_mach: i32,
+        pti_syscalls_unix: i32,
+        pti_csw: i32,
+        pti_threadnum: i32,
+        pti_numrunning: i32,
+        pti_priority: i32,
+    }
+
+    /// `struct mach_timebase_info` from `<mach/mach_time.h>`
+    #[repr(C)]
+    #[derive(Default)]
+    struct MachTimebaseInfo {
+        numer: u32,
+        denom: u32,
+    }
+
+    extern "C" {
+        fn proc_pidinfo(pid: i32, flavor: i32, arg: u64, buffer: *mut std::ffi::c_void, buffersize: i32) -> i32;
+        fn mach_timebase_info(info: *mut MachTimebaseInfo) -> i32;
+    }
+
+    pub fn cpu_time_seconds(pid: u32) -> Option<f64> {
+        let mut info = ProcTaskInfo::default();
+        let mut timebase = MachTimebaseInfo::default();
+        let size = std::mem::size_of::<ProcTaskInfo>() as i32;
+
+        // SAFETY: both calls write at most the size of the struct passed in
+        unsafe {
+            if proc_pidinfo(pid as i32, PROC_PIDTASKINFO, 0, (&mut info as *mut ProcTaskInfo).cast(), size) != size {
+                return None;
+            }
+            if mach_timebase_info(&mut timebase) != 0 || timebase.denom == 0 {
+                return None;
+            }
+        }
+
+        // Task times are in Mach absolute time units, which are nanoseconds
+        // only on Intel
+        let ticks = (info.pti_total_user + info.pti_total_system) as f64;
+        Some(ticks * timebase.numer as f64 / timebase.denom as f64 / 1_000_000_000.0)
+    }
+
+    pub fn fd_count(pid: u32) -> Option<u32> {
+        let entry_size = std::mem::size_of::<ProcFdInfo>() as i32;
+
+        // SAFETY: the first call only sizes the list; the second writes at
+        // most `buffersize` bytes into a buffer of that many entries
+        unsafe {
+            let needed = proc_pidinfo(pid as i32, PROC_PIDLISTFDS, 0, std::ptr::null_mut(), 0);
+            if needed <= 0 {
+                return None;
+            }
+
+            let mut entries: Vec<ProcFdInfo> = Vec::with_capacity((needed / entry_size) as usize);
+            let written = proc_pidinfo(
+                pid as i32,
+                PROC_PIDLISTFDS,
+                0,
+                entries.as_mut_ptr().cast(),
+                entries.capacity() as i32 * entry_size,
+            );
+            (written > 0).then(|| (written / entry_size) as u32)
+        }
+    }
+}
+
+/// Thread counts from a Toolhelp snapshot, which lists every thread on the
+/// system with its owning process
+#[cfg(target_os = "windows")]
+pub mod toolhelp {
+    use std::collections::HashMap;
+
+    const TH32CS_SNAPTHREAD: u32 = 0x0000_0004;
+    const INVALID_HANDLE_VALUE: isize = -1;
+
+    #[repr(C)]
+    struct ThreadEntry32 {
+        dw_size: u32,
+        cnt_usage: u32,
+        th32_thread_id: u32,
+        th32_owner_process_id: u32,
+        tp_base_pri: i32,
+        tp_delta_pri: i32,
+        dw_flags: u32,
+    }
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn CreateToolhelp32Snapshot(flags: u32, process_id: u32) -> isize;
+        fn Thread32First(snapshot: isize, entry: *mut ThreadEntry32) -> i32;
+        fn Thread32Next(snapshot: isize, entry: *mut ThreadEntry32) -> i32;
+        fn CloseHandle(handle: isize) -> i32;
+    }
+
+    pub fn thread_counts() -> HashMap<u32, u32> {
+        let mut counts = HashMap::new();
+
+        // SAFETY: the snapshot handle is checked before use and closed once,
+        // and the entry is sized as the API requires
+        unsafe {
+            let snapshot = CreateToolhelp32Snapshot(TH32CS_SNAPTHREAD, 0);
+            if snapshot == INVALID_HANDLE_VALUE {
+                return counts;
+            }
+
+            let mut entry: ThreadEntry32 = std::mem::zeroed();
+            entry.dw_size = std::mem::size_of::<ThreadEntry32>() as u32;
+
+            let mut more = Thread32First(snapshot, &mut entry);
+            while more != 0 {
+                *counts.entry(entry.th32_owner_process_id).or_insert(0) += 1;
+                more = Thread32Next(snapshot, &mut entry);
+            }
+
+            CloseHandle(snapshot);
+        }
+
+        counts
+    }
+}
@@ -2,31 +2,72 @@ use async_trait::async_trait;
 use parking_lot::RwLock;
 use std::collections::{HashMap, VecDeque};
 use std::sync::Arc;
-use std::time::{Duration, SystemTime};
-use sysinfo::{System, RefreshKind, ProcessRefreshKind};
+use std::time::{Duration, Instant, SystemTime};
+use sysinfo::{Pid, ProcessRefreshKind, RefreshKind, Signal, System};
 
 use crate::core::{
-    ProcessMetrics, Metric, MetricType, MetricValue, Monitor, MonitorConfig, MonitorError,
-    MonitorState, Result,
+    counter_rate, GroupedProcess, ProcessMetrics, ProcessQuery, history_in_range, history_since, TimedHistory, trim_history, Metric, MetricType, MetricValue, Monitor, MonitorConfig, MonitorShared, MonitorError,
+    MonitorState, ProcessGrouping, ProcessSortKey, Result, SharedClock, SystemClock,
 };
 
+pub use super::process_info::{
+    cpu_affinity, cpu_time_seconds, cpus_in_mask, handle_count, parse_cpu_list, parse_stat_cpu_ticks,
+    parse_status_cpus_allowed, parse_status_threads, thread_count,
+};
+#[cfg(target_os = "windows")]
+use super::process_info::toolhelp;
+
+/// Merges processes that share a key into one group per key, named after it
+pub fn group_processes<'a>(processes: impl IntoIterator<Item = (String, &'a ProcessMetrics)>) -> Vec<GroupedProcess> {
+    let mut groups: HashMap<String, GroupedProcess> = HashMap::new();
+    for (key, process) in processes {
+        groups
+            .entry(key)
+            .or_insert_with_key(|key| GroupedProcess::new(key.as_str()))
+            .add(process);
+    }
+    groups.into_values().collect()
+}
+
+/// Writes CPU numbers in the kernel's list form, collapsing runs into
+/// ranges; the inverse of `parse_cpu_list`
+pub fn format_cpu_list(cpus: &[u32]) -> String {
+    let mut ranges: Vec<(u32, u32)> = Vec::new();
+    for &cpu in cpus {
+        match ranges.last_mut() {
+            Some((_, last)) if cpu == *last + 1 => *last = cpu,
+            _ => ranges.push((cpu, cpu)),
+        }
+    }
+    ranges
+        .iter()
+        .map(|&(first, last)| if first == last { first.to_string() } else { format!("{}-{}", first, last) })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
 pub struct ProcessMonitor {
-    state: Arc<RwLock<MonitorState>>,
-    config: Arc<RwLock<MonitorConfig>>,
+    shared: MonitorShared,
     system: Arc<RwLock<System>>,
-    metrics_history: Arc<RwLock<VecDeque<Vec<ProcessMetrics>>>>,
-    last_update: Arc<RwLock<SystemTime>>,
+    // Each sample with the time it was collected
+    metrics_history: TimedHistory<Vec<ProcessMetrics>>,
     process_cpu_history: Arc<RwLock<HashMap<u32, f32>>>,
-    sort_by: Arc<RwLock<ProcessSortBy>>,
+    /// Cumulative disk I/O of every live process at the last collection
+    previous_disk_io: Arc<RwLock<HashMap<u32, DiskIoSample>>>,
     filter: Arc<RwLock<ProcessFilter>>,
+    clock: SharedClock,
 }
 
-#[derive(Debug, Clone, Copy)]
-pub enum ProcessSortBy {
-    Cpu,
-    Memory,
-    Name,
-    Pid,
+#[derive(Clone, Debug)]
+struct DiskIoSample {
+    /// Tells a reused PID apart from the process it replaced
+    start_time: u64,
+    read_bytes: u64,
+    written_bytes: u64,
+    taken_at: Instant,
+    /// Rates since the sample before, kept for `find_processes`
+    read_rate: u64,
+    write_rate: u64,
 }
 
 #[derive(Debug, Clone)]
@@ -48,28 +89,69 @@ impl Default for ProcessFilter {
     }
 }
 
+impl Default for ProcessMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl ProcessMonitor {
     pub fn new() -> Self {
+        Self::with_clock(SystemClock::shared())
+    }
+
+    /// Timestamps samples and computes rates by `clock`
+    pub fn with_clock(clock: SharedClock) -> Self {
         Self {
-            state: Arc::new(RwLock::new(MonitorState::Uninitialized)),
-            config: Arc::new(RwLock::new(MonitorConfig::default())),
+            shared: MonitorShared::new(clock.now()),
             system: Arc::new(RwLock::new(System::new_with_specifics(RefreshKind::everything()))),
             metrics_history: Arc::new(RwLock::new(VecDeque::new())),
-            last_update: Arc::new(RwLock::new(SystemTime::now())),
             process_cpu_history: Arc::new(RwLock::new(HashMap::new())),
-            sort_by: Arc::new(RwLock::new(ProcessSortBy::Cpu)),
+            previous_disk_io: Arc::new(RwLock::new(HashMap::new())),
             filter: Arc::new(RwLock::new(ProcessFilter::default())),
+            clock,
         }
     }
 
-    pub fn set_sort_by(&self, sort_by: ProcessSortBy) {
-        *self.sort_by.write() = sort_by;
+    pub fn set_sort_by(&self, sort_by: ProcessSortKey) {
+        self.shared.config.write().process_sort_key = sort_by;
     }
 
     pub fn set_filter(&self, filter: ProcessFilter) {
         *self.filter.write() = filter;
     }
 
+    /// Asks a process to exit with SIGTERM, or kills it outright with SIGKILL
+    /// when `force` is set. On Windows both end the process immediately.
+    ///
+    /// The process is only signalled if its start time still matches
+    /// `start_time`, so a reused PID is left alone. Returns `false` when no
+    /// such process exists any more.
+    pub fn terminate(pid: u32, start_time: SystemTime, force: bool) -> Result<bool> {
+        let mut system = System::new();
+        let sys_pid = Pid::from_u32(pid);
+        if !system.refresh_process(sys_pid) {
+            return Ok(false);
+        }
+        let process = match system.process(sys_pid) {
+            Some(process) => process,
+            None => return Ok(false),
+        };
+        if SystemTime::UNIX_EPOCH + Duration::from_secs(process.start_time()) != start_time {
+            return Ok(false);
+        }
+
+        let signal = if force { Signal::Kill } else { Signal::Term };
+        // kill_with returns None when the platform has no such signal
+        let delivered = process.kill_with(signal).unwrap_or_else(|| process.kill());
+
+        if delivered {
+            Ok(true)
+        } else {
+            Err(MonitorError::SystemError(format!("Failed to send {:?} to process {}", signal, pid)))
+        }
+    }
+
     fn collect_process_metrics(&self) -> Result<Vec<ProcessMetrics>> {
         let mut system = self.system.write();
         system.refresh_processes_specifics(ProcessRefreshKind::everything());
@@ -77,11 +159,34 @@ impl ProcessMonitor {
         let mut metrics = Vec::new();
         let filter = self.filter.read().clone();
         let total_memory = system.total_memory() * 1024; // Convert to bytes
+        let now = self.clock.instant();
+        let previous_disk_io = std::mem::take(&mut *self.previous_disk_io.write());
+        // Rebuilt every cycle from live processes, so exited PIDs drop out
+        let mut current_disk_io = HashMap::with_capacity(system.processes().len());
         
         for (pid, process) in system.processes() {
             let pid_u32 = pid.as_u32();
             let name = process.name().to_string();
             
+            // Track I/O for every process, so one that only now passes the
+            // filters already has a rate
+            let disk_usage = process.disk_usage();
+            let mut disk_io = DiskIoSample {
+                start_time: process.start_time(),
+                read_bytes: disk_usage.total_read_bytes,
+                written_bytes: disk_usage.total_written_bytes,
+                taken_at: now,
+                read_rate: 0,
+                write_rate: 0,
+            };
+            if let Some(prev) = previous_disk_io.get(&pid_u32).filter(|prev| prev.start_time == disk_io.start_time) {
+                let elapsed = now.duration_since(prev.taken_at).as_secs_f64();
+                disk_io.read_rate = counter_rate(disk_io.read_bytes, prev.read_bytes, elapsed) as u64;
+                disk_io.write_rate = counter_rate(disk_io.written_bytes, prev.written_bytes, elapsed) as u64;
+            }
+            let disk_rates = (disk_io.read_rate, disk_io.write_rate);
+            current_disk_io.insert(pid_u32, disk_io);
+            
             // Apply name filter
             if let Some(pattern) = &filter.name_pattern {
                 if !name.to_lowercase().contains(&pattern.to_lowercase()) {
@@ -102,48 +207,86 @@ impl ProcessMonitor {
                 continue;
             }
             
-            let memory_percent = if total_memory > 0 {
-                (memory_bytes as f32 / total_memory as f32) * 100.0
-            } else {
-                0.0
-            };
-            
-            let disk_usage = process.disk_usage();
-            let status = process.status().to_string();
-            
-            // Get process start time
-            let start_time = SystemTime::UNIX_EPOCH + Duration::from_secs(process.start_time());
-            
-            // Get thread count
-            #[cfg(target_os = "linux")]
-            let threads = self.get_linux_thread_count(pid_u32).unwrap_or(1);
-            #[cfg(not(target_os = "linux"))]
-            let threads = 1; // Default fallback
-            
-            metrics.push(ProcessMetrics {
-                pid: pid_u32,
-                name,
-                cpu_usage_percent: cpu_usage,
-                memory_bytes,
-                memory_percent,
-                disk_read_bytes: disk_usage.read_bytes,
-                disk_write_bytes: disk_usage.written_bytes,
-                status,
-                threads,
-                start_time,
-            });
+            metrics.push(Self::process_metrics(pid_u32, process, total_memory, disk_rates));
         }
         
+        drop(system);
+        *self.previous_disk_io.write() = current_disk_io;
+        
         // Sort processes based on selected criteria
         self.sort_processes(&mut metrics);
         
-        // Limit to top N processes if configured
-        let max_processes = self.config.read().max_processes.unwrap_or(100);
+        // Limit to top N processes if configured, never below the requested top count
+        let config = self.shared.config.read();
+        let max_processes = config.max_processes.unwrap_or(100).max(config.top_processes_count.unwrap_or(0));
         metrics.truncate(max_processes);
+        drop(config);
+        
+        // Only the processes kept pay for the system calls per process
+        #[cfg(target_os = "windows")]
+        let windows_threads = toolhelp::thread_counts();
+        for process in &mut metrics {
+            #[cfg(target_os = "windows")]
+            let threads = windows_threads.get(&process.pid).copied().unwrap_or(1);
+            #[cfg(not(target_os = "windows"))]
+            let threads = thread_count(process.pid);
+            Self::read_counts(process, threads);
+        }
         
         Ok(metrics)
     }
 
+    /// The metrics of one process that its sysinfo entry holds; the counts
+    /// that take a system call per process are left for `read_counts`
+    fn process_metrics(
+        pid: u32,
+        process: &sysinfo::Process,
+        total_memory: u64,
+        (disk_read_rate, disk_write_rate): (u64, u64),
+    ) -> ProcessMetrics {
+        let memory_bytes = process.memory() * 1024; // Convert KB to bytes
+        let memory_percent = if total_memory > 0 {
+            (memory_bytes as f32 / total_memory as f32) * 100.0
+        } else {
+            0.0
+        };
+        let disk_usage = process.disk_usage();
+        let cmd = process.cmd();
+
+        ProcessMetrics {
+            pid,
+            name: process.name().to_string(),
+            cpu_usage_percent: process.cpu_usage(),
+            memory_bytes,
+            memory_percent,
+            disk_read_bytes: disk_usage.total_read_bytes,
+            disk_write_bytes: disk_usage.total_written_bytes,
+            disk_read_bytes_per_sec: disk_read_rate,
+            disk_write_bytes_per_sec: disk_write_rate,
+            status: process.status().to_string(),
+            threads: 1,
+            start_time: SystemTime::UNIX_EPOCH + Duration::from_secs(process.start_time()),
+            parent_pid: process.parent().map(|parent| parent.as_u32()),
+            open_handles: 0,
+            handles_available: false,
+            cpu_time_seconds: 0.0,
+            elapsed_seconds: process.run_time(),
+            cmdline: (!cmd.is_empty()).then(|| cmd.join(" ")),
+            cpu_affinity: Vec::new(),
+        }
+    }
+
+    /// Fills in the open handles, CPU time and affinity; the thread count
+    /// comes from the caller, as Windows reads it for every process at once
+    fn read_counts(process: &mut ProcessMetrics, threads: u32) {
+        let open_handles = handle_count(process.pid);
+        process.threads = threads;
+        process.open_handles = open_handles.unwrap_or(0);
+        process.handles_available = open_handles.is_some();
+        process.cpu_time_seconds = cpu_time_seconds(process.pid).unwrap_or(0.0);
+        process.cpu_affinity = cpu_affinity(process.pid);
+    }
+
     fn is_system_process(&self, name: &str, pid: u32) -> bool {
         // Common system process patterns
         let system_patterns = [
@@ -163,41 +306,195 @@ impl ProcessMonitor {
         system_patterns.iter().any(|pattern| name_lower.contains(pattern))
     }
 
-    fn sort_processes(&self, processes: &mut Vec<ProcessMetrics>) {
-        let sort_by = *self.sort_by.read();
+    fn sort_processes(&self, processes: &mut [ProcessMetrics]) {
+        let sort_by = self.shared.config.read().process_sort_key;
         
         match sort_by {
-            ProcessSortBy::Cpu => {
+            ProcessSortKey::Cpu => {
                 processes.sort_by(|a, b| b.cpu_usage_percent.partial_cmp(&a.cpu_usage_percent).unwrap());
             }
-            ProcessSortBy::Memory => {
-                processes.sort_by(|a, b| b.memory_bytes.cmp(&a.memory_bytes));
+            ProcessSortKey::Memory => {
+                processes.sort_by_key(|p| std::cmp::Reverse(p.memory_bytes));
+            }
+            ProcessSortKey::DiskIo => {
+                processes.sort_by_key(|p| std::cmp::Reverse(p.disk_read_bytes_per_sec + p.disk_write_bytes_per_sec));
             }
-            ProcessSortBy::Name => {
+            ProcessSortKey::Name => {
                 processes.sort_by(|a, b| a.name.cmp(&b.name));
             }
-            ProcessSortBy::Pid => {
-                processes.sort_by(|a, b| a.pid.cmp(&b.pid));
+            ProcessSortKey::Pid => {
+                processes.sort_by_key(|a| a.pid);
             }
         }
     }
 
-    #[cfg(target_os = "linux")]
-    fn get_linux_thread_count(&self, pid: u32) -> Option<u32> {
-        use std::fs;
+    fn sort_groups(&self, groups: &mut [GroupedProcess]) {
+        match self.shared.config.read().process_sort_key {
+            ProcessSortKey::Cpu => {
+                groups.sort_by(|a, b| b.total_cpu.total_cmp(&a.total_cpu));
+            }
+            ProcessSortKey::Memory => {
+                groups.sort_by_key(|g| std::cmp::Reverse(g.total_memory));
+            }
+            ProcessSortKey::DiskIo => {
+                groups.sort_by_key(|g| std::cmp::Reverse(g.total_disk_read_bytes_per_sec + g.total_disk_write_bytes_per_sec));
+            }
+            ProcessSortKey::Name => {
+                groups.sort_by(|a, b| a.name.cmp(&b.name));
+            }
+            ProcessSortKey::Pid => {
+                groups.sort_by_key(|g| g.pids.first().copied());
+            }
+        }
+    }
+
+    /// Every process in the table as of the last collection, with only the
+    /// fields `process_metrics` fills, alongside its sysinfo entry
+    fn latest_processes<'a>(&self, system: &'a System) -> Vec<(&'a sysinfo::Process, ProcessMetrics)> {
+        let total_memory = system.total_memory() * 1024; // Convert to bytes
+        let disk_io = self.previous_disk_io.read();
         
-        let task_path = format!("/proc/{}/task", pid);
-        if let Ok(entries) = fs::read_dir(&task_path) {
-            let count = entries.filter_map(|e| e.ok()).count() as u32;
-            Some(count)
-        } else {
-            None
+        system
+            .processes()
+            .iter()
+            .map(|(pid, process)| {
+                let pid = pid.as_u32();
+                let disk_rates = disk_io
+                    .get(&pid)
+                    .filter(|sample| sample.start_time == process.start_time())
+                    .map_or((0, 0), |sample| (sample.read_rate, sample.write_rate));
+                (process, Self::process_metrics(pid, process, total_memory, disk_rates))
+            })
+            .collect()
+    }
+
+    /// The metrics `collect()` reports for one sample, with individual
+    /// metrics for the first `top_count` processes
+    fn processes_to_metrics(process_metrics: &[ProcessMetrics], top_count: usize) -> Vec<Metric> {
+        let mut metrics = Vec::new();
+        
+        // Add aggregated metrics
+        let total_processes = process_metrics.len();
+        let total_cpu_usage: f32 = process_metrics.iter().map(|p| p.cpu_usage_percent).sum();
+        let total_memory_usage: u64 = process_metrics.iter().map(|p| p.memory_bytes).sum();
+        
+        metrics.push(Metric::new(
+            MetricType::ProcessCount,
+            MetricValue::Integer(total_processes as i64),
+            "count",
+        ).with_tag("type", "monitored"));
+        
+        metrics.push(Metric::new(
+            MetricType::ProcessCpuTotal,
+            MetricValue::Float(total_cpu_usage as f64),
+            "%",
+        ));
+        
+        metrics.push(Metric::new(
+            MetricType::ProcessMemoryTotal,
+            MetricValue::Unsigned(total_memory_usage),
+            "bytes",
+        ));
+        
+        // Add individual process metrics for top processes
+        for (idx, process) in process_metrics.iter().take(top_count).enumerate() {
+            let rank = (idx + 1).to_string();
+            // Empty when the process has no parent
+            let parent_pid = process.parent_pid.map(|p| p.to_string()).unwrap_or_default();
+            
+            let mut cpu_metric = Metric::new(
+                MetricType::ProcessCpu,
+                MetricValue::Float(process.cpu_usage_percent as f64),
+                "%",
+            ).with_tag("pid", process.pid.to_string())
+             .with_tag("name", &process.name)
+             .with_tag("rank", &rank)
+             .with_tag("parent_pid", parent_pid.clone());
+            if let Some(cmdline) = &process.cmdline {
+                cpu_metric = cpu_metric.with_tag("cmdline", cmdline);
+            }
+            if !process.cpu_affinity.is_empty() {
+                cpu_metric = cpu_metric.with_tag("cpu_affinity", format_cpu_list(&process.cpu_affinity));
+            }
+            metrics.push(cpu_metric);
+            
+            metrics.push(Metric::new(
+                MetricType::ProcessMemory,
+                MetricValue::Unsigned(process.memory_bytes),
+                "bytes",
+            ).with_tag("pid", process.pid.to_string())
+             .with_tag("name", &process.name)
+             .with_tag("rank", &rank)
+             .with_tag("parent_pid", parent_pid.clone()));
+            
+            if process.handles_available {
+                metrics.push(Metric::new(
+                    MetricType::ProcessHandles,
+                    MetricValue::Unsigned(process.open_handles as u64),
+                    "count",
+                ).with_tag("pid", process.pid.to_string())
+                 .with_tag("name", &process.name));
+            }
+            
+            metrics.push(Metric::new(
+                MetricType::ProcessCpuTime,
+                MetricValue::Duration(Duration::from_secs_f64(process.cpu_time_seconds.max(0.0))),
+                "s",
+            ).with_tag("pid", process.pid.to_string())
+             .with_tag("name", &process.name));
+            
+            metrics.push(Metric::new(
+                MetricType::ProcessElapsed,
+                MetricValue::Duration(Duration::from_secs(process.elapsed_seconds)),
+                "s",
+            ).with_tag("pid", process.pid.to_string())
+             .with_tag("name", &process.name));
+            
+            if process.disk_read_bytes > 0 || process.disk_write_bytes > 0 {
+                metrics.push(Metric::new(
+                    MetricType::ProcessDiskIo,
+                    MetricValue::Unsigned(process.disk_read_bytes),
+                    "bytes",
+                ).with_tag("pid", process.pid.to_string())
+                 .with_tag("name", &process.name)
+                 .with_tag("operation", "read"));
+                
+                metrics.push(Metric::new(
+                    MetricType::ProcessDiskIo,
+                    MetricValue::Unsigned(process.disk_write_bytes),
+                    "bytes",
+                ).with_tag("pid", process.pid.to_string())
+                 .with_tag("name", &process.name)
+                 .with_tag("operation", "write"));
+                
+                metrics.push(Metric::new(
+                    MetricType::ProcessDiskIoRate,
+                    MetricValue::Unsigned(process.disk_read_bytes_per_sec),
+                    "bytes/s",
+                ).with_tag("pid", process.pid.to_string())
+                 .with_tag("name", &process.name)
+                 .with_tag("operation", "read"));
+                
+                metrics.push(Metric::new(
+                    MetricType::ProcessDiskIoRate,
+                    MetricValue::Unsigned(process.disk_write_bytes_per_sec),
+                    "bytes/s",
+                ).with_tag("pid", process.pid.to_string())
+                 .with_tag("name", &process.name)
+                 .with_tag("operation", "write"));
+            }
         }
+        
+        metrics
+    }
+
+    fn top_count(&self) -> usize {
+        self.shared.config.read().top_processes_count.unwrap_or(10)
     }
 
     fn update_history(&self, metrics: Vec<ProcessMetrics>) {
         let mut history = self.metrics_history.write();
-        let config = self.config.read();
+        let config = self.shared.config.read();
         
         // Update CPU history for better accuracy
         let mut cpu_history = self.process_cpu_history.write();
@@ -208,13 +505,15 @@ impl ProcessMonitor {
         // Clean up old CPU history entries
         cpu_history.retain(|pid, _| metrics.iter().any(|p| p.pid == *pid));
         
-        history.push_back(metrics);
+        history.push_back((self.clock.now(), metrics));
         
         // Remove old metrics based on retention policy
         let max_entries = (config.retain_history_seconds * 1000 / config.interval_ms) as usize;
         while history.len() > max_entries {
             history.pop_front();
         }
+
+        trim_history(&mut history, config.max_history_bytes);
     }
 }
 
@@ -225,130 +524,72 @@ impl Monitor for ProcessMonitor {
     }
 
     fn state(&self) -> MonitorState {
-        *self.state.read()
+        self.shared.state.read().clone()
+    }
+
+    fn shared(&self) -> Option<&MonitorShared> {
+        Some(&self.shared)
     }
 
     async fn initialize(&mut self, config: MonitorConfig) -> Result<()> {
-        *self.state.write() = MonitorState::Initializing;
-        *self.config.write() = config;
+        *self.shared.state.write() = MonitorState::Initializing;
+        *self.shared.config.write() = config;
         
         // Initialize system info
         let mut system = self.system.write();
         system.refresh_all();
         
-        *self.state.write() = MonitorState::Running;
+        *self.shared.state.write() = MonitorState::Running;
         Ok(())
     }
 
+
     async fn start(&mut self) -> Result<()> {
         match self.state() {
-            MonitorState::Running => return Ok(()),
+            MonitorState::Running | MonitorState::Degraded { .. } => return Ok(()),
             MonitorState::Uninitialized => {
                 return Err(MonitorError::NotInitialized);
             }
             _ => {}
         }
         
-        *self.state.write() = MonitorState::Running;
+        *self.shared.state.write() = MonitorState::Running;
         Ok(())
     }
 
     async fn stop(&mut self) -> Result<()> {
-        *self.state.write() = MonitorState::Stopped;
+        *self.shared.state.write() = MonitorState::Stopped;
         Ok(())
     }
 
     async fn pause(&mut self) -> Result<()> {
-        *self.state.write() = MonitorState::Paused;
+        *self.shared.state.write() = MonitorState::Paused;
         Ok(())
     }
 
     async fn resume(&mut self) -> Result<()> {
-        *self.state.write() = MonitorState::Running;
+        *self.shared.state.write() = MonitorState::Running;
         Ok(())
     }
 
     async fn collect(&mut self) -> Result<Vec<Metric>> {
-        if self.state() != MonitorState::Running {
+        if !self.state().is_collecting() {
             return Err(MonitorError::NotInitialized);
         }
 
-        let process_metrics = self.collect_process_metrics()?;
+        let result = self.collect_process_metrics();
+        self.shared.state.write().record_collection(&result);
+        let process_metrics = result?;
         self.update_history(process_metrics.clone());
-        *self.last_update.write() = SystemTime::now();
+        *self.shared.last_update.write() = self.clock.now();
 
-        let mut metrics = Vec::new();
-        
-        // Add aggregated metrics
-        let total_processes = process_metrics.len();
-        let total_cpu_usage: f32 = process_metrics.iter().map(|p| p.cpu_usage_percent).sum();
-        let total_memory_usage: u64 = process_metrics.iter().map(|p| p.memory_bytes).sum();
-        
-        metrics.push(Metric::new(
-            MetricType::ProcessCount,
-            MetricValue::Integer(total_processes as i64),
-            "count",
-        ).with_tag("type", "monitored"));
-        
-        metrics.push(Metric::new(
-            MetricType::ProcessCpuTotal,
-            MetricValue::Float(total_cpu_usage as f64),
-            "%",
-        ));
-        
-        metrics.push(Metric::new(
-            MetricType::ProcessMemoryTotal,
-            MetricValue::Unsigned(total_memory_usage),
-            "bytes",
-        ));
-        
-        // Add individual process metrics for top processes
-        let top_count = self.config.read().top_processes_count.unwrap_or(10);
-        for (idx, process) in process_metrics.iter().take(top_count).enumerate() {
-            let rank = (idx + 1).to_string();
-            
-            metrics.push(Metric::new(
-                MetricType::ProcessCpu,
-                MetricValue::Float(process.cpu_usage_percent as f64),
-                "%",
-            ).with_tag("pid", process.pid.to_string())
-             .with_tag("name", &process.name)
-             .with_tag("rank", &rank));
-            
-            metrics.push(Metric::new(
-                MetricType::ProcessMemory,
-                MetricValue::Unsigned(process.memory_bytes),
-                "bytes",
-            ).with_tag("pid", process.pid.to_string())
-             .with_tag("name", &process.name)
-             .with_tag("rank", &rank));
-            
-            if process.disk_read_bytes > 0 || process.disk_write_bytes > 0 {
-                metrics.push(Metric::new(
-                    MetricType::ProcessDiskIo,
-                    MetricValue::Unsigned(process.disk_read_bytes),
-                    "bytes",
-                ).with_tag("pid", process.pid.to_string())
-                 .with_tag("name", &process.name)
-                 .with_tag("operation", "read"));
-                
-                metrics.push(Metric::new(
-                    MetricType::ProcessDiskIo,
-                    MetricValue::Unsigned(process.disk_write_bytes),
-                    "bytes",
-                ).with_tag("pid", process.pid.to_string())
-                 .with_tag("name", &process.name)
-                 .with_tag("operation", "write"));
-            }
-        }
-        
-        Ok(metrics)
+        Ok(Self::processes_to_metrics(&process_metrics, self.top_count()))
     }
 
     async fn get_current_metrics(&self) -> Result<Vec<Metric>> {
         let history = self.metrics_history.read();
         
-        if let Some(latest) = history.back() {
+        if let Some((_, latest)) = history.back() {
             let mut metrics = Vec::new();
             
             let total_cpu: f32 = latest.iter().map(|p| p.cpu_usage_percent).sum();
@@ -364,11 +605,11 @@ impl Monitor for ProcessMonitor {
         }
     }
 
-    async fn get_historical_metrics(&self, _duration_seconds: u64) -> Result<Vec<Metric>> {
+    async fn get_historical_metrics(&self, duration_seconds: u64) -> Result<Vec<Metric>> {
         let history = self.metrics_history.read();
         let mut metrics = Vec::new();
         
-        for process_list in history.iter() {
+        for process_list in history_since(&history, duration_seconds, self.clock.now()) {
             let total_cpu: f32 = process_list.iter().map(|p| p.cpu_usage_percent).sum();
             metrics.push(Metric::new(
                 MetricType::ProcessCpuTotal,
@@ -380,12 +621,94 @@ impl Monitor for ProcessMonitor {
         Ok(metrics)
     }
 
+    async fn history_range(&self, start: SystemTime, end: SystemTime) -> Vec<(SystemTime, Vec<Metric>)> {
+        let top_count = self.top_count();
+        history_in_range(&self.metrics_history.read(), start, end, |sample| Self::processes_to_metrics(sample, top_count))
+    }
+
     fn supports_feature(&self, feature: &str) -> bool {
         matches!(feature, 
             "process_list" | "process_cpu" | "process_memory" | 
             "process_disk_io" | "process_filtering" | "process_sorting"
         )
     }
+
+    fn supported_metrics(&self) -> Vec<MetricType> {
+        let mut types = vec![
+            MetricType::ProcessCount,
+            MetricType::ProcessCpuTotal,
+            MetricType::ProcessMemoryTotal,
+            MetricType::ProcessCpu,
+            MetricType::ProcessMemory,
+            MetricType::ProcessCpuTime,
+            MetricType::ProcessElapsed,
+            MetricType::ProcessDiskIo,
+            MetricType::ProcessDiskIoRate,
+        ];
+        let history = self.metrics_history.read();
+        if history.back().is_some_and(|(_, processes)| processes.iter().any(|p| p.handles_available)) {
+            types.push(MetricType::ProcessHandles);
+        }
+        types
+    }
+
+    // Reads the table as of the last collection rather than refreshing it,
+    // which would cut short the interval the next sample's CPU usage covers
+    fn find_processes(&self, query: &ProcessQuery) -> Vec<ProcessMetrics> {
+        let system = self.system.read();
+        
+        #[cfg(target_os = "windows")]
+        let windows_threads = toolhelp::thread_counts();
+        
+        let mut matches: Vec<ProcessMetrics> = self
+            .latest_processes(&system)
+            .into_iter()
+            .map(|(_, process)| process)
+            .filter(|process| query.matches(process))
+            .collect();
+        
+        for process in &mut matches {
+            #[cfg(target_os = "windows")]
+            let threads = windows_threads.get(&process.pid).copied().unwrap_or(1);
+            #[cfg(not(target_os = "windows"))]
+            let threads = thread_count(process.pid);
+            Self::read_counts(process, threads);
+        }
+        
+        self.sort_processes(&mut matches);
+        matches
+    }
+
+    // Like `find_processes`, from the table as of the last collection
+    fn grouped_processes(&self) -> Vec<GroupedProcess> {
+        let grouping = self.shared.config.read().process_grouping;
+        let system = self.system.read();
+        let processes = self.latest_processes(&system);
+        
+        let mut groups = match grouping {
+            ProcessGrouping::None => processes
+                .iter()
+                .map(|(_, metrics)| {
+                    let mut group = GroupedProcess::new(metrics.name.as_str());
+                    group.add(metrics);
+                    group
+                })
+                .collect(),
+            ProcessGrouping::ByName => {
+                group_processes(processes.iter().map(|(_, metrics)| (metrics.name.clone(), metrics)))
+            }
+            ProcessGrouping::ByExecutable => group_processes(processes.iter().map(|(process, metrics)| {
+                let key = process
+                    .exe()
+                    .map(|exe| exe.to_string_lossy().into_owned())
+                    .unwrap_or_else(|| metrics.name.clone());
+                (key, metrics)
+            })),
+        };
+        
+        self.sort_groups(&mut groups);
+        groups
+    }
 }
 
 // Public API extensions for process monitoring
@@ -393,7 +716,7 @@ impl ProcessMonitor {
     pub async fn get_top_processes(&self, count: usize) -> Result<Vec<ProcessMetrics>> {
         let history = self.metrics_history.read();
         
-        if let Some(latest) = history.back() {
+        if let Some((_, latest)) = history.back() {
             Ok(latest.iter().take(count).cloned().collect())
         } else {
             Ok(Vec::new())
@@ -403,7 +726,7 @@ impl ProcessMonitor {
     pub async fn find_process_by_name(&self, name: &str) -> Result<Vec<ProcessMetrics>> {
         let history = self.metrics_history.read();
         
-        if let Some(latest) = history.back() {
+        if let Some((_, latest)) = history.back() {
             Ok(latest.iter()
                 .filter(|p| p.name.to_lowercase().contains(&name.to_lowercase()))
                 .cloned()
@@ -416,7 +739,7 @@ impl ProcessMonitor {
     pub async fn get_process_by_pid(&self, pid: u32) -> Result<Option<ProcessMetrics>> {
         let history = self.metrics_history.read();
         
-        if let Some(latest) = history.back() {
+        if let Some((_, latest)) = history.back() {
             Ok(latest.iter().find(|p| p.pid == pid).cloned())
         } else {
             Ok(None)
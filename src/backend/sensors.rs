@@ -1,37 +1,103 @@
+use async_trait::async_trait;
+use parking_lot::RwLock;
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::SystemTime;
 
-use crate::core::Result;
+use crate::core::{history_in_range, history_since, TimedHistory, trim_history, Metric, MetricType, MetricValue, Monitor, MonitorConfig, MonitorShared, MonitorError, MonitorState, Result, SharedClock, SystemClock};
+pub use crate::core::{SensorReading, SensorType};
 
-#[derive(Debug, Clone)]
-pub struct SensorReading {
-    pub name: String,
-    pub value: f32,
-    pub unit: String,
-    pub sensor_type: SensorType,
+#[cfg(target_os = "linux")]
+use std::path::Path;
+
+/// Turns the `temp*`, `fan*` and `in*` attributes of one hwmon chip into
+/// readings; `files` lists the chip directory and `read` returns the trimmed
+/// content of a file in it.
+///
+/// Readings are labelled `"<chip> - <label>"`, falling back to the attribute
+/// name when the chip has no `*_label` file. Stopped fans and implausible
+/// voltages are skipped.
+pub fn parse_hwmon_chip(chip: &str, files: &[String], read: impl Fn(&str) -> Option<String>) -> Vec<SensorReading> {
+    let mut inputs: Vec<(SensorType, u32, &str)> = files
+        .iter()
+        .filter_map(|file| {
+            let attr = file.strip_suffix("_input")?;
+            let (sensor_type, index) = if let Some(index) = attr.strip_prefix("temp") {
+                (SensorType::Temperature, index)
+            } else if let Some(index) = attr.strip_prefix("fan") {
+                (SensorType::Fan, index)
+            } else if let Some(index) = attr.strip_prefix("in") {
+                (SensorType::Voltage, index)
+            } else {
+                return None;
+            };
+            Some((sensor_type, index.parse().ok()?, attr))
+        })
+        .collect();
+    inputs.sort_by_key(|(sensor_type, index, _)| (*sensor_type as u8, *index));
+
+    inputs
+        .into_iter()
+        .filter_map(|(sensor_type, _, attr)| {
+            let raw = read(&format!("{}_input", attr))?.parse::<f32>().ok()?;
+            let (value, unit) = match sensor_type {
+                SensorType::Temperature => (raw / 1000.0, "°C"),
+                SensorType::Fan if raw > 0.0 => (raw, "RPM"),
+                SensorType::Voltage if raw > 0.0 && raw < 20_000.0 => (raw / 1000.0, "V"),
+                _ => return None,
+            };
+            let label = read(&format!("{}_label", attr)).unwrap_or_else(|| attr.to_string());
+
+            Some(SensorReading {
+                name: format!("{} - {}", chip, label),
+                value,
+                unit: unit.to_string(),
+                sensor_type,
+            })
+        })
+        .collect()
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum SensorType {
-    Temperature,
-    Fan,
-    Voltage,
-    Power,
-    Current,
+/// Names each hwmon chip in readings, given its `name` and the device it
+/// belongs to: the plain name when it's unique, otherwise `"<name> (<device>)"`
+/// so e.g. the `nvme` chips of two drives don't share sensor names.
+pub fn hwmon_chip_labels(chips: &[(String, String)]) -> Vec<String> {
+    chips
+        .iter()
+        .map(|(name, device)| {
+            if chips.iter().filter(|(other, _)| other == name).count() > 1 {
+                format!("{} ({})", name, device)
+            } else {
+                name.clone()
+            }
+        })
+        .collect()
 }
 
 pub struct SensorsManager {
+    // Chip label and directory; see `hwmon_chip_labels`
     #[cfg(target_os = "linux")]
-    hwmon_sensors: HashMap<String, String>,
+    hwmon_sensors: Vec<(String, String)>,
+}
+
+impl Default for SensorsManager {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl SensorsManager {
     pub fn new() -> Self {
-        let manager = Self {
+        let mut manager = Self {
             #[cfg(target_os = "linux")]
-            hwmon_sensors: HashMap::new(),
+            hwmon_sensors: Vec::new(),
         };
         
+        // WSL exposes no real hwmon devices
         #[cfg(target_os = "linux")]
-        manager.discover_hwmon_sensors();
+        if !super::environment::is_wsl() {
+            manager.discover_hwmon_sensors();
+        }
         
         manager
     }
@@ -39,23 +105,40 @@ impl SensorsManager {
     #[cfg(target_os = "linux")]
     fn discover_hwmon_sensors(&mut self) {
         use std::fs;
-        use std::path::Path;
         
         let hwmon_path = Path::new("/sys/class/hwmon");
+        let mut chips = Vec::new();
         if let Ok(entries) = fs::read_dir(hwmon_path) {
             for entry in entries.flatten() {
                 let path = entry.path();
                 if let Ok(name) = fs::read_to_string(path.join("name")) {
-                    let name = name.trim().to_string();
-                    self.hwmon_sensors.insert(name.clone(), path.to_string_lossy().to_string());
+                    // e.g. `nvme0` or a PCI address; virtual chips have no device
+                    let device = fs::read_link(path.join("device"))
+                        .ok()
+                        .and_then(|target| target.file_name().map(|n| n.to_string_lossy().to_string()))
+                        .unwrap_or_else(|| entry.file_name().to_string_lossy().to_string());
+                    chips.push((name.trim().to_string(), device, path.to_string_lossy().to_string()));
                 }
             }
         }
+        chips.sort_by(|a, b| a.2.cmp(&b.2));
+
+        let names: Vec<(String, String)> = chips.iter().map(|(name, device, _)| (name.clone(), device.clone())).collect();
+        self.hwmon_sensors = hwmon_chip_labels(&names)
+            .into_iter()
+            .zip(chips)
+            .map(|(label, (_, _, path))| (label, path))
+            .collect();
     }
     
     pub fn read_cpu_temperature(&self) -> Result<Option<f32>> {
         #[cfg(target_os = "linux")]
         {
+            // Thermal zones under WSL are absent or report placeholder values
+            if super::environment::is_wsl() {
+                return Ok(None);
+            }
+            
             // Try different temperature sources
             let temp_sources = [
                 "/sys/class/thermal/thermal_zone0/temp",
@@ -111,137 +194,53 @@ impl SensorsManager {
         Ok(None)
     }
     
-    pub fn read_all_temperatures(&self) -> Vec<SensorReading> {
-        let readings = Vec::new();
+    /// Every temperature, fan and voltage reading the hwmon chips expose
+    pub fn read_hwmon(&self) -> Vec<SensorReading> {
+        #[allow(unused_mut)]
+        let mut readings = Vec::new();
         
         #[cfg(target_os = "linux")]
         {
-            // Read all temperature sensors
             for (name, path) in &self.hwmon_sensors {
                 let base_path = Path::new(path);
+                let Ok(entries) = std::fs::read_dir(base_path) else {
+                    continue;
+                };
+                let files: Vec<String> = entries
+                    .flatten()
+                    .map(|entry| entry.file_name().to_string_lossy().to_string())
+                    .collect();
                 
-                // Look for all temp*_input files
-                for i in 1..10 {
-                    let temp_input = format!("temp{}_input", i);
-                    let temp_label = format!("temp{}_label", i);
-                    
-                    let input_path = base_path.join(&temp_input);
-                    if input_path.exists() {
-                        if let Ok(temp_str) = std::fs::read_to_string(&input_path) {
-                            if let Ok(temp_millidegree) = temp_str.trim().parse::<f32>() {
-                                let temp_celsius = temp_millidegree / 1000.0;
-                                
-                                // Try to get the label
-                                let label = if let Ok(label_str) = std::fs::read_to_string(base_path.join(&temp_label)) {
-                                    format!("{} - {}", name, label_str.trim())
-                                } else {
-                                    format!("{} - temp{}", name, i)
-                                };
-                                
-                                readings.push(SensorReading {
-                                    name: label,
-                                    value: temp_celsius,
-                                    unit: "°C".to_string(),
-                                    sensor_type: SensorType::Temperature,
-                                });
-                            }
-                        }
-                    }
-                }
+                readings.extend(parse_hwmon_chip(name, &files, |file| {
+                    std::fs::read_to_string(base_path.join(file)).ok().map(|v| v.trim().to_string())
+                }));
             }
         }
         
         readings
     }
     
+    fn read_hwmon_type(&self, sensor_type: SensorType) -> Vec<SensorReading> {
+        self.read_hwmon()
+            .into_iter()
+            .filter(|reading| reading.sensor_type == sensor_type)
+            .collect()
+    }
+    
+    pub fn read_all_temperatures(&self) -> Vec<SensorReading> {
+        self.read_hwmon_type(SensorType::Temperature)
+    }
+    
     pub fn read_fan_speeds(&self) -> Vec<SensorReading> {
-        let readings = Vec::new();
-        
-        #[cfg(target_os = "linux")]
-        {
-            for (name, path) in &self.hwmon_sensors {
-                let base_path = Path::new(path);
-                
-                // Look for all fan*_input files
-                for i in 1..10 {
-                    let fan_input = format!("fan{}_input", i);
-                    let fan_label = format!("fan{}_label", i);
-                    
-                    let input_path = base_path.join(&fan_input);
-                    if input_path.exists() {
-                        if let Ok(rpm_str) = std::fs::read_to_string(&input_path) {
-                            if let Ok(rpm) = rpm_str.trim().parse::<f32>() {
-                                // Skip if fan is not spinning
-                                if rpm > 0.0 {
-                                    let label = if let Ok(label_str) = std::fs::read_to_string(base_path.join(&fan_label)) {
-                                        format!("{} - {}", name, label_str.trim())
-                                    } else {
-                                        format!("{} - fan{}", name, i)
-                                    };
-                                    
-                                    readings.push(SensorReading {
-                                        name: label,
-                                        value: rpm,
-                                        unit: "RPM".to_string(),
-                                        sensor_type: SensorType::Fan,
-                                    });
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-        }
-        
-        readings
+        self.read_hwmon_type(SensorType::Fan)
     }
     
     pub fn read_voltages(&self) -> Vec<SensorReading> {
-        let readings = Vec::new();
-        
-        #[cfg(target_os = "linux")]
-        {
-            for (name, path) in &self.hwmon_sensors {
-                let base_path = Path::new(path);
-                
-                // Look for all in*_input files (voltages)
-                for i in 0..20 {
-                    let voltage_input = format!("in{}_input", i);
-                    let voltage_label = format!("in{}_label", i);
-                    
-                    let input_path = base_path.join(&voltage_input);
-                    if input_path.exists() {
-                        if let Ok(mv_str) = std::fs::read_to_string(&input_path) {
-                            if let Ok(millivolts) = mv_str.trim().parse::<f32>() {
-                                let volts = millivolts / 1000.0;
-                                
-                                // Skip obviously invalid readings
-                                if volts > 0.0 && volts < 20.0 {
-                                    let label = if let Ok(label_str) = std::fs::read_to_string(base_path.join(&voltage_label)) {
-                                        format!("{} - {}", name, label_str.trim())
-                                    } else {
-                                        format!("{} - in{}", name, i)
-                                    };
-                                    
-                                    readings.push(SensorReading {
-                                        name: label,
-                                        value: volts,
-                                        unit: "V".to_string(),
-                                        sensor_type: SensorType::Voltage,
-                                    });
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-        }
-        
-        readings
+        self.read_hwmon_type(SensorType::Voltage)
     }
     
     pub fn read_power_sensors(&self) -> Vec<SensorReading> {
-        let readings = Vec::new();
+        let mut readings = Vec::new();
         
         #[cfg(target_os = "linux")]
         {
@@ -310,6 +309,172 @@ impl SensorsManager {
     }
 }
 
+/// Reports every hwmon temperature, fan and voltage sensor, e.g. motherboard
+/// and NVMe temperatures that `CpuMonitor` doesn't cover.
+///
+/// Each metric is tagged `sensor=<name>` and `type=temperature|fan|voltage`.
+pub struct SensorsMonitor {
+    shared: MonitorShared,
+    manager: Arc<RwLock<SensorsManager>>,
+    // Each sample with the time it was collected
+    metrics_history: TimedHistory<Vec<SensorReading>>,
+    clock: SharedClock,
+}
+
+impl Default for SensorsMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SensorsMonitor {
+    pub fn new() -> Self {
+        Self::with_clock(SystemClock::shared())
+    }
+
+    /// Timestamps samples and computes rates by `clock`
+    pub fn with_clock(clock: SharedClock) -> Self {
+        Self {
+            shared: MonitorShared::new(clock.now()),
+            manager: Arc::new(RwLock::new(SensorsManager::new())),
+            metrics_history: Arc::new(RwLock::new(VecDeque::new())),
+            clock,
+        }
+    }
+
+    fn update_history(&self, readings: Vec<SensorReading>) {
+        let mut history = self.metrics_history.write();
+        let config = self.shared.config.read();
+
+        history.push_back((self.clock.now(), readings));
+
+        // Remove old metrics based on retention policy
+        let max_entries = (config.retain_history_seconds * 1000 / config.interval_ms) as usize;
+        while history.len() > max_entries {
+            history.pop_front();
+        }
+
+        trim_history(&mut history, config.max_history_bytes);
+    }
+
+    fn reading_to_metric(reading: &SensorReading) -> Option<Metric> {
+        let (metric_type, kind) = match reading.sensor_type {
+            SensorType::Temperature => (MetricType::SensorTemperature, "temperature"),
+            SensorType::Fan => (MetricType::SensorFanSpeed, "fan"),
+            SensorType::Voltage => (MetricType::SensorVoltage, "voltage"),
+            SensorType::Power | SensorType::Current => return None,
+        };
+
+        Some(Metric::new(
+            metric_type,
+            MetricValue::Float(reading.value as f64),
+            &reading.unit,
+        ).with_tag("sensor", &reading.name).with_tag("type", kind))
+    }
+}
+
+#[async_trait]
+impl Monitor for SensorsMonitor {
+    fn name(&self) -> &str {
+        "Sensors Monitor"
+    }
+
+    fn state(&self) -> MonitorState {
+        self.shared.state.read().clone()
+    }
+
+    fn shared(&self) -> Option<&MonitorShared> {
+        Some(&self.shared)
+    }
+
+    async fn initialize(&mut self, config: MonitorConfig) -> Result<()> {
+        *self.shared.state.write() = MonitorState::Initializing;
+        *self.shared.config.write() = config;
+        // Pick up chips whose drivers loaded after construction
+        *self.manager.write() = SensorsManager::new();
+        *self.shared.state.write() = MonitorState::Running;
+        Ok(())
+    }
+
+
+    async fn start(&mut self) -> Result<()> {
+        match self.state() {
+            MonitorState::Running | MonitorState::Degraded { .. } => return Ok(()),
+            MonitorState::Uninitialized => {
+                return Err(MonitorError::NotInitialized);
+            }
+            _ => {}
+        }
+
+        *self.shared.state.write() = MonitorState::Running;
+        Ok(())
+    }
+
+    async fn stop(&mut self) -> Result<()> {
+        *self.shared.state.write() = MonitorState::Stopped;
+        Ok(())
+    }
+
+    async fn pause(&mut self) -> Result<()> {
+        *self.shared.state.write() = MonitorState::Paused;
+        Ok(())
+    }
+
+    async fn resume(&mut self) -> Result<()> {
+        *self.shared.state.write() = MonitorState::Running;
+        Ok(())
+    }
+
+    async fn collect(&mut self) -> Result<Vec<Metric>> {
+        if !self.state().is_collecting() {
+            return Err(MonitorError::NotInitialized);
+        }
+
+        let readings = self.manager.read().read_hwmon();
+        let metrics = readings.iter().filter_map(Self::reading_to_metric).collect();
+        self.update_history(readings);
+        *self.shared.last_update.write() = self.clock.now();
+
+        Ok(metrics)
+    }
+
+    async fn get_current_metrics(&self) -> Result<Vec<Metric>> {
+        let history = self.metrics_history.read();
+        Ok(history
+            .back()
+            .map(|(_, readings)| readings.iter().filter_map(Self::reading_to_metric).collect())
+            .unwrap_or_default())
+    }
+
+    async fn get_historical_metrics(&self, duration_seconds: u64) -> Result<Vec<Metric>> {
+        let history = self.metrics_history.read();
+        Ok(history_since(&history, duration_seconds, self.clock.now())
+            .flat_map(|readings| readings.iter().filter_map(Self::reading_to_metric))
+            .collect())
+    }
+
+    async fn history_range(&self, start: SystemTime, end: SystemTime) -> Vec<(SystemTime, Vec<Metric>)> {
+        history_in_range(&self.metrics_history.read(), start, end, |readings| {
+            readings.iter().filter_map(Self::reading_to_metric).collect()
+        })
+    }
+
+    fn supports_feature(&self, feature: &str) -> bool {
+        matches!(feature, "temperature" | "fan" | "voltage")
+    }
+
+    fn supported_metrics(&self) -> Vec<MetricType> {
+        let history = self.metrics_history.read();
+        let mut types: Vec<MetricType> = Vec::new();
+        for metric in history.back().into_iter().flat_map(|(_, readings)| readings).filter_map(Self::reading_to_metric) {
+            if !types.contains(&metric.metric_type) {
+                types.push(metric.metric_type);
+            }
+        }
+        types
+    }
+}
+
 // Integration with CPU monitor
 impl super::cpu_monitor::CpuMonitor {
     pub fn update_temperature(&self) -> Option<f32> {
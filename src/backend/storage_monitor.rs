@@ -2,43 +2,534 @@ use async_trait::async_trait;
 use parking_lot::RwLock;
 use std::collections::{HashMap, VecDeque};
 use std::sync::Arc;
-use std::time::SystemTime;
+use std::time::{Duration, Instant, SystemTime};
 use sysinfo::{System, RefreshKind, Disks};
 
 use crate::core::{
-    DiskMetrics, Metric, MetricType, MetricValue, Monitor, MonitorConfig, MonitorError,
-    MonitorState, Result,
+    disk_id, DiskMetrics, history_in_range, history_since, TimedHistory, trim_history, HistorySize, Metric, MetricType, MetricValue, Monitor, MonitorConfig, MonitorShared, MonitorError,
+    MonitorState, PoolKind, PoolMetrics, Result, SharedClock, SystemClock, SmartHealth,
 };
 
+/// How long a drive's SMART data is reused before it is read again; the
+/// attributes change slowly and each read starts a separate process
+pub const SMART_REFRESH_INTERVAL: Duration = Duration::from_secs(300);
+
+/// The whole drive a partition lives on: `/dev/sda1` is on `/dev/sda` and
+/// `/dev/nvme0n1p2` on `/dev/nvme0n1`
+///
+/// Returns `None` for anything that isn't a plain disk, such as
+/// device-mapper volumes, which SMART can't be read through.
+pub fn physical_device(device_name: &str) -> Option<String> {
+    let name = device_name.strip_prefix("/dev/")?;
+    let all_digits = |s: &str| !s.is_empty() && s.chars().all(|c| c.is_ascii_digit());
+
+    if name.starts_with("nvme") || name.starts_with("mmcblk") {
+        // The drive name already ends in a digit, so partitions add `p<N>`
+        let drive = match name.rsplit_once('p') {
+            Some((drive, partition)) if all_digits(partition) && drive.ends_with(|c: char| c.is_ascii_digit()) => drive,
+            _ => name,
+        };
+        return Some(format!("/dev/{}", drive));
+    }
+    if ["sd", "hd", "vd", "xvd"].iter().any(|prefix| name.starts_with(prefix)) {
+        return Some(format!("/dev/{}", name.trim_end_matches(|c: char| c.is_ascii_digit())));
+    }
+    None
+}
+
+/// Parses `smartctl --json -a <device>` output
+///
+/// Wear comes from the NVMe `percentage_used` field, or for SATA SSDs from
+/// the normalized value of the vendor's remaining-life attribute (177, 231
+/// or 233), inverted so both read as life used. Returns `None` when the
+/// report has no overall verdict, as when smartctl couldn't open the drive.
+pub fn parse_smartctl_json(device: &str, json: &str) -> Option<SmartHealth> {
+    let report: serde_json::Value = serde_json::from_str(json).ok()?;
+    let healthy = report["smart_status"]["passed"].as_bool()?;
+
+    let ata_attribute = |id: u64| {
+        report["ata_smart_attributes"]["table"]
+            .as_array()?
+            .iter()
+            .find(|attribute| attribute["id"].as_u64() == Some(id))
+    };
+    let wear = report["nvme_smart_health_information_log"]["percentage_used"]
+        .as_f64()
+        .or_else(|| {
+            [177, 231, 233]
+                .into_iter()
+                .find_map(|id| ata_attribute(id)?["value"].as_f64().map(|remaining| 100.0 - remaining))
+        });
+
+    Some(SmartHealth {
+        device: device.to_string(),
+        healthy,
+        temperature_c: report["temperature"]["current"].as_f64().map(|t| t as f32),
+        power_on_hours: report["power_on_time"]["hours"].as_u64(),
+        reallocated_sectors: ata_attribute(5).and_then(|attribute| attribute["raw"]["value"].as_u64()),
+        wear_level_percent: wear.map(|w| w.clamp(0.0, 100.0) as f32),
+    })
+}
+
+/// Parses `wmic /namespace:\\root\wmi path MSStorageDriver_FailurePredictStatus
+/// get InstanceName,PredictFailure /format:csv` output, one entry per drive
+///
+/// The class only carries the failure prediction, so the other attributes
+/// are left empty.
+pub fn parse_failure_predict_csv(output: &str) -> Vec<SmartHealth> {
+    let mut lines = output.lines().map(str::trim).filter(|line| !line.is_empty());
+    let Some(header) = lines.next() else {
+        return Vec::new();
+    };
+    let columns: Vec<&str> = header.split(',').collect();
+    let column = |name: &str| columns.iter().position(|c| c.eq_ignore_ascii_case(name));
+    let (Some(instance), Some(predict)) = (column("InstanceName"), column("PredictFailure")) else {
+        return Vec::new();
+    };
+
+    lines
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split(',').collect();
+            Some(SmartHealth {
+                device: fields.get(instance)?.to_string(),
+                healthy: !fields.get(predict)?.eq_ignore_ascii_case("true"),
+                temperature_c: None,
+                power_on_hours: None,
+                reallocated_sectors: None,
+                wear_level_percent: None,
+            })
+        })
+        .collect()
+}
+
+/// `Key=Value` records of `wmic ... /format:list` output, which separates
+/// records with blank lines
+fn wmic_list_records(output: &str) -> Vec<Vec<(&str, &str)>> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    for line in output.lines().map(str::trim).chain([""]) {
+        match line.split_once('=') {
+            Some((key, value)) => record.push((key, value)),
+            None if !record.is_empty() => records.push(std::mem::take(&mut record)),
+            None => {}
+        }
+    }
+    records
+}
+
+/// `(Index, PNPDeviceID)` of each drive in `wmic diskdrive get
+/// Index,PNPDeviceID /format:list` output
+pub fn parse_disk_drives(output: &str) -> Vec<(u32, String)> {
+    wmic_list_records(output)
+        .into_iter()
+        .filter_map(|record| {
+            let field = |name: &str| record.iter().find(|(key, _)| *key == name).map(|(_, value)| *value);
+            Some((field("Index")?.parse().ok()?, field("PNPDeviceID")?.to_string()))
+        })
+        .collect()
+}
+
+/// `(drive letter, disk index)` of each volume in `wmic path
+/// Win32_LogicalDiskToPartition get Antecedent,Dependent /format:list` output
+pub fn parse_volume_disks(output: &str) -> Vec<(String, u32)> {
+    wmic_list_records(output)
+        .into_iter()
+        .filter_map(|record| {
+            let field = |name: &str| record.iter().find(|(key, _)| *key == name).map(|(_, value)| *value);
+            // ...Win32_DiskPartition.DeviceID="Disk #0, Partition #2"
+            let disk = field("Antecedent")?.split("Disk #").nth(1)?.split(',').next()?.parse().ok()?;
+            // ...Win32_LogicalDisk.DeviceID="C:"
+            let letter = field("Dependent")?.split("DeviceID=").nth(1)?.trim_matches('"');
+            Some((letter.to_ascii_uppercase(), disk))
+        })
+        .collect()
+}
+
+/// Attaches each failure prediction to the volumes on its drive, by drive
+/// letter
+///
+/// A prediction's instance name is the drive's PNP device id plus an
+/// instance suffix, in whatever case the driver chose.
+pub fn failure_predictions_by_volume(
+    predictions: &[SmartHealth],
+    drives: &[(u32, String)],
+    volumes: &[(String, u32)],
+) -> HashMap<String, SmartHealth> {
+    volumes
+        .iter()
+        .filter_map(|(letter, disk)| {
+            let (_, pnp_id) = drives.iter().find(|(index, _)| index == disk)?;
+            let pnp_id = pnp_id.to_ascii_uppercase();
+            let prediction = predictions.iter().find(|p| p.device.to_ascii_uppercase().starts_with(&pnp_id))?;
+            Some((letter.clone(), prediction.clone()))
+        })
+        .collect()
+}
+
+/// `(total, used)` inodes from `statvfs`' `f_files` and `f_ffree`; `None`
+/// when the filesystem reports no inode table
+pub fn inode_usage(files: u64, free: u64) -> Option<(u64, u64)> {
+    (files > 0).then(|| (files, files.saturating_sub(free)))
+}
+
+/// Inode counts of the filesystem mounted at `mount_point`
+///
+/// Always `None` on Windows, where NTFS has no fixed inode count.
+#[allow(clippy::unnecessary_cast)] // fsfilcnt_t is u32 on macOS
+pub fn inode_counts(mount_point: &str) -> Option<(u64, u64)> {
+    #[cfg(unix)]
+    {
+        let path = std::ffi::CString::new(mount_point).ok()?;
+        let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+        if unsafe { libc::statvfs(path.as_ptr(), &mut stat) } != 0 {
+            return None;
+        }
+        inode_usage(stat.f_files as u64, stat.f_ffree as u64)
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = mount_point;
+        None
+    }
+}
+
+/// Share of `elapsed_secs` the device spent with I/O in flight (iostat `%util`),
+/// from two `io_ticks` readings in milliseconds
+pub fn busy_percent(previous_ticks_ms: u64, current_ticks_ms: u64, elapsed_secs: f64) -> f32 {
+    if elapsed_secs <= 0.0 {
+        return 0.0;
+    }
+    let busy_ms = current_ticks_ms.saturating_sub(previous_ticks_ms) as f64;
+    ((busy_ms / (elapsed_secs * 1000.0)) * 100.0).clamp(0.0, 100.0) as f32
+}
+
+/// "1.52x", or "1.52" as `-p` prints it
+fn parse_ratio(value: &str) -> Option<f64> {
+    value.trim().trim_end_matches('x').parse().ok()
+}
+
+/// Parses `zpool list -H -p -o name,size,alloc,free,dedupratio,health`
+/// output; compression and scrub state come from other commands
+pub fn parse_zpool_list(output: &str) -> Vec<PoolMetrics> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split('\t').map(str::trim).collect();
+            let [name, size, allocated, free, dedup, health] = fields[..] else {
+                return None;
+            };
+            Some(PoolMetrics {
+                name: name.to_string(),
+                kind: PoolKind::Zfs,
+                raw_capacity: size.parse().ok()?,
+                allocated: allocated.parse().ok()?,
+                free: free.parse().ok()?,
+                compression_ratio: 1.0,
+                dedup_ratio: parse_ratio(dedup).unwrap_or(1.0),
+                health: health.to_string(),
+                scrub_state: String::new(),
+            })
+        })
+        .collect()
+}
+
+/// Compression ratio per pool from `zfs get -H -p -o name,value
+/// compressratio` output, taken from each pool's root dataset, whose ratio
+/// covers the datasets below it
+pub fn parse_zfs_compressratio(output: &str) -> HashMap<String, f64> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (name, value) = line.split_once('\t')?;
+            if name.contains('/') {
+                return None;
+            }
+            Some((name.to_string(), parse_ratio(value)?))
+        })
+        .collect()
+}
+
+/// The `scan:` line of each pool in `zpool status` output, e.g. `scrub
+/// repaired 0B in 00:01:02 with 0 errors on ...` or `scrub in progress
+/// since ...`; progress details on the lines after it are left out
+pub fn parse_zpool_status_scans(output: &str) -> HashMap<String, String> {
+    let mut scans = HashMap::new();
+    let mut pool = None;
+    for line in output.lines().map(str::trim) {
+        if let Some(name) = line.strip_prefix("pool:") {
+            pool = Some(name.trim().to_string());
+        } else if let Some(scan) = line.strip_prefix("scan:") {
+            if let Some(name) = pool.take() {
+                scans.insert(name, scan.trim().to_string());
+            }
+        }
+    }
+    scans
+}
+
+/// Parses `btrfs filesystem usage -b <mount>` output, naming the pool
+/// `name`
+pub fn parse_btrfs_usage(name: &str, output: &str) -> Option<PoolMetrics> {
+    // "    Free (estimated):          400000000000      (min: 200000000000)"
+    let field = |key: &str| {
+        output.lines().find_map(|line| {
+            let (k, v) = line.split_once(':')?;
+            if k.trim() != key {
+                return None;
+            }
+            v.split_whitespace().next()?.parse::<u64>().ok()
+        })
+    };
+    Some(PoolMetrics {
+        name: name.to_string(),
+        kind: PoolKind::Btrfs,
+        raw_capacity: field("Device size")?,
+        allocated: field("Used")?,
+        free: field("Free (estimated)")?,
+        compression_ratio: 1.0,
+        dedup_ratio: 1.0,
+        health: String::new(),
+        scrub_state: String::new(),
+    })
+}
+
+/// Summarizes `btrfs scrub status <mount>` output as `finished, no errors
+/// found` and the like
+pub fn parse_btrfs_scrub_status(output: &str) -> String {
+    let field = |key: &str| {
+        output.lines().find_map(|line| {
+            let (k, v) = line.split_once(':')?;
+            (k.trim() == key).then(|| v.trim())
+        })
+    };
+    match (field("Status"), field("Error summary")) {
+        (Some(status), Some(errors)) => format!("{}, {}", status, errors),
+        (Some(status), None) => status.to_string(),
+        // btrfs-progs before 5.1 print a sentence instead
+        _ => output
+            .lines()
+            .map(str::trim)
+            .find(|line| line.starts_with("scrub started") || line.starts_with("no stats"))
+            .unwrap_or_default()
+            .to_string(),
+    }
+}
+
+/// ZFS pools and Btrfs filesystems, sorted by name; empty when there are
+/// none or `zpool`/`btrfs` aren't installed
+///
+/// Btrfs filesystems are found among `disks`, the mounts the storage
+/// monitor reports.
+pub fn read_pools(disks: &[DiskMetrics]) -> Vec<PoolMetrics> {
+    #[cfg(unix)]
+    {
+        let run = |program: &str, args: &[&str]| {
+            let output = std::process::Command::new(program).args(args).output().ok()?;
+            output.status.success().then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+        };
+
+        let mut pools = run("zpool", &["list", "-H", "-p", "-o", "name,size,alloc,free,dedupratio,health"])
+            .map(|output| parse_zpool_list(&output))
+            .unwrap_or_default();
+        if !pools.is_empty() {
+            let ratios = run("zfs", &["get", "-H", "-p", "-o", "name,value", "compressratio"])
+                .map(|output| parse_zfs_compressratio(&output))
+                .unwrap_or_default();
+            let scans = run("zpool", &["status"]).map(|output| parse_zpool_status_scans(&output)).unwrap_or_default();
+            for pool in &mut pools {
+                pool.compression_ratio = ratios.get(&pool.name).copied().unwrap_or(1.0);
+                pool.scrub_state = scans.get(&pool.name).cloned().unwrap_or_default();
+            }
+        }
+
+        // Every subvolume mount of a filesystem has the same device, and
+        // disks are sorted by mount point, so `/` names the root filesystem
+        let mut seen = std::collections::HashSet::new();
+        for disk in disks.iter().filter(|disk| disk.fs_type == "btrfs") {
+            if !seen.insert(disk.device_name.as_str()) {
+                continue;
+            }
+            let usage = run("btrfs", &["filesystem", "usage", "-b", &disk.mount_point]);
+            if let Some(mut pool) = usage.and_then(|output| parse_btrfs_usage(&disk.mount_point, &output)) {
+                pool.scrub_state = run("btrfs", &["scrub", "status", &disk.mount_point])
+                    .map(|output| parse_btrfs_scrub_status(&output))
+                    .unwrap_or_default();
+                pools.push(pool);
+            }
+        }
+
+        pools.sort_by(|a, b| a.name.cmp(&b.name));
+        pools
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = disks;
+        Vec::new()
+    }
+}
+
+type SmartSample = (Instant, Option<SmartHealth>);
+
+/// `C:` for a volume mounted at `C:\`, as Windows names volumes in WMI and
+/// performance counter instances
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn drive_letter(mount_point: &str) -> Option<String> {
+    let letter = mount_point.get(..2).filter(|letter| letter.ends_with(':'))?;
+    Some(letter.to_ascii_uppercase())
+}
+
+/// Cumulative bytes read and written and time busy with I/O, per
+/// `\LogicalDisk` instance (`C:`)
+#[cfg(target_os = "windows")]
+const WINDOWS_DISK_COUNTERS: [&str; 3] = [
+    "\\LogicalDisk(*)\\Disk Read Bytes/sec",
+    "\\LogicalDisk(*)\\Disk Write Bytes/sec",
+    "\\LogicalDisk(*)\\% Disk Time",
+];
+
+/// What SMART readings of the volume `device_name` mounted at `mount_point`
+/// are cached and read under: its physical drive on Linux, its drive letter
+/// on Windows
+fn smart_key(device_name: &str, mount_point: &str) -> Option<String> {
+    #[cfg(target_os = "linux")]
+    {
+        let _ = mount_point;
+        physical_device(device_name)
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        let _ = device_name;
+        drive_letter(mount_point)
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "windows")))]
+    {
+        let _ = (device_name, mount_point);
+        None
+    }
+}
+
+/// SMART health under each of `keys` (see `smart_key`). Starts processes,
+/// so it runs on the blocking pool.
+///
+/// Linux needs smartmontools installed and usually root; without either
+/// there is simply no SMART data. Drives in standby aren't spun up for it.
+/// Windows only has the drive's failure prediction.
+fn read_smart(keys: &[String]) -> HashMap<String, Option<SmartHealth>> {
+    #[cfg(target_os = "linux")]
+    {
+        keys.iter()
+            .map(|key| {
+                let health = std::process::Command::new("smartctl")
+                    .args(["--json", "-a", "-n", "standby", key])
+                    .output()
+                    .ok()
+                    .and_then(|output| parse_smartctl_json(key, &String::from_utf8_lossy(&output.stdout)));
+                (key.clone(), health)
+            })
+            .collect()
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        let wmic = |args: &[&str]| {
+            std::process::Command::new("wmic")
+                .args(args)
+                .output()
+                .map(|output| String::from_utf8_lossy(&output.stdout).into_owned())
+                .unwrap_or_default()
+        };
+        let predictions = parse_failure_predict_csv(&wmic(&[
+            "/namespace:\\\\root\\wmi",
+            "path",
+            "MSStorageDriver_FailurePredictStatus",
+            "get",
+            "InstanceName,PredictFailure",
+            "/format:csv",
+        ]));
+        let mut by_volume = if predictions.is_empty() {
+            HashMap::new()
+        } else {
+            failure_predictions_by_volume(
+                &predictions,
+                &parse_disk_drives(&wmic(&["diskdrive", "get", "Index,PNPDeviceID", "/format:list"])),
+                &parse_volume_disks(&wmic(&[
+                    "path",
+                    "Win32_LogicalDiskToPartition",
+                    "get",
+                    "Antecedent,Dependent",
+                    "/format:list",
+                ])),
+            )
+        };
+        keys.iter().map(|key| (key.clone(), by_volume.remove(key))).collect()
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "windows")))]
+    {
+        keys.iter().map(|key| (key.clone(), None)).collect()
+    }
+}
+
+struct StorageSample {
+    disks: Vec<DiskMetrics>,
+    pools: Vec<PoolMetrics>,
+}
+
+impl HistorySize for StorageSample {
+    fn heap_bytes(&self) -> usize {
+        self.disks.heap_bytes() + self.pools.heap_bytes()
+    }
+}
+
 pub struct StorageMonitor {
-    state: Arc<RwLock<MonitorState>>,
-    config: Arc<RwLock<MonitorConfig>>,
+    shared: MonitorShared,
     #[allow(dead_code)] // Will be used for future platform-specific optimizations
     system: Arc<RwLock<System>>,
-    metrics_history: Arc<RwLock<VecDeque<Vec<DiskMetrics>>>>,
-    last_update: Arc<RwLock<SystemTime>>,
+    // Each sample with the time it was collected
+    metrics_history: TimedHistory<StorageSample>,
     previous_io_stats: Arc<RwLock<HashMap<String, IoStats>>>,
+    /// Last SMART read per `smart_key`, and when it was taken
+    smart_cache: Arc<RwLock<HashMap<String, SmartSample>>>,
+    // Performance counter query, opened on first use
+    #[cfg(target_os = "windows")]
+    windows_counters: Arc<RwLock<Option<super::windows_pdh::RawCounters>>>,
+    clock: SharedClock,
 }
 
 #[derive(Clone, Debug)]
 struct IoStats {
-    #[allow(dead_code)] // Used in platform-specific implementations
     read_bytes: u64,
-    #[allow(dead_code)] // Used in platform-specific implementations
     write_bytes: u64,
-    #[allow(dead_code)] // Used in platform-specific implementations
+    io_ticks_ms: u64,
     timestamp: SystemTime,
 }
 
+impl Default for StorageMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl StorageMonitor {
     pub fn new() -> Self {
+        Self::with_clock(SystemClock::shared())
+    }
+
+    /// Timestamps samples and computes rates by `clock`
+    pub fn with_clock(clock: SharedClock) -> Self {
         Self {
-            state: Arc::new(RwLock::new(MonitorState::Uninitialized)),
-            config: Arc::new(RwLock::new(MonitorConfig::default())),
+            shared: MonitorShared::new(clock.now()),
             system: Arc::new(RwLock::new(System::new_with_specifics(RefreshKind::everything()))),
             metrics_history: Arc::new(RwLock::new(VecDeque::new())),
-            last_update: Arc::new(RwLock::new(SystemTime::now())),
             previous_io_stats: Arc::new(RwLock::new(HashMap::new())),
+            smart_cache: Arc::new(RwLock::new(HashMap::new())),
+            #[cfg(target_os = "windows")]
+            windows_counters: Arc::new(RwLock::new(None)),
+            clock,
         }
     }
 
@@ -48,8 +539,10 @@ impl StorageMonitor {
 
         let mut metrics = Vec::new();
         let mut current_io_stats = HashMap::new();
-        let now = SystemTime::now();
+        let now = self.clock.now();
         let previous_stats = self.previous_io_stats.read();
+        #[cfg(target_os = "windows")]
+        let windows_io = self.read_windows_io_stats();
 
         for disk in disks.iter() {
             let mount_point = disk.mount_point().to_string_lossy().to_string();
@@ -65,8 +558,16 @@ impl StorageMonitor {
             };
 
             // Calculate I/O rates
-            let (read_bytes_per_sec, write_bytes_per_sec) = self.calculate_io_rates(
+            #[cfg(target_os = "linux")]
+            let io_counters = self.read_linux_io_stats(&device_name).ok();
+            #[cfg(target_os = "windows")]
+            let io_counters = drive_letter(&mount_point).and_then(|letter| windows_io.get(&letter).copied());
+            // macOS would need IOKit
+            #[cfg(not(any(target_os = "linux", target_os = "windows")))]
+            let io_counters = None;
+            let (read_bytes_per_sec, write_bytes_per_sec, busy_percent) = Self::calculate_io_rates(
                 &device_name,
+                io_counters,
                 &previous_stats,
                 &mut current_io_stats,
                 now,
@@ -76,7 +577,11 @@ impl StorageMonitor {
                 .to_string_lossy()
                 .to_string();
 
+            let inodes = inode_counts(&mount_point);
+            let inode_usage_percent = inodes.map(|(total, used)| (used as f32 / total as f32) * 100.0);
+
             metrics.push(DiskMetrics {
+                id: disk_id(&device_name, &mount_point),
                 mount_point,
                 device_name: device_name.clone(),
                 fs_type,
@@ -87,66 +592,120 @@ impl StorageMonitor {
                 read_bytes_per_sec,
                 write_bytes_per_sec,
                 io_operations_per_sec: 0, // Platform-specific, would need additional implementation
+                busy_percent,
+                // Filled in by `fill_smart`
+                smart: None,
+                inodes_total: inodes.map(|(total, _)| total),
+                inodes_used: inodes.map(|(_, used)| used),
+                inode_usage_percent,
             });
         }
 
         // Update previous I/O stats for next calculation
+        drop(previous_stats);
         *self.previous_io_stats.write() = current_io_stats;
 
+        metrics.sort_by(|a, b| a.mount_point.cmp(&b.mount_point));
         Ok(metrics)
     }
 
+    /// Rates since the previous sample from cumulative `(read_bytes,
+    /// write_bytes, io_ticks_ms)` counters; zero on the first sample
     fn calculate_io_rates(
-        &self,
-        _device_name: &str,
-        _previous_stats: &HashMap<String, IoStats>,
-        _current_stats: &mut HashMap<String, IoStats>,
-        _now: SystemTime,
-    ) -> (u64, u64) {
-        // Platform-specific I/O statistics
-        #[cfg(target_os = "linux")]
-        {
-            if let Ok((read_bytes, write_bytes)) = self.read_linux_io_stats(device_name) {
-                let io_stats = IoStats {
-                    read_bytes,
-                    write_bytes,
-                    timestamp: now,
-                };
-
-                if let Some(prev_stats) = previous_stats.get(device_name) {
-                    if let Ok(duration) = now.duration_since(prev_stats.timestamp) {
-                        let secs = duration.as_secs_f64();
-                        if secs > 0.0 {
-                            let read_rate = ((read_bytes.saturating_sub(prev_stats.read_bytes)) as f64 / secs) as u64;
-                            let write_rate = ((write_bytes.saturating_sub(prev_stats.write_bytes)) as f64 / secs) as u64;
-                            
-                            current_stats.insert(device_name.to_string(), io_stats);
-                            return (read_rate, write_rate);
-                        }
-                    }
-                }
+        device_name: &str,
+        counters: Option<(u64, u64, u64)>,
+        previous_stats: &HashMap<String, IoStats>,
+        current_stats: &mut HashMap<String, IoStats>,
+        now: SystemTime,
+    ) -> (u64, u64, f32) {
+        let Some((read_bytes, write_bytes, io_ticks_ms)) = counters else {
+            return (0, 0, 0.0);
+        };
+        current_stats.insert(device_name.to_string(), IoStats {
+            read_bytes,
+            write_bytes,
+            io_ticks_ms,
+            timestamp: now,
+        });
+
+        let secs = previous_stats
+            .get(device_name)
+            .and_then(|prev_stats| Some((prev_stats, now.duration_since(prev_stats.timestamp).ok()?.as_secs_f64())));
+        match secs {
+            Some((prev_stats, secs)) if secs > 0.0 => (
+                crate::core::counter_rate(read_bytes, prev_stats.read_bytes, secs) as u64,
+                crate::core::counter_rate(write_bytes, prev_stats.write_bytes, secs) as u64,
+                busy_percent(prev_stats.io_ticks_ms, io_ticks_ms, secs),
+            ),
+            _ => (0, 0, 0.0),
+        }
+    }
 
-                current_stats.insert(device_name.to_string(), io_stats);
+    /// Fills in the SMART health of `disks`, refreshing readings older than
+    /// `SMART_REFRESH_INTERVAL` on the blocking pool
+    async fn fill_smart(&self, disks: &mut [DiskMetrics]) {
+        let keys: Vec<Option<String>> = disks.iter().map(|disk| smart_key(&disk.device_name, &disk.mount_point)).collect();
+        let now = self.clock.instant();
+
+        let mut stale: Vec<String> = {
+            let cache = self.smart_cache.read();
+            keys.iter()
+                .flatten()
+                .filter(|key| {
+                    cache.get(*key).is_none_or(|(read_at, _)| now.duration_since(*read_at) >= SMART_REFRESH_INTERVAL)
+                })
+                .cloned()
+                .collect()
+        };
+        stale.sort();
+        stale.dedup();
+
+        if !stale.is_empty() {
+            let readings = tokio::task::spawn_blocking(move || read_smart(&stale)).await.unwrap_or_default();
+            let mut cache = self.smart_cache.write();
+            for (key, health) in readings {
+                // A drive smartctl left asleep in standby keeps its last reading
+                let health = health.or_else(|| cache.get(&key).and_then(|(_, health)| health.clone()));
+                cache.insert(key, (now, health));
             }
+            cache.retain(|key, _| keys.iter().flatten().any(|k| k == key));
         }
 
-        #[cfg(target_os = "windows")]
-        {
-            // Windows implementation would use Performance Counters or WMI
-            // This is a placeholder
+        let cache = self.smart_cache.read();
+        for (disk, key) in disks.iter_mut().zip(&keys) {
+            disk.smart = key.as_ref().and_then(|key| cache.get(key)).and_then(|(_, health)| health.clone());
         }
+    }
 
-        #[cfg(target_os = "macos")]
-        {
-            // macOS implementation would use IOKit
-            // This is a placeholder
+    /// Cumulative `(read_bytes, write_bytes, io_ticks_ms)` per volume letter
+    ///
+    /// `% Disk Time` is raw busy time in 100ns units; like Linux `io_ticks`
+    /// it counts overlapping requests more than once, so `busy_percent`
+    /// clamps it.
+    #[cfg(target_os = "windows")]
+    fn read_windows_io_stats(&self) -> HashMap<String, (u64, u64, u64)> {
+        let mut counters = self.windows_counters.write();
+        if counters.is_none() {
+            *counters = super::windows_pdh::RawCounters::open(&WINDOWS_DISK_COUNTERS);
         }
-
-        (0, 0)
+        let Some(counters) = counters.as_ref().filter(|counters| counters.collect()) else {
+            return HashMap::new();
+        };
+
+        let written: HashMap<String, u64> = counters.instances(1).into_iter().collect();
+        let busy: HashMap<String, u64> = counters.instances(2).into_iter().collect();
+        counters
+            .instances(0)
+            .into_iter()
+            .filter_map(|(volume, read)| {
+                let stats = (read, *written.get(&volume)?, *busy.get(&volume)? / 10_000);
+                Some((volume.to_ascii_uppercase(), stats))
+            })
+            .collect()
     }
 
     #[cfg(target_os = "linux")]
-    fn read_linux_io_stats(&self, device_name: &str) -> Result<(u64, u64)> {
+    fn read_linux_io_stats(&self, device_name: &str) -> Result<(u64, u64, u64)> {
         use std::fs;
         
         // Extract the base device name (e.g., sda from sda1)
@@ -159,33 +718,128 @@ impl StorageMonitor {
         
         if let Ok(contents) = fs::read_to_string(&stat_path) {
             let parts: Vec<&str> = contents.split_whitespace().collect();
-            if parts.len() >= 6 {
-                // Format: reads read_sectors writes written_sectors
+            if parts.len() >= 7 {
+                // Format: reads read_merges read_sectors read_ticks writes write_merges
+                // written_sectors write_ticks in_flight io_ticks ...
                 let read_sectors = parts[2].parse::<u64>().unwrap_or(0);
                 let written_sectors = parts[6].parse::<u64>().unwrap_or(0);
+                // Milliseconds the device had at least one request in flight
+                let io_ticks_ms = parts.get(9)
+                    .and_then(|ticks| ticks.parse::<u64>().ok())
+                    .unwrap_or(0);
                 
                 // Convert sectors to bytes (typically 512 bytes per sector)
                 let read_bytes = read_sectors * 512;
                 let write_bytes = written_sectors * 512;
                 
-                return Ok((read_bytes, write_bytes));
+                return Ok((read_bytes, write_bytes, io_ticks_ms));
             }
         }
 
-        Ok((0, 0))
+        Ok((0, 0, 0))
+    }
+
+    /// The metrics `collect()` reports for one sample
+    fn sample_to_metrics(sample: &StorageSample) -> Vec<Metric> {
+        let mut metrics = Vec::new();
+        
+        for disk in sample.disks.iter() {
+            // Disk usage percentage
+            metrics.push(Metric::new(
+                MetricType::DiskUsage,
+                MetricValue::percentage(disk.usage_percent as f64),
+                "%",
+            ).with_tag("mount", &disk.mount_point)
+             .with_tag("device", &disk.device_name));
+            
+            // Disk space metrics
+            metrics.push(Metric::new(
+                MetricType::DiskSpace,
+                MetricValue::Unsigned(disk.used_bytes),
+                "bytes",
+            ).with_tag("mount", &disk.mount_point)
+             .with_tag("type", "used"));
+            
+            metrics.push(Metric::new(
+                MetricType::DiskSpace,
+                MetricValue::Unsigned(disk.available_bytes),
+                "bytes",
+            ).with_tag("mount", &disk.mount_point)
+             .with_tag("type", "available"));
+            
+            metrics.push(Metric::new(
+                MetricType::DiskSpace,
+                MetricValue::Unsigned(disk.total_bytes),
+                "bytes",
+            ).with_tag("mount", &disk.mount_point)
+             .with_tag("type", "total"));
+            
+            // I/O metrics
+            if disk.read_bytes_per_sec > 0 || disk.write_bytes_per_sec > 0 {
+                metrics.push(Metric::new(
+                    MetricType::DiskIo,
+                    MetricValue::Unsigned(disk.read_bytes_per_sec),
+                    "bytes/s",
+                ).with_tag("mount", &disk.mount_point)
+                 .with_tag("operation", "read"));
+                
+                metrics.push(Metric::new(
+                    MetricType::DiskIo,
+                    MetricValue::Unsigned(disk.write_bytes_per_sec),
+                    "bytes/s",
+                ).with_tag("mount", &disk.mount_point)
+                 .with_tag("operation", "write"));
+            }
+            
+            metrics.push(Metric::new(
+                MetricType::DiskBusy,
+                MetricValue::Float(disk.busy_percent as f64),
+                "%",
+            ).with_tag("mount", &disk.mount_point)
+             .with_tag("device", &disk.device_name));
+            
+            if let Some(smart) = &disk.smart {
+                metrics.push(smart.to_metric().with_tag("mount", &disk.mount_point));
+            }
+            
+            if let (Some(total), Some(used), Some(usage)) = (disk.inodes_total, disk.inodes_used, disk.inode_usage_percent) {
+                metrics.push(Metric::new(
+                    MetricType::DiskInodes,
+                    MetricValue::percentage(usage as f64),
+                    "%",
+                ).with_tag("mount", &disk.mount_point)
+                 .with_tag("type", "usage"));
+                
+                for (kind, count) in [("used", used), ("total", total)] {
+                    metrics.push(Metric::new(
+                        MetricType::DiskInodes,
+                        MetricValue::Unsigned(count),
+                        "count",
+                    ).with_tag("mount", &disk.mount_point)
+                     .with_tag("type", kind));
+                }
+            }
+        }
+        
+        // Pools carry a pool tag rather than a mount
+        metrics.extend(sample.pools.iter().map(PoolMetrics::to_metric));
+        
+        metrics
     }
 
-    fn update_history(&self, metrics: Vec<DiskMetrics>) {
+    fn update_history(&self, sample: StorageSample) {
         let mut history = self.metrics_history.write();
-        let config = self.config.read();
+        let config = self.shared.config.read();
         
-        history.push_back(metrics);
+        history.push_back((self.clock.now(), sample));
         
         // Remove old metrics based on retention policy
         let max_entries = (config.retain_history_seconds * 1000 / config.interval_ms) as usize;
         while history.len() > max_entries {
             history.pop_front();
         }
+
+        trim_history(&mut history, config.max_history_bytes);
     }
 }
 
@@ -196,12 +850,16 @@ impl Monitor for StorageMonitor {
     }
 
     fn state(&self) -> MonitorState {
-        *self.state.read()
+        self.shared.state.read().clone()
+    }
+
+    fn shared(&self) -> Option<&MonitorShared> {
+        Some(&self.shared)
     }
 
     async fn initialize(&mut self, config: MonitorConfig) -> Result<()> {
-        *self.state.write() = MonitorState::Initializing;
-        *self.config.write() = config;
+        *self.shared.state.write() = MonitorState::Initializing;
+        *self.shared.config.write() = config;
         
         // Initialize disk info
         let mut disks = Disks::new_with_refreshed_list();
@@ -210,111 +868,68 @@ impl Monitor for StorageMonitor {
         // Collect initial I/O stats
         let _ = self.collect_storage_metrics()?;
         
-        *self.state.write() = MonitorState::Running;
+        *self.shared.state.write() = MonitorState::Running;
         Ok(())
     }
 
+
     async fn start(&mut self) -> Result<()> {
         match self.state() {
-            MonitorState::Running => return Ok(()),
+            MonitorState::Running | MonitorState::Degraded { .. } => return Ok(()),
             MonitorState::Uninitialized => {
                 return Err(MonitorError::NotInitialized);
             }
             _ => {}
         }
         
-        *self.state.write() = MonitorState::Running;
+        *self.shared.state.write() = MonitorState::Running;
         Ok(())
     }
 
     async fn stop(&mut self) -> Result<()> {
-        *self.state.write() = MonitorState::Stopped;
+        *self.shared.state.write() = MonitorState::Stopped;
         Ok(())
     }
 
     async fn pause(&mut self) -> Result<()> {
-        *self.state.write() = MonitorState::Paused;
+        *self.shared.state.write() = MonitorState::Paused;
         Ok(())
     }
 
     async fn resume(&mut self) -> Result<()> {
-        *self.state.write() = MonitorState::Running;
+        *self.shared.state.write() = MonitorState::Running;
         Ok(())
     }
 
     async fn collect(&mut self) -> Result<Vec<Metric>> {
-        if self.state() != MonitorState::Running {
+        if !self.state().is_collecting() {
             return Err(MonitorError::NotInitialized);
         }
 
-        let disk_metrics = self.collect_storage_metrics()?;
-        self.update_history(disk_metrics.clone());
-        *self.last_update.write() = SystemTime::now();
-
-        let mut metrics = Vec::new();
-        
-        for disk in disk_metrics.iter() {
-            // Disk usage percentage
-            metrics.push(Metric::new(
-                MetricType::DiskUsage,
-                MetricValue::Float(disk.usage_percent as f64),
-                "%",
-            ).with_tag("mount", &disk.mount_point)
-             .with_tag("device", &disk.device_name));
-            
-            // Disk space metrics
-            metrics.push(Metric::new(
-                MetricType::DiskSpace,
-                MetricValue::Unsigned(disk.used_bytes),
-                "bytes",
-            ).with_tag("mount", &disk.mount_point)
-             .with_tag("type", "used"));
-            
-            metrics.push(Metric::new(
-                MetricType::DiskSpace,
-                MetricValue::Unsigned(disk.available_bytes),
-                "bytes",
-            ).with_tag("mount", &disk.mount_point)
-             .with_tag("type", "available"));
-            
-            metrics.push(Metric::new(
-                MetricType::DiskSpace,
-                MetricValue::Unsigned(disk.total_bytes),
-                "bytes",
-            ).with_tag("mount", &disk.mount_point)
-             .with_tag("type", "total"));
-            
-            // I/O metrics
-            if disk.read_bytes_per_sec > 0 || disk.write_bytes_per_sec > 0 {
-                metrics.push(Metric::new(
-                    MetricType::DiskIo,
-                    MetricValue::Unsigned(disk.read_bytes_per_sec),
-                    "bytes/s",
-                ).with_tag("mount", &disk.mount_point)
-                 .with_tag("operation", "read"));
-                
-                metrics.push(Metric::new(
-                    MetricType::DiskIo,
-                    MetricValue::Unsigned(disk.write_bytes_per_sec),
-                    "bytes/s",
-                ).with_tag("mount", &disk.mount_point)
-                 .with_tag("operation", "write"));
-            }
+        let mut result = self.collect_storage_metrics();
+        if let Ok(disks) = &mut result {
+            self.fill_smart(disks).await;
         }
-        
+        self.shared.state.write().record_collection(&result);
+        let disks = result?;
+        let sample = StorageSample { pools: read_pools(&disks), disks };
+        let metrics = Self::sample_to_metrics(&sample);
+        self.update_history(sample);
+        *self.shared.last_update.write() = self.clock.now();
+
         Ok(metrics)
     }
 
     async fn get_current_metrics(&self) -> Result<Vec<Metric>> {
         let history = self.metrics_history.read();
         
-        if let Some(latest) = history.back() {
+        if let Some((_, latest)) = history.back() {
             let mut metrics = Vec::new();
             
-            for disk in latest.iter() {
+            for disk in latest.disks.iter() {
                 metrics.push(Metric::new(
                     MetricType::DiskUsage,
-                    MetricValue::Float(disk.usage_percent as f64),
+                    MetricValue::percentage(disk.usage_percent as f64),
                     "%",
                 ).with_tag("mount", &disk.mount_point));
                 
@@ -331,15 +946,15 @@ impl Monitor for StorageMonitor {
         }
     }
 
-    async fn get_historical_metrics(&self, _duration_seconds: u64) -> Result<Vec<Metric>> {
+    async fn get_historical_metrics(&self, duration_seconds: u64) -> Result<Vec<Metric>> {
         let history = self.metrics_history.read();
         let mut metrics = Vec::new();
         
-        for disk_list in history.iter() {
-            for disk in disk_list.iter() {
+        for sample in history_since(&history, duration_seconds, self.clock.now()) {
+            for disk in sample.disks.iter() {
                 metrics.push(Metric::new(
                     MetricType::DiskUsage,
-                    MetricValue::Float(disk.usage_percent as f64),
+                    MetricValue::percentage(disk.usage_percent as f64),
                     "%",
                 ).with_tag("mount", &disk.mount_point));
             }
@@ -348,10 +963,29 @@ impl Monitor for StorageMonitor {
         Ok(metrics)
     }
 
+    async fn history_range(&self, start: SystemTime, end: SystemTime) -> Vec<(SystemTime, Vec<Metric>)> {
+        history_in_range(&self.metrics_history.read(), start, end, Self::sample_to_metrics)
+    }
+
     fn supports_feature(&self, feature: &str) -> bool {
         matches!(feature, 
             "disk_usage" | "disk_space" | "disk_io" | 
-            "disk_read" | "disk_write"
+            "disk_read" | "disk_write" | "storage_pools"
         )
     }
+
+    fn supported_metrics(&self) -> Vec<MetricType> {
+        let mut types = vec![MetricType::DiskUsage, MetricType::DiskSpace, MetricType::DiskIo, MetricType::DiskBusy];
+        let history = self.metrics_history.read();
+        if history.back().is_some_and(|(_, sample)| sample.disks.iter().any(|d| d.smart.is_some())) {
+            types.push(MetricType::DiskHealth);
+        }
+        if history.back().is_some_and(|(_, sample)| sample.disks.iter().any(|d| d.inodes_total.is_some())) {
+            types.push(MetricType::DiskInodes);
+        }
+        if history.back().is_some_and(|(_, sample)| !sample.pools.is_empty()) {
+            types.push(MetricType::StoragePool);
+        }
+        types
+    }
 }
\ No newline at end of file
@@ -1,5 +1,5 @@
 use crate::backend::{cpu_monitor::CpuMonitor, gpu_monitor::GpuMonitor, memory_monitor::MemoryMonitor, storage_monitor::StorageMonitor};
-use crate::core::{Monitor, MonitorConfig, MetricType, MetricValue};
+use crate::core::{format_bytes, Monitor, MonitorConfig, MetricType, MetricValue, UiConfig};
 use std::collections::HashMap;
 
 
@@ -7,8 +7,10 @@ use std::collections::HashMap;
 pub struct SystemMonitor;
 
 impl SystemMonitor {
-    /// Collects system metrics asynchronously
-    pub async fn collect_metrics() -> HashMap<String, String> {
+    /// Collects system metrics asynchronously, with byte sizes rendered in
+    /// the configured `display_units`
+    pub async fn collect_metrics(ui: &UiConfig) -> HashMap<String, String> {
+        let units = ui.display_units;
         let mut metrics = HashMap::new();
 
         // Initialize monitors
@@ -28,11 +30,9 @@ impl SystemMonitor {
         if let Ok(cpu_metrics) = cpu_monitor.collect().await {
             for metric in cpu_metrics {
                 match metric.metric_type {
-                    MetricType::CpuUsage => {
-                        if metric.tags.is_empty() {  // Global CPU usage
-                            if let MetricValue::Float(usage) = metric.value {
-                                metrics.insert("CPU Usage".to_string(), format!("{:.2}%", usage));
-                            }
+                    MetricType::CpuUsage if metric.tags.is_empty() => {  // Global CPU usage
+                        if let Some(usage) = metric.value.as_f64() {
+                            metrics.insert("CPU Usage".to_string(), format!("{:.2}%", usage));
                         }
                     }
                     MetricType::CpuFrequency => {
@@ -63,7 +63,7 @@ impl SystemMonitor {
                                     _ => {}
                                 }
                             }
-                        } else if let MetricValue::Float(percent) = metric.value {
+                        } else if let Some(percent) = metric.value.as_f64() {
                             usage_percent = percent;
                         }
                     }
@@ -76,14 +76,9 @@ impl SystemMonitor {
                 }
             }
 
-            // Convert bytes to GB
-            let total_gb = total_bytes as f64 / (1024.0 * 1024.0 * 1024.0);
-            let used_gb = used_bytes as f64 / (1024.0 * 1024.0 * 1024.0);
-            let available_gb = available_bytes as f64 / (1024.0 * 1024.0 * 1024.0);
-
-            metrics.insert("Memory Total".to_string(), format!("{:.2} GB", total_gb));
-            metrics.insert("Memory Available".to_string(), format!("{:.2} GB", available_gb));
-            metrics.insert("Memory Used".to_string(), format!("{:.2} GB", used_gb));
+            metrics.insert("Memory Total".to_string(), format_bytes(total_bytes, units));
+            metrics.insert("Memory Available".to_string(), format_bytes(available_bytes, units));
+            metrics.insert("Memory Used".to_string(), format_bytes(used_bytes, units));
             metrics.insert("Memory Usage %".to_string(), format!("{:.2}%", usage_percent));
         }
 
@@ -93,28 +88,21 @@ impl SystemMonitor {
             let mut available_bytes = 0u64;
 
             for metric in storage_metrics {
-                match metric.metric_type {
-                    MetricType::DiskSpace => {
-                        if let Some(tag_type) = metric.tags.get("type") {
-                            if let MetricValue::Unsigned(bytes) = metric.value {
-                                match tag_type.as_str() {
-                                    "total" => total_bytes += bytes,
-                                    "available" => available_bytes += bytes,
-                                    _ => {}
-                                }
+                if metric.metric_type == MetricType::DiskSpace {
+                    if let Some(tag_type) = metric.tags.get("type") {
+                        if let MetricValue::Unsigned(bytes) = metric.value {
+                            match tag_type.as_str() {
+                                "total" => total_bytes += bytes,
+                                "available" => available_bytes += bytes,
+                                _ => {}
                             }
                         }
                     }
-                    _ => {}
                 }
             }
 
-            // Convert bytes to GB
-            let total_gb = total_bytes as f64 / (1024.0 * 1024.0 * 1024.0);
-            let available_gb = available_bytes as f64 / (1024.0 * 1024.0 * 1024.0);
-
-            metrics.insert("Storage Total".to_string(), format!("{:.2} GB", total_gb));
-            metrics.insert("Storage Available".to_string(), format!("{:.2} GB", available_gb));
+            metrics.insert("Storage Total".to_string(), format_bytes(total_bytes, units));
+            metrics.insert("Storage Available".to_string(), format_bytes(available_bytes, units));
         }
 
         // Collect GPU metrics
@@ -122,13 +110,13 @@ impl SystemMonitor {
             for metric in gpu_metrics {
                 match metric.metric_type {
                     MetricType::GpuUsage => {
-                        if let MetricValue::Float(usage) = metric.value {
+                        if let Some(usage) = metric.value.as_f64() {
                             let gpu_name = metric.tags.get("name").unwrap_or(&"GPU".to_string()).clone();
                             metrics.insert(format!("{} Usage", gpu_name), format!("{:.2}%", usage));
                         }
                     }
                     MetricType::GpuMemoryUsage => {
-                        if let MetricValue::Float(usage) = metric.value {
+                        if let Some(usage) = metric.value.as_f64() {
                             let gpu_name = metric.tags.get("name").unwrap_or(&"GPU".to_string()).clone();
                             metrics.insert(format!("{} Memory Usage", gpu_name), format!("{:.2}%", usage));
                         }
@@ -0,0 +1,213 @@
+//! GPU usage and video memory on Windows for adapters without a vendor
+//! library, i.e. AMD and Intel, which `Win32_VideoController` reports only by
+//! name and (capped at 4 GiB) memory size.
+//!
+//! DXGI enumerates the adapters with their dedicated memory and LUID; the
+//! `GPU Engine` and `GPU Adapter Memory` performance counters, keyed by that
+//! LUID, give utilization and memory in use. The memory counter is used rather
+//! than `IDXGIAdapter3::QueryVideoMemoryInfo`, whose `CurrentUsage` only
+//! covers the calling process.
+//!
+//! This file has no dependencies on the rest of the crate so the Tauri
+//! backend can include it as well.
+
+use std::collections::HashMap;
+
+/// PCI vendor IDs as reported in `DXGI_ADAPTER_DESC1::VendorId`
+pub const VENDOR_AMD: u32 = 0x1002;
+pub const VENDOR_INTEL: u32 = 0x8086;
+
+/// A hardware adapter as enumerated by DXGI
+#[derive(Debug, Clone, PartialEq)]
+pub struct DxgiAdapter {
+    pub name: String,
+    pub vendor_id: u32,
+    /// Same form as the LUID in performance counter instance names, see
+    /// `format_luid`
+    pub luid: String,
+    pub dedicated_memory_bytes: u64,
+}
+
+/// Counter readings for one adapter
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct AdapterUsage {
+    /// Utilization of the busiest engine, as Task Manager shows it
+    pub usage_percent: f32,
+    pub dedicated_used_bytes: u64,
+}
+
+/// Renders a LUID as it appears in counter instance names, lowercased:
+/// `0x00000000_0x0000d1b7`
+pub fn format_luid(high_part: i32, low_part: u32) -> String {
+    format!("0x{:08x}_0x{:08x}", high_part as u32, low_part)
+}
+
+/// Splits a counter instance name such as
+/// `pid_1234_luid_0x00000000_0x0000D1B7_phys_0_eng_0_engtype_3D` into the
+/// lowercased adapter LUID and the rest of the name after it, which
+/// identifies the engine
+pub fn parse_counter_instance(instance: &str) -> Option<(String, &str)> {
+    let start = instance.find("luid_")? + "luid_".len();
+    let luid = instance.get(start..start + 21)?;
+    let (high, low) = luid.split_once('_')?;
+    if !(high.starts_with("0x") && low.starts_with("0x")) {
+        return None;
+    }
+    Some((luid.to_ascii_lowercase(), &instance[start + 21..]))
+}
+
+/// Combines `GPU Engine(*)\Utilization Percentage` and
+/// `GPU Adapter Memory(*)\Dedicated Usage` readings into per-adapter usage,
+/// keyed by LUID.
+///
+/// Engine instances are per process; they are summed per engine and the
+/// busiest engine is the adapter's usage.
+pub fn adapter_usage<'a>(
+    engines: impl IntoIterator<Item = (&'a str, f64)>,
+    memory: impl IntoIterator<Item = (&'a str, f64)>,
+) -> HashMap<String, AdapterUsage> {
+    let mut per_engine: HashMap<(String, &str), f64> = HashMap::new();
+    for (instance, value) in engines {
+        if let Some((luid, engine)) = parse_counter_instance(instance) {
+            *per_engine.entry((luid, engine)).or_default() += value;
+        }
+    }
+
+    let mut usage: HashMap<String, AdapterUsage> = HashMap::new();
+    for ((luid, _), busy) in per_engine {
+        let adapter = usage.entry(luid).or_default();
+        adapter.usage_percent = adapter.usage_percent.max(busy.clamp(0.0, 100.0) as f32);
+    }
+    for (instance, bytes) in memory {
+        if let Some((luid, _)) = parse_counter_instance(instance) {
+            usage.entry(luid).or_default().dedicated_used_bytes += bytes.max(0.0) as u64;
+        }
+    }
+    usage
+}
+
+#[cfg(target_os = "windows")]
+pub use self::windows_impl::{dxgi_adapters, GpuCounters};
+
+#[cfg(target_os = "windows")]
+mod windows_impl {
+    use super::{adapter_usage, format_luid, AdapterUsage, DxgiAdapter};
+    use std::collections::HashMap;
+    use windows::core::{w, PCWSTR};
+    use windows::Win32::Graphics::Dxgi::{CreateDXGIFactory1, IDXGIFactory1, DXGI_ADAPTER_DESC1, DXGI_ADAPTER_FLAG_SOFTWARE};
+    use windows::Win32::System::Performance::{
+        PdhAddEnglishCounterW, PdhCloseQuery, PdhCollectQueryData, PdhGetFormattedCounterArrayW, PdhOpenQueryW,
+        PDH_FMT_COUNTERVALUE_ITEM_W, PDH_FMT_DOUBLE, PDH_MORE_DATA,
+    };
+
+    /// Hardware adapters; the software rasterizer is left out
+    pub fn dxgi_adapters() -> windows::core::Result<Vec<DxgiAdapter>> {
+        let factory: IDXGIFactory1 = unsafe { CreateDXGIFactory1()? };
+        let mut adapters = Vec::new();
+
+        // EnumAdapters1 fails with DXGI_ERROR_NOT_FOUND past the last adapter
+        for index in 0.. {
+            let Ok(adapter) = (unsafe { factory.EnumAdapters1(index) }) else {
+                break;
+            };
+            let mut desc = DXGI_ADAPTER_DESC1::default();
+            unsafe { adapter.GetDesc1(&mut desc)? };
+            if desc.Flags & DXGI_ADAPTER_FLAG_SOFTWARE.0 as u32 != 0 {
+                continue;
+            }
+
+            let name_len = desc.Description.iter().position(|&c| c == 0).unwrap_or(desc.Description.len());
+            adapters.push(DxgiAdapter {
+                name: String::from_utf16_lossy(&desc.Description[..name_len]).trim().to_string(),
+                vendor_id: desc.VendorId,
+                luid: format_luid(desc.AdapterLuid.HighPart, desc.AdapterLuid.LowPart),
+                dedicated_memory_bytes: desc.DedicatedVideoMemory as u64,
+            });
+        }
+
+        Ok(adapters)
+    }
+
+    /// An open PDH query over the GPU counters.
+    ///
+    /// Utilization is a rate between two collections, so the query is kept
+    /// open and each `sample` reports the time since the previous one.
+    pub struct GpuCounters {
+        query: isize,
+        engines: isize,
+        memory: isize,
+    }
+
+    impl GpuCounters {
+        /// None when the counters don't exist, e.g. before Windows 10 1709
+        pub fn open() -> Option<Self> {
+            let mut query = 0;
+
+            unsafe {
+                if PdhOpenQueryW(PCWSTR::null(), 0, &mut query) != 0 {
+                    return None;
+                }
+                // Dropped, and the query closed, if a counter can't be added
+                let mut counters = Self { query, engines: 0, memory: 0 };
+                if PdhAddEnglishCounterW(query, w!("\\GPU Engine(*)\\Utilization Percentage"), 0, &mut counters.engines) != 0
+                    || PdhAddEnglishCounterW(query, w!("\\GPU Adapter Memory(*)\\Dedicated Usage"), 0, &mut counters.memory) != 0
+                {
+                    return None;
+                }
+
+                // Baseline for the first utilization reading
+                PdhCollectQueryData(query);
+                Some(counters)
+            }
+        }
+
+        /// Usage per adapter LUID since the previous sample
+        pub fn sample(&self) -> HashMap<String, AdapterUsage> {
+            if unsafe { PdhCollectQueryData(self.query) } != 0 {
+                return HashMap::new();
+            }
+
+            let engines = read_counter_array(self.engines);
+            let memory = read_counter_array(self.memory);
+            adapter_usage(
+                engines.iter().map(|(name, value)| (name.as_str(), *value)),
+                memory.iter().map(|(name, value)| (name.as_str(), *value)),
+            )
+        }
+    }
+
+    impl Drop for GpuCounters {
+        fn drop(&mut self) {
+            unsafe {
+                PdhCloseQuery(self.query);
+            }
+        }
+    }
+
+    /// Instance name and value of every instance of a wildcard counter
+    fn read_counter_array(counter: isize) -> Vec<(String, f64)> {
+        let mut size = 0u32;
+        let mut count = 0u32;
+
+        unsafe {
+            if PdhGetFormattedCounterArrayW(counter, PDH_FMT_DOUBLE, &mut size, &mut count, None) != PDH_MORE_DATA {
+                return Vec::new();
+            }
+
+            // `size` is in bytes and covers the instance names stored after the items
+            let item_size = std::mem::size_of::<PDH_FMT_COUNTERVALUE_ITEM_W>();
+            let mut items: Vec<PDH_FMT_COUNTERVALUE_ITEM_W> = Vec::with_capacity(size as usize / item_size + 1);
+            if PdhGetFormattedCounterArrayW(counter, PDH_FMT_DOUBLE, &mut size, &mut count, Some(items.as_mut_ptr())) != 0 {
+                return Vec::new();
+            }
+            items.set_len(count as usize);
+
+            items
+                .iter()
+                // PDH_CSTATUS_VALID_DATA or PDH_CSTATUS_NEW_DATA
+                .filter(|item| item.FmtValue.CStatus <= 1)
+                .filter_map(|item| Some((item.szName.to_string().ok()?, item.FmtValue.Anonymous.doubleValue)))
+                .collect()
+        }
+    }
+}
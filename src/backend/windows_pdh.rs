@@ -0,0 +1,101 @@
+//! Raw Windows performance counters read through PDH.
+//!
+//! The monitors want the running totals behind counters like
+//! `\System\Context Switches/sec` and take the rates themselves, so values
+//! are read raw rather than formatted.
+
+use windows::core::{HSTRING, PCWSTR};
+use windows::Win32::System::Performance::{
+    PdhAddEnglishCounterW, PdhCloseQuery, PdhCollectQueryData, PdhGetRawCounterArrayW, PdhGetRawCounterValue,
+    PdhOpenQueryW, PDH_MORE_DATA, PDH_RAW_COUNTER, PDH_RAW_COUNTER_ITEM_W,
+};
+
+/// An open PDH query over a fixed list of counters.
+///
+/// Opening the query is the expensive part, so monitors keep it across
+/// collections and call `collect` once per pass.
+pub struct RawCounters {
+    query: isize,
+    counters: Vec<isize>,
+}
+
+impl RawCounters {
+    /// Adds each of `paths` (English counter paths, `*` for every instance);
+    /// None when the query can't be opened or a counter doesn't exist
+    pub fn open(paths: &[&str]) -> Option<Self> {
+        let mut query = 0;
+
+        unsafe {
+            if PdhOpenQueryW(PCWSTR::null(), 0, &mut query) != 0 {
+                return None;
+            }
+            // Dropped, and the query closed, if a counter can't be added
+            let mut raw = Self { query, counters: Vec::with_capacity(paths.len()) };
+            for path in paths {
+                let mut counter = 0;
+                if PdhAddEnglishCounterW(query, &HSTRING::from(*path), 0, &mut counter) != 0 {
+                    return None;
+                }
+                raw.counters.push(counter);
+            }
+            Some(raw)
+        }
+    }
+
+    /// Samples every counter; false when the sample failed
+    pub fn collect(&self) -> bool {
+        unsafe { PdhCollectQueryData(self.query) == 0 }
+    }
+
+    /// Raw value of the `index`th counter in the last sample
+    pub fn value(&self, index: usize) -> Option<u64> {
+        let counter = *self.counters.get(index)?;
+        let mut value = PDH_RAW_COUNTER::default();
+
+        unsafe {
+            if PdhGetRawCounterValue(counter, None, &mut value) != 0 {
+                return None;
+            }
+        }
+        // PDH_CSTATUS_VALID_DATA or PDH_CSTATUS_NEW_DATA
+        (value.CStatus <= 1).then_some(value.FirstValue.max(0) as u64)
+    }
+
+    /// Instance name and raw value of every instance of the `index`th
+    /// counter in the last sample
+    pub fn instances(&self, index: usize) -> Vec<(String, u64)> {
+        let Some(&counter) = self.counters.get(index) else {
+            return Vec::new();
+        };
+        let mut size = 0u32;
+        let mut count = 0u32;
+
+        unsafe {
+            if PdhGetRawCounterArrayW(counter, &mut size, &mut count, None) != PDH_MORE_DATA {
+                return Vec::new();
+            }
+
+            // `size` is in bytes and covers the instance names stored after the items
+            let item_size = std::mem::size_of::<PDH_RAW_COUNTER_ITEM_W>();
+            let mut items: Vec<PDH_RAW_COUNTER_ITEM_W> = Vec::with_capacity(size as usize / item_size + 1);
+            if PdhGetRawCounterArrayW(counter, &mut size, &mut count, Some(items.as_mut_ptr())) != 0 {
+                return Vec::new();
+            }
+            items.set_len(count as usize);
+
+            items
+                .iter()
+                .filter(|item| item.RawValue.CStatus <= 1)
+                .filter_map(|item| Some((item.szName.to_string().ok()?, item.RawValue.FirstValue.max(0) as u64)))
+                .collect()
+        }
+    }
+}
+
+impl Drop for RawCounters {
+    fn drop(&mut self) {
+        unsafe {
+            PdhCloseQuery(self.query);
+        }
+    }
+}
@@ -22,7 +22,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     
     // Initialize monitoring service
     println!("\nInitializing monitoring service...");
-    let monitoring_service = MonitoringService::new();
+    let monitoring_service = MonitoringService::from_config(config);
     monitoring_service.initialize().await?;
     
     // Apply configuration
@@ -1,6 +1,5 @@
 use system_monitor::backend::{CpuMonitor, MemoryMonitor, GpuMonitor, StorageMonitor};
 use system_monitor::core::{Monitor, MonitorConfig};
-use tokio;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
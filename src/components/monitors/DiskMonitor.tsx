@@ -1,7 +1,7 @@
 import React from 'react';
 import { HardDrive } from 'lucide-react';
 import { DiskMetrics } from '../../types';
-import { formatBytes, formatPercent } from '../../utils/format';
+import { formatBytes, formatBytesPerSecond, formatPercent } from '../../utils/format';
 
 interface DiskMonitorProps {
   disks: DiskMetrics[];
@@ -29,7 +29,7 @@ export const DiskMonitor: React.FC<DiskMonitorProps> = ({ disks }) => {
       
       <div className="space-y-4">
         {disks.map((disk) => (
-          <div key={disk.mount_point} className="p-4 bg-gray-700/50 rounded-lg">
+          <div key={disk.id} className="p-4 bg-gray-700/50 rounded-lg">
             <div className="flex items-center justify-between mb-2">
               <div>
                 <p className="font-medium">{disk.mount_point}</p>
@@ -53,10 +53,10 @@ export const DiskMonitor: React.FC<DiskMonitorProps> = ({ disks }) => {
             {(disk.read_bytes_per_sec > 0 || disk.write_bytes_per_sec > 0) && (
               <div className="mt-2 flex justify-between text-sm">
                 <span className="text-gray-400">
-                  Read: {formatBytes(disk.read_bytes_per_sec)}/s
+                  Read: {formatBytesPerSecond(disk.read_bytes_per_sec)}
                 </span>
                 <span className="text-gray-400">
-                  Write: {formatBytes(disk.write_bytes_per_sec)}/s
+                  Write: {formatBytesPerSecond(disk.write_bytes_per_sec)}
                 </span>
               </div>
             )}
@@ -1,5 +1,6 @@
 import React from 'react';
 import { DpuMetrics } from '../../types';
+import { formatBytes } from '../../utils/format';
 import { Activity, Cpu, Thermometer, Zap, Network } from 'lucide-react';
 
 interface DpuMonitorProps {
@@ -66,8 +67,7 @@ const DpuMonitor: React.FC<DpuMonitorProps> = ({ dpus }) => {
                     {((dpu.memory_used_bytes / dpu.memory_total_bytes) * 100).toFixed(1)}%
                   </div>
                   <div className="text-muted-foreground">
-                    {Math.round(dpu.memory_used_bytes / 1024 / 1024 / 1024)}/
-                    {Math.round(dpu.memory_total_bytes / 1024 / 1024 / 1024)} GB
+                    {formatBytes(dpu.memory_used_bytes)} / {formatBytes(dpu.memory_total_bytes)}
                   </div>
                 </div>
                 <div>
@@ -29,7 +29,7 @@ export const NetworkMonitor: React.FC<NetworkMonitorProps> = ({ networks }) => {
       
       <div className="space-y-4">
         {networks.map((network) => (
-          <div key={network.interface_name} className="p-4 bg-gray-700/50 rounded-lg">
+          <div key={network.id} className="p-4 bg-gray-700/50 rounded-lg">
             <div className="flex items-center justify-between mb-2">
               <div className="flex items-center space-x-2">
                 {network.is_up ? (
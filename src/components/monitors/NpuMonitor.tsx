@@ -1,5 +1,6 @@
 import React from 'react';
 import { NpuMetrics } from '../../types';
+import { formatBytes } from '../../utils/format';
 import { Brain, Cpu, Thermometer, Zap, Activity } from 'lucide-react';
 
 interface NpuMonitorProps {
@@ -66,8 +67,7 @@ const NpuMonitor: React.FC<NpuMonitorProps> = ({ npus }) => {
                     {((npu.memory_used_bytes / npu.memory_total_bytes) * 100).toFixed(1)}%
                   </div>
                   <div className="text-muted-foreground">
-                    {Math.round(npu.memory_used_bytes / 1024 / 1024 / 1024)}/
-                    {Math.round(npu.memory_total_bytes / 1024 / 1024 / 1024)} GB
+                    {formatBytes(npu.memory_used_bytes)} / {formatBytes(npu.memory_total_bytes)}
                   </div>
                 </div>
                 <div>
@@ -0,0 +1,84 @@
+use std::fmt;
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use parking_lot::Mutex;
+
+/// Source of the timestamps monitors put on samples and of the instants
+/// they compute rates from, so rate logic can be driven by a `MockClock`
+pub trait Clock: Send + Sync + fmt::Debug {
+    /// Wall-clock time, for timestamps
+    fn now(&self) -> SystemTime;
+
+    /// Monotonic time, for the elapsed time between samples
+    fn instant(&self) -> Instant;
+}
+
+/// A clock shared by a service and the monitors it registers
+pub type SharedClock = Arc<dyn Clock>;
+
+/// The real clock
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl SystemClock {
+    pub fn shared() -> SharedClock {
+        Arc::new(SystemClock)
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+
+    fn instant(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// A clock that only moves when told to; clones share the same time
+#[derive(Debug, Clone)]
+pub struct MockClock {
+    time: Arc<Mutex<(SystemTime, Instant)>>,
+}
+
+impl Default for MockClock {
+    fn default() -> Self {
+        Self::new(UNIX_EPOCH + Duration::from_secs(1_700_000_000))
+    }
+}
+
+impl MockClock {
+    /// Starts at `now`; instants start at the real instant of creation,
+    /// since `Instant` can't be built from a fixed value
+    pub fn new(now: SystemTime) -> Self {
+        Self { time: Arc::new(Mutex::new((now, Instant::now()))) }
+    }
+
+    /// Moves both the wall clock and the monotonic clock forward
+    pub fn advance(&self, by: Duration) {
+        let mut time = self.time.lock();
+        time.0 += by;
+        time.1 += by;
+    }
+
+    /// Sets the wall clock alone, as a clock adjustment would
+    pub fn set_now(&self, now: SystemTime) {
+        self.time.lock().0 = now;
+    }
+
+    pub fn shared(&self) -> SharedClock {
+        Arc::new(self.clone())
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> SystemTime {
+        self.time.lock().0
+    }
+
+    fn instant(&self) -> Instant {
+        self.time.lock().1
+    }
+}
@@ -29,8 +29,33 @@ pub enum MonitorError {
     #[error("Collection error: {0}")]
     CollectionError(String),
     
+    /// A serialized `SystemMetrics` was written with a different schema;
+    /// `found` is 0 when the version marker is missing
+    #[error("Schema version mismatch: expected {expected}, found {found}")]
+    SchemaMismatch { expected: u32, found: u32 },
+    
+    /// The collector needs root or administrator rights that the process
+    /// doesn't have
+    #[error("Permission denied: {operation} requires root or administrator privileges")]
+    PermissionDenied { operation: String },
+    
+    /// The platform or hardware doesn't offer this at all
+    #[error("Unsupported: {feature}")]
+    Unsupported { feature: String },
+    
     #[error("Unknown error: {0}")]
     Unknown(String),
 }
 
+impl MonitorError {
+    /// Wraps an I/O error from `operation`, keeping permission failures
+    /// distinct so callers can ask for elevated privileges
+    pub fn from_io(operation: impl Into<String>, error: std::io::Error) -> Self {
+        match error.kind() {
+            std::io::ErrorKind::PermissionDenied => MonitorError::PermissionDenied { operation: operation.into() },
+            _ => MonitorError::IoError(error),
+        }
+    }
+}
+
 pub type Result<T> = std::result::Result<T, MonitorError>;
\ No newline at end of file
@@ -0,0 +1,141 @@
+use std::io::Write;
+use std::time::UNIX_EPOCH;
+
+use crate::core::{format_bytes, format_rate, Result, SystemMetrics, ThroughputUnit, UnitSystem};
+
+/// The disks and interfaces a `CsvWriter` has columns for, in column order
+///
+/// Columns are fixed when the writer is created so that every row lines up
+/// with the header even as disks are mounted or interfaces come and go.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CsvSchema {
+    pub mount_points: Vec<String>,
+    pub interfaces: Vec<String>,
+}
+
+impl CsvSchema {
+    /// Takes the disks and interfaces present in `metrics`
+    pub fn from_metrics(metrics: &SystemMetrics) -> Self {
+        Self {
+            mount_points: metrics.disks.iter().map(|d| d.mount_point.clone()).collect(),
+            interfaces: metrics.networks.iter().map(|n| n.interface_name.clone()).collect(),
+        }
+    }
+
+    /// Header names, one per column
+    ///
+    /// After the fixed `timestamp`, `cpu_usage`, `mem_used` and `mem_total`
+    /// columns, each mount point gets `disk<N>_usage`, `disk<N>_read_bps` and
+    /// `disk<N>_write_bps`, then each interface gets `net<N>_rx_bps` and
+    /// `net<N>_tx_bps`, where N is its index in the schema. Timestamps are
+    /// Unix seconds, memory is in bytes, usage in percent and rates in bytes
+    /// per second.
+    pub fn columns(&self) -> Vec<String> {
+        let mut columns: Vec<String> = ["timestamp", "cpu_usage", "mem_used", "mem_total"]
+            .iter()
+            .map(|c| c.to_string())
+            .collect();
+        for index in 0..self.mount_points.len() {
+            columns.push(format!("disk{}_usage", index));
+            columns.push(format!("disk{}_read_bps", index));
+            columns.push(format!("disk{}_write_bps", index));
+        }
+        for index in 0..self.interfaces.len() {
+            columns.push(format!("net{}_rx_bps", index));
+            columns.push(format!("net{}_tx_bps", index));
+        }
+        columns
+    }
+}
+
+/// Appends `SystemMetrics` samples to a CSV sink, one row each
+///
+/// The header is written before the first row. Disks and interfaces missing
+/// from a sample are left as empty cells, and ones not in the schema are
+/// dropped.
+pub struct CsvWriter<W: Write> {
+    sink: W,
+    schema: CsvSchema,
+    header_written: bool,
+    display_units: Option<(UnitSystem, ThroughputUnit)>,
+}
+
+impl<W: Write> CsvWriter<W> {
+    pub fn new(sink: W, schema: CsvSchema) -> Self {
+        Self { sink, schema, header_written: false, display_units: None }
+    }
+
+    /// Writes memory and rate cells as human-readable text (`1.50 GiB`,
+    /// `12.00 MiB/s`) instead of raw byte counts, for spreadsheets read by
+    /// people rather than tools
+    pub fn with_display_units(mut self, units: UnitSystem, throughput: ThroughputUnit) -> Self {
+        self.display_units = Some((units, throughput));
+        self
+    }
+
+    fn bytes_cell(&self, bytes: u64) -> String {
+        match self.display_units {
+            Some((units, _)) => format_bytes(bytes, units),
+            None => bytes.to_string(),
+        }
+    }
+
+    fn rate_cell(&self, bytes_per_sec: u64) -> String {
+        match self.display_units {
+            Some((units, throughput)) => format_rate(bytes_per_sec, throughput, units),
+            None => bytes_per_sec.to_string(),
+        }
+    }
+
+    pub fn schema(&self) -> &CsvSchema {
+        &self.schema
+    }
+
+    pub fn write_row(&mut self, metrics: &SystemMetrics) -> Result<()> {
+        if !self.header_written {
+            writeln!(self.sink, "{}", self.schema.columns().join(","))?;
+            self.header_written = true;
+        }
+
+        let timestamp = metrics.timestamp.duration_since(UNIX_EPOCH).unwrap_or_default();
+        let mut cells = vec![
+            format!("{:.3}", timestamp.as_secs_f64()),
+            metrics.cpu.usage_percent.to_string(),
+            self.bytes_cell(metrics.memory.used_bytes),
+            self.bytes_cell(metrics.memory.total_bytes),
+        ];
+
+        for mount in &self.schema.mount_points {
+            match metrics.disks.iter().find(|d| &d.mount_point == mount) {
+                Some(disk) => cells.extend([
+                    disk.usage_percent.to_string(),
+                    self.rate_cell(disk.read_bytes_per_sec),
+                    self.rate_cell(disk.write_bytes_per_sec),
+                ]),
+                None => cells.extend([String::new(), String::new(), String::new()]),
+            }
+        }
+
+        for interface in &self.schema.interfaces {
+            match metrics.networks.iter().find(|n| &n.interface_name == interface) {
+                Some(network) => cells.extend([
+                    self.rate_cell(network.bytes_received_rate),
+                    self.rate_cell(network.bytes_sent_rate),
+                ]),
+                None => cells.extend([String::new(), String::new()]),
+            }
+        }
+
+        writeln!(self.sink, "{}", cells.join(","))?;
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> Result<()> {
+        self.sink.flush()?;
+        Ok(())
+    }
+
+    pub fn into_inner(self) -> W {
+        self.sink
+    }
+}
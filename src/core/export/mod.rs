@@ -0,0 +1,7 @@
+pub mod csv;
+pub mod otlp;
+pub mod prometheus;
+
+pub use csv::{CsvSchema, CsvWriter};
+pub use otlp::to_otlp_json;
+pub use prometheus::to_prometheus;
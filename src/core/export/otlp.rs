@@ -0,0 +1,204 @@
+use serde_json::{json, Value};
+use std::time::UNIX_EPOCH;
+
+use crate::core::{SystemMetrics, TcpStats};
+
+/// One OTLP gauge: a name, description and UCUM unit, and attributed points
+struct Gauge {
+    name: &'static str,
+    description: &'static str,
+    unit: &'static str,
+    points: Vec<(Vec<(&'static str, String)>, f64)>,
+}
+
+impl Gauge {
+    fn new(name: &'static str, description: &'static str, unit: &'static str) -> Self {
+        Self { name, description, unit, points: Vec::new() }
+    }
+
+    fn point(mut self, value: f64) -> Self {
+        self.points.push((Vec::new(), value));
+        self
+    }
+
+    fn attributed(&mut self, attributes: Vec<(&'static str, String)>, value: f64) {
+        self.points.push((attributes, value));
+    }
+
+    fn render(&self, time_unix_nano: &str) -> Option<Value> {
+        if self.points.is_empty() {
+            return None;
+        }
+
+        let data_points: Vec<Value> = self
+            .points
+            .iter()
+            .map(|(attributes, value)| {
+                json!({
+                    "attributes": key_values(attributes),
+                    "timeUnixNano": time_unix_nano,
+                    "asDouble": value,
+                })
+            })
+            .collect();
+
+        Some(json!({
+            "name": self.name,
+            "description": self.description,
+            "unit": self.unit,
+            "gauge": { "dataPoints": data_points },
+        }))
+    }
+}
+
+fn key_values(attributes: &[(&'static str, String)]) -> Vec<Value> {
+    attributes
+        .iter()
+        .map(|(key, value)| json!({ "key": key, "value": { "stringValue": value } }))
+        .collect()
+}
+
+/// Encodes `metrics` as an OTLP `ExportMetricsServiceRequest` in the protobuf
+/// JSON mapping, ready to POST to a collector's `/v1/metrics` endpoint.
+///
+/// The resource carries the host name, OS and architecture from
+/// `SystemInfo`. As with the Prometheus exporter, per-process data is left
+/// out to keep attribute cardinality bounded.
+pub fn to_otlp_json(metrics: &SystemMetrics) -> Value {
+    let cpu = &metrics.cpu;
+    let memory = &metrics.memory;
+    let info = &metrics.system_info;
+
+    // Protobuf JSON encodes 64-bit integers as strings
+    let time_unix_nano = metrics
+        .timestamp
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos()
+        .to_string();
+
+    let mut per_core = Gauge::new("system.cpu.core.usage", "Usage of each logical core", "%");
+    for (core, usage) in cpu.per_core_usage.iter().enumerate() {
+        per_core.attributed(vec![("core", core.to_string())], *usage as f64);
+    }
+
+    let mut per_core_time = Gauge::new("system.cpu.core.time", "Share of each logical core's time by state", "%");
+    for (core, times) in cpu.per_core_times.iter().enumerate() {
+        for (state, percent) in times.states() {
+            per_core_time.attributed(vec![("core", core.to_string()), ("state", state.to_string())], percent as f64);
+        }
+    }
+
+    let mut load = Gauge::new("system.cpu.load_average", "Load average over 1, 5 and 15 minutes", "1");
+    for (period, value) in ["1m", "5m", "15m"].iter().zip(cpu.load_average) {
+        load.attributed(vec![("period", period.to_string())], value as f64);
+    }
+
+    let mut gauges = vec![
+        Gauge::new("system.cpu.usage", "Overall CPU usage", "%").point(cpu.usage_percent as f64),
+        per_core,
+        per_core_time,
+        Gauge::new("system.cpu.frequency", "Highest current clock across cores", "MHz").point(cpu.frequency_mhz as f64),
+        load,
+        Gauge::new("system.memory.total", "Total physical memory", "By").point(memory.total_bytes as f64),
+        Gauge::new("system.memory.used", "Used physical memory", "By").point(memory.used_bytes as f64),
+        Gauge::new("system.memory.available", "Memory available for new allocations", "By")
+            .point(memory.available_bytes as f64),
+        Gauge::new("system.memory.usage", "Physical memory usage", "%").point(memory.usage_percent as f64),
+        Gauge::new("system.swap.total", "Total swap space", "By").point(memory.swap_total_bytes as f64),
+        Gauge::new("system.swap.used", "Used swap space", "By").point(memory.swap_used_bytes as f64),
+        Gauge::new("system.memory.page_faults.rate", "Page fault rate", "{fault}/s").point(memory.page_fault_rate),
+        Gauge::new("system.memory.major_faults.rate", "Major page fault rate", "{fault}/s").point(memory.major_fault_rate),
+        Gauge::new("system.swap.in.rate", "Pages swapped in", "{page}/s").point(memory.swap_in_rate),
+        Gauge::new("system.swap.out.rate", "Pages swapped out", "{page}/s").point(memory.swap_out_rate),
+        Gauge::new("system.health.score", "Weighted headroom of CPU, memory, disk and swap", "1")
+            .point(metrics.health_score as f64),
+    ];
+
+    if let Some(temperature) = cpu.temperature_celsius {
+        gauges.push(Gauge::new("system.cpu.temperature", "CPU package temperature", "Cel").point(temperature as f64));
+    }
+
+    let mut gpu_usage = Gauge::new("system.gpu.usage", "GPU utilization", "%");
+    let mut gpu_memory_used = Gauge::new("system.gpu.memory.used", "Used GPU memory", "By");
+    let mut gpu_temperature = Gauge::new("system.gpu.temperature", "GPU temperature", "Cel");
+    let mut gpu_power = Gauge::new("system.gpu.power", "GPU power draw", "W");
+    for (index, gpu) in metrics.gpus.iter().enumerate() {
+        let attributes = || vec![("gpu", index.to_string()), ("name", gpu.name.clone())];
+        gpu_usage.attributed(attributes(), gpu.usage_percent as f64);
+        gpu_memory_used.attributed(attributes(), gpu.memory_used_bytes as f64);
+        gpu_temperature.attributed(attributes(), gpu.temperature_celsius as f64);
+        gpu_power.attributed(attributes(), gpu.power_watts as f64);
+    }
+    gauges.extend([gpu_usage, gpu_memory_used, gpu_temperature, gpu_power]);
+
+    let mut disk_used = Gauge::new("system.disk.used", "Used filesystem space", "By");
+    let mut disk_usage = Gauge::new("system.disk.usage", "Filesystem usage", "%");
+    let mut disk_read = Gauge::new("system.disk.read.rate", "Disk read throughput", "By/s");
+    let mut disk_write = Gauge::new("system.disk.write.rate", "Disk write throughput", "By/s");
+    let mut disk_inodes = Gauge::new("system.disk.inodes.usage", "Share of the filesystem's inodes in use", "%");
+    for disk in &metrics.disks {
+        let attributes = || vec![("mount", disk.mount_point.clone()), ("device", disk.device_name.clone())];
+        disk_used.attributed(attributes(), disk.used_bytes as f64);
+        disk_usage.attributed(attributes(), disk.usage_percent as f64);
+        disk_read.attributed(attributes(), disk.read_bytes_per_sec as f64);
+        disk_write.attributed(attributes(), disk.write_bytes_per_sec as f64);
+        if let Some(usage) = disk.inode_usage_percent {
+            disk_inodes.attributed(attributes(), usage as f64);
+        }
+    }
+    gauges.extend([disk_used, disk_usage, disk_read, disk_write, disk_inodes]);
+
+    let mut pool_usage = Gauge::new("system.filesystem.pool.usage", "Storage pool space by state", "By");
+    for pool in &metrics.pools {
+        let attributes = |state: &str| vec![("pool", pool.name.clone()), ("state", state.to_string())];
+        pool_usage.attributed(attributes("used"), pool.allocated as f64);
+        pool_usage.attributed(attributes("free"), pool.free as f64);
+    }
+    gauges.push(pool_usage);
+
+    let mut net_rate = Gauge::new("system.network.io.rate", "Network throughput", "By/s");
+    for network in &metrics.networks {
+        let attributes = |direction: &str| vec![("interface", network.interface_name.clone()), ("direction", direction.to_string())];
+        net_rate.attributed(attributes("sent"), network.bytes_sent_rate as f64);
+        net_rate.attributed(attributes("received"), network.bytes_received_rate as f64);
+    }
+    gauges.push(net_rate);
+
+    let mut wifi_signal = Gauge::new("system.network.wireless.signal", "Signal level of the associated access point", "dBm");
+    for network in &metrics.networks {
+        if let Some(wireless) = &network.wireless {
+            // The SSID would identify where the host is, so it stays out
+            wifi_signal.attributed(vec![("interface", network.interface_name.clone())], wireless.signal_dbm as f64);
+        }
+    }
+    gauges.push(wifi_signal);
+
+    let mut tcp_connections = Gauge::new("system.network.connections", "TCP connections by state", "{connection}");
+    for (state, count) in metrics.tcp.iter().flat_map(TcpStats::by_state) {
+        tcp_connections.attributed(vec![("state", state.to_string())], count as f64);
+    }
+    gauges.push(tcp_connections);
+
+    let mut battery_charge = Gauge::new("system.battery.charge", "Battery charge", "%");
+    for battery in &metrics.batteries {
+        battery_charge.attributed(vec![("battery", battery.name.clone())], battery.charge_percent as f64);
+    }
+    gauges.push(battery_charge);
+
+    let resource = vec![
+        ("host.name", info.hostname.clone()),
+        ("os.name", info.os_name.clone()),
+        ("host.arch", info.architecture.clone()),
+    ];
+
+    json!({
+        "resourceMetrics": [{
+            "resource": { "attributes": key_values(&resource) },
+            "scopeMetrics": [{
+                "scope": { "name": env!("CARGO_PKG_NAME"), "version": env!("CARGO_PKG_VERSION") },
+                "metrics": gauges.iter().filter_map(|gauge| gauge.render(&time_unix_nano)).collect::<Vec<_>>(),
+            }],
+        }],
+    })
+}
@@ -0,0 +1,217 @@
+use std::fmt::Write;
+
+use crate::core::{SystemMetrics, TcpStats};
+
+/// One metric family: a name, its help text and type, and labelled samples
+struct Family {
+    name: &'static str,
+    help: &'static str,
+    kind: &'static str,
+    samples: Vec<(Vec<(&'static str, String)>, f64)>,
+}
+
+impl Family {
+    fn gauge(name: &'static str, help: &'static str) -> Self {
+        Self { name, help, kind: "gauge", samples: Vec::new() }
+    }
+
+    fn counter(name: &'static str, help: &'static str) -> Self {
+        Self { name, help, kind: "counter", samples: Vec::new() }
+    }
+
+    fn sample(mut self, value: f64) -> Self {
+        self.samples.push((Vec::new(), value));
+        self
+    }
+
+    fn labelled(&mut self, labels: Vec<(&'static str, String)>, value: f64) {
+        self.samples.push((labels, value));
+    }
+
+    fn render(&self, out: &mut String) {
+        if self.samples.is_empty() {
+            return;
+        }
+
+        let _ = writeln!(out, "# HELP {} {}", self.name, self.help);
+        let _ = writeln!(out, "# TYPE {} {}", self.name, self.kind);
+        for (labels, value) in &self.samples {
+            out.push_str(self.name);
+            if !labels.is_empty() {
+                let rendered: Vec<String> = labels
+                    .iter()
+                    .map(|(key, value)| format!("{}=\"{}\"", key, escape_label(value)))
+                    .collect();
+                let _ = write!(out, "{{{}}}", rendered.join(","));
+            }
+            let _ = writeln!(out, " {}", format_value(*value));
+        }
+    }
+}
+
+fn escape_label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+fn format_value(value: f64) -> String {
+    if value.is_nan() {
+        "NaN".to_string()
+    } else if value.is_infinite() {
+        if value > 0.0 { "+Inf" } else { "-Inf" }.to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+/// Renders `metrics` in the Prometheus text exposition format.
+///
+/// Per-process data is left out: process names are unbounded label values
+/// and would blow up the series count of the scraping server. Values are
+/// always in base units (bytes, bytes per second) as Prometheus expects;
+/// `UiConfig::display_units` is for dashboards built on top to apply.
+pub fn to_prometheus(metrics: &SystemMetrics) -> String {
+    let cpu = &metrics.cpu;
+    let memory = &metrics.memory;
+
+    let mut per_core = Family::gauge("system_cpu_core_usage_percent", "Usage of each logical core");
+    for (core, usage) in cpu.per_core_usage.iter().enumerate() {
+        per_core.labelled(vec![("core", core.to_string())], *usage as f64);
+    }
+
+    let mut per_core_frequency = Family::gauge("system_cpu_core_frequency_mhz", "Current clock of each logical core");
+    for (core, frequency) in cpu.per_core_frequency_mhz.iter().enumerate() {
+        per_core_frequency.labelled(vec![("core", core.to_string())], *frequency as f64);
+    }
+
+    let mut per_core_time = Family::gauge("system_cpu_core_time_percent", "Share of each logical core's time by state");
+    for (core, times) in cpu.per_core_times.iter().enumerate() {
+        for (state, percent) in times.states() {
+            per_core_time.labelled(vec![("core", core.to_string()), ("state", state.to_string())], percent as f64);
+        }
+    }
+
+    let mut load = Family::gauge("system_load_average", "Load average over 1, 5 and 15 minutes");
+    for (period, value) in ["1m", "5m", "15m"].iter().zip(cpu.load_average) {
+        load.labelled(vec![("period", period.to_string())], value as f64);
+    }
+
+    let mut families = vec![
+        Family::gauge("system_cpu_usage_percent", "Overall CPU usage").sample(cpu.usage_percent as f64),
+        per_core,
+        Family::gauge("system_cpu_frequency_mhz", "Highest current clock across cores").sample(cpu.frequency_mhz as f64),
+        per_core_frequency,
+        per_core_time,
+        load,
+        Family::gauge("system_memory_total_bytes", "Total physical memory").sample(memory.total_bytes as f64),
+        Family::gauge("system_memory_used_bytes", "Used physical memory").sample(memory.used_bytes as f64),
+        Family::gauge("system_memory_available_bytes", "Memory available for new allocations")
+            .sample(memory.available_bytes as f64),
+        Family::gauge("system_memory_cached_bytes", "Page cache size").sample(memory.cached_bytes as f64),
+        Family::gauge("system_memory_usage_percent", "Physical memory usage").sample(memory.usage_percent as f64),
+        Family::gauge("system_swap_total_bytes", "Total swap space").sample(memory.swap_total_bytes as f64),
+        Family::gauge("system_swap_used_bytes", "Used swap space").sample(memory.swap_used_bytes as f64),
+        Family::gauge("system_memory_page_faults_per_second", "Page fault rate").sample(memory.page_fault_rate),
+        Family::gauge("system_memory_major_faults_per_second", "Major page fault rate").sample(memory.major_fault_rate),
+        Family::gauge("system_swap_in_pages_per_second", "Pages swapped in").sample(memory.swap_in_rate),
+        Family::gauge("system_swap_out_pages_per_second", "Pages swapped out").sample(memory.swap_out_rate),
+        Family::gauge("system_health_score", "Weighted headroom of CPU, memory, disk and swap, 0 to 100")
+            .sample(metrics.health_score as f64),
+    ];
+
+    if let Some(temperature) = cpu.temperature_celsius {
+        families.push(Family::gauge("system_cpu_temperature_celsius", "CPU package temperature").sample(temperature as f64));
+    }
+
+    let mut gpu_usage = Family::gauge("system_gpu_usage_percent", "GPU utilization");
+    let mut gpu_memory_used = Family::gauge("system_gpu_memory_used_bytes", "Used GPU memory");
+    let mut gpu_memory_total = Family::gauge("system_gpu_memory_total_bytes", "Total GPU memory");
+    let mut gpu_temperature = Family::gauge("system_gpu_temperature_celsius", "GPU temperature");
+    let mut gpu_power = Family::gauge("system_gpu_power_watts", "GPU power draw");
+    for (index, gpu) in metrics.gpus.iter().enumerate() {
+        let labels = || vec![("gpu", index.to_string()), ("name", gpu.name.clone())];
+        gpu_usage.labelled(labels(), gpu.usage_percent as f64);
+        gpu_memory_used.labelled(labels(), gpu.memory_used_bytes as f64);
+        gpu_memory_total.labelled(labels(), gpu.memory_total_bytes as f64);
+        gpu_temperature.labelled(labels(), gpu.temperature_celsius as f64);
+        gpu_power.labelled(labels(), gpu.power_watts as f64);
+    }
+    families.extend([gpu_usage, gpu_memory_used, gpu_memory_total, gpu_temperature, gpu_power]);
+
+    let mut disk_total = Family::gauge("system_disk_total_bytes", "Filesystem size");
+    let mut disk_used = Family::gauge("system_disk_used_bytes", "Used filesystem space");
+    let mut disk_usage = Family::gauge("system_disk_usage_percent", "Filesystem usage");
+    let mut disk_read = Family::gauge("system_disk_read_bytes_per_second", "Disk read throughput");
+    let mut disk_write = Family::gauge("system_disk_write_bytes_per_second", "Disk write throughput");
+    let mut disk_busy = Family::gauge("system_disk_busy_percent", "Share of time the device had I/O in flight");
+    let mut disk_inodes = Family::gauge("system_disk_inode_usage_percent", "Share of the filesystem's inodes in use");
+    for disk in &metrics.disks {
+        let labels = || vec![("mount", disk.mount_point.clone()), ("device", disk.device_name.clone())];
+        disk_total.labelled(labels(), disk.total_bytes as f64);
+        disk_used.labelled(labels(), disk.used_bytes as f64);
+        disk_usage.labelled(labels(), disk.usage_percent as f64);
+        disk_read.labelled(labels(), disk.read_bytes_per_sec as f64);
+        disk_write.labelled(labels(), disk.write_bytes_per_sec as f64);
+        disk_busy.labelled(labels(), disk.busy_percent as f64);
+        if let Some(usage) = disk.inode_usage_percent {
+            disk_inodes.labelled(labels(), usage as f64);
+        }
+    }
+    families.extend([disk_total, disk_used, disk_usage, disk_read, disk_write, disk_busy, disk_inodes]);
+
+    let mut pool_size = Family::gauge("system_storage_pool_size_bytes", "Raw capacity of a ZFS pool or Btrfs filesystem");
+    let mut pool_allocated = Family::gauge("system_storage_pool_allocated_bytes", "Allocated bytes of a storage pool");
+    let mut pool_free = Family::gauge("system_storage_pool_free_bytes", "Free bytes of a storage pool");
+    let mut pool_compression = Family::gauge("system_storage_pool_compression_ratio", "Logical over physical data size");
+    for pool in &metrics.pools {
+        let labels = || vec![("pool", pool.name.clone()), ("kind", pool.kind.as_str().to_string())];
+        pool_size.labelled(labels(), pool.raw_capacity as f64);
+        pool_allocated.labelled(labels(), pool.allocated as f64);
+        pool_free.labelled(labels(), pool.free as f64);
+        pool_compression.labelled(labels(), pool.compression_ratio);
+    }
+    families.extend([pool_size, pool_allocated, pool_free, pool_compression]);
+
+    let mut net_up = Family::gauge("system_network_up", "Whether the interface is up (1) or down (0)");
+    let mut net_sent = Family::counter("system_network_sent_bytes_total", "Bytes sent since boot");
+    let mut net_received = Family::counter("system_network_received_bytes_total", "Bytes received since boot");
+    let mut net_sent_rate = Family::gauge("system_network_sent_bytes_per_second", "Transmit throughput");
+    let mut net_received_rate = Family::gauge("system_network_received_bytes_per_second", "Receive throughput");
+    let mut net_errors = Family::counter("system_network_errors_total", "Transmit and receive errors since boot");
+    let mut wifi_signal = Family::gauge("system_network_wireless_signal_dbm", "Signal level of the associated access point");
+    let mut wifi_quality = Family::gauge("system_network_wireless_link_quality_percent", "Wireless link quality");
+    for network in &metrics.networks {
+        let labels = || vec![("interface", network.interface_name.clone())];
+        net_up.labelled(labels(), if network.is_up { 1.0 } else { 0.0 });
+        net_sent.labelled(labels(), network.bytes_sent as f64);
+        net_received.labelled(labels(), network.bytes_received as f64);
+        net_sent_rate.labelled(labels(), network.bytes_sent_rate as f64);
+        net_received_rate.labelled(labels(), network.bytes_received_rate as f64);
+        net_errors.labelled(
+            vec![("interface", network.interface_name.clone()), ("direction", "sent".to_string())],
+            network.errors_sent as f64,
+        );
+        net_errors.labelled(
+            vec![("interface", network.interface_name.clone()), ("direction", "received".to_string())],
+            network.errors_received as f64,
+        );
+        if let Some(wireless) = &network.wireless {
+            // The SSID would identify where the host is, so it stays out
+            let labels = || vec![("interface", network.interface_name.clone())];
+            wifi_signal.labelled(labels(), wireless.signal_dbm as f64);
+            wifi_quality.labelled(labels(), wireless.link_quality_percent as f64);
+        }
+    }
+    families.extend([net_up, net_sent, net_received, net_sent_rate, net_received_rate, net_errors, wifi_signal, wifi_quality]);
+
+    let mut tcp_connections = Family::gauge("system_tcp_connections", "TCP connections by state");
+    for (state, count) in metrics.tcp.iter().flat_map(TcpStats::by_state) {
+        tcp_connections.labelled(vec![("state", state.to_string())], count as f64);
+    }
+    families.push(tcp_connections);
+
+    let mut out = String::new();
+    for family in &families {
+        family.render(&mut out);
+    }
+    out
+}
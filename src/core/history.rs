@@ -0,0 +1,127 @@
+use std::collections::VecDeque;
+use std::mem::{size_of, size_of_val};
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use parking_lot::RwLock;
+
+use super::types::{
+    BatteryMetrics, CpuCoreTimes, CpuMetrics, DiskMetrics, GpuMetrics, GpuProcessInfo, IpAddress, MemoryMetrics, NetworkMetrics,
+    PoolMetrics, ProcessMetrics, SensorReading,
+};
+
+/// A monitor's retained samples, oldest first, each with its collection time
+pub type TimedHistory<T> = Arc<RwLock<VecDeque<(SystemTime, T)>>>;
+
+/// Approximate memory held by a history entry: its inline size plus the
+/// capacity of the strings and vectors it owns
+pub trait HistorySize {
+    fn estimated_bytes(&self) -> usize {
+        size_of_val(self) + self.heap_bytes()
+    }
+
+    /// Bytes owned outside the value itself
+    fn heap_bytes(&self) -> usize;
+}
+
+impl<T: HistorySize> HistorySize for Vec<T> {
+    fn heap_bytes(&self) -> usize {
+        self.capacity() * size_of::<T>() + self.iter().map(HistorySize::heap_bytes).sum::<usize>()
+    }
+}
+
+/// A sample with its collection time
+impl<T: HistorySize> HistorySize for (SystemTime, T) {
+    fn heap_bytes(&self) -> usize {
+        self.1.heap_bytes()
+    }
+}
+
+impl HistorySize for CpuMetrics {
+    fn heap_bytes(&self) -> usize {
+        self.per_core_frequency_mhz.capacity() * size_of::<u64>()
+            + self.per_core_usage.capacity() * size_of::<f32>()
+            + self.per_core_times.capacity() * size_of::<CpuCoreTimes>()
+    }
+}
+
+impl HistorySize for MemoryMetrics {
+    fn heap_bytes(&self) -> usize {
+        self.fragmentation
+            .as_ref()
+            .map_or(0, |f| f.free_blocks_by_order.capacity() * size_of::<u64>())
+    }
+}
+
+impl HistorySize for GpuMetrics {
+    fn heap_bytes(&self) -> usize {
+        self.name.capacity()
+            + self.driver_version.capacity()
+            + self.gpu_processes.capacity() * size_of::<GpuProcessInfo>()
+            + self.gpu_processes.iter().map(|p| p.name.capacity()).sum::<usize>()
+            + self.instance_id.as_ref().map_or(0, String::capacity)
+            + self.parent_id.as_ref().map_or(0, String::capacity)
+    }
+}
+
+impl HistorySize for DiskMetrics {
+    fn heap_bytes(&self) -> usize {
+        self.id.capacity()
+            + self.mount_point.capacity()
+            + self.device_name.capacity()
+            + self.fs_type.capacity()
+            + self.smart.as_ref().map_or(0, |s| s.device.capacity())
+    }
+}
+
+impl HistorySize for PoolMetrics {
+    fn heap_bytes(&self) -> usize {
+        self.name.capacity() + self.health.capacity() + self.scrub_state.capacity()
+    }
+}
+
+impl HistorySize for NetworkMetrics {
+    fn heap_bytes(&self) -> usize {
+        self.id.capacity()
+            + self.interface_name.capacity()
+            + self.mac_address.capacity()
+            + self.ip_addresses.capacity() * size_of::<IpAddress>()
+            + self.wireless.as_ref().map_or(0, |w| w.ssid.capacity())
+    }
+}
+
+impl HistorySize for ProcessMetrics {
+    fn heap_bytes(&self) -> usize {
+        self.name.capacity()
+            + self.status.capacity()
+            + self.cmdline.as_ref().map_or(0, String::capacity)
+            + self.cpu_affinity.capacity() * size_of::<u32>()
+    }
+}
+
+impl HistorySize for BatteryMetrics {
+    fn heap_bytes(&self) -> usize {
+        self.name.capacity()
+    }
+}
+
+impl HistorySize for SensorReading {
+    fn heap_bytes(&self) -> usize {
+        self.name.capacity() + self.unit.capacity()
+    }
+}
+
+/// Drops the oldest entries until the rest fit in `max_bytes`; the newest
+/// entry is always kept
+pub fn trim_history<T: HistorySize>(history: &mut VecDeque<T>, max_bytes: Option<usize>) {
+    let Some(max_bytes) = max_bytes else {
+        return;
+    };
+
+    let mut total: usize = history.iter().map(HistorySize::estimated_bytes).sum();
+    while total > max_bytes && history.len() > 1 {
+        if let Some(oldest) = history.pop_front() {
+            total -= oldest.estimated_bytes();
+        }
+    }
+}
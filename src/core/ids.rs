@@ -0,0 +1,8 @@
+//! Stable identifiers shared with the tauri crate, which includes this file
+
+/// The `DiskMetrics::id` of a filesystem: its device path and mount point,
+/// since one device can be mounted in several places (btrfs subvolumes, bind
+/// mounts) and Windows volumes often have no device name
+pub fn disk_id(device_name: &str, mount_point: &str) -> String {
+    format!("{}:{}", device_name, mount_point)
+}
@@ -1,5 +1,8 @@
 use serde::{Deserialize, Serialize};
 use std::fmt;
+use std::time::Duration;
+
+use crate::core::{MonitorError, Result};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", content = "value")]
@@ -9,6 +12,11 @@ pub enum MetricValue {
     Unsigned(u64),
     String(String),
     Boolean(bool),
+    /// A share of some whole, always within `0.0..=100.0`
+    ///
+    /// Build with `percentage()` or `try_percentage()` so the range holds.
+    Percentage(f64),
+    Duration(Duration),
     FloatArray(Vec<f64>),
     IntegerArray(Vec<i64>),
 }
@@ -21,18 +29,64 @@ impl fmt::Display for MetricValue {
             MetricValue::Unsigned(v) => write!(f, "{}", v),
             MetricValue::String(v) => write!(f, "{}", v),
             MetricValue::Boolean(v) => write!(f, "{}", v),
+            MetricValue::Percentage(v) => write!(f, "{:.2}%", v),
+            MetricValue::Duration(v) => write!(f, "{:.3}s", v.as_secs_f64()),
             MetricValue::FloatArray(v) => write!(f, "{:?}", v),
             MetricValue::IntegerArray(v) => write!(f, "{:?}", v),
         }
     }
 }
 
+impl MetricValue {
+    /// A percentage, clamped into `0.0..=100.0`
+    ///
+    /// For readings that can overshoot slightly, like CPU usage sampled over
+    /// an interval that ran long. NaN becomes `0.0`.
+    pub fn percentage(value: f64) -> Self {
+        if value.is_nan() {
+            MetricValue::Percentage(0.0)
+        } else {
+            MetricValue::Percentage(value.clamp(0.0, 100.0))
+        }
+    }
+
+    /// A percentage, or an error if `value` is NaN or outside `0.0..=100.0`
+    pub fn try_percentage(value: f64) -> Result<Self> {
+        if (0.0..=100.0).contains(&value) {
+            Ok(MetricValue::Percentage(value))
+        } else {
+            Err(MonitorError::CollectionError(format!(
+                "percentage out of range: {}",
+                value
+            )))
+        }
+    }
+
+    /// The value as a number, for the numeric variants
+    ///
+    /// Percentages come back in percent and durations in seconds.
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            MetricValue::Float(v) => Some(*v),
+            MetricValue::Integer(v) => Some(*v as f64),
+            MetricValue::Unsigned(v) => Some(*v as f64),
+            MetricValue::Percentage(v) => Some(*v),
+            MetricValue::Duration(v) => Some(v.as_secs_f64()),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum MetricType {
     CpuUsage,
     CpuFrequency,
+    /// Share of a core's time in one state (user, system, iowait, ...)
+    CpuTime,
     CpuTemperature,
     SystemLoad,
+    ContextSwitches,
+    Interrupts,
     MemoryUsage,
     MemoryAvailable,
     Memory,
@@ -44,22 +98,43 @@ pub enum MetricType {
     GpuTemperature,
     GpuPower,
     GpuFanSpeed,
+    GpuProcessMemory,
+    ThermalThrottling,
     DiskUsage,
     DiskSpace,
     DiskIo,
+    DiskBusy,
+    DiskHealth,
+    DiskInodes,
+    /// Allocated bytes of a ZFS or Btrfs pool, with its other figures in tags
+    StoragePool,
     NetworkThroughput,
     NetworkBytes,
     NetworkPackets,
     NetworkErrors,
     NetworkStatus,
     NetworkSpeed,
+    /// Signal strength of a wireless link, with its SSID and rates in tags
+    WirelessSignal,
+    /// Host-wide TCP connections in one state
+    TcpConnections,
     ProcessCount,
     ProcessCpu,
     ProcessCpuTotal,
     ProcessMemory,
     ProcessMemoryTotal,
     ProcessDiskIo,
+    ProcessDiskIoRate,
+    ProcessHandles,
+    ProcessCpuTime,
+    ProcessElapsed,
     SystemUptime,
+    BatteryCharge,
+    BatteryState,
+    BatteryHealth,
+    SensorTemperature,
+    SensorFanSpeed,
+    SensorVoltage,
 }
 
 impl fmt::Display for MetricType {
@@ -67,8 +142,11 @@ impl fmt::Display for MetricType {
         match self {
             MetricType::CpuUsage => write!(f, "CPU Usage"),
             MetricType::CpuFrequency => write!(f, "CPU Frequency"),
+            MetricType::CpuTime => write!(f, "CPU Time"),
             MetricType::CpuTemperature => write!(f, "CPU Temperature"),
             MetricType::SystemLoad => write!(f, "System Load"),
+            MetricType::ContextSwitches => write!(f, "Context Switches"),
+            MetricType::Interrupts => write!(f, "Interrupts"),
             MetricType::MemoryUsage => write!(f, "Memory Usage"),
             MetricType::MemoryAvailable => write!(f, "Memory Available"),
             MetricType::Memory => write!(f, "Memory"),
@@ -80,22 +158,40 @@ impl fmt::Display for MetricType {
             MetricType::GpuTemperature => write!(f, "GPU Temperature"),
             MetricType::GpuPower => write!(f, "GPU Power"),
             MetricType::GpuFanSpeed => write!(f, "GPU Fan Speed"),
+            MetricType::GpuProcessMemory => write!(f, "GPU Process Memory"),
+            MetricType::ThermalThrottling => write!(f, "Thermal Throttling"),
             MetricType::DiskUsage => write!(f, "Disk Usage"),
             MetricType::DiskSpace => write!(f, "Disk Space"),
             MetricType::DiskIo => write!(f, "Disk I/O"),
+            MetricType::DiskBusy => write!(f, "Disk Busy"),
+            MetricType::DiskHealth => write!(f, "Disk Health"),
+            MetricType::DiskInodes => write!(f, "Disk Inodes"),
+            MetricType::StoragePool => write!(f, "Storage Pool"),
             MetricType::NetworkThroughput => write!(f, "Network Throughput"),
             MetricType::NetworkBytes => write!(f, "Network Bytes"),
             MetricType::NetworkPackets => write!(f, "Network Packets"),
             MetricType::NetworkErrors => write!(f, "Network Errors"),
             MetricType::NetworkStatus => write!(f, "Network Status"),
             MetricType::NetworkSpeed => write!(f, "Network Speed"),
+            MetricType::WirelessSignal => write!(f, "Wireless Signal"),
+            MetricType::TcpConnections => write!(f, "TCP Connections"),
             MetricType::ProcessCount => write!(f, "Process Count"),
             MetricType::ProcessCpu => write!(f, "Process CPU"),
             MetricType::ProcessCpuTotal => write!(f, "Total Process CPU"),
             MetricType::ProcessMemory => write!(f, "Process Memory"),
             MetricType::ProcessMemoryTotal => write!(f, "Total Process Memory"),
             MetricType::ProcessDiskIo => write!(f, "Process Disk I/O"),
+            MetricType::ProcessDiskIoRate => write!(f, "Process Disk I/O Rate"),
+            MetricType::ProcessHandles => write!(f, "Process Handles"),
+            MetricType::ProcessCpuTime => write!(f, "Process CPU Time"),
+            MetricType::ProcessElapsed => write!(f, "Process Elapsed Time"),
             MetricType::SystemUptime => write!(f, "System Uptime"),
+            MetricType::BatteryCharge => write!(f, "Battery Charge"),
+            MetricType::BatteryState => write!(f, "Battery State"),
+            MetricType::BatteryHealth => write!(f, "Battery Health"),
+            MetricType::SensorTemperature => write!(f, "Sensor Temperature"),
+            MetricType::SensorFanSpeed => write!(f, "Sensor Fan Speed"),
+            MetricType::SensorVoltage => write!(f, "Sensor Voltage"),
         }
     }
 }
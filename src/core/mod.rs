@@ -1,11 +1,27 @@
+pub mod clock;
 pub mod error;
+pub mod export;
+pub mod history;
+mod ids;
 pub mod metrics;
 pub mod monitor;
+mod process_sort;
 pub mod types;
 pub mod config;
+pub mod units;
 
+pub use clock::{Clock, MockClock, SharedClock, SystemClock};
 pub use error::{MonitorError, Result};
+pub use history::{trim_history, HistorySize, TimedHistory};
+pub use ids::disk_id;
 pub use metrics::{Metric, MetricType, MetricValue};
-pub use monitor::{Monitor, MonitorConfig, MonitorState};
+pub use monitor::{history_in_range, history_since, Aggregate, Monitor, MonitorConfig, MonitorHealth, MonitorShared, MonitorState, ProcessGrouping};
+pub use process_sort::ProcessSortKey;
 pub use types::*;
-pub use config::{AppConfig, MonitorSettings};
\ No newline at end of file
+pub use units::{format_bytes, format_rate, ThroughputUnit, UnitSystem};
+pub use config::{
+    AlertAggregation, AlertConfig, AppConfig, ChangeFilterConfig, ConfigManager, ConfigWatcher,
+    GuardPolicyConfig, GuardResource, GuardRule, HealthScoreWeights, HttpServerConfig, MonitorSettings, MonitoringConfig,
+    ProcessMonitorSettings,
+    RedactionConfig, ScheduleMode, SmoothingConfig, SmoothingMethod, UiConfig,
+};
\ No newline at end of file
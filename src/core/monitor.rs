@@ -1,9 +1,23 @@
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
 use std::sync::Arc;
+use std::time::{Duration, SystemTime};
 use tokio::sync::RwLock;
 
-use crate::core::{Metric, MonitorError, Result};
+use crate::core::{GroupedProcess, Metric, MetricType, MonitorError, ProcessMetrics, ProcessQuery, ProcessSortKey, Result};
+
+/// How `MonitoringService::grouped_processes` merges processes, e.g. the
+/// many processes of a browser into one entry
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ProcessGrouping {
+    /// Every process is its own group
+    #[default]
+    None,
+    ByName,
+    /// Processes whose executable path can't be read are grouped by name
+    ByExecutable,
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MonitorConfig {
@@ -13,7 +27,19 @@ pub struct MonitorConfig {
     pub alert_thresholds: std::collections::HashMap<String, f64>,
     pub max_processes: Option<usize>,
     pub top_processes_count: Option<usize>,
+    #[serde(default)]
+    pub process_sort_key: ProcessSortKey,
+    #[serde(default)]
+    pub process_grouping: ProcessGrouping,
     pub include_loopback: bool,
+    /// Glob patterns (`en*`, `eth?`); when set, only matching interfaces are
+    /// reported
+    #[serde(default)]
+    pub interface_filter: Option<Vec<String>>,
+    /// Upper bound on the estimated memory held by the history, applied on
+    /// top of `retain_history_seconds`
+    #[serde(default)]
+    pub max_history_bytes: Option<usize>,
 }
 
 impl Default for MonitorConfig {
@@ -25,21 +51,150 @@ impl Default for MonitorConfig {
             alert_thresholds: std::collections::HashMap::new(),
             max_processes: Some(100),
             top_processes_count: Some(10),
+            process_sort_key: ProcessSortKey::Cpu,
+            process_grouping: ProcessGrouping::None,
             include_loopback: false,
+            interface_filter: None,
+            max_history_bytes: None,
         }
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum MonitorState {
     Uninitialized,
     Initializing,
     Running,
+    /// Still collecting, but the last collection failed; history holds the
+    /// last good samples
+    Degraded { since: SystemTime, reason: String },
     Paused,
     Stopped,
     Error,
 }
 
+impl MonitorState {
+    /// Whether `collect()` should run in this state
+    pub fn is_collecting(&self) -> bool {
+        matches!(self, MonitorState::Running | MonitorState::Degraded { .. })
+    }
+
+    /// Why the monitor is degraded, if it is
+    pub fn degraded_reason(&self) -> Option<String> {
+        match self {
+            MonitorState::Degraded { reason, .. } => Some(reason.clone()),
+            _ => None,
+        }
+    }
+
+    /// Moves a collecting monitor to `Degraded` when a collection fails and
+    /// back to `Running` when one succeeds. A monitor that keeps failing
+    /// stays degraded since its first failure, with the latest reason.
+    pub fn record_collection<T>(&mut self, result: &Result<T>) {
+        match (&*self, result) {
+            (MonitorState::Running, Err(e)) => {
+                *self = MonitorState::Degraded { since: SystemTime::now(), reason: e.to_string() };
+            }
+            (MonitorState::Degraded { since, .. }, Err(e)) => {
+                *self = MonitorState::Degraded { since: *since, reason: e.to_string() };
+            }
+            (MonitorState::Degraded { .. }, Ok(_)) => *self = MonitorState::Running,
+            _ => {}
+        }
+    }
+}
+
+/// Point-in-time status of one monitor, for telling a stalled monitor apart
+/// from a quiet one
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MonitorHealth {
+    pub state: MonitorState,
+    /// When the monitor last finished a collection
+    pub last_update: SystemTime,
+    pub last_error: Option<String>,
+    /// Failed collections since the last successful one
+    pub consecutive_failures: u32,
+}
+
+/// The lifecycle state, configuration and last collection time every
+/// built-in monitor keeps; exposing it through `Monitor::shared()` provides
+/// `reconfigure()` and `health()`
+#[derive(Clone)]
+pub struct MonitorShared {
+    pub state: Arc<parking_lot::RwLock<MonitorState>>,
+    pub config: Arc<parking_lot::RwLock<MonitorConfig>>,
+    pub last_update: Arc<parking_lot::RwLock<SystemTime>>,
+}
+
+impl MonitorShared {
+    /// Uninitialized with the default config, last updated at `now`
+    pub fn new(now: SystemTime) -> Self {
+        Self {
+            state: Arc::new(parking_lot::RwLock::new(MonitorState::Uninitialized)),
+            config: Arc::new(parking_lot::RwLock::new(MonitorConfig::default())),
+            last_update: Arc::new(parking_lot::RwLock::new(now)),
+        }
+    }
+}
+
+/// Summary statistics over a window of samples
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Aggregate {
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    /// Nearest-rank 95th percentile
+    pub p95: f64,
+    pub sample_count: usize,
+}
+
+impl Aggregate {
+    /// Summarizes `values`, or `None` if there are none
+    pub fn from_values(values: impl IntoIterator<Item = f64>) -> Option<Self> {
+        let mut values: Vec<f64> = values.into_iter().filter(|v| !v.is_nan()).collect();
+        if values.is_empty() {
+            return None;
+        }
+        values.sort_by(f64::total_cmp);
+
+        let count = values.len();
+        let rank = (count as f64 * 0.95).ceil() as usize;
+        Some(Self {
+            min: values[0],
+            max: values[count - 1],
+            mean: values.iter().sum::<f64>() / count as f64,
+            p95: values[rank.clamp(1, count) - 1],
+            sample_count: count,
+        })
+    }
+}
+
+/// Samples of a timestamped history collected in the `duration_seconds`
+/// up to `now`, oldest first
+pub fn history_since<T>(
+    history: &VecDeque<(SystemTime, T)>,
+    duration_seconds: u64,
+    now: SystemTime,
+) -> impl Iterator<Item = &T> {
+    let since = now.checked_sub(Duration::from_secs(duration_seconds)).unwrap_or(SystemTime::UNIX_EPOCH);
+    history.iter().filter(move |(at, _)| *at >= since).map(|(_, sample)| sample)
+}
+
+/// Samples of a timestamped history collected between `start` and `end`,
+/// inclusive, each converted with `to_metrics`
+pub fn history_in_range<T>(
+    history: &VecDeque<(SystemTime, T)>,
+    start: SystemTime,
+    end: SystemTime,
+    to_metrics: impl Fn(&T) -> Vec<Metric>,
+) -> Vec<(SystemTime, Vec<Metric>)> {
+    history
+        .iter()
+        .filter(|(at, _)| (start..=end).contains(at))
+        .map(|(at, sample)| (*at, to_metrics(sample)))
+        .collect()
+}
+
 /// Core trait for implementing system monitors
 /// 
 /// This trait defines the interface that all monitoring implementations must follow.
@@ -63,7 +218,7 @@ pub enum MonitorState {
 ///     }
 ///     
 ///     fn state(&self) -> MonitorState {
-///         self.state
+///         self.state.clone()
 ///     }
 ///     
 ///     async fn initialize(&mut self, config: MonitorConfig) -> Result<()> {
@@ -94,6 +249,27 @@ pub trait Monitor: Send + Sync {
     /// collecting metrics yet. Call `start()` to begin metric collection.
     async fn initialize(&mut self, config: MonitorConfig) -> Result<()>;
     
+    /// The state the default `reconfigure()` and `health()` work from;
+    /// monitors that keep their own return `None`
+    fn shared(&self) -> Option<&MonitorShared> {
+        None
+    }
+    
+    /// Applies a new configuration to a monitor that is already running
+    /// 
+    /// Unlike `initialize()`, this keeps the current state and collected
+    /// history. The default swaps the config in `shared()`, or falls back to
+    /// `initialize()` for monitors without one.
+    async fn reconfigure(&mut self, config: MonitorConfig) -> Result<()> {
+        match self.shared() {
+            Some(shared) => {
+                *shared.config.write() = config;
+                Ok(())
+            }
+            None => self.initialize(config).await,
+        }
+    }
+    
     /// Starts the monitor and begins metric collection
     async fn start(&mut self) -> Result<()>;
     
@@ -122,12 +298,71 @@ pub trait Monitor: Send + Sync {
     /// * `duration_seconds` - How far back in time to retrieve metrics
     async fn get_historical_metrics(&self, duration_seconds: u64) -> Result<Vec<Metric>>;
     
+    /// Returns the retained samples collected between `start` and `end`,
+    /// inclusive, oldest first, each with its collection time and the
+    /// metrics `collect()` reported for it
+    async fn history_range(&self, start: SystemTime, end: SystemTime) -> Vec<(SystemTime, Vec<Metric>)>;
+    
+    /// Summarizes `metric_type` over the last `duration_seconds` of history
+    /// 
+    /// Returns `None` if the history holds no numeric samples of that type.
+    /// Samples from every device (each core, disk or interface) are pooled;
+    /// only the metric types `get_historical_metrics()` returns are covered.
+    async fn aggregate(&self, metric_type: MetricType, duration_seconds: u64) -> Option<Aggregate> {
+        let history = self.get_historical_metrics(duration_seconds).await.ok()?;
+        Aggregate::from_values(
+            history
+                .iter()
+                .filter(|metric| metric.metric_type == metric_type)
+                .filter_map(|metric| metric.value.as_f64()),
+        )
+    }
+    
     /// Checks if this monitor supports a specific feature
     /// 
     /// # Arguments
     /// 
     /// * `feature` - The feature name to check (e.g., "cpu_temperature", "gpu_memory")
     fn supports_feature(&self, feature: &str) -> bool;
+    
+    /// The metric types `collect()` populates on this machine
+    /// 
+    /// Types that depend on hardware (a battery, GPU sensors, SMART data)
+    /// are judged from the latest collection, so they are missing until the
+    /// monitor has collected once.
+    fn supported_metrics(&self) -> Vec<MetricType>;
+    
+    /// Every process matching `query`, searched in the full process table
+    /// rather than only the top processes `collect()` reports
+    /// 
+    /// Only the process monitor tracks processes; the rest return nothing.
+    fn find_processes(&self, _query: &ProcessQuery) -> Vec<ProcessMetrics> {
+        Vec::new()
+    }
+    
+    /// The full process table merged by the configured `process_grouping`
+    /// 
+    /// Only the process monitor tracks processes; the rest return nothing.
+    fn grouped_processes(&self) -> Vec<GroupedProcess> {
+        Vec::new()
+    }
+    
+    /// Reports the monitor's state and when it last collected
+    /// 
+    /// Failures are counted by `MonitorManager`, which sees every `collect()`
+    /// result, so implementations only fill in what they track themselves.
+    fn health(&self) -> MonitorHealth {
+        let (state, last_update) = match self.shared() {
+            Some(shared) => (shared.state.read().clone(), *shared.last_update.read()),
+            None => (self.state(), SystemTime::UNIX_EPOCH),
+        };
+        MonitorHealth {
+            last_error: state.degraded_reason(),
+            state,
+            last_update,
+            consecutive_failures: 0,
+        }
+    }
 }
 
 pub type SharedMonitor = Arc<RwLock<Box<dyn Monitor>>>;
@@ -135,12 +370,24 @@ pub type SharedMonitor = Arc<RwLock<Box<dyn Monitor>>>;
 #[derive(Clone)]
 pub struct MonitorManager {
     monitors: Arc<RwLock<std::collections::HashMap<String, SharedMonitor>>>,
+    /// Consecutive failure count and latest error, per monitor name
+    failures: Arc<RwLock<std::collections::HashMap<String, (u32, String)>>>,
+    /// Registered monitors that are paused and skipped by collection
+    disabled: Arc<RwLock<std::collections::HashSet<String>>>,
+}
+
+impl Default for MonitorManager {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl MonitorManager {
     pub fn new() -> Self {
         Self {
             monitors: Arc::new(RwLock::new(std::collections::HashMap::new())),
+            failures: Arc::new(RwLock::new(std::collections::HashMap::new())),
+            disabled: Arc::new(RwLock::new(std::collections::HashSet::new())),
         }
     }
 
@@ -158,10 +405,14 @@ impl MonitorManager {
         Ok(())
     }
 
+    /// Stops and drops the monitor, releasing whatever it holds (such as
+    /// the NVML handle of the GPU monitor); unknown names are ignored
     pub async fn unregister_monitor(&self, name: &str) -> Result<()> {
         let mut monitors = self.monitors.write().await;
         
         if let Some(monitor) = monitors.remove(name) {
+            self.failures.write().await.remove(name);
+            self.disabled.write().await.remove(name);
             let mut m = monitor.write().await;
             m.stop().await?;
         }
@@ -169,17 +420,53 @@ impl MonitorManager {
         Ok(())
     }
 
+    /// Pauses a monitor and leaves it out of collection, or resumes it
+    /// 
+    /// A disabled monitor stays registered with its history. Enabling one
+    /// that was never started leaves it for `start_all()` to start.
+    pub async fn set_monitor_enabled(&self, name: &str, enabled: bool) -> Result<()> {
+        let monitor = self.get_monitor(name).await.ok_or_else(|| {
+            MonitorError::InvalidConfig(format!("Monitor '{}' is not registered", name))
+        })?;
+        
+        // `disabled` is never locked while holding a monitor, as `start_all()`
+        // and collection take them in the opposite order. Collection skips the
+        // monitor before it's paused and only sees it again once resumed.
+        if enabled {
+            {
+                let mut m = monitor.write().await;
+                if m.state() == MonitorState::Paused {
+                    m.resume().await?;
+                }
+            }
+            self.disabled.write().await.remove(name);
+        } else {
+            self.disabled.write().await.insert(name.to_string());
+            self.failures.write().await.remove(name);
+            let mut m = monitor.write().await;
+            if m.state().is_collecting() {
+                m.pause().await?;
+            }
+        }
+        
+        Ok(())
+    }
+
+    pub async fn is_monitor_enabled(&self, name: &str) -> bool {
+        !self.disabled.read().await.contains(name)
+    }
+
     pub async fn get_monitor(&self, name: &str) -> Option<SharedMonitor> {
         let monitors = self.monitors.read().await;
         monitors.get(name).cloned()
     }
 
+    /// Starts every enabled monitor
     pub async fn start_all(&self) -> Result<()> {
-        let monitors = self.monitors.read().await;
-        
-        for (_, monitor) in monitors.iter() {
-            let mut m = monitor.write().await;
-            m.start().await?;
+        for name in self.enabled_monitor_names().await {
+            if let Some(monitor) = self.get_monitor(&name).await {
+                monitor.write().await.start().await?;
+            }
         }
         
         Ok(())
@@ -196,15 +483,88 @@ impl MonitorManager {
         Ok(())
     }
 
-    pub async fn collect_all_metrics(&self) -> Result<std::collections::HashMap<String, Vec<Metric>>> {
+    pub async fn monitor_names(&self) -> Vec<String> {
+        self.monitors.read().await.keys().cloned().collect()
+    }
+
+    /// Names of the registered monitors that aren't disabled
+    pub async fn enabled_monitor_names(&self) -> Vec<String> {
         let monitors = self.monitors.read().await;
-        let mut all_metrics = std::collections::HashMap::new();
+        let disabled = self.disabled.read().await;
+        monitors.keys().filter(|name| !disabled.contains(*name)).cloned().collect()
+    }
+
+    /// Collects from every enabled monitor, keeping each monitor's failure
+    /// separate so one broken subsystem doesn't hide the others
+    pub async fn collect_each(&self) -> std::collections::HashMap<String, Result<Vec<Metric>>> {
+        self.collect_named(&self.enabled_monitor_names().await).await
+    }
+
+    /// Like `collect_each()`, but only for the monitors in `names`
+    pub async fn collect_named(&self, names: &[String]) -> std::collections::HashMap<String, Result<Vec<Metric>>> {
+        let monitors = self.monitors.read().await;
+        let mut results = std::collections::HashMap::new();
         
-        for (name, monitor) in monitors.iter() {
+        for (name, monitor) in monitors.iter().filter(|(name, _)| names.contains(name)) {
             let mut m = monitor.write().await;
-            match m.collect().await {
+            results.insert(name.clone(), m.collect().await);
+        }
+        
+        let mut failures = self.failures.write().await;
+        for (name, result) in &results {
+            match result {
+                Ok(_) => {
+                    failures.remove(name);
+                }
+                Err(e) => {
+                    let entry = failures.entry(name.clone()).or_insert((0, String::new()));
+                    entry.0 += 1;
+                    entry.1 = e.to_string();
+                }
+            }
+        }
+        
+        results
+    }
+
+    /// Health of every registered monitor, with failures counted across
+    /// `collect_each()` rounds
+    pub async fn health_each(&self) -> std::collections::HashMap<String, MonitorHealth> {
+        let monitors = self.monitors.read().await;
+        let failures = self.failures.read().await;
+        let mut health = std::collections::HashMap::new();
+        
+        for (name, monitor) in monitors.iter() {
+            let mut report = monitor.read().await.health();
+            if let Some((count, error)) = failures.get(name) {
+                report.consecutive_failures = *count;
+                report.last_error = Some(error.clone());
+            }
+            health.insert(name.clone(), report);
+        }
+        
+        health
+    }
+
+    /// `supported_metrics()` of every registered monitor
+    pub async fn supported_metrics_each(&self) -> std::collections::HashMap<String, Vec<MetricType>> {
+        let monitors = self.monitors.read().await;
+        let mut supported = std::collections::HashMap::new();
+        
+        for (name, monitor) in monitors.iter() {
+            supported.insert(name.clone(), monitor.read().await.supported_metrics());
+        }
+        
+        supported
+    }
+
+    pub async fn collect_all_metrics(&self) -> Result<std::collections::HashMap<String, Vec<Metric>>> {
+        let mut all_metrics = std::collections::HashMap::new();
+        
+        for (name, result) in self.collect_each().await {
+            match result {
                 Ok(metrics) => {
-                    all_metrics.insert(name.clone(), metrics);
+                    all_metrics.insert(name, metrics);
                 }
                 Err(e) => {
                     tracing::error!("Failed to collect metrics from {}: {}", name, e);
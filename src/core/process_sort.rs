@@ -0,0 +1,15 @@
+//! Process ranking shared with the tauri crate, which includes this file
+
+use serde::{Deserialize, Serialize};
+
+/// Order in which processes are ranked before the top N are kept
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ProcessSortKey {
+    #[default]
+    Cpu,
+    Memory,
+    /// Bytes read plus bytes written
+    DiskIo,
+    Name,
+    Pid,
+}
@@ -1,7 +1,11 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::net::IpAddr;
 use std::time::{Duration, SystemTime};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+use crate::core::{HealthScoreWeights, Metric, MetricType, MetricValue, MonitorError, Result};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct SystemInfo {
     pub hostname: String,
     pub os_name: String,
@@ -13,34 +17,254 @@ pub struct SystemInfo {
     pub cpu_threads: usize,
     pub total_memory: u64,
     pub boot_time: SystemTime,
+    pub transparent_hugepages: Option<ThpStatus>,
+    pub numa_topology: Vec<NumaNode>,
+    pub cross_node_irqs: Vec<CrossNodeIrq>,
+    pub environment: RuntimeEnvironment,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Environment the monitor is running in, which determines which kernel
+/// interfaces can be trusted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum RuntimeEnvironment {
+    #[default]
+    Native,
+    /// Windows Subsystem for Linux, where most of `/sys` is synthetic or absent
+    Wsl,
+}
+
+/// Active transparent huge pages modes, as selected in
+/// `/sys/kernel/mm/transparent_hugepage/{enabled,defrag}`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ThpStatus {
+    pub enabled: String,
+    pub defrag: String,
+}
+
+/// A NUMA node with the logical CPUs and memory attached to it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NumaNode {
+    pub id: usize,
+    pub cpus: Vec<usize>,
+    pub memory_bytes: u64,
+}
+
+/// A device interrupt that was partly handled on CPUs outside the NUMA node
+/// its device is attached to, with counts since boot
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CrossNodeIrq {
+    /// IRQ number, as in `/proc/irq/<irq>`
+    pub irq: String,
+    /// Last field of the IRQ's `/proc/interrupts` line, usually the driver or queue
+    pub device: String,
+    /// Node the device is attached to
+    pub node: usize,
+    pub total_count: u64,
+    /// Interrupts handled on CPUs of other nodes
+    pub remote_count: u64,
+}
+
+/// Where one logical core spent the last collection interval, in percent.
+///
+/// On Linux this comes from the per-core lines of `/proc/stat` and the shares
+/// sum to 100 (`nice` time is counted as `user`). Windows reports no I/O wait
+/// or steal time, so those stay at 0 and DPC time is reported as `softirq`.
+/// Elsewhere only the overall usage of each core is known: it is reported as
+/// `user` with the rest as `idle`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct CpuCoreTimes {
+    pub user: f32,
+    pub system: f32,
+    pub idle: f32,
+    pub iowait: f32,
+    pub irq: f32,
+    pub softirq: f32,
+    pub steal: f32,
+}
+
+impl CpuCoreTimes {
+    /// Every share with the name used for it in the `state` tag of
+    /// `CpuTime` metrics
+    pub fn states(&self) -> [(&'static str, f32); 7] {
+        [
+            ("user", self.user),
+            ("system", self.system),
+            ("idle", self.idle),
+            ("iowait", self.iowait),
+            ("irq", self.irq),
+            ("softirq", self.softirq),
+            ("steal", self.steal),
+        ]
+    }
+
+    /// Sets the share named `state` as returned by `states()`; unknown names
+    /// are ignored
+    pub fn set_state(&mut self, state: &str, percent: f32) {
+        let share = match state {
+            "user" => &mut self.user,
+            "system" => &mut self.system,
+            "idle" => &mut self.idle,
+            "iowait" => &mut self.iowait,
+            "irq" => &mut self.irq,
+            "softirq" => &mut self.softirq,
+            "steal" => &mut self.steal,
+            _ => return,
+        };
+        *share = percent;
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct CpuMetrics {
     pub usage_percent: f32,
+    /// Highest current clock across cores
     pub frequency_mhz: u64,
+    #[serde(default)]
+    pub per_core_frequency_mhz: Vec<u64>,
     pub temperature_celsius: Option<f32>,
     pub load_average: [f32; 3],
     pub per_core_usage: Vec<f32>,
+    /// Breakdown of each core's time, in the same order as `per_core_usage`
+    #[serde(default)]
+    pub per_core_times: Vec<CpuCoreTimes>,
     pub processes_running: usize,
     pub processes_total: usize,
+    /// Context switches since boot
     pub context_switches: u64,
+    /// Interrupts serviced since boot
     pub interrupts: u64,
+    /// Context switch rate over the last collection interval
+    #[serde(default)]
+    pub context_switches_per_sec: f64,
+    /// Interrupt rate over the last collection interval
+    #[serde(default)]
+    pub interrupts_per_sec: f64,
+    #[serde(default)]
+    pub cpu_thermal: Option<ThermalStatus>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Why a CPU or GPU is running below its normal clocks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ThrottleReason {
+    #[default]
+    None,
+    /// Temperature reached a thermal limit
+    Thermal,
+    /// Power draw reached a power limit
+    Power,
+    /// Slowed down by the hardware for a reason it doesn't single out
+    Other,
+}
+
+impl std::fmt::Display for ThrottleReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ThrottleReason::None => write!(f, "none"),
+            ThrottleReason::Thermal => write!(f, "thermal"),
+            ThrottleReason::Power => write!(f, "power"),
+            ThrottleReason::Other => write!(f, "other"),
+        }
+    }
+}
+
+impl std::str::FromStr for ThrottleReason {
+    type Err = std::convert::Infallible;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        Ok(match s.trim().to_lowercase().as_str() {
+            "none" => ThrottleReason::None,
+            "thermal" => ThrottleReason::Thermal,
+            "power" => ThrottleReason::Power,
+            _ => ThrottleReason::Other,
+        })
+    }
+}
+
+/// Whether a CPU or GPU is throttling, and how close it runs to its limit
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ThermalStatus {
+    pub throttling: bool,
+    pub reason: ThrottleReason,
+    pub temperature: f32,
+    /// Temperature at which the hardware starts throttling, or 0 when the
+    /// driver doesn't report one
+    pub limit: f32,
+}
+
+impl ThermalStatus {
+    /// A `ThermalThrottling` metric carrying the status in its value and tags;
+    /// GPU monitors add a `gpu` tag
+    pub fn to_metric(&self) -> Metric {
+        Metric::new(MetricType::ThermalThrottling, MetricValue::Boolean(self.throttling), "")
+            .with_tag("reason", self.reason.to_string())
+            .with_tag("temperature", self.temperature.to_string())
+            .with_tag("limit", self.limit.to_string())
+    }
+
+    /// Reads back a metric built by `to_metric()`
+    pub fn from_metric(metric: &Metric) -> Option<Self> {
+        let MetricValue::Boolean(throttling) = metric.value else {
+            return None;
+        };
+        let number = |tag: &str| metric.tags.get(tag).and_then(|v| v.parse::<f32>().ok());
+        Some(Self {
+            throttling,
+            reason: metric.tags.get("reason")?.parse().unwrap_or_default(),
+            temperature: number("temperature")?,
+            limit: number("limit").unwrap_or(0.0),
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct MemoryMetrics {
     pub total_bytes: u64,
     pub used_bytes: u64,
     pub available_bytes: u64,
+    /// Page cache, plus reclaimable slab on Linux
     pub cached_bytes: u64,
+    /// Block device buffers (Linux only)
+    #[serde(default)]
+    pub buffer_bytes: u64,
     pub swap_total_bytes: u64,
     pub swap_used_bytes: u64,
     pub usage_percent: f32,
     pub swap_usage_percent: f32,
+    pub anon_hugepages_bytes: u64,
+    pub fragmentation: Option<MemoryFragmentation>,
+    /// Page faults per second since the previous sample, minor and major
+    #[serde(default)]
+    pub page_fault_rate: f64,
+    /// Faults per second that had to read from disk (Linux and Windows)
+    #[serde(default)]
+    pub major_fault_rate: f64,
+    /// Pages read in per second from swap; on Windows from the paging file
+    /// and mapped files
+    #[serde(default)]
+    pub swap_in_rate: f64,
+    /// Pages written out per second to swap, or on Windows to the paging
+    /// file and mapped files
+    #[serde(default)]
+    pub swap_out_rate: f64,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// External fragmentation indicators from `/proc/buddyinfo` and `/proc/vmstat`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MemoryFragmentation {
+    /// Free blocks of each order (`2^order` pages), summed over all zones
+    pub free_blocks_by_order: Vec<u64>,
+    /// Share of free memory unusable for a huge-page (order 9) allocation, 0.0..=1.0
+    pub hugepage_unusable_index: f32,
+    pub compact_stall: u64,
+    pub compact_fail: u64,
+    pub compact_success: u64,
+    /// Direct reclaim stalls, summed over the per-zone `allocstall_*` counters
+    pub allocstall: u64,
+    /// Huge page faults that fell back to small pages
+    pub thp_fault_fallback: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct GpuMetrics {
     pub name: String,
     pub driver_version: String,
@@ -51,12 +275,44 @@ pub struct GpuMetrics {
     pub memory_usage_percent: f32,
     pub power_watts: f32,
     pub fan_speed_percent: Option<f32>,
+    /// Current graphics clock in whole MHz, as every driver reports it; 0
+    /// when unknown. The Tauri and high-performance structs use the same type.
     pub clock_mhz: u32,
+    /// Current memory clock in whole MHz; 0 when unknown
     pub memory_clock_mhz: u32,
+    /// Processes holding a context on this GPU; only reported for NVIDIA
+    #[serde(default)]
+    pub gpu_processes: Vec<GpuProcessInfo>,
+    /// Only reported for NVIDIA
+    #[serde(default)]
+    pub thermal: Option<ThermalStatus>,
+    /// MIG device UUID when this entry is one instance of a partitioned
+    /// NVIDIA GPU. Instances report memory and processes only; usage,
+    /// temperature, power and clocks stay with the physical GPU.
+    #[serde(default)]
+    pub instance_id: Option<String>,
+    /// UUID of the physical GPU, on a GPU in MIG mode and on each of its
+    /// instances so they can be grouped
+    #[serde(default)]
+    pub parent_id: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// A process using a GPU, as reported by the driver
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GpuProcessInfo {
+    pub pid: u32,
+    pub name: String,
+    pub used_memory_bytes: u64,
+    /// Share of the streaming multiprocessors over the last sample period,
+    /// when the driver keeps per-process utilization
+    pub sm_utilization: Option<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct DiskMetrics {
+    /// Stable key for this filesystem across samples, from `disk_id`
+    #[serde(default)]
+    pub id: String,
     pub mount_point: String,
     pub device_name: String,
     pub fs_type: String,
@@ -67,14 +323,208 @@ pub struct DiskMetrics {
     pub read_bytes_per_sec: u64,
     pub write_bytes_per_sec: u64,
     pub io_operations_per_sec: u64,
+    pub busy_percent: f32,
+    /// Health of the physical drive holding this filesystem, shared by all
+    /// of its partitions
+    #[serde(default)]
+    pub smart: Option<SmartHealth>,
+    /// Inode counts from `statvfs`; `None` on Windows and on filesystems
+    /// that allocate inodes dynamically, such as btrfs
+    #[serde(default)]
+    pub inodes_total: Option<u64>,
+    #[serde(default)]
+    pub inodes_used: Option<u64>,
+    #[serde(default)]
+    pub inode_usage_percent: Option<f32>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// SMART self-assessment and wear indicators for a physical drive
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SmartHealth {
+    /// The whole drive, e.g. `/dev/sda` rather than `/dev/sda1`
+    pub device: String,
+    /// False when the drive predicts its own failure
+    pub healthy: bool,
+    pub temperature_c: Option<f32>,
+    pub power_on_hours: Option<u64>,
+    pub reallocated_sectors: Option<u64>,
+    /// Share of the drive's rated write endurance used up; SSDs only
+    pub wear_level_percent: Option<f32>,
+}
+
+impl SmartHealth {
+    /// A `DiskHealth` metric carrying the health in its value and the
+    /// attributes that were read as tags
+    pub fn to_metric(&self) -> Metric {
+        let mut metric = Metric::new(MetricType::DiskHealth, MetricValue::Boolean(self.healthy), "")
+            .with_tag("device", &self.device);
+        let attributes = [
+            ("temperature_c", self.temperature_c.map(|v| v.to_string())),
+            ("power_on_hours", self.power_on_hours.map(|v| v.to_string())),
+            ("reallocated_sectors", self.reallocated_sectors.map(|v| v.to_string())),
+            ("wear_level_percent", self.wear_level_percent.map(|v| v.to_string())),
+        ];
+        for (tag, value) in attributes {
+            if let Some(value) = value {
+                metric = metric.with_tag(tag, value);
+            }
+        }
+        metric
+    }
+
+    /// Reads back a metric built by `to_metric()`
+    pub fn from_metric(metric: &Metric) -> Option<Self> {
+        let MetricValue::Boolean(healthy) = metric.value else {
+            return None;
+        };
+        let tag = |name: &str| metric.tags.get(name);
+        Some(Self {
+            device: tag("device")?.clone(),
+            healthy,
+            temperature_c: tag("temperature_c").and_then(|v| v.parse().ok()),
+            power_on_hours: tag("power_on_hours").and_then(|v| v.parse().ok()),
+            reallocated_sectors: tag("reallocated_sectors").and_then(|v| v.parse().ok()),
+            wear_level_percent: tag("wear_level_percent").and_then(|v| v.parse().ok()),
+        })
+    }
+}
+
+/// A ZFS pool or Btrfs filesystem, whose capacity spans its devices and
+/// which `DiskMetrics` of the individual mounts don't reflect
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PoolMetrics {
+    /// The ZFS pool name, or the first mount point of a Btrfs filesystem
+    pub name: String,
+    pub kind: PoolKind,
+    /// Bytes across all devices, before redundancy
+    pub raw_capacity: u64,
+    pub allocated: u64,
+    pub free: u64,
+    /// Logical over physical size of the data; 1.0 when uncompressed or
+    /// unknown (Btrfs doesn't report it)
+    pub compression_ratio: f64,
+    /// 1.0 when deduplication is off; always 1.0 on Btrfs
+    pub dedup_ratio: f64,
+    /// `ONLINE`, `DEGRADED`, `FAULTED` and so on; empty on Btrfs, which has
+    /// no pool-level health
+    pub health: String,
+    /// The last or running scrub as the tools describe it, e.g. `scrub
+    /// repaired 0B in 00:01:02 with 0 errors on ...`; empty when unknown
+    pub scrub_state: String,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PoolKind {
+    #[default]
+    Zfs,
+    Btrfs,
+}
+
+impl PoolKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            PoolKind::Zfs => "zfs",
+            PoolKind::Btrfs => "btrfs",
+        }
+    }
+}
+
+impl PoolMetrics {
+    /// A `StoragePool` metric carrying the allocated bytes in its value and
+    /// the rest in tags
+    pub fn to_metric(&self) -> Metric {
+        Metric::new(MetricType::StoragePool, MetricValue::Unsigned(self.allocated), "bytes")
+            .with_tag("pool", &self.name)
+            .with_tag("kind", self.kind.as_str())
+            .with_tag("raw_capacity", self.raw_capacity.to_string())
+            .with_tag("free", self.free.to_string())
+            .with_tag("compression_ratio", self.compression_ratio.to_string())
+            .with_tag("dedup_ratio", self.dedup_ratio.to_string())
+            .with_tag("health", &self.health)
+            .with_tag("scrub_state", &self.scrub_state)
+    }
+
+    /// Reads back a metric built by `to_metric()`
+    pub fn from_metric(metric: &Metric) -> Option<Self> {
+        let MetricValue::Unsigned(allocated) = metric.value else {
+            return None;
+        };
+        let tag = |name: &str| metric.tags.get(name);
+        Some(Self {
+            name: tag("pool")?.clone(),
+            kind: if tag("kind").is_some_and(|k| k == PoolKind::Btrfs.as_str()) { PoolKind::Btrfs } else { PoolKind::Zfs },
+            raw_capacity: tag("raw_capacity").and_then(|v| v.parse().ok()).unwrap_or(0),
+            allocated,
+            free: tag("free").and_then(|v| v.parse().ok()).unwrap_or(0),
+            compression_ratio: tag("compression_ratio").and_then(|v| v.parse().ok()).unwrap_or(1.0),
+            dedup_ratio: tag("dedup_ratio").and_then(|v| v.parse().ok()).unwrap_or(1.0),
+            health: tag("health").cloned().unwrap_or_default(),
+            scrub_state: tag("scrub_state").cloned().unwrap_or_default(),
+        })
+    }
+}
+
+/// Reachability class of an interface address
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AddrScope {
+    Loopback,
+    /// `169.254.0.0/16` and `fe80::/10`
+    LinkLocal,
+    /// RFC 1918 ranges and IPv6 unique local `fc00::/7`
+    Private,
+    Global,
+}
+
+impl AddrScope {
+    pub fn of(addr: &IpAddr) -> Self {
+        match addr {
+            IpAddr::V4(v4) if v4.is_loopback() => AddrScope::Loopback,
+            IpAddr::V4(v4) if v4.is_link_local() => AddrScope::LinkLocal,
+            IpAddr::V4(v4) if v4.is_private() => AddrScope::Private,
+            IpAddr::V6(v6) if v6.is_loopback() => AddrScope::Loopback,
+            IpAddr::V6(v6) if v6.is_unicast_link_local() => AddrScope::LinkLocal,
+            IpAddr::V6(v6) if v6.is_unique_local() => AddrScope::Private,
+            _ => AddrScope::Global,
+        }
+    }
+}
+
+/// One address assigned to an interface
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IpAddress {
+    pub addr: IpAddr,
+    pub prefix_len: u8,
+    pub scope: AddrScope,
+}
+
+impl IpAddress {
+    pub fn new(addr: IpAddr, prefix_len: u8) -> Self {
+        Self { addr, prefix_len, scope: AddrScope::of(&addr) }
+    }
+
+    /// Parses `addr` or `addr/prefix`, dropping any IPv6 zone (`fe80::1%en0`);
+    /// without a prefix the address is taken as a single host
+    pub fn parse(text: &str) -> Option<Self> {
+        let (addr, prefix) = match text.split_once('/') {
+            Some((addr, prefix)) => (addr, Some(prefix.parse().ok()?)),
+            None => (text, None),
+        };
+        let addr: IpAddr = addr.split('%').next()?.parse().ok()?;
+        let host_len = if addr.is_ipv4() { 32 } else { 128 };
+        Some(Self::new(addr, prefix.unwrap_or(host_len)))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct NetworkMetrics {
+    /// Stable key for this interface across samples; the interface name
+    #[serde(default)]
+    pub id: String,
     pub interface_name: String,
     pub is_up: bool,
     pub mac_address: String,
-    pub ip_addresses: Vec<String>,
+    pub ip_addresses: Vec<IpAddress>,
     pub bytes_sent: u64,
     pub bytes_received: u64,
     pub packets_sent: u64,
@@ -84,32 +534,741 @@ pub struct NetworkMetrics {
     pub speed_mbps: Option<u64>,
     pub bytes_sent_rate: u64,
     pub bytes_received_rate: u64,
+    /// Radio link of a wireless interface; `None` for wired interfaces and
+    /// for wireless ones not associated with a network
+    #[serde(default)]
+    pub wireless: Option<WirelessInfo>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// The access point a wireless interface is associated with and the quality
+/// of the link to it
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct WirelessInfo {
+    /// Empty when only `/proc/net/wireless` could be read
+    pub ssid: String,
+    pub signal_dbm: i32,
+    pub link_quality_percent: f32,
+    /// 0 when unknown
+    pub frequency_mhz: u32,
+    /// Current transmit bitrate; 0 when unknown
+    pub tx_rate_mbps: u32,
+}
+
+impl WirelessInfo {
+    /// A `WirelessSignal` metric carrying the signal in its value and the
+    /// rest in tags; the network monitor adds an `interface` tag
+    pub fn to_metric(&self) -> Metric {
+        Metric::new(MetricType::WirelessSignal, MetricValue::Integer(self.signal_dbm as i64), "dBm")
+            .with_tag("ssid", &self.ssid)
+            .with_tag("link_quality", self.link_quality_percent.to_string())
+            .with_tag("frequency_mhz", self.frequency_mhz.to_string())
+            .with_tag("tx_rate_mbps", self.tx_rate_mbps.to_string())
+    }
+
+    /// Reads back a metric built by `to_metric()`
+    pub fn from_metric(metric: &Metric) -> Option<Self> {
+        let MetricValue::Integer(signal_dbm) = metric.value else {
+            return None;
+        };
+        let tag = |name: &str| metric.tags.get(name);
+        Some(Self {
+            ssid: tag("ssid").cloned().unwrap_or_default(),
+            signal_dbm: signal_dbm as i32,
+            link_quality_percent: tag("link_quality").and_then(|v| v.parse().ok()).unwrap_or(0.0),
+            frequency_mhz: tag("frequency_mhz").and_then(|v| v.parse().ok()).unwrap_or(0),
+            tx_rate_mbps: tag("tx_rate_mbps").and_then(|v| v.parse().ok()).unwrap_or(0),
+        })
+    }
+}
+
+/// Host-wide count of TCP connections, IPv4 and IPv6 together, in the
+/// states that show leaks or saturation: a growing `time_wait` points at
+/// connection churn, a growing `close_wait` at sockets the application never
+/// closes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TcpStats {
+    pub established: u64,
+    pub listen: u64,
+    pub time_wait: u64,
+    pub close_wait: u64,
+    pub syn_sent: u64,
+}
+
+impl TcpStats {
+    /// The counts with the `state` tag their `TcpConnections` metrics carry
+    pub fn by_state(&self) -> [(&'static str, u64); 5] {
+        [
+            ("established", self.established),
+            ("listen", self.listen),
+            ("time_wait", self.time_wait),
+            ("close_wait", self.close_wait),
+            ("syn_sent", self.syn_sent),
+        ]
+    }
+
+    /// Counts one connection given its state as `netstat` prints it, e.g.
+    /// `TIME_WAIT`; states that aren't tracked are ignored
+    pub fn count(&mut self, state: &str) {
+        match state {
+            "ESTABLISHED" => self.established += 1,
+            "LISTEN" => self.listen += 1,
+            "TIME_WAIT" => self.time_wait += 1,
+            "CLOSE_WAIT" => self.close_wait += 1,
+            "SYN_SENT" => self.syn_sent += 1,
+            _ => {}
+        }
+    }
+
+    /// Sets the count for a `state` tag from `by_state`
+    pub fn set(&mut self, state: &str, value: u64) {
+        match state {
+            "established" => self.established = value,
+            "listen" => self.listen = value,
+            "time_wait" => self.time_wait = value,
+            "close_wait" => self.close_wait = value,
+            "syn_sent" => self.syn_sent = value,
+            _ => {}
+        }
+    }
+}
+
+/// Loopback interface names: `lo` on Linux, `lo0` on macOS, and
+/// `Loopback Pseudo-Interface 1` on Windows
+pub fn is_loopback_interface(name: &str) -> bool {
+    name.strip_prefix("lo").is_some_and(|rest| rest.chars().all(|c| c.is_ascii_digit()))
+        || name.starts_with("Loopback Pseudo-Interface")
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum BatteryState {
+    Charging,
+    Discharging,
+    Full,
+    /// On external power but not charging, e.g. held at a charge limit
+    NotCharging,
+    #[default]
+    Unknown,
+}
+
+impl std::fmt::Display for BatteryState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BatteryState::Charging => write!(f, "charging"),
+            BatteryState::Discharging => write!(f, "discharging"),
+            BatteryState::Full => write!(f, "full"),
+            BatteryState::NotCharging => write!(f, "not charging"),
+            BatteryState::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
+impl std::str::FromStr for BatteryState {
+    type Err = std::convert::Infallible;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        Ok(match s.trim().to_lowercase().as_str() {
+            "charging" => BatteryState::Charging,
+            "discharging" => BatteryState::Discharging,
+            "full" | "charged" => BatteryState::Full,
+            "not charging" => BatteryState::NotCharging,
+            _ => BatteryState::Unknown,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BatteryMetrics {
+    pub name: String,
+    pub charge_percent: f32,
+    pub state: BatteryState,
+    pub time_to_empty_seconds: Option<u64>,
+    pub time_to_full_seconds: Option<u64>,
+    pub cycle_count: Option<u32>,
+    /// Full-charge capacity as a percentage of the design capacity
+    pub health_percent: Option<f32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SensorType {
+    Temperature,
+    Fan,
+    Voltage,
+    Power,
+    Current,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SensorReading {
+    pub name: String,
+    pub value: f32,
+    pub unit: String,
+    pub sensor_type: SensorType,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ProcessMetrics {
     pub pid: u32,
     pub name: String,
     pub cpu_usage_percent: f32,
     pub memory_bytes: u64,
     pub memory_percent: f32,
+    /// Bytes read since the process started
     pub disk_read_bytes: u64,
+    /// Bytes written since the process started
     pub disk_write_bytes: u64,
+    /// Read rate over the last collection interval
+    #[serde(default)]
+    pub disk_read_bytes_per_sec: u64,
+    /// Write rate over the last collection interval
+    #[serde(default)]
+    pub disk_write_bytes_per_sec: u64,
     pub status: String,
     pub threads: u32,
     pub start_time: SystemTime,
+    #[serde(default)]
+    pub parent_pid: Option<u32>,
+    /// Open file descriptors (handles on Windows)
+    #[serde(default)]
+    pub open_handles: u32,
+    /// False when the count could not be read, usually for lack of
+    /// permission, so `open_handles` is not a real zero
+    #[serde(default)]
+    pub handles_available: bool,
+    /// User plus kernel CPU time consumed since the process started
+    #[serde(default)]
+    pub cpu_time_seconds: f64,
+    /// Wall-clock time since the process started
+    #[serde(default)]
+    pub elapsed_seconds: u64,
+    /// Program and arguments joined by spaces; `None` when they can't be
+    /// read, as for kernel threads or other users' processes on some systems
+    #[serde(default)]
+    pub cmdline: Option<String>,
+    /// Logical CPUs the process may be scheduled on, ascending; empty when
+    /// the affinity is unknown, which callers should read as all cores
+    #[serde(default)]
+    pub cpu_affinity: Vec<u32>,
+}
+
+/// Criteria for `MonitoringService::find_processes`; a process must meet
+/// every one that is set
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ProcessQuery {
+    /// Case-insensitive substring of the name or the command line
+    pub name_contains: Option<String>,
+    pub pid: Option<u32>,
+    pub min_cpu: Option<f32>,
+    pub min_memory: Option<u64>,
 }
 
+impl ProcessQuery {
+    pub fn matches(&self, process: &ProcessMetrics) -> bool {
+        let text_matches = self.name_contains.as_ref().is_none_or(|needle| {
+            let needle = needle.to_lowercase();
+            process.name.to_lowercase().contains(&needle)
+                || process.cmdline.as_ref().is_some_and(|cmdline| cmdline.to_lowercase().contains(&needle))
+        });
+        text_matches
+            && self.pid.is_none_or(|pid| process.pid == pid)
+            && self.min_cpu.is_none_or(|min| process.cpu_usage_percent >= min)
+            && self.min_memory.is_none_or(|min| process.memory_bytes >= min)
+    }
+}
+
+/// Processes sharing a name or executable, with their usage summed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GroupedProcess {
+    /// The shared name or executable path; the process name when not grouped
+    pub name: String,
+    pub instance_count: usize,
+    pub total_cpu: f32,
+    pub total_memory: u64,
+    pub total_disk_read_bytes_per_sec: u64,
+    pub total_disk_write_bytes_per_sec: u64,
+    /// In ascending order
+    pub pids: Vec<u32>,
+}
+
+impl GroupedProcess {
+    /// An empty group to `add` processes to
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            instance_count: 0,
+            total_cpu: 0.0,
+            total_memory: 0,
+            total_disk_read_bytes_per_sec: 0,
+            total_disk_write_bytes_per_sec: 0,
+            pids: Vec::new(),
+        }
+    }
+
+    pub fn add(&mut self, process: &ProcessMetrics) {
+        self.instance_count += 1;
+        self.total_cpu += process.cpu_usage_percent;
+        self.total_memory += process.memory_bytes;
+        self.total_disk_read_bytes_per_sec += process.disk_read_bytes_per_sec;
+        self.total_disk_write_bytes_per_sec += process.disk_write_bytes_per_sec;
+        if let Err(index) = self.pids.binary_search(&process.pid) {
+            self.pids.insert(index, process.pid);
+        }
+    }
+}
+
+/// A process and its descendants, with CPU and memory summed over the subtree
 #[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProcessNode {
+    /// `None` for the synthetic root that holds processes without a known parent
+    pub process: Option<ProcessMetrics>,
+    pub children: Vec<ProcessNode>,
+    pub subtree_cpu_percent: f32,
+    pub subtree_memory_bytes: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct SystemMetrics {
     pub timestamp: SystemTime,
     pub system_info: SystemInfo,
     pub cpu: CpuMetrics,
     pub memory: MemoryMetrics,
     pub gpus: Vec<GpuMetrics>,
+    /// Sorted by mount point, so the order doesn't change between samples
     pub disks: Vec<DiskMetrics>,
+    /// ZFS pools and Btrfs filesystems, sorted by name; empty without them
+    /// or their tools
+    #[serde(default)]
+    pub pools: Vec<PoolMetrics>,
+    /// Sorted by interface name, so the order doesn't change between samples
     pub networks: Vec<NetworkMetrics>,
+    /// `None` where the connection table can't be read
+    #[serde(default)]
+    pub tcp: Option<TcpStats>,
     pub top_processes: Vec<ProcessMetrics>,
+    #[serde(default)]
+    pub batteries: Vec<BatteryMetrics>,
+    #[serde(default)]
+    pub sensors: Vec<SensorReading>,
+    /// Monitors whose collection failed this round, with the error; their
+    /// sections hold defaults
+    #[serde(default)]
+    pub last_errors: HashMap<String, String>,
+    /// 0 (saturated) to 100 (idle), from CPU, memory, disk and swap usage
+    /// weighted by `HealthScoreWeights`; see `compute_health_score`
+    #[serde(default)]
+    pub health_score: f32,
+}
+
+/// Version of the `SystemMetrics` JSON written by `to_json`; bump it whenever
+/// a change would make older readers misparse newer files or vice versa
+pub const METRICS_SCHEMA_VERSION: u32 = 2;
+
+const SCHEMA_VERSION_KEY: &str = "schema_version";
+
+impl SystemMetrics {
+    /// Serializes to JSON tagged with `METRICS_SCHEMA_VERSION`
+    pub fn to_json(&self) -> Result<String> {
+        let mut value = serde_json::to_value(self)?;
+        if let serde_json::Value::Object(fields) = &mut value {
+            fields.insert(SCHEMA_VERSION_KEY.to_string(), METRICS_SCHEMA_VERSION.into());
+        }
+        Ok(serde_json::to_string(&value)?)
+    }
+
+    /// Parses JSON written by `to_json`, rejecting any other schema version
+    pub fn from_json(json: &str) -> Result<Self> {
+        let mut value: serde_json::Value = serde_json::from_str(json)?;
+        let found = value
+            .as_object_mut()
+            .and_then(|fields| fields.remove(SCHEMA_VERSION_KEY))
+            .and_then(|version| version.as_u64())
+            .unwrap_or(0) as u32;
+        if found != METRICS_SCHEMA_VERSION {
+            return Err(MonitorError::SchemaMismatch { expected: METRICS_SCHEMA_VERSION, found });
+        }
+        Ok(serde_json::from_value(value)?)
+    }
+
+    /// Whether any monitor failed to contribute to this snapshot
+    pub fn is_degraded(&self) -> bool {
+        !self.last_errors.is_empty()
+    }
+
+    /// Bytes per second received across all non-loopback interfaces
+    pub fn total_network_rx_bps(&self) -> u64 {
+        self.external_networks().map(|n| n.bytes_received_rate).sum()
+    }
+
+    /// Bytes per second sent across all non-loopback interfaces
+    pub fn total_network_tx_bps(&self) -> u64 {
+        self.external_networks().map(|n| n.bytes_sent_rate).sum()
+    }
+
+    fn external_networks(&self) -> impl Iterator<Item = &NetworkMetrics> {
+        self.networks.iter().filter(|n| !is_loopback_interface(&n.interface_name))
+    }
+
+    pub fn total_disk_read_bps(&self) -> u64 {
+        self.disks.iter().map(|d| d.read_bytes_per_sec).sum()
+    }
+
+    pub fn total_disk_write_bps(&self) -> u64 {
+        self.disks.iter().map(|d| d.write_bytes_per_sec).sum()
+    }
+
+    pub fn total_gpu_power_watts(&self) -> f32 {
+        self.gpus.iter().map(|g| g.power_watts).sum()
+    }
+
+    /// Overall CPU usage, or the mean across cores when the global figure is
+    /// still 0 because the first sample had no previous reading to compare to
+    pub fn overall_cpu_usage(&self) -> f32 {
+        if self.cpu.usage_percent > 0.0 || self.cpu.per_core_usage.is_empty() {
+            return self.cpu.usage_percent;
+        }
+        self.cpu.per_core_usage.iter().sum::<f32>() / self.cpu.per_core_usage.len() as f32
+    }
+
+    /// 100 minus the weighted mean of CPU, memory, fullest-disk and swap
+    /// usage, clamped to 0..=100. Disk and swap only count when there are
+    /// disks or swap; with no weight left the score is 100.
+    pub fn compute_health_score(&self, weights: &HealthScoreWeights) -> f32 {
+        let fullest_disk = self.disks.iter().map(|d| d.usage_percent).reduce(f32::max);
+        let swap = (self.memory.swap_total_bytes > 0).then_some(self.memory.swap_usage_percent);
+        let figures = [
+            (Some(self.overall_cpu_usage()), weights.cpu),
+            (Some(self.memory.usage_percent), weights.memory),
+            (fullest_disk, weights.disk),
+            (swap, weights.swap),
+        ];
+
+        let (weighted, total_weight) = figures
+            .iter()
+            .filter_map(|&(usage, weight)| Some((usage?.clamp(0.0, 100.0), weight.max(0.0))))
+            .fold((0.0, 0.0), |(sum, total), (usage, weight)| (sum + usage * weight, total + weight));
+        if total_weight <= 0.0 {
+            return 100.0;
+        }
+        (100.0 - weighted / total_weight).clamp(0.0, 100.0)
+    }
+
+    /// Current values of the gauges of `metric_type`, each with the core, GPU,
+    /// disk or interface it belongs to (empty for system-wide values). Cores
+    /// and GPUs go by position, since identical cards share a name.
+    ///
+    /// Covers the same types as `MetricsSmoother`, plus `CpuFrequency` and
+    /// `SwapUsage`; other types yield nothing.
+    pub fn gauge_values(&self, metric_type: MetricType) -> Vec<(String, f64)> {
+        let mut values = Vec::new();
+        match metric_type {
+            MetricType::CpuUsage => {
+                values.push((String::new(), self.cpu.usage_percent as f64));
+                for (core, usage) in self.cpu.per_core_usage.iter().enumerate() {
+                    values.push((core.to_string(), *usage as f64));
+                }
+            }
+            MetricType::CpuFrequency => values.push((String::new(), self.cpu.frequency_mhz as f64)),
+            MetricType::CpuTemperature => {
+                values.extend(self.cpu.temperature_celsius.map(|t| (String::new(), t as f64)));
+            }
+            MetricType::MemoryUsage => values.push((String::new(), self.memory.usage_percent as f64)),
+            MetricType::SwapUsage => values.push((String::new(), self.memory.swap_usage_percent as f64)),
+            MetricType::GpuUsage => values.extend(self.gpu_values(|g| g.usage_percent)),
+            MetricType::GpuMemoryUsage => values.extend(self.gpu_values(|g| g.memory_usage_percent)),
+            MetricType::GpuTemperature => values.extend(self.gpu_values(|g| g.temperature_celsius)),
+            MetricType::GpuPower => values.extend(self.gpu_values(|g| g.power_watts)),
+            MetricType::DiskBusy => {
+                values.extend(self.disks.iter().map(|d| (d.mount_point.clone(), d.busy_percent as f64)));
+            }
+            MetricType::DiskIo => {
+                for disk in &self.disks {
+                    values.push((format!("{}:read", disk.mount_point), disk.read_bytes_per_sec as f64));
+                    values.push((format!("{}:write", disk.mount_point), disk.write_bytes_per_sec as f64));
+                }
+            }
+            MetricType::NetworkThroughput => {
+                for network in &self.networks {
+                    let name = &network.interface_name;
+                    values.push((format!("{}:sent", name), network.bytes_sent_rate as f64));
+                    values.push((format!("{}:received", name), network.bytes_received_rate as f64));
+                }
+            }
+            _ => {}
+        }
+        values
+    }
+
+    fn gpu_values(&self, value: fn(&GpuMetrics) -> f32) -> impl Iterator<Item = (String, f64)> + '_ {
+        self.gpus.iter().enumerate().map(move |(index, gpu)| (index.to_string(), value(gpu) as f64))
+    }
+
+    /// Rates of change since `previous`, with gauges taken from `self`
+    ///
+    /// Interfaces are matched by name, disks by mount point and processes by
+    /// pid and start time; devices present in only one sample are listed as
+    /// added or removed instead of diffed. A counter that went backwards, as
+    /// when an interface is reset, yields a rate of 0.
+    pub fn diff(&self, previous: &SystemMetrics) -> SystemMetricsDelta {
+        let elapsed = self.timestamp.duration_since(previous.timestamp).unwrap_or_default().as_secs_f64();
+        let rate = |current: u64, prior: u64| counter_rate(current, prior, elapsed);
+
+        let networks = self
+            .networks
+            .iter()
+            .filter_map(|current| {
+                let prior = previous.networks.iter().find(|n| n.interface_name == current.interface_name)?;
+                Some(NetworkDelta {
+                    interface_name: current.interface_name.clone(),
+                    bytes_sent_per_sec: rate(current.bytes_sent, prior.bytes_sent),
+                    bytes_received_per_sec: rate(current.bytes_received, prior.bytes_received),
+                    packets_sent_per_sec: rate(current.packets_sent, prior.packets_sent),
+                    packets_received_per_sec: rate(current.packets_received, prior.packets_received),
+                    errors_sent_per_sec: rate(current.errors_sent, prior.errors_sent),
+                    errors_received_per_sec: rate(current.errors_received, prior.errors_received),
+                })
+            })
+            .collect();
+
+        let processes = self
+            .top_processes
+            .iter()
+            .filter_map(|current| {
+                let prior = previous
+                    .top_processes
+                    .iter()
+                    .find(|p| p.pid == current.pid && p.start_time == current.start_time)?;
+                let cpu_seconds = (current.cpu_time_seconds - prior.cpu_time_seconds).max(0.0);
+                Some(ProcessDelta {
+                    pid: current.pid,
+                    name: current.name.clone(),
+                    disk_read_bytes_per_sec: rate(current.disk_read_bytes, prior.disk_read_bytes),
+                    disk_write_bytes_per_sec: rate(current.disk_write_bytes, prior.disk_write_bytes),
+                    cpu_percent: if elapsed > 0.0 { cpu_seconds / elapsed * 100.0 } else { 0.0 },
+                })
+            })
+            .collect();
+
+        let disks = self
+            .disks
+            .iter()
+            .filter(|current| previous.disks.iter().any(|d| d.mount_point == current.mount_point))
+            .map(|disk| DiskDelta {
+                mount_point: disk.mount_point.clone(),
+                usage_percent: disk.usage_percent,
+                busy_percent: disk.busy_percent,
+                read_bytes_per_sec: disk.read_bytes_per_sec,
+                write_bytes_per_sec: disk.write_bytes_per_sec,
+            })
+            .collect();
+
+        let interface_names = |metrics: &SystemMetrics| -> Vec<String> {
+            metrics.networks.iter().map(|n| n.interface_name.clone()).collect()
+        };
+        let mount_points = |metrics: &SystemMetrics| -> Vec<String> {
+            metrics.disks.iter().map(|d| d.mount_point.clone()).collect()
+        };
+        let (current_interfaces, previous_interfaces) = (interface_names(self), interface_names(previous));
+        let (current_mounts, previous_mounts) = (mount_points(self), mount_points(previous));
+
+        SystemMetricsDelta {
+            elapsed_seconds: elapsed,
+            cpu_usage_percent: self.cpu.usage_percent,
+            cpu_temperature_celsius: self.cpu.temperature_celsius,
+            memory_usage_percent: self.memory.usage_percent,
+            gpu_temperatures_celsius: self.gpus.iter().map(|g| g.temperature_celsius).collect(),
+            context_switches_per_sec: rate(self.cpu.context_switches, previous.cpu.context_switches),
+            interrupts_per_sec: rate(self.cpu.interrupts, previous.cpu.interrupts),
+            networks,
+            disks,
+            processes,
+            added_interfaces: missing_from(&current_interfaces, &previous_interfaces),
+            removed_interfaces: missing_from(&previous_interfaces, &current_interfaces),
+            added_disks: missing_from(&current_mounts, &previous_mounts),
+            removed_disks: missing_from(&previous_mounts, &current_mounts),
+        }
+    }
+}
+
+/// Per-second rate of a cumulative counter over `elapsed_seconds`; 0 when
+/// no time passed or the counter went backwards
+pub fn counter_rate(current: u64, previous: u64, elapsed_seconds: f64) -> f64 {
+    if elapsed_seconds > 0.0 {
+        current.saturating_sub(previous) as f64 / elapsed_seconds
+    } else {
+        0.0
+    }
+}
+
+/// Entries of `names` that `other` lacks
+fn missing_from(names: &[String], other: &[String]) -> Vec<String> {
+    names.iter().filter(|name| !other.contains(name)).cloned().collect()
+}
+
+/// What changed between two `SystemMetrics` samples, from `SystemMetrics::diff`
+///
+/// Counters become per-second rates over `elapsed_seconds`; gauges such as
+/// usage and temperature are the newer sample's values.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SystemMetricsDelta {
+    pub elapsed_seconds: f64,
+    pub cpu_usage_percent: f32,
+    pub cpu_temperature_celsius: Option<f32>,
+    pub memory_usage_percent: f32,
+    pub gpu_temperatures_celsius: Vec<f32>,
+    pub context_switches_per_sec: f64,
+    pub interrupts_per_sec: f64,
+    /// Interfaces present in both samples
+    pub networks: Vec<NetworkDelta>,
+    /// Disks present in both samples
+    pub disks: Vec<DiskDelta>,
+    /// Top processes present in both samples
+    pub processes: Vec<ProcessDelta>,
+    pub added_interfaces: Vec<String>,
+    pub removed_interfaces: Vec<String>,
+    pub added_disks: Vec<String>,
+    pub removed_disks: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NetworkDelta {
+    pub interface_name: String,
+    pub bytes_sent_per_sec: f64,
+    pub bytes_received_per_sec: f64,
+    pub packets_sent_per_sec: f64,
+    pub packets_received_per_sec: f64,
+    pub errors_sent_per_sec: f64,
+    pub errors_received_per_sec: f64,
+}
+
+/// Disks only report rates and gauges, so these are carried over as-is
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DiskDelta {
+    pub mount_point: String,
+    pub usage_percent: f32,
+    pub busy_percent: f32,
+    pub read_bytes_per_sec: u64,
+    pub write_bytes_per_sec: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProcessDelta {
+    pub pid: u32,
+    pub name: String,
+    pub disk_read_bytes_per_sec: f64,
+    pub disk_write_bytes_per_sec: f64,
+    /// CPU time used between the samples as a share of the time between
+    /// them; over 100 when the process kept more than one core busy
+    pub cpu_percent: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AlertLevel {
+    Warning,
+    Critical,
+    /// The metric fell back below the warning threshold
+    Cleared,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlertEvent {
+    pub metric_type: MetricType,
+    /// The instance that crossed, e.g. a mount point or GPU name; empty for
+    /// system-wide metrics
+    pub source: String,
+    pub level: AlertLevel,
+    pub value: f64,
+    /// The threshold that was crossed; the lowest configured one for `Cleared`
+    pub threshold: f64,
+    pub timestamp: SystemTime,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum MetricCategory {
+    Cpu,
+    Memory,
+    Gpu,
+    Disk,
+    Network,
+    Process,
+    Battery,
+    Sensor,
+}
+
+impl MetricCategory {
+    fn bit(self) -> u32 {
+        1 << self as u32
+    }
+}
+
+/// A set of metric categories a subscriber wants delivered
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MetricCategorySet(u32);
+
+impl MetricCategorySet {
+    pub const fn empty() -> Self {
+        Self(0)
+    }
+
+    pub const fn all() -> Self {
+        // One bit for each category up to the last
+        Self((1 << (MetricCategory::Sensor as u32 + 1)) - 1)
+    }
+
+    pub fn with(mut self, category: MetricCategory) -> Self {
+        self.insert(category);
+        self
+    }
+
+    pub fn insert(&mut self, category: MetricCategory) {
+        self.0 |= category.bit();
+    }
+
+    pub fn contains(&self, category: MetricCategory) -> bool {
+        self.0 & category.bit() != 0
+    }
+
+    pub fn is_all(&self) -> bool {
+        *self == Self::all()
+    }
+}
+
+impl FromIterator<MetricCategory> for MetricCategorySet {
+    fn from_iter<I: IntoIterator<Item = MetricCategory>>(iter: I) -> Self {
+        iter.into_iter().fold(Self::empty(), Self::with)
+    }
+}
+
+impl SystemMetrics {
+    /// Copies only the wanted categories; the rest are left at their defaults
+    /// (zeroed CPU/memory structs, empty device and process lists)
+    pub fn filtered(&self, categories: MetricCategorySet) -> SystemMetrics {
+        let wants = |category| categories.contains(category);
+
+        SystemMetrics {
+            timestamp: self.timestamp,
+            system_info: self.system_info.clone(),
+            cpu: if wants(MetricCategory::Cpu) { self.cpu.clone() } else { CpuMetrics::default() },
+            memory: if wants(MetricCategory::Memory) { self.memory.clone() } else { MemoryMetrics::default() },
+            gpus: if wants(MetricCategory::Gpu) { self.gpus.clone() } else { Vec::new() },
+            disks: if wants(MetricCategory::Disk) { self.disks.clone() } else { Vec::new() },
+            pools: if wants(MetricCategory::Disk) { self.pools.clone() } else { Vec::new() },
+            networks: if wants(MetricCategory::Network) { self.networks.clone() } else { Vec::new() },
+            tcp: if wants(MetricCategory::Network) { self.tcp } else { None },
+            top_processes: if wants(MetricCategory::Process) { self.top_processes.clone() } else { Vec::new() },
+            batteries: if wants(MetricCategory::Battery) { self.batteries.clone() } else { Vec::new() },
+            sensors: if wants(MetricCategory::Sensor) { self.sensors.clone() } else { Vec::new() },
+            last_errors: self.last_errors.clone(),
+            health_score: self.health_score,
+        }
+    }
+}
+
+/// Samples subscribers lost by falling behind `MonitoringService`'s channels
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LagStats {
+    /// Samples overwritten before the slowest subscriber received them
+    pub total_dropped: u64,
+    /// Most samples a subscriber was behind, at most the channel capacity
+    pub max_lag_observed: u64,
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
@@ -122,6 +1281,25 @@ pub struct MonitoringInterval {
     pub process: Duration,
 }
 
+impl MonitoringInterval {
+    /// Interval for the monitor registered as `name`; monitors without a
+    /// setting of their own (battery, sensors) follow the slowest one
+    pub fn for_monitor(&self, name: &str) -> Duration {
+        match name {
+            "cpu" => self.cpu,
+            "memory" => self.memory,
+            "gpu" => self.gpu,
+            "storage" => self.disk,
+            "network" => self.network,
+            "process" => self.process,
+            _ => [self.cpu, self.memory, self.gpu, self.disk, self.network, self.process]
+                .into_iter()
+                .max()
+                .unwrap_or_default(),
+        }
+    }
+}
+
 impl Default for MonitoringInterval {
     fn default() -> Self {
         Self {
@@ -0,0 +1,78 @@
+use serde::{Deserialize, Serialize};
+
+/// Base used when scaling byte counts for display
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UnitSystem {
+    /// Powers of 1024: KiB, MiB, GiB
+    #[default]
+    Binary,
+    /// Powers of 1000: kB, MB, GB
+    Decimal,
+}
+
+/// Whether throughput is shown in bytes or bits per second
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ThroughputUnit {
+    #[default]
+    Bytes,
+    Bits,
+}
+
+const BINARY_BYTES: [&str; 6] = ["B", "KiB", "MiB", "GiB", "TiB", "PiB"];
+const DECIMAL_BYTES: [&str; 6] = ["B", "kB", "MB", "GB", "TB", "PB"];
+const BINARY_BITS: [&str; 6] = ["bit", "Kibit", "Mibit", "Gibit", "Tibit", "Pibit"];
+const DECIMAL_BITS: [&str; 6] = ["bit", "kbit", "Mbit", "Gbit", "Tbit", "Pbit"];
+
+impl UnitSystem {
+    fn base(self) -> f64 {
+        match self {
+            UnitSystem::Binary => 1024.0,
+            UnitSystem::Decimal => 1000.0,
+        }
+    }
+}
+
+/// Scales `value` to the largest unit it reaches; whole numbers below the
+/// first step are printed without decimals
+fn scale(value: f64, units: UnitSystem, names: &[&str; 6]) -> String {
+    let base = units.base();
+    let mut scaled = value;
+    let mut step = 0;
+    while scaled >= base && step < names.len() - 1 {
+        scaled /= base;
+        step += 1;
+    }
+
+    if step == 0 {
+        format!("{} {}", scaled, names[0])
+    } else {
+        format!("{:.2} {}", scaled, names[step])
+    }
+}
+
+/// Renders a byte count, e.g. `1.50 GiB` or `1.61 GB`
+pub fn format_bytes(bytes: u64, units: UnitSystem) -> String {
+    let names = match units {
+        UnitSystem::Binary => &BINARY_BYTES,
+        UnitSystem::Decimal => &DECIMAL_BYTES,
+    };
+    scale(bytes as f64, units, names)
+}
+
+/// Renders a rate given in bytes per second, e.g. `12.00 MiB/s` or
+/// `100.66 Mbit/s`
+pub fn format_rate(bytes_per_sec: u64, throughput: ThroughputUnit, units: UnitSystem) -> String {
+    let rendered = match throughput {
+        ThroughputUnit::Bytes => format_bytes(bytes_per_sec, units),
+        ThroughputUnit::Bits => {
+            let names = match units {
+                UnitSystem::Binary => &BINARY_BITS,
+                UnitSystem::Decimal => &DECIMAL_BITS,
+            };
+            scale(bytes_per_sec as f64 * 8.0, units, names)
+        }
+    };
+    format!("{}/s", rendered)
+}
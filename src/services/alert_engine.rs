@@ -0,0 +1,104 @@
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, SystemTime};
+
+use crate::core::AlertAggregation;
+
+/// Keeps a timestamped window of samples per metric so alert rules can be
+/// evaluated against an aggregate (`avg`, `percentile`) instead of the
+/// latest, possibly spiky, reading.
+///
+/// The monitors' own history can't serve here: its retention and byte cap
+/// are configured per monitor and may be shorter than a rule's window, and
+/// rules and the guard policy evaluate values read from the assembled
+/// `SystemMetrics` (the top processes only, for the policy), not the
+/// monitors' own collections.
+pub struct AlertEngine {
+    samples: HashMap<String, VecDeque<(SystemTime, f64)>>,
+    retention: Duration,
+}
+
+impl AlertEngine {
+    /// Creates an engine that retains samples for `retention`
+    pub fn new(retention: Duration) -> Self {
+        Self {
+            samples: HashMap::new(),
+            retention,
+        }
+    }
+
+    /// Records a sample for `key` and drops samples older than the retention window
+    pub fn record(&mut self, key: &str, value: f64, at: SystemTime) {
+        let history = self.samples.entry(key.to_string()).or_default();
+        history.push_back((at, value));
+
+        if let Some(cutoff) = at.checked_sub(self.retention) {
+            while history.front().is_some_and(|(ts, _)| *ts < cutoff) {
+                history.pop_front();
+            }
+        }
+    }
+
+    /// Aggregates the retained samples for `key` over the rule's window.
+    ///
+    /// Returns `None` when no samples fall inside the window.
+    pub fn aggregate(&self, key: &str, aggregation: AlertAggregation, now: SystemTime) -> Option<f64> {
+        let history = self.samples.get(key)?;
+
+        match aggregation {
+            AlertAggregation::Instant => history.back().map(|(_, value)| *value),
+            AlertAggregation::Average { window_seconds } => {
+                let values = Self::window_values(history, window_seconds, now);
+                if values.is_empty() {
+                    None
+                } else {
+                    Some(values.iter().sum::<f64>() / values.len() as f64)
+                }
+            }
+            AlertAggregation::Minimum { window_seconds } => Self::window_values(history, window_seconds, now)
+                .into_iter()
+                .reduce(f64::min),
+            AlertAggregation::Percentile { percentile, window_seconds } => {
+                let mut values = Self::window_values(history, window_seconds, now);
+                percentile_of(&mut values, percentile)
+            }
+        }
+    }
+
+    fn window_values(history: &VecDeque<(SystemTime, f64)>, window_seconds: u64, now: SystemTime) -> Vec<f64> {
+        let cutoff = now
+            .checked_sub(Duration::from_secs(window_seconds))
+            .unwrap_or(SystemTime::UNIX_EPOCH);
+
+        history
+            .iter()
+            .filter(|(ts, _)| *ts >= cutoff)
+            .map(|(_, value)| *value)
+            .collect()
+    }
+
+    /// Returns true when the retained samples for `key` reach back at least
+    /// `window_seconds`, so a windowed aggregate is not based on a partial window
+    pub fn covers(&self, key: &str, window_seconds: u64, now: SystemTime) -> bool {
+        let Some((oldest, _)) = self.samples.get(key).and_then(|history| history.front()) else {
+            return false;
+        };
+        now.duration_since(*oldest).unwrap_or_default() >= Duration::from_secs(window_seconds)
+    }
+
+    /// Forgets all samples for `key`
+    pub fn clear(&mut self, key: &str) {
+        self.samples.remove(key);
+    }
+}
+
+/// Nearest-rank percentile of `values` (sorted in place); `p` is in (0, 100]
+pub fn percentile_of(values: &mut [f64], p: f64) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+
+    values.sort_by(|a, b| a.total_cmp(b));
+    let rank = ((p / 100.0) * values.len() as f64).ceil() as usize;
+    let idx = rank.clamp(1, values.len()) - 1;
+    Some(values[idx])
+}
@@ -0,0 +1,190 @@
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, SystemTime};
+
+use crate::core::{
+    AlertAggregation, AlertConfig, AlertEvent, AlertLevel, MetricType, MonitorSettings,
+    MonitoringConfig, SystemMetrics,
+};
+use crate::services::alert_engine::AlertEngine;
+
+#[derive(Debug, Clone)]
+pub struct AlertRule {
+    pub metric_type: MetricType,
+    pub warning: Option<f64>,
+    pub critical: Option<f64>,
+    pub aggregation: AlertAggregation,
+}
+
+impl AlertRule {
+    fn from_settings(metric_type: MetricType, settings: &MonitorSettings) -> Option<Self> {
+        if !settings.enabled || (settings.warning_threshold.is_none() && settings.critical_threshold.is_none()) {
+            return None;
+        }
+
+        Some(Self {
+            metric_type,
+            warning: settings.warning_threshold.map(f64::from),
+            critical: settings.critical_threshold.map(f64::from),
+            aggregation: settings.aggregation,
+        })
+    }
+
+    /// The lowest configured threshold, below which the alert clears
+    fn lowest(&self) -> Option<f64> {
+        self.warning.or(self.critical)
+    }
+}
+
+/// Compares scalar metrics against their thresholds and reports level changes.
+///
+/// A metric escalates as soon as it reaches a threshold but only steps down
+/// once it is `hysteresis` below it, so a value hovering at the boundary
+/// produces one event instead of one per tick.
+pub struct AlertEvaluator {
+    rules: Vec<AlertRule>,
+    hysteresis: f64,
+    engine: AlertEngine,
+    active: HashMap<String, AlertLevel>,
+    /// Drives whose SMART self-assessment is currently failing
+    failing_drives: HashSet<String>,
+}
+
+impl AlertEvaluator {
+    /// Builds rules from the per-monitor thresholds: CPU, memory, GPU and disk
+    /// usage, plus inode usage against the disk's critical threshold only
+    pub fn new(monitoring: &MonitoringConfig, alerts: &AlertConfig) -> Self {
+        let mut rules: Vec<AlertRule> = [
+            (MetricType::CpuUsage, &monitoring.cpu),
+            (MetricType::MemoryUsage, &monitoring.memory),
+            (MetricType::GpuUsage, &monitoring.gpu),
+            (MetricType::DiskUsage, &monitoring.disk),
+        ]
+        .into_iter()
+        .filter_map(|(metric_type, settings)| AlertRule::from_settings(metric_type, settings))
+        .collect();
+
+        rules.extend(
+            AlertRule::from_settings(MetricType::DiskInodes, &monitoring.disk)
+                .filter(|rule| rule.critical.is_some())
+                .map(|rule| AlertRule { warning: None, ..rule }),
+        );
+
+        Self::from_rules(rules, alerts.hysteresis_percent)
+    }
+
+    pub fn from_rules(rules: Vec<AlertRule>, hysteresis: f64) -> Self {
+        let retention = rules
+            .iter()
+            .map(|rule| rule.aggregation.window_seconds())
+            .max()
+            .unwrap_or(0);
+
+        Self {
+            rules,
+            hysteresis,
+            // Keep one extra interval so the oldest sample still covers the window
+            engine: AlertEngine::new(Duration::from_secs(retention + 60)),
+            active: HashMap::new(),
+            failing_drives: HashSet::new(),
+        }
+    }
+
+    /// Records one sample and returns an event if the alert level changed
+    pub fn observe(&mut self, metric_type: MetricType, source: &str, value: f64, now: SystemTime) -> Option<AlertEvent> {
+        let rule = self.rules.iter().find(|rule| rule.metric_type == metric_type)?;
+        let key = format!("{:?}:{}", metric_type, source);
+
+        self.engine.record(&key, value, now);
+        if rule.aggregation != AlertAggregation::Instant
+            && !self.engine.covers(&key, rule.aggregation.window_seconds(), now)
+        {
+            return None;
+        }
+        let value = self.engine.aggregate(&key, rule.aggregation, now)?;
+
+        let reached = |threshold: Option<f64>| threshold.is_some_and(|t| value >= t);
+        let below = |threshold: Option<f64>| threshold.is_some_and(|t| value < t - self.hysteresis);
+
+        let current = self.active.get(&key).copied();
+        let next = match current {
+            None if reached(rule.critical) => Some(AlertLevel::Critical),
+            None if reached(rule.warning) => Some(AlertLevel::Warning),
+            Some(_) if below(rule.lowest()) => Some(AlertLevel::Cleared),
+            Some(AlertLevel::Warning) if reached(rule.critical) => Some(AlertLevel::Critical),
+            Some(AlertLevel::Critical) if rule.warning.is_some() && below(rule.critical) => Some(AlertLevel::Warning),
+            _ => None,
+        }?;
+
+        let threshold = match next {
+            AlertLevel::Critical => rule.critical,
+            AlertLevel::Warning => rule.warning,
+            AlertLevel::Cleared => rule.lowest(),
+        }?;
+
+        match next {
+            AlertLevel::Cleared => self.active.remove(&key),
+            level => self.active.insert(key, level),
+        };
+
+        Some(AlertEvent {
+            metric_type,
+            source: source.to_string(),
+            level: next,
+            value,
+            threshold,
+            timestamp: now,
+        })
+    }
+
+    /// Raises a critical alert when a drive starts predicting its own
+    /// failure, and clears it if the drive reports healthy again
+    ///
+    /// Needs no configured threshold. The event's value is 0 while failing
+    /// and 1 once healthy, against a threshold of 1.
+    pub fn observe_drive_health(&mut self, device: &str, healthy: bool, now: SystemTime) -> Option<AlertEvent> {
+        let level = if healthy {
+            self.failing_drives.remove(device).then_some(AlertLevel::Cleared)?
+        } else {
+            self.failing_drives.insert(device.to_string()).then_some(AlertLevel::Critical)?
+        };
+
+        Some(AlertEvent {
+            metric_type: MetricType::DiskHealth,
+            source: device.to_string(),
+            level,
+            value: if healthy { 1.0 } else { 0.0 },
+            threshold: 1.0,
+            timestamp: now,
+        })
+    }
+
+    /// Evaluates every thresholded metric in a snapshot
+    pub fn evaluate(&mut self, metrics: &SystemMetrics) -> Vec<AlertEvent> {
+        let now = metrics.timestamp;
+        let mut samples = vec![
+            (MetricType::CpuUsage, String::new(), metrics.cpu.usage_percent as f64),
+            (MetricType::MemoryUsage, String::new(), metrics.memory.usage_percent as f64),
+        ];
+        // By position, as the `gpu` tag does: identical cards share a name
+        samples.extend(metrics.gpus.iter().enumerate().map(|(index, gpu)| {
+            (MetricType::GpuUsage, index.to_string(), gpu.usage_percent as f64)
+        }));
+        samples.extend(metrics.disks.iter().map(|disk| (MetricType::DiskUsage, disk.mount_point.clone(), disk.usage_percent as f64)));
+        samples.extend(metrics.disks.iter().filter_map(|disk| {
+            Some((MetricType::DiskInodes, disk.mount_point.clone(), disk.inode_usage_percent? as f64))
+        }));
+
+        let mut events: Vec<AlertEvent> = samples
+            .into_iter()
+            .filter_map(|(metric_type, source, value)| self.observe(metric_type, &source, value, now))
+            .collect();
+
+        // Partitions of one drive share its SMART data; only the first of
+        // them can change the drive's state, so each drive reports once
+        for smart in metrics.disks.iter().filter_map(|disk| disk.smart.as_ref()) {
+            events.extend(self.observe_drive_health(&smart.device, smart.healthy, now));
+        }
+
+        events
+    }
+}
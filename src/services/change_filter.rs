@@ -0,0 +1,118 @@
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime};
+
+use crate::core::{ChangeFilterConfig, MetricType, SystemMetrics};
+
+/// Gauges `ChangeFilter::should_store_sample` compares between samples
+const SAMPLE_GAUGES: [MetricType; 12] = [
+    MetricType::CpuUsage,
+    MetricType::CpuFrequency,
+    MetricType::CpuTemperature,
+    MetricType::MemoryUsage,
+    MetricType::SwapUsage,
+    MetricType::GpuUsage,
+    MetricType::GpuMemoryUsage,
+    MetricType::GpuTemperature,
+    MetricType::GpuPower,
+    MetricType::DiskBusy,
+    MetricType::DiskIo,
+    MetricType::NetworkThroughput,
+];
+
+/// Decides which samples are worth writing to history.
+///
+/// Each series remembers the last value it stored. A new sample is kept when
+/// it differs by more than the configured delta or when the keyframe interval
+/// elapsed, so the series can be reconstructed by holding the previous value.
+pub struct ChangeFilter {
+    enabled: bool,
+    min_delta: f64,
+    keyframe_interval: Duration,
+    last_stored: HashMap<String, (SystemTime, f64)>,
+}
+
+impl ChangeFilter {
+    pub fn new(config: &ChangeFilterConfig) -> Self {
+        Self {
+            enabled: config.enabled,
+            min_delta: config.min_delta,
+            keyframe_interval: Duration::from_secs(config.keyframe_interval_seconds),
+            last_stored: HashMap::new(),
+        }
+    }
+
+    /// Returns true when `value` should be stored for `key`, and records it as
+    /// the last stored value if so
+    pub fn should_store(&mut self, key: &str, value: f64, at: SystemTime) -> bool {
+        if !self.enabled {
+            return true;
+        }
+
+        let store = self.changed(key, value, at);
+        if store {
+            self.last_stored.insert(key.to_string(), (at, value));
+        }
+        store
+    }
+
+    /// Returns true when the whole sample `metrics` should be stored: when
+    /// any of its gauges (see `SAMPLE_GAUGES`) should be, or one appeared or
+    /// went away. A stored sample becomes the last stored value of every
+    /// gauge, since they are all written together.
+    pub fn should_store_sample(&mut self, metrics: &SystemMetrics) -> bool {
+        if !self.enabled {
+            return true;
+        }
+
+        let at = metrics.timestamp;
+        let gauges: Vec<(String, f64)> = SAMPLE_GAUGES
+            .iter()
+            .flat_map(|&metric_type| {
+                metrics
+                    .gauge_values(metric_type)
+                    .into_iter()
+                    .map(move |(source, value)| (format!("{:?}/{}", metric_type, source), value))
+            })
+            .collect();
+
+        let store = gauges.len() != self.last_stored.len()
+            || gauges.iter().any(|(key, value)| self.changed(key, *value, at));
+        if store {
+            self.last_stored = gauges.into_iter().map(|(key, value)| (key, (at, value))).collect();
+        }
+        store
+    }
+
+    fn changed(&self, key: &str, value: f64, at: SystemTime) -> bool {
+        match self.last_stored.get(key) {
+            None => true,
+            Some((stored_at, stored_value)) => {
+                (value - stored_value).abs() > self.min_delta
+                    || at.duration_since(*stored_at).unwrap_or_default() >= self.keyframe_interval
+                    // A NaN never compares, so treat any change to or from it as a change
+                    || value.is_nan() != stored_value.is_nan()
+            }
+        }
+    }
+
+    /// Forgets `key`, so its next sample is stored unconditionally
+    pub fn reset(&mut self, key: &str) {
+        self.last_stored.remove(key);
+    }
+}
+
+/// Whether any gauge of `metrics` moved by more than `threshold` between
+/// `previous` and `current`, as listed by `SystemMetrics::gauge_values`.
+///
+/// A device present in only one of the two samples counts as a change.
+pub fn deadband_exceeded(previous: &SystemMetrics, current: &SystemMetrics, metrics: &[MetricType], threshold: f64) -> bool {
+    metrics.iter().any(|&metric_type| {
+        let before: HashMap<String, f64> = previous.gauge_values(metric_type).into_iter().collect();
+        let after = current.gauge_values(metric_type);
+        after.len() != before.len()
+            || after.iter().any(|(source, value)| match before.get(source) {
+                None => true,
+                Some(prior) => (value - prior).abs() > threshold || value.is_nan() != prior.is_nan(),
+            })
+    })
+}
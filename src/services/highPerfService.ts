@@ -13,6 +13,7 @@ try {
   listen = async () => () => {};
 }
 import { HighPerfMetrics } from '../types';
+import { decodeHighPerf } from './highPerfWire';
 
 export class HighPerfMonitoringService {
   private isRunning = false;
@@ -28,17 +29,9 @@ export class HighPerfMonitoringService {
       this.unsubscribe = await listen('high-perf-metrics', (event: any) => {
         if (this.metricsCallback && event.payload) {
           try {
-            // Decode binary data (assuming it's base64 encoded from Rust)
-            const binaryData = atob(event.payload as string);
-            const uint8Array = new Uint8Array(binaryData.length);
-            for (let i = 0; i < binaryData.length; i++) {
-              uint8Array[i] = binaryData.charCodeAt(i);
-            }
-            
-            // For now, we'll use JSON as fallback until we implement proper binary decoding
-            // In a real implementation, you'd use a binary decoder like protobuf or flatbuffers
-            const metrics = event.payload as HighPerfMetrics;
-            this.metricsCallback(metrics);
+            // Tauri serializes the frame's bytes as a JSON array
+            const frame = Uint8Array.from(event.payload as number[]);
+            this.metricsCallback(decodeHighPerf(frame));
           } catch (error) {
             console.error('Error processing high-performance metrics:', error);
           }
@@ -0,0 +1,291 @@
+// Decoder for the framed high-performance stream (see src-tauri wire.rs).
+//
+// A frame is the 4-byte "SDHP" magic, the schema version as a little-endian
+// u16 and then the bincode encoding of `HighPerfMetrics`. Both header fields
+// are checked before the payload is read, so a frame from a backend with a
+// different schema is rejected instead of decoded into garbage.
+//
+// Keep HIGH_PERF_VERSION and the readers below in step with wire.rs and the
+// structs in high_perf_monitor.rs.
+import {
+  HighPerfMetrics,
+  HighPerfCpuMetrics,
+  HighPerfMemoryMetrics,
+  HighPerfGpuMetrics,
+  HighPerfDiskMetrics,
+  HighPerfNetworkMetrics,
+  HighPerfProcessMetrics,
+  DpuMetrics,
+  NpuMetrics,
+  ExternalDdrMetrics,
+  FpgaMetrics,
+  AsicMetrics,
+  QuantumProcessorMetrics,
+} from '../types';
+
+export const HIGH_PERF_MAGIC = 'SDHP';
+export const HIGH_PERF_VERSION = 1;
+
+const HEADER_LEN = 6;
+
+export class WireError extends Error {
+  constructor(message: string) {
+    super(message);
+    this.name = 'WireError';
+  }
+}
+
+// bincode's default encoding: little-endian fixed-width integers, u64
+// lengths before strings and sequences, a u8 tag before options
+class BincodeReader {
+  private view: DataView;
+  private offset = 0;
+
+  constructor(private bytes: Uint8Array) {
+    this.view = new DataView(bytes.buffer, bytes.byteOffset, bytes.byteLength);
+  }
+
+  private take(size: number): number {
+    if (this.offset + size > this.bytes.length) {
+      throw new WireError(`Malformed frame payload: ended at byte ${this.bytes.length}`);
+    }
+    const at = this.offset;
+    this.offset += size;
+    return at;
+  }
+
+  u32(): number {
+    return this.view.getUint32(this.take(4), true);
+  }
+
+  i32(): number {
+    return this.view.getInt32(this.take(4), true);
+  }
+
+  // Exact up to 2^53, which covers byte counts and nanosecond timestamps
+  u64(): number {
+    return Number(this.view.getBigUint64(this.take(8), true));
+  }
+
+  f32(): number {
+    return this.view.getFloat32(this.take(4), true);
+  }
+
+  string(): string {
+    const length = this.u64();
+    const at = this.take(length);
+    return new TextDecoder().decode(this.bytes.subarray(at, at + length));
+  }
+
+  option<T>(read: () => T): T | undefined {
+    const tag = this.view.getUint8(this.take(1));
+    if (tag > 1) {
+      throw new WireError(`Malformed frame payload: option tag ${tag}`);
+    }
+    return tag === 1 ? read() : undefined;
+  }
+
+  vec<T>(read: (reader: BincodeReader) => T): T[] {
+    const length = this.u64();
+    const items: T[] = [];
+    for (let i = 0; i < length; i++) {
+      items.push(read(this));
+    }
+    return items;
+  }
+
+  finish(): void {
+    if (this.offset !== this.bytes.length) {
+      throw new WireError(`Malformed frame payload: ${this.bytes.length - this.offset} trailing bytes`);
+    }
+  }
+}
+
+const readCpu = (r: BincodeReader): HighPerfCpuMetrics => ({
+  global_usage: r.f32(),
+  per_core_usage: r.vec((r) => r.f32()),
+  frequency_mhz: r.vec((r) => r.u64()),
+  temperature: r.option(() => r.f32()),
+  load_average: [r.f32(), r.f32(), r.f32()],
+  context_switches: r.u64(),
+  interrupts: r.u64(),
+  cache_misses: r.u64(),
+  cache_hits: r.u64(),
+});
+
+const readMemory = (r: BincodeReader): HighPerfMemoryMetrics => ({
+  total_bytes: r.u64(),
+  used_bytes: r.u64(),
+  available_bytes: r.u64(),
+  cached_bytes: r.u64(),
+  buffer_bytes: r.u64(),
+  swap_total_bytes: r.u64(),
+  swap_used_bytes: r.u64(),
+  page_faults: r.u64(),
+  page_ins: r.u64(),
+  page_outs: r.u64(),
+});
+
+const readGpu = (r: BincodeReader): HighPerfGpuMetrics => ({
+  name: r.string(),
+  usage_percent: r.f32(),
+  memory_used_bytes: r.u64(),
+  memory_total_bytes: r.u64(),
+  temperature_celsius: r.f32(),
+  power_watts: r.f32(),
+  fan_speed_percent: r.option(() => r.f32()),
+  clock_mhz: r.u32(),
+  memory_clock_mhz: r.u32(),
+});
+
+const readDisk = (r: BincodeReader): HighPerfDiskMetrics => ({
+  device_name: r.string(),
+  mount_point: r.string(),
+  total_bytes: r.u64(),
+  used_bytes: r.u64(),
+  read_bytes_per_sec: r.u64(),
+  write_bytes_per_sec: r.u64(),
+  io_operations_per_sec: r.u64(),
+  read_latency_ms: r.f32(),
+  write_latency_ms: r.f32(),
+});
+
+const readNetwork = (r: BincodeReader): HighPerfNetworkMetrics => ({
+  interface_name: r.string(),
+  bytes_sent_per_sec: r.u64(),
+  bytes_received_per_sec: r.u64(),
+  packets_sent_per_sec: r.u64(),
+  packets_received_per_sec: r.u64(),
+  errors_per_sec: r.u64(),
+  latency_ms: r.f32(),
+});
+
+const readProcess = (r: BincodeReader): HighPerfProcessMetrics => ({
+  pid: r.u32(),
+  name: r.string(),
+  cpu_usage_percent: r.f32(),
+  memory_bytes: r.u64(),
+  disk_read_bytes_per_sec: r.u64(),
+  disk_write_bytes_per_sec: r.u64(),
+  network_bytes_per_sec: r.u64(),
+  threads: r.u32(),
+  priority: r.i32(),
+});
+
+const readDpu = (r: BincodeReader): DpuMetrics => ({
+  name: r.string(),
+  vendor: r.string(),
+  model: r.string(),
+  usage_percent: r.f32(),
+  memory_used_bytes: r.u64(),
+  memory_total_bytes: r.u64(),
+  temperature_celsius: r.f32(),
+  power_watts: r.f32(),
+  clock_mhz: r.f32(),
+  throughput_gbps: r.f32(),
+  packet_processing_rate: r.u64(),
+  active_flows: r.u64(),
+  driver_version: r.string(),
+});
+
+const readNpu = (r: BincodeReader): NpuMetrics => ({
+  name: r.string(),
+  vendor: r.string(),
+  model: r.string(),
+  usage_percent: r.f32(),
+  memory_used_bytes: r.u64(),
+  memory_total_bytes: r.u64(),
+  temperature_celsius: r.f32(),
+  power_watts: r.f32(),
+  clock_mhz: r.f32(),
+  inference_rate: r.u64(),
+  model_accuracy: r.f32(),
+  active_models: r.u32(),
+  driver_version: r.string(),
+});
+
+const readExternalDdr = (r: BincodeReader): ExternalDdrMetrics => ({
+  name: r.string(),
+  vendor: r.string(),
+  capacity_bytes: r.u64(),
+  used_bytes: r.u64(),
+  bandwidth_gbps: r.f32(),
+  latency_ns: r.f32(),
+  temperature_celsius: r.f32(),
+  power_watts: r.f32(),
+  error_rate: r.f32(),
+  refresh_rate_hz: r.u32(),
+});
+
+const readFpga = (r: BincodeReader): FpgaMetrics => ({
+  name: r.string(),
+  vendor: r.string(),
+  model: r.string(),
+  usage_percent: r.f32(),
+  temperature_celsius: r.f32(),
+  power_watts: r.f32(),
+  clock_mhz: r.f32(),
+  logic_utilization: r.f32(),
+  memory_utilization: r.f32(),
+  dsp_utilization: r.f32(),
+  bitstream_version: r.string(),
+});
+
+const readAsic = (r: BincodeReader): AsicMetrics => ({
+  name: r.string(),
+  vendor: r.string(),
+  model: r.string(),
+  usage_percent: r.f32(),
+  temperature_celsius: r.f32(),
+  power_watts: r.f32(),
+  clock_mhz: r.f32(),
+  throughput_gbps: r.f32(),
+  packet_processing_rate: r.u64(),
+  active_channels: r.u32(),
+});
+
+const readQuantumProcessor = (r: BincodeReader): QuantumProcessorMetrics => ({
+  name: r.string(),
+  vendor: r.string(),
+  qubits: r.u32(),
+  coherence_time_ms: r.f32(),
+  gate_fidelity: r.f32(),
+  temperature_mk: r.f32(),
+  power_watts: r.f32(),
+  active_qubits: r.u32(),
+  error_rate: r.f32(),
+});
+
+export function decodeHighPerf(frame: Uint8Array): HighPerfMetrics {
+  if (frame.length < HEADER_LEN) {
+    throw new WireError(`Frame too short: ${frame.length} bytes`);
+  }
+
+  const magic = String.fromCharCode(...frame.subarray(0, 4));
+  if (magic !== HIGH_PERF_MAGIC) {
+    throw new WireError(`Not a high-performance metrics frame (magic ${JSON.stringify(magic)})`);
+  }
+  const version = frame[4] | (frame[5] << 8);
+  if (version !== HIGH_PERF_VERSION) {
+    throw new WireError(`Unsupported frame version: expected ${HIGH_PERF_VERSION}, found ${version}`);
+  }
+
+  const r = new BincodeReader(frame.subarray(HEADER_LEN));
+  const metrics: HighPerfMetrics = {
+    timestamp_nanos: r.u64(),
+    cpu: readCpu(r),
+    memory: readMemory(r),
+    gpus: r.vec(readGpu),
+    disks: r.vec(readDisk),
+    networks: r.vec(readNetwork),
+    processes: r.vec(readProcess),
+    dpus: r.vec(readDpu),
+    npus: r.vec(readNpu),
+    external_ddr: r.vec(readExternalDdr),
+    fpgas: r.vec(readFpga),
+    asics: r.vec(readAsic),
+    quantum_processors: r.vec(readQuantumProcessor),
+  };
+  r.finish();
+  return metrics;
+}
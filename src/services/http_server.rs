@@ -0,0 +1,157 @@
+use hyper::header::CONTENT_TYPE;
+use hyper::server::conn::Http;
+use hyper::service::service_fn;
+use hyper::{Body, Method, Request, Response, StatusCode};
+use parking_lot::RwLock;
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::net::{TcpListener, ToSocketAddrs};
+use tokio::sync::broadcast;
+
+use crate::core::monitor::MonitorManager;
+use crate::core::{RedactionConfig, Result, SystemMetrics};
+use crate::services::redaction::Redactor;
+
+/// Content type of the Prometheus text exposition format
+const PROMETHEUS_CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// Serves the latest broadcast sample over HTTP:
+///
+/// - `/metrics`: the Prometheus text format
+/// - `/metrics.json`: the `SystemMetrics::to_json` format
+/// - `/health`: `MonitorHealth` per monitor, as a JSON object
+///
+/// Requests are answered from the last sample the collection loop
+/// broadcast, so a scrape never triggers or waits on a collection. Until the
+/// first sample arrives the metrics endpoints answer 503.
+pub struct HttpServer {
+    listener: TcpListener,
+    metrics: broadcast::Receiver<SystemMetrics>,
+    manager: MonitorManager,
+    redactor: Redactor,
+}
+
+struct ServerState {
+    latest: RwLock<Option<SystemMetrics>>,
+    manager: MonitorManager,
+}
+
+impl HttpServer {
+    /// Binds `addr` and starts listening for samples; requests are served
+    /// once `run()` is called
+    pub async fn bind(
+        addr: impl ToSocketAddrs,
+        metrics: &broadcast::Sender<SystemMetrics>,
+        manager: MonitorManager,
+    ) -> Result<Self> {
+        Ok(Self {
+            listener: TcpListener::bind(addr).await?,
+            metrics: metrics.subscribe(),
+            manager,
+            redactor: Redactor::new(&RedactionConfig::default())?,
+        })
+    }
+
+    /// Uses `redactor` instead of the default rules for process data served
+    /// from `/metrics.json`
+    pub fn with_redactor(mut self, redactor: Redactor) -> Self {
+        self.redactor = redactor;
+        self
+    }
+
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        Ok(self.listener.local_addr()?)
+    }
+
+    /// Serves requests until the task running it is dropped
+    pub async fn run(self) {
+        let state = Arc::new(ServerState {
+            latest: RwLock::new(None),
+            manager: self.manager,
+        });
+
+        tokio::join!(
+            track_latest(self.metrics, self.redactor, state.clone()),
+            accept(self.listener, state),
+        );
+    }
+}
+
+/// Keeps `state.latest` at the most recent broadcast sample
+async fn track_latest(mut receiver: broadcast::Receiver<SystemMetrics>, redactor: Redactor, state: Arc<ServerState>) {
+    loop {
+        match receiver.recv().await {
+            Ok(mut metrics) => {
+                redactor.redact_metrics(&mut metrics);
+                *state.latest.write() = Some(metrics);
+            }
+            // Only the newest sample is served, so skipped ones don't matter
+            Err(broadcast::error::RecvError::Lagged(_)) => {}
+            Err(broadcast::error::RecvError::Closed) => break,
+        }
+    }
+}
+
+async fn accept(listener: TcpListener, state: Arc<ServerState>) {
+    loop {
+        let (stream, peer) = match listener.accept().await {
+            Ok(accepted) => accepted,
+            Err(e) => {
+                tracing::warn!("Failed to accept HTTP client: {}", e);
+                continue;
+            }
+        };
+
+        let state = state.clone();
+        tokio::spawn(async move {
+            let service = service_fn(move |request| {
+                let state = state.clone();
+                async move { Ok::<_, Infallible>(respond(request, &state).await) }
+            });
+            if let Err(e) = Http::new().serve_connection(stream, service).await {
+                tracing::debug!("HTTP client {} disconnected: {}", peer, e);
+            }
+        });
+    }
+}
+
+async fn respond(request: Request<Body>, state: &ServerState) -> Response<Body> {
+    if request.method() != Method::GET {
+        return plain(StatusCode::METHOD_NOT_ALLOWED, "method not allowed\n");
+    }
+
+    match request.uri().path() {
+        "/metrics" => match state.latest.read().as_ref() {
+            Some(metrics) => body(StatusCode::OK, PROMETHEUS_CONTENT_TYPE, crate::core::export::to_prometheus(metrics)),
+            None => no_sample(),
+        },
+        "/metrics.json" => match state.latest.read().as_ref().map(SystemMetrics::to_json) {
+            Some(Ok(json)) => body(StatusCode::OK, "application/json", json),
+            Some(Err(e)) => plain(StatusCode::INTERNAL_SERVER_ERROR, &format!("{}\n", e)),
+            None => no_sample(),
+        },
+        "/health" => match serde_json::to_string(&state.manager.health_each().await) {
+            Ok(json) => body(StatusCode::OK, "application/json", json),
+            Err(e) => plain(StatusCode::INTERNAL_SERVER_ERROR, &format!("{}\n", e)),
+        },
+        _ => plain(StatusCode::NOT_FOUND, "not found\n"),
+    }
+}
+
+fn no_sample() -> Response<Body> {
+    plain(StatusCode::SERVICE_UNAVAILABLE, "no sample collected yet\n")
+}
+
+fn plain(status: StatusCode, text: &str) -> Response<Body> {
+    body(status, "text/plain; charset=utf-8", text.to_string())
+}
+
+fn body(status: StatusCode, content_type: &str, content: String) -> Response<Body> {
+    let mut response = Response::new(Body::from(content));
+    *response.status_mut() = status;
+    if let Ok(value) = content_type.parse() {
+        response.headers_mut().insert(CONTENT_TYPE, value);
+    }
+    response
+}
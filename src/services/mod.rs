@@ -1,3 +1,34 @@
+pub mod alert_engine;
+pub mod alerts;
+pub mod change_filter;
+#[cfg(feature = "http-server")]
+pub mod http_server;
 pub mod monitoring_service;
+#[cfg(feature = "otlp")]
+pub mod otlp_exporter;
+pub mod policy;
+pub mod process_tree;
+pub mod recorder;
+pub mod redaction;
+pub mod replay;
+pub(crate) mod schedule;
+pub mod smoothing;
+#[cfg(feature = "ws-server")]
+pub mod ws_server;
 
-pub use monitoring_service::MonitoringService;
\ No newline at end of file
+pub use alert_engine::AlertEngine;
+pub use alerts::AlertEvaluator;
+pub use change_filter::ChangeFilter;
+#[cfg(feature = "http-server")]
+pub use http_server::HttpServer;
+pub use monitoring_service::MonitoringService;
+#[cfg(feature = "otlp")]
+pub use otlp_exporter::OtlpExporter;
+pub use policy::GuardPolicy;
+pub use process_tree::build_process_tree;
+pub use recorder::{Compression, MetricsRecorder};
+pub use redaction::Redactor;
+pub use smoothing::MetricsSmoother;
+pub use replay::ReplayService;
+#[cfg(feature = "ws-server")]
+pub use ws_server::WsServer;
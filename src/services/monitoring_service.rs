@@ -1,41 +1,188 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant, SystemTime};
 use tokio::sync::{broadcast, RwLock};
-use tokio::time::interval;
 
 use crate::backend::{
-    CpuMonitor, MemoryMonitor, GpuMonitor, StorageMonitor, NetworkMonitor, ProcessMonitor
+    CpuMonitor, MemoryMonitor, GpuMonitor, StorageMonitor, NetworkMonitor, ProcessMonitor,
+    BatteryMonitor, SensorsMonitor,
 };
 use crate::core::{
-    MonitorConfig, MonitoringInterval, Result, SystemMetrics, SystemInfo,
-    CpuMetrics, MemoryMetrics, GpuMetrics, DiskMetrics, NetworkMetrics, ProcessMetrics,
-    Metric, MetricType, MetricValue,
+    Aggregate, HealthScoreWeights, LagStats, MonitorConfig, MonitorHealth, MonitorState, MonitoringInterval, Result, ScheduleMode, SharedClock, SystemClock, SmoothingConfig, SystemMetrics, SystemMetricsDelta, SystemInfo,
+    CpuCoreTimes, CpuMetrics, MemoryMetrics, GpuMetrics, GpuProcessInfo, DiskMetrics, disk_id, PoolMetrics, NetworkMetrics, GroupedProcess, ProcessMetrics, ProcessNode, ProcessQuery,
+    BatteryMetrics, BatteryState, SensorReading, SensorType, SmartHealth, TcpStats, ThermalStatus, WirelessInfo, Metric, MetricType, MetricValue, MetricCategorySet, AlertEvent,
 };
+use crate::backend::process_monitor::parse_cpu_list;
 use crate::core::monitor::MonitorManager;
+use crate::services::alerts::AlertEvaluator;
+use crate::services::change_filter::deadband_exceeded;
+use crate::services::policy::GuardPolicy;
+use crate::services::recorder::MetricsRecorder;
+use crate::services::redaction::Redactor;
+use crate::services::schedule::CollectionSchedule;
+use crate::services::smoothing::MetricsSmoother;
+
+/// How often the collection loop checks back when no monitor is registered
+const IDLE_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+type MetricsCallback = Arc<RwLock<Option<Box<dyn Fn(SystemMetrics) + Send + Sync>>>>;
+type FilteredSenders = Arc<parking_lot::Mutex<Vec<(MetricCategorySet, broadcast::Sender<SystemMetrics>)>>>;
+
+#[derive(Clone)]
+struct Alerts {
+    evaluator: Arc<RwLock<AlertEvaluator>>,
+    sender: broadcast::Sender<AlertEvent>,
+}
+
+/// Capacity of the metric channels and the samples their subscribers lost
+struct ChannelLag {
+    capacity: usize,
+    dropped: AtomicU64,
+    max_lag: AtomicU64,
+}
+
+impl ChannelLag {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            dropped: AtomicU64::new(0),
+            max_lag: AtomicU64::new(0),
+        }
+    }
+
+    fn channel<T: Clone>(&self) -> broadcast::Sender<T> {
+        broadcast::channel(self.capacity).0
+    }
+
+    /// Sends `value`, counting the oldest sample as dropped when the slowest
+    /// subscriber hasn't received it yet and the channel is full
+    fn send<T: Clone>(&self, sender: &broadcast::Sender<T>, value: T) -> bool {
+        // tokio rounds the capacity up to a power of two
+        if sender.receiver_count() > 0 && sender.len() >= self.capacity.next_power_of_two() {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+        let sent = sender.send(value).is_ok();
+        self.max_lag.fetch_max(sender.len() as u64, Ordering::Relaxed);
+        sent
+    }
+
+    fn stats(&self) -> LagStats {
+        LagStats {
+            total_dropped: self.dropped.load(Ordering::Relaxed),
+            max_lag_observed: self.max_lag.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Display smoothing of broadcast samples, and the last sample before it
+#[derive(Clone)]
+struct Smoothing {
+    smoother: Arc<parking_lot::Mutex<MetricsSmoother>>,
+    latest_raw: Arc<parking_lot::Mutex<Option<SystemMetrics>>>,
+}
+
+/// The HTTP server `apply_config` started, and the settings it runs with
+#[cfg(feature = "http-server")]
+struct ConfiguredHttpServer {
+    address: String,
+    redaction: crate::core::RedactionConfig,
+    local_addr: std::net::SocketAddr,
+    task: tokio::task::JoinHandle<()>,
+}
 
 pub struct MonitoringService {
     manager: Arc<MonitorManager>,
     metrics_sender: broadcast::Sender<SystemMetrics>,
     monitoring_interval: Arc<RwLock<MonitoringInterval>>,
+    schedule_mode: Arc<RwLock<ScheduleMode>>,
     system_info: Arc<RwLock<Option<SystemInfo>>>,
     is_running: Arc<RwLock<bool>>,
-    metrics_callback: Arc<RwLock<Option<Box<dyn Fn(SystemMetrics) + Send + Sync>>>>,
+    metrics_callback: MetricsCallback,
+    guard_policy: Arc<RwLock<Option<GuardPolicy>>>,
+    filtered_senders: FilteredSenders,
+    alerts: Alerts,
+    smoothing: Smoothing,
+    recorders: Arc<parking_lot::Mutex<Vec<Arc<MetricsRecorder>>>>,
+    lag: Arc<ChannelLag>,
+    clock: SharedClock,
+    health_weights: Arc<parking_lot::RwLock<HealthScoreWeights>>,
+    redactor: Arc<parking_lot::RwLock<Redactor>>,
+    change_filter: Arc<parking_lot::RwLock<crate::core::ChangeFilterConfig>>,
+    #[cfg(feature = "http-server")]
+    configured_http: Arc<tokio::sync::Mutex<Option<ConfiguredHttpServer>>>,
+}
+
+impl Default for MonitoringService {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl MonitoringService {
     pub fn new() -> Self {
-        let (tx, _) = broadcast::channel(1024);
+        Self::with_channel_capacity(crate::core::MonitoringConfig::default_channel_capacity())
+    }
+
+    /// A service whose subscriber channels buffer the configured
+    /// `monitoring.channel_capacity`; call `apply_config()` with the same
+    /// config for the rest of the settings
+    pub fn from_config(config: &crate::core::AppConfig) -> Self {
+        Self::with_channel_capacity(config.monitoring.channel_capacity)
+    }
+
+    /// Buffers `capacity` samples per subscriber channel before slow
+    /// subscribers start losing the oldest; see `lag_stats()`
+    pub fn with_channel_capacity(capacity: usize) -> Self {
+        let lag = Arc::new(ChannelLag::new(capacity));
+        let tx = lag.channel();
+        let (alert_tx, _) = broadcast::channel(256);
+        let evaluator = AlertEvaluator::new(
+            &crate::core::MonitoringConfig::default(),
+            &crate::core::AlertConfig::default(),
+        );
         
         Self {
             manager: Arc::new(MonitorManager::new()),
             metrics_sender: tx,
             monitoring_interval: Arc::new(RwLock::new(MonitoringInterval::default())),
+            schedule_mode: Arc::new(RwLock::new(ScheduleMode::default())),
             system_info: Arc::new(RwLock::new(None)),
             is_running: Arc::new(RwLock::new(false)),
             metrics_callback: Arc::new(RwLock::new(None)),
+            guard_policy: Arc::new(RwLock::new(None)),
+            filtered_senders: Arc::new(parking_lot::Mutex::new(Vec::new())),
+            alerts: Alerts {
+                evaluator: Arc::new(RwLock::new(evaluator)),
+                sender: alert_tx,
+            },
+            smoothing: Smoothing {
+                smoother: Arc::new(parking_lot::Mutex::new(MetricsSmoother::new(SmoothingConfig::default()))),
+                latest_raw: Arc::new(parking_lot::Mutex::new(None)),
+            },
+            recorders: Arc::new(parking_lot::Mutex::new(Vec::new())),
+            lag,
+            clock: SystemClock::shared(),
+            health_weights: Arc::new(parking_lot::RwLock::new(HealthScoreWeights::default())),
+            redactor: Arc::new(parking_lot::RwLock::new(
+                Redactor::new(&crate::core::RedactionConfig::default()).expect("default redaction patterns compile"),
+            )),
+            change_filter: Arc::new(parking_lot::RwLock::new(crate::core::ChangeFilterConfig::default())),
+            #[cfg(feature = "http-server")]
+            configured_http: Arc::new(tokio::sync::Mutex::new(None)),
         }
     }
 
+    /// Timestamps snapshots, and the samples and rates of the monitors
+    /// `initialize()` registers, by `clock`; call before `initialize()`
+    ///
+    /// The collection loop still sleeps in real time, so a `MockClock` is
+    /// meant for driving `collect_once()` in tests.
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
     pub async fn initialize(&self) -> Result<()> {
         // Initialize system info
         let system_info = self.collect_system_info().await?;
@@ -44,38 +191,48 @@ impl MonitoringService {
         // Register all monitors
         self.manager.register_monitor(
             "cpu".to_string(),
-            Box::new(CpuMonitor::new()),
+            Box::new(CpuMonitor::with_clock(self.clock.clone())),
         ).await?;
 
         self.manager.register_monitor(
             "memory".to_string(),
-            Box::new(MemoryMonitor::new()),
+            Box::new(MemoryMonitor::with_clock(self.clock.clone())),
         ).await?;
 
         self.manager.register_monitor(
             "gpu".to_string(),
-            Box::new(GpuMonitor::new()),
+            Box::new(GpuMonitor::with_clock(self.clock.clone())),
         ).await?;
 
         self.manager.register_monitor(
             "storage".to_string(),
-            Box::new(StorageMonitor::new()),
+            Box::new(StorageMonitor::with_clock(self.clock.clone())),
         ).await?;
 
         self.manager.register_monitor(
             "network".to_string(),
-            Box::new(NetworkMonitor::new()),
+            Box::new(NetworkMonitor::with_clock(self.clock.clone())),
         ).await?;
 
         self.manager.register_monitor(
             "process".to_string(),
-            Box::new(ProcessMonitor::new()),
+            Box::new(ProcessMonitor::with_clock(self.clock.clone())),
+        ).await?;
+
+        self.manager.register_monitor(
+            "battery".to_string(),
+            Box::new(BatteryMonitor::with_clock(self.clock.clone())),
+        ).await?;
+
+        self.manager.register_monitor(
+            "sensors".to_string(),
+            Box::new(SensorsMonitor::with_clock(self.clock.clone())),
         ).await?;
 
         // Initialize all monitors with default config
         let config = MonitorConfig::default();
         
-        for monitor_name in ["cpu", "memory", "gpu", "storage", "network", "process"] {
+        for monitor_name in ["cpu", "memory", "gpu", "storage", "network", "process", "battery", "sensors"] {
             if let Some(monitor) = self.manager.get_monitor(monitor_name).await {
                 let mut monitor = monitor.write().await;
                 monitor.initialize(config.clone()).await?;
@@ -94,6 +251,7 @@ impl MonitoringService {
         
         let info = os_info::get();
         let cpu_info = sys.global_cpu_info();
+        let numa_topology = crate::backend::cpu_monitor::read_numa_topology();
         
         Ok(SystemInfo {
             hostname: hostname::get()
@@ -104,11 +262,15 @@ impl MonitoringService {
             os_version: info.version().to_string(),
             kernel_version: System::kernel_version().unwrap_or_default(),
             architecture: std::env::consts::ARCH.to_string(),
-            cpu_brand: cpu_info.brand().to_string(),
+            cpu_brand: sys.cpus().first().map(|cpu| cpu.brand()).unwrap_or(cpu_info.brand()).to_string(),
             cpu_cores: sys.physical_core_count().unwrap_or(0),
             cpu_threads: sys.cpus().len(),
             total_memory: sys.total_memory() * 1024, // Convert KB to bytes
-            boot_time: std::time::SystemTime::now() - Duration::from_secs(System::uptime()),
+            boot_time: self.clock.now() - Duration::from_secs(System::uptime()),
+            transparent_hugepages: crate::backend::memory_monitor::read_transparent_hugepages(),
+            cross_node_irqs: crate::backend::cpu_monitor::read_cross_node_irqs(&numa_topology),
+            numa_topology,
+            environment: crate::backend::environment::detect_environment(),
         })
     }
 
@@ -124,372 +286,116 @@ impl MonitoringService {
         let system_info = self.system_info.clone();
         let is_running = self.is_running.clone();
         let metrics_callback = self.metrics_callback.clone();
+        let guard_policy = self.guard_policy.clone();
+        let filtered_senders = self.filtered_senders.clone();
+        let alerts = self.alerts.clone();
+        let smoothing = self.smoothing.clone();
+        let monitoring_interval = self.monitoring_interval.clone();
+        let schedule_mode = self.schedule_mode.clone();
+        let lag = self.lag.clone();
+        let clock = self.clock.clone();
+        let health_weights = self.health_weights.clone();
         
+        // Each monitor is collected on its own interval; every pass broadcasts
+        // a full snapshot, reusing the latest results of monitors not yet due
         tokio::spawn(async move {
-            let mut interval = interval(Duration::from_millis(500));
+            let mut schedule = CollectionSchedule::default();
             
             loop {
-                interval.tick().await;
-                
                 if !*is_running.read().await {
                     break;
                 }
                 
-                if let Err(e) = Self::collect_and_broadcast(
-                    &manager, 
-                    &sender, 
-                    &system_info,
-                    &metrics_callback,
-                ).await {
-                    tracing::error!("Failed to collect metrics: {}", e);
+                let now = Instant::now();
+                let names = manager.enabled_monitor_names().await;
+                schedule.retain(&names);
+                let due = schedule.due(&names, now);
+                if !due.is_empty() {
+                    let results = manager.collect_named(&due).await;
+                    let intervals = *monitoring_interval.read().await;
+                    schedule.set_mode(*schedule_mode.read().await);
+                    schedule.record(results, &intervals, now);
+                    
+                    if let Err(e) = Self::broadcast(
+                        schedule.latest(),
+                        schedule.errors().clone(),
+                        &sender, 
+                        &system_info,
+                        &metrics_callback,
+                        &guard_policy,
+                        &filtered_senders,
+                        &alerts,
+                        &smoothing,
+                        &lag,
+                        &clock,
+                        &health_weights,
+                    ).await {
+                        tracing::error!("Failed to broadcast metrics: {}", e);
+                    }
                 }
+                
+                let wakeup = schedule.next_wakeup().unwrap_or(now + IDLE_POLL_INTERVAL);
+                tokio::time::sleep_until(wakeup.into()).await;
             }
         });
         
         Ok(())
     }
 
-    async fn collect_and_broadcast(
-        manager: &Arc<MonitorManager>,
+    #[allow(clippy::too_many_arguments)]
+    async fn broadcast(
+        all_metrics: &HashMap<String, Vec<Metric>>,
+        last_errors: HashMap<String, String>,
         sender: &broadcast::Sender<SystemMetrics>,
         system_info: &Arc<RwLock<Option<SystemInfo>>>,
-        metrics_callback: &Arc<RwLock<Option<Box<dyn Fn(SystemMetrics) + Send + Sync>>>>,
+        metrics_callback: &MetricsCallback,
+        guard_policy: &Arc<RwLock<Option<GuardPolicy>>>,
+        filtered_senders: &FilteredSenders,
+        alerts: &Alerts,
+        smoothing: &Smoothing,
+        lag: &ChannelLag,
+        clock: &SharedClock,
+        health_weights: &parking_lot::RwLock<HealthScoreWeights>,
     ) -> Result<()> {
-        let all_metrics = manager.collect_all_metrics().await?;
-        
         if let Some(info) = system_info.read().await.clone() {
-            // Parse collected metrics into structured format
-            let mut cpu_metrics = CpuMetrics::default();
-            let mut memory_metrics = MemoryMetrics::default();
-            let mut gpu_metrics = Vec::new();
-            let mut disk_metrics = Vec::new();
-            let mut network_metrics = Vec::new();
-            let mut process_metrics = Vec::new();
-
-            // Process CPU metrics
-            if let Some(metrics) = all_metrics.get("cpu") {
-                for metric in metrics {
-                    match metric.metric_type {
-                        MetricType::CpuUsage => {
-                            if metric.tags.is_empty() {
-                                if let MetricValue::Float(v) = metric.value {
-                                    cpu_metrics.usage_percent = v as f32;
-                                }
-                            } else if let Some(core_str) = metric.tags.get("core") {
-                                if let Ok(core_idx) = core_str.parse::<usize>() {
-                                    if let MetricValue::Float(v) = metric.value {
-                                        if core_idx >= cpu_metrics.per_core_usage.len() {
-                                            cpu_metrics.per_core_usage.resize(core_idx + 1, 0.0);
-                                        }
-                                        cpu_metrics.per_core_usage[core_idx] = v as f32;
-                                    }
-                                }
-                            }
-                        }
-                        MetricType::CpuFrequency => {
-                            if let MetricValue::Unsigned(v) = metric.value {
-                                cpu_metrics.frequency_mhz = v;
-                            }
-                        }
-                        MetricType::ProcessCount => {
-                            if let Some(t) = metric.tags.get("type") {
-                                if let MetricValue::Integer(v) = metric.value {
-                                    match t.as_str() {
-                                        "total" => cpu_metrics.processes_total = v as usize,
-                                        "running" => cpu_metrics.processes_running = v as usize,
-                                        _ => {}
-                                    }
-                                }
-                            }
-                        }
-                        _ => {}
-                    }
-                }
-            }
-
-            // Process Memory metrics
-            if let Some(metrics) = all_metrics.get("memory") {
-                for metric in metrics {
-                    match metric.metric_type {
-                        MetricType::MemoryUsage => {
-                            if metric.tags.is_empty() {
-                                if let MetricValue::Float(v) = metric.value {
-                                    memory_metrics.usage_percent = v as f32;
-                                }
-                            } else if let Some(t) = metric.tags.get("type") {
-                                if let MetricValue::Unsigned(v) = metric.value {
-                                    match t.as_str() {
-                                        "used" => memory_metrics.used_bytes = v,
-                                        "total" => memory_metrics.total_bytes = v,
-                                        _ => {}
-                                    }
-                                }
-                            }
-                        }
-                        MetricType::MemoryAvailable => {
-                            if let MetricValue::Unsigned(v) = metric.value {
-                                memory_metrics.available_bytes = v;
-                            }
-                        }
-                        MetricType::SwapUsage => {
-                            if let MetricValue::Float(v) = metric.value {
-                                memory_metrics.swap_usage_percent = v as f32;
-                            }
-                        }
-                        _ => {}
-                    }
-                }
-            }
-
-            // Process GPU metrics
-            if let Some(metrics) = all_metrics.get("gpu") {
-                let mut gpu_map = std::collections::HashMap::new();
-                
-                for metric in metrics {
-                    if let Some(gpu_id) = metric.tags.get("gpu") {
-                        let gpu = gpu_map.entry(gpu_id.clone()).or_insert_with(|| {
-                            GpuMetrics {
-                                name: metric.tags.get("name").cloned().unwrap_or_default(),
-                                driver_version: String::new(),
-                                temperature_celsius: 0.0,
-                                usage_percent: 0.0,
-                                memory_total_bytes: 0,
-                                memory_used_bytes: 0,
-                                memory_usage_percent: 0.0,
-                                power_watts: 0.0,
-                                fan_speed_percent: None,
-                                clock_mhz: 0,
-                                memory_clock_mhz: 0,
-                            }
-                        });
-                        
-                        match metric.metric_type {
-                            MetricType::GpuUsage => {
-                                if let MetricValue::Float(v) = metric.value {
-                                    gpu.usage_percent = v as f32;
-                                }
-                            }
-                            MetricType::GpuTemperature => {
-                                if let MetricValue::Float(v) = metric.value {
-                                    gpu.temperature_celsius = v as f32;
-                                }
-                            }
-                            MetricType::GpuMemoryUsage => {
-                                if let MetricValue::Float(v) = metric.value {
-                                    gpu.memory_usage_percent = v as f32;
-                                }
-                            }
-                            MetricType::GpuPower => {
-                                if let MetricValue::Float(v) = metric.value {
-                                    gpu.power_watts = v as f32;
-                                }
-                            }
-                            MetricType::GpuFanSpeed => {
-                                if let MetricValue::Float(v) = metric.value {
-                                    gpu.fan_speed_percent = Some(v as f32);
-                                }
-                            }
-                            _ => {}
-                        }
-                    }
-                }
-                
-                gpu_metrics.extend(gpu_map.into_values());
-            }
-
-            // Process Disk metrics
-            if let Some(metrics) = all_metrics.get("storage") {
-                let mut disk_map = std::collections::HashMap::new();
-                
-                for metric in metrics {
-                    if let Some(mount) = metric.tags.get("mount") {
-                        let disk = disk_map.entry(mount.clone()).or_insert_with(|| {
-                            DiskMetrics {
-                                mount_point: mount.clone(),
-                                device_name: metric.tags.get("device").cloned().unwrap_or_default(),
-                                fs_type: String::new(),
-                                total_bytes: 0,
-                                used_bytes: 0,
-                                available_bytes: 0,
-                                usage_percent: 0.0,
-                                read_bytes_per_sec: 0,
-                                write_bytes_per_sec: 0,
-                                io_operations_per_sec: 0,
-                            }
-                        });
-                        
-                        match metric.metric_type {
-                            MetricType::DiskUsage => {
-                                if let MetricValue::Float(v) = metric.value {
-                                    disk.usage_percent = v as f32;
-                                }
-                            }
-                            MetricType::DiskSpace => {
-                                if let Some(t) = metric.tags.get("type") {
-                                    if let MetricValue::Unsigned(v) = metric.value {
-                                        match t.as_str() {
-                                            "used" => disk.used_bytes = v,
-                                            "available" => disk.available_bytes = v,
-                                            "total" => disk.total_bytes = v,
-                                            _ => {}
-                                        }
-                                    }
-                                }
-                            }
-                            MetricType::DiskIo => {
-                                if let Some(op) = metric.tags.get("operation") {
-                                    if let MetricValue::Unsigned(v) = metric.value {
-                                        match op.as_str() {
-                                            "read" => disk.read_bytes_per_sec = v,
-                                            "write" => disk.write_bytes_per_sec = v,
-                                            _ => {}
-                                        }
-                                    }
-                                }
-                            }
-                            _ => {}
-                        }
-                    }
-                }
-                
-                disk_metrics.extend(disk_map.into_values());
+            let weights = *health_weights.read();
+            let mut metrics = Self::build_system_metrics(all_metrics, last_errors, info, clock.now(), &weights);
+            
+            // Act on processes sustaining pathological usage (opt-in)
+            if let Some(policy) = guard_policy.write().await.as_mut() {
+                policy.enforce(&metrics.top_processes, metrics.timestamp).await;
             }
-
-            // Process Network metrics
-            if let Some(metrics) = all_metrics.get("network") {
-                let mut net_map = std::collections::HashMap::new();
-                
-                for metric in metrics {
-                    if let Some(iface) = metric.tags.get("interface") {
-                        let net = net_map.entry(iface.clone()).or_insert_with(|| {
-                            NetworkMetrics {
-                                interface_name: iface.clone(),
-                                is_up: false,
-                                mac_address: String::from("00:00:00:00:00:00"),
-                                ip_addresses: Vec::new(),
-                                bytes_sent: 0,
-                                bytes_received: 0,
-                                packets_sent: 0,
-                                packets_received: 0,
-                                errors_sent: 0,
-                                errors_received: 0,
-                                speed_mbps: None,
-                                bytes_sent_rate: 0,
-                                bytes_received_rate: 0,
-                            }
-                        });
-                        
-                        match metric.metric_type {
-                            MetricType::NetworkThroughput => {
-                                if let Some(dir) = metric.tags.get("direction") {
-                                    if let MetricValue::Unsigned(v) = metric.value {
-                                        match dir.as_str() {
-                                            "sent" => net.bytes_sent_rate = v,
-                                            "received" => net.bytes_received_rate = v,
-                                            _ => {}
-                                        }
-                                    }
-                                }
-                            }
-                            MetricType::NetworkBytes => {
-                                if let Some(dir) = metric.tags.get("direction") {
-                                    if let MetricValue::Unsigned(v) = metric.value {
-                                        match dir.as_str() {
-                                            "sent" => net.bytes_sent = v,
-                                            "received" => net.bytes_received = v,
-                                            _ => {}
-                                        }
-                                    }
-                                }
-                            }
-                            MetricType::NetworkStatus => {
-                                if let MetricValue::Boolean(v) = metric.value {
-                                    net.is_up = v;
-                                }
-                            }
-                            MetricType::NetworkSpeed => {
-                                if let MetricValue::Unsigned(v) = metric.value {
-                                    net.speed_mbps = Some(v);
-                                }
-                            }
-                            _ => {}
-                        }
-                    }
-                }
-                
-                network_metrics.extend(net_map.into_values());
+            
+            for event in alerts.evaluator.write().await.evaluate(&metrics) {
+                tracing::info!(
+                    "Alert {:?} for {:?} {}: {:.1} (threshold {:.1})",
+                    event.level, event.metric_type, event.source, event.value, event.threshold
+                );
+                let _ = alerts.sender.send(event);
             }
-
-            // Process Process metrics
-            if let Some(metrics) = all_metrics.get("process") {
-                let mut top_processes: Vec<ProcessMetrics> = Vec::new();
-                
-                for metric in metrics {
-                    if let Some(pid_str) = metric.tags.get("pid") {
-                        if let Ok(pid) = pid_str.parse::<u32>() {
-                            if let Some(name) = metric.tags.get("name") {
-                                let mut process = ProcessMetrics {
-                                    pid,
-                                    name: name.clone(),
-                                    cpu_usage_percent: 0.0,
-                                    memory_bytes: 0,
-                                    memory_percent: 0.0,
-                                    disk_read_bytes: 0,
-                                    disk_write_bytes: 0,
-                                    status: String::from("Running"),
-                                    threads: 1,
-                                    start_time: std::time::SystemTime::now(),
-                                };
-                                
-                                match metric.metric_type {
-                                    MetricType::ProcessCpu => {
-                                        if let MetricValue::Float(v) = metric.value {
-                                            process.cpu_usage_percent = v as f32;
-                                        }
-                                    }
-                                    MetricType::ProcessMemory => {
-                                        if let MetricValue::Unsigned(v) = metric.value {
-                                            process.memory_bytes = v;
-                                        }
-                                    }
-                                    _ => {}
-                                }
-                                
-                                if let Some(existing) = top_processes.iter_mut().find(|p| p.pid == pid) {
-                                    if process.cpu_usage_percent > 0.0 {
-                                        existing.cpu_usage_percent = process.cpu_usage_percent;
-                                    }
-                                    if process.memory_bytes > 0 {
-                                        existing.memory_bytes = process.memory_bytes;
-                                    }
-                                } else if process.cpu_usage_percent > 0.0 || process.memory_bytes > 0 {
-                                    top_processes.push(process);
-                                }
-                            }
-                        }
-                    }
-                }
-                
-                // Sort by CPU usage and take top 10
-                top_processes.sort_by(|a, b| b.cpu_usage_percent.partial_cmp(&a.cpu_usage_percent).unwrap());
-                top_processes.truncate(10);
-                process_metrics = top_processes;
+            
+            // Alerts and the guard policy above act on raw values so
+            // smoothing can't mask a spike
+            {
+                let mut smoother = smoothing.smoother.lock();
+                let raw = smoother.is_active().then(|| metrics.clone());
+                smoother.apply(&mut metrics);
+                // Scored from the values subscribers are sent
+                metrics.health_score = metrics.compute_health_score(&weights);
+                *smoothing.latest_raw.lock() = raw;
             }
-
-            // Build SystemMetrics from collected data
-            let metrics = SystemMetrics {
-                timestamp: std::time::SystemTime::now(),
-                system_info: info,
-                cpu: cpu_metrics,
-                memory: memory_metrics,
-                gpus: gpu_metrics,
-                disks: disk_metrics,
-                networks: network_metrics,
-                top_processes: process_metrics,
-            };
             
             // Send metrics to subscribers
-            let _ = sender.send(metrics.clone());
+            lag.send(sender, metrics.clone());
+            
+            // Filtered subscribers only pay for the categories they asked for
+            filtered_senders.lock().retain(|(categories, filtered)| {
+                if filtered.receiver_count() == 0 {
+                    return false;
+                }
+                lag.send(filtered, metrics.filtered(*categories));
+                true
+            });
             
             // Call the callback if set
             if let Some(callback) = metrics_callback.read().await.as_ref() {
@@ -503,68 +409,366 @@ impl MonitoringService {
     pub async fn stop(&self) -> Result<()> {
         *self.is_running.write().await = false;
         self.manager.stop_all().await?;
+        
+        for recorder in self.recorders.lock().iter() {
+            match recorder.flush() {
+                Ok(records) => tracing::info!("Recorded {} samples to {}", records, recorder.path().display()),
+                Err(e) => tracing::error!("Failed to flush recorder {}: {}", recorder.path().display(), e),
+            }
+        }
         Ok(())
     }
 
-    pub fn subscribe(&self) -> broadcast::Receiver<SystemMetrics> {
-        self.metrics_sender.subscribe()
+    /// Starts the service and runs until SIGINT or SIGTERM (Ctrl-C on
+    /// Windows), then stops the monitors and flushes attached recorders
+    pub async fn run_until_shutdown(&self) -> Result<()> {
+        self.run_until(async {
+            if let Err(e) = shutdown_signal().await {
+                tracing::error!("Failed to listen for shutdown signals: {}", e);
+            }
+        })
+        .await
     }
 
-    pub async fn set_monitoring_interval(&self, interval: MonitoringInterval) {
-        *self.monitoring_interval.write().await = interval;
+    /// Like `run_until_shutdown`, but stops once `shutdown` completes
+    pub async fn run_until(&self, shutdown: impl std::future::Future<Output = ()>) -> Result<()> {
+        self.start().await?;
+        shutdown.await;
+        tracing::info!("Shutting down monitoring");
+        self.stop().await
     }
 
-    pub async fn get_system_info(&self) -> Option<SystemInfo> {
-        self.system_info.read().await.clone()
-    }
-    
-    pub async fn apply_config(&self, config: &crate::core::AppConfig) -> Result<()> {
-        // Apply monitoring intervals
-        let monitoring_interval = MonitoringInterval {
-            cpu: Duration::from_millis(config.monitoring.cpu.interval_ms),
-            memory: Duration::from_millis(config.monitoring.memory.interval_ms),
-            gpu: Duration::from_millis(config.monitoring.gpu.interval_ms),
-            disk: Duration::from_millis(config.monitoring.disk.interval_ms),
-            network: Duration::from_millis(config.monitoring.network.interval_ms),
-            process: Duration::from_millis(config.monitoring.process.interval_ms),
-        };
-        
-        self.set_monitoring_interval(monitoring_interval).await;
-        
-        // Apply individual monitor configs
-        let monitors = ["cpu", "memory", "gpu", "storage", "network", "process"];
-        for monitor_name in monitors {
-            if let Some(monitor) = self.manager.get_monitor(monitor_name).await {
-                let mut monitor = monitor.write().await;
-                
-                let monitor_config = match monitor_name {
-                    "cpu" => self.create_monitor_config(&config.monitoring.cpu),
-                    "memory" => self.create_monitor_config(&config.monitoring.memory),
-                    "gpu" => self.create_monitor_config(&config.monitoring.gpu),
-                    "storage" => self.create_monitor_config(&config.monitoring.disk),
-                    "network" => self.create_monitor_config(&config.monitoring.network),
-                    "process" => {
-                        let mut cfg = self.create_monitor_config(&config.monitoring.network);
-                        cfg.top_processes_count = Some(config.monitoring.process.top_processes_count);
-                        cfg
+    /// Writes every broadcast sample to `recorder` on a background task,
+    /// masking process data with the service's redaction rules and skipping
+    /// samples its change filter rejects.
+    ///
+    /// The returned handle reports how many records were written; recorders
+    /// are flushed when the service stops.
+    pub fn attach_recorder(&self, recorder: MetricsRecorder) -> Arc<MetricsRecorder> {
+        let recorder = Arc::new(
+            recorder
+                .with_redactor(self.redactor())
+                .with_change_filter(&self.change_filter.read()),
+        );
+        self.recorders.lock().push(recorder.clone());
+
+        let mut receiver = self.subscribe();
+        let task_recorder = recorder.clone();
+        tokio::spawn(async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(metrics) => {
+                        if let Err(e) = task_recorder.record(&metrics) {
+                            tracing::error!("Failed to record metrics to {}: {}", task_recorder.path().display(), e);
+                        }
                     }
-                    _ => continue,
-                };
-                
-                monitor.initialize(monitor_config).await?;
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        tracing::warn!("Recorder fell behind and skipped {} samples", skipped);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
             }
+        });
+
+        recorder
+    }
+
+    /// Streams metrics to WebSocket clients connecting on `addr`, returning
+    /// the bound address (useful when `addr` asks for port 0)
+    #[cfg(feature = "ws-server")]
+    pub async fn serve_websocket(&self, addr: impl tokio::net::ToSocketAddrs) -> Result<std::net::SocketAddr> {
+        let server = crate::services::WsServer::bind(addr, self.metrics_sender.clone())
+            .await?
+            .with_redactor(self.redactor());
+        let local_addr = server.local_addr()?;
+        tokio::spawn(server.run());
+        tracing::info!("Streaming metrics over WebSocket on {}", local_addr);
+        Ok(local_addr)
+    }
+
+    /// Serves `/metrics`, `/metrics.json` and `/health` on `addr` (typically
+    /// `AppConfig::http_server.address`), returning the bound address
+    #[cfg(feature = "http-server")]
+    pub async fn serve_http(&self, addr: impl tokio::net::ToSocketAddrs) -> Result<std::net::SocketAddr> {
+        Ok(self.spawn_http(addr).await?.0)
+    }
+
+    #[cfg(feature = "http-server")]
+    async fn spawn_http(
+        &self,
+        addr: impl tokio::net::ToSocketAddrs,
+    ) -> Result<(std::net::SocketAddr, tokio::task::JoinHandle<()>)> {
+        let server = crate::services::HttpServer::bind(addr, &self.metrics_sender, (*self.manager).clone())
+            .await?
+            .with_redactor(self.redactor());
+        let local_addr = server.local_addr()?;
+        let task = tokio::spawn(server.run());
+        tracing::info!("Serving metrics over HTTP on {}", local_addr);
+        Ok((local_addr, task))
+    }
+
+    /// Starts, restarts or stops the server `AppConfig::http_server` asks
+    /// for; servers started through `serve_http()` are left alone
+    #[cfg(feature = "http-server")]
+    async fn apply_http_server(&self, config: &crate::core::AppConfig) -> Result<()> {
+        let mut configured = self.configured_http.lock().await;
+        let unchanged = configured.as_ref().is_some_and(|server| {
+            server.address == config.http_server.address && server.redaction == config.redaction
+        });
+        if config.http_server.enabled && unchanged {
+            return Ok(());
+        }
+
+        if let Some(server) = configured.take() {
+            server.task.abort();
+            // Wait for the listener to close so the address can be bound again
+            let _ = server.task.await;
+            tracing::info!("Stopped serving metrics over HTTP on {}", server.local_addr);
+        }
+        if config.http_server.enabled {
+            let (local_addr, task) = self.spawn_http(config.http_server.address.as_str()).await?;
+            *configured = Some(ConfiguredHttpServer {
+                address: config.http_server.address.clone(),
+                redaction: config.redaction.clone(),
+                local_addr,
+                task,
+            });
         }
-        
         Ok(())
     }
-    
-    fn create_monitor_config(&self, settings: &crate::core::MonitorSettings) -> MonitorConfig {
-        MonitorConfig {
-            enabled: settings.enabled,
-            interval_ms: settings.interval_ms,
-            retain_history_seconds: settings.retain_history_seconds,
-            alert_thresholds: {
-                let mut thresholds = std::collections::HashMap::new();
+
+    /// Where the server `AppConfig::http_server` enabled is listening
+    #[cfg(feature = "http-server")]
+    pub async fn http_server_addr(&self) -> Option<std::net::SocketAddr> {
+        self.configured_http.lock().await.as_ref().map(|server| server.local_addr)
+    }
+
+    /// Redaction rules for the recorders and servers started from now on;
+    /// ones already running keep the rules they started with
+    pub fn set_redaction(&self, config: &crate::core::RedactionConfig) -> Result<()> {
+        *self.redactor.write() = Redactor::new(config)?;
+        Ok(())
+    }
+
+    /// The redaction rules exporters started by the service apply
+    pub fn redactor(&self) -> Redactor {
+        self.redactor.read().clone()
+    }
+
+    /// Change filter for the recorders attached from now on; attached ones
+    /// keep the filter they started with
+    pub fn set_change_filter(&self, config: crate::core::ChangeFilterConfig) {
+        *self.change_filter.write() = config;
+    }
+
+    /// Installs the process guard policy; a disabled config removes it
+    pub async fn set_guard_policy(&self, config: crate::core::GuardPolicyConfig) {
+        *self.guard_policy.write().await = if config.enabled {
+            tracing::warn!(
+                "Process guard policy enabled with {} rule(s), allowlist: {:?}",
+                config.rules.len(), config.allowlist
+            );
+            Some(GuardPolicy::new(config))
+        } else {
+            None
+        };
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<SystemMetrics> {
+        self.metrics_sender.subscribe()
+    }
+
+    /// Samples lost by metric subscribers that fell behind, counted across
+    /// `subscribe()`, the filtered and conditional subscriptions, and the
+    /// servers and recorders built on them
+    pub fn lag_stats(&self) -> LagStats {
+        self.lag.stats()
+    }
+
+    /// Subscribes to `Warning`/`Critical`/`Cleared` transitions of thresholded metrics
+    pub fn subscribe_alerts(&self) -> broadcast::Receiver<AlertEvent> {
+        self.alerts.sender.subscribe()
+    }
+
+    /// Subscribes to metrics with every category outside `categories` emptied.
+    ///
+    /// Subscribers asking for the same set share one channel, so the filtered
+    /// copy is built once per set per tick.
+    pub fn subscribe_filtered(&self, categories: MetricCategorySet) -> broadcast::Receiver<SystemMetrics> {
+        if categories.is_all() {
+            return self.subscribe();
+        }
+
+        let mut senders = self.filtered_senders.lock();
+        if let Some((_, sender)) = senders.iter().find(|(set, _)| *set == categories) {
+            return sender.subscribe();
+        }
+
+        let tx = self.lag.channel();
+        let rx = tx.subscribe();
+        senders.push((categories, tx));
+        rx
+    }
+
+    /// Subscribes to the samples for which `predicate(previous, current)`
+    /// holds, where `previous` is the last sample forwarded to this
+    /// subscriber. The first sample is always forwarded.
+    ///
+    /// Comparing against the last forwarded sample rather than the last
+    /// broadcast lets slow drift through once it adds up. Each subscriber runs
+    /// its own forwarding task, so this must be called within a Tokio runtime.
+    pub fn subscribe_when<F>(&self, mut predicate: F) -> broadcast::Receiver<SystemMetrics>
+    where
+        F: FnMut(&SystemMetrics, &SystemMetrics) -> bool + Send + 'static,
+    {
+        let mut source = self.subscribe();
+        let tx = self.lag.channel();
+        let rx = tx.subscribe();
+        let lag = self.lag.clone();
+
+        tokio::spawn(async move {
+            let mut previous: Option<SystemMetrics> = None;
+            loop {
+                let current = match source.recv().await {
+                    Ok(metrics) => metrics,
+                    Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(broadcast::error::RecvError::Closed) => break,
+                };
+                if tx.receiver_count() == 0 {
+                    break;
+                }
+                let forward = match &previous {
+                    None => true,
+                    Some(previous) => predicate(previous, &current),
+                };
+                if forward {
+                    previous = Some(current.clone());
+                    if !lag.send(&tx, current) {
+                        break;
+                    }
+                }
+            }
+        });
+
+        rx
+    }
+
+    /// Subscribes to samples in which any gauge of `metrics` moved by more
+    /// than `threshold` since the last one forwarded, per core, GPU, disk or
+    /// interface; see `SystemMetrics::gauge_values` for the supported types
+    pub fn subscribe_deadband(&self, metrics: &[MetricType], threshold: f64) -> broadcast::Receiver<SystemMetrics> {
+        let metrics = metrics.to_vec();
+        self.subscribe_when(move |previous, current| deadband_exceeded(previous, current, &metrics, threshold))
+    }
+
+    pub async fn set_monitoring_interval(&self, interval: MonitoringInterval) {
+        *self.monitoring_interval.write().await = interval;
+    }
+
+    /// Takes effect from each monitor's next collection
+    pub async fn set_schedule_mode(&self, mode: ScheduleMode) {
+        *self.schedule_mode.write().await = mode;
+    }
+
+    /// Smoothing applied to broadcast samples from the next one on; the
+    /// windows start afresh
+    pub fn set_smoothing(&self, config: SmoothingConfig) {
+        self.smoothing.smoother.lock().set_config(config);
+    }
+
+    /// Weights of the health score of snapshots from the next one on
+    pub fn set_health_weights(&self, weights: HealthScoreWeights) {
+        *self.health_weights.write() = weights;
+    }
+
+    /// The latest broadcast sample as collected, before smoothing; `None`
+    /// while smoothing is off, when broadcasts already carry raw values
+    pub fn raw_metrics(&self) -> Option<SystemMetrics> {
+        self.smoothing.latest_raw.lock().clone()
+    }
+
+    pub async fn get_system_info(&self) -> Option<SystemInfo> {
+        self.system_info.read().await.clone()
+    }
+    
+    pub async fn apply_config(&self, config: &crate::core::AppConfig) -> Result<()> {
+        // Fails before anything else changes on an invalid pattern
+        self.set_redaction(&config.redaction)?;
+        self.set_change_filter(config.storage.change_filter.clone());
+        
+        // Apply monitoring intervals
+        let monitoring_interval = MonitoringInterval {
+            cpu: Duration::from_millis(config.monitoring.cpu.interval_ms),
+            memory: Duration::from_millis(config.monitoring.memory.interval_ms),
+            gpu: Duration::from_millis(config.monitoring.gpu.interval_ms),
+            disk: Duration::from_millis(config.monitoring.disk.interval_ms),
+            network: Duration::from_millis(config.monitoring.network.interval_ms),
+            process: Duration::from_millis(config.monitoring.process.interval_ms),
+        };
+        
+        self.set_monitoring_interval(monitoring_interval).await;
+        self.set_schedule_mode(config.monitoring.schedule).await;
+        
+        if config.monitoring.channel_capacity.max(1) != self.lag.capacity {
+            tracing::warn!(
+                "Channel capacity {} only applies to a service created with it; keeping {}",
+                config.monitoring.channel_capacity, self.lag.capacity
+            );
+        }
+        
+        if *self.smoothing.smoother.lock().config() != config.ui.smoothing {
+            self.set_smoothing(config.ui.smoothing.clone());
+        }
+        
+        self.set_health_weights(config.monitoring.health_score);
+        self.set_guard_policy(config.guard.clone()).await;
+        
+        *self.alerts.evaluator.write().await = AlertEvaluator::new(&config.monitoring, &config.alerts);
+        
+        #[cfg(feature = "http-server")]
+        self.apply_http_server(config).await?;
+        
+        // Apply individual monitor configs
+        let monitors = ["cpu", "memory", "gpu", "storage", "network", "process"];
+        for monitor_name in monitors {
+            if let Some(monitor) = self.manager.get_monitor(monitor_name).await {
+                let monitor_config = match monitor_name {
+                    "cpu" => self.create_monitor_config(&config.monitoring.cpu),
+                    "memory" => self.create_monitor_config(&config.monitoring.memory),
+                    "gpu" => self.create_monitor_config(&config.monitoring.gpu),
+                    "storage" => self.create_monitor_config(&config.monitoring.disk),
+                    "network" => self.create_monitor_config(&config.monitoring.network),
+                    "process" => self.create_process_monitor_config(&config.monitoring.process),
+                    _ => continue,
+                };
+                let enabled = monitor_config.enabled;
+                
+                // Out of collection before `initialize()` can set it running
+                if !enabled {
+                    self.manager.set_monitor_enabled(monitor_name, false).await?;
+                }
+                
+                // Keep the history of monitors that are already collecting
+                let mut monitor = monitor.write().await;
+                match monitor.state() {
+                    MonitorState::Running | MonitorState::Degraded { .. } | MonitorState::Paused => monitor.reconfigure(monitor_config).await?,
+                    _ => monitor.initialize(monitor_config).await?,
+                }
+                drop(monitor);
+                self.manager.set_monitor_enabled(monitor_name, enabled).await?;
+            }
+        }
+        
+        Ok(())
+    }
+    
+    fn create_monitor_config(&self, settings: &crate::core::MonitorSettings) -> MonitorConfig {
+        MonitorConfig {
+            enabled: settings.enabled,
+            interval_ms: settings.interval_ms,
+            retain_history_seconds: settings.retain_history_seconds,
+            alert_thresholds: {
+                let mut thresholds = std::collections::HashMap::new();
                 if let Some(warn) = settings.warning_threshold {
                     thresholds.insert("warning".to_string(), warn as f64);
                 }
@@ -575,7 +779,27 @@ impl MonitoringService {
             },
             max_processes: Some(100),
             top_processes_count: Some(10),
+            process_sort_key: crate::core::ProcessSortKey::Cpu,
+            process_grouping: crate::core::ProcessGrouping::None,
+            include_loopback: false,
+            interface_filter: None,
+            max_history_bytes: settings.max_history_bytes,
+        }
+    }
+    
+    fn create_process_monitor_config(&self, settings: &crate::core::ProcessMonitorSettings) -> MonitorConfig {
+        MonitorConfig {
+            enabled: settings.enabled,
+            interval_ms: settings.interval_ms,
+            retain_history_seconds: settings.retain_history_seconds,
+            alert_thresholds: std::collections::HashMap::new(),
+            max_processes: Some(100),
+            top_processes_count: Some(settings.top_processes_count),
+            process_sort_key: settings.sort_key,
+            process_grouping: settings.grouping,
             include_loopback: false,
+            interface_filter: None,
+            max_history_bytes: settings.max_history_bytes,
         }
     }
     
@@ -597,19 +821,200 @@ impl MonitoringService {
         self.stop().await
     }
     
+    /// Takes a single fully-populated snapshot without starting the collection
+    /// loop; only `initialize()` is needed beforehand
+    pub async fn collect_once(&self) -> Result<SystemMetrics> {
+        let (all_metrics, last_errors) = Self::split_collection(self.manager.collect_each().await);
+        self.parse_metrics(all_metrics, last_errors).await
+    }
+    
     pub async fn get_current_metrics(&self) -> Result<SystemMetrics> {
-        let all_metrics = self.manager.collect_all_metrics().await?;
-        self.parse_metrics(all_metrics).await
+        self.collect_once().await
+    }
+
+    /// Collects a fresh sample and diffs it against `previous`, for turning
+    /// a consumer's last snapshot into rates
+    pub async fn snapshot_diff(&self, previous: &SystemMetrics) -> Result<SystemMetricsDelta> {
+        Ok(self.collect_once().await?.diff(previous))
+    }
+
+    /// Health of every registered monitor, for serving from a `/health`
+    /// handler
+    pub async fn health_report(&self) -> HashMap<String, MonitorHealth> {
+        self.manager.health_each().await
+    }
+
+    /// Metric types each monitor populates on this machine, keyed like
+    /// `health_report()`, so a UI can hide panels that would only show zeros
+    pub async fn capabilities(&self) -> HashMap<String, Vec<MetricType>> {
+        self.manager.supported_metrics_each().await
+    }
+
+    /// Min, max, mean and p95 of `metric_type` as retained by the monitor
+    /// registered as `monitor_name` ("cpu", "memory", ...), over the last
+    /// `duration`
+    pub async fn aggregate(&self, monitor_name: &str, metric_type: MetricType, duration: Duration) -> Option<Aggregate> {
+        let monitor = self.manager.get_monitor(monitor_name).await?;
+        let monitor = monitor.read().await;
+        monitor.aggregate(metric_type, duration.as_secs()).await
+    }
+
+    /// Retained samples of one monitor collected between `start` and `end`,
+    /// e.g. `"gpu"` between 14:00 and 14:05; `None` if no such monitor is
+    /// registered
+    pub async fn history_range(
+        &self,
+        monitor_name: &str,
+        start: SystemTime,
+        end: SystemTime,
+    ) -> Option<Vec<(SystemTime, Vec<Metric>)>> {
+        let monitor = self.manager.get_monitor(monitor_name).await?;
+        let monitor = monitor.read().await;
+        Some(monitor.history_range(start, end).await)
+    }
+
+    /// Collects a fresh snapshot rendered in the Prometheus text format, for
+    /// serving from a `/metrics` handler
+    pub async fn prometheus_snapshot(&self) -> Result<String> {
+        let metrics = self.get_current_metrics().await?;
+        Ok(crate::core::export::to_prometheus(&metrics))
+    }
+
+    /// Stops and removes a monitor, e.g. `"gpu"` on a headless machine, so
+    /// its subsystem drops out of the snapshots for good
+    pub async fn unregister_monitor(&self, name: &str) -> Result<()> {
+        self.manager.unregister_monitor(name).await
+    }
+
+    /// Pauses or resumes a monitor without unregistering it; while disabled
+    /// it isn't collected and its subsystem is missing from the snapshots
+    ///
+    /// `apply_config()` sets this from each monitor's `enabled` setting.
+    pub async fn set_monitor_enabled(&self, name: &str, enabled: bool) -> Result<()> {
+        self.manager.set_monitor_enabled(name, enabled).await
+    }
+
+    /// Every process matching `query`, from the whole process table rather
+    /// than the top processes in `SystemMetrics`
+    ///
+    /// Values are those of the process monitor's latest collection, so CPU
+    /// usage reads 0 until it has collected twice.
+    pub async fn find_processes(&self, query: ProcessQuery) -> Vec<ProcessMetrics> {
+        let Some(monitor) = self.manager.get_monitor("process").await else {
+            return Vec::new();
+        };
+        let monitor = monitor.read().await;
+        monitor.find_processes(&query)
+    }
+
+    /// The whole process table merged as `process_grouping` configures, e.g.
+    /// one entry for all `chrome` processes, ordered by the process sort key
+    ///
+    /// `find_processes()` still lists every PID separately, and values are
+    /// those of the latest collection as there.
+    pub async fn grouped_processes(&self) -> Vec<GroupedProcess> {
+        let Some(monitor) = self.manager.get_monitor("process").await else {
+            return Vec::new();
+        };
+        let monitor = monitor.read().await;
+        monitor.grouped_processes()
+    }
+
+    /// The whole process table nested by parent, so every ancestor of a busy
+    /// process is there and not only the top processes in `SystemMetrics`
+    ///
+    /// Values are those of the process monitor's latest collection, as with
+    /// `find_processes()`.
+    pub async fn process_tree(&self) -> Result<ProcessNode> {
+        if self.manager.get_monitor("process").await.is_none() {
+            return Err(crate::core::MonitorError::NotInitialized);
+        }
+        let processes = self.find_processes(ProcessQuery::default()).await;
+        Ok(crate::services::build_process_tree(&processes))
+    }
+
+    /// Collects a fresh snapshot encoded as an OTLP/JSON metrics export
+    /// request, for pushing to a collector's `/v1/metrics` endpoint
+    pub async fn otlp_snapshot(&self) -> Result<serde_json::Value> {
+        let metrics = self.get_current_metrics().await?;
+        Ok(crate::core::export::to_otlp_json(&metrics))
+    }
+
+    /// Pushes each broadcast sample to the OTLP/HTTP collector at `endpoint`
+    /// (e.g. `http://localhost:4318`), batched every `interval`; failed
+    /// exports are retried in the background
+    #[cfg(feature = "otlp")]
+    pub fn attach_otlp(&self, endpoint: &str, interval: Duration) -> Result<()> {
+        let exporter = crate::services::OtlpExporter::new(endpoint, interval, &self.metrics_sender)?;
+        tracing::info!("Exporting metrics to OTLP collector {} every {:?}", exporter.endpoint(), interval);
+        tokio::spawn(exporter.run());
+        Ok(())
     }
     
-    async fn parse_metrics(&self, all_metrics: std::collections::HashMap<String, Vec<Metric>>) -> Result<SystemMetrics> {
+    /// Separates successful collections from failed ones, logging each failure
+    fn split_collection(
+        results: HashMap<String, Result<Vec<Metric>>>,
+    ) -> (HashMap<String, Vec<Metric>>, HashMap<String, String>) {
+        let mut all_metrics = HashMap::new();
+        let mut errors = HashMap::new();
+
+        for (name, result) in results {
+            match result {
+                Ok(metrics) => {
+                    all_metrics.insert(name, metrics);
+                }
+                Err(e) => {
+                    tracing::warn!("Monitor '{}' failed, reporting it as degraded: {}", name, e);
+                    errors.insert(name, e.to_string());
+                }
+            }
+        }
+
+        (all_metrics, errors)
+    }
+
+    async fn parse_metrics(
+        &self,
+        all_metrics: HashMap<String, Vec<Metric>>,
+        last_errors: HashMap<String, String>,
+    ) -> Result<SystemMetrics> {
+        let system_info = self.get_system_info().await.unwrap_or(SystemInfo {
+            hostname: String::new(),
+            os_name: String::new(),
+            os_version: String::new(),
+            kernel_version: String::new(),
+            architecture: String::new(),
+            cpu_brand: String::new(),
+            cpu_cores: 0,
+            cpu_threads: 0,
+            total_memory: 0,
+            boot_time: self.clock.now(),
+            transparent_hugepages: None,
+            numa_topology: Vec::new(),
+            cross_node_irqs: Vec::new(),
+            environment: crate::core::RuntimeEnvironment::default(),
+        });
+
+        let weights = *self.health_weights.read();
+        Ok(Self::build_system_metrics(&all_metrics, last_errors, system_info, self.clock.now(), &weights))
+    }
+
+    /// Rebuilds structured metrics from each monitor's flat metric list into
+    /// a snapshot taken at `timestamp`, scored with `health_weights`
+    fn build_system_metrics(
+        all_metrics: &HashMap<String, Vec<Metric>>,
+        last_errors: HashMap<String, String>,
+        system_info: SystemInfo,
+        timestamp: SystemTime,
+        health_weights: &HealthScoreWeights,
+    ) -> SystemMetrics {
         // Parse collected metrics into structured format
         let mut cpu_metrics = CpuMetrics::default();
         let mut memory_metrics = MemoryMetrics::default();
-        let gpu_metrics = Vec::new();
-        let disk_metrics = Vec::new();
-        let network_metrics = Vec::new();
-        let process_metrics = Vec::new();
+        let mut gpu_metrics = Vec::new();
+        let mut disk_metrics = Vec::new();
+        let mut network_metrics = Vec::new();
+        let mut process_metrics = Vec::new();
 
         // Process CPU metrics
         if let Some(metrics) = all_metrics.get("cpu") {
@@ -617,12 +1022,12 @@ impl MonitoringService {
                 match metric.metric_type {
                     MetricType::CpuUsage => {
                         if metric.tags.is_empty() {
-                            if let MetricValue::Float(v) = metric.value {
+                            if let Some(v) = metric.value.as_f64() {
                                 cpu_metrics.usage_percent = v as f32;
                             }
                         } else if let Some(core_str) = metric.tags.get("core") {
                             if let Ok(core_idx) = core_str.parse::<usize>() {
-                                if let MetricValue::Float(v) = metric.value {
+                                if let Some(v) = metric.value.as_f64() {
                                     if core_idx >= cpu_metrics.per_core_usage.len() {
                                         cpu_metrics.per_core_usage.resize(core_idx + 1, 0.0);
                                     }
@@ -633,7 +1038,36 @@ impl MonitoringService {
                     }
                     MetricType::CpuFrequency => {
                         if let MetricValue::Unsigned(v) = metric.value {
-                            cpu_metrics.frequency_mhz = v;
+                            match metric.tags.get("core").and_then(|c| c.parse::<usize>().ok()) {
+                                Some(core_idx) => {
+                                    if core_idx >= cpu_metrics.per_core_frequency_mhz.len() {
+                                        cpu_metrics.per_core_frequency_mhz.resize(core_idx + 1, 0);
+                                    }
+                                    cpu_metrics.per_core_frequency_mhz[core_idx] = v;
+                                }
+                                None => cpu_metrics.frequency_mhz = v,
+                            }
+                        }
+                    }
+                    MetricType::CpuTime => {
+                        let core = metric.tags.get("core").and_then(|c| c.parse::<usize>().ok());
+                        if let (Some(core_idx), Some(state), Some(v)) = (core, metric.tags.get("state"), metric.value.as_f64()) {
+                            if core_idx >= cpu_metrics.per_core_times.len() {
+                                cpu_metrics.per_core_times.resize(core_idx + 1, CpuCoreTimes::default());
+                            }
+                            cpu_metrics.per_core_times[core_idx].set_state(state, v as f32);
+                        }
+                    }
+                    MetricType::ContextSwitches | MetricType::Interrupts => {
+                        let (total, rate) = if metric.metric_type == MetricType::ContextSwitches {
+                            (&mut cpu_metrics.context_switches, &mut cpu_metrics.context_switches_per_sec)
+                        } else {
+                            (&mut cpu_metrics.interrupts, &mut cpu_metrics.interrupts_per_sec)
+                        };
+                        match metric.value {
+                            MetricValue::Unsigned(v) => *total = v,
+                            MetricValue::Float(v) => *rate = v,
+                            _ => {}
                         }
                     }
                     MetricType::ProcessCount => {
@@ -647,23 +1081,26 @@ impl MonitoringService {
                             }
                         }
                     }
-                    MetricType::CpuTemperature => {
-                        if let MetricValue::Float(v) = metric.value {
-                            cpu_metrics.temperature_celsius = Some(v as f32);
-                        }
-                    }
-                    MetricType::SystemLoad => {
-                        if let Some(period) = metric.tags.get("period") {
+                        MetricType::CpuTemperature => {
                             if let MetricValue::Float(v) = metric.value {
-                                match period.as_str() {
-                                    "1" => cpu_metrics.load_average[0] = v as f32,
-                                    "5" => cpu_metrics.load_average[1] = v as f32,
-                                    "15" => cpu_metrics.load_average[2] = v as f32,
-                                    _ => {}
+                                cpu_metrics.temperature_celsius = Some(v as f32);
+                            }
+                        }
+                        MetricType::ThermalThrottling => {
+                            cpu_metrics.cpu_thermal = ThermalStatus::from_metric(metric);
+                        }
+                        MetricType::SystemLoad => {
+                            if let Some(period) = metric.tags.get("period") {
+                                if let MetricValue::Float(v) = metric.value {
+                                    match period.as_str() {
+                                        "1" => cpu_metrics.load_average[0] = v as f32,
+                                        "5" => cpu_metrics.load_average[1] = v as f32,
+                                        "15" => cpu_metrics.load_average[2] = v as f32,
+                                        _ => {}
+                                    }
                                 }
                             }
                         }
-                    }
                     _ => {}
                 }
             }
@@ -674,8 +1111,28 @@ impl MonitoringService {
             for metric in metrics {
                 match metric.metric_type {
                     MetricType::MemoryUsage => {
-                        if let MetricValue::Float(v) = metric.value {
-                            memory_metrics.usage_percent = v as f32;
+                        if metric.tags.is_empty() {
+                            if let Some(v) = metric.value.as_f64() {
+                                memory_metrics.usage_percent = v as f32;
+                            }
+                        } else if let Some(t) = metric.tags.get("type") {
+                            if let MetricValue::Unsigned(v) = metric.value {
+                                match t.as_str() {
+                                    "used" => memory_metrics.used_bytes = v,
+                                    "total" => memory_metrics.total_bytes = v,
+                                    _ => {}
+                                }
+                            }
+                        }
+                    }
+                    MetricType::MemoryAvailable => {
+                        if let MetricValue::Unsigned(v) = metric.value {
+                            memory_metrics.available_bytes = v;
+                        }
+                    }
+                    MetricType::SwapUsage => {
+                        if let Some(v) = metric.value.as_f64() {
+                            memory_metrics.swap_usage_percent = v as f32;
                         }
                     }
                     MetricType::Memory => {
@@ -686,14 +1143,21 @@ impl MonitoringService {
                                     "used" => memory_metrics.used_bytes = v,
                                     "available" => memory_metrics.available_bytes = v,
                                     "cached" => memory_metrics.cached_bytes = v,
+                                    "buffers" => memory_metrics.buffer_bytes = v,
+                                    "anon_hugepages" => memory_metrics.anon_hugepages_bytes = v,
                                     _ => {}
                                 }
                             }
-                        }
-                    }
-                    MetricType::SwapUsage => {
-                        if let MetricValue::Float(v) = metric.value {
-                            memory_metrics.swap_usage_percent = v as f32;
+                            if let MetricValue::Float(v) = metric.value {
+                                match t.as_str() {
+                                    "page_fault_rate" => memory_metrics.page_fault_rate = v,
+                                    "major_fault_rate" => memory_metrics.major_fault_rate = v,
+                                    "swap_in_rate" => memory_metrics.swap_in_rate = v,
+                                    "swap_out_rate" => memory_metrics.swap_out_rate = v,
+                                    _ => {}
+                                }
+                            }
+                            Self::apply_fragmentation_metric(&mut memory_metrics, t, &metric.value);
                         }
                     }
                     MetricType::Swap => {
@@ -712,29 +1176,512 @@ impl MonitoringService {
             }
         }
 
-        let system_info = self.get_system_info().await.unwrap_or(SystemInfo {
-            hostname: String::new(),
-            os_name: String::new(),
-            os_version: String::new(),
-            kernel_version: String::new(),
-            architecture: String::new(),
-            cpu_brand: String::new(),
-            cpu_cores: 0,
-            cpu_threads: 0,
-            total_memory: 0,
-            boot_time: std::time::SystemTime::now(),
-        });
+        // Process GPU metrics
+        if let Some(metrics) = all_metrics.get("gpu") {
+            let mut gpu_map = std::collections::HashMap::new();
+            
+            for metric in metrics {
+                if let Some(gpu_id) = metric.tags.get("gpu") {
+                    let gpu = gpu_map.entry(gpu_id.clone()).or_insert_with(|| {
+                        GpuMetrics {
+                            name: metric.tags.get("name").cloned().unwrap_or_default(),
+                            driver_version: String::new(),
+                            temperature_celsius: 0.0,
+                            usage_percent: 0.0,
+                            memory_total_bytes: 0,
+                            memory_used_bytes: 0,
+                            memory_usage_percent: 0.0,
+                            power_watts: 0.0,
+                            fan_speed_percent: None,
+                            clock_mhz: 0,
+                            memory_clock_mhz: 0,
+                            gpu_processes: Vec::new(),
+                            thermal: None,
+                            instance_id: metric.tags.get("instance_id").cloned(),
+                            parent_id: metric.tags.get("parent_id").cloned(),
+                        }
+                    });
+                    
+                    match metric.metric_type {
+                        MetricType::GpuUsage => {
+                            if let Some(v) = metric.value.as_f64() {
+                                gpu.usage_percent = v as f32;
+                            }
+                        }
+                        MetricType::GpuTemperature => {
+                            if let MetricValue::Float(v) = metric.value {
+                                gpu.temperature_celsius = v as f32;
+                            }
+                        }
+                        MetricType::GpuMemoryUsage => {
+                            if let Some(v) = metric.value.as_f64() {
+                                gpu.memory_usage_percent = v as f32;
+                            }
+                        }
+                        MetricType::GpuPower => {
+                            if let MetricValue::Float(v) = metric.value {
+                                gpu.power_watts = v as f32;
+                            }
+                        }
+                        MetricType::GpuFanSpeed => {
+                            if let MetricValue::Float(v) = metric.value {
+                                gpu.fan_speed_percent = Some(v as f32);
+                            }
+                        }
+                        MetricType::ThermalThrottling => {
+                            gpu.thermal = ThermalStatus::from_metric(metric);
+                        }
+                        MetricType::GpuProcessMemory => {
+                            let pid = metric.tags.get("pid").and_then(|p| p.parse().ok());
+                            if let (Some(pid), MetricValue::Unsigned(v)) = (pid, &metric.value) {
+                                gpu.gpu_processes.push(GpuProcessInfo {
+                                    pid,
+                                    name: metric.tags.get("name").cloned().unwrap_or_default(),
+                                    used_memory_bytes: *v,
+                                    sm_utilization: metric.tags.get("sm_utilization").and_then(|s| s.parse().ok()),
+                                });
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            
+            gpu_metrics.extend(gpu_map.into_values());
+        }
 
-        Ok(SystemMetrics {
-            timestamp: std::time::SystemTime::now(),
+        // Process Disk metrics
+        if let Some(metrics) = all_metrics.get("storage") {
+            let mut disk_map = std::collections::HashMap::new();
+            
+            for metric in metrics {
+                if let Some(mount) = metric.tags.get("mount") {
+                    let disk = disk_map.entry(mount.clone()).or_insert_with(|| {
+                        let device_name = metric.tags.get("device").cloned().unwrap_or_default();
+                        DiskMetrics {
+                            id: disk_id(&device_name, mount),
+                            mount_point: mount.clone(),
+                            device_name,
+                            fs_type: String::new(),
+                            total_bytes: 0,
+                            used_bytes: 0,
+                            available_bytes: 0,
+                            usage_percent: 0.0,
+                            read_bytes_per_sec: 0,
+                            write_bytes_per_sec: 0,
+                            io_operations_per_sec: 0,
+                            busy_percent: 0.0,
+                            smart: None,
+                            inodes_total: None,
+                            inodes_used: None,
+                            inode_usage_percent: None,
+                        }
+                    });
+                    
+                    match metric.metric_type {
+                        MetricType::DiskUsage => {
+                            if let Some(v) = metric.value.as_f64() {
+                                disk.usage_percent = v as f32;
+                            }
+                        }
+                        MetricType::DiskSpace => {
+                            if let Some(t) = metric.tags.get("type") {
+                                if let MetricValue::Unsigned(v) = metric.value {
+                                    match t.as_str() {
+                                        "used" => disk.used_bytes = v,
+                                        "available" => disk.available_bytes = v,
+                                        "total" => disk.total_bytes = v,
+                                        _ => {}
+                                    }
+                                }
+                            }
+                        }
+                        MetricType::DiskIo => {
+                            if let Some(op) = metric.tags.get("operation") {
+                                if let MetricValue::Unsigned(v) = metric.value {
+                                    match op.as_str() {
+                                        "read" => disk.read_bytes_per_sec = v,
+                                        "write" => disk.write_bytes_per_sec = v,
+                                        _ => {}
+                                    }
+                                }
+                            }
+                        }
+                        MetricType::DiskBusy => {
+                            if let MetricValue::Float(v) = metric.value {
+                                disk.busy_percent = v as f32;
+                            }
+                        }
+                        MetricType::DiskHealth => {
+                            disk.smart = SmartHealth::from_metric(metric);
+                        }
+                        MetricType::DiskInodes => {
+                            match (metric.tags.get("type").map(String::as_str), &metric.value) {
+                                (Some("usage"), MetricValue::Percentage(v)) => disk.inode_usage_percent = Some(*v as f32),
+                                (Some("used"), MetricValue::Unsigned(v)) => disk.inodes_used = Some(*v),
+                                (Some("total"), MetricValue::Unsigned(v)) => disk.inodes_total = Some(*v),
+                                _ => {}
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            
+            disk_metrics.extend(disk_map.into_values());
+            disk_metrics.sort_by(|a, b| a.mount_point.cmp(&b.mount_point));
+        }
+
+        // Process Network metrics
+        if let Some(metrics) = all_metrics.get("network") {
+            let mut net_map = std::collections::HashMap::new();
+            
+            for metric in metrics {
+                if let Some(iface) = metric.tags.get("interface") {
+                    let net = net_map.entry(iface.clone()).or_insert_with(|| {
+                        NetworkMetrics {
+                            id: iface.clone(),
+                            interface_name: iface.clone(),
+                            is_up: false,
+                            mac_address: String::from("00:00:00:00:00:00"),
+                            ip_addresses: Vec::new(),
+                            bytes_sent: 0,
+                            bytes_received: 0,
+                            packets_sent: 0,
+                            packets_received: 0,
+                            errors_sent: 0,
+                            errors_received: 0,
+                            speed_mbps: None,
+                            bytes_sent_rate: 0,
+                            bytes_received_rate: 0,
+                            wireless: None,
+                        }
+                    });
+                    
+                    match metric.metric_type {
+                        MetricType::NetworkThroughput => {
+                            if let Some(dir) = metric.tags.get("direction") {
+                                if let MetricValue::Unsigned(v) = metric.value {
+                                    match dir.as_str() {
+                                        "sent" => net.bytes_sent_rate = v,
+                                        "received" => net.bytes_received_rate = v,
+                                        _ => {}
+                                    }
+                                }
+                            }
+                        }
+                        MetricType::NetworkBytes => {
+                            if let Some(dir) = metric.tags.get("direction") {
+                                if let MetricValue::Unsigned(v) = metric.value {
+                                    match dir.as_str() {
+                                        "sent" => net.bytes_sent = v,
+                                        "received" => net.bytes_received = v,
+                                        _ => {}
+                                    }
+                                }
+                            }
+                        }
+                        MetricType::NetworkStatus => {
+                            if let MetricValue::Boolean(v) = metric.value {
+                                net.is_up = v;
+                            }
+                        }
+                        MetricType::NetworkSpeed => {
+                            if let MetricValue::Unsigned(v) = metric.value {
+                                net.speed_mbps = Some(v);
+                            }
+                        }
+                        MetricType::WirelessSignal => {
+                            net.wireless = WirelessInfo::from_metric(metric);
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            
+            network_metrics.extend(net_map.into_values());
+            network_metrics.sort_by(|a, b| a.interface_name.cmp(&b.interface_name));
+        }
+
+        // Process Process metrics
+        if let Some(metrics) = all_metrics.get("process") {
+            let mut top_processes: Vec<ProcessMetrics> = Vec::new();
+            let mut ranks: HashMap<u32, usize> = HashMap::new();
+            
+            for metric in metrics {
+                if let Some(pid_str) = metric.tags.get("pid") {
+                    if let Ok(pid) = pid_str.parse::<u32>() {
+                        if let Some(name) = metric.tags.get("name") {
+                            let mut process = ProcessMetrics {
+                                pid,
+                                name: name.clone(),
+                                cpu_usage_percent: 0.0,
+                                memory_bytes: 0,
+                                memory_percent: 0.0,
+                                disk_read_bytes: 0,
+                                disk_write_bytes: 0,
+                                disk_read_bytes_per_sec: 0,
+                                disk_write_bytes_per_sec: 0,
+                                status: String::from("Running"),
+                                threads: 1,
+                                start_time: timestamp,
+                                parent_pid: metric.tags.get("parent_pid").and_then(|p| p.parse().ok()),
+                                open_handles: 0,
+                                handles_available: false,
+                                cpu_time_seconds: 0.0,
+                                elapsed_seconds: 0,
+                                cmdline: metric.tags.get("cmdline").cloned(),
+                                cpu_affinity: metric
+                                    .tags
+                                    .get("cpu_affinity")
+                                    .and_then(|list| parse_cpu_list(list))
+                                    .unwrap_or_default(),
+                            };
+                            
+                            match metric.metric_type {
+                                MetricType::ProcessCpu => {
+                                    if let MetricValue::Float(v) = metric.value {
+                                        process.cpu_usage_percent = v as f32;
+                                    }
+                                }
+                                MetricType::ProcessMemory => {
+                                    if let MetricValue::Unsigned(v) = metric.value {
+                                        process.memory_bytes = v;
+                                    }
+                                }
+                                MetricType::ProcessHandles => {
+                                    if let MetricValue::Unsigned(v) = metric.value {
+                                        process.open_handles = v as u32;
+                                        process.handles_available = true;
+                                    }
+                                }
+                                MetricType::ProcessCpuTime => {
+                                    if let MetricValue::Duration(v) = metric.value {
+                                        process.cpu_time_seconds = v.as_secs_f64();
+                                    }
+                                }
+                                MetricType::ProcessElapsed => {
+                                    if let MetricValue::Duration(v) = metric.value {
+                                        process.elapsed_seconds = v.as_secs();
+                                    }
+                                }
+                                MetricType::ProcessDiskIo => {
+                                    if let MetricValue::Unsigned(v) = metric.value {
+                                        match metric.tags.get("operation").map(String::as_str) {
+                                            Some("read") => process.disk_read_bytes = v,
+                                            Some("write") => process.disk_write_bytes = v,
+                                            _ => {}
+                                        }
+                                    }
+                                }
+                                MetricType::ProcessDiskIoRate => {
+                                    if let MetricValue::Unsigned(v) = metric.value {
+                                        match metric.tags.get("operation").map(String::as_str) {
+                                            Some("read") => process.disk_read_bytes_per_sec = v,
+                                            Some("write") => process.disk_write_bytes_per_sec = v,
+                                            _ => {}
+                                        }
+                                    }
+                                }
+                                _ => {}
+                            }
+                            
+                            if let Some(rank) = metric.tags.get("rank").and_then(|r| r.parse::<usize>().ok()) {
+                                ranks.insert(pid, rank);
+                            }
+                            
+                            if let Some(existing) = top_processes.iter_mut().find(|p| p.pid == pid) {
+                                if process.cpu_usage_percent > 0.0 {
+                                    existing.cpu_usage_percent = process.cpu_usage_percent;
+                                }
+                                if process.memory_bytes > 0 {
+                                    existing.memory_bytes = process.memory_bytes;
+                                }
+                                if process.disk_read_bytes > 0 {
+                                    existing.disk_read_bytes = process.disk_read_bytes;
+                                }
+                                if process.disk_write_bytes > 0 {
+                                    existing.disk_write_bytes = process.disk_write_bytes;
+                                }
+                                if process.disk_read_bytes_per_sec > 0 {
+                                    existing.disk_read_bytes_per_sec = process.disk_read_bytes_per_sec;
+                                }
+                                if process.disk_write_bytes_per_sec > 0 {
+                                    existing.disk_write_bytes_per_sec = process.disk_write_bytes_per_sec;
+                                }
+                                if process.handles_available {
+                                    existing.open_handles = process.open_handles;
+                                    existing.handles_available = true;
+                                }
+                                if process.cpu_time_seconds > 0.0 {
+                                    existing.cpu_time_seconds = process.cpu_time_seconds;
+                                }
+                                if process.elapsed_seconds > 0 {
+                                    existing.elapsed_seconds = process.elapsed_seconds;
+                                }
+                                if process.cmdline.is_some() {
+                                    existing.cmdline = process.cmdline;
+                                }
+                                if !process.cpu_affinity.is_empty() {
+                                    existing.cpu_affinity = process.cpu_affinity;
+                                }
+                            } else if process.cpu_usage_percent > 0.0 || process.memory_bytes > 0 {
+                                top_processes.push(process);
+                            }
+                        }
+                    }
+                }
+            }
+            
+            // The process monitor already ranked and limited these by the
+            // configured sort key and count
+            top_processes.sort_by_key(|p| ranks.get(&p.pid).copied().unwrap_or(usize::MAX));
+            process_metrics = top_processes;
+        }
+
+        let mut metrics = SystemMetrics {
+            timestamp,
             system_info,
             cpu: cpu_metrics,
             memory: memory_metrics,
             gpus: gpu_metrics,
             disks: disk_metrics,
+            pools: all_metrics.get("storage").map(|m| Self::parse_pools(m)).unwrap_or_default(),
             networks: network_metrics,
+            tcp: all_metrics.get("network").and_then(|m| Self::parse_tcp_stats(m)),
             top_processes: process_metrics,
-        })
+            batteries: all_metrics.get("battery").map(|m| Self::parse_batteries(m)).unwrap_or_default(),
+            sensors: all_metrics.get("sensors").map(|m| Self::parse_sensors(m)).unwrap_or_default(),
+            last_errors,
+            health_score: 0.0,
+        };
+        metrics.health_score = metrics.compute_health_score(health_weights);
+        metrics
+    }
+
+    /// The storage monitor sorts pools by name, which the metrics keep
+    fn parse_pools(metrics: &[Metric]) -> Vec<PoolMetrics> {
+        metrics
+            .iter()
+            .filter(|metric| metric.metric_type == MetricType::StoragePool)
+            .filter_map(PoolMetrics::from_metric)
+            .collect()
+    }
+
+    fn parse_sensors(metrics: &[Metric]) -> Vec<SensorReading> {
+        metrics
+            .iter()
+            .filter_map(|metric| {
+                let sensor_type = match metric.metric_type {
+                    MetricType::SensorTemperature => SensorType::Temperature,
+                    MetricType::SensorFanSpeed => SensorType::Fan,
+                    MetricType::SensorVoltage => SensorType::Voltage,
+                    _ => return None,
+                };
+                let MetricValue::Float(value) = metric.value else {
+                    return None;
+                };
+
+                Some(SensorReading {
+                    name: metric.tags.get("sensor")?.clone(),
+                    value: value as f32,
+                    unit: metric.unit.clone(),
+                    sensor_type,
+                })
+            })
+            .collect()
+    }
+
+    /// `None` when the network monitor reported no connection states
+    fn parse_tcp_stats(metrics: &[Metric]) -> Option<TcpStats> {
+        let mut tcp = None;
+        for metric in metrics.iter().filter(|m| m.metric_type == MetricType::TcpConnections) {
+            if let (Some(state), MetricValue::Unsigned(count)) = (metric.tags.get("state"), &metric.value) {
+                tcp.get_or_insert_with(TcpStats::default).set(state, *count);
+            }
+        }
+        tcp
+    }
+
+    /// Groups battery metrics back into one entry per `battery` tag
+    fn parse_batteries(metrics: &[Metric]) -> Vec<BatteryMetrics> {
+        let mut batteries: Vec<BatteryMetrics> = Vec::new();
+
+        for metric in metrics {
+            let Some(name) = metric.tags.get("battery") else {
+                continue;
+            };
+            let index = match batteries.iter().position(|b| &b.name == name) {
+                Some(index) => index,
+                None => {
+                    batteries.push(BatteryMetrics { name: name.clone(), ..Default::default() });
+                    batteries.len() - 1
+                }
+            };
+            let battery = &mut batteries[index];
+
+            match (metric.metric_type, metric.tags.get("type").map(String::as_str), &metric.value) {
+                (MetricType::BatteryCharge, None, MetricValue::Float(v)) => battery.charge_percent = *v as f32,
+                (MetricType::BatteryCharge, Some("time_to_empty"), MetricValue::Unsigned(v)) => {
+                    battery.time_to_empty_seconds = Some(*v)
+                }
+                (MetricType::BatteryCharge, Some("time_to_full"), MetricValue::Unsigned(v)) => {
+                    battery.time_to_full_seconds = Some(*v)
+                }
+                (MetricType::BatteryState, _, MetricValue::String(v)) => {
+                    battery.state = v.parse().unwrap_or(BatteryState::Unknown)
+                }
+                (MetricType::BatteryHealth, None, MetricValue::Float(v)) => battery.health_percent = Some(*v as f32),
+                (MetricType::BatteryHealth, Some("cycle_count"), MetricValue::Unsigned(v)) => {
+                    battery.cycle_count = Some(*v as u32)
+                }
+                _ => {}
+            }
+        }
+
+        batteries
+    }
+
+    fn apply_fragmentation_metric(memory_metrics: &mut MemoryMetrics, kind: &str, value: &MetricValue) {
+        let fragmentation = match kind {
+            "free_blocks_by_order" | "hugepage_unusable_index" | "compact_stall" | "compact_fail"
+            | "compact_success" | "allocstall" | "thp_fault_fallback" => {
+                memory_metrics.fragmentation.get_or_insert_with(Default::default)
+            }
+            _ => return,
+        };
+
+        match (kind, value) {
+            ("free_blocks_by_order", MetricValue::IntegerArray(counts)) => {
+                fragmentation.free_blocks_by_order = counts.iter().map(|c| *c as u64).collect();
+            }
+            ("hugepage_unusable_index", MetricValue::Float(v)) => fragmentation.hugepage_unusable_index = *v as f32,
+            ("compact_stall", MetricValue::Unsigned(v)) => fragmentation.compact_stall = *v,
+            ("compact_fail", MetricValue::Unsigned(v)) => fragmentation.compact_fail = *v,
+            ("compact_success", MetricValue::Unsigned(v)) => fragmentation.compact_success = *v,
+            ("allocstall", MetricValue::Unsigned(v)) => fragmentation.allocstall = *v,
+            ("thp_fault_fallback", MetricValue::Unsigned(v)) => fragmentation.thp_fault_fallback = *v,
+            _ => {}
+        }
+    }
+}
+
+/// Resolves on SIGINT or SIGTERM on Unix and on Ctrl-C elsewhere
+async fn shutdown_signal() -> std::io::Result<()> {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        let mut terminate = signal(SignalKind::terminate())?;
+        tokio::select! {
+            result = tokio::signal::ctrl_c() => result,
+            _ = terminate.recv() => Ok(()),
+        }
+    }
+
+    #[cfg(not(unix))]
+    {
+        tokio::signal::ctrl_c().await
     }
 }
 
@@ -744,13 +1691,18 @@ impl Default for CpuMetrics {
         Self {
             usage_percent: 0.0,
             frequency_mhz: 0,
+            per_core_frequency_mhz: Vec::new(),
             temperature_celsius: None,
             load_average: [0.0; 3],
             per_core_usage: Vec::new(),
+            per_core_times: Vec::new(),
             processes_running: 0,
             processes_total: 0,
             context_switches: 0,
             interrupts: 0,
+            context_switches_per_sec: 0.0,
+            interrupts_per_sec: 0.0,
+            cpu_thermal: None,
         }
     }
 }
@@ -762,10 +1714,17 @@ impl Default for MemoryMetrics {
             used_bytes: 0,
             available_bytes: 0,
             cached_bytes: 0,
+            buffer_bytes: 0,
             swap_total_bytes: 0,
             swap_used_bytes: 0,
             usage_percent: 0.0,
             swap_usage_percent: 0.0,
+            anon_hugepages_bytes: 0,
+            fragmentation: None,
+            page_fault_rate: 0.0,
+            major_fault_rate: 0.0,
+            swap_in_rate: 0.0,
+            swap_out_rate: 0.0,
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,181 @@
+use hyper::client::HttpConnector;
+use hyper::header::CONTENT_TYPE;
+use hyper::{Body, Client, Method, Request, StatusCode, Uri};
+use serde_json::{json, Value};
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tokio::time::{Instant, MissedTickBehavior};
+
+use crate::core::export::to_otlp_json;
+use crate::core::{MonitorError, Result, SystemMetrics};
+
+/// Path collectors serve OTLP/HTTP metrics on
+const METRICS_PATH: &str = "/v1/metrics";
+/// Samples kept while the collector is unreachable; older ones are dropped
+const MAX_PENDING_SAMPLES: usize = 600;
+/// Longest wait between retries of a failed export
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+/// Time allowed for the collector to answer an export
+const EXPORT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Why an export failed, and whether sending it again can help
+enum ExportError {
+    /// Connection failures, timeouts and the statuses OTLP marks retryable
+    Transient(String),
+    /// The collector rejected the data; resending it won't change that
+    Rejected(String),
+}
+
+/// Pushes broadcast `SystemMetrics` to an OTLP/HTTP collector as gauges, in
+/// the `to_otlp_json` encoding.
+///
+/// Samples are batched and sent every `interval`. When an export fails
+/// transiently the batch is kept and retried with exponential backoff,
+/// starting at `interval` and capped at a minute; batches the collector
+/// rejects are dropped. Only plain `http://` endpoints are supported.
+pub struct OtlpExporter {
+    endpoint: Uri,
+    interval: Duration,
+    metrics: broadcast::Receiver<SystemMetrics>,
+    client: Client<HttpConnector>,
+}
+
+impl OtlpExporter {
+    /// Exports to `endpoint`, e.g. `http://localhost:4318`; `/v1/metrics`
+    /// is used when the endpoint has no path
+    pub fn new(endpoint: &str, interval: Duration, metrics: &broadcast::Sender<SystemMetrics>) -> Result<Self> {
+        let invalid = |reason: &str| MonitorError::InvalidConfig(format!("OTLP endpoint '{}' {}", endpoint, reason));
+
+        let uri: Uri = endpoint.parse().map_err(|_| invalid("is not a valid URI"))?;
+        if uri.scheme_str() != Some("http") {
+            return Err(invalid("must use http://"));
+        }
+        let authority = uri.authority().ok_or_else(|| invalid("has no host"))?.clone();
+        let path = match uri.path() {
+            "" | "/" => METRICS_PATH,
+            path => path,
+        };
+        let endpoint = Uri::builder()
+            .scheme("http")
+            .authority(authority)
+            .path_and_query(path)
+            .build()
+            .map_err(|_| invalid("is not a valid URI"))?;
+
+        if interval.is_zero() {
+            return Err(MonitorError::InvalidConfig("OTLP export interval must be positive".to_string()));
+        }
+
+        Ok(Self {
+            endpoint,
+            interval,
+            metrics: metrics.subscribe(),
+            client: Client::new(),
+        })
+    }
+
+    pub fn endpoint(&self) -> &Uri {
+        &self.endpoint
+    }
+
+    /// Exports until the metrics channel closes, then sends what's left once
+    pub async fn run(mut self) {
+        let mut ticker = tokio::time::interval(self.interval);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        let mut pending: Vec<SystemMetrics> = Vec::new();
+        let mut failures = 0u32;
+        let mut retry_at = Instant::now();
+
+        loop {
+            tokio::select! {
+                received = self.metrics.recv() => match received {
+                    Ok(metrics) => {
+                        if pending.len() == MAX_PENDING_SAMPLES {
+                            pending.remove(0);
+                        }
+                        pending.push(metrics);
+                    }
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        tracing::debug!("OTLP exporter skipped {} samples", skipped);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                },
+                _ = ticker.tick() => {
+                    if pending.is_empty() || Instant::now() < retry_at {
+                        continue;
+                    }
+                    match self.export(&pending).await {
+                        Ok(()) => {
+                            pending.clear();
+                            failures = 0;
+                        }
+                        Err(ExportError::Rejected(reason)) => {
+                            tracing::warn!("OTLP collector {} rejected {} samples: {}", self.endpoint, pending.len(), reason);
+                            pending.clear();
+                            failures = 0;
+                        }
+                        Err(ExportError::Transient(reason)) => {
+                            failures += 1;
+                            let backoff = self.backoff(failures);
+                            tracing::warn!(
+                                "OTLP export to {} failed ({}), retrying in {:?}",
+                                self.endpoint, reason, backoff
+                            );
+                            retry_at = Instant::now() + backoff;
+                        }
+                    }
+                }
+            }
+        }
+
+        if !pending.is_empty() {
+            if let Err(ExportError::Transient(reason) | ExportError::Rejected(reason)) = self.export(&pending).await {
+                tracing::warn!("Dropped {} samples for OTLP collector {}: {}", pending.len(), self.endpoint, reason);
+            }
+        }
+    }
+
+    /// Wait after the `failures`th consecutive failure: the interval,
+    /// doubled per failure
+    fn backoff(&self, failures: u32) -> Duration {
+        self.interval
+            .saturating_mul(1 << failures.min(16))
+            .min(MAX_BACKOFF.max(self.interval))
+    }
+
+    async fn export(&self, batch: &[SystemMetrics]) -> std::result::Result<(), ExportError> {
+        let request = Request::builder()
+            .method(Method::POST)
+            .uri(self.endpoint.clone())
+            .header(CONTENT_TYPE, "application/json")
+            .body(Body::from(encode_batch(batch).to_string()))
+            .map_err(|e| ExportError::Rejected(e.to_string()))?;
+
+        let response = tokio::time::timeout(EXPORT_TIMEOUT, self.client.request(request))
+            .await
+            .map_err(|_| ExportError::Transient("timed out".to_string()))?
+            .map_err(|e| ExportError::Transient(e.to_string()))?;
+
+        match response.status() {
+            status if status.is_success() => Ok(()),
+            StatusCode::TOO_MANY_REQUESTS
+            | StatusCode::BAD_GATEWAY
+            | StatusCode::SERVICE_UNAVAILABLE
+            | StatusCode::GATEWAY_TIMEOUT => Err(ExportError::Transient(response.status().to_string())),
+            status => Err(ExportError::Rejected(status.to_string())),
+        }
+    }
+}
+
+/// One export request carrying every sample in `batch`
+fn encode_batch(batch: &[SystemMetrics]) -> Value {
+    let resource_metrics: Vec<Value> = batch
+        .iter()
+        .flat_map(|metrics| match to_otlp_json(metrics)["resourceMetrics"].take() {
+            Value::Array(resource_metrics) => resource_metrics,
+            _ => Vec::new(),
+        })
+        .collect();
+    json!({ "resourceMetrics": resource_metrics })
+}
@@ -0,0 +1,216 @@
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, SystemTime};
+
+use crate::backend::ProcessMonitor;
+use crate::core::{AlertAggregation, GuardPolicyConfig, GuardResource, ProcessMetrics};
+use crate::services::alert_engine::AlertEngine;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GuardActionKind {
+    /// SIGTERM, giving the process the grace period to exit
+    Terminate,
+    /// SIGKILL after the grace period expired
+    Kill,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GuardAction {
+    pub pid: u32,
+    /// Start time of the process, checked again before it is signalled
+    pub start_time: SystemTime,
+    pub name: String,
+    pub kind: GuardActionKind,
+    pub reason: String,
+}
+
+/// Terminates processes that sustain resource use above the configured rules.
+///
+/// The policy does nothing unless `enabled` is set. It never touches
+/// allowlisted processes, PID 0/1 or the monitor itself, and it logs every
+/// action it takes.
+pub struct GuardPolicy {
+    config: GuardPolicyConfig,
+    allowlist: HashSet<String>,
+    engine: AlertEngine,
+    /// Start time of each tracked PID, so a reused PID starts over
+    tracked: HashMap<u32, SystemTime>,
+    /// Name and time SIGTERM was sent, by PID and start time. Kept apart from
+    /// the snapshot so a process that leaves the top-N is still escalated
+    pending_kill: HashMap<(u32, SystemTime), (String, SystemTime)>,
+}
+
+impl GuardPolicy {
+    pub fn new(config: GuardPolicyConfig) -> Self {
+        let retention = config
+            .rules
+            .iter()
+            .map(|rule| rule.sustained_seconds)
+            .max()
+            .unwrap_or(0);
+        let allowlist = config.allowlist.iter().map(|name| name.to_lowercase()).collect();
+
+        Self {
+            config,
+            allowlist,
+            // Keep one extra interval so the oldest sample still covers the window
+            engine: AlertEngine::new(Duration::from_secs(retention + 60)),
+            tracked: HashMap::new(),
+            pending_kill: HashMap::new(),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.config.enabled
+    }
+
+    fn is_protected(&self, process: &ProcessMetrics) -> bool {
+        process.pid <= 1
+            || process.pid == std::process::id()
+            || self.allowlist.contains(&process.name.to_lowercase())
+    }
+
+    fn sample_key(pid: u32, resource: GuardResource) -> String {
+        match resource {
+            GuardResource::Cpu => format!("{}:cpu", pid),
+            GuardResource::Memory => format!("{}:memory", pid),
+        }
+    }
+
+    /// Records a snapshot of processes and returns the actions that are due,
+    /// without executing them
+    pub fn evaluate(&mut self, processes: &[ProcessMetrics], now: SystemTime) -> Vec<GuardAction> {
+        if !self.config.enabled {
+            return Vec::new();
+        }
+
+        // Forget processes that exited or dropped out of the snapshot, and
+        // those whose PID now belongs to a newer process
+        let live: HashSet<(u32, SystemTime)> = processes.iter().map(|p| (p.pid, p.start_time)).collect();
+        let gone: Vec<u32> = self
+            .tracked
+            .iter()
+            .filter(|&(&pid, &start_time)| !live.contains(&(pid, start_time)))
+            .map(|(&pid, _)| pid)
+            .collect();
+        for pid in gone {
+            self.forget(pid);
+        }
+
+        // A pending kill only ends early once its PID is seen on a newer process
+        let current: HashMap<u32, SystemTime> = processes.iter().map(|p| (p.pid, p.start_time)).collect();
+        self.pending_kill
+            .retain(|&(pid, start_time), _| current.get(&pid).is_none_or(|&seen| seen == start_time));
+
+        let grace = Duration::from_secs(self.config.grace_period_seconds);
+        let mut actions = Vec::new();
+        let mut terminated = Vec::new();
+
+        for process in processes {
+            if self.is_protected(process) {
+                continue;
+            }
+            self.tracked.insert(process.pid, process.start_time);
+            self.engine.record(&Self::sample_key(process.pid, GuardResource::Cpu), process.cpu_usage_percent as f64, now);
+            self.engine.record(&Self::sample_key(process.pid, GuardResource::Memory), process.memory_percent as f64, now);
+
+            if self.pending_kill.contains_key(&(process.pid, process.start_time)) {
+                continue;
+            }
+
+            for rule in &self.config.rules {
+                let key = Self::sample_key(process.pid, rule.resource);
+                if !self.engine.covers(&key, rule.sustained_seconds, now) {
+                    continue;
+                }
+
+                let aggregation = AlertAggregation::Minimum { window_seconds: rule.sustained_seconds };
+                if let Some(sustained) = self.engine.aggregate(&key, aggregation, now) {
+                    if sustained > rule.threshold {
+                        actions.push(GuardAction {
+                            pid: process.pid,
+                            start_time: process.start_time,
+                            name: process.name.clone(),
+                            kind: GuardActionKind::Terminate,
+                            reason: format!(
+                                "{:?} above {:.1}% for {}s (minimum {:.1}%)",
+                                rule.resource, rule.threshold, rule.sustained_seconds, sustained
+                            ),
+                        });
+                        terminated.push((process.pid, process.start_time, process.name.clone()));
+                        break;
+                    }
+                }
+            }
+        }
+
+        // Escalate whether or not the process is still in the snapshot;
+        // `terminate` skips it if it has exited meanwhile
+        let due: Vec<(u32, SystemTime)> = self
+            .pending_kill
+            .iter()
+            .filter(|(_, (_, since))| now.duration_since(*since).unwrap_or_default() >= grace)
+            .map(|(&identity, _)| identity)
+            .collect();
+        for (pid, start_time) in due {
+            if let Some((name, _)) = self.pending_kill.remove(&(pid, start_time)) {
+                actions.push(GuardAction {
+                    pid,
+                    start_time,
+                    name,
+                    kind: GuardActionKind::Kill,
+                    reason: format!("still running {}s after SIGTERM", grace.as_secs()),
+                });
+                self.forget(pid);
+            }
+        }
+
+        for (pid, start_time, name) in terminated {
+            self.pending_kill.insert((pid, start_time), (name, now));
+        }
+
+        actions
+    }
+
+    /// Evaluates the snapshot and signals the offending processes. Signalling
+    /// refreshes each process through sysinfo, so it runs on the blocking pool.
+    pub async fn enforce(&mut self, processes: &[ProcessMetrics], now: SystemTime) -> Vec<GuardAction> {
+        let actions = self.evaluate(processes, now);
+        if actions.is_empty() {
+            return actions;
+        }
+
+        let pending = actions.clone();
+        let signalled = tokio::task::spawn_blocking(move || {
+            for action in &pending {
+                let force = action.kind == GuardActionKind::Kill;
+                match ProcessMonitor::terminate(action.pid, action.start_time, force) {
+                    Ok(true) => tracing::warn!(
+                        "Guard policy sent {} to {} (pid {}): {}",
+                        if force { "SIGKILL" } else { "SIGTERM" },
+                        action.name, action.pid, action.reason
+                    ),
+                    Ok(false) => tracing::info!(
+                        "Guard policy skipped {} (pid {}): process already exited",
+                        action.name, action.pid
+                    ),
+                    Err(e) => tracing::error!(
+                        "Guard policy failed to stop {} (pid {}): {}",
+                        action.name, action.pid, e
+                    ),
+                }
+            }
+        })
+        .await;
+        if let Err(e) = signalled {
+            tracing::error!("Guard policy task failed: {}", e);
+        }
+
+        actions
+    }
+
+    fn forget(&mut self, pid: u32) {
+        self.tracked.remove(&pid);
+        self.engine.clear(&Self::sample_key(pid, GuardResource::Cpu));
+        self.engine.clear(&Self::sample_key(pid, GuardResource::Memory));
+    }
+}
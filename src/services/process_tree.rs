@@ -0,0 +1,74 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::core::{ProcessMetrics, ProcessNode};
+
+/// Nests `processes` under their parents
+///
+/// Processes whose parent is unknown or outside the set hang off a synthetic
+/// root. A malformed parent chain that loops back on itself is broken at its
+/// lowest pid, which is then treated as a root, so every process appears
+/// exactly once.
+pub fn build_process_tree(processes: &[ProcessMetrics]) -> ProcessNode {
+    let by_pid: HashMap<u32, &ProcessMetrics> = processes.iter().map(|p| (p.pid, p)).collect();
+
+    let mut children: HashMap<u32, Vec<u32>> = HashMap::new();
+    let mut roots = Vec::new();
+    for process in by_pid.values() {
+        match process.parent_pid {
+            Some(parent) if parent != process.pid && by_pid.contains_key(&parent) => {
+                children.entry(parent).or_default().push(process.pid);
+            }
+            _ => roots.push(process.pid),
+        }
+    }
+    for siblings in children.values_mut() {
+        siblings.sort_unstable();
+    }
+    roots.sort_unstable();
+
+    let mut visited = HashSet::new();
+    let mut nodes: Vec<ProcessNode> = roots
+        .into_iter()
+        .map(|pid| build_node(pid, &by_pid, &children, &mut visited))
+        .collect();
+
+    // Anything left unvisited sits on a cycle with no way up to a root
+    let mut stranded: Vec<u32> = by_pid.keys().copied().filter(|pid| !visited.contains(pid)).collect();
+    stranded.sort_unstable();
+    for pid in stranded {
+        if !visited.contains(&pid) {
+            nodes.push(build_node(pid, &by_pid, &children, &mut visited));
+        }
+    }
+
+    ProcessNode {
+        process: None,
+        subtree_cpu_percent: nodes.iter().map(|n| n.subtree_cpu_percent).sum(),
+        subtree_memory_bytes: nodes.iter().map(|n| n.subtree_memory_bytes).sum(),
+        children: nodes,
+    }
+}
+
+fn build_node(
+    pid: u32,
+    by_pid: &HashMap<u32, &ProcessMetrics>,
+    children: &HashMap<u32, Vec<u32>>,
+    visited: &mut HashSet<u32>,
+) -> ProcessNode {
+    visited.insert(pid);
+    let process = by_pid[&pid].clone();
+
+    let mut nodes = Vec::new();
+    for &child in children.get(&pid).map(Vec::as_slice).unwrap_or_default() {
+        if !visited.contains(&child) {
+            nodes.push(build_node(child, by_pid, children, visited));
+        }
+    }
+
+    ProcessNode {
+        subtree_cpu_percent: process.cpu_usage_percent + nodes.iter().map(|n| n.subtree_cpu_percent).sum::<f32>(),
+        subtree_memory_bytes: process.memory_bytes + nodes.iter().map(|n| n.subtree_memory_bytes).sum::<u64>(),
+        process: Some(process),
+        children: nodes,
+    }
+}
@@ -0,0 +1,224 @@
+use parking_lot::Mutex;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::core::{ChangeFilterConfig, RedactionConfig, Result, SystemMetrics};
+use crate::services::change_filter::ChangeFilter;
+use crate::services::redaction::Redactor;
+
+const DEFAULT_MAX_BYTES: u64 = 64 * 1024 * 1024;
+const DEFAULT_MAX_FILES: usize = 5;
+const DEFAULT_FLUSH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How `MetricsRecorder` stores samples on disk
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Compression {
+    /// Plain JSON Lines
+    #[default]
+    None,
+    /// A zstd stream at the given level (1-22, 3 is zstd's default); name
+    /// the file `.jsonl.zst`. `ReplayService` detects it by its magic bytes.
+    Zstd(i32),
+}
+
+enum RecorderWriter {
+    Plain(BufWriter<File>),
+    // Finishes the frame when dropped, i.e. on rotation and shutdown
+    Zstd(zstd::stream::AutoFinishEncoder<'static, BufWriter<File>>),
+}
+
+impl Write for RecorderWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match self {
+            RecorderWriter::Plain(writer) => writer.write(buf),
+            RecorderWriter::Zstd(writer) => writer.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self {
+            RecorderWriter::Plain(writer) => writer.flush(),
+            RecorderWriter::Zstd(writer) => writer.flush(),
+        }
+    }
+}
+
+struct RecorderFile {
+    writer: RecorderWriter,
+    bytes: u64,
+    last_flush: Instant,
+}
+
+/// Appends `SystemMetrics` samples to a JSON Lines file, one object per line.
+///
+/// When the file would grow past `max_bytes` it is rotated: `metrics.jsonl`
+/// becomes `metrics.jsonl.1`, `.1` becomes `.2` and so on, keeping at most
+/// `max_files` rotated files. Output is flushed every `flush_interval`, on
+/// rotation and on `flush()`, so a crash loses at most that much history;
+/// dropping the recorder writes out the rest.
+///
+/// With `Compression::Zstd` the lines go through a zstd stream and
+/// `max_bytes` applies to the compressed size on disk. With a change filter,
+/// samples whose gauges barely moved are left out.
+pub struct MetricsRecorder {
+    path: PathBuf,
+    compression: Compression,
+    max_bytes: u64,
+    max_files: usize,
+    flush_interval: Duration,
+    redactor: Redactor,
+    change_filter: Mutex<ChangeFilter>,
+    file: Mutex<RecorderFile>,
+    records: AtomicU64,
+}
+
+impl MetricsRecorder {
+    /// Opens `path` for appending, creating it and its parent directory if needed
+    pub fn new(path: impl AsRef<Path>, compression: Compression) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+
+        Ok(Self {
+            file: Mutex::new(Self::open(&path, compression)?),
+            path,
+            compression,
+            max_bytes: DEFAULT_MAX_BYTES,
+            max_files: DEFAULT_MAX_FILES,
+            flush_interval: DEFAULT_FLUSH_INTERVAL,
+            redactor: Redactor::new(&RedactionConfig::default())?,
+            change_filter: Mutex::new(ChangeFilter::new(&ChangeFilterConfig::default())),
+            records: AtomicU64::new(0),
+        })
+    }
+
+    /// Rotates once the file would exceed `max_bytes`
+    pub fn with_max_bytes(mut self, max_bytes: u64) -> Self {
+        self.max_bytes = max_bytes;
+        self
+    }
+
+    /// Number of rotated files (`.1` ..= `.max_files`) kept on disk
+    pub fn with_max_files(mut self, max_files: usize) -> Self {
+        self.max_files = max_files;
+        self
+    }
+
+    /// Longest time a recorded line may sit in the write buffers
+    pub fn with_flush_interval(mut self, flush_interval: Duration) -> Self {
+        self.flush_interval = flush_interval;
+        self
+    }
+
+    pub fn with_redactor(mut self, redactor: Redactor) -> Self {
+        self.redactor = redactor;
+        self
+    }
+
+    /// Skips samples `ChangeFilter::should_store_sample` rejects
+    pub fn with_change_filter(mut self, config: &ChangeFilterConfig) -> Self {
+        self.change_filter = Mutex::new(ChangeFilter::new(config));
+        self
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn open(path: &Path, compression: Compression) -> Result<RecorderFile> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let bytes = file.metadata()?.len();
+        let writer = match compression {
+            Compression::None => RecorderWriter::Plain(BufWriter::new(file)),
+            // Appending to an existing recording starts a new frame; zstd
+            // decoders read concatenated frames as one stream
+            Compression::Zstd(level) => {
+                RecorderWriter::Zstd(zstd::stream::Encoder::new(BufWriter::new(file), level)?.auto_finish())
+            }
+        };
+        Ok(RecorderFile { writer, bytes, last_flush: Instant::now() })
+    }
+
+    fn rotated_path(&self, index: usize) -> PathBuf {
+        let mut name = self.path.clone().into_os_string();
+        name.push(format!(".{}", index));
+        PathBuf::from(name)
+    }
+
+    fn rotate(&self, file: &mut RecorderFile) -> Result<()> {
+        file.writer.flush()?;
+
+        if self.max_files == 0 {
+            fs::remove_file(&self.path)?;
+        } else {
+            let _ = fs::remove_file(self.rotated_path(self.max_files));
+            for index in (1..self.max_files).rev() {
+                let from = self.rotated_path(index);
+                if from.exists() {
+                    fs::rename(&from, self.rotated_path(index + 1))?;
+                }
+            }
+            fs::rename(&self.path, self.rotated_path(1))?;
+        }
+
+        // Dropping the old writer finishes its zstd frame in the rotated file
+        *file = Self::open(&self.path, self.compression)?;
+        Ok(())
+    }
+
+    /// Appends one sample as a JSON line, rotating first if it would not fit;
+    /// does nothing when the change filter rejects it
+    pub fn record(&self, metrics: &SystemMetrics) -> Result<()> {
+        if !self.change_filter.lock().should_store_sample(metrics) {
+            return Ok(());
+        }
+
+        let mut metrics = metrics.clone();
+        self.redactor.redact_metrics(&mut metrics);
+
+        let mut line = metrics.to_json()?.into_bytes();
+        line.push(b'\n');
+
+        let mut file = self.file.lock();
+        // The compressed size of a line is only known once it reaches the
+        // disk, so compressed files rotate once they have reached the limit
+        let full = match self.compression {
+            Compression::None => file.bytes + line.len() as u64 > self.max_bytes,
+            Compression::Zstd(_) => file.bytes >= self.max_bytes,
+        };
+        if file.bytes > 0 && full {
+            self.rotate(&mut file)?;
+        }
+
+        file.writer.write_all(&line)?;
+        // Flushing a zstd stream ends a block, so doing it per line would
+        // cost most of the compression as well as a write per sample
+        if file.last_flush.elapsed() >= self.flush_interval {
+            file.writer.flush()?;
+            file.last_flush = Instant::now();
+        }
+        file.bytes = match &file.writer {
+            RecorderWriter::Plain(_) => file.bytes + line.len() as u64,
+            RecorderWriter::Zstd(writer) => writer.get_ref().get_ref().metadata()?.len(),
+        };
+        self.records.fetch_add(1, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Flushes buffered output and returns the number of records written so far
+    pub fn flush(&self) -> Result<u64> {
+        let mut file = self.file.lock();
+        file.writer.flush()?;
+        file.last_flush = Instant::now();
+        drop(file);
+        Ok(self.records_written())
+    }
+
+    pub fn records_written(&self) -> u64 {
+        self.records.load(Ordering::Relaxed)
+    }
+}
@@ -0,0 +1,90 @@
+use regex::{Captures, Regex};
+use std::borrow::Cow;
+
+use crate::core::{MonitorError, ProcessMetrics, RedactionConfig, Result, SystemMetrics};
+
+/// Masks secrets in process data, and Wi-Fi SSIDs, before they are handed
+/// to an exporter.
+///
+/// Every exporter should route `SystemMetrics` through [`Redactor::redact_metrics`]
+/// so the same rules apply no matter where the data ends up.
+#[derive(Debug, Clone)]
+pub struct Redactor {
+    enabled: bool,
+    mask: String,
+    patterns: Vec<Regex>,
+    ssid: bool,
+}
+
+impl Redactor {
+    /// Compiles the configured patterns, failing on the first invalid regex
+    pub fn new(config: &RedactionConfig) -> Result<Self> {
+        let patterns = config
+            .patterns
+            .iter()
+            .map(|pattern| {
+                Regex::new(pattern).map_err(|e| {
+                    MonitorError::InvalidConfig(format!("Invalid redaction pattern '{}': {}", pattern, e))
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self {
+            enabled: config.enabled,
+            mask: config.mask.clone(),
+            patterns,
+            ssid: config.ssid,
+        })
+    }
+
+    /// A redactor that passes everything through unchanged
+    pub fn disabled() -> Self {
+        Self {
+            enabled: false,
+            mask: String::new(),
+            patterns: Vec::new(),
+            ssid: false,
+        }
+    }
+
+    /// Masks every configured pattern in `value`
+    pub fn redact_str<'a>(&self, value: &'a str) -> Cow<'a, str> {
+        if !self.enabled {
+            return Cow::Borrowed(value);
+        }
+
+        let mut result = Cow::Borrowed(value);
+        for pattern in &self.patterns {
+            if let Cow::Owned(replaced) = pattern.replace_all(&result, |caps: &Captures| {
+                let prefix = caps.get(1).map_or("", |m| m.as_str());
+                let suffix = caps.get(2).map_or("", |m| m.as_str());
+                format!("{}{}{}", prefix, self.mask, suffix)
+            }) {
+                result = Cow::Owned(replaced);
+            }
+        }
+        result
+    }
+
+    pub fn redact_process(&self, process: &mut ProcessMetrics) {
+        if let Cow::Owned(name) = self.redact_str(&process.name) {
+            process.name = name;
+        }
+        if let Some(Cow::Owned(cmdline)) = process.cmdline.as_deref().map(|cmdline| self.redact_str(cmdline)) {
+            process.cmdline = Some(cmdline);
+        }
+    }
+
+    pub fn redact_metrics(&self, metrics: &mut SystemMetrics) {
+        for process in &mut metrics.top_processes {
+            self.redact_process(process);
+        }
+        if self.enabled && self.ssid {
+            for wireless in metrics.networks.iter_mut().filter_map(|network| network.wireless.as_mut()) {
+                if !wireless.ssid.is_empty() {
+                    wireless.ssid = self.mask.clone();
+                }
+            }
+        }
+    }
+}
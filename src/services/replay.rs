@@ -0,0 +1,197 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader, ErrorKind};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tokio::sync::{broadcast, Notify};
+
+use crate::core::{MonitorError, Result, SystemMetrics};
+
+/// First bytes of every zstd frame
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xB5, 0x2F, 0xFD];
+
+/// Plays a recorded JSON Lines file back through the same broadcast API as
+/// `MonitoringService`, so dashboards can be exercised without the hardware
+/// being in the recorded state.
+///
+/// Samples are spaced by their original timestamps divided by `speed`.
+pub struct ReplayService {
+    samples: Arc<Vec<SystemMetrics>>,
+    sender: broadcast::Sender<SystemMetrics>,
+    speed: f64,
+    looping: bool,
+    position: Arc<AtomicUsize>,
+    running: Arc<AtomicBool>,
+    // Bumped on every start so a loop left over from before a stop exits
+    generation: Arc<AtomicUsize>,
+    wake: Arc<Notify>,
+}
+
+impl ReplayService {
+    /// Loads every sample from a file written by `MetricsRecorder`,
+    /// decompressing it if it starts with a zstd frame
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let mut file = BufReader::new(File::open(path.as_ref())?);
+        let compressed = file.fill_buf()?.starts_with(&ZSTD_MAGIC);
+        let reader: Box<dyn BufRead> = if compressed {
+            Box::new(BufReader::new(zstd::stream::Decoder::with_buffer(file)?))
+        } else {
+            Box::new(file)
+        };
+        let mut samples = Vec::new();
+
+        for (number, line) in reader.lines().enumerate() {
+            let line = match line {
+                Ok(line) => line,
+                // A recorder that never shut down leaves its last frame
+                // unfinished; every flushed line before that is still intact
+                Err(e) if compressed && e.kind() == ErrorKind::UnexpectedEof => break,
+                Err(e) => return Err(e.into()),
+            };
+            if line.trim().is_empty() {
+                continue;
+            }
+            let sample = SystemMetrics::from_json(&line).map_err(|e| match e {
+                MonitorError::SchemaMismatch { .. } => e,
+                e => MonitorError::CollectionError(format!(
+                    "Invalid sample on line {} of {}: {}",
+                    number + 1,
+                    path.as_ref().display(),
+                    e
+                )),
+            })?;
+            samples.push(sample);
+        }
+
+        Ok(Self::from_samples(samples))
+    }
+
+    pub fn from_samples(samples: Vec<SystemMetrics>) -> Self {
+        let (sender, _) = broadcast::channel(1024);
+
+        Self {
+            samples: Arc::new(samples),
+            sender,
+            speed: 1.0,
+            looping: false,
+            position: Arc::new(AtomicUsize::new(0)),
+            running: Arc::new(AtomicBool::new(false)),
+            generation: Arc::new(AtomicUsize::new(0)),
+            wake: Arc::new(Notify::new()),
+        }
+    }
+
+    /// Playback rate; 2.0 replays twice as fast as recorded
+    pub fn with_speed(mut self, speed: f64) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    /// Restart from the first sample after the last one
+    pub fn with_loop(mut self, looping: bool) -> Self {
+        self.looping = looping;
+        self
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<SystemMetrics> {
+        self.sender.subscribe()
+    }
+
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    /// Index of the next sample to be sent
+    pub fn position(&self) -> usize {
+        self.position.load(Ordering::SeqCst)
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running.load(Ordering::SeqCst)
+    }
+
+    /// Continues playback from the first sample at or after `timestamp`
+    pub fn seek(&self, timestamp: SystemTime) {
+        let index = self.samples.partition_point(|sample| sample.timestamp < timestamp);
+        self.position.store(index, Ordering::SeqCst);
+        self.wake.notify_one();
+    }
+
+    pub async fn start(&self) -> Result<()> {
+        if !(self.speed.is_finite() && self.speed > 0.0) {
+            return Err(MonitorError::InvalidConfig(format!("Replay speed must be positive, got {}", self.speed)));
+        }
+        if self.running.swap(true, Ordering::SeqCst) {
+            return Ok(());
+        }
+
+        let samples = self.samples.clone();
+        let sender = self.sender.clone();
+        let position = self.position.clone();
+        let running = self.running.clone();
+        let generation = self.generation.clone();
+        let current = generation.fetch_add(1, Ordering::SeqCst) + 1;
+        let wake = self.wake.clone();
+        let speed = self.speed;
+        let looping = self.looping;
+
+        tokio::spawn(async move {
+            // Used between the last and the first sample when looping
+            let wrap_gap = match (samples.first(), samples.get(1)) {
+                (Some(first), Some(second)) => Self::gap(first, second),
+                _ => Duration::from_secs(1),
+            };
+
+            let is_current = || running.load(Ordering::SeqCst) && generation.load(Ordering::SeqCst) == current;
+
+            while is_current() {
+                let index = position.load(Ordering::SeqCst);
+                let Some(sample) = samples.get(index) else {
+                    if looping && !samples.is_empty() {
+                        position.store(0, Ordering::SeqCst);
+                        continue;
+                    }
+                    break;
+                };
+
+                // A seek during the previous wait wins over advancing
+                if position.compare_exchange(index, index + 1, Ordering::SeqCst, Ordering::SeqCst).is_err() {
+                    continue;
+                }
+                let _ = sender.send(sample.clone());
+
+                let gap = match samples.get(index + 1) {
+                    Some(next) => Self::gap(sample, next),
+                    None if looping => wrap_gap,
+                    None => break,
+                };
+
+                tokio::select! {
+                    _ = tokio::time::sleep(gap.div_f64(speed)) => {}
+                    _ = wake.notified() => {}
+                }
+            }
+
+            if generation.load(Ordering::SeqCst) == current {
+                running.store(false, Ordering::SeqCst);
+            }
+        });
+
+        Ok(())
+    }
+
+    pub async fn stop(&self) -> Result<()> {
+        self.running.store(false, Ordering::SeqCst);
+        self.wake.notify_waiters();
+        Ok(())
+    }
+
+    fn gap(from: &SystemMetrics, to: &SystemMetrics) -> Duration {
+        to.timestamp.duration_since(from.timestamp).unwrap_or_default()
+    }
+}
@@ -0,0 +1,151 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::core::{Metric, MonitoringInterval, Result, ScheduleMode};
+
+/// Tracks when each monitor is next due and the latest result from each, so
+/// every broadcast can carry a full snapshot while slow monitors are only
+/// collected as often as their interval allows
+///
+/// A monitor's first collection happens at once in every mode, so a snapshot
+/// is available right away; `ScheduleMode` places the ones after it.
+#[derive(Debug, Default)]
+pub(crate) struct CollectionSchedule {
+    mode: ScheduleMode,
+    next_due: HashMap<String, Instant>,
+    // Monitors whose jitter offset is in `next_due` for the current mode
+    jittered: HashSet<String>,
+    latest: HashMap<String, Vec<Metric>>,
+    errors: HashMap<String, String>,
+}
+
+impl CollectionSchedule {
+    /// Applies from the next `record`; a new jitter picks each monitor's
+    /// offset again at its next collection
+    pub(crate) fn set_mode(&mut self, mode: ScheduleMode) {
+        if mode != self.mode {
+            self.jittered.clear();
+        }
+        self.mode = mode;
+    }
+
+    /// Monitors among `names` whose interval has elapsed; ones never
+    /// collected are always due
+    pub(crate) fn due(&self, names: &[String], now: Instant) -> Vec<String> {
+        names
+            .iter()
+            .filter(|name| self.next_due.get(*name).is_none_or(|due| *due <= now))
+            .cloned()
+            .collect()
+    }
+
+    /// Forgets monitors not among `names`, so ones unregistered or disabled
+    /// drop out of the snapshot and are collected at once if they return
+    pub(crate) fn retain(&mut self, names: &[String]) {
+        self.next_due.retain(|name, _| names.contains(name));
+        self.jittered.retain(|name| names.contains(name));
+        self.latest.retain(|name, _| names.contains(name));
+        self.errors.retain(|name, _| names.contains(name));
+    }
+
+    /// Stores the results of collecting at `now` and schedules each monitor's
+    /// next collection
+    ///
+    /// A failed monitor contributes nothing until it next succeeds, and its
+    /// error stays reported in between.
+    pub(crate) fn record(
+        &mut self,
+        results: HashMap<String, Result<Vec<Metric>>>,
+        intervals: &MonitoringInterval,
+        now: Instant,
+    ) {
+        for (name, result) in results {
+            let next_due = self.next_due_after(&name, intervals.for_monitor(&name), now);
+            self.next_due.insert(name.clone(), next_due);
+            match result {
+                Ok(metrics) => {
+                    self.errors.remove(&name);
+                    self.latest.insert(name, metrics);
+                }
+                Err(e) => {
+                    tracing::warn!("Monitor '{}' failed, reporting it as degraded: {}", name, e);
+                    self.latest.remove(&name);
+                    self.errors.insert(name, e.to_string());
+                }
+            }
+        }
+    }
+
+    fn next_due_after(&mut self, name: &str, interval: Duration, now: Instant) -> Instant {
+        if interval.is_zero() {
+            return now;
+        }
+
+        match self.mode {
+            ScheduleMode::Fixed => now + interval,
+            ScheduleMode::Aligned => now + until_aligned(wall_clock_at(now), interval),
+            ScheduleMode::Jittered(jitter) => match self.next_due.get(name) {
+                // Step from the previous due time so the offset picked at the
+                // first collection isn't eroded by the loop's wakeup latency
+                Some(&previous) if self.jittered.contains(name) && previous + interval > now => previous + interval,
+                Some(_) if self.jittered.contains(name) => now + interval,
+                _ => {
+                    self.jittered.insert(name.to_string());
+                    now + interval + jitter_offset(name, jitter.min(interval))
+                }
+            },
+        }
+    }
+
+    /// When the next monitor falls due, if any has been collected yet
+    pub(crate) fn next_wakeup(&self) -> Option<Instant> {
+        self.next_due.values().min().copied()
+    }
+
+    pub(crate) fn latest(&self) -> &HashMap<String, Vec<Metric>> {
+        &self.latest
+    }
+
+    pub(crate) fn errors(&self) -> &HashMap<String, String> {
+        &self.errors
+    }
+}
+
+/// Time from `since_epoch` until one interval past the wall-clock boundary
+/// nearest to it.
+///
+/// Rounding to the nearest boundary rather than the next one means a
+/// collection that woke a little early or late still schedules the boundary
+/// after the one it was meant for.
+pub(crate) fn until_aligned(since_epoch: Duration, interval: Duration) -> Duration {
+    let interval = interval.as_nanos();
+    if interval == 0 {
+        return Duration::ZERO;
+    }
+
+    let now = since_epoch.as_nanos();
+    let nearest = (now + interval / 2) / interval * interval;
+    Duration::from_nanos((nearest + interval - now) as u64)
+}
+
+/// Wall-clock time, since the Unix epoch, at which `instant` was taken
+fn wall_clock_at(instant: Instant) -> Duration {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .saturating_sub(Instant::now().saturating_duration_since(instant))
+}
+
+/// Stable offset in `[0, max)` for the monitor registered as `name`
+pub(crate) fn jitter_offset(name: &str, max: Duration) -> Duration {
+    let max = max.as_nanos() as u64;
+    if max == 0 {
+        return Duration::ZERO;
+    }
+
+    let mut hasher = DefaultHasher::new();
+    name.hash(&mut hasher);
+    Duration::from_nanos(hasher.finish() % max)
+}
@@ -0,0 +1,131 @@
+use std::collections::{HashMap, VecDeque};
+
+use crate::core::{MetricType, SmoothingConfig, SmoothingMethod, SystemMetrics};
+
+/// Smooths selected gauges of successive `SystemMetrics` snapshots.
+///
+/// Each series (a metric type plus the core, GPU, disk or interface it
+/// belongs to) keeps its own window, so devices appearing or disappearing
+/// don't disturb the others. GPUs are told apart by their position, since
+/// identical cards share a name, and series missing from a sample are
+/// dropped. Supported types are `CpuUsage` (overall and per
+/// core), `CpuTemperature`, `MemoryUsage`, `GpuUsage`, `GpuMemoryUsage`,
+/// `GpuTemperature`, `GpuPower`, `DiskBusy`, `DiskIo` and
+/// `NetworkThroughput`; others in the config are left as they are.
+pub struct MetricsSmoother {
+    config: SmoothingConfig,
+    series: HashMap<(MetricType, String), Series>,
+    /// Samples applied so far
+    pass: u64,
+}
+
+#[derive(Default)]
+struct Series {
+    window: VecDeque<f64>,
+    average: Option<f64>,
+    /// `pass` of the last sample that carried this series
+    seen: u64,
+}
+
+impl Series {
+    fn next(&mut self, method: SmoothingMethod, value: f64) -> f64 {
+        match method {
+            SmoothingMethod::None => value,
+            SmoothingMethod::Sma(size) => {
+                self.window.push_back(value);
+                while self.window.len() > size.max(1) {
+                    self.window.pop_front();
+                }
+                self.window.iter().sum::<f64>() / self.window.len() as f64
+            }
+            SmoothingMethod::Ema(alpha) => {
+                let average = match self.average {
+                    Some(previous) => alpha * value + (1.0 - alpha) * previous,
+                    None => value,
+                };
+                self.average = Some(average);
+                average
+            }
+        }
+    }
+}
+
+impl MetricsSmoother {
+    pub fn new(config: SmoothingConfig) -> Self {
+        Self { config, series: HashMap::new(), pass: 0 }
+    }
+
+    pub fn config(&self) -> &SmoothingConfig {
+        &self.config
+    }
+
+    /// Replaces the config and starts every series afresh
+    pub fn set_config(&mut self, config: SmoothingConfig) {
+        self.config = config;
+        self.series.clear();
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.config.method != SmoothingMethod::None && !self.config.metrics.is_empty()
+    }
+
+    /// Smooths `metrics` in place, feeding this sample into every selected
+    /// series
+    pub fn apply(&mut self, metrics: &mut SystemMetrics) {
+        if !self.is_active() {
+            return;
+        }
+
+        self.pass += 1;
+        let pass = self.pass;
+        let mut smooth = |metric_type: MetricType, source: &str, value: f64| -> f64 {
+            if !self.config.metrics.contains(&metric_type) {
+                return value;
+            }
+            let series = self.series.entry((metric_type, source.to_string())).or_default();
+            series.seen = pass;
+            series.next(self.config.method, value)
+        };
+
+        let cpu = &mut metrics.cpu;
+        cpu.usage_percent = smooth(MetricType::CpuUsage, "", cpu.usage_percent as f64) as f32;
+        for (core, usage) in cpu.per_core_usage.iter_mut().enumerate() {
+            *usage = smooth(MetricType::CpuUsage, &core.to_string(), *usage as f64) as f32;
+        }
+        if let Some(temperature) = cpu.temperature_celsius.as_mut() {
+            *temperature = smooth(MetricType::CpuTemperature, "", *temperature as f64) as f32;
+        }
+
+        let memory = &mut metrics.memory;
+        memory.usage_percent = smooth(MetricType::MemoryUsage, "", memory.usage_percent as f64) as f32;
+
+        for (index, gpu) in metrics.gpus.iter_mut().enumerate() {
+            let index = index.to_string();
+            gpu.usage_percent = smooth(MetricType::GpuUsage, &index, gpu.usage_percent as f64) as f32;
+            gpu.memory_usage_percent =
+                smooth(MetricType::GpuMemoryUsage, &index, gpu.memory_usage_percent as f64) as f32;
+            gpu.temperature_celsius =
+                smooth(MetricType::GpuTemperature, &index, gpu.temperature_celsius as f64) as f32;
+            gpu.power_watts = smooth(MetricType::GpuPower, &index, gpu.power_watts as f64) as f32;
+        }
+
+        for disk in &mut metrics.disks {
+            disk.busy_percent = smooth(MetricType::DiskBusy, &disk.mount_point, disk.busy_percent as f64) as f32;
+            let read = smooth(MetricType::DiskIo, &format!("{}:read", disk.mount_point), disk.read_bytes_per_sec as f64);
+            let write = smooth(MetricType::DiskIo, &format!("{}:write", disk.mount_point), disk.write_bytes_per_sec as f64);
+            disk.read_bytes_per_sec = read.round() as u64;
+            disk.write_bytes_per_sec = write.round() as u64;
+        }
+
+        for network in &mut metrics.networks {
+            let name = &network.interface_name;
+            let sent = smooth(MetricType::NetworkThroughput, &format!("{}:sent", name), network.bytes_sent_rate as f64);
+            let received =
+                smooth(MetricType::NetworkThroughput, &format!("{}:received", name), network.bytes_received_rate as f64);
+            network.bytes_sent_rate = sent.round() as u64;
+            network.bytes_received_rate = received.round() as u64;
+        }
+
+        self.series.retain(|_, series| series.seen == pass);
+    }
+}
@@ -0,0 +1,152 @@
+use futures_util::{SinkExt, StreamExt};
+use serde::Deserialize;
+use std::net::SocketAddr;
+use tokio::net::{TcpListener, TcpStream, ToSocketAddrs};
+use tokio::sync::broadcast;
+use tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode;
+use tokio_tungstenite::tungstenite::protocol::{CloseFrame, WebSocketConfig};
+use tokio_tungstenite::tungstenite::Message;
+
+use crate::core::{MetricCategory, MetricCategorySet, RedactionConfig, Result, SystemMetrics};
+use crate::services::redaction::Redactor;
+
+/// Largest client message or frame accepted; clients only ever send small
+/// control messages
+const MAX_CLIENT_BYTES: usize = 16 * 1024;
+
+/// Streams `SystemMetrics` to WebSocket clients as JSON text frames, one per
+/// sample, in the `SystemMetrics::to_json` format.
+///
+/// A client can narrow what it receives by sending
+/// `{ "subscribe": ["cpu", "memory"] }`; categories left out arrive empty,
+/// as with `MonitoringService::subscribe_filtered`. Each client reads from
+/// its own broadcast receiver, so a client that can't keep up skips samples
+/// instead of holding up the others.
+pub struct WsServer {
+    listener: TcpListener,
+    metrics: broadcast::Sender<SystemMetrics>,
+    redactor: Redactor,
+}
+
+/// A control message sent by a client
+#[derive(Debug, Deserialize)]
+struct ClientMessage {
+    subscribe: Vec<String>,
+}
+
+impl WsServer {
+    /// Binds `addr`; clients are served once `run()` is called
+    pub async fn bind(addr: impl ToSocketAddrs, metrics: broadcast::Sender<SystemMetrics>) -> Result<Self> {
+        Ok(Self {
+            listener: TcpListener::bind(addr).await?,
+            metrics,
+            redactor: Redactor::new(&RedactionConfig::default())?,
+        })
+    }
+
+    /// Uses `redactor` instead of the default rules for outgoing process data
+    pub fn with_redactor(mut self, redactor: Redactor) -> Self {
+        self.redactor = redactor;
+        self
+    }
+
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        Ok(self.listener.local_addr()?)
+    }
+
+    /// Accepts clients until the task running it is dropped
+    pub async fn run(self) {
+        loop {
+            let (stream, peer) = match self.listener.accept().await {
+                Ok(accepted) => accepted,
+                Err(e) => {
+                    tracing::warn!("Failed to accept WebSocket client: {}", e);
+                    continue;
+                }
+            };
+
+            let receiver = self.metrics.subscribe();
+            let redactor = self.redactor.clone();
+            tokio::spawn(async move {
+                if let Err(e) = serve_client(stream, receiver, redactor).await {
+                    tracing::debug!("WebSocket client {} disconnected: {}", peer, e);
+                }
+            });
+        }
+    }
+}
+
+/// Parses a client control message into the categories it asks for;
+/// unknown category names are ignored
+pub fn parse_subscription(text: &str) -> Option<MetricCategorySet> {
+    let message: ClientMessage = serde_json::from_str(text).ok()?;
+    Some(
+        message
+            .subscribe
+            .iter()
+            .filter_map(|name| match name.to_ascii_lowercase().as_str() {
+                "cpu" => Some(MetricCategory::Cpu),
+                "memory" => Some(MetricCategory::Memory),
+                "gpu" => Some(MetricCategory::Gpu),
+                "disk" => Some(MetricCategory::Disk),
+                "network" => Some(MetricCategory::Network),
+                "process" => Some(MetricCategory::Process),
+                "battery" => Some(MetricCategory::Battery),
+                "sensor" => Some(MetricCategory::Sensor),
+                _ => None,
+            })
+            .collect(),
+    )
+}
+
+async fn serve_client(
+    stream: TcpStream,
+    mut receiver: broadcast::Receiver<SystemMetrics>,
+    redactor: Redactor,
+) -> Result<()> {
+    let config = WebSocketConfig {
+        max_message_size: Some(MAX_CLIENT_BYTES),
+        max_frame_size: Some(MAX_CLIENT_BYTES),
+        ..WebSocketConfig::default()
+    };
+    let mut socket = tokio_tungstenite::accept_async_with_config(stream, Some(config))
+        .await
+        .map_err(websocket_error)?;
+
+    let mut categories = MetricCategorySet::all();
+    loop {
+        tokio::select! {
+            sample = receiver.recv() => match sample {
+                Ok(metrics) => {
+                    let mut metrics = if categories.is_all() { metrics } else { metrics.filtered(categories) };
+                    redactor.redact_metrics(&mut metrics);
+                    socket.send(Message::Text(metrics.to_json()?)).await.map_err(websocket_error)?;
+                }
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    tracing::debug!("WebSocket client fell behind and skipped {} samples", skipped);
+                }
+                Err(broadcast::error::RecvError::Closed) => {
+                    let _ = socket
+                        .close(Some(CloseFrame { code: CloseCode::Away, reason: "".into() }))
+                        .await;
+                    return Ok(());
+                }
+            },
+            // Pings are answered by tungstenite as part of reading
+            message = socket.next() => match message {
+                Some(Ok(Message::Text(text))) => match parse_subscription(&text) {
+                    Some(set) => categories = set,
+                    None => tracing::debug!("Ignoring unrecognized WebSocket client message"),
+                },
+                Some(Ok(Message::Close(_))) | None => return Ok(()),
+                // Binary frames, pings and pongs carry nothing the server uses
+                Some(Ok(_)) => {}
+                Some(Err(e)) => return Err(websocket_error(e)),
+            },
+        }
+    }
+}
+
+fn websocket_error(error: tokio_tungstenite::tungstenite::Error) -> crate::core::MonitorError {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, error).into()
+}
@@ -0,0 +1,67 @@
+use crate::core::AlertAggregation;
+use crate::services::alert_engine::{percentile_of, AlertEngine};
+use std::time::{Duration, SystemTime};
+
+#[test]
+fn test_percentile_nearest_rank() {
+    let mut values: Vec<f64> = (1..=100).map(|v| v as f64).collect();
+    assert_eq!(percentile_of(&mut values, 95.0), Some(95.0));
+    assert_eq!(percentile_of(&mut values, 100.0), Some(100.0));
+    assert_eq!(percentile_of(&mut values, 0.5), Some(1.0));
+    assert_eq!(percentile_of(&mut [], 95.0), None);
+}
+
+#[test]
+fn test_instant_uses_latest_sample() {
+    let mut engine = AlertEngine::new(Duration::from_secs(600));
+    let now = SystemTime::now();
+
+    engine.record("cpu", 10.0, now - Duration::from_secs(2));
+    engine.record("cpu", 99.0, now);
+
+    assert_eq!(engine.aggregate("cpu", AlertAggregation::Instant, now), Some(99.0));
+    assert_eq!(engine.aggregate("memory", AlertAggregation::Instant, now), None);
+}
+
+#[test]
+fn test_windowed_aggregation_ignores_single_spike() {
+    let mut engine = AlertEngine::new(Duration::from_secs(600));
+    let start = SystemTime::now();
+
+    // 5 minutes of 1s samples at 50% with one 100% spike
+    for i in 0..300 {
+        let value = if i == 150 { 100.0 } else { 50.0 };
+        engine.record("cpu", value, start + Duration::from_secs(i));
+    }
+    let now = start + Duration::from_secs(299);
+
+    let avg = engine
+        .aggregate("cpu", AlertAggregation::Average { window_seconds: 300 }, now)
+        .unwrap();
+    assert!(avg < 51.0);
+
+    let p95 = engine
+        .aggregate("cpu", AlertAggregation::Percentile { percentile: 95.0, window_seconds: 300 }, now)
+        .unwrap();
+    assert_eq!(p95, 50.0);
+}
+
+#[test]
+fn test_window_and_retention() {
+    let mut engine = AlertEngine::new(Duration::from_secs(60));
+    let start = SystemTime::now();
+
+    engine.record("cpu", 90.0, start);
+    engine.record("cpu", 10.0, start + Duration::from_secs(120));
+    let now = start + Duration::from_secs(120);
+
+    // The old sample is outside both the retention and the 30s window
+    assert_eq!(
+        engine.aggregate("cpu", AlertAggregation::Average { window_seconds: 3600 }, now),
+        Some(10.0)
+    );
+    assert_eq!(
+        engine.aggregate("cpu", AlertAggregation::Average { window_seconds: 30 }, now + Duration::from_secs(60)),
+        None
+    );
+}
@@ -0,0 +1,147 @@
+use crate::core::{AlertAggregation, AlertLevel, MetricType};
+use crate::services::alerts::{AlertEvaluator, AlertRule};
+use std::time::{Duration, SystemTime};
+
+
+fn cpu_evaluator(aggregation: AlertAggregation) -> AlertEvaluator {
+    AlertEvaluator::from_rules(
+        vec![AlertRule {
+            metric_type: MetricType::CpuUsage,
+            warning: Some(80.0),
+            critical: Some(95.0),
+            aggregation,
+        }],
+        2.0,
+    )
+}
+
+#[test]
+fn test_alert_levels_with_hysteresis() {
+    let mut evaluator = cpu_evaluator(AlertAggregation::Instant);
+    let start = SystemTime::now();
+    let mut levels = Vec::new();
+
+    // Hovering around the warning threshold fires once
+    for (i, value) in [50.0, 81.0, 79.5, 80.5, 79.0, 96.0, 94.0, 92.0, 70.0].iter().enumerate() {
+        if let Some(event) = evaluator.observe(MetricType::CpuUsage, "", *value, start + Duration::from_secs(i as u64)) {
+            levels.push((event.level, event.threshold));
+        }
+    }
+
+    assert_eq!(
+        levels,
+        vec![
+            (AlertLevel::Warning, 80.0),
+            (AlertLevel::Critical, 95.0),
+            (AlertLevel::Warning, 80.0),
+            (AlertLevel::Cleared, 80.0),
+        ]
+    );
+}
+
+#[test]
+fn test_sources_are_tracked_separately() {
+    let mut evaluator = cpu_evaluator(AlertAggregation::Instant);
+    let now = SystemTime::now();
+
+    assert!(evaluator.observe(MetricType::CpuUsage, "a", 90.0, now).is_some());
+    assert!(evaluator.observe(MetricType::CpuUsage, "b", 90.0, now).is_some());
+    assert!(evaluator.observe(MetricType::CpuUsage, "a", 90.0, now).is_none());
+    // Metrics without a rule never alert
+    assert!(evaluator.observe(MetricType::MemoryUsage, "", 99.0, now).is_none());
+}
+
+#[test]
+fn test_windowed_rule_waits_for_full_window() {
+    let mut evaluator = cpu_evaluator(AlertAggregation::Average { window_seconds: 60 });
+    let start = SystemTime::now();
+
+    for i in 0..60 {
+        assert!(evaluator.observe(MetricType::CpuUsage, "", 99.0, start + Duration::from_secs(i)).is_none());
+    }
+    let event = evaluator
+        .observe(MetricType::CpuUsage, "", 99.0, start + Duration::from_secs(60))
+        .unwrap();
+    assert_eq!(event.level, AlertLevel::Critical);
+}
+
+#[test]
+fn test_failing_drive_raises_critical_alert() {
+    use crate::core::SmartHealth;
+    use super::export_tests::sample_metrics;
+
+    let mut evaluator = AlertEvaluator::from_rules(Vec::new(), 2.0);
+    let smart = |healthy| SmartHealth {
+        device: "/dev/sda".to_string(),
+        healthy,
+        temperature_c: Some(41.0),
+        power_on_hours: Some(31_000),
+        reallocated_sectors: Some(512),
+        wear_level_percent: None,
+    };
+
+    let mut metrics = sample_metrics();
+    metrics.disks[0].smart = Some(smart(false));
+    let mut second = metrics.disks[0].clone();
+    second.mount_point = "/home".to_string();
+    metrics.disks.push(second);
+
+    let events = evaluator.evaluate(&metrics);
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].metric_type, MetricType::DiskHealth);
+    assert_eq!(events[0].level, AlertLevel::Critical);
+    assert_eq!(events[0].source, "/dev/sda");
+    assert!(evaluator.evaluate(&metrics).is_empty());
+
+    for disk in &mut metrics.disks {
+        disk.smart = Some(smart(true));
+    }
+    let events = evaluator.evaluate(&metrics);
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].level, AlertLevel::Cleared);
+}
+
+#[test]
+fn test_inode_exhaustion_raises_critical_alert() {
+    use crate::core::{AlertConfig, MonitoringConfig};
+    use super::export_tests::sample_metrics;
+
+    // Inodes follow the disk's critical threshold (95%) but not its warning
+    let mut evaluator = AlertEvaluator::new(&MonitoringConfig::default(), &AlertConfig::default());
+    let mut metrics = sample_metrics();
+    metrics.disks[0].inode_usage_percent = Some(90.0);
+    assert!(evaluator.evaluate(&metrics).is_empty());
+
+    metrics.disks[0].inode_usage_percent = Some(97.0);
+    let events = evaluator.evaluate(&metrics);
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].metric_type, MetricType::DiskInodes);
+    assert_eq!(events[0].level, AlertLevel::Critical);
+    assert_eq!(events[0].source, "/");
+    assert_eq!(events[0].threshold, 95.0);
+
+    metrics.disks[0].inode_usage_percent = Some(50.0);
+    assert_eq!(evaluator.evaluate(&metrics)[0].level, AlertLevel::Cleared);
+}
+
+#[test]
+fn test_identical_gpus_alert_separately() {
+    use crate::core::{AlertConfig, MonitoringConfig};
+    use super::export_tests::{sample_gpu, sample_metrics};
+
+    let mut evaluator = AlertEvaluator::new(&MonitoringConfig::default(), &AlertConfig::default());
+    let mut metrics = sample_metrics();
+    metrics.gpus = vec![sample_gpu(), sample_gpu()];
+    metrics.gpus[0].usage_percent = 99.0;
+    metrics.gpus[1].usage_percent = 10.0;
+
+    // The idle card doesn't clear the busy one's alert, sample after sample
+    let events: Vec<_> = (0..3).flat_map(|_| evaluator.evaluate(&metrics)).collect();
+    assert_eq!(events.len(), 1, "{:?}", events);
+    assert_eq!((events[0].source.as_str(), events[0].level), ("0", AlertLevel::Critical));
+
+    metrics.gpus[1].usage_percent = 99.0;
+    let events = evaluator.evaluate(&metrics);
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].source, "1");
+}
@@ -0,0 +1,128 @@
+use std::collections::HashMap;
+
+use crate::backend::battery_monitor::{parse_ioreg_battery, parse_pmset_batt, parse_power_supply};
+use crate::backend::BatteryMonitor;
+use crate::core::{BatteryState, Monitor, MonitorConfig, MonitorState, MetricType};
+
+fn supply(attrs: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+    let attrs: HashMap<String, String> = attrs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+    move |attr| attrs.get(attr).cloned()
+}
+
+#[tokio::test]
+async fn test_battery_monitor_collection() {
+    let mut monitor = BatteryMonitor::new();
+    assert_eq!(monitor.name(), "Battery Monitor");
+    assert_eq!(monitor.state(), MonitorState::Uninitialized);
+
+    monitor.initialize(MonitorConfig::default()).await.unwrap();
+
+    // Machines without a battery report nothing rather than failing
+    let metrics = monitor.collect().await.unwrap();
+    assert!(metrics.iter().all(|m| matches!(
+        m.metric_type,
+        MetricType::BatteryCharge | MetricType::BatteryState | MetricType::BatteryHealth
+    )));
+}
+
+#[test]
+fn test_parse_power_supply_discharging_energy() {
+    let battery = parse_power_supply("BAT0", supply(&[
+        ("type", "Battery"),
+        ("status", "Discharging"),
+        ("capacity", "50"),
+        ("energy_now", "25000000"),
+        ("energy_full", "50000000"),
+        ("energy_full_design", "62500000"),
+        ("power_now", "12500000"),
+        ("cycle_count", "321"),
+    ])).unwrap();
+
+    assert_eq!(battery.name, "BAT0");
+    assert_eq!(battery.charge_percent, 50.0);
+    assert_eq!(battery.state, BatteryState::Discharging);
+    assert_eq!(battery.time_to_empty_seconds, Some(7200));
+    assert_eq!(battery.time_to_full_seconds, None);
+    assert_eq!(battery.cycle_count, Some(321));
+    assert_eq!(battery.health_percent, Some(80.0));
+}
+
+#[test]
+fn test_parse_power_supply_negative_discharge_rate() {
+    let battery = parse_power_supply("BAT1", supply(&[
+        ("type", "Battery"),
+        ("status", "Discharging"),
+        ("charge_now", "2000000"),
+        ("charge_full", "4000000"),
+        ("current_now", "-1000000"),
+    ])).unwrap();
+
+    assert_eq!(battery.time_to_empty_seconds, Some(7200));
+}
+
+#[test]
+fn test_parse_power_supply_charging_charge() {
+    let battery = parse_power_supply("BAT1", supply(&[
+        ("type", "Battery"),
+        ("status", "Charging"),
+        ("charge_now", "1000000"),
+        ("charge_full", "4000000"),
+        ("current_now", "1500000"),
+        ("cycle_count", "0"),
+    ])).unwrap();
+
+    assert_eq!(battery.charge_percent, 25.0);
+    assert_eq!(battery.time_to_full_seconds, Some(7200));
+    assert_eq!(battery.time_to_empty_seconds, None);
+    // Firmware that doesn't count cycles reports zero
+    assert_eq!(battery.cycle_count, None);
+    assert_eq!(battery.health_percent, None);
+}
+
+#[test]
+fn test_parse_power_supply_skips_non_batteries() {
+    assert!(parse_power_supply("AC", supply(&[("type", "Mains"), ("online", "1")])).is_none());
+    assert!(parse_power_supply("hidpp_battery_0", supply(&[
+        ("type", "Battery"),
+        ("scope", "Device"),
+        ("capacity", "90"),
+    ])).is_none());
+}
+
+#[test]
+fn test_parse_pmset_batt() {
+    let output = "Now drawing from 'Battery Power'\n -InternalBattery-0 (id=4653155)\t85%; discharging; 4:12 remaining present: true\n";
+    let batteries = parse_pmset_batt(output);
+
+    assert_eq!(batteries.len(), 1);
+    assert_eq!(batteries[0].name, "InternalBattery-0");
+    assert_eq!(batteries[0].charge_percent, 85.0);
+    assert_eq!(batteries[0].state, BatteryState::Discharging);
+    assert_eq!(batteries[0].time_to_empty_seconds, Some(4 * 3600 + 12 * 60));
+
+    let charged = parse_pmset_batt(" -InternalBattery-0 (id=1)\t100%; charged; 0:00 remaining present: true");
+    assert_eq!(charged[0].state, BatteryState::Full);
+    assert_eq!(charged[0].time_to_full_seconds, None);
+
+    let estimating = parse_pmset_batt(" -InternalBattery-0 (id=1)\t40%; charging; (no estimate) present: true");
+    assert_eq!(estimating[0].state, BatteryState::Charging);
+    assert_eq!(estimating[0].time_to_full_seconds, None);
+
+    assert!(parse_pmset_batt("Now drawing from 'AC Power'\n").is_empty());
+}
+
+#[test]
+fn test_parse_ioreg_battery() {
+    let output = r#"
+    "CycleCount" = 412
+    "DesignCapacity" = 5000
+    "MaxCapacity" = 100
+    "AppleRawMaxCapacity" = 4500
+"#;
+    assert_eq!(parse_ioreg_battery(output), (Some(412), Some(90.0)));
+
+    let legacy = "\"CycleCount\" = 10\n\"DesignCapacity\" = 4000\n\"MaxCapacity\" = 3000\n";
+    assert_eq!(parse_ioreg_battery(legacy), (Some(10), Some(75.0)));
+
+    assert_eq!(parse_ioreg_battery(""), (None, None));
+}
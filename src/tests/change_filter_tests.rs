@@ -0,0 +1,114 @@
+use super::export_tests::{sample_gpu, sample_metrics};
+use crate::core::{ChangeFilterConfig, MetricType};
+use crate::services::change_filter::{deadband_exceeded, ChangeFilter};
+use std::time::{Duration, SystemTime};
+
+fn enabled_config() -> ChangeFilterConfig {
+    ChangeFilterConfig {
+        enabled: true,
+        min_delta: 1.0,
+        keyframe_interval_seconds: 60,
+    }
+}
+
+#[test]
+fn test_stable_series_is_deadbanded() {
+    let mut filter = ChangeFilter::new(&enabled_config());
+    let start = SystemTime::now();
+
+    assert!(filter.should_store("cpu", 10.0, start));
+    assert!(!filter.should_store("cpu", 10.5, start + Duration::from_secs(1)));
+    assert!(!filter.should_store("cpu", 9.2, start + Duration::from_secs(2)));
+    // Delta is measured against the last stored value, not the last sample
+    assert!(filter.should_store("cpu", 11.5, start + Duration::from_secs(3)));
+    // Series are independent
+    assert!(filter.should_store("memory", 11.5, start + Duration::from_secs(3)));
+}
+
+#[test]
+fn test_keyframe_is_written_for_unchanged_series() {
+    let mut filter = ChangeFilter::new(&enabled_config());
+    let start = SystemTime::now();
+
+    assert!(filter.should_store("cpu", 10.0, start));
+    assert!(!filter.should_store("cpu", 10.0, start + Duration::from_secs(59)));
+    assert!(filter.should_store("cpu", 10.0, start + Duration::from_secs(60)));
+    assert!(!filter.should_store("cpu", 10.0, start + Duration::from_secs(61)));
+}
+
+#[test]
+fn test_disabled_filter_stores_everything() {
+    let mut filter = ChangeFilter::new(&ChangeFilterConfig::default());
+    let now = SystemTime::now();
+
+    assert!(filter.should_store("cpu", 10.0, now));
+    assert!(filter.should_store("cpu", 10.0, now));
+}
+
+#[test]
+fn test_whole_samples_are_filtered() {
+    let mut filter = ChangeFilter::new(&enabled_config());
+    let start = SystemTime::now();
+    let at = |seconds: u64, usage: f32| {
+        let mut metrics = sample_metrics();
+        metrics.timestamp = start + Duration::from_secs(seconds);
+        metrics.cpu.per_core_usage[0] = usage;
+        metrics
+    };
+
+    assert!(filter.should_store_sample(&at(0, 40.0)));
+    assert!(!filter.should_store_sample(&at(1, 40.5)));
+    // One gauge moving stores the sample
+    assert!(filter.should_store_sample(&at(2, 42.0)));
+    assert!(!filter.should_store_sample(&at(3, 42.0)));
+    assert!(filter.should_store_sample(&at(62, 42.0)));
+
+    // So does a device going away
+    let mut metrics = at(63, 42.0);
+    metrics.disks.clear();
+    assert!(filter.should_store_sample(&metrics));
+
+    let mut disabled = ChangeFilter::new(&ChangeFilterConfig::default());
+    assert!(disabled.should_store_sample(&at(0, 40.0)));
+    assert!(disabled.should_store_sample(&at(0, 40.0)));
+}
+
+#[test]
+fn test_deadband_compares_selected_gauges() {
+    let previous = sample_metrics();
+    let mut current = sample_metrics();
+    let metrics = [MetricType::CpuUsage, MetricType::DiskBusy];
+
+    assert!(!deadband_exceeded(&previous, &current, &metrics, 5.0));
+
+    // Unselected metrics don't count, however far they move
+    current.memory.usage_percent = 90.0;
+    assert!(!deadband_exceeded(&previous, &current, &metrics, 5.0));
+
+    // Within the band on one core, past it on the other
+    current.cpu.per_core_usage = vec![44.0, 51.0];
+    assert!(!deadband_exceeded(&previous, &current, &[MetricType::CpuUsage], 10.0));
+    assert!(deadband_exceeded(&previous, &current, &[MetricType::CpuUsage], 5.0));
+
+    // A disk appearing is a change even at the same values
+    let mut current = sample_metrics();
+    let mut disk = current.disks[0].clone();
+    disk.mount_point = "/home".to_string();
+    current.disks.push(disk);
+    assert!(deadband_exceeded(&previous, &current, &metrics, 5.0));
+}
+
+#[test]
+fn test_deadband_tells_identical_gpus_apart() {
+    let mut previous = sample_metrics();
+    previous.gpus = vec![sample_gpu(), sample_gpu()];
+    let mut current = previous.clone();
+    let metrics = [MetricType::GpuUsage];
+
+    assert!(!deadband_exceeded(&previous, &current, &metrics, 5.0));
+
+    // Only the second of two same-named cards gets busy
+    current.gpus[1].usage_percent = 50.0;
+    assert!(deadband_exceeded(&previous, &current, &metrics, 5.0));
+    assert_eq!(current.gauge_values(MetricType::GpuUsage), vec![("0".to_string(), 0.0), ("1".to_string(), 50.0)]);
+}
@@ -43,6 +43,7 @@ const mockMetrics: SystemMetrics = {
   gpus: [],
   disks: [
     {
+      id: '/dev/sda1:/',
       mount_point: '/',
       device_name: '/dev/sda1',
       fs_type: 'ext4',
@@ -57,6 +58,7 @@ const mockMetrics: SystemMetrics = {
   ],
   networks: [
     {
+      id: 'eth0',
       interface_name: 'eth0',
       is_up: true,
       mac_address: '00:00:00:00:00:00',
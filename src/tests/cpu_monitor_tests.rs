@@ -1,5 +1,5 @@
 use crate::backend::CpuMonitor;
-use crate::core::{Monitor, MonitorConfig, MonitorState, MetricType};
+use crate::core::{Monitor, MonitorConfig, MonitorState, MetricType, MetricValue};
 // Removed unused rstest import
 use std::time::Duration;
 
@@ -106,9 +106,214 @@ async fn test_cpu_usage_bounds() {
     
     for metric in metrics.iter() {
         if let MetricType::CpuUsage = metric.metric_type {
-            if let crate::core::MetricValue::Float(usage) = metric.value {
-                assert!(usage >= 0.0 && usage <= 100.0);
+            if let crate::core::MetricValue::Percentage(usage) = metric.value {
+                assert!((0.0..=100.0).contains(&usage));
             }
         }
     }
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_numa_topology() {
+    let nodes = crate::backend::cpu_monitor::read_numa_topology();
+
+    // Nodes are sorted by id and every CPU belongs to at most one node
+    let mut seen = std::collections::HashSet::new();
+    for pair in nodes.windows(2) {
+        assert!(pair[0].id < pair[1].id);
+    }
+    for node in &nodes {
+        for cpu in &node.cpus {
+            assert!(seen.insert(*cpu));
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_cpu_monitor_per_core_frequency() {
+    let mut monitor = CpuMonitor::new();
+    monitor.initialize(MonitorConfig::default()).await.unwrap();
+    let metrics = monitor.collect().await.unwrap();
+
+    let per_core: Vec<u64> = metrics
+        .iter()
+        .filter(|m| m.metric_type == MetricType::CpuFrequency && m.tags.contains_key("core"))
+        .filter_map(|m| match m.value {
+            MetricValue::Unsigned(v) => Some(v),
+            _ => None,
+        })
+        .collect();
+    let usage_cores = metrics
+        .iter()
+        .filter(|m| m.metric_type == MetricType::CpuUsage && m.tags.contains_key("core"))
+        .count();
+    assert_eq!(per_core.len(), usage_cores);
+
+    // The untagged scalar is the fastest core
+    let scalar = metrics
+        .iter()
+        .find(|m| m.metric_type == MetricType::CpuFrequency && m.tags.is_empty())
+        .and_then(|m| match m.value {
+            MetricValue::Unsigned(v) => Some(v),
+            _ => None,
+        });
+    assert_eq!(scalar, Some(per_core.iter().copied().max().unwrap_or(0)));
+}
+
+#[test]
+fn test_cross_node_irqs() {
+    use crate::backend::cpu_monitor::cross_node_irqs;
+    use crate::core::NumaNode;
+
+    let nodes = [
+        NumaNode { id: 0, cpus: vec![0, 1], memory_bytes: 0 },
+        NumaNode { id: 1, cpus: vec![2, 3], memory_bytes: 0 },
+    ];
+    // CPU 1 is offline, so it has no column
+    let interrupts = "\
+           CPU0       CPU2       CPU3
+   0:         40          0          0   IO-APIC    2-edge      timer
+  24:        120         30         50   IR-PCI-MSI 327680-edge      nvme0q1
+  25:          0         75          5   IR-PCI-MSI 524288-edge      mlx5_comp0
+  26:         10          0          0   IR-PCI-MSI 524289-edge      mlx5_comp1
+ NMI:          3          2          1   Non-maskable interrupts
+ LOC:     912345     823456     734567   Local timer interrupts
+";
+    let read_node = |irq: &str| match irq {
+        "0" => Some("-1\n".to_string()),
+        "24" => Some("0\n".to_string()),
+        "25" | "26" => Some("1\n".to_string()),
+        _ => None,
+    };
+
+    let irqs = cross_node_irqs(interrupts, &nodes, read_node);
+    let summary: Vec<_> = irqs
+        .iter()
+        .map(|irq| (irq.irq.as_str(), irq.device.as_str(), irq.node, irq.total_count, irq.remote_count))
+        .collect();
+    // The timer has no node, and the NMI and LOC rows are not device IRQs
+    assert_eq!(summary, vec![("24", "nvme0q1", 0, 200, 80), ("26", "mlx5_comp1", 1, 10, 10)]);
+
+    assert!(cross_node_irqs("", &nodes, read_node).is_empty());
+}
+
+#[test]
+fn test_cpu_thermal_status() {
+    use crate::backend::cpu_monitor::{cpu_thermal_status, parse_thermal_zone_limit};
+    use crate::core::{ThermalStatus, ThrottleReason};
+    use std::collections::HashMap;
+
+    // An x86_pkg_temp zone
+    let zone: HashMap<&str, &str> = [
+        ("trip_point_0_type", "critical\n"),
+        ("trip_point_0_temp", "105000\n"),
+        ("trip_point_1_type", "passive\n"),
+        ("trip_point_1_temp", "97000\n"),
+        ("trip_point_2_type", "passive\n"),
+        ("trip_point_2_temp", "0\n"),
+    ]
+    .into_iter()
+    .collect();
+    let read = |file: &str| zone.get(file).map(|content| content.to_string());
+    assert_eq!(parse_thermal_zone_limit(read), Some(97.0));
+
+    let critical_only = |file: &str| match file {
+        "trip_point_0_type" => Some("critical".to_string()),
+        "trip_point_0_temp" => Some("100000".to_string()),
+        _ => None,
+    };
+    assert_eq!(parse_thermal_zone_limit(critical_only), Some(100.0));
+    assert_eq!(parse_thermal_zone_limit(|_| None), None);
+
+    assert!(!cpu_thermal_status(70.0, 97.0, 0).throttling);
+    assert_eq!(cpu_thermal_status(97.0, 97.0, 0).reason, ThrottleReason::Thermal);
+    // Counted throttle events win even once the temperature has dropped
+    let status = cpu_thermal_status(80.0, 97.0, 3);
+    assert!(status.throttling);
+    assert_eq!(ThermalStatus::from_metric(&status.to_metric()), Some(status));
+}
+
+#[test]
+fn test_scheduler_counters() {
+    use crate::backend::cpu_monitor::parse_proc_stat_counters;
+
+    let stat = "cpu  10132153 290696 3084719 46828483 16683 0 25195 0 0 0\n\
+                intr 1462898 39 9 0 0 0 0 0\n\
+                ctxt 3012784821\n\
+                btime 1700000000\n";
+    assert_eq!(parse_proc_stat_counters(stat), (Some(3012784821), Some(1462898)));
+    assert_eq!(parse_proc_stat_counters("cpu 1 2 3\n"), (None, None));
+
+    assert_eq!(crate::core::counter_rate(15_000, 10_000, 0.5), 10_000.0);
+    assert_eq!(crate::core::counter_rate(5, 10, 1.0), 0.0);
+}
+
+#[tokio::test]
+async fn test_cpu_monitor_scheduler_rates() {
+    let mut monitor = CpuMonitor::new();
+    monitor.initialize(MonitorConfig::default()).await.unwrap();
+    monitor.collect().await.unwrap();
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    let metrics = monitor.collect().await.unwrap();
+
+    let rate = metrics
+        .iter()
+        .find(|m| m.metric_type == MetricType::ContextSwitches && m.tags.get("type").map(String::as_str) == Some("rate"))
+        .and_then(|m| m.value.as_f64());
+    assert!(rate.unwrap_or(0.0) >= 0.0);
+
+    #[cfg(target_os = "linux")]
+    {
+        // Sleeping alone switches contexts
+        assert!(rate.unwrap() > 0.0);
+        assert!(metrics.iter().any(|m| m.metric_type == MetricType::Interrupts));
+    }
+}
+
+#[test]
+fn test_per_core_time_breakdown() {
+    use crate::backend::cpu_monitor::{parse_proc_stat_cores, processor_core_counters, CpuTimeCounters};
+
+    let previous = "cpu  200 0 100 1400 100 0 0 0 0 0\n\
+                    cpu0 100 0 50 700 50 0 0 0 0 0\n\
+                    cpu1 100 0 50 700 50 0 0 0 0 0\n\
+                    intr 1462898 39 9\n";
+    let current = "cpu  400 0 200 1500 500 0 0 0 0 0\n\
+                   cpu0 150 10 80 720 230 5 5 0 0 0\n\
+                   cpu1 250 0 70 780 70 0 0 30 0 0\n\
+                   intr 1462999 39 9\n";
+    let before = parse_proc_stat_cores(previous);
+    let after = parse_proc_stat_cores(current);
+    assert_eq!(after.len(), 2);
+
+    // cpu0 spent most of its 300 ticks waiting on I/O
+    let core0 = after[0].share_since(&before[0]);
+    assert_eq!(core0.iowait, 60.0);
+    assert_eq!(core0.user, 20.0);
+    assert_eq!(core0.system, 10.0);
+    let total: f32 = core0.states().iter().map(|(_, share)| share).sum();
+    assert!((total - 100.0).abs() < 0.01);
+
+    let core1 = after[1].share_since(&before[1]);
+    assert_eq!(core1.steal, 10.0);
+    assert_eq!(core1.user, 50.0);
+
+    // No time passed
+    assert_eq!(after[0].share_since(&after[0]).idle, 0.0);
+    assert!(parse_proc_stat_cores("cpu 1 2 3\nctxt 5\n").is_empty());
+
+    let instances = |values: [u64; 3]| -> Vec<(String, u64)> {
+        ["1", "0", "_Total"].iter().map(|name| name.to_string()).zip(values).collect()
+    };
+    let cores = processor_core_counters(
+        &instances([100, 300, 400]),
+        &instances([300, 200, 500]),
+        &instances([600, 500, 1100]),
+        &instances([10, 0, 10]),
+        &instances([5, 0, 5]),
+    );
+    assert_eq!(cores.len(), 2);
+    assert_eq!(cores[0], CpuTimeCounters { user: 300, system: 200, idle: 500, ..CpuTimeCounters::default() });
+    assert_eq!(cores[1].system, 285);
+    assert_eq!(cores[1].softirq, 5);
+}
@@ -0,0 +1,12 @@
+use crate::core::MonitorError;
+use std::io::{Error, ErrorKind};
+
+#[test]
+fn test_permission_errors_stay_distinct() {
+    let denied = MonitorError::from_io("reading debugfs", Error::from(ErrorKind::PermissionDenied));
+    assert!(matches!(&denied, MonitorError::PermissionDenied { operation } if operation == "reading debugfs"));
+    assert!(denied.to_string().contains("administrator"));
+
+    let other = MonitorError::from_io("reading debugfs", Error::from(ErrorKind::NotFound));
+    assert!(matches!(other, MonitorError::IoError(_)));
+}
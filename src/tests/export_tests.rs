@@ -0,0 +1,356 @@
+use crate::core::export::{to_otlp_json, to_prometheus};
+use crate::core::{
+    CpuMetrics, DiskMetrics, GpuMetrics, MemoryMetrics, NetworkMetrics, ProcessMetrics, RuntimeEnvironment, SystemInfo,
+    SystemMetrics,
+};
+use std::collections::HashMap;
+use std::time::SystemTime;
+
+
+pub(super) fn sample_metrics() -> SystemMetrics {
+    SystemMetrics {
+        timestamp: SystemTime::now(),
+        system_info: SystemInfo {
+            hostname: "host".to_string(),
+            os_name: "Linux".to_string(),
+            os_version: "6.1".to_string(),
+            kernel_version: "6.1.0".to_string(),
+            architecture: "x86_64".to_string(),
+            cpu_brand: "Test CPU".to_string(),
+            cpu_cores: 2,
+            cpu_threads: 2,
+            total_memory: 1024,
+            boot_time: SystemTime::UNIX_EPOCH,
+            transparent_hugepages: None,
+            numa_topology: Vec::new(),
+            cross_node_irqs: Vec::new(),
+            environment: RuntimeEnvironment::Native,
+        },
+        cpu: CpuMetrics {
+            usage_percent: 42.5,
+            per_core_usage: vec![40.0, 45.0],
+            ..CpuMetrics::default()
+        },
+        memory: MemoryMetrics {
+            used_bytes: 123,
+            ..MemoryMetrics::default()
+        },
+        gpus: Vec::new(),
+        disks: vec![DiskMetrics {
+            id: "nvme0n1:/".to_string(),
+            mount_point: "/".to_string(),
+            device_name: "nvme0n1".to_string(),
+            fs_type: "ext4".to_string(),
+            total_bytes: 1000,
+            used_bytes: 250,
+            available_bytes: 750,
+            usage_percent: 25.0,
+            read_bytes_per_sec: 0,
+            write_bytes_per_sec: 0,
+            io_operations_per_sec: 0,
+            busy_percent: 3.5,
+            smart: None,
+            inodes_total: Some(65_536),
+            inodes_used: Some(1_024),
+            inode_usage_percent: Some(1.5625),
+        }],
+        networks: vec![NetworkMetrics {
+            id: "eth\"0".to_string(),
+            interface_name: "eth\"0".to_string(),
+            is_up: true,
+            mac_address: "00:11:22:33:44:55".to_string(),
+            ip_addresses: Vec::new(),
+            bytes_sent: 10,
+            bytes_received: 20,
+            packets_sent: 1,
+            packets_received: 2,
+            errors_sent: 0,
+            errors_received: 0,
+            speed_mbps: None,
+            bytes_sent_rate: 0,
+            bytes_received_rate: 0,
+            wireless: None,
+        }],
+        pools: Vec::new(),
+        tcp: None,
+        top_processes: Vec::new(),
+        batteries: Vec::new(),
+        sensors: Vec::new(),
+        last_errors: HashMap::new(),
+        health_score: 72.5,
+    }
+}
+
+/// An idle, running process with no parent
+pub(super) fn sample_process(pid: u32, name: &str) -> ProcessMetrics {
+    ProcessMetrics {
+        pid,
+        name: name.to_string(),
+        cpu_usage_percent: 0.0,
+        memory_bytes: 0,
+        memory_percent: 0.0,
+        disk_read_bytes: 0,
+        disk_write_bytes: 0,
+        disk_read_bytes_per_sec: 0,
+        disk_write_bytes_per_sec: 0,
+        status: "Run".to_string(),
+        threads: 1,
+        start_time: SystemTime::UNIX_EPOCH,
+        parent_pid: None,
+        open_handles: 0,
+        handles_available: false,
+        cpu_time_seconds: 0.0,
+        elapsed_seconds: 0,
+        cmdline: None,
+        cpu_affinity: Vec::new(),
+    }
+}
+
+/// An idle GPU to add to `sample_metrics()`
+pub(super) fn sample_gpu() -> GpuMetrics {
+    GpuMetrics {
+        name: "GPU".to_string(),
+        driver_version: String::new(),
+        temperature_celsius: 0.0,
+        usage_percent: 0.0,
+        memory_total_bytes: 0,
+        memory_used_bytes: 0,
+        memory_usage_percent: 0.0,
+        power_watts: 0.0,
+        fan_speed_percent: None,
+        clock_mhz: 0,
+        memory_clock_mhz: 0,
+        gpu_processes: Vec::new(),
+        thermal: None,
+        instance_id: None,
+        parent_id: None,
+    }
+}
+
+#[test]
+fn test_prometheus_exposition_format() {
+    let text = to_prometheus(&sample_metrics());
+
+    assert!(text.contains("# HELP system_cpu_usage_percent Overall CPU usage\n"));
+    assert!(text.contains("# TYPE system_cpu_usage_percent gauge\n"));
+    assert!(text.contains("\nsystem_cpu_usage_percent 42.5\n"));
+    assert!(text.contains("system_cpu_core_usage_percent{core=\"1\"} 45\n"));
+    assert!(text.contains("system_memory_used_bytes 123\n"));
+    assert!(text.contains("system_health_score 72.5\n"));
+    assert!(text.contains("system_disk_busy_percent{mount=\"/\",device=\"nvme0n1\"} 3.5\n"));
+    assert!(text.contains("system_disk_inode_usage_percent{mount=\"/\",device=\"nvme0n1\"} 1.5625\n"));
+    assert!(text.contains("# TYPE system_network_sent_bytes_total counter\n"));
+    // Label values are escaped
+    assert!(text.contains("system_network_sent_bytes_total{interface=\"eth\\\"0\"} 10\n"));
+    // Families without samples are omitted entirely
+    assert!(!text.contains("system_gpu_usage_percent"));
+}
+
+#[test]
+fn test_otlp_json_request_shape() {
+    let request = to_otlp_json(&sample_metrics());
+    let resource_metrics = &request["resourceMetrics"][0];
+
+    let attributes = resource_metrics["resource"]["attributes"].as_array().unwrap();
+    assert!(attributes.iter().any(|a| a["key"] == "host.name" && a["value"]["stringValue"] == "host"));
+    assert!(attributes.iter().any(|a| a["key"] == "host.arch" && a["value"]["stringValue"] == "x86_64"));
+
+    let metrics = resource_metrics["scopeMetrics"][0]["metrics"].as_array().unwrap();
+    let find = |name: &str| metrics.iter().find(|m| m["name"] == name);
+
+    let cpu = find("system.cpu.usage").unwrap();
+    assert_eq!(cpu["unit"], "%");
+    let point = &cpu["gauge"]["dataPoints"][0];
+    assert_eq!(point["asDouble"], 42.5);
+    assert!(point["timeUnixNano"].is_string());
+
+    let per_core = &find("system.cpu.core.usage").unwrap()["gauge"]["dataPoints"][1];
+    assert_eq!(per_core["attributes"][0]["key"], "core");
+    assert_eq!(per_core["attributes"][0]["value"]["stringValue"], "1");
+
+    assert_eq!(find("system.memory.used").unwrap()["unit"], "By");
+    // Gauges without points are omitted entirely
+    assert!(find("system.gpu.usage").is_none());
+}
+
+#[test]
+fn test_system_metrics_json_round_trip() {
+    use crate::core::{MonitorError, METRICS_SCHEMA_VERSION};
+
+    let metrics = sample_metrics();
+    let json = metrics.to_json().unwrap();
+    assert_eq!(SystemMetrics::from_json(&json).unwrap(), metrics);
+
+    let mut value: serde_json::Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(value["schema_version"], METRICS_SCHEMA_VERSION);
+
+    value["schema_version"] = (METRICS_SCHEMA_VERSION + 1).into();
+    assert!(matches!(
+        SystemMetrics::from_json(&value.to_string()),
+        Err(MonitorError::SchemaMismatch { found, .. }) if found == METRICS_SCHEMA_VERSION + 1
+    ));
+
+    // Plain serde output carries no marker
+    let unversioned = serde_json::to_string(&metrics).unwrap();
+    assert!(matches!(
+        SystemMetrics::from_json(&unversioned),
+        Err(MonitorError::SchemaMismatch { found: 0, .. })
+    ));
+}
+
+#[test]
+fn test_csv_columns_stay_aligned() {
+    use crate::core::export::{CsvSchema, CsvWriter};
+
+    let first = sample_metrics();
+    let schema = CsvSchema {
+        mount_points: vec!["/".to_string(), "/data".to_string()],
+        interfaces: vec!["eth\"0".to_string()],
+    };
+    let mut writer = CsvWriter::new(Vec::new(), schema);
+    writer.write_row(&first).unwrap();
+
+    // The disk goes away and an interface outside the schema appears
+    let mut second = sample_metrics();
+    second.disks.clear();
+    let mut extra = second.networks[0].clone();
+    extra.interface_name = "wlan0".to_string();
+    second.networks.push(extra);
+    writer.write_row(&second).unwrap();
+
+    let output = String::from_utf8(writer.into_inner()).unwrap();
+    let lines: Vec<&str> = output.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert_eq!(
+        lines[0],
+        "timestamp,cpu_usage,mem_used,mem_total,disk0_usage,disk0_read_bps,disk0_write_bps,\
+         disk1_usage,disk1_read_bps,disk1_write_bps,net0_rx_bps,net0_tx_bps"
+    );
+
+    let row: Vec<&str> = lines[1].split(',').collect();
+    assert_eq!(&row[1..], ["42.5", "123", "0", "25", "0", "0", "", "", "", "0", "0"]);
+    assert!(row[0].parse::<f64>().unwrap() > 0.0);
+
+    let row: Vec<&str> = lines[2].split(',').collect();
+    assert_eq!(row.len(), 12);
+    assert_eq!(&row[4..10], ["", "", "", "", "", ""]);
+}
+
+#[test]
+fn test_csv_display_units() {
+    use crate::core::export::{CsvSchema, CsvWriter};
+    use crate::core::{ThroughputUnit, UnitSystem};
+
+    let mut metrics = sample_metrics();
+    metrics.memory.used_bytes = 2 * 1024 * 1024 * 1024;
+    metrics.disks[0].read_bytes_per_sec = 1_000_000;
+    let mut writer = CsvWriter::new(Vec::new(), CsvSchema::from_metrics(&metrics))
+        .with_display_units(UnitSystem::Decimal, ThroughputUnit::Bits);
+    writer.write_row(&metrics).unwrap();
+
+    let output = String::from_utf8(writer.into_inner()).unwrap();
+    let row: Vec<&str> = output.lines().nth(1).unwrap().split(',').collect();
+    assert_eq!(row[2], "2.15 GB");
+    assert_eq!(row[5], "8.00 Mbit/s");
+}
+
+#[test]
+fn test_system_metrics_totals() {
+
+    let mut metrics = sample_metrics();
+    metrics.networks[0].bytes_received_rate = 1000;
+    metrics.networks[0].bytes_sent_rate = 200;
+    let mut loopback = metrics.networks[0].clone();
+    loopback.interface_name = "lo".to_string();
+    loopback.bytes_received_rate = 1 << 30;
+    let mut wifi = metrics.networks[0].clone();
+    wifi.interface_name = "wlan0".to_string();
+    metrics.networks.extend([loopback, wifi]);
+    assert_eq!(metrics.total_network_rx_bps(), 2000);
+    assert_eq!(metrics.total_network_tx_bps(), 400);
+
+    metrics.disks[0].read_bytes_per_sec = 10;
+    metrics.disks[0].write_bytes_per_sec = 30;
+    let second = metrics.disks[0].clone();
+    metrics.disks.push(second);
+    assert_eq!(metrics.total_disk_read_bps(), 20);
+    assert_eq!(metrics.total_disk_write_bps(), 60);
+
+    let gpu = GpuMetrics { power_watts: 75.5, ..sample_gpu() };
+    metrics.gpus = vec![gpu.clone(), gpu];
+    assert_eq!(metrics.total_gpu_power_watts(), 151.0);
+
+    assert_eq!(metrics.overall_cpu_usage(), 42.5);
+    // First sample: no global delta yet, but per-core readings exist
+    metrics.cpu.usage_percent = 0.0;
+    assert_eq!(metrics.overall_cpu_usage(), 42.5);
+    metrics.cpu.per_core_usage.clear();
+    assert_eq!(metrics.overall_cpu_usage(), 0.0);
+}
+
+#[test]
+fn test_health_score() {
+    use crate::core::HealthScoreWeights;
+
+    let mut metrics = sample_metrics();
+    metrics.cpu.usage_percent = 40.0;
+    metrics.memory.usage_percent = 60.0;
+    metrics.disks[0].usage_percent = 90.0;
+    let weights = HealthScoreWeights { cpu: 1.0, memory: 1.0, disk: 2.0, swap: 4.0 };
+
+    // No swap, so its weight is left out: 100 - (40 + 60 + 180) / 4
+    assert_eq!(metrics.compute_health_score(&weights), 30.0);
+
+    metrics.memory.swap_total_bytes = 1 << 30;
+    metrics.memory.swap_usage_percent = 10.0;
+    assert_eq!(metrics.compute_health_score(&weights), 60.0);
+
+    // Only the fullest disk counts
+    let mut spare = metrics.disks[0].clone();
+    spare.usage_percent = 5.0;
+    metrics.disks.push(spare);
+    assert_eq!(metrics.compute_health_score(&weights), 60.0);
+
+    // Out-of-range usage is clamped, and no weight at all means no penalty
+    metrics.cpu.usage_percent = 400.0;
+    assert_eq!(metrics.compute_health_score(&HealthScoreWeights { cpu: 1.0, memory: 0.0, disk: 0.0, swap: 0.0 }), 0.0);
+    assert_eq!(metrics.compute_health_score(&HealthScoreWeights { cpu: 0.0, memory: 0.0, disk: 0.0, swap: 0.0 }), 100.0);
+}
+
+#[test]
+fn test_system_metrics_diff() {
+    use std::time::Duration;
+
+    let previous = sample_metrics();
+    let mut current = sample_metrics();
+    current.timestamp = previous.timestamp + Duration::from_secs(2);
+    current.cpu.context_switches = 4000;
+    current.networks[0].bytes_sent = 2010;
+    current.networks[0].bytes_received = 5;
+    current.networks[0].packets_received = 12;
+    let mut wifi = current.networks[0].clone();
+    wifi.interface_name = "wlan0".to_string();
+    current.networks.push(wifi);
+    current.disks[0].mount_point = "/data".to_string();
+
+    let delta = current.diff(&previous);
+    assert_eq!(delta.elapsed_seconds, 2.0);
+    assert_eq!(delta.cpu_usage_percent, 42.5);
+    assert_eq!(delta.context_switches_per_sec, 2000.0);
+
+    assert_eq!(delta.networks.len(), 1);
+    assert_eq!(delta.networks[0].bytes_sent_per_sec, 1000.0);
+    assert_eq!(delta.networks[0].packets_received_per_sec, 5.0);
+    // The counter went backwards, so the interface was reset
+    assert_eq!(delta.networks[0].bytes_received_per_sec, 0.0);
+    assert_eq!(delta.added_interfaces, ["wlan0"]);
+    assert!(delta.removed_interfaces.is_empty());
+
+    assert!(delta.disks.is_empty());
+    assert_eq!(delta.added_disks, ["/data"]);
+    assert_eq!(delta.removed_disks, ["/"]);
+
+    let same = previous.diff(&previous);
+    assert_eq!(same.elapsed_seconds, 0.0);
+    assert_eq!(same.networks[0].bytes_sent_per_sec, 0.0);
+}
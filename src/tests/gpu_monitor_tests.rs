@@ -60,4 +60,253 @@ async fn test_nvidia_gpu_detection() {
     // We check that initialization doesn't panic
     let metrics = monitor.collect().await;
     assert!(metrics.is_ok());
-}
\ No newline at end of file
+}
+
+#[cfg(feature = "nvidia")]
+#[test]
+fn test_nvml_recovery_reinitializes_once() {
+    use crate::backend::gpu_monitor::NvmlRecovery;
+    use std::time::{Duration, Instant};
+
+    let now = Instant::now();
+    let mut recovery = NvmlRecovery::default();
+    assert!(!recovery.is_recovering());
+    assert!(!recovery.is_due(now));
+
+    recovery.handle_lost(now);
+    assert!(recovery.is_recovering());
+    assert!(!recovery.is_due(now));
+    assert!(recovery.is_due(now + Duration::from_secs(5)));
+
+    // A failed attempt is not retried
+    recovery.attempt_failed();
+    assert!(recovery.is_exhausted());
+    assert!(!recovery.is_due(now + Duration::from_secs(3600)));
+
+    // A later lost handle gets its own attempt
+    recovery.handle_lost(now);
+    assert!(recovery.is_due(now + Duration::from_secs(5)));
+}
+
+#[test]
+fn test_engine_busy_percent() {
+    use crate::backend::gpu_monitor::engine_busy_percent;
+    use std::collections::HashMap;
+    use std::time::Duration;
+
+    let previous: HashMap<String, u64> = [("rcs0".to_string(), 1_000_000_000), ("vcs0".to_string(), 0)].into();
+    let idle = previous.clone();
+    let busy: HashMap<String, u64> = [("rcs0".to_string(), 1_250_000_000), ("vcs0".to_string(), 100_000_000)].into();
+
+    assert_eq!(engine_busy_percent(&previous, &idle, Duration::from_secs(1)), Some(0.0));
+    // The busiest engine wins
+    assert_eq!(engine_busy_percent(&previous, &busy, Duration::from_secs(1)), Some(25.0));
+    assert_eq!(engine_busy_percent(&previous, &busy, Duration::from_millis(100)), Some(100.0));
+    assert_eq!(engine_busy_percent(&previous, &busy, Duration::ZERO), None);
+    assert_eq!(engine_busy_percent(&HashMap::new(), &busy, Duration::from_secs(1)), None);
+}
+
+#[test]
+fn test_dpm_clock_formats() {
+    use crate::backend::gpu_monitor::parse_dpm_clock;
+
+    // Captured from a Polaris card (indexed) and an RDNA2 card's memory clock table
+    let fixtures = [
+        ("pp_dpm_sclk", "0: 300Mhz\n1: 608Mhz\n2: 1340Mhz *\n", Some(1340)),
+        ("pp_dpm_mclk", "96Mhz\n456Mhz\n1000Mhz *\n", Some(1000)),
+        ("pp_dpm_mclk_upper", "0: 96MHz\n1: 875MHz *\n", Some(875)),
+        ("pp_dpm_idle", "0: 300Mhz\n1: 608Mhz\n", None),
+    ];
+    for (file, content, expected) in fixtures {
+        assert_eq!(parse_dpm_clock(content), expected, "{file}");
+    }
+}
+
+#[test]
+fn test_smoothed_usage() {
+    use crate::backend::gpu_monitor::smoothed_usage;
+    use std::collections::VecDeque;
+
+    let mut history = VecDeque::new();
+    assert_eq!(smoothed_usage(&mut history, 100.0, 4), 100.0);
+    assert_eq!(smoothed_usage(&mut history, 0.0, 4), 50.0);
+    smoothed_usage(&mut history, 0.0, 4);
+    smoothed_usage(&mut history, 0.0, 4);
+    // The first spike has aged out of a four-sample window
+    assert_eq!(smoothed_usage(&mut history, 40.0, 4), 10.0);
+    assert_eq!(history.len(), 4);
+    // A window of one passes readings through
+    assert_eq!(smoothed_usage(&mut history, 70.0, 1), 70.0);
+}
+
+#[cfg(feature = "nvidia")]
+#[test]
+fn test_merge_gpu_processes() {
+    use crate::backend::gpu_monitor::merge_gpu_processes;
+    use nvml_wrapper::enums::device::UsedGpuMemory;
+    use nvml_wrapper::struct_wrappers::device::{ProcessInfo, ProcessUtilizationSample};
+
+    let info = |pid, used_gpu_memory| ProcessInfo {
+        pid,
+        used_gpu_memory,
+        gpu_instance_id: None,
+        compute_instance_id: None,
+    };
+    let sample = |pid, timestamp, sm_util| ProcessUtilizationSample {
+        pid,
+        timestamp,
+        sm_util,
+        mem_util: 0,
+        enc_util: 0,
+        dec_util: 0,
+    };
+
+    let compute = [info(300, UsedGpuMemory::Used(2 << 30)), info(100, UsedGpuMemory::Used(512 << 20))];
+    // pid 300 also holds a graphics context; pid 200 is a WDDM process
+    let graphics = [info(300, UsedGpuMemory::Used(2 << 30)), info(200, UsedGpuMemory::Unavailable)];
+    let samples = [sample(300, 10, 40), sample(300, 20, 85), sample(999, 20, 10)];
+
+    let processes = merge_gpu_processes(&compute, &graphics, &samples);
+    let pids: Vec<u32> = processes.iter().map(|p| p.pid).collect();
+    assert_eq!(pids, vec![100, 200, 300]);
+
+    assert_eq!(processes[0].used_memory_bytes, 512 << 20);
+    assert_eq!(processes[0].sm_utilization, None);
+    assert_eq!(processes[1].used_memory_bytes, 0);
+    // Listed in both tables but only counted once, with the newest sample
+    assert_eq!(processes[2].used_memory_bytes, 2 << 30);
+    assert_eq!(processes[2].sm_utilization, Some(85));
+}
+
+#[cfg(feature = "nvidia")]
+#[test]
+fn test_mig_instance_processes() {
+    use crate::backend::gpu_monitor::mig_instance_processes;
+    use nvml_wrapper::enums::device::UsedGpuMemory;
+    use nvml_wrapper::struct_wrappers::device::ProcessInfo;
+
+    let info = |pid, gpu_instance_id| ProcessInfo {
+        pid,
+        used_gpu_memory: UsedGpuMemory::Used(1 << 30),
+        gpu_instance_id,
+        compute_instance_id: gpu_instance_id.map(|_| 0),
+    };
+
+    let compute = [info(100, Some(1)), info(200, Some(2)), info(300, Some(1))];
+    let graphics = [info(400, None)];
+
+    let first: Vec<u32> = mig_instance_processes(&compute, &graphics, &[], 1).iter().map(|p| p.pid).collect();
+    assert_eq!(first, vec![100, 300]);
+    let second: Vec<u32> = mig_instance_processes(&compute, &graphics, &[], 2).iter().map(|p| p.pid).collect();
+    assert_eq!(second, vec![200]);
+    assert!(mig_instance_processes(&compute, &graphics, &[], 3).is_empty());
+}
+
+#[test]
+fn test_gpu_redetection_backoff() {
+    use crate::backend::gpu_monitor::GpuRedetection;
+    use std::time::{Duration, Instant};
+
+    let now = Instant::now();
+    let mut redetection = GpuRedetection::default();
+
+    // A detected GPU gets a few empty cycles before detection is re-run
+    for _ in 1..GpuRedetection::EMPTY_CYCLES {
+        redetection.observe(false, true, now);
+    }
+    assert!(!redetection.is_due(now + Duration::from_secs(3600)));
+    redetection.observe(false, true, now);
+    assert!(!redetection.is_due(now));
+    assert!(redetection.is_due(now + Duration::from_secs(1)));
+
+    // With nothing detected, the first empty cycle schedules an attempt, and
+    // each attempt that doesn't help doubles the wait up to the cap
+    let mut redetection = GpuRedetection::default();
+    let mut previous = redetection.backoff();
+    for _ in 0..10 {
+        redetection.observe(false, false, now);
+        assert!(redetection.is_due(now + previous));
+        redetection.attempted();
+        assert!(!redetection.is_due(now + Duration::from_secs(3600)));
+        assert!(redetection.backoff() >= previous);
+        previous = redetection.backoff();
+    }
+    assert_eq!(redetection.backoff(), Duration::from_secs(60));
+
+    // Once GPUs show up the state starts over
+    redetection.observe(true, true, now);
+    assert_eq!(redetection.backoff(), Duration::from_secs(1));
+    assert!(!redetection.is_due(now + Duration::from_secs(3600)));
+}
+
+#[cfg(feature = "nvidia")]
+#[test]
+fn test_nvidia_throttle_reason() {
+    use crate::backend::gpu_monitor::throttle_reason;
+    use crate::core::ThrottleReason;
+    use nvml_wrapper::bitmasks::device::ThrottleReasons;
+
+    assert_eq!(throttle_reason(ThrottleReasons::GPU_IDLE), ThrottleReason::None);
+    assert_eq!(throttle_reason(ThrottleReasons::APPLICATIONS_CLOCKS_SETTING), ThrottleReason::None);
+    assert_eq!(throttle_reason(ThrottleReasons::SW_POWER_CAP), ThrottleReason::Power);
+    assert_eq!(throttle_reason(ThrottleReasons::HW_SLOWDOWN), ThrottleReason::Other);
+    assert_eq!(
+        throttle_reason(ThrottleReasons::SW_POWER_CAP | ThrottleReasons::HW_THERMAL_SLOWDOWN),
+        ThrottleReason::Thermal
+    );
+}
+
+#[test]
+fn test_windows_gpu_counter_usage() {
+    use crate::backend::windows_gpu::{adapter_usage, format_luid, parse_counter_instance};
+
+    let luid = format_luid(0, 0xD1B7);
+    assert_eq!(luid, "0x00000000_0x0000d1b7");
+    assert_eq!(
+        parse_counter_instance("pid_1234_luid_0x00000000_0x0000D1B7_phys_0_eng_0_engtype_3D"),
+        Some((luid.clone(), "_phys_0_eng_0_engtype_3D"))
+    );
+    assert_eq!(parse_counter_instance("pid_1234_phys_0"), None);
+
+    let engines = [
+        // Two processes on the 3D engine add up; the copy engine is less busy
+        ("pid_1_luid_0x00000000_0x0000D1B7_phys_0_eng_0_engtype_3D", 30.0),
+        ("pid_2_luid_0x00000000_0x0000D1B7_phys_0_eng_0_engtype_3D", 25.0),
+        ("pid_2_luid_0x00000000_0x0000D1B7_phys_0_eng_1_engtype_Copy", 40.0),
+        ("pid_3_luid_0x00000000_0x0000E000_phys_0_eng_0_engtype_3D", 5.0),
+    ];
+    let memory = [
+        ("luid_0x00000000_0x0000D1B7_phys_0", 512.0 * 1024.0 * 1024.0),
+        ("luid_0x00000000_0x0000E000_phys_0", 0.0),
+    ];
+    let usage = adapter_usage(engines, memory);
+
+    assert_eq!(usage.len(), 2);
+    assert_eq!(usage[&luid].usage_percent, 55.0);
+    assert_eq!(usage[&luid].dedicated_used_bytes, 512 * 1024 * 1024);
+    assert_eq!(usage[&format_luid(0, 0xE000)].usage_percent, 5.0);
+}
+
+#[test]
+fn test_mig_instance_metrics() {
+    use crate::tests::export_tests::sample_gpu;
+
+    let mut parent = sample_gpu();
+    parent.parent_id = Some("GPU-0".to_string());
+    let mut instance = sample_gpu();
+    instance.instance_id = Some("MIG-1".to_string());
+    instance.parent_id = Some("GPU-0".to_string());
+
+    let metrics = GpuMonitor::gpus_to_metrics(&[parent, instance]);
+    let of_instance: Vec<_> = metrics.iter().filter(|m| m.tags.get("gpu").map(String::as_str) == Some("1")).collect();
+
+    // Only the memory series; the rest would be NVML's zeros
+    assert_eq!(of_instance.len(), 1);
+    assert_eq!(of_instance[0].metric_type, MetricType::GpuMemoryUsage);
+    assert_eq!(of_instance[0].tags["instance_id"], "MIG-1");
+    assert_eq!(of_instance[0].tags["parent_id"], "GPU-0");
+
+    let of_parent = metrics.len() - of_instance.len();
+    assert_eq!(of_parent, 4);
+    assert!(metrics.iter().filter(|m| m.tags["gpu"] == "0").all(|m| m.tags["parent_id"] == "GPU-0"));
+}
@@ -0,0 +1,61 @@
+import { describe, it, expect } from 'vitest';
+import { decodeHighPerf, HIGH_PERF_VERSION, WireError } from '../services/highPerfWire';
+
+// Header plus the bincode encoding of a snapshot with no devices
+function emptyFrame(version = HIGH_PERF_VERSION): Uint8Array {
+  const bytes: number[] = [...'SDHP'].map((c) => c.charCodeAt(0));
+  bytes.push(version & 0xff, version >> 8);
+
+  const u64 = (value: number) => {
+    const view = new DataView(new ArrayBuffer(8));
+    view.setBigUint64(0, BigInt(value), true);
+    bytes.push(...new Uint8Array(view.buffer));
+  };
+  const f32 = (value: number) => {
+    const view = new DataView(new ArrayBuffer(4));
+    view.setFloat32(0, value, true);
+    bytes.push(...new Uint8Array(view.buffer));
+  };
+
+  u64(1_700_000_000);
+  // CPU: usage, no cores, no frequencies, no temperature, load, 4 counters
+  f32(12.5);
+  u64(0);
+  u64(0);
+  bytes.push(0);
+  [1, 2, 3].forEach(f32);
+  [10, 20, 30, 40].forEach(u64);
+  // Memory: 10 counters
+  for (let i = 0; i < 10; i++) u64(i * 1024);
+  // gpus through quantum_processors
+  for (let i = 0; i < 11; i++) u64(0);
+
+  return Uint8Array.from(bytes);
+}
+
+describe('decodeHighPerf', () => {
+  it('decodes a frame of the current version', () => {
+    const metrics = decodeHighPerf(emptyFrame());
+    expect(metrics.cpu.global_usage).toBe(12.5);
+    expect(metrics.cpu.temperature).toBeUndefined();
+    expect(metrics.cpu.load_average).toEqual([1, 2, 3]);
+    expect(metrics.cpu.cache_hits).toBe(40);
+    expect(metrics.memory.page_outs).toBe(9 * 1024);
+    expect(metrics.gpus).toEqual([]);
+    expect(metrics.quantum_processors).toEqual([]);
+  });
+
+  it('rejects frames it cannot trust', () => {
+    const frame = emptyFrame();
+    expect(() => decodeHighPerf(frame.subarray(0, 3))).toThrow('Frame too short: 3 bytes');
+
+    const bare = frame.slice();
+    bare[0] = 0;
+    expect(() => decodeHighPerf(bare)).toThrow(WireError);
+
+    expect(() => decodeHighPerf(emptyFrame(HIGH_PERF_VERSION + 1))).toThrow(
+      `Unsupported frame version: expected ${HIGH_PERF_VERSION}, found ${HIGH_PERF_VERSION + 1}`,
+    );
+    expect(() => decodeHighPerf(frame.subarray(0, frame.length - 1))).toThrow(/Malformed frame payload/);
+  });
+});
@@ -0,0 +1,78 @@
+use crate::core::monitor::MonitorManager;
+use crate::core::SystemMetrics;
+use crate::services::HttpServer;
+use crate::tests::export_tests::sample_metrics;
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::sync::broadcast;
+
+/// Issues a GET for `path` and returns the status code and body
+async fn get(addr: SocketAddr, path: &str) -> (u16, String) {
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    let request = format!("GET {} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n", path);
+    stream.write_all(request.as_bytes()).await.unwrap();
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await.unwrap();
+    let (head, body) = response.split_once("\r\n\r\n").unwrap();
+    let status = head.split_whitespace().nth(1).unwrap().parse().unwrap();
+    (status, body.to_string())
+}
+
+#[tokio::test]
+async fn test_serves_latest_sample() {
+    let (sender, _) = broadcast::channel(16);
+    let server = HttpServer::bind("127.0.0.1:0", &sender, MonitorManager::new()).await.unwrap();
+    let addr = server.local_addr().unwrap();
+    tokio::spawn(server.run());
+
+    // Nothing has been broadcast yet
+    assert_eq!(get(addr, "/metrics").await.0, 503);
+    assert_eq!(get(addr, "/metrics.json").await.0, 503);
+
+    sender.send(sample_metrics()).unwrap();
+    let body = tokio::time::timeout(Duration::from_secs(5), async {
+        loop {
+            match get(addr, "/metrics").await {
+                (200, body) => break body,
+                _ => tokio::time::sleep(Duration::from_millis(10)).await,
+            }
+        }
+    })
+    .await
+    .unwrap();
+    assert!(body.contains("system_cpu_usage_percent 42.5"));
+
+    let (status, json) = get(addr, "/metrics.json").await;
+    assert_eq!(status, 200);
+    assert_eq!(SystemMetrics::from_json(&json).unwrap().cpu.usage_percent, 42.5);
+
+    let (status, health) = get(addr, "/health").await;
+    assert_eq!(status, 200);
+    assert_eq!(health, "{}");
+
+    assert_eq!(get(addr, "/missing").await.0, 404);
+}
+
+#[tokio::test]
+async fn test_apply_config_starts_and_stops_server() {
+    let service = crate::services::MonitoringService::new();
+    let mut config = crate::core::AppConfig::default();
+    config.http_server.enabled = true;
+    config.http_server.address = "127.0.0.1:0".to_string();
+
+    service.apply_config(&config).await.unwrap();
+    let addr = service.http_server_addr().await.unwrap();
+    assert_eq!(get(addr, "/health").await.0, 200);
+
+    // Reapplying the same settings keeps the running server
+    service.apply_config(&config).await.unwrap();
+    assert_eq!(service.http_server_addr().await, Some(addr));
+
+    config.http_server.enabled = false;
+    service.apply_config(&config).await.unwrap();
+    assert_eq!(service.http_server_addr().await, None);
+    assert!(TcpStream::connect(addr).await.is_err());
+}
@@ -1,4 +1,7 @@
 use crate::services::MonitoringService;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use tokio::sync::broadcast;
 use tokio::time::{timeout, Duration};
 
 #[tokio::test]
@@ -106,4 +109,218 @@ async fn test_metrics_collection_consistency() {
 // mod mock_tests {
 //     use super::*;
 //     use mockall::mock;
-// }
\ No newline at end of file
+// }
+#[test]
+fn test_wsl_release_detection() {
+    use crate::backend::environment::is_wsl_release;
+
+    assert!(is_wsl_release("5.15.90.1-microsoft-standard-WSL2\n"));
+    assert!(is_wsl_release("4.4.0-19041-Microsoft"));
+    assert!(!is_wsl_release("6.5.0-14-generic"));
+}
+
+#[test]
+fn test_metric_category_set() {
+    use crate::core::{MetricCategory, MetricCategorySet};
+
+    let set: MetricCategorySet = [MetricCategory::Cpu, MetricCategory::Memory].into_iter().collect();
+    assert!(set.contains(MetricCategory::Cpu));
+    assert!(set.contains(MetricCategory::Memory));
+    assert!(!set.contains(MetricCategory::Process));
+    assert!(!set.is_all());
+    assert!(MetricCategorySet::all().contains(MetricCategory::Process));
+
+    let every: MetricCategorySet = [
+        MetricCategory::Cpu,
+        MetricCategory::Memory,
+        MetricCategory::Gpu,
+        MetricCategory::Disk,
+        MetricCategory::Network,
+        MetricCategory::Process,
+        MetricCategory::Battery,
+        MetricCategory::Sensor,
+    ]
+    .into_iter()
+    .collect();
+    assert!(every.is_all());
+}
+
+#[test]
+fn test_filtered_empties_unwanted_categories() {
+    use super::export_tests::{sample_metrics, sample_process};
+    use crate::core::{MetricCategory, MetricCategorySet, SystemMetrics};
+
+    let metrics = SystemMetrics {
+        top_processes: vec![sample_process(42, "worker")],
+        ..sample_metrics()
+    };
+    let filtered = metrics.filtered(MetricCategorySet::empty().with(MetricCategory::Cpu));
+
+    assert_eq!(filtered.cpu.usage_percent, metrics.cpu.usage_percent);
+    assert!(filtered.top_processes.is_empty());
+    assert!(filtered.disks.is_empty());
+    assert!(filtered.networks.is_empty());
+    assert_eq!(filtered.memory.used_bytes, 0);
+    assert_eq!(filtered.system_info.hostname, metrics.system_info.hostname);
+}
+
+#[tokio::test]
+async fn test_predicate_subscription_forwards_first_sample_only_when_rejecting() {
+    let service = MonitoringService::new();
+    timeout(Duration::from_secs(5), service.initialize()).await.unwrap().unwrap();
+
+    let evaluated = Arc::new(AtomicUsize::new(0));
+    let counter = evaluated.clone();
+    let mut receiver = service.subscribe_when(move |_, _| {
+        counter.fetch_add(1, Ordering::SeqCst);
+        false
+    });
+    timeout(Duration::from_secs(5), service.start()).await.unwrap().unwrap();
+
+    timeout(Duration::from_secs(10), receiver.recv()).await.unwrap().unwrap();
+
+    // The predicate only runs on later samples, and each one was rejected
+    timeout(Duration::from_secs(10), async {
+        while evaluated.load(Ordering::SeqCst) == 0 {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    })
+    .await
+    .unwrap();
+    assert!(matches!(receiver.try_recv(), Err(broadcast::error::TryRecvError::Empty)));
+
+    service.stop().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_lag_stats_count_samples_a_slow_subscriber_missed() {
+    let mut config = crate::core::AppConfig::default();
+    config.monitoring.channel_capacity = 1;
+    let service = MonitoringService::from_config(&config);
+    timeout(Duration::from_secs(5), service.initialize()).await.unwrap().unwrap();
+    let _never_read = service.subscribe();
+    timeout(Duration::from_secs(5), service.start()).await.unwrap().unwrap();
+
+    // The CPU monitor's interval makes a broadcast at least every 500 ms
+    let dropped = timeout(Duration::from_secs(5), async {
+        while service.lag_stats().total_dropped == 0 {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+    })
+    .await;
+    assert!(dropped.is_ok());
+    assert_eq!(service.lag_stats().max_lag_observed, 1);
+
+    service.stop().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_collect_each_isolates_failing_monitors() {
+    use crate::backend::{CpuMonitor, MemoryMonitor};
+    use crate::core::monitor::MonitorManager;
+    use crate::core::{Monitor, MonitorConfig};
+
+    let manager = MonitorManager::new();
+    let mut memory = MemoryMonitor::new();
+    memory.initialize(MonitorConfig::default()).await.unwrap();
+    manager.register_monitor("memory".to_string(), Box::new(memory)).await.unwrap();
+    // Never initialized, so every collection fails
+    manager.register_monitor("cpu".to_string(), Box::new(CpuMonitor::new())).await.unwrap();
+
+    let results = manager.collect_each().await;
+    assert!(results["memory"].is_ok());
+    assert!(results["cpu"].is_err());
+
+    let all = manager.collect_all_metrics().await.unwrap();
+    assert!(all.contains_key("memory"));
+    assert!(!all.contains_key("cpu"));
+}
+
+#[tokio::test]
+async fn test_health_counts_consecutive_failures() {
+    use crate::backend::{CpuMonitor, MemoryMonitor};
+    use crate::core::monitor::MonitorManager;
+    use crate::core::{Monitor, MonitorConfig, MonitorState};
+
+    let manager = MonitorManager::new();
+    let mut memory = MemoryMonitor::new();
+    memory.initialize(MonitorConfig::default()).await.unwrap();
+    memory.start().await.unwrap();
+    manager.register_monitor("memory".to_string(), Box::new(memory)).await.unwrap();
+    manager.register_monitor("cpu".to_string(), Box::new(CpuMonitor::new())).await.unwrap();
+
+    manager.collect_each().await;
+    manager.collect_each().await;
+
+    let health = manager.health_each().await;
+    assert_eq!(health["memory"].state, MonitorState::Running);
+    assert_eq!(health["memory"].consecutive_failures, 0);
+    assert!(health["memory"].last_error.is_none());
+    assert_eq!(health["cpu"].state, MonitorState::Uninitialized);
+    assert_eq!(health["cpu"].consecutive_failures, 2);
+    assert!(health["cpu"].last_error.is_some());
+}
+
+#[tokio::test]
+async fn test_disabled_monitors_are_paused_and_skipped() {
+    use crate::backend::{CpuMonitor, MemoryMonitor};
+    use crate::core::monitor::MonitorManager;
+    use crate::core::{Monitor, MonitorConfig, MonitorState};
+
+    let manager = MonitorManager::new();
+    let mut memory = MemoryMonitor::new();
+    memory.initialize(MonitorConfig::default()).await.unwrap();
+    memory.start().await.unwrap();
+    manager.register_monitor("memory".to_string(), Box::new(memory)).await.unwrap();
+    let mut cpu = CpuMonitor::new();
+    cpu.initialize(MonitorConfig::default()).await.unwrap();
+    cpu.start().await.unwrap();
+    manager.register_monitor("cpu".to_string(), Box::new(cpu)).await.unwrap();
+
+    manager.set_monitor_enabled("cpu", false).await.unwrap();
+    assert!(!manager.is_monitor_enabled("cpu").await);
+    assert_eq!(manager.enabled_monitor_names().await, vec!["memory".to_string()]);
+    let results = manager.collect_each().await;
+    assert!(results.contains_key("memory"));
+    assert!(!results.contains_key("cpu"));
+    assert_eq!(manager.health_each().await["cpu"].state, MonitorState::Paused);
+
+    manager.set_monitor_enabled("cpu", true).await.unwrap();
+    assert_eq!(manager.health_each().await["cpu"].state, MonitorState::Running);
+    assert!(manager.collect_each().await["cpu"].is_ok());
+
+    assert!(manager.set_monitor_enabled("gpu", false).await.is_err());
+
+    manager.unregister_monitor("cpu").await.unwrap();
+    assert_eq!(manager.monitor_names().await, vec!["memory".to_string()]);
+    assert!(manager.get_monitor("cpu").await.is_none());
+}
+
+#[tokio::test]
+async fn test_collect_once_without_starting() {
+    let service = MonitoringService::new();
+    timeout(Duration::from_secs(5), service.initialize()).await.unwrap().unwrap();
+
+    let metrics = timeout(Duration::from_secs(10), service.collect_once()).await.unwrap().unwrap();
+    assert!(!metrics.system_info.hostname.is_empty());
+    assert!(metrics.memory.total_bytes > 0);
+    assert!(!metrics.cpu.per_core_usage.is_empty());
+    assert!(!metrics.top_processes.is_empty());
+}
+
+#[tokio::test]
+async fn test_disks_and_networks_sorted_with_ids() {
+    let service = MonitoringService::new();
+    timeout(Duration::from_secs(5), service.initialize()).await.unwrap().unwrap();
+
+    for _ in 0..2 {
+        let metrics = timeout(Duration::from_secs(10), service.collect_once()).await.unwrap().unwrap();
+        assert!(metrics.disks.windows(2).all(|pair| pair[0].mount_point <= pair[1].mount_point));
+        assert!(metrics.networks.windows(2).all(|pair| pair[0].interface_name <= pair[1].interface_name));
+        assert!(metrics.networks.iter().all(|network| network.id == network.interface_name));
+        assert!(metrics
+            .disks
+            .iter()
+            .all(|disk| disk.id == crate::core::disk_id(&disk.device_name, &disk.mount_point)));
+    }
+}
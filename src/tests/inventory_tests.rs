@@ -0,0 +1,56 @@
+use crate::backend::inventory::{
+    parse_hex_id, parse_lspci_names, parse_lsusb, parse_wmic_devices, pnp_id_field, HardwareInventory,
+};
+
+#[test]
+fn test_linux_device_parsing() {
+    assert_eq!(parse_hex_id("0x10de\n"), Some(0x10de));
+    assert_eq!(parse_hex_id("0x030000\n"), Some(0x030000));
+    assert_eq!(parse_hex_id("garbage"), None);
+
+    let lspci = "0000:00:02.0 \"VGA compatible controller\" \"Intel Corporation\" \"UHD Graphics 620\" -r07 \"Lenovo\" \"ThinkPad\"\n\
+                 0000:01:00.0 \"3D controller\" \"NVIDIA Corporation\" \"GP108M [GeForce MX150]\" -ra1 \"Lenovo\" \"\"\n";
+    assert_eq!(
+        parse_lspci_names(lspci),
+        vec![
+            ("0000:00:02.0".to_string(), "Intel Corporation UHD Graphics 620".to_string()),
+            ("0000:01:00.0".to_string(), "NVIDIA Corporation GP108M [GeForce MX150]".to_string()),
+        ]
+    );
+
+    let lsusb = "Bus 001 Device 003: ID 046d:c52b Logitech, Inc. Unifying Receiver\n\
+                 Bus 002 Device 001: ID 1d6b:0003\n";
+    let devices = parse_lsusb(lsusb);
+    assert_eq!(devices.len(), 2);
+    assert_eq!(devices[0].address, "Bus 001 Device 003");
+    assert_eq!((devices[0].vendor_id, devices[0].product_id), (0x046d, 0xc52b));
+    assert_eq!(devices[0].name.as_deref(), Some("Logitech, Inc. Unifying Receiver"));
+    assert_eq!(devices[1].name, None);
+}
+
+#[test]
+fn test_windows_device_parsing() {
+    let pci = "PCI\\VEN_10DE&DEV_2484&SUBSYS_146B10DE&REV_A1\\4&2A1B3C4D&0&0008";
+    assert_eq!(pnp_id_field(pci, "VEN_"), Some(0x10de));
+    assert_eq!(pnp_id_field(pci, "DEV_"), Some(0x2484));
+    assert_eq!(pnp_id_field("PCIIDE\\IDECHANNEL\\4&1", "VEN_"), None);
+    assert_eq!(pnp_id_field("USB\\VID_046D&PID_C52B\\5&1", "PID_"), Some(0xc52b));
+
+    let wmic = "\r\nNode,DeviceID,Name\r\n\
+                HOST,USB\\VID_046D&PID_C52B\\5&1,Logitech USB Input Device, Receiver\r\n\
+                HOST,USB\\ROOT_HUB30\\4&2,\r\n";
+    let rows = parse_wmic_devices(wmic);
+    assert_eq!(rows.len(), 2);
+    assert_eq!(rows[0].1.as_deref(), Some("Logitech USB Input Device, Receiver"));
+    assert_eq!(rows[1], ("USB\\ROOT_HUB30\\4&2".to_string(), None));
+}
+
+#[test]
+fn test_inventory_snapshot() {
+    let inventory = HardwareInventory::new().collect();
+    assert!(inventory.pci_devices.iter().all(|d| !d.address.is_empty()));
+    assert!(inventory.usb_devices.iter().all(|d| !d.address.is_empty()));
+
+    let json = serde_json::to_string(&inventory).unwrap();
+    assert!(json.contains("\"pci_devices\""));
+}
@@ -30,11 +30,9 @@ async fn test_memory_monitor_collection() {
     // Verify memory values are reasonable
     for metric in metrics.iter() {
         match &metric.metric_type {
-            MetricType::MemoryUsage => {
-                if metric.tags.is_empty() {
-                    if let MetricValue::Float(usage) = metric.value {
-                        assert!(usage >= 0.0 && usage <= 100.0);
-                    }
+            MetricType::MemoryUsage if metric.tags.is_empty() => {
+                if let MetricValue::Percentage(usage) = metric.value {
+                    assert!((0.0..=100.0).contains(&usage));
                 }
             }
             MetricType::MemoryAvailable => {
@@ -91,4 +89,178 @@ async fn test_memory_metrics_consistency() {
     assert!(available_memory > 0);
     assert!(used_memory <= total_memory);
     assert!(available_memory <= total_memory);
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_parse_sysfs_selection() {
+    use crate::backend::memory_monitor::parse_sysfs_selection;
+
+    assert_eq!(parse_sysfs_selection("always [madvise] never\n"), Some("madvise".to_string()));
+    assert_eq!(
+        parse_sysfs_selection("always defer defer+madvise [madvise] never"),
+        Some("madvise".to_string())
+    );
+    assert_eq!(parse_sysfs_selection("[always] madvise never"), Some("always".to_string()));
+    assert_eq!(parse_sysfs_selection("always madvise never"), None);
+}
+
+#[tokio::test]
+async fn test_anon_hugepages_metric() {
+    let mut monitor = MemoryMonitor::new();
+    monitor.initialize(MonitorConfig::default()).await.unwrap();
+    let metrics = monitor.collect().await.unwrap();
+
+    assert!(metrics.iter().any(|m| {
+        matches!(m.metric_type, MetricType::Memory)
+            && m.tags.get("type").map(String::as_str) == Some("anon_hugepages")
+    }));
+}
+
+#[test]
+fn test_parse_buddyinfo_and_unusable_index() {
+    use crate::backend::memory_monitor::{parse_buddyinfo, unusable_index};
+
+    let buddyinfo = "\
+Node 0, zone      DMA      1      1      1      0      2      1      1      0      1      1      3
+Node 0, zone    DMA32      8      6      5      4      3      2      1      0      0      0      0
+Node 0, zone   Normal   2750   3134    844    202     63     24      5      1      2      0      6
+";
+    let orders = parse_buddyinfo(buddyinfo);
+    assert_eq!(orders.len(), 11);
+    assert_eq!(orders[0], 2759);
+    assert_eq!(orders[10], 9);
+
+    // All free memory in order-0 pages cannot serve a huge page
+    assert_eq!(unusable_index(&[100, 0, 0], 2), 1.0);
+    // All free memory in order-2 blocks can
+    assert_eq!(unusable_index(&[0, 0, 25], 2), 0.0);
+    assert_eq!(unusable_index(&[], 9), 0.0);
+}
+
+#[test]
+fn test_parse_vmstat_fragmentation() {
+    use crate::backend::memory_monitor::parse_vmstat_fragmentation;
+    use crate::core::MemoryFragmentation;
+
+    let vmstat = "\
+nr_free_pages 123
+allocstall_dma 0
+allocstall_normal 7
+allocstall_movable 3
+compact_stall 12
+compact_fail 4
+compact_success 8
+thp_fault_fallback 2
+";
+    let mut fragmentation = MemoryFragmentation::default();
+    parse_vmstat_fragmentation(vmstat, &mut fragmentation);
+
+    assert_eq!(fragmentation.allocstall, 10);
+    assert_eq!(fragmentation.compact_stall, 12);
+    assert_eq!(fragmentation.compact_fail, 4);
+    assert_eq!(fragmentation.compact_success, 8);
+    assert_eq!(fragmentation.thp_fault_fallback, 2);
+}
+
+#[test]
+fn test_parse_vmstat_paging() {
+    use crate::backend::memory_monitor::{parse_vmstat_paging, PagingCounters};
+
+    let vmstat = "\
+nr_free_pages 123
+pswpin 40
+pswpout 75
+pgfault 981234
+pgmajfault 321
+pgmajfault_s 9
+";
+    assert_eq!(
+        parse_vmstat_paging(vmstat),
+        PagingCounters {
+            page_faults: Some(981234),
+            major_faults: Some(321),
+            pages_in: Some(40),
+            pages_out: Some(75),
+        }
+    );
+    assert_eq!(parse_vmstat_paging("pgfault 10\n").pages_in, None);
+}
+
+#[tokio::test]
+async fn test_memory_monitor_reconfigure_keeps_history() {
+    let mut monitor = MemoryMonitor::new();
+    monitor.initialize(MonitorConfig::default()).await.unwrap();
+    monitor.collect().await.unwrap();
+    monitor.pause().await.unwrap();
+
+    let before = monitor.get_historical_metrics(60).await.unwrap().len();
+    assert!(before > 0);
+
+    let config = MonitorConfig {
+        interval_ms: 500,
+        ..Default::default()
+    };
+    monitor.reconfigure(config).await.unwrap();
+
+    assert_eq!(monitor.state(), MonitorState::Paused);
+    assert_eq!(monitor.get_historical_metrics(60).await.unwrap().len(), before);
+}
+
+#[test]
+fn test_parse_cache_usage() {
+    use crate::backend::memory_monitor::{parse_meminfo_cache, parse_vm_stat_cache};
+
+    let meminfo = "MemTotal:       16303428 kB\nBuffers:          204800 kB\nCached:          4096000 kB\nSwapCached:        10240 kB\nSReclaimable:     512000 kB\n";
+    assert_eq!(parse_meminfo_cache(meminfo), (4_608_000 * 1024, 204_800 * 1024));
+    assert_eq!(parse_meminfo_cache(""), (0, 0));
+
+    let vm_stat = "Mach Virtual Memory Statistics: (page size of 16384 bytes)\nPages free:                               12345.\nPages purgeable:                            100.\nFile-backed pages:                          900.\n";
+    assert_eq!(parse_vm_stat_cache(vm_stat), Some(1000 * 16384));
+    assert_eq!(parse_vm_stat_cache("garbage"), None);
+}
+
+#[tokio::test]
+async fn test_memory_monitor_history_range() {
+    use std::time::{Duration, SystemTime};
+
+    let mut monitor = MemoryMonitor::new();
+    monitor.initialize(MonitorConfig::default()).await.unwrap();
+
+    let start = SystemTime::now();
+    monitor.collect().await.unwrap();
+    monitor.collect().await.unwrap();
+    let end = SystemTime::now();
+
+    let samples = monitor.history_range(start, end).await;
+    assert_eq!(samples.len(), 2);
+    assert!(samples.windows(2).all(|pair| pair[0].0 <= pair[1].0));
+    for (at, metrics) in &samples {
+        assert!((start..=end).contains(at));
+        assert!(metrics.iter().any(|m| matches!(m.metric_type, MetricType::MemoryUsage)));
+    }
+
+    let earlier = start - Duration::from_secs(60);
+    assert!(monitor.history_range(earlier, earlier).await.is_empty());
+}
+
+#[tokio::test]
+async fn test_monitor_aggregate_window() {
+    use crate::core::MockClock;
+    use std::time::Duration;
+
+    let clock = MockClock::default();
+    let mut monitor = MemoryMonitor::with_clock(clock.shared());
+    monitor.initialize(MonitorConfig { interval_ms: 1000, ..MonitorConfig::default() }).await.unwrap();
+    for _ in 0..3 {
+        monitor.collect().await.unwrap();
+        clock.advance(Duration::from_secs(2));
+    }
+
+    let all = monitor.aggregate(MetricType::MemoryUsage, 300).await.unwrap();
+    assert_eq!(all.sample_count, 3);
+    assert!(all.min <= all.p95 && all.p95 <= all.max);
+    // Windows follow the sample timestamps: the newest is 2s old
+    assert_eq!(monitor.aggregate(MetricType::MemoryUsage, 1).await, None);
+    assert_eq!(monitor.aggregate(MetricType::MemoryUsage, 3).await.unwrap().sample_count, 1);
+    assert_eq!(monitor.aggregate(MetricType::CpuUsage, 300).await, None);
+}
@@ -0,0 +1,23 @@
+
+use crate::core::MetricValue;
+use std::time::Duration;
+
+#[test]
+fn test_percentage_and_duration_values() {
+    assert!(matches!(MetricValue::percentage(104.2), MetricValue::Percentage(v) if v == 100.0));
+    assert!(matches!(MetricValue::percentage(-1.0), MetricValue::Percentage(v) if v == 0.0));
+    assert!(matches!(MetricValue::percentage(f64::NAN), MetricValue::Percentage(v) if v == 0.0));
+    assert!(MetricValue::try_percentage(100.5).is_err());
+    assert!(MetricValue::try_percentage(f64::NAN).is_err());
+
+    let usage = MetricValue::try_percentage(42.5).unwrap();
+    assert_eq!(usage.as_f64(), Some(42.5));
+    assert_eq!(usage.to_string(), "42.50%");
+
+    let elapsed = MetricValue::Duration(Duration::from_millis(1500));
+    assert_eq!(elapsed.as_f64(), Some(1.5));
+    assert_eq!(elapsed.to_string(), "1.500s");
+
+    let json = serde_json::to_string(&usage).unwrap();
+    assert!(matches!(serde_json::from_str(&json).unwrap(), MetricValue::Percentage(v) if v == 42.5));
+}
@@ -11,4 +11,48 @@ mod network_monitor_tests;
 #[cfg(test)]
 mod process_monitor_tests;
 #[cfg(test)]
-mod integration_tests;
\ No newline at end of file
+mod integration_tests;
+#[cfg(test)]
+mod alert_engine_tests;
+#[cfg(test)]
+mod redaction_tests;
+#[cfg(test)]
+mod policy_tests;
+#[cfg(test)]
+mod change_filter_tests;
+#[cfg(test)]
+mod export_tests;
+#[cfg(test)]
+mod alerts_tests;
+#[cfg(test)]
+mod recorder_tests;
+#[cfg(test)]
+mod replay_tests;
+#[cfg(test)]
+mod battery_monitor_tests;
+#[cfg(test)]
+mod sensors_tests;
+#[cfg(test)]
+mod process_tree_tests;
+#[cfg(test)]
+mod units_tests;
+#[cfg(all(test, feature = "ws-server"))]
+mod ws_server_tests;
+#[cfg(all(test, feature = "http-server"))]
+mod http_server_tests;
+#[cfg(all(test, feature = "otlp"))]
+mod otlp_exporter_tests;
+#[cfg(test)]
+mod inventory_tests;
+#[cfg(test)]
+mod smoothing_tests;
+#[cfg(test)]
+mod error_tests;
+#[cfg(test)]
+mod monitor_tests;
+#[cfg(test)]
+mod schedule_tests;
+#[cfg(test)]
+mod metrics_tests;
+#[cfg(test)]
+mod monitoring_service_tests;
@@ -0,0 +1,47 @@
+
+use crate::core::{Aggregate, MonitorError, MonitorState, Result};
+
+#[test]
+fn test_aggregate_from_values() {
+    assert_eq!(Aggregate::from_values(Vec::new()), None);
+
+    let single = Aggregate::from_values([7.0]).unwrap();
+    assert_eq!((single.min, single.max, single.mean, single.p95), (7.0, 7.0, 7.0, 7.0));
+
+    let stats = Aggregate::from_values((1..=100).rev().map(f64::from)).unwrap();
+    assert_eq!(stats.min, 1.0);
+    assert_eq!(stats.max, 100.0);
+    assert_eq!(stats.mean, 50.5);
+    assert_eq!(stats.p95, 95.0);
+    assert_eq!(stats.sample_count, 100);
+}
+
+#[test]
+fn test_degraded_state_transitions() {
+    let failure: Result<()> = Err(MonitorError::GpuError("NVML timeout".to_string()));
+    let retry: Result<()> = Err(MonitorError::GpuError("NVML lost".to_string()));
+
+    let mut state = MonitorState::Running;
+    state.record_collection(&failure);
+    let since = match &state {
+        MonitorState::Degraded { since, reason } => {
+            assert!(reason.contains("NVML timeout"));
+            *since
+        }
+        other => panic!("expected Degraded, got {:?}", other),
+    };
+    assert!(state.is_collecting());
+
+    // Repeated failures keep the original start time but the newest reason
+    state.record_collection(&retry);
+    assert_eq!(state, MonitorState::Degraded { since, reason: "GPU error: NVML lost".to_string() });
+
+    state.record_collection(&Ok(()));
+    assert_eq!(state, MonitorState::Running);
+
+    // Only collecting monitors degrade
+    let mut paused = MonitorState::Paused;
+    paused.record_collection(&failure);
+    assert_eq!(paused, MonitorState::Paused);
+    assert!(!paused.is_collecting());
+}
@@ -0,0 +1,33 @@
+use crate::core::{MetricType, MonitorState};
+use crate::services::MonitoringService;
+use tokio::time::{timeout, Duration};
+
+#[tokio::test]
+async fn test_run_until_stops_monitors() {
+    let service = MonitoringService::new();
+    timeout(Duration::from_secs(5), service.initialize()).await.unwrap().unwrap();
+
+    let mut receiver = service.subscribe();
+    let first_sample = async move {
+        let _ = receiver.recv().await;
+    };
+    timeout(Duration::from_secs(10), service.run_until(first_sample)).await.unwrap().unwrap();
+
+    let health = service.health_report().await;
+    assert!(!health.is_empty());
+    assert!(health.values().all(|h| h.state == MonitorState::Stopped));
+}
+
+#[tokio::test]
+async fn test_capabilities_after_collection() {
+    let service = MonitoringService::new();
+    timeout(Duration::from_secs(5), service.initialize()).await.unwrap().unwrap();
+    timeout(Duration::from_secs(10), service.collect_once()).await.unwrap().unwrap();
+
+    let capabilities = service.capabilities().await;
+    assert!(capabilities["cpu"].contains(&MetricType::CpuUsage));
+    assert!(capabilities["memory"].contains(&MetricType::MemoryUsage));
+    assert!(capabilities.values().all(|types| {
+        types.iter().enumerate().all(|(i, t)| !types[..i].contains(t))
+    }));
+}
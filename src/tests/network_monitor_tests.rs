@@ -1,6 +1,9 @@
 use crate::backend::NetworkMonitor;
-use crate::core::{Monitor, MonitorConfig, MonitorState, MetricType, MetricValue};
+use crate::core::{AddrScope, IpAddress, Monitor, MonitorConfig, MonitorState, MetricType, MetricValue};
 
+fn ip(text: &str) -> IpAddress {
+    IpAddress::parse(text).unwrap()
+}
 
 #[tokio::test]
 async fn test_network_monitor_initialization() {
@@ -69,9 +72,349 @@ async fn test_network_throughput_calculation() {
     // Verify throughput values are reasonable
     for metric in throughput_metrics {
         if let MetricValue::Unsigned(bytes_per_sec) = metric.value {
-            // Throughput should be non-negative
-            // bytes_per_sec is u64, so it's always >= 0
-            assert!(bytes_per_sec >= 0u64);
+            // bytes_per_sec is u64, so it's always non-negative; guard against
+            // counter-wrap artifacts producing absurd rates instead
+            assert!(bytes_per_sec < 1_000_000_000_000);
         }
     }
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_parse_ifconfig() {
+    use crate::backend::network_monitor::parse_ifconfig;
+
+    let output = "\
+en0: flags=8863<UP,BROADCAST,SMART,RUNNING,SIMPLEX,MULTICAST> mtu 1500
+\toptions=6463<RXCSUM,TXCSUM,TSO4,TSO6,CHANNEL_IO,PARTIAL_CSUM,ZEROINVERT_CSUM>
+\tether 3C:22:FB:12:34:56
+\tinet6 fe80::1c2d:3e4f:5a6b:7c8d%en0 prefixlen 64 secured scopeid 0x6
+\tinet 192.168.1.23 netmask 0xffffff00 broadcast 192.168.1.255
+\tmedia: autoselect (1000baseT <full-duplex>)
+\tstatus: active
+";
+    let (is_up, mac, ips, speed) = parse_ifconfig(output);
+    assert_eq!(is_up, Some(true));
+    assert_eq!(mac.as_deref(), Some("3c:22:fb:12:34:56"));
+    assert_eq!(
+        ips,
+        vec![ip("fe80::1c2d:3e4f:5a6b:7c8d/64"), ip("192.168.1.23/24")]
+    );
+    assert_eq!(ips[0].scope, AddrScope::LinkLocal);
+    assert_eq!(ips[1].scope, AddrScope::Private);
+    assert_eq!(speed, Some(1000));
+
+    let (is_up, mac, ips, _) = parse_ifconfig("en1: flags=8822<BROADCAST,SMART,SIMPLEX,MULTICAST> mtu 1500\n");
+    assert_eq!(is_up, Some(false));
+    assert_eq!(mac, None);
+    assert!(ips.is_empty());
+}
+
+#[test]
+fn test_windows_adapter_fields() {
+    use crate::backend::network_monitor::{format_mac, link_speed_mbps};
+
+    assert_eq!(format_mac(&[0x00, 0x1a, 0x2b, 0x3c, 0x4d, 0x5e]), "00:1a:2b:3c:4d:5e");
+    assert_eq!(format_mac(&[]), "");
+
+    assert_eq!(link_speed_mbps(1_000_000_000), Some(1000));
+    assert_eq!(link_speed_mbps(0), None);
+    assert_eq!(link_speed_mbps(u64::MAX), None);
+}
+
+#[test]
+fn test_parse_ip_addr_show_and_scopes() {
+    use crate::backend::network_monitor::parse_ip_addr_show;
+
+    let output = "\
+2: eth0: <BROADCAST,MULTICAST,UP,LOWER_UP> mtu 1500 qdisc fq_codel state UP group default qlen 1000
+    link/ether 52:54:00:12:34:56 brd ff:ff:ff:ff:ff:ff
+    inet 10.0.2.15/24 brd 10.0.2.255 scope global dynamic eth0
+    inet 203.0.113.7/32 scope global eth0
+    inet6 2001:db8::1/64 scope global
+    inet6 fd12:3456::1/48 scope global
+    inet6 fe80::5054:ff:fe12:3456/64 scope link
+";
+    let scopes: Vec<(String, u8, AddrScope)> = parse_ip_addr_show(output)
+        .into_iter()
+        .map(|a| (a.addr.to_string(), a.prefix_len, a.scope))
+        .collect();
+    assert_eq!(
+        scopes,
+        vec![
+            ("10.0.2.15".to_string(), 24, AddrScope::Private),
+            ("203.0.113.7".to_string(), 32, AddrScope::Global),
+            ("2001:db8::1".to_string(), 64, AddrScope::Global),
+            ("fd12:3456::1".to_string(), 48, AddrScope::Private),
+            ("fe80::5054:ff:fe12:3456".to_string(), 64, AddrScope::LinkLocal),
+        ]
+    );
+
+    assert_eq!(ip("127.0.0.1").scope, AddrScope::Loopback);
+    assert_eq!(ip("::1").prefix_len, 128);
+    assert_eq!(ip("169.254.1.1/16").scope, AddrScope::LinkLocal);
+    assert!(IpAddress::parse("not-an-ip").is_none());
+
+    // Addresses serialize as plain strings
+    let json = serde_json::to_value(ip("10.0.0.1/8")).unwrap();
+    assert_eq!(json["addr"], "10.0.0.1");
+    assert_eq!(json["scope"], "Private");
+}
+
+#[test]
+fn test_interface_filtering() {
+    use crate::backend::network_monitor::{glob_match, interface_included, is_loopback_interface};
+
+    assert!(glob_match("en*", "en0"));
+    assert!(glob_match("eth?", "eth1"));
+    assert!(!glob_match("eth?", "eth10"));
+    assert!(glob_match("*br*", "virbr0"));
+    assert!(glob_match("*", ""));
+    assert!(!glob_match("en*", "wlo1"));
+
+    assert!(is_loopback_interface("lo"));
+    assert!(is_loopback_interface("lo0"));
+    assert!(is_loopback_interface("Loopback Pseudo-Interface 1"));
+    // Names that merely contain "lo" are real interfaces
+    assert!(!is_loopback_interface("wlo1"));
+    assert!(!is_loopback_interface("vlan100"));
+
+    let mut config = MonitorConfig::default();
+    assert!(!interface_included(&config, "lo"));
+    assert!(interface_included(&config, "docker0"));
+
+    config.include_loopback = true;
+    config.interface_filter = Some(vec!["en*".to_string(), "eth*".to_string(), "lo".to_string()]);
+    assert!(interface_included(&config, "eth0"));
+    assert!(interface_included(&config, "lo"));
+    assert!(!interface_included(&config, "docker0"));
+    assert!(!interface_included(&config, "veth1a2b3c"));
+}
+
+#[test]
+fn test_history_byte_budget() {
+    use crate::core::{trim_history, HistorySize, NetworkMetrics};
+    use std::collections::VecDeque;
+
+    let interface = |i: usize| NetworkMetrics {
+        id: format!("eth{}", i),
+        interface_name: format!("eth{}", i),
+        is_up: true,
+        mac_address: "00:11:22:33:44:55".to_string(),
+        ip_addresses: vec![ip("10.0.0.1/24")],
+        bytes_sent: 0,
+        bytes_received: 0,
+        packets_sent: 0,
+        packets_received: 0,
+        errors_sent: 0,
+        errors_received: 0,
+        speed_mbps: None,
+        bytes_sent_rate: 0,
+        bytes_received_rate: 0,
+        wireless: None,
+    };
+    let entry: Vec<NetworkMetrics> = (0..40).map(interface).collect();
+    let mut history: VecDeque<_> = (0..10).map(|_| entry.clone()).collect();
+    let entry_bytes = history[0].estimated_bytes();
+    assert!(entry_bytes > 40 * std::mem::size_of::<NetworkMetrics>());
+
+    trim_history(&mut history, None);
+    assert_eq!(history.len(), 10);
+
+    trim_history(&mut history, Some(entry_bytes * 3 + entry_bytes / 2));
+    assert_eq!(history.len(), 3);
+
+    // The newest entry survives even a budget it can't fit in
+    trim_history(&mut history, Some(1));
+    assert_eq!(history.len(), 1);
+}
+
+#[test]
+fn test_parse_tcp_states() {
+    use crate::backend::network_monitor::{parse_netstat_tcp, parse_proc_net_tcp};
+    use crate::core::TcpStats;
+
+    let tcp = "\
+  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 0100007F:0277 00000000:0000 0A 00000000:00000000 00:00000000 00000000     0        0 23071 1 0000000000000000 100 0 0 10 0
+   1: 0F02000A:0016 0202000A:C5E2 01 00000000:00000000 02:0009A4DA 00000000     0        0 41012 2 0000000000000000 20 4 31 10 -1
+   2: 0F02000A:B5A4 5DB8D822:01BB 06 00000000:00000000 03:00000D8B 00000000     0        0 0 3 0000000000000000
+   3: 0F02000A:B5A6 5DB8D822:01BB 08 00000000:00000000 00:00000000 00000000  1000        0 51234 1 0000000000000000 20 4 30 10 -1
+";
+    let tcp6 = "\
+  sl  local_address                         remote_address                        st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 00000000000000000000000000000000:0016 00000000000000000000000000000000:0000 0A 00000000:00000000 00:00000000 00000000     0        0 23073 1 0000000000000000 100 0 0 10 0
+";
+    let mut stats = TcpStats::default();
+    parse_proc_net_tcp(tcp, &mut stats);
+    parse_proc_net_tcp(tcp6, &mut stats);
+    assert_eq!(
+        stats,
+        TcpStats { established: 1, listen: 2, time_wait: 1, close_wait: 1, syn_sent: 0 }
+    );
+
+    let netstat = "\
+Active Internet connections (including servers)
+Proto Recv-Q Send-Q  Local Address          Foreign Address        (state)
+tcp4       0      0  192.168.1.23.52114     17.57.144.84.5223      ESTABLISHED
+tcp4       0      0  192.168.1.23.52120     140.82.112.25.443      SYN_SENT
+tcp6       0      0  *.22                   *.*                    LISTEN
+tcp4       0      0  127.0.0.1.52001        127.0.0.1.8080         TIME_WAIT
+";
+    assert_eq!(
+        parse_netstat_tcp(netstat),
+        TcpStats { established: 1, listen: 1, time_wait: 1, close_wait: 0, syn_sent: 1 }
+    );
+}
+
+#[test]
+fn test_parse_wireless_link() {
+    use crate::backend::network_monitor::{
+        parse_airport_info, parse_iw_link, parse_netsh_wlan, parse_proc_net_wireless, parse_wifi_ports,
+    };
+    use crate::core::WirelessInfo;
+
+    let iw = "\
+Connected to 3c:37:86:5a:1b:20 (on wlp2s0)
+\tSSID: HomeNet
+\tfreq: 5180.0
+\tRX: 187634 bytes (1043 packets)
+\tTX: 41235 bytes (286 packets)
+\tsignal: -52 dBm
+\trx bitrate: 433.3 MBit/s VHT-MCS 9 80MHz short GI VHT-NSS 1
+\ttx bitrate: 390.0 MBit/s VHT-MCS 8 80MHz short GI VHT-NSS 1
+";
+    assert_eq!(
+        parse_iw_link(iw),
+        Some(WirelessInfo {
+            ssid: "HomeNet".to_string(),
+            signal_dbm: -52,
+            link_quality_percent: 96.0,
+            frequency_mhz: 5180,
+            tx_rate_mbps: 390,
+        })
+    );
+    assert_eq!(parse_iw_link("Not connected.\n"), None);
+
+    let proc_wireless = "\
+Inter-| sta-|   Quality        |   Discarded packets               | Missed | WE
+ face | tus | link level noise |  nwid  crypt   frag  retry   misc | beacon | 22
+wlp2s0: 0000   56.  -54.  -256        0      0      0      0     12        0
+";
+    assert_eq!(parse_proc_net_wireless(proc_wireless, "wlp2s0"), Some((80.0, -54)));
+    assert_eq!(parse_proc_net_wireless(proc_wireless, "wlan1"), None);
+
+    let airport = "\
+     agrCtlRSSI: -61
+     agrExtRSSI: 0
+    agrCtlNoise: -92
+          state: running
+        op mode: station
+     lastTxRate: 585
+        maxRate: 867
+          BSSID: 3c:37:86:5a:1b:20
+           SSID: Office
+            MCS: 7
+        channel: 149,80
+";
+    assert_eq!(
+        parse_airport_info(airport),
+        Some(WirelessInfo {
+            ssid: "Office".to_string(),
+            signal_dbm: -61,
+            link_quality_percent: 78.0,
+            frequency_mhz: 5745,
+            tx_rate_mbps: 585,
+        })
+    );
+    assert_eq!(parse_airport_info("AirPort: Off\n"), None);
+
+    let netsh = "\r
+There is 1 interface on the system: \r
+\r
+    Name                   : Wi-Fi\r
+    Description            : Intel(R) Wi-Fi 6 AX201 160MHz\r
+    Physical address       : a4:c3:f0:12:34:56\r
+    State                  : connected\r
+    SSID                   : Cafe\r
+    Radio type             : 802.11ac\r
+    Channel                : 6\r
+    Receive rate (Mbps)    : 144.4\r
+    Transmit rate (Mbps)   : 130\r
+    Signal                 : 70% \r
+    Profile                : Cafe\r
+\r
+    Hosted network status  : Not available\r
+";
+    assert_eq!(
+        parse_netsh_wlan(netsh),
+        vec![(
+            "Wi-Fi".to_string(),
+            WirelessInfo {
+                ssid: "Cafe".to_string(),
+                signal_dbm: -65,
+                link_quality_percent: 70.0,
+                frequency_mhz: 2437,
+                tx_rate_mbps: 130,
+            }
+        )]
+    );
+    assert!(parse_netsh_wlan(&netsh.replace(": connected", ": disconnected")).is_empty());
+
+    let ports = "\
+Hardware Port: Ethernet
+Device: en0
+Ethernet Address: 3c:22:fb:00:00:01
+
+Hardware Port: Wi-Fi
+Device: en1
+Ethernet Address: 3c:22:fb:00:00:02
+";
+    assert_eq!(parse_wifi_ports(ports), vec!["en1".to_string()]);
+}
+
+#[test]
+fn test_byte_rates_follow_the_clock() {
+    use crate::core::MockClock;
+    use std::time::Duration;
+
+    let clock = MockClock::default();
+    let monitor = NetworkMonitor::with_clock(clock.shared());
+
+    assert_eq!(monitor.record_byte_counters("eth0", 1_000, 5_000), (0, 0));
+    clock.advance(Duration::from_secs(1));
+    assert_eq!(monitor.record_byte_counters("eth0", 3_500, 9_000), (2_500, 4_000));
+    clock.advance(Duration::from_millis(500));
+    assert_eq!(monitor.record_byte_counters("eth0", 4_000, 10_000), (1_000, 2_000));
+
+    // A counter reset reads as 0, as does a sample with no time elapsed
+    clock.advance(Duration::from_secs(1));
+    assert_eq!(monitor.record_byte_counters("eth0", 100, 11_000), (0, 1_000));
+    assert_eq!(monitor.record_byte_counters("eth0", 200, 11_500), (0, 0));
+
+    // Each interface is timed from its own previous sample
+    assert_eq!(monitor.record_byte_counters("wlan0", 0, 0), (0, 0));
+    clock.advance(Duration::from_secs(2));
+    assert_eq!(monitor.record_byte_counters("wlan0", 8_000, 2_000), (4_000, 1_000));
+    assert_eq!(monitor.record_byte_counters("eth0", 200, 17_500), (0, 3_000));
+}
+
+#[tokio::test]
+async fn test_history_is_timestamped_by_the_clock() {
+    use crate::core::{Clock, MockClock};
+    use std::time::{Duration, UNIX_EPOCH};
+
+    let clock = MockClock::new(UNIX_EPOCH + Duration::from_secs(1_000));
+    let mut monitor = NetworkMonitor::with_clock(clock.shared());
+    monitor.initialize(MonitorConfig::default()).await.unwrap();
+    monitor.collect().await.unwrap();
+    clock.advance(Duration::from_secs(1));
+    monitor.collect().await.unwrap();
+
+    let times: Vec<_> = monitor
+        .history_range(UNIX_EPOCH, clock.now())
+        .await
+        .into_iter()
+        .map(|(at, _)| at)
+        .collect();
+    assert_eq!(times, vec![UNIX_EPOCH + Duration::from_secs(1_000), UNIX_EPOCH + Duration::from_secs(1_001)]);
+}
@@ -0,0 +1,87 @@
+use crate::services::OtlpExporter;
+use crate::tests::export_tests::sample_metrics;
+use serde_json::Value;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio::sync::{broadcast, mpsc};
+
+/// Answers each request with the next of `statuses`, forwarding the
+/// request line and JSON body
+async fn fake_collector(statuses: Vec<u16>) -> (String, mpsc::UnboundedReceiver<(String, Value)>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let endpoint = format!("http://{}", listener.local_addr().unwrap());
+    let (requests, received) = mpsc::unbounded_channel();
+
+    tokio::spawn(async move {
+        for status in statuses {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buffer = [0u8; 4096];
+            let (head, body) = loop {
+                let read = stream.read(&mut buffer).await.unwrap();
+                request.extend_from_slice(&buffer[..read]);
+                let text = String::from_utf8_lossy(&request).to_string();
+                if let Some((head, body)) = text.split_once("\r\n\r\n") {
+                    let length: usize = head
+                        .lines()
+                        .find_map(|line| line.to_ascii_lowercase().strip_prefix("content-length:").map(|v| v.trim().parse().unwrap()))
+                        .unwrap_or(0);
+                    if body.len() >= length {
+                        break (head.to_string(), body.to_string());
+                    }
+                }
+            };
+
+            let response = format!("HTTP/1.1 {} Status\r\ncontent-length: 0\r\nconnection: close\r\n\r\n", status);
+            stream.write_all(response.as_bytes()).await.unwrap();
+            let request_line = head.lines().next().unwrap().to_string();
+            requests.send((request_line, serde_json::from_str(&body).unwrap())).unwrap();
+        }
+    });
+
+    (endpoint, received)
+}
+
+#[test]
+fn test_endpoint_validation() {
+    let (sender, _) = broadcast::channel(4);
+    let interval = Duration::from_secs(10);
+
+    let exporter = OtlpExporter::new("http://collector:4318", interval, &sender).unwrap();
+    assert_eq!(exporter.endpoint().to_string(), "http://collector:4318/v1/metrics");
+
+    let exporter = OtlpExporter::new("http://collector:4318/otlp/v1/metrics", interval, &sender).unwrap();
+    assert_eq!(exporter.endpoint().path(), "/otlp/v1/metrics");
+
+    assert!(OtlpExporter::new("https://collector:4318", interval, &sender).is_err());
+    assert!(OtlpExporter::new("not a uri", interval, &sender).is_err());
+    assert!(OtlpExporter::new("http://collector:4318", Duration::ZERO, &sender).is_err());
+}
+
+#[tokio::test]
+async fn test_retries_transient_failures() {
+    let (endpoint, mut requests) = fake_collector(vec![503, 200]).await;
+    let (sender, _) = broadcast::channel(4);
+    let exporter = OtlpExporter::new(&endpoint, Duration::from_millis(20), &sender).unwrap();
+    tokio::spawn(exporter.run());
+
+    sender.send(sample_metrics()).unwrap();
+
+    let timeout = Duration::from_secs(5);
+    let (request_line, failed) = tokio::time::timeout(timeout, requests.recv()).await.unwrap().unwrap();
+    assert_eq!(request_line, "POST /v1/metrics HTTP/1.1");
+
+    // The batch that got a 503 is sent again
+    let (_, retried) = tokio::time::timeout(timeout, requests.recv()).await.unwrap().unwrap();
+    assert_eq!(retried, failed);
+    let host = &retried["resourceMetrics"][0]["resource"]["attributes"];
+    assert!(host.as_array().unwrap().iter().any(|a| a["value"]["stringValue"] == "host"));
+}
+
+#[tokio::test]
+async fn test_service_attach_otlp() {
+    let service = crate::services::MonitoringService::new();
+    assert!(service.attach_otlp("ftp://collector", Duration::from_secs(1)).is_err());
+    assert!(service.attach_otlp("http://127.0.0.1:4318", Duration::from_secs(1)).is_ok());
+}
@@ -0,0 +1,120 @@
+use super::export_tests::sample_process;
+use crate::core::{GuardPolicyConfig, GuardResource, GuardRule, ProcessMetrics};
+use crate::services::policy::{GuardActionKind, GuardPolicy};
+use std::time::{Duration, SystemTime};
+
+fn process(pid: u32, name: &str, cpu: f32) -> ProcessMetrics {
+    ProcessMetrics {
+        cpu_usage_percent: cpu,
+        memory_bytes: 1024,
+        memory_percent: 1.0,
+        ..sample_process(pid, name)
+    }
+}
+
+fn cpu_policy(enabled: bool) -> GuardPolicy {
+    GuardPolicy::new(GuardPolicyConfig {
+        enabled,
+        rules: vec![GuardRule {
+            resource: GuardResource::Cpu,
+            threshold: 95.0,
+            sustained_seconds: 300,
+        }],
+        allowlist: vec!["Postgres".to_string()],
+        grace_period_seconds: 10,
+    })
+}
+
+#[test]
+fn test_guard_policy_is_opt_in() {
+    let mut policy = cpu_policy(false);
+    let start = SystemTime::now();
+
+    for i in 0..=300 {
+        let actions = policy.evaluate(&[process(4242, "miner", 100.0)], start + Duration::from_secs(i));
+        assert!(actions.is_empty());
+    }
+}
+
+#[test]
+fn test_guard_policy_requires_sustained_usage() {
+    let mut policy = cpu_policy(true);
+    let start = SystemTime::now();
+    let mut actions = Vec::new();
+
+    // A single dip below the threshold resets the sustained window
+    for i in 0..=300 {
+        let cpu = if i == 200 { 50.0 } else { 100.0 };
+        actions.extend(policy.evaluate(&[process(4242, "miner", cpu)], start + Duration::from_secs(i)));
+    }
+    assert!(actions.is_empty());
+
+    for i in 301..=505 {
+        actions.extend(policy.evaluate(&[process(4242, "miner", 100.0)], start + Duration::from_secs(i)));
+    }
+    assert_eq!(actions.len(), 1);
+    assert_eq!(actions[0].kind, GuardActionKind::Terminate);
+    assert_eq!(actions[0].pid, 4242);
+}
+
+#[test]
+fn test_guard_policy_escalates_and_respects_allowlist() {
+    let mut policy = cpu_policy(true);
+    let start = SystemTime::now();
+    let snapshot = [process(4242, "miner", 100.0), process(77, "postgres", 100.0)];
+    let mut actions = Vec::new();
+
+    for i in 0..=320 {
+        actions.extend(policy.evaluate(&snapshot, start + Duration::from_secs(i)));
+    }
+
+    // Allowlisted postgres is never touched; the miner gets TERM then KILL
+    assert!(actions.iter().all(|a| a.pid == 4242));
+    let kinds: Vec<_> = actions.iter().map(|a| a.kind).collect();
+    assert_eq!(kinds, vec![GuardActionKind::Terminate, GuardActionKind::Kill]);
+}
+
+#[test]
+fn test_guard_policy_does_not_kill_a_reused_pid() {
+    let mut policy = cpu_policy(true);
+    let start = SystemTime::now();
+    let miner = process(4242, "miner", 100.0);
+    let mut actions = Vec::new();
+
+    for i in 0..=300 {
+        actions.extend(policy.evaluate(std::slice::from_ref(&miner), start + Duration::from_secs(i)));
+    }
+    assert_eq!(actions.len(), 1);
+    assert_eq!(actions[0].kind, GuardActionKind::Terminate);
+
+    // The miner exited and an idle process got its PID before the grace period ran out
+    let successor = ProcessMetrics {
+        start_time: miner.start_time + Duration::from_secs(305),
+        ..process(4242, "editor", 1.0)
+    };
+    for i in 305..=320 {
+        assert!(policy.evaluate(std::slice::from_ref(&successor), start + Duration::from_secs(i)).is_empty());
+    }
+}
+
+#[test]
+fn test_guard_policy_kills_after_leaving_the_snapshot() {
+    let mut policy = cpu_policy(true);
+    let start = SystemTime::now();
+    let miner = process(4242, "miner", 100.0);
+    let mut actions = Vec::new();
+
+    for i in 0..=300 {
+        actions.extend(policy.evaluate(std::slice::from_ref(&miner), start + Duration::from_secs(i)));
+    }
+    assert_eq!(actions.len(), 1);
+
+    // SIGTERM lowered its usage enough to fall out of the top processes
+    let others = [process(77, "postgres", 100.0)];
+    for i in 301..=320 {
+        actions.extend(policy.evaluate(&others, start + Duration::from_secs(i)));
+    }
+    let kinds: Vec<_> = actions.iter().map(|a| (a.pid, a.kind)).collect();
+    assert_eq!(kinds, vec![(4242, GuardActionKind::Terminate), (4242, GuardActionKind::Kill)]);
+    assert_eq!(actions[1].start_time, miner.start_time);
+}
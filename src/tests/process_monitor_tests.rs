@@ -31,20 +31,15 @@ async fn test_process_monitor_collection() {
     
     // Verify process data is valid
     for metric in metrics.iter() {
-        match metric.metric_type {
-            MetricType::ProcessCpu => {
-                if let MetricValue::Float(cpu) = metric.value {
-                    assert!(cpu >= 0.0);
-                }
-            }
-            MetricType::ProcessMemory => {
-                if let MetricValue::Unsigned(memory) = metric.value {
-                    assert!(memory > 0);
-                }
-            }
-            _ => {}
+        if let (MetricType::ProcessCpu, MetricValue::Float(cpu)) = (&metric.metric_type, &metric.value) {
+            assert!(*cpu >= 0.0);
         }
     }
+    
+    // Kernel threads are listed too, with no resident memory of their own
+    assert!(metrics.iter().any(|m| {
+        matches!(m.metric_type, MetricType::ProcessMemory) && matches!(m.value, MetricValue::Unsigned(memory) if memory > 0)
+    }));
 }
 
 #[tokio::test]
@@ -75,4 +70,180 @@ async fn test_top_processes_limit() {
     
     // Should not exceed the configured limit
     assert!(pids.len() <= 5);
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_thread_count() {
+    use crate::backend::process_monitor::{parse_status_threads, thread_count};
+
+    let status = "Name:\tfirefox\nState:\tS (sleeping)\nTgid:\t4242\nThreads:\t87\nVmRSS:\t102400 kB\n";
+    assert_eq!(parse_status_threads(status), Some(87));
+    assert_eq!(parse_status_threads("Name:\tinit\n"), None);
+
+    // The test harness runs tests on worker threads, so there are at least two
+    #[cfg(target_os = "linux")]
+    assert!(thread_count(std::process::id()) >= 2);
+    assert!(thread_count(std::process::id()) >= 1);
+}
+
+#[test]
+fn test_cpu_time() {
+    use crate::backend::process_monitor::{cpu_time_seconds, parse_stat_cpu_ticks};
+
+    let stat = "4242 (Web Content (x)) S 1 4242 4242 0 -1 4194560 9530 0 0 0 1250 310 0 0 20 0 87 0 1337 0 0";
+    assert_eq!(parse_stat_cpu_ticks(stat), Some(1560));
+    assert_eq!(parse_stat_cpu_ticks("4242 (init) S 1"), None);
+    assert_eq!(parse_stat_cpu_ticks("garbage"), None);
+
+    #[cfg(any(target_os = "linux", target_os = "windows", target_os = "macos"))]
+    assert!(cpu_time_seconds(std::process::id()).unwrap() >= 0.0);
+}
+
+#[test]
+fn test_cpu_affinity() {
+    use crate::backend::process_monitor::{cpu_affinity, cpus_in_mask, format_cpu_list, parse_cpu_list, parse_status_cpus_allowed};
+
+    assert_eq!(parse_cpu_list("0-3,8,10-11"), Some(vec![0, 1, 2, 3, 8, 10, 11]));
+    assert_eq!(parse_cpu_list("5"), Some(vec![5]));
+    // As read from sysfs, e.g. a NUMA node's cpulist
+    assert_eq!(parse_cpu_list("0-3\n"), Some(vec![0, 1, 2, 3]));
+    assert_eq!(parse_cpu_list(""), Some(vec![]));
+    assert_eq!(parse_cpu_list("\n"), Some(vec![]));
+    assert_eq!(parse_cpu_list("3-1"), None);
+    assert_eq!(parse_cpu_list("0-x"), None);
+    assert_eq!(format_cpu_list(&[0, 1, 2, 3, 8, 10, 11]), "0-3,8,10-11");
+    assert_eq!(format_cpu_list(&[]), "");
+
+    let status = "Name:\tworker\nCpus_allowed:\tff\nCpus_allowed_list:\t0-1,4\nMems_allowed_list:\t0\n";
+    assert_eq!(parse_status_cpus_allowed(status), Some(vec![0, 1, 4]));
+    assert_eq!(parse_status_cpus_allowed("Name:\tinit\n"), None);
+
+    assert_eq!(cpus_in_mask(0b1011), vec![0, 1, 3]);
+    assert_eq!(cpus_in_mask(1 << 63), vec![63]);
+
+    // Every process may run on at least one CPU
+    #[cfg(any(target_os = "linux", target_os = "windows"))]
+    assert!(!cpu_affinity(std::process::id()).is_empty());
+    // No such process
+    assert!(cpu_affinity(u32::MAX).is_empty());
+}
+
+#[test]
+fn test_io_rate() {
+    use crate::core::counter_rate;
+
+    assert_eq!(counter_rate(3_000_000, 1_000_000, 2.0), 1_000_000.0);
+    assert_eq!(counter_rate(4096, 0, 0.5), 8192.0);
+    // A counter that went backwards or no elapsed time is not a rate
+    assert_eq!(counter_rate(100, 200, 1.0), 0.0);
+    assert_eq!(counter_rate(200, 100, 0.0), 0.0);
+}
+
+#[test]
+fn test_handle_count() {
+    use crate::backend::process_monitor::handle_count;
+
+    // Our own process always has stdin, stdout and stderr open
+    #[cfg(any(target_os = "linux", target_os = "macos"))]
+    assert!(handle_count(std::process::id()).unwrap() >= 3);
+    #[cfg(target_os = "windows")]
+    assert!(handle_count(std::process::id()).unwrap() > 0);
+    // No such process
+    assert_eq!(handle_count(u32::MAX), None);
+}
+
+#[tokio::test]
+async fn test_top_processes_sorted_by_memory() {
+    use crate::core::ProcessSortKey;
+
+    let mut monitor = ProcessMonitor::new();
+    let config = MonitorConfig {
+        top_processes_count: Some(5),
+        process_sort_key: ProcessSortKey::Memory,
+        ..Default::default()
+    };
+    monitor.initialize(config).await.unwrap();
+
+    let metrics = monitor.collect().await.unwrap();
+    let mut ranked: Vec<(usize, u64)> = metrics
+        .iter()
+        .filter(|m| m.metric_type == MetricType::ProcessMemory)
+        .filter_map(|m| match m.value {
+            MetricValue::Unsigned(bytes) => Some((m.tags.get("rank")?.parse().ok()?, bytes)),
+            _ => None,
+        })
+        .collect();
+    ranked.sort();
+
+    assert!(ranked.len() <= 5);
+    assert!(ranked.windows(2).all(|pair| pair[0].1 >= pair[1].1));
+}
+
+#[tokio::test]
+async fn test_find_processes_searches_past_top_n() {
+    use crate::core::ProcessQuery;
+
+    let mut monitor = ProcessMonitor::new();
+    let config = MonitorConfig {
+        top_processes_count: Some(1),
+        max_processes: Some(1),
+        ..Default::default()
+    };
+    monitor.initialize(config).await.unwrap();
+    monitor.collect().await.unwrap();
+
+    let own_pid = std::process::id();
+    let found = monitor.find_processes(&ProcessQuery {
+        pid: Some(own_pid),
+        ..Default::default()
+    });
+    assert_eq!(found.len(), 1);
+    let cmdline = found[0].cmdline.clone().expect("own command line is readable");
+
+    // Any argument of the command line finds it as well
+    let program = cmdline.split(' ').next().unwrap().to_uppercase();
+    let by_cmdline = monitor.find_processes(&ProcessQuery {
+        name_contains: Some(program),
+        ..Default::default()
+    });
+    assert!(by_cmdline.iter().any(|p| p.pid == own_pid));
+
+    let none = monitor.find_processes(&ProcessQuery {
+        pid: Some(own_pid),
+        min_memory: Some(u64::MAX),
+        ..Default::default()
+    });
+    assert!(none.is_empty());
+}
+
+#[tokio::test]
+async fn test_grouped_processes_merge_by_name() {
+    use crate::core::{ProcessGrouping, ProcessQuery};
+
+    let mut monitor = ProcessMonitor::new();
+    monitor.initialize(MonitorConfig::default()).await.unwrap();
+    monitor.collect().await.unwrap();
+
+    let ungrouped = monitor.grouped_processes();
+    assert!(ungrouped.iter().all(|group| group.instance_count == 1 && group.pids.len() == 1));
+
+    let config = MonitorConfig {
+        process_grouping: ProcessGrouping::ByName,
+        ..Default::default()
+    };
+    monitor.reconfigure(config).await.unwrap();
+    let grouped = monitor.grouped_processes();
+
+    // Every process lands in exactly one group
+    let total: usize = grouped.iter().map(|group| group.instance_count).sum();
+    assert_eq!(total, ungrouped.len());
+    let names: std::collections::HashSet<&str> = grouped.iter().map(|group| group.name.as_str()).collect();
+    assert_eq!(names.len(), grouped.len());
+
+    let own_pid = std::process::id();
+    let own = &monitor.find_processes(&ProcessQuery { pid: Some(own_pid), ..Default::default() })[0];
+    let group = grouped.iter().find(|group| group.pids.contains(&own_pid)).unwrap();
+    assert_eq!(group.name, own.name);
+    assert!(group.total_memory >= own.memory_bytes);
+    assert!(group.pids.windows(2).all(|pair| pair[0] < pair[1]));
+}
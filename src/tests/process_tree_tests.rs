@@ -0,0 +1,74 @@
+use super::export_tests::sample_process;
+use crate::core::{ProcessMetrics, ProcessNode};
+use crate::services::build_process_tree;
+
+
+fn process(pid: u32, parent_pid: Option<u32>, cpu: f32, memory_bytes: u64) -> ProcessMetrics {
+    ProcessMetrics {
+        name: format!("proc{}", pid),
+        cpu_usage_percent: cpu,
+        memory_bytes,
+        parent_pid,
+        ..sample_process(pid, "")
+    }
+}
+
+fn pids(node: &ProcessNode) -> Vec<u32> {
+    node.children.iter().map(|c| c.process.as_ref().unwrap().pid).collect()
+}
+
+#[test]
+fn test_process_tree_nests_children_and_rolls_up() {
+    let tree = build_process_tree(&[
+        process(1, None, 1.0, 100),
+        process(10, Some(1), 2.0, 200),
+        process(11, Some(10), 3.0, 300),
+        // Parent is not in the set, so it hangs off the root
+        process(20, Some(999), 4.0, 400),
+    ]);
+
+    assert!(tree.process.is_none());
+    assert_eq!(pids(&tree), vec![1, 20]);
+    assert_eq!(tree.subtree_cpu_percent, 10.0);
+    assert_eq!(tree.subtree_memory_bytes, 1000);
+
+    let init = &tree.children[0];
+    assert_eq!(pids(init), vec![10]);
+    assert_eq!(init.subtree_cpu_percent, 6.0);
+    assert_eq!(init.subtree_memory_bytes, 600);
+    assert_eq!(pids(&init.children[0]), vec![11]);
+}
+
+#[test]
+fn test_process_tree_breaks_parent_cycles() {
+    let tree = build_process_tree(&[
+        process(5, Some(7), 1.0, 10),
+        process(7, Some(5), 1.0, 10),
+        process(9, Some(9), 1.0, 10),
+    ]);
+
+    // The cycle is broken at its lowest pid; a self-parent is a root
+    assert_eq!(pids(&tree), vec![9, 5]);
+    assert_eq!(pids(&tree.children[1]), vec![7]);
+    assert!(tree.children[1].children[0].children.is_empty());
+    assert_eq!(tree.subtree_memory_bytes, 30);
+}
+
+#[tokio::test]
+async fn test_service_tree_covers_the_whole_process_table() {
+    let service = crate::services::MonitoringService::new();
+    assert!(service.process_tree().await.is_err());
+
+    service.initialize().await.unwrap();
+    service.collect_once().await.unwrap();
+    let snapshot = service.collect_once().await.unwrap();
+    let tree = service.process_tree().await.unwrap();
+
+    fn count(node: &ProcessNode) -> usize {
+        usize::from(node.process.is_some()) + node.children.iter().map(count).sum::<usize>()
+    }
+    // Both come from the process monitor's latest collection
+    let table = service.find_processes(crate::core::ProcessQuery::default()).await;
+    assert_eq!(count(&tree), table.len());
+    assert!(count(&tree) > snapshot.top_processes.len());
+}
@@ -0,0 +1,127 @@
+use super::export_tests::{sample_metrics, sample_process};
+use crate::core::{AppConfig, ChangeFilterConfig, SystemMetrics};
+use crate::services::recorder::{Compression, MetricsRecorder};
+use crate::services::MonitoringService;
+use std::time::Duration;
+
+
+#[test]
+fn test_records_one_json_line_per_sample() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("history").join("metrics.jsonl");
+    let recorder = MetricsRecorder::new(&path, Compression::None).unwrap();
+
+    recorder.record(&sample_metrics()).unwrap();
+    recorder.record(&sample_metrics()).unwrap();
+    assert_eq!(recorder.flush().unwrap(), 2);
+
+    let content = std::fs::read_to_string(&path).unwrap();
+    let lines: Vec<&str> = content.lines().collect();
+    assert_eq!(lines.len(), 2);
+    let parsed: SystemMetrics = serde_json::from_str(lines[0]).unwrap();
+    assert_eq!(parsed.system_info.hostname, "host");
+}
+
+#[test]
+fn test_flushes_on_interval() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("metrics.jsonl");
+    let recorder = MetricsRecorder::new(&path, Compression::None).unwrap().with_flush_interval(Duration::from_millis(50));
+
+    // Buffered until the interval has passed
+    recorder.record(&sample_metrics()).unwrap();
+    assert_eq!(std::fs::read_to_string(&path).unwrap().lines().count(), 0);
+
+    std::thread::sleep(Duration::from_millis(60));
+    recorder.record(&sample_metrics()).unwrap();
+    assert_eq!(std::fs::read_to_string(&path).unwrap().lines().count(), 2);
+
+    recorder.record(&sample_metrics()).unwrap();
+    drop(recorder);
+    assert_eq!(std::fs::read_to_string(&path).unwrap().lines().count(), 3);
+}
+
+#[test]
+fn test_change_filter_skips_unchanged_samples() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("metrics.jsonl");
+    let config = ChangeFilterConfig { enabled: true, min_delta: 1.0, keyframe_interval_seconds: 60 };
+    let recorder = MetricsRecorder::new(&path, Compression::None).unwrap().with_change_filter(&config);
+
+    let mut metrics = sample_metrics();
+    recorder.record(&metrics).unwrap();
+    recorder.record(&metrics).unwrap();
+    metrics.memory.usage_percent += 5.0;
+    recorder.record(&metrics).unwrap();
+
+    assert_eq!(recorder.flush().unwrap(), 2);
+    assert_eq!(std::fs::read_to_string(&path).unwrap().lines().count(), 2);
+}
+
+#[tokio::test]
+async fn test_service_recorders_use_configured_change_filter() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("metrics.jsonl");
+    let service = MonitoringService::new();
+    let mut config = AppConfig::default();
+    // Nothing moves this far, and no keyframe is due during the test
+    config.storage.change_filter = ChangeFilterConfig { enabled: true, min_delta: 1e18, keyframe_interval_seconds: 3600 };
+    service.apply_config(&config).await.unwrap();
+    tokio::time::timeout(Duration::from_secs(5), service.initialize()).await.unwrap().unwrap();
+
+    let recorder = service.attach_recorder(MetricsRecorder::new(&path, Compression::None).unwrap());
+    let mut samples = service.subscribe();
+    tokio::time::timeout(Duration::from_secs(5), service.start()).await.unwrap().unwrap();
+
+    // The recorder sees the same broadcasts, a little later
+    for _ in 0..6 {
+        tokio::time::timeout(Duration::from_secs(10), samples.recv()).await.unwrap().unwrap();
+    }
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    service.stop().await.unwrap();
+
+    // Only the first sample, or one where a device came or went, is written
+    let written = recorder.records_written();
+    assert!((1..6).contains(&written), "{} of 6 samples written", written);
+}
+
+#[test]
+fn test_rotation_keeps_max_files() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("metrics.jsonl");
+    // Small enough that every sample forces a rotation
+    let recorder = MetricsRecorder::new(&path, Compression::None).unwrap().with_max_bytes(16).with_max_files(2);
+
+    for _ in 0..4 {
+        recorder.record(&sample_metrics()).unwrap();
+    }
+    recorder.flush().unwrap();
+
+    let line_count = |p: &std::path::Path| std::fs::read_to_string(p).unwrap().lines().count();
+    assert_eq!(line_count(&path), 1);
+    assert_eq!(line_count(&dir.path().join("metrics.jsonl.1")), 1);
+    assert_eq!(line_count(&dir.path().join("metrics.jsonl.2")), 1);
+    assert!(!dir.path().join("metrics.jsonl.3").exists());
+    assert_eq!(recorder.records_written(), 4);
+}
+
+#[test]
+fn test_recorded_processes_are_redacted() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("metrics.jsonl");
+    let recorder = MetricsRecorder::new(&path, Compression::None).unwrap();
+
+    let mut metrics = sample_metrics();
+    metrics.top_processes.push(crate::core::ProcessMetrics {
+        cmdline: Some("psql -U admin --password=s3cret -h db".to_string()),
+        ..sample_process(42, "psql --password=hunter2")
+    });
+    recorder.record(&metrics).unwrap();
+    recorder.flush().unwrap();
+
+    let content = std::fs::read_to_string(&path).unwrap();
+    assert!(!content.contains("hunter2"));
+    // Command lines are where secrets usually are
+    assert!(!content.contains("s3cret"));
+    assert!(content.contains("psql -U admin --password=***"));
+}
@@ -0,0 +1,97 @@
+use super::export_tests::sample_metrics;
+use crate::core::SystemMetrics;
+use crate::services::recorder::{Compression, MetricsRecorder};
+use crate::services::replay::ReplayService;
+use std::time::{Duration, SystemTime};
+use tokio::time::timeout;
+
+
+fn samples_every(gap: Duration, count: usize) -> Vec<SystemMetrics> {
+    let start = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+    (0..count)
+        .map(|i| {
+            let mut metrics = sample_metrics();
+            metrics.timestamp = start + gap * i as u32;
+            metrics.cpu.usage_percent = i as f32;
+            metrics
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn test_replays_recorded_file_in_order() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("metrics.jsonl");
+    let recorder = MetricsRecorder::new(&path, Compression::None).unwrap();
+    for sample in samples_every(Duration::from_millis(100), 3) {
+        recorder.record(&sample).unwrap();
+    }
+    recorder.flush().unwrap();
+
+    let replay = ReplayService::open(&path).unwrap().with_speed(10.0);
+    assert_eq!(replay.len(), 3);
+
+    let mut receiver = replay.subscribe();
+    replay.start().await.unwrap();
+
+    for expected in 0..3 {
+        let metrics = timeout(Duration::from_secs(2), receiver.recv()).await.unwrap().unwrap();
+        assert_eq!(metrics.cpu.usage_percent, expected as f32);
+    }
+
+    // Without looping playback ends after the last sample
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert!(!replay.is_running());
+}
+
+#[tokio::test]
+async fn test_seek_and_loop() {
+    let samples = samples_every(Duration::from_secs(60), 4);
+    let third = samples[2].timestamp;
+    let replay = ReplayService::from_samples(samples).with_speed(1000.0).with_loop(true);
+
+    replay.seek(third - Duration::from_secs(1));
+    assert_eq!(replay.position(), 2);
+
+    let mut receiver = replay.subscribe();
+    replay.start().await.unwrap();
+
+    let mut seen = Vec::new();
+    for _ in 0..4 {
+        let metrics = timeout(Duration::from_secs(2), receiver.recv()).await.unwrap().unwrap();
+        seen.push(metrics.cpu.usage_percent as usize);
+    }
+    assert_eq!(seen, vec![2, 3, 0, 1]);
+
+    replay.stop().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_invalid_speed_is_rejected() {
+    let replay = ReplayService::from_samples(Vec::new()).with_speed(0.0);
+    assert!(replay.start().await.is_err());
+}
+
+#[tokio::test]
+async fn test_replays_zstd_recording() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("metrics.jsonl.zst");
+    let samples = samples_every(Duration::from_millis(10), 3);
+
+    let recorder = MetricsRecorder::new(&path, Compression::Zstd(3)).unwrap();
+    for sample in &samples {
+        recorder.record(sample).unwrap();
+    }
+    drop(recorder);
+    assert!(std::fs::read(&path).unwrap().starts_with(&[0x28, 0xB5, 0x2F, 0xFD]));
+
+    let replay = ReplayService::open(&path).unwrap().with_speed(100.0);
+    assert_eq!(replay.len(), samples.len());
+
+    let mut receiver = replay.subscribe();
+    replay.start().await.unwrap();
+    for expected in &samples {
+        let metrics = timeout(Duration::from_secs(2), receiver.recv()).await.unwrap().unwrap();
+        assert_eq!(&metrics, expected);
+    }
+}
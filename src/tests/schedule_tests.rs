@@ -0,0 +1,95 @@
+
+use crate::core::{Metric, MonitorError, MonitoringInterval, Result, ScheduleMode};
+use crate::services::schedule::{jitter_offset, until_aligned, CollectionSchedule};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+#[test]
+fn test_collection_schedule_honors_intervals() {
+    let intervals = MonitoringInterval {
+        cpu: Duration::from_millis(500),
+        memory: Duration::from_secs(1),
+        gpu: Duration::from_secs(1),
+        disk: Duration::from_secs(5),
+        network: Duration::from_secs(1),
+        process: Duration::from_secs(2),
+    };
+    assert_eq!(intervals.for_monitor("storage"), Duration::from_secs(5));
+    assert_eq!(intervals.for_monitor("battery"), Duration::from_secs(5));
+
+    let names = vec!["cpu".to_string(), "storage".to_string(), "network".to_string()];
+    let start = Instant::now();
+    let mut schedule = CollectionSchedule::default();
+    assert_eq!(schedule.due(&names, start).len(), 3);
+    assert_eq!(schedule.next_wakeup(), None);
+
+    let results: HashMap<String, Result<Vec<Metric>>> = HashMap::from([
+        ("cpu".to_string(), Ok(Vec::new())),
+        ("storage".to_string(), Ok(Vec::new())),
+        ("network".to_string(), Err(MonitorError::CollectionError("down".to_string()))),
+    ]);
+    schedule.record(results, &intervals, start);
+    assert_eq!(schedule.next_wakeup(), Some(start + Duration::from_millis(500)));
+    assert!(schedule.latest().contains_key("storage"));
+    assert!(!schedule.latest().contains_key("network"));
+
+    let mut due = schedule.due(&names, start + Duration::from_secs(1));
+    due.sort();
+    assert_eq!(due, vec!["cpu".to_string(), "network".to_string()]);
+
+    // Only the CPU refreshes; storage keeps its earlier result and the
+    // network error stays reported until it next succeeds
+    schedule.record(HashMap::from([("cpu".to_string(), Ok(Vec::new()))]), &intervals, start + Duration::from_secs(1));
+    assert!(schedule.latest().contains_key("storage"));
+    assert_eq!(schedule.errors().get("network").map(String::as_str), Some("Collection error: down"));
+    assert_eq!(schedule.due(&names, start + Duration::from_secs(4)).len(), 2);
+    assert_eq!(schedule.due(&names, start + Duration::from_secs(5)).len(), 3);
+}
+
+#[test]
+fn test_schedule_modes() {
+    assert_eq!("aligned".parse::<ScheduleMode>(), Ok(ScheduleMode::Aligned));
+    assert_eq!("jittered(250ms)".parse::<ScheduleMode>(), Ok(ScheduleMode::Jittered(Duration::from_millis(250))));
+    assert_eq!("Jittered(2s)".parse::<ScheduleMode>(), Ok(ScheduleMode::Jittered(Duration::from_secs(2))));
+    assert!("jittered".parse::<ScheduleMode>().is_err());
+    assert_eq!(ScheduleMode::Jittered(Duration::from_secs(1)).to_string(), "jittered(1000ms)");
+
+    // Waking a little early or late still targets the following boundary
+    let second = Duration::from_secs(1);
+    assert_eq!(until_aligned(Duration::from_millis(10_003), second), Duration::from_millis(997));
+    assert_eq!(until_aligned(Duration::from_millis(9_998), second), Duration::from_millis(1_002));
+    assert_eq!(until_aligned(Duration::from_secs(10), second), second);
+
+    let offset = jitter_offset("cpu", Duration::from_millis(300));
+    assert!(offset < Duration::from_millis(300));
+    assert_eq!(offset, jitter_offset("cpu", Duration::from_millis(300)));
+
+    let intervals = MonitoringInterval::default();
+    let start = Instant::now();
+    let mut schedule = CollectionSchedule::default();
+    schedule.set_mode(ScheduleMode::Jittered(Duration::from_millis(300)));
+    schedule.record(HashMap::from([("cpu".to_string(), Ok(Vec::new()))]), &intervals, start);
+    let first_due = start + intervals.cpu + offset;
+    assert_eq!(schedule.next_wakeup(), Some(first_due));
+
+    // Later collections keep the offset even when the loop wakes late
+    schedule.record(
+        HashMap::from([("cpu".to_string(), Ok(Vec::new()))]),
+        &intervals,
+        first_due + Duration::from_millis(20),
+    );
+    assert_eq!(schedule.next_wakeup(), Some(first_due + intervals.cpu));
+
+    // A new jitter places the monitor afresh instead of keeping the old offset
+    let now = first_due + intervals.cpu;
+    schedule.set_mode(ScheduleMode::Jittered(Duration::from_millis(100)));
+    schedule.record(HashMap::from([("cpu".to_string(), Ok(Vec::new()))]), &intervals, now);
+    let reseeded = now + intervals.cpu + jitter_offset("cpu", Duration::from_millis(100));
+    assert_eq!(schedule.next_wakeup(), Some(reseeded));
+
+    schedule.set_mode(ScheduleMode::Fixed);
+    schedule.record(HashMap::from([("cpu".to_string(), Ok(Vec::new()))]), &intervals, reseeded);
+    schedule.set_mode(ScheduleMode::Jittered(Duration::from_millis(100)));
+    schedule.record(HashMap::from([("cpu".to_string(), Ok(Vec::new()))]), &intervals, reseeded + intervals.cpu);
+    assert_eq!(schedule.next_wakeup(), Some(reseeded + intervals.cpu * 2 + jitter_offset("cpu", Duration::from_millis(100))));
+}
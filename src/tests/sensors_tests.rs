@@ -0,0 +1,69 @@
+use std::collections::HashMap;
+
+use crate::backend::sensors::{hwmon_chip_labels, parse_hwmon_chip};
+use crate::backend::SensorsMonitor;
+use crate::core::{Monitor, MonitorConfig, MonitorState, MetricType, SensorType};
+
+#[tokio::test]
+async fn test_sensors_monitor_collection() {
+    let mut monitor = SensorsMonitor::new();
+    assert_eq!(monitor.name(), "Sensors Monitor");
+    assert_eq!(monitor.state(), MonitorState::Uninitialized);
+
+    monitor.initialize(MonitorConfig::default()).await.unwrap();
+
+    let metrics = monitor.collect().await.unwrap();
+    for metric in &metrics {
+        assert!(metric.tags.contains_key("sensor"));
+        let expected = match metric.metric_type {
+            MetricType::SensorTemperature => "temperature",
+            MetricType::SensorFanSpeed => "fan",
+            MetricType::SensorVoltage => "voltage",
+            other => panic!("unexpected metric type {:?}", other),
+        };
+        assert_eq!(metric.tags.get("type").map(String::as_str), Some(expected));
+    }
+}
+
+#[test]
+fn test_parse_hwmon_chip() {
+    let attrs: HashMap<&str, &str> = [
+        ("temp1_input", "45500"),
+        ("temp1_label", "Composite"),
+        ("temp2_input", "51000"),
+        ("temp10_input", "30000"),
+        ("fan1_input", "1200"),
+        ("fan2_input", "0"),
+        ("in0_input", "1200"),
+        ("in1_input", "65535000"),
+        ("name", "nvme"),
+        ("temp1_max", "84850"),
+    ]
+    .into_iter()
+    .collect();
+    let files: Vec<String> = attrs.keys().map(|k| k.to_string()).collect();
+
+    let readings = parse_hwmon_chip("nvme", &files, |file| attrs.get(file).map(|v| v.to_string()));
+    let summary: Vec<(&str, SensorType, f32, &str)> = readings
+        .iter()
+        .map(|r| (r.name.as_str(), r.sensor_type, r.value, r.unit.as_str()))
+        .collect();
+
+    assert_eq!(summary, vec![
+        ("nvme - Composite", SensorType::Temperature, 45.5, "°C"),
+        ("nvme - temp2", SensorType::Temperature, 51.0, "°C"),
+        ("nvme - temp10", SensorType::Temperature, 30.0, "°C"),
+        ("nvme - fan1", SensorType::Fan, 1200.0, "RPM"),
+        ("nvme - in0", SensorType::Voltage, 1.2, "V"),
+    ]);
+}
+
+#[test]
+fn test_hwmon_chip_labels_tell_repeated_chips_apart() {
+    let chips: Vec<(String, String)> = [("coretemp", "coretemp.0"), ("nvme", "nvme0"), ("nvme", "nvme1")]
+        .into_iter()
+        .map(|(name, device)| (name.to_string(), device.to_string()))
+        .collect();
+
+    assert_eq!(hwmon_chip_labels(&chips), vec!["coretemp", "nvme (nvme0)", "nvme (nvme1)"]);
+}
@@ -0,0 +1,85 @@
+use crate::core::{GpuMetrics, MetricType, SmoothingConfig, SmoothingMethod};
+use crate::services::smoothing::MetricsSmoother;
+use super::export_tests::{sample_gpu, sample_metrics};
+
+
+fn smoother(method: SmoothingMethod) -> MetricsSmoother {
+    MetricsSmoother::new(SmoothingConfig {
+        method,
+        metrics: [MetricType::CpuUsage].into_iter().collect(),
+    })
+}
+
+#[test]
+fn test_simple_moving_average() {
+    let mut smoother = smoother(SmoothingMethod::Sma(3));
+    let mut outputs = Vec::new();
+
+    for usage in [10.0, 40.0, 70.0, 10.0] {
+        let mut metrics = sample_metrics();
+        metrics.cpu.usage_percent = usage;
+        metrics.memory.usage_percent = usage;
+        smoother.apply(&mut metrics);
+        outputs.push(metrics.cpu.usage_percent);
+        // Types that weren't selected pass through
+        assert_eq!(metrics.memory.usage_percent, usage);
+    }
+
+    assert_eq!(outputs, vec![10.0, 25.0, 40.0, 40.0]);
+}
+
+#[test]
+fn test_exponential_moving_average_per_series() {
+    let mut smoother = smoother(SmoothingMethod::Ema(0.5));
+
+    let mut first = sample_metrics();
+    smoother.apply(&mut first);
+    assert_eq!(first.cpu.per_core_usage, vec![40.0, 45.0]);
+
+    let mut second = sample_metrics();
+    second.cpu.per_core_usage = vec![80.0, 45.0];
+    smoother.apply(&mut second);
+    // Each core is its own series
+    assert_eq!(second.cpu.per_core_usage, vec![60.0, 45.0]);
+
+    // A new config starts afresh
+    smoother.set_config(SmoothingConfig::default());
+    assert!(!smoother.is_active());
+    let mut third = sample_metrics();
+    third.cpu.usage_percent = 99.0;
+    smoother.apply(&mut third);
+    assert_eq!(third.cpu.usage_percent, 99.0);
+}
+
+#[test]
+fn test_gpus_smoothed_by_position() {
+    let mut smoother = MetricsSmoother::new(SmoothingConfig {
+        method: SmoothingMethod::Ema(0.5),
+        metrics: [MetricType::GpuUsage].into_iter().collect(),
+    });
+    let gpu = |usage_percent: f32| GpuMetrics { name: "NVIDIA A100".to_string(), usage_percent, ..sample_gpu() };
+    let usages = |smoother: &mut MetricsSmoother, gpus: Vec<GpuMetrics>| {
+        let mut metrics = sample_metrics();
+        metrics.gpus = gpus;
+        smoother.apply(&mut metrics);
+        metrics.gpus.iter().map(|gpu| gpu.usage_percent).collect::<Vec<_>>()
+    };
+
+    assert_eq!(usages(&mut smoother, vec![gpu(0.0), gpu(100.0)]), vec![0.0, 100.0]);
+    // Identically named cards don't share a series
+    assert_eq!(usages(&mut smoother, vec![gpu(20.0), gpu(80.0)]), vec![10.0, 90.0]);
+
+    // The second card's series goes when it does, so it comes back fresh
+    assert_eq!(usages(&mut smoother, vec![gpu(10.0)]), vec![10.0]);
+    assert_eq!(usages(&mut smoother, vec![gpu(10.0), gpu(40.0)]), vec![10.0, 40.0]);
+}
+
+#[test]
+fn test_smoothing_config_format() {
+    let config: SmoothingConfig = toml::from_str("method = { ema = 0.3 }\nmetrics = [\"CpuUsage\", \"GpuUsage\"]\n").unwrap();
+    assert_eq!(config.method, SmoothingMethod::Ema(0.3));
+    assert!(config.metrics.contains(&MetricType::GpuUsage));
+
+    let config: SmoothingConfig = toml::from_str("method = \"none\"\nmetrics = []\n").unwrap();
+    assert_eq!(config.method, SmoothingMethod::None);
+}
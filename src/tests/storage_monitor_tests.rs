@@ -32,8 +32,8 @@ async fn test_storage_monitor_collection() {
     // Verify disk usage percentages are valid
     for metric in metrics.iter() {
         if matches!(metric.metric_type, MetricType::DiskUsage) {
-            if let MetricValue::Float(usage) = metric.value {
-                assert!(usage >= 0.0 && usage <= 100.0);
+            if let MetricValue::Percentage(usage) = metric.value {
+                assert!((0.0..=100.0).contains(&usage));
             }
         }
     }
@@ -85,4 +85,214 @@ async fn test_disk_space_consistency() {
         assert!(used <= total, "Disk {} used space exceeds total", mount);
         assert!(available <= total, "Disk {} available space exceeds total", mount);
     }
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_inode_usage() {
+    use crate::backend::storage_monitor::{inode_counts, inode_usage};
+
+    assert_eq!(inode_usage(1_000, 250), Some((1_000, 750)));
+    // btrfs and similar report no fixed inode table
+    assert_eq!(inode_usage(0, 0), None);
+
+    #[cfg(windows)]
+    assert_eq!(inode_counts("C:\\"), None);
+    #[cfg(unix)]
+    if let Some((total, used)) = inode_counts("/") {
+        assert!(used <= total);
+    }
+}
+
+#[test]
+fn test_disk_busy_percent() {
+    use crate::backend::storage_monitor::busy_percent;
+
+    // 500ms of I/O time over one second is 50% utilization
+    assert_eq!(busy_percent(1_000, 1_500, 1.0), 50.0);
+    // Overlapping accounting can exceed wall time; utilization is capped
+    assert_eq!(busy_percent(0, 3_000, 2.0), 100.0);
+    // Counter resets and zero intervals do not produce garbage
+    assert_eq!(busy_percent(5_000, 100, 1.0), 0.0);
+    assert_eq!(busy_percent(0, 100, 0.0), 0.0);
+}
+
+#[test]
+fn test_smart_health_parsing() {
+    use crate::backend::storage_monitor::{parse_failure_predict_csv, parse_smartctl_json, physical_device};
+
+    assert_eq!(physical_device("/dev/sda3").as_deref(), Some("/dev/sda"));
+    assert_eq!(physical_device("/dev/nvme0n1p2").as_deref(), Some("/dev/nvme0n1"));
+    assert_eq!(physical_device("/dev/nvme0n1").as_deref(), Some("/dev/nvme0n1"));
+    assert_eq!(physical_device("/dev/mmcblk0p1").as_deref(), Some("/dev/mmcblk0"));
+    assert_eq!(physical_device("/dev/mapper/vg-root"), None);
+    assert_eq!(physical_device("overlay"), None);
+
+    // Trimmed from smartctl 7.3 on a SATA SSD
+    let sata = r#"{
+        "smart_status": {"passed": true},
+        "temperature": {"current": 34},
+        "power_on_time": {"hours": 18236},
+        "ata_smart_attributes": {"table": [
+            {"id": 5, "name": "Reallocated_Sector_Ct", "value": 100, "raw": {"value": 8}},
+            {"id": 177, "name": "Wear_Leveling_Count", "value": 93, "raw": {"value": 112}}
+        ]}
+    }"#;
+    let health = parse_smartctl_json("/dev/sda", sata).unwrap();
+    assert!(health.healthy);
+    assert_eq!(health.temperature_c, Some(34.0));
+    assert_eq!(health.power_on_hours, Some(18236));
+    assert_eq!(health.reallocated_sectors, Some(8));
+    assert_eq!(health.wear_level_percent, Some(7.0));
+
+    let nvme = r#"{
+        "smart_status": {"passed": false},
+        "temperature": {"current": 51},
+        "nvme_smart_health_information_log": {"percentage_used": 104, "power_on_hours": 9000}
+    }"#;
+    let health = parse_smartctl_json("/dev/nvme0n1", nvme).unwrap();
+    assert!(!health.healthy);
+    assert_eq!(health.reallocated_sectors, None);
+    assert_eq!(health.wear_level_percent, Some(100.0));
+
+    // smartctl without permission to open the drive
+    assert_eq!(parse_smartctl_json("/dev/sda", r#"{"smartctl": {"exit_status": 2}}"#), None);
+    assert_eq!(parse_smartctl_json("/dev/sda", ""), None);
+
+    let csv = "\r\nNode,InstanceName,PredictFailure\r\nDESKTOP,SCSI\\Disk&Ven_NVMe\\5&1e2f_0,FALSE\r\nDESKTOP,IDE\\DiskST2000\\4&2a_0,TRUE\r\n";
+    let drives = parse_failure_predict_csv(csv);
+    assert_eq!(drives.len(), 2);
+    assert!(drives[0].healthy);
+    assert!(!drives[1].healthy);
+    assert_eq!(drives[1].device, "IDE\\DiskST2000\\4&2a_0");
+    assert!(parse_failure_predict_csv("No Instance(s) Available.\r\n").is_empty());
+}
+
+#[test]
+fn test_failure_predictions_by_volume() {
+    use crate::backend::storage_monitor::{
+        failure_predictions_by_volume, parse_disk_drives, parse_failure_predict_csv, parse_volume_disks,
+    };
+
+    let drives = parse_disk_drives(
+        "\r\r\nIndex=0\r\r\nPNPDeviceID=SCSI\\DISK&VEN_NVME&PROD_SAMSUNG\\5&1E2F&0&000000\r\r\n\r\r\n\
+         \r\r\nIndex=1\r\r\nPNPDeviceID=IDE\\DISKST2000\\4&2A\r\r\n\r\r\n",
+    );
+    assert_eq!(
+        drives,
+        vec![
+            (0, "SCSI\\DISK&VEN_NVME&PROD_SAMSUNG\\5&1E2F&0&000000".to_string()),
+            (1, "IDE\\DISKST2000\\4&2A".to_string()),
+        ]
+    );
+
+    let volumes = parse_volume_disks(
+        "\r\r\nAntecedent=\\\\PC\\root\\cimv2:Win32_DiskPartition.DeviceID=\"Disk #1, Partition #0\"\r\r\n\
+         Dependent=\\\\PC\\root\\cimv2:Win32_LogicalDisk.DeviceID=\"D:\"\r\r\n\r\r\n\
+         \r\r\nAntecedent=\\\\PC\\root\\cimv2:Win32_DiskPartition.DeviceID=\"Disk #0, Partition #2\"\r\r\n\
+         Dependent=\\\\PC\\root\\cimv2:Win32_LogicalDisk.DeviceID=\"c:\"\r\r\n\r\r\n",
+    );
+    assert_eq!(volumes, vec![("D:".to_string(), 1), ("C:".to_string(), 0)]);
+
+    // Instance names differ in case from the PNP ids and carry a suffix
+    let predictions = parse_failure_predict_csv(
+        "Node,InstanceName,PredictFailure\r\n\
+         PC,IDE\\DiskST2000\\4&2a_0,TRUE\r\n\
+         PC,SCSI\\Disk&Ven_NVMe&Prod_Samsung\\5&1e2f&0&000000_0,FALSE\r\n",
+    );
+    let by_volume = failure_predictions_by_volume(&predictions, &drives, &volumes);
+    assert!(by_volume["C:"].healthy);
+    assert!(!by_volume["D:"].healthy);
+
+    // Drives without a prediction leave their volumes out
+    let by_volume = failure_predictions_by_volume(&predictions[..1], &drives, &volumes);
+    assert_eq!(by_volume.len(), 1);
+    assert!(by_volume.contains_key("D:"));
+}
+
+#[test]
+fn test_pool_parsing() {
+    use crate::backend::storage_monitor::{
+        parse_btrfs_scrub_status, parse_btrfs_usage, parse_zfs_compressratio, parse_zpool_list,
+        parse_zpool_status_scans,
+    };
+    use crate::core::{PoolKind, PoolMetrics};
+
+    let list = "tank\t11991548690432\t4398046511104\t7593502179328\t1.00\tONLINE\n\
+                backup\t3985729650688\t1099511627776\t2886218022912\t1.35\tDEGRADED\n";
+    let pools = parse_zpool_list(list);
+    assert_eq!(pools.len(), 2);
+    assert_eq!(pools[0].name, "tank");
+    assert_eq!(pools[0].raw_capacity, 11991548690432);
+    assert_eq!(pools[0].allocated, 4398046511104);
+    assert_eq!(pools[0].free, 7593502179328);
+    assert_eq!(pools[1].dedup_ratio, 1.35);
+    assert_eq!(pools[1].health, "DEGRADED");
+    assert!(parse_zpool_list("no pools available\n").is_empty());
+
+    let ratios = parse_zfs_compressratio("tank\t1.52\ntank/media\t1.01\nbackup\t2.10x\n");
+    assert_eq!(ratios.len(), 2);
+    assert_eq!(ratios["tank"], 1.52);
+    assert_eq!(ratios["backup"], 2.10);
+
+    let status = "  pool: backup
+ state: DEGRADED
+  scan: scrub in progress since Sun Oct 11 00:24:01 2026
+\t1.23T scanned at 1.10G/s, 512G issued at 456M/s, 2.00T total
+\t0B repaired, 25.00% done, 00:57:01 to go
+config:
+
+\tNAME        STATE     READ WRITE CKSUM
+\tbackup      DEGRADED     0     0     0
+
+  pool: tank
+ state: ONLINE
+  scan: scrub repaired 0B in 03:12:44 with 0 errors on Sun Oct 11 03:36:45 2026
+config:
+";
+    let scans = parse_zpool_status_scans(status);
+    assert_eq!(scans["backup"], "scrub in progress since Sun Oct 11 00:24:01 2026");
+    assert_eq!(scans["tank"], "scrub repaired 0B in 03:12:44 with 0 errors on Sun Oct 11 03:36:45 2026");
+
+    let usage = "Overall:
+    Device size:\t\t        1000204886016
+    Device allocated:\t\t         214748364800
+    Device unallocated:\t\t         785456521216
+    Device missing:\t\t                    0
+    Used:\t\t\t         180388626432
+    Free (estimated):\t\t         812363022336\t(min: 419634761728)
+    Data ratio:\t\t\t                 1.00
+";
+    assert_eq!(
+        parse_btrfs_usage("/", usage),
+        Some(PoolMetrics {
+            name: "/".to_string(),
+            kind: PoolKind::Btrfs,
+            raw_capacity: 1000204886016,
+            allocated: 180388626432,
+            free: 812363022336,
+            compression_ratio: 1.0,
+            dedup_ratio: 1.0,
+            health: String::new(),
+            scrub_state: String::new(),
+        })
+    );
+    assert_eq!(parse_btrfs_usage("/", "ERROR: not a btrfs filesystem: /boot\n"), None);
+
+    let scrub = "UUID:             5c1e3a34-8d2f-4e4a-9f3d-2b7e1c0f9a11
+Scrub started:    Sun Oct 11 02:00:01 2026
+Status:           finished
+Duration:         0:10:21
+Total to scrub:   168.00GiB
+Rate:             277.06MiB/s
+Error summary:    no errors found
+";
+    assert_eq!(parse_btrfs_scrub_status(scrub), "finished, no errors found");
+    let old = "scrub status for 5c1e3a34-8d2f-4e4a-9f3d-2b7e1c0f9a11
+\tscrub started at Sun Oct 11 02:00:01 2026 and finished after 00:10:21
+\ttotal bytes scrubbed: 168.00GiB with 0 errors
+";
+    assert_eq!(parse_btrfs_scrub_status(old), "scrub started at Sun Oct 11 02:00:01 2026 and finished after 00:10:21");
+
+    let pool = PoolMetrics { compression_ratio: 1.52, ..pools[1].clone() };
+    assert_eq!(PoolMetrics::from_metric(&pool.to_metric()), Some(pool));
+}
@@ -0,0 +1,37 @@
+use crate::backend::system_monitor::SystemMonitor;
+use crate::core::{format_bytes, format_rate, ThroughputUnit, UiConfig, UnitSystem};
+
+#[test]
+fn test_format_bytes() {
+    assert_eq!(format_bytes(512, UnitSystem::Binary), "512 B");
+    assert_eq!(format_bytes(1536, UnitSystem::Binary), "1.50 KiB");
+    assert_eq!(format_bytes(1536, UnitSystem::Decimal), "1.54 kB");
+    assert_eq!(format_bytes(3 * 1024 * 1024 * 1024, UnitSystem::Binary), "3.00 GiB");
+    assert_eq!(format_bytes(3 * 1024 * 1024 * 1024, UnitSystem::Decimal), "3.22 GB");
+    // Past the largest unit the value just grows
+    assert_eq!(format_bytes(u64::MAX, UnitSystem::Decimal), "18446.74 PB");
+}
+
+#[test]
+fn test_format_rate() {
+    assert_eq!(format_rate(0, ThroughputUnit::Bytes, UnitSystem::Binary), "0 B/s");
+    assert_eq!(format_rate(12 * 1024 * 1024, ThroughputUnit::Bytes, UnitSystem::Binary), "12.00 MiB/s");
+    assert_eq!(format_rate(12_500_000, ThroughputUnit::Bits, UnitSystem::Decimal), "100.00 Mbit/s");
+    assert_eq!(format_rate(100, ThroughputUnit::Bits, UnitSystem::Binary), "800 bit/s");
+}
+
+#[test]
+fn test_unit_config_names() {
+    assert_eq!(serde_json::to_string(&UnitSystem::Decimal).unwrap(), "\"decimal\"");
+    assert_eq!(serde_json::from_str::<ThroughputUnit>("\"bits\"").unwrap(), ThroughputUnit::Bits);
+    assert_eq!(UnitSystem::default(), UnitSystem::Binary);
+}
+
+#[tokio::test]
+async fn test_system_metrics_use_configured_units() {
+    let ui = UiConfig { display_units: UnitSystem::Decimal, ..UiConfig::default() };
+    let metrics = SystemMonitor::collect_metrics(&ui).await;
+
+    let total = metrics.get("Memory Total").expect("memory total is collected");
+    assert!(!total.contains("iB"), "{} is in binary units", total);
+}
@@ -0,0 +1,58 @@
+use crate::core::{MetricCategory, SystemMetrics};
+use crate::services::ws_server::parse_subscription;
+use crate::services::WsServer;
+use crate::tests::export_tests::sample_metrics;
+use futures_util::{SinkExt, StreamExt};
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tokio_tungstenite::tungstenite::Message;
+
+#[test]
+fn test_parse_subscription() {
+    let set = parse_subscription(r#"{ "subscribe": ["cpu", "Memory", "bogus"] }"#).unwrap();
+    assert!(set.contains(MetricCategory::Cpu));
+    assert!(set.contains(MetricCategory::Memory));
+    assert!(!set.contains(MetricCategory::Network));
+    assert!(parse_subscription("not json").is_none());
+}
+
+#[tokio::test]
+async fn test_streams_filtered_metrics() {
+    let (sender, _) = broadcast::channel(16);
+    let server = WsServer::bind("127.0.0.1:0", sender.clone()).await.unwrap();
+    let addr = server.local_addr().unwrap();
+    tokio::spawn(server.run());
+
+    let (mut socket, _) = tokio_tungstenite::connect_async(format!("ws://{}/", addr)).await.unwrap();
+    socket.send(Message::Text(r#"{"subscribe":["cpu"]}"#.to_string())).await.unwrap();
+
+    // Keep sending until a sample arrives after the subscription took effect
+    let filtered = tokio::time::timeout(Duration::from_secs(5), async {
+        loop {
+            sender.send(sample_metrics()).unwrap();
+            let text = match socket.next().await.unwrap().unwrap() {
+                Message::Text(text) => text,
+                other => panic!("unexpected message {:?}", other),
+            };
+            let metrics = SystemMetrics::from_json(&text).unwrap();
+            if metrics.disks.is_empty() {
+                break metrics;
+            }
+        }
+    })
+    .await
+    .unwrap();
+    assert_eq!(filtered.cpu.usage_percent, 42.5);
+    assert!(filtered.networks.is_empty());
+
+    // Disconnecting drops the client's subscription
+    drop(socket);
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while sender.receiver_count() > 0 {
+            let _ = sender.send(sample_metrics());
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .unwrap();
+}
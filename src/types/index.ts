@@ -14,13 +14,37 @@ export interface SystemInfo {
 export interface CpuMetrics {
   usage_percent: number;
   frequency_mhz: number;
+  per_core_frequency_mhz?: number[];
   temperature_celsius?: number;
   load_average: [number, number, number];
   per_core_usage: number[];
+  per_core_times?: CpuCoreTimes[];
   processes_running: number;
   processes_total: number;
   context_switches: number;
   interrupts: number;
+  context_switches_per_sec?: number;
+  interrupts_per_sec?: number;
+  cpu_thermal?: ThermalStatus | null;
+}
+
+export interface CpuCoreTimes {
+  user: number;
+  system: number;
+  idle: number;
+  iowait: number;
+  irq: number;
+  softirq: number;
+  steal: number;
+}
+
+export type ThrottleReason = 'None' | 'Thermal' | 'Power' | 'Other';
+
+export interface ThermalStatus {
+  throttling: boolean;
+  reason: ThrottleReason;
+  temperature: number;
+  limit: number;
 }
 
 export interface MemoryMetrics {
@@ -44,11 +68,27 @@ export interface GpuMetrics {
   memory_usage_percent: number;
   power_watts: number;
   fan_speed_percent?: number;
+  // Whole MHz; 0 when unknown
   clock_mhz: number;
   memory_clock_mhz: number;
+  gpu_processes?: GpuProcessInfo[];
+  thermal?: ThermalStatus | null;
+  // MIG device UUID when this is one instance of a partitioned GPU
+  instance_id?: string | null;
+  // UUID of the physical GPU, shared by a MIG-mode GPU and its instances
+  parent_id?: string | null;
+}
+
+export interface GpuProcessInfo {
+  pid: number;
+  name: string;
+  used_memory_bytes: number;
+  sm_utilization?: number;
 }
 
 export interface DiskMetrics {
+  // Stable across samples; key rows by this rather than by position
+  id: string;
   mount_point: string;
   device_name: string;
   fs_type: string;
@@ -59,9 +99,39 @@ export interface DiskMetrics {
   read_bytes_per_sec: number;
   write_bytes_per_sec: number;
   io_operations_per_sec: number;
+  smart?: SmartHealth | null;
+  inodes_total?: number | null;
+  inodes_used?: number | null;
+  inode_usage_percent?: number | null;
+}
+
+export interface SmartHealth {
+  device: string;
+  healthy: boolean;
+  temperature_c?: number | null;
+  power_on_hours?: number | null;
+  reallocated_sectors?: number | null;
+  wear_level_percent?: number | null;
+}
+
+// A ZFS pool or Btrfs filesystem, spanning its devices
+export interface PoolMetrics {
+  // ZFS pool name, or the first mount point of a Btrfs filesystem
+  name: string;
+  kind: 'zfs' | 'btrfs';
+  raw_capacity: number;
+  allocated: number;
+  free: number;
+  compression_ratio: number;
+  dedup_ratio: number;
+  // Empty on Btrfs
+  health: string;
+  scrub_state: string;
 }
 
 export interface NetworkMetrics {
+  // Stable across samples; key rows by this rather than by position
+  id: string;
   interface_name: string;
   is_up: boolean;
   mac_address: string;
@@ -75,6 +145,26 @@ export interface NetworkMetrics {
   speed_mbps?: number;
   bytes_sent_rate: number;
   bytes_received_rate: number;
+  // Null for wired interfaces and wireless ones that aren't connected
+  wireless?: WirelessInfo | null;
+}
+
+// Radio link of a connected wireless interface
+export interface WirelessInfo {
+  ssid: string;
+  signal_dbm: number;
+  link_quality_percent: number;
+  frequency_mhz: number;
+  tx_rate_mbps: number;
+}
+
+// Host-wide TCP connection counts by state
+export interface TcpStats {
+  established: number;
+  listen: number;
+  time_wait: number;
+  close_wait: number;
+  syn_sent: number;
 }
 
 export interface ProcessMetrics {
@@ -85,9 +175,19 @@ export interface ProcessMetrics {
   memory_percent: number;
   disk_read_bytes: number;
   disk_write_bytes: number;
+  disk_read_bytes_per_sec?: number;
+  disk_write_bytes_per_sec?: number;
   status: string;
   threads: number;
   start_time: string;
+  parent_pid?: number | null;
+  open_handles?: number;
+  handles_available?: boolean;
+  cpu_time_seconds?: number;
+  elapsed_seconds?: number;
+  cmdline?: string | null;
+  // Logical CPUs the process may run on; empty when unknown, meaning all cores
+  cpu_affinity?: number[];
 }
 
 export interface SystemMetrics {
@@ -96,9 +196,16 @@ export interface SystemMetrics {
   cpu: CpuMetrics;
   memory: MemoryMetrics;
   gpus: GpuMetrics[];
+  // Sorted by mount point
   disks: DiskMetrics[];
+  // Sorted by name; empty without ZFS/Btrfs or their tools
+  pools?: PoolMetrics[];
+  // Sorted by interface name
   networks: NetworkMetrics[];
+  tcp?: TcpStats | null;
   top_processes: ProcessMetrics[];
+  // 0 (saturated) to 100 (idle): weighted CPU, memory, disk and swap usage
+  health_score?: number;
   // Specialized hardware accelerators (only populated if detected)
   dpus: DpuMetrics[];
   npus: NpuMetrics[];
@@ -142,6 +249,7 @@ export interface HighPerfGpuMetrics {
   temperature_celsius: number;
   power_watts: number;
   fan_speed_percent?: number;
+  // Whole MHz
   clock_mhz: number;
   memory_clock_mhz: number;
 }
@@ -173,7 +281,6 @@ export interface HighPerfProcessMetrics {
   name: string;
   cpu_usage_percent: number;
   memory_bytes: number;
-  memory_percent: number;
   disk_read_bytes_per_sec: number;
   disk_write_bytes_per_sec: number;
   network_bytes_per_sec: number;
@@ -1,13 +1,46 @@
-export function formatBytes(bytes: number, decimals = 2): string {
-  if (bytes === 0) return '0 Bytes';
+/** Mirrors `UiConfig.display_units`: powers of 1024 or of 1000 */
+export type UnitSystem = 'binary' | 'decimal';
+/** Mirrors `UiConfig.throughput_unit` */
+export type ThroughputUnit = 'bytes' | 'bits';
 
-  const k = 1024;
-  const dm = decimals < 0 ? 0 : decimals;
-  const sizes = ['Bytes', 'KB', 'MB', 'GB', 'TB', 'PB'];
+const UNIT_NAMES: Record<UnitSystem, Record<ThroughputUnit, string[]>> = {
+  binary: {
+    bytes: ['B', 'KiB', 'MiB', 'GiB', 'TiB', 'PiB'],
+    bits: ['bit', 'Kibit', 'Mibit', 'Gibit', 'Tibit', 'Pibit'],
+  },
+  decimal: {
+    bytes: ['B', 'kB', 'MB', 'GB', 'TB', 'PB'],
+    bits: ['bit', 'kbit', 'Mbit', 'Gbit', 'Tbit', 'Pbit'],
+  },
+};
 
-  const i = Math.floor(Math.log(bytes) / Math.log(k));
+let displayUnits: UnitSystem = (localStorage.getItem('displayUnits') as UnitSystem) || 'binary';
+let throughputUnit: ThroughputUnit = (localStorage.getItem('throughputUnit') as ThroughputUnit) || 'bytes';
 
-  return parseFloat((bytes / Math.pow(k, i)).toFixed(dm)) + ' ' + sizes[i];
+/** Sets the units every formatter below defaults to */
+export function setDisplayUnits(units: UnitSystem, throughput: ThroughputUnit): void {
+  displayUnits = units;
+  throughputUnit = throughput;
+  localStorage.setItem('displayUnits', units);
+  localStorage.setItem('throughputUnit', throughput);
+}
+
+// Same rendering as `core::units::scale`: whole numbers below the first
+// step without decimals, two decimals above it
+function scale(value: number, units: UnitSystem, names: string[]): string {
+  const base = units === 'binary' ? 1024 : 1000;
+  let scaled = value;
+  let step = 0;
+  while (scaled >= base && step < names.length - 1) {
+    scaled /= base;
+    step++;
+  }
+
+  return step === 0 ? `${scaled} ${names[0]}` : `${scaled.toFixed(2)} ${names[step]}`;
+}
+
+export function formatBytes(bytes: number, units: UnitSystem = displayUnits): string {
+  return scale(bytes, units, UNIT_NAMES[units].bytes);
 }
 
 export function formatPercent(value: number, decimals = 1): string {
@@ -35,15 +68,13 @@ export function formatFrequency(mhz: number): string {
   return `${mhz} MHz`;
 }
 
-export function formatBytesPerSecond(bytesPerSec: number): string {
-  if (bytesPerSec === 0) return '0 B/s';
-  
-  const k = 1024;
-  const sizes = ['B/s', 'KB/s', 'MB/s', 'GB/s'];
-  
-  const i = Math.floor(Math.log(bytesPerSec) / Math.log(k));
-  
-  return parseFloat((bytesPerSec / Math.pow(k, i)).toFixed(2)) + ' ' + sizes[i];
+export function formatBytesPerSecond(
+  bytesPerSec: number,
+  throughput: ThroughputUnit = throughputUnit,
+  units: UnitSystem = displayUnits,
+): string {
+  const value = throughput === 'bits' ? bytesPerSec * 8 : bytesPerSec;
+  return `${scale(value, units, UNIT_NAMES[units][throughput])}/s`;
 }
 
 export function formatTemperature(celsius: number): string {